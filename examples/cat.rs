@@ -0,0 +1,42 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Prints the content of the blob at a `safe://` URL, or of the file at the path of the URL
+//! in a files container, e.g. `safe://<container>/docs/notes.txt`.
+//!
+//! Connects to the local network, unless `SN_GENESIS_KEY` and `SN_BOOTSTRAP_NODES` are set.
+
+mod common;
+
+use eyre::{bail, Result};
+use safe_network::{client::client_api::FetchedContent, url::Url};
+use std::io::{self, Write};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+struct Args {
+    /// URL of the blob, or of the file in a files container.
+    url: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+    let args = Args::from_args();
+    let url = Url::from_url(&args.url)?;
+
+    let client = common::connect().await?;
+    match client.fetch(&url).await? {
+        FetchedContent::Blob { content, .. } => io::stdout().write_all(&content)?,
+        FetchedContent::Container { path, .. } => {
+            bail!("{} is a directory, list it with the ls example", path)
+        }
+        _ => bail!("{} points to neither a blob nor a file", args.url),
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Helpers shared by the `put`, `cat` and `ls` examples.
+
+// Not every example uses every helper.
+#![allow(dead_code)]
+
+use eyre::{eyre, Result, WrapErr};
+use safe_network::{
+    client::{client_api::BlobAddress, utils::test_utils::read_network_conn_info, Client, Config},
+    types::PublicKey,
+    url::{ContentType, Scope, Url, DEFAULT_XORURL_BASE},
+};
+use std::{collections::BTreeSet, env, net::SocketAddr};
+use xor_name::XorName;
+
+/// Genesis key of the network to connect to, in hex, instead of the local one.
+const GENESIS_KEY_VAR: &str = "SN_GENESIS_KEY";
+/// Comma-separated addresses of nodes of the network to connect to, instead of the local one.
+const BOOTSTRAP_NODES_VAR: &str = "SN_BOOTSTRAP_NODES";
+
+/// Connects a new client to the network given by the `SN_GENESIS_KEY` and `SN_BOOTSTRAP_NODES`
+/// environment variables, or else to the local network.
+pub async fn connect() -> Result<Client> {
+    let (genesis_key, bootstrap_nodes) =
+        match (env::var(GENESIS_KEY_VAR), env::var(BOOTSTRAP_NODES_VAR)) {
+            (Ok(genesis_key), Ok(bootstrap_nodes)) => {
+                let genesis_key = PublicKey::bls_from_hex(&genesis_key)?
+                    .bls()
+                    .ok_or_else(|| eyre!("{} isn't a BLS key", GENESIS_KEY_VAR))?;
+                let bootstrap_nodes = bootstrap_nodes
+                    .split(',')
+                    .map(|addr| addr.trim().parse())
+                    .collect::<Result<BTreeSet<SocketAddr>, _>>()
+                    .wrap_err_with(|| format!("Invalid address in {}", BOOTSTRAP_NODES_VAR))?;
+                (genesis_key, bootstrap_nodes)
+            }
+            _ => read_network_conn_info()?,
+        };
+
+    let config = Config::new(None, None, genesis_key, None, None).await;
+    Ok(Client::new(config, bootstrap_nodes, None).await?)
+}
+
+/// XOR-URL of the blob at `address`.
+pub fn blob_url(address: BlobAddress) -> Result<String> {
+    Ok(Url::encode_blob(
+        *address.name(),
+        address.scope(),
+        ContentType::Raw,
+        DEFAULT_XORURL_BASE,
+    )?)
+}
+
+/// XOR-URL of the files container kept in the Register of the given name and tag.
+pub fn container_url(name: XorName, tag: u64, scope: Scope) -> Result<String> {
+    Ok(Url::encode_register(
+        name,
+        tag,
+        scope,
+        ContentType::FilesContainer,
+        DEFAULT_XORURL_BASE,
+    )?)
+}
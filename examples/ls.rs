@@ -0,0 +1,48 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Lists the files and subdirectories at the path of a `safe://` URL in a files container,
+//! with the size of the files.
+//!
+//! Connects to the local network, unless `SN_GENESIS_KEY` and `SN_BOOTSTRAP_NODES` are set.
+
+mod common;
+
+use eyre::{bail, Result};
+use safe_network::{
+    client::client_api::{DirEntry, FetchedContent},
+    url::Url,
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+struct Args {
+    /// URL of the files container, with the path of the directory to list, if not the root.
+    url: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+    let args = Args::from_args();
+    let url = Url::from_url(&args.url)?;
+
+    let client = common::connect().await?;
+    let entries = match client.fetch(&url).await? {
+        FetchedContent::Container { entries, .. } => entries,
+        _ => bail!("{} points to no directory of a files container", args.url),
+    };
+    for entry in entries {
+        match entry {
+            DirEntry::File { name, item } => println!("{:>12}  {}", item.size, name),
+            DirEntry::Dir { name } => println!("{:>12}  {}/", "", name),
+        }
+    }
+
+    Ok(())
+}
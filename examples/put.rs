@@ -0,0 +1,91 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Stores a file as a blob, or a directory as a files container, printing the XOR-URL it's
+//! stored at.
+//!
+//! Connects to the local network, unless `SN_GENESIS_KEY` and `SN_BOOTSTRAP_NODES` are set.
+
+mod common;
+
+use bytes::Bytes;
+use eyre::Result;
+use safe_network::{client::client_api::FilesContainer, url::Scope};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use xor_name::XorName;
+
+/// Type tag of the Registers files containers are kept in.
+const FILES_CONTAINER_TAG: u64 = 15_000;
+
+#[derive(StructOpt)]
+struct Args {
+    /// File or directory to store.
+    path: PathBuf,
+    /// Store the data privately, for only this client's keypair to read it.
+    #[structopt(long)]
+    private: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+    let args = Args::from_args();
+    let scope = if args.private {
+        Scope::Private
+    } else {
+        Scope::Public
+    };
+
+    let client = common::connect().await?;
+    let url = if args.path.is_dir() {
+        let name = XorName::random();
+        let container = client
+            .create_files_container(name, FILES_CONTAINER_TAG, scope)
+            .await?;
+        let count = add_dir(&container, &args.path, &args.path).await?;
+        eprintln!("Stored {} files in a files container", count);
+        common::container_url(name, FILES_CONTAINER_TAG, scope)?
+    } else {
+        let content = Bytes::from(fs::read(&args.path)?);
+        eprintln!("Storing {} bytes...", content.len());
+        common::blob_url(client.write_to_network(content, scope).await?)?
+    };
+
+    println!("{}", url);
+    Ok(())
+}
+
+// Adds the files under `dir` to the container, at their path relative to `root`, returning
+// how many there were.
+async fn add_dir(container: &FilesContainer, root: &Path, dir: &Path) -> Result<usize> {
+    let mut count = 0;
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let relative = path
+                .strip_prefix(root)?
+                .to_string_lossy()
+                .replace('\\', "/");
+            eprintln!("Storing /{}...", relative);
+            let _ = container
+                .add(&format!("/{}", relative), Bytes::from(fs::read(&path)?))
+                .await?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
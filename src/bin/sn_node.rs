@@ -27,7 +27,6 @@
     unused_results
 )]
 
-use color_eyre::{Section, SectionExt};
 use eyre::{eyre, Result, WrapErr};
 use safe_network::node::{add_connection_info, set_connection_info, Config, Error, Node};
 use self_update::{cargo_crate_version, Status};
@@ -68,6 +67,11 @@ fn main() -> Result<()> {
 async fn run_node() -> Result<()> {
     let config = Config::new().await?;
 
+    if config.describe() {
+        println!("{}", config.description()?);
+        return Ok(());
+    }
+
     if let Some(c) = &config.completions() {
         let shell = c.parse().map_err(|err: String| eyre!(err))?;
         let buf = gen_completions_for_shell(shell).map_err(|err| eyre!(err))?;
@@ -157,78 +161,77 @@ async fn run_node() -> Result<()> {
         BOOTSTRAP_RETRY_TIME
     );
 
-    let (node, event_stream) = loop {
-        match Node::new(&config).await {
-            Ok(result) => break result,
-            Err(Error::Routing(routing::Error::CannotConnectEndpoint {
-                err: qp2p::EndpointError::Upnp(error),
-            })) => {
-                return Err(error).suggestion(
-                    "You can disable port forwarding by supplying --skip-igd. Without port\n\
-                    forwarding, your machine must be publicly reachable by the given\n\
-                    --public-addr. If your machine is not publicly reachable, you may have to\n\
-                    adjust your router settings to either:\n\
-                    \n\
-                    - Resolve the error (e.g. by enabling UPnP).\n\
-                    - Manually configure port forwarding, such that your machine is publicly \
-                      reachable, and supplying that address with --public-addr."
-                        .header("Disable port forwarding or change your router settings"),
-                );
-            }
-            Err(Error::Routing(routing::Error::TryJoinLater)) => {
-                println!("{}", log);
-                info!("{}", log);
-            }
-            Err(Error::Routing(routing::Error::NodeNotReachable(addr))) => {
-                let err_msg = format!(
-                    "Unfortunately we are unable to establish a connection to your machine ({}) either through a \
-                    public IP address, or via IGD on your router. Please ensure that IGD is enabled on your router - \
-                    if it is and you are still unable to add your node to the testnet, then skip adding a node for this \
+    loop {
+        let (node, event_stream) = loop {
+            match Node::new(&config).await {
+                Ok(result) => break result,
+                Err(Error::Routing(routing::Error::TryJoinLater)) => {
+                    println!("{}", log);
+                    info!("{}", log);
+                }
+                Err(Error::NodeNotReachable { addr, reachability }) => {
+                    let err_msg = format!(
+                    "Unfortunately we are unable to establish a connection to your machine ({}). {}\n\
+                    If you are still unable to add your node to the testnet, then skip adding a node for this \
                     testnet iteration. You can still use the testnet as a client, uploading and downloading content, etc. \
                     https://safenetforum.org/",
-                    addr
+                    addr,
+                    reachability.diagnosis()
                 );
-                println!("{}", err_msg);
-                error!("{}", err_msg);
-                exit(1);
-            }
-            Err(Error::JoinTimeout) => {
-                let message = format!("Encountered a timeout while trying to join the network. Retrying after {} minutes.", BOOTSTRAP_RETRY_TIME);
-                println!("{}", &message);
-                error!("{}", &message);
-            }
-            Err(e) => {
-                return Err(e).wrap_err(
+                    println!("{}", err_msg);
+                    error!("{}", err_msg);
+                    exit(1);
+                }
+                Err(Error::Misconfiguration(misconfiguration)) => {
+                    // Retrying would only have the node flap in and out of its section.
+                    let err_msg = format!("Refusing to run the node. {}", misconfiguration);
+                    println!("{}", err_msg);
+                    error!("{}", err_msg);
+                    exit(1);
+                }
+                Err(Error::JoinTimeout) => {
+                    let message = format!("Encountered a timeout while trying to join the network. Retrying after {} minutes.", BOOTSTRAP_RETRY_TIME);
+                    println!("{}", &message);
+                    error!("{}", &message);
+                }
+                Err(e) => {
+                    return Err(e).wrap_err(
                     "Cannot start node. If this is the first node on the network pass the local \
                     address to be used using --first",
                 );
+                }
             }
-        }
-        sleep(Duration::from_secs(BOOTSTRAP_RETRY_TIME * 60)).await;
-    };
+            sleep(Duration::from_secs(BOOTSTRAP_RETRY_TIME * 60)).await;
+        };
 
-    let our_conn_info = node.our_connection_info().await;
+        let our_conn_info = node.our_connection_info().await;
 
-    if config.is_first() {
-        let genesis_key = node.genesis_key().await;
-        set_connection_info(genesis_key, our_conn_info)
-            .await
-            .unwrap_or_else(|err| {
-                error!("Unable to write our connection info to disk: {:?}", err);
-            });
-    } else {
-        add_connection_info(our_conn_info)
-            .await
-            .unwrap_or_else(|err| {
-                error!("Unable to add our connection info to disk: {:?}", err);
-            });
-    }
+        if config.is_first() {
+            let genesis_key = node.genesis_key().await;
+            set_connection_info(genesis_key, our_conn_info)
+                .await
+                .unwrap_or_else(|err| {
+                    error!("Unable to write our connection info to disk: {:?}", err);
+                });
+        } else {
+            add_connection_info(our_conn_info)
+                .await
+                .unwrap_or_else(|err| {
+                    error!("Unable to add our connection info to disk: {:?}", err);
+                });
+        }
 
-    node.run(event_stream)
-        .await
-        .wrap_err("Node failed to start")?;
+        let result = node.run(event_stream).await;
 
-    Ok(())
+        // Chaos mode stops the node now and then, for it to be started afresh.
+        #[cfg(feature = "chaos")]
+        if let Err(Error::ChaosRestart) = result {
+            info!("Restarting the node, as forced by chaos mode");
+            continue;
+        }
+
+        return result.wrap_err("Node failed to start");
+    }
 }
 
 fn update() -> Result<Status, Box<dyn (::std::error::Error)>> {
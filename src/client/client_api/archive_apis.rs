@@ -0,0 +1,226 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{BlobAddress, Client};
+use crate::client::{Error, Result};
+use crate::types::{JsonRepr, MAX_CHUNK_SIZE_IN_BYTES};
+use crate::url::Scope;
+
+use bincode::{deserialize, serialize};
+use bytes::{Bytes, BytesMut};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tracing::{debug, trace};
+use xor_name::XorName;
+
+// Files up to this size are packed together with others, larger ones are stored as their own blob.
+const MAX_PACKED_FILE_SIZE: usize = MAX_CHUNK_SIZE_IN_BYTES / 4;
+// Size packs are filled up to before being stored.
+const PACK_SIZE: usize = 4 * MAX_CHUNK_SIZE_IN_BYTES;
+
+/// Index of an archive, mapping the path of each file in it to where its content is stored.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveIndex {
+    /// Where each file's content is stored, by path.
+    pub files: BTreeMap<String, ArchiveEntry>,
+}
+
+/// Where the content of a file in an archive is stored.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// Blob the content is stored in, shared with other files if it's small.
+    pub blob: BlobAddress,
+    /// Position of the content in the blob.
+    pub position: usize,
+    /// Length of the content.
+    pub len: usize,
+}
+
+impl ArchiveIndex {
+    /// Returns where the content of the file at `path` is stored, if it's in the archive.
+    pub fn get(&self, path: &str) -> Option<&ArchiveEntry> {
+        self.files.get(path)
+    }
+
+    /// Returns the paths of the files in the archive.
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        self.files.keys()
+    }
+}
+
+impl JsonRepr for ArchiveIndex {
+    const JSON_TYPE: &'static str = "ArchiveIndex";
+}
+
+// Aggregates small files' content into packs, storing each distinct content only once.
+#[derive(Default)]
+struct Packer {
+    packs: Vec<BytesMut>,
+    // Pack index, position and length of each distinct content packed so far.
+    packed: HashMap<XorName, (usize, usize, usize)>,
+}
+
+impl Packer {
+    fn add(&mut self, content: &Bytes) -> (usize, usize, usize) {
+        let hash = XorName::from_content(&[content]);
+        if let Some(location) = self.packed.get(&hash) {
+            return *location;
+        }
+
+        let start_new = self
+            .packs
+            .last()
+            .map(|pack| pack.len() + content.len() > PACK_SIZE)
+            .unwrap_or(true);
+        if start_new {
+            self.packs.push(BytesMut::with_capacity(PACK_SIZE));
+        }
+        let index = self.packs.len() - 1;
+        let pack = &mut self.packs[index];
+        let location = (index, pack.len(), content.len());
+        pack.extend_from_slice(content);
+
+        let _ = self.packed.insert(hash, location);
+        location
+    }
+}
+
+impl Client {
+    /// Store many files as an archive, returning the address of its index.
+    ///
+    /// Small files are aggregated into shared blobs rather than stored as a blob each,
+    /// avoiding the overhead of minimum-sized chunks, e.g. for websites' static assets.
+    /// Files with identical content are stored once. The archive's index is stored as a blob too,
+    /// and added to the catalog of the data the client owns.
+    pub async fn write_archive(
+        &self,
+        files: BTreeMap<String, Bytes>,
+        scope: Scope,
+    ) -> Result<BlobAddress> {
+        debug!("Writing archive of {} files", files.len());
+        let mut packer = Packer::default();
+        let mut index = ArchiveIndex::default();
+        let mut packed_files = Vec::new();
+
+        for (path, content) in files {
+            if content.len() > MAX_PACKED_FILE_SIZE {
+                let blob = self.write_blob(content.clone(), scope).await?;
+                let entry = ArchiveEntry {
+                    blob,
+                    position: 0,
+                    len: content.len(),
+                };
+                let _ = index.files.insert(path, entry);
+            } else {
+                packed_files.push((path, packer.add(&content)));
+            }
+        }
+
+        let mut pack_addresses = Vec::with_capacity(packer.packs.len());
+        for pack in packer.packs {
+            trace!("Writing archive pack of {} bytes", pack.len());
+            pack_addresses.push(self.write_blob(pack.freeze(), scope).await?);
+        }
+
+        for (path, (pack, position, len)) in packed_files {
+            let entry = ArchiveEntry {
+                blob: pack_addresses[pack],
+                position,
+                len,
+            };
+            let _ = index.files.insert(path, entry);
+        }
+
+        // Only the index is catalogued, the blobs it refers to being reachable through it.
+        self.write_to_network(Bytes::from(serialize(&index)?), scope)
+            .await
+    }
+
+    /// Read the index of the archive at `address`.
+    pub async fn read_archive_index(&self, address: BlobAddress) -> Result<ArchiveIndex> {
+        let bytes = self.read_blob(address).await?;
+        Ok(deserialize(&bytes)?)
+    }
+
+    /// Read the file at `path` from the archive with the given index, fetching only
+    /// the chunks its content is in.
+    pub async fn read_archive_file(&self, index: &ArchiveIndex, path: &str) -> Result<Bytes> {
+        let entry = index
+            .get(path)
+            .ok_or_else(|| Error::ArchiveFileNotFound(path.to_string()))?;
+        if entry.len == 0 {
+            return Ok(Bytes::new());
+        }
+        self.read_blob_from(entry.blob, entry.position, entry.len)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArchiveEntry, ArchiveIndex, BlobAddress};
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::client::Error;
+    use crate::types::{utils::random_bytes, JsonRepr};
+    use crate::url::Scope;
+    use bytes::Bytes;
+    use eyre::Result;
+    use std::collections::BTreeMap;
+    use xor_name::XorName;
+
+    #[test]
+    fn index_round_trips_through_json() -> Result<()> {
+        let entry = ArchiveEntry {
+            blob: BlobAddress::Public(XorName::random()),
+            position: 10,
+            len: 20,
+        };
+        let mut index = ArchiveIndex::default();
+        let _ = index.files.insert("index.html".to_string(), entry);
+        let _ = index.files.insert("style.css".to_string(), entry);
+
+        assert_eq!(ArchiveIndex::from_json(&index.to_json()?)?, index);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn small_files_share_blobs() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let style = Bytes::from("body { margin: 0 }");
+        let mut files = BTreeMap::new();
+        let _ = files.insert("index.html".to_string(), random_bytes(1000));
+        let _ = files.insert("about.html".to_string(), random_bytes(3000));
+        let _ = files.insert("css/main.css".to_string(), style.clone());
+        let _ = files.insert("css/copy.css".to_string(), style.clone());
+        let _ = files.insert("large.bin".to_string(), random_bytes(400 * 1024));
+
+        let address = client.write_archive(files.clone(), Scope::Public).await?;
+        let index = run_w_backoff_delayed(|| client.read_archive_index(address), 10, 1).await?;
+
+        let small_blob = index.files["index.html"].blob;
+        assert_eq!(index.files["about.html"].blob, small_blob);
+        assert_ne!(index.files["large.bin"].blob, small_blob);
+        // Identical content is only stored once.
+        assert_eq!(index.files["css/main.css"], index.files["css/copy.css"]);
+
+        for (path, content) in files {
+            let read =
+                run_w_backoff_delayed(|| client.read_archive_file(&index, &path), 10, 1).await?;
+            assert_eq!(read, content);
+        }
+
+        assert!(matches!(
+            client.read_archive_file(&index, "missing.html").await,
+            Err(Error::ArchiveFileNotFound(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    limits_apis::check_blob_size,
+    upload_report::{encrypt, ProgressTracker},
+    BlobAddress, ChunkUpload, Client, TransferPhase,
+};
+use crate::client::{Error, Result};
+use crate::types::Chunk;
+use crate::url::Scope;
+use bytes::Bytes;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::{collections::BTreeSet, sync::Arc};
+use tokio::{sync::mpsc, task::JoinError, time::Instant};
+use tracing::{debug, warn};
+
+/// The outcome of writing many blobs at once, as returned by [`Client::write_many`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchUpload {
+    /// Addresses of the blobs, in the order of the items written.
+    pub addresses: Vec<BlobAddress>,
+    /// Number of distinct chunks the blobs are made of, those shared by several blobs
+    /// counted once.
+    pub total_chunks: usize,
+    /// Number of chunks found on the network already, which weren't uploaded again.
+    pub existing_chunks: usize,
+    /// The chunks uploaded, in no particular order.
+    pub chunks: Vec<ChunkUpload>,
+}
+
+impl BatchUpload {
+    /// The chunks which couldn't be stored.
+    pub fn failed(&self) -> impl Iterator<Item = &ChunkUpload> {
+        self.chunks.iter().filter(|chunk| chunk.error.is_some())
+    }
+
+    /// Whether all the chunks of the blobs were stored.
+    pub fn is_complete(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+impl Client {
+    /// Write many blobs to the network in one go, e.g. the thousands of small files of a
+    /// backup, each as per [`Client::write_to_network_with_report`].
+    ///
+    /// The items are self-encrypted one after the other, the chunks of each being uploaded
+    /// while the next ones are encrypted, and those they share with earlier items, e.g. the
+    /// chunks of identical files, being uploaded once. If `skip_existing`, each chunk is checked
+    /// with [`Client::chunk_exists`] beforehand, and only those the network doesn't hold yet are
+    /// uploaded, e.g. when a backup is run again. The chunks are uploaded within the client's
+    /// transfer limits, rather than blob after blob. Nothing is uploaded if any of the public
+    /// items is rejected by the client's publish hook.
+    pub async fn write_many(
+        &self,
+        items: Vec<(Bytes, Scope)>,
+        skip_existing: bool,
+    ) -> Result<BatchUpload> {
+        let limits = self.upload_limits().await;
+        for (data, scope) in &items {
+            check_blob_size(data.len(), &limits)?;
+            self.check_publish(data, *scope).await?;
+        }
+
+        // The chunks of an item are uploaded as the next one is encrypted, only a bounded
+        // number of them being held at a time.
+        let (sender, receiver) = mpsc::channel(self.transfer.max_writes_in_flight());
+        let (addresses, (total_chunks, existing_chunks, chunks)) = futures::join!(
+            self.encrypt_items(items, sender),
+            self.upload_new_chunks(receiver, skip_existing)
+        );
+        let addresses = addresses?;
+        debug!(
+            "Wrote {} blobs made of {} chunks, {} of which were stored already",
+            addresses.len(),
+            total_chunks,
+            existing_chunks
+        );
+
+        Ok(BatchUpload {
+            addresses,
+            total_chunks,
+            existing_chunks,
+            chunks,
+        })
+    }
+
+    // Self-encrypts the items one after the other, sending their chunks on to be uploaded.
+    async fn encrypt_items(
+        &self,
+        items: Vec<(Bytes, Scope)>,
+        sender: mpsc::Sender<Chunk>,
+    ) -> Result<Vec<BlobAddress>> {
+        let mut addresses = Vec::with_capacity(items.len());
+        for (data, scope) in items {
+            let started = Instant::now();
+            let owner = self
+                .encryption_provider
+                .encryption(scope, self.public_key());
+            let (address, blob_chunks) = tokio::task::spawn_blocking(move || encrypt(data, owner))
+                .await
+                .map_err(|err| Error::Generic(format!("Encrypting the blob failed: {}", err)))??;
+            self.record_phase(TransferPhase::Encryption, started);
+            addresses.push(address);
+            for chunk in blob_chunks {
+                sender
+                    .send(chunk)
+                    .await
+                    .map_err(|_| Error::Generic("Batch upload was aborted.".to_string()))?;
+            }
+        }
+        Ok(addresses)
+    }
+
+    // Uploads the chunks received until the sender is dropped, a bounded number at a time,
+    // skipping those received already, and those the network holds if `skip_existing`.
+    // Returns the number of distinct chunks received, of those found on the network already,
+    // and the outcome of the uploads.
+    async fn upload_new_chunks(
+        &self,
+        mut receiver: mpsc::Receiver<Chunk>,
+        skip_existing: bool,
+    ) -> (usize, usize, Vec<ChunkUpload>) {
+        // No progress is reported, so the total number of chunks needn't be known upfront.
+        let tracker = Arc::new(ProgressTracker::new(0, None));
+        let started = Instant::now();
+        let mut received = BTreeSet::new();
+        let mut existing = 0;
+        let mut outcomes = vec![];
+        let mut uploads = FuturesUnordered::new();
+        let mut record = |upload: std::result::Result<Option<ChunkUpload>, JoinError>| match upload
+        {
+            Ok(Some(outcome)) => outcomes.push(outcome),
+            Ok(None) => existing += 1,
+            Err(error) => warn!("A chunk upload task failed: {}", error),
+        };
+
+        while let Some(chunk) = receiver.recv().await {
+            if !received.insert(*chunk.name()) {
+                continue;
+            }
+            if uploads.len() >= self.transfer.max_writes_in_flight() {
+                if let Some(upload) = uploads.next().await {
+                    record(upload);
+                }
+            }
+            let writer = self.clone();
+            let tracker = tracker.clone();
+            uploads.push(self.session.spawn("store_chunk", async move {
+                writer
+                    .upload_unless_stored(chunk, skip_existing, &tracker)
+                    .await
+            }));
+        }
+        while let Some(upload) = uploads.next().await {
+            record(upload);
+        }
+        self.record_phase(TransferPhase::Upload, started);
+
+        (received.len(), existing, outcomes)
+    }
+
+    // Uploads `chunk`, unless `skip_existing` and the network holds it already, those which
+    // couldn't be checked being deemed missing. Returns None if it wasn't uploaded.
+    async fn upload_unless_stored(
+        &self,
+        chunk: Chunk,
+        skip_existing: bool,
+        tracker: &ProgressTracker,
+    ) -> Option<ChunkUpload> {
+        if skip_existing && matches!(self.chunk_exists(*chunk.address()).await, Ok(true)) {
+            return None;
+        }
+        Some(self.store_chunk_with_retries(chunk, tracker).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::types::utils::random_bytes;
+    use crate::url::Scope;
+    use eyre::Result;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn shared_and_stored_chunks_are_uploaded_once() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let blob = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let other = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let items = vec![
+            (blob.clone(), Scope::Public),
+            (blob.clone(), Scope::Public),
+            (other.clone(), Scope::Public),
+        ];
+        let upload = client.write_many(items.clone(), false).await?;
+        assert!(upload.is_complete());
+        assert_eq!(upload.addresses.len(), 3);
+        assert_eq!(upload.addresses[0], upload.addresses[1]);
+        // Each min size blob is self-encrypted into 3 chunks, plus its head chunk.
+        assert_eq!(upload.total_chunks, 8);
+        assert_eq!(upload.chunks.len(), 8);
+
+        let read = run_w_backoff_delayed(|| client.read_blob(upload.addresses[2]), 10, 1).await?;
+        assert_eq!(read, other);
+        let read = run_w_backoff_delayed(|| client.read_blob(upload.addresses[0]), 10, 1).await?;
+        assert_eq!(read, blob);
+
+        let again = client.write_many(items, true).await?;
+        assert_eq!(again.addresses, upload.addresses);
+        assert_eq!(again.existing_chunks, 8);
+        assert!(again.chunks.is_empty());
+
+        Ok(())
+    }
+}
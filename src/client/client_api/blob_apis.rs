@@ -6,28 +6,64 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{data::get_data_chunks, Client};
+use super::{
+    data::{
+        check_parts_version, get_data_chunks, get_part_chunks, pack_inline, pack_keys, pack_parts,
+        UPLOAD_PART_SIZE,
+    },
+    limits_apis::check_blob_size,
+    Client, TransferPhase, WithStats,
+};
 use crate::messaging::data::{DataCmd, DataQuery, QueryResponse};
-use crate::types::{Chunk, ChunkAddress, Encryption};
+use crate::types::{
+    Chunk, ChunkAddress, DataAddress, Encryption, JsonRepr, PublicKey, MAX_CHUNK_SIZE_IN_BYTES,
+};
 use crate::{
-    client::{client_api::data::SecretKey, utils::encryption, Error, Result},
+    client::{client_api::data::SecretKey, CmdHandle, Error, OperationPriority, Result},
     url::Scope,
 };
 
 use bincode::deserialize;
-use bytes::Bytes;
-use futures::future::join_all;
+use bytes::{Bytes, BytesMut};
+use futures::{
+    stream::{self, FuturesUnordered},
+    StreamExt,
+};
 use itertools::Itertools;
 use self_encryption::{self, ChunkKey, EncryptedChunk, SecretKey as BlobSecretKey};
-use tokio::task;
-use tracing::trace;
+use std::{fs::File, path::Path, sync::Arc};
+use tempfile::NamedTempFile;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sync::mpsc,
+    task::JoinError,
+    time::Instant,
+};
+use tracing::{debug, trace};
 use xor_name::XorName;
 
+// Max number of head chunks being prefetched at any one time.
+const MAX_CONCURRENT_HEAD_CHUNK_PREFETCHES: usize = 4;
+// Max number of chunks queued up to be sent. Once reached, queueing, or the encryption of the
+// parts of data streamed, waits for uploads to catch up, bounding memory use.
+const MAX_QUEUED_CHUNKS: usize = 64;
+// Number of chunks read and decrypted at a time when streaming a blob to a writer.
+const STREAMED_CHUNKS_PER_BATCH: usize = 8;
+
 struct HeadChunk {
     chunk: Chunk,
     address: BlobAddress,
 }
 
+// What the head chunk of a blob holds.
+enum HeadContent {
+    // The content of a blob too small to be self-encrypted.
+    Inline(Bytes),
+    // The secret keys of the parts the content was self-encrypted in, a single one unless it
+    // was uploaded in parts.
+    Parts(Vec<BlobSecretKey>),
+}
+
 /// Address of a Blob.
 #[derive(
     Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, serde::Serialize, serde::Deserialize, Debug,
@@ -67,6 +103,48 @@ impl BlobAddress {
     }
 }
 
+impl JsonRepr for BlobAddress {
+    const JSON_TYPE: &'static str = "BlobAddress";
+}
+
+/// Contents of a blob read with [`Client::read_blob_spilling`].
+#[derive(Debug)]
+pub enum BlobContent {
+    /// Contents held in memory.
+    InMemory(Bytes),
+    /// Contents written to a temporary file, as they exceeded the client's read memory limit.
+    Spilled(SpilledBlob),
+}
+
+/// Contents of a blob written to a temporary file, which is deleted once this is dropped.
+#[derive(Debug)]
+pub struct SpilledBlob {
+    file: NamedTempFile,
+    len: usize,
+}
+
+impl SpilledBlob {
+    /// Path of the temporary file.
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// Size of the contents in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the blob is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Opens the temporary file for reading from its start.
+    pub fn open(&self) -> Result<File> {
+        self.file.reopen().map_err(Error::IoError)
+    }
+}
+
 impl Client {
     /// Read the contents of a blob from the network. The contents might be spread across
     /// different chunks in the network. This function invokes the self-encryptor and returns
@@ -80,9 +158,11 @@ impl Client {
     where
         Self: Sized,
     {
-        let chunk = self.read_from_network(address.name()).await?;
-        let secret_key = self.unpack_head_chunk(HeadChunk { chunk, address }).await?;
-        self.read_all(secret_key).await
+        let chunk = self.read_head_chunk(address.name()).await?;
+        match self.unpack_head_chunk(HeadChunk { chunk, address }).await? {
+            HeadContent::Inline(data) => Ok(data),
+            HeadContent::Parts(parts) => self.read_parts(parts).await,
+        }
     }
 
     /// Read the contents of a blob from the network. The contents might be spread across
@@ -113,9 +193,190 @@ impl Client {
             &position,
         );
 
-        let chunk = self.read_from_network(address.name()).await?;
-        let secret_key = self.unpack_head_chunk(HeadChunk { chunk, address }).await?;
-        self.seek(secret_key, position, length).await
+        let chunk = self.read_head_chunk(address.name()).await?;
+        match self.unpack_head_chunk(HeadChunk { chunk, address }).await? {
+            HeadContent::Inline(data) => {
+                let start = position.min(data.len());
+                let end = position.saturating_add(length).min(data.len());
+                Ok(data.slice(start..end))
+            }
+            HeadContent::Parts(parts) => self.seek_parts(&parts, position, length).await,
+        }
+    }
+
+    /// Read the contents of a blob from the network, as per [`Client::read_blob`], along with
+    /// what reading it cost in bandwidth.
+    pub async fn read_blob_with_stats(&self, address: BlobAddress) -> Result<WithStats<Bytes>> {
+        self.recording_stats(|client| async move { client.read_blob(address).await })
+            .await
+    }
+
+    /// Read part of the contents of a blob from the network, as per [`Client::read_blob_from`],
+    /// along with what reading it cost in bandwidth.
+    pub async fn read_blob_from_with_stats(
+        &self,
+        address: BlobAddress,
+        position: usize,
+        length: usize,
+    ) -> Result<WithStats<Bytes>> {
+        self.recording_stats(|client| async move {
+            client.read_blob_from(address, position, length).await
+        })
+        .await
+    }
+
+    /// Read the contents of a blob from the network, as per [`Client::read_blob`], unless it's
+    /// larger than the client's configured read memory limit. In that case the contents are
+    /// written to a temporary file as they're read, a batch of chunks at a time, so that reading
+    /// it doesn't require holding it all in memory.
+    pub async fn read_blob_spilling(&self, address: BlobAddress) -> Result<BlobContent> {
+        let chunk = self.read_head_chunk(address.name()).await?;
+        let parts = match self.unpack_head_chunk(HeadChunk { chunk, address }).await? {
+            HeadContent::Inline(data) => return Ok(BlobContent::InMemory(data)),
+            HeadContent::Parts(parts) => parts,
+        };
+
+        let size = parts.iter().map(BlobSecretKey::file_size).sum();
+        let limit = match self.read_memory_limit {
+            Some(limit) if size > limit => limit,
+            _ => return Ok(BlobContent::InMemory(self.read_parts(parts).await?)),
+        };
+
+        debug!(
+            "Spilling blob at {:?} of {} bytes to disk, as it exceeds the {} bytes read limit",
+            address, size, limit
+        );
+        // Blobs read by a profile's client are spilled within the profile's directory.
+        let spilled = match self.profile() {
+            Some(profile) => NamedTempFile::new_in(profile.spill_dir()),
+            None => NamedTempFile::new(),
+        }
+        .map_err(Error::IoError)?;
+        let mut file = tokio::fs::File::from_std(spilled.reopen().map_err(Error::IoError)?);
+
+        // Chunks are decrypted whole, so there's no use in reading less than a chunk at a time.
+        let batch_size = limit.max(MAX_CHUNK_SIZE_IN_BYTES);
+        let _ = self.write_parts_to(&parts, batch_size, &mut file).await?;
+
+        Ok(BlobContent::Spilled(SpilledBlob {
+            file: spilled,
+            len: size,
+        }))
+    }
+
+    /// Read the contents of a blob from the network, as per [`Client::read_blob`], writing them
+    /// to `writer` in order as they're read, a batch of chunks at a time, so that even very large
+    /// blobs can be streamed to disk or to a socket without holding them all in memory.
+    ///
+    /// Returns the number of bytes written. The writer is flushed, but not shut down. If reading
+    /// fails partway through, what was read up to then has already been written.
+    pub async fn read_blob_to<W>(&self, address: BlobAddress, writer: &mut W) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let chunk = self.read_head_chunk(address.name()).await?;
+        let parts = match self.unpack_head_chunk(HeadChunk { chunk, address }).await? {
+            HeadContent::Inline(data) => {
+                writer.write_all(&data).await.map_err(Error::IoError)?;
+                writer.flush().await.map_err(Error::IoError)?;
+                return Ok(data.len() as u64);
+            }
+            HeadContent::Parts(parts) => parts,
+        };
+        self.write_parts_to(
+            &parts,
+            STREAMED_CHUNKS_PER_BATCH * MAX_CHUNK_SIZE_IN_BYTES,
+            writer,
+        )
+        .await
+    }
+
+    /// Share a private blob with the user of the given public key, without storing its data
+    /// again: the secret keys of the blob are decrypted and encrypted anew for the recipient,
+    /// in an access chunk of their own.
+    ///
+    /// Returns the address the recipient reads the blob at, with [`Client::read_blob`] and the
+    /// like. Access can't be revoked, as the recipient is able to keep the secret keys.
+    pub async fn share_private_blob(
+        &self,
+        address: BlobAddress,
+        recipient: PublicKey,
+    ) -> Result<BlobAddress> {
+        if address.is_public() {
+            return Err(Error::NotPrivateBlob(address));
+        }
+
+        let chunk = self.read_head_chunk(address.name()).await?;
+        let content = self.unpack_head_chunk(HeadChunk { chunk, address }).await?;
+        let encryption = self
+            .encryption_provider
+            .encryption(Scope::Private, recipient)
+            .ok_or_else(|| Error::Generic("Could not get an encryption object.".to_string()))?;
+        let (shared_address, chunks) = match content {
+            HeadContent::Inline(data) => pack_inline(data, Some(encryption.as_ref()))?,
+            HeadContent::Parts(secret_keys) => pack_keys(secret_keys, Some(encryption.as_ref()))?,
+        };
+
+        for chunk in chunks {
+            let _ = self.store_chunk(chunk).await?;
+        }
+        debug!(
+            "Shared private blob at {:?} with {:?} at {:?}",
+            address, recipient, shared_address
+        );
+
+        Ok(shared_address)
+    }
+
+    /// Fetch the head chunks of the given blobs in the background, so subsequent reads of them
+    /// don't have to wait for it, e.g. when the blobs are listed as the contents of a container.
+    ///
+    /// The chunks are fetched with [`OperationPriority::Background`] and bounded concurrency.
+    /// This does nothing if head chunk prefetching was disabled in the client's config.
+    pub fn prefetch_head_chunks(&self, addresses: impl IntoIterator<Item = BlobAddress>) {
+        if !self.prefetch_head_chunks {
+            return;
+        }
+
+        let names = addresses
+            .into_iter()
+            .map(|address| *address.name())
+            .collect_vec();
+        let client = self.with_priority(OperationPriority::Background);
+
+        let _ = self.session.spawn("prefetch_head_chunks", async move {
+            stream::iter(names)
+                .for_each_concurrent(MAX_CONCURRENT_HEAD_CHUNK_PREFETCHES, |name| {
+                    let client = client.clone();
+                    async move {
+                        if client.head_chunks.get(&name).await.is_some() {
+                            return;
+                        }
+                        match client.read_from_network(&name).await {
+                            Ok(chunk) => {
+                                let _ = client.head_chunks.set(name, chunk, None).await;
+                            }
+                            Err(error) => {
+                                debug!("Failed to prefetch head chunk {:?}: {}", name, error)
+                            }
+                        }
+                    }
+                })
+                .await
+        });
+    }
+
+    // Reads a head chunk, from the prefetched ones if it's there.
+    async fn read_head_chunk(&self, name: &XorName) -> Result<Chunk> {
+        if let Some(chunk) = self.head_chunks.get(name).await {
+            trace!("Using prefetched head chunk: {:?}", name);
+            return Ok(chunk);
+        }
+
+        let started = Instant::now();
+        let chunk = self.read_chunk(name).await;
+        self.record_phase(TransferPhase::Download, started);
+        chunk
     }
 
     pub(crate) async fn read_from_network(&self, name: &XorName) -> Result<Chunk> {
@@ -133,44 +394,335 @@ impl Client {
             _ => return Err(Error::ReceivedUnexpectedEvent),
         }?;
 
+        // The address of a chunk is that of its content, which the Elders may not have checked.
+        if chunk.name() != name {
+            warn!(
+                "Chunk received for {:?} is another one: {:?}",
+                name,
+                chunk.name()
+            );
+            return Err(Error::ReceivedUnexpectedData);
+        }
+
         Ok(chunk)
     }
 
     /// Directly writes raw data to the network
     /// in the form of immutable self encrypted chunks,
     /// without any batching.
+    ///
+    /// The data is self-encrypted whole, whatever its size, its chunks being queued up to be
+    /// uploaded a bounded number at a time, as soon as the first ones are. If uploading any of
+    /// the chunks fails, so does the write.
+    /// Data too small to be self-encrypted, under 3KB, is held inline in a single chunk,
+    /// padded so its size isn't given away, and encrypted too if it's private.
+    /// Public data is first checked with the client's publish hook, if one is set with
+    /// [`Client::with_publish_hook`]. The blob is then added to the catalog of the data the
+    /// client owns, listed with [`Client::list_owned_data`], which is only logged if it fails.
     pub async fn write_to_network(&self, data: Bytes, scope: Scope) -> Result<BlobAddress> {
-        let owner = encryption(scope, self.public_key());
-        let (head_address, all_chunks) = get_data_chunks(data, owner.as_ref())?;
-
-        let tasks = all_chunks.into_iter().map(|chunk| {
-            let writer = self.clone();
-            task::spawn(async move { writer.send_cmd(DataCmd::StoreChunk(chunk)).await })
-        });
+        let address = self.write_blob(data, scope).await?;
+        self.catalog_blob(address).await;
+        Ok(address)
+    }
 
-        let _ = join_all(tasks)
-            .await
-            .into_iter()
-            .flatten() // swallows errors
-            .collect_vec();
+    /// Writes a blob as per [`Client::write_to_network`], without adding it to the catalog of
+    /// owned data, e.g. for the blobs an archive is made of.
+    pub(crate) async fn write_blob(&self, data: Bytes, scope: Scope) -> Result<BlobAddress> {
+        check_blob_size(data.len(), &self.upload_limits().await)?;
+        self.check_publish(&data, scope).await?;
+
+        let owner = self
+            .encryption_provider
+            .encryption(scope, self.public_key());
+        let started = Instant::now();
+        let (head_address, all_chunks) = get_data_chunks(data, owner.as_deref())?;
+        self.record_phase(TransferPhase::Encryption, started);
+
+        let (sender, receiver) = mpsc::channel(MAX_QUEUED_CHUNKS);
+        let queue = async move {
+            for chunk in all_chunks {
+                // The receiver is only dropped once an upload failed.
+                if sender.send(chunk).await.is_err() {
+                    break;
+                }
+            }
+        };
+        let ((), uploaded) = tokio::join!(queue, self.upload_chunks(receiver));
+        uploaded?;
 
         Ok(head_address)
     }
 
+    /// Write the data read from `reader` to the network, as per [`Client::write_to_network`],
+    /// without holding it all in memory, e.g. to upload multi-GB files.
+    ///
+    /// Data up to a part, 16MB, is written whole, getting the same address as if it was written
+    /// with [`Client::write_to_network`]. Larger data is read a part at a time, each part being
+    /// self-encrypted and its chunks uploaded while the next one is read, so that no more than
+    /// a couple of parts and the chunks queued for upload are held in memory. Such blobs are
+    /// stored in a versioned format of their own, with the secret keys of all the parts in their
+    /// head chunk: they get another address than if written whole, and clients predating the
+    /// format can't read them. If reading fails, the chunks of the parts already uploaded are
+    /// left behind, unreachable.
+    ///
+    /// The network's max blob size doesn't apply to data streamed, which is only ever held a
+    /// part at a time, so there's no limit to its size.
+    ///
+    /// Public data can't be checked with the client's publish hook before it's uploaded if
+    /// it's streamed, so it's refused with [`Error::PublishRejected`] if larger than a part
+    /// when a hook is set.
+    pub async fn write_stream<R>(&self, reader: R, scope: Scope) -> Result<BlobAddress>
+    where
+        R: AsyncRead + Unpin,
+    {
+        // Data fitting in a single part is written as a whole.
+        let mut parts = PartReader::new(reader).await?;
+        if let Some(data) = parts.take_whole() {
+            return self.write_to_network(data, scope).await;
+        }
+        if self.checks_publish(scope) {
+            return Err(Error::PublishRejected(
+                "Public data streamed can't be checked before it's published".to_string(),
+            ));
+        }
+
+        let owner: Option<Arc<dyn Encryption>> = self
+            .encryption_provider
+            .encryption(scope, self.public_key())
+            .map(Arc::from);
+        let (sender, receiver) = mpsc::channel(MAX_QUEUED_CHUNKS);
+        let (address, uploaded) = tokio::join!(
+            self.encrypt_stream(parts, owner, sender),
+            self.upload_chunks(receiver)
+        );
+        // An upload failing aborts the encryption, so its error is the one to report.
+        uploaded?;
+        let address = address?;
+        self.catalog_blob(address).await;
+        Ok(address)
+    }
+
+    /// Write raw data to the network, as per [`Client::write_to_network`], along with what
+    /// writing it cost in bandwidth.
+    pub async fn write_to_network_with_stats(
+        &self,
+        data: Bytes,
+        scope: Scope,
+    ) -> Result<WithStats<BlobAddress>> {
+        self.recording_stats(|client| async move { client.write_to_network(data, scope).await })
+            .await
+    }
+
     // --------------------------------------------
     // ---------- Private helpers -----------------
     // --------------------------------------------
 
+    // Adds the blob at `address` to the catalog of the data the client owns.
+    async fn catalog_blob(&self, address: BlobAddress) {
+        self.catalog(DataAddress::Chunk(ChunkAddress(*address.name())))
+            .await
+    }
+
+    // Uploads the chunks received until the sender is dropped, a bounded number at a time.
+    // Fails as soon as an upload does, dropping the receiver for the sender to stop.
+    async fn upload_chunks(&self, mut receiver: mpsc::Receiver<Chunk>) -> Result<()> {
+        let started = Instant::now();
+        let mut uploads = FuturesUnordered::new();
+        while let Some(chunk) = receiver.recv().await {
+            if uploads.len() >= self.transfer.max_writes_in_flight() {
+                if let Some(upload) = uploads.next().await {
+                    check_upload(upload)?;
+                }
+            }
+            let writer = self.clone();
+            uploads.push(self.session.spawn("store_chunk", async move {
+                writer.store_chunk(chunk).await
+            }));
+        }
+        while let Some(upload) = uploads.next().await {
+            check_upload(upload)?;
+        }
+        self.record_phase(TransferPhase::Upload, started);
+        Ok(())
+    }
+
+    // Sends the chunk to be stored, once within the client's concurrency limits.
+    pub(super) async fn store_chunk(&self, chunk: Chunk) -> Result<CmdHandle> {
+        let _permit = self.transfer.acquire_write().await?;
+        self.transfer.throttle(chunk.payload_size()).await;
+        self.send_cmd(DataCmd::StoreChunk(chunk)).await
+    }
+
+    // Self-encrypts the parts read, sending their chunks on to be uploaded, then the head
+    // chunks.
+    async fn encrypt_stream<R>(
+        &self,
+        mut parts: PartReader<R>,
+        owner: Option<Arc<dyn Encryption>>,
+        sender: mpsc::Sender<Chunk>,
+    ) -> Result<BlobAddress>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut secret_keys = vec![];
+        while let Some(part) = parts.next_part().await? {
+            let started = Instant::now();
+            let part_owner = owner.clone();
+            let (secret_key, chunks) =
+                tokio::task::spawn_blocking(move || get_part_chunks(part, part_owner.as_deref()))
+                    .await
+                    .map_err(|err| {
+                        Error::Generic(format!("Encrypting the blob failed: {}", err))
+                    })??;
+            self.record_phase(TransferPhase::Encryption, started);
+            for chunk in chunks {
+                sender
+                    .send(chunk)
+                    .await
+                    .map_err(|_| Error::Generic("Blob upload was aborted.".to_string()))?;
+            }
+            secret_keys.push(secret_key);
+        }
+
+        // The head chunks go last, so the blob is only reachable once all its parts are.
+        let (head_address, head_chunks) = pack_parts(secret_keys, owner.as_deref())?;
+        for chunk in head_chunks {
+            sender
+                .send(chunk)
+                .await
+                .map_err(|_| Error::Generic("Blob upload was aborted.".to_string()))?;
+        }
+        Ok(head_address)
+    }
+
+    // Secret keys of the parts of a blob, read from its head chunk. Empty if its content is held
+    // inline in the head chunk.
+    pub(super) async fn blob_parts(&self, address: BlobAddress) -> Result<Vec<BlobSecretKey>> {
+        let chunk = self.read_head_chunk(address.name()).await?;
+        let (parts, _) = self.unpack_parts(address, chunk).await?;
+        Ok(parts)
+    }
+
+    // Secret keys of the parts of the blob with the given head chunk, with the size of its
+    // content. Empty if its content is held inline in the head chunk.
+    pub(super) async fn unpack_parts(
+        &self,
+        address: BlobAddress,
+        chunk: Chunk,
+    ) -> Result<(Vec<BlobSecretKey>, usize)> {
+        match self.unpack_head_chunk(HeadChunk { chunk, address }).await? {
+            HeadContent::Inline(data) => Ok((vec![], data.len())),
+            HeadContent::Parts(parts) => {
+                let size = parts.iter().map(BlobSecretKey::file_size).sum();
+                Ok((parts, size))
+            }
+        }
+    }
+
+    // Addresses of the head chunk of a blob, of the chunks of the additional levels its data map
+    // was self-encrypted over, if any, and of the chunks holding its content.
+    pub(super) async fn chunk_addresses(&self, address: BlobAddress) -> Result<Vec<ChunkAddress>> {
+        let mut addresses = vec![ChunkAddress(*address.name())];
+        let mut chunk = self.read_head_chunk(address.name()).await?;
+        let parts = loop {
+            match self.head_level(address, &chunk)? {
+                SecretKey::FirstLevel(secret_key) => break vec![secret_key],
+                SecretKey::Parts { version, parts } => {
+                    check_parts_version(version)?;
+                    break parts;
+                }
+                SecretKey::Inline(_) => break vec![],
+                SecretKey::AdditionalLevel(secret_key) => {
+                    addresses.extend(
+                        secret_key
+                            .keys()
+                            .iter()
+                            .map(|key| ChunkAddress(key.dst_hash)),
+                    );
+                    chunk = deserialize(&self.read_all(secret_key).await?)?;
+                }
+            }
+        };
+        addresses.extend(
+            parts
+                .iter()
+                .flat_map(BlobSecretKey::keys)
+                .map(|key| ChunkAddress(key.dst_hash)),
+        );
+        Ok(addresses)
+    }
+
+    // Reads all the parts of a blob and joins them up.
+    async fn read_parts(&self, mut parts: Vec<BlobSecretKey>) -> Result<Bytes> {
+        if parts.len() == 1 {
+            return self.read_all(parts.remove(0)).await;
+        }
+
+        let mut bytes = BytesMut::with_capacity(parts.iter().map(BlobSecretKey::file_size).sum());
+        for secret_key in parts {
+            bytes.extend_from_slice(&self.read_all(secret_key).await?);
+        }
+        Ok(bytes.freeze())
+    }
+
+    // Reads the parts of a blob `batch_size` bytes at a time, writing each batch to `writer`
+    // before reading the next one. Returns the number of bytes written.
+    async fn write_parts_to<W>(
+        &self,
+        parts: &[BlobSecretKey],
+        batch_size: usize,
+        writer: &mut W,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let size = parts.iter().map(BlobSecretKey::file_size).sum();
+        let mut position = 0;
+        while position < size {
+            let len = batch_size.min(size - position);
+            let bytes = self.seek_parts(parts, position, len).await?;
+            writer.write_all(&bytes).await.map_err(Error::IoError)?;
+            position += len;
+        }
+        writer.flush().await.map_err(Error::IoError)?;
+        Ok(size as u64)
+    }
+
+    // Reads `len` bytes of a blob starting at `pos`, from the parts that range spans.
+    async fn seek_parts(&self, parts: &[BlobSecretKey], pos: usize, len: usize) -> Result<Bytes> {
+        if let [secret_key] = parts {
+            return self.seek(secret_key, pos, len).await;
+        }
+
+        let end = pos.saturating_add(len);
+        let mut bytes = BytesMut::new();
+        let mut part_start = 0;
+        for secret_key in parts {
+            let part_end = part_start + secret_key.file_size();
+            if part_end > pos && part_start < end {
+                let from = pos.max(part_start);
+                let to = end.min(part_end);
+                bytes
+                    .extend_from_slice(&self.seek(secret_key, from - part_start, to - from).await?);
+            }
+            part_start = part_end;
+        }
+        Ok(bytes.freeze())
+    }
+
     // Gets and decrypts chunks from the network using nothing else but the secret key, then returns the raw data.
     async fn read_all(&self, secret_key: BlobSecretKey) -> Result<Bytes> {
         let encrypted_chunks = Self::try_get_chunks(self.clone(), secret_key.keys()).await?;
-        self_encryption::decrypt_full_set(&secret_key, &encrypted_chunks)
-            .map_err(Error::SelfEncryption)
+        let started = Instant::now();
+        let bytes = self_encryption::decrypt_full_set(&secret_key, &encrypted_chunks)
+            .map_err(Error::SelfEncryption);
+        self.record_phase(TransferPhase::Decryption, started);
+        bytes
     }
 
     // Gets a subset of chunks from the network, decrypts and
     // reads `len` bytes of the data starting at given `pos` of original file.
-    async fn seek(&self, secret_key: BlobSecretKey, pos: usize, len: usize) -> Result<Bytes> {
+    async fn seek(&self, secret_key: &BlobSecretKey, pos: usize, len: usize) -> Result<Bytes> {
         let info = self_encryption::seek_info(secret_key.file_size(), pos, len);
         let range = &info.index_range;
         let all_keys = secret_key.keys();
@@ -184,21 +736,31 @@ impl Client {
         )
         .await?;
 
-        self_encryption::decrypt_range(&secret_key, &encrypted_chunks, info.relative_pos, len)
-            .map_err(Error::SelfEncryption)
+        let started = Instant::now();
+        let bytes =
+            self_encryption::decrypt_range(secret_key, &encrypted_chunks, info.relative_pos, len)
+                .map_err(Error::SelfEncryption);
+        self.record_phase(TransferPhase::Decryption, started);
+        bytes
     }
 
     async fn try_get_chunks(reader: Client, keys: Vec<ChunkKey>) -> Result<Vec<EncryptedChunk>> {
         let expected_count = keys.len();
+        let started = Instant::now();
 
+        let max_in_flight = reader.transfer.max_reads_in_flight();
         let tasks = keys.into_iter().map(|key| {
             let reader = reader.clone();
-            task::spawn(async move {
-                match reader.read_from_network(&key.dst_hash).await {
-                    Ok(chunk) => Some(EncryptedChunk {
-                        index: key.index,
-                        content: chunk.value().clone(),
-                    }),
+            reader.session.clone().spawn("get_chunk", async move {
+                let _permit = reader.transfer.acquire_read().await.ok()?;
+                match reader.read_chunk(&key.dst_hash).await {
+                    Ok(chunk) => {
+                        reader.transfer.throttle(chunk.value().len()).await;
+                        Some(EncryptedChunk {
+                            index: key.index,
+                            content: chunk.value().clone(),
+                        })
+                    }
                     Err(e) => {
                         warn!(
                             "Reading chunk {} from network, resulted in error {}.",
@@ -210,15 +772,19 @@ impl Client {
             })
         });
 
+        // Tasks are only spawned as earlier ones are done, a bounded number at a time.
         // This swallowing of errors
         // is basically a compaction into a single
         // error saying "didn't get all chunks".
-        let encrypted_chunks = join_all(tasks)
+        let encrypted_chunks = stream::iter(tasks)
+            .buffer_unordered(max_in_flight)
+            .collect::<Vec<_>>()
             .await
             .into_iter()
             .flatten()
             .flatten()
             .collect_vec();
+        reader.record_phase(TransferPhase::Download, started);
 
         if expected_count > encrypted_chunks.len() {
             Err(Error::NotEnoughChunks(
@@ -230,24 +796,23 @@ impl Client {
         }
     }
 
-    /// Extracts a blob secretkey from a head chunk.
+    /// Extracts the secretkeys of the parts of a blob from a head chunk, a single one
+    /// unless it was uploaded in parts, or its content if it's held inline.
     /// If the secretkey is not the first level mapping directly to the user's contents,
     /// the process repeats itself until it obtains the first level secretkey.
-    async fn unpack_head_chunk(&self, chunk: HeadChunk) -> Result<BlobSecretKey> {
+    async fn unpack_head_chunk(&self, chunk: HeadChunk) -> Result<HeadContent> {
         let HeadChunk { mut chunk, address } = chunk;
         loop {
-            let bytes = if address.is_public() {
-                chunk.value().clone()
-            } else {
-                let owner = encryption(Scope::Private, self.public_key()).ok_or_else(|| {
-                    Error::Generic("Could not get an encryption object.".to_string())
-                })?;
-                owner.decrypt(chunk.value().clone())?
-            };
-
-            match deserialize(&bytes)? {
+            match self.head_level(address, &chunk)? {
                 SecretKey::FirstLevel(secret_key) => {
-                    return Ok(secret_key);
+                    return Ok(HeadContent::Parts(vec![secret_key]));
+                }
+                SecretKey::Parts { version, parts } => {
+                    check_parts_version(version)?;
+                    return Ok(HeadContent::Parts(parts));
+                }
+                SecretKey::Inline(data) => {
+                    return Ok(HeadContent::Inline(data));
                 }
                 SecretKey::AdditionalLevel(secret_key) => {
                     let serialized_chunk = self.read_all(secret_key).await?;
@@ -256,18 +821,157 @@ impl Client {
             }
         }
     }
+
+    // The secret key held by a head chunk of the blob at `address`, decrypting it first if the
+    // blob is private.
+    pub(super) fn head_level(&self, address: BlobAddress, chunk: &Chunk) -> Result<SecretKey> {
+        let bytes = if address.is_public() {
+            chunk.value().clone()
+        } else {
+            let owner = self
+                .encryption_provider
+                .encryption(Scope::Private, self.public_key())
+                .ok_or_else(|| Error::Generic("Could not get an encryption object.".to_string()))?;
+            owner.decrypt(chunk.value().clone())?
+        };
+        Ok(deserialize(&bytes)?)
+    }
+}
+
+// The head chunk of the level below, decrypted from the chunks of an additional level.
+pub(super) fn decrypt_head_level(
+    secret_key: &BlobSecretKey,
+    encrypted_chunks: &[EncryptedChunk],
+) -> Result<Chunk> {
+    let serialized_chunk = self_encryption::decrypt_full_set(secret_key, encrypted_chunks)
+        .map_err(Error::SelfEncryption)?;
+    Ok(deserialize(&serialized_chunk)?)
+}
+
+// Fails if the upload of a chunk did, or its task panicked.
+fn check_upload(upload: std::result::Result<Result<CmdHandle>, JoinError>) -> Result<()> {
+    let _ =
+        upload.map_err(|err| Error::Generic(format!("Uploading a chunk failed: {}", err)))??;
+    Ok(())
+}
+
+/// Reads data a part at a time, merging parts too small to be self-encrypted into the previous
+/// one, as per `part_ranges`, so streamed blobs are always split up the same way.
+/// No more than two parts are held at a time.
+pub(super) struct PartReader<R> {
+    reader: R,
+    part: Option<Bytes>,
+    // The part after `part`, if already read.
+    next: Option<Bytes>,
+    ended: bool,
+}
+
+impl<R> PartReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    /// Starts reading from `reader`, reading ahead as far as the second part, if any.
+    pub(super) async fn new(mut reader: R) -> Result<Self> {
+        let part = read_part(&mut reader).await?;
+        let mut ended = part.len() < UPLOAD_PART_SIZE;
+        let next = if ended {
+            None
+        } else {
+            let next = read_part(&mut reader).await?;
+            ended = next.len() < UPLOAD_PART_SIZE;
+            Some(next).filter(|next| !next.is_empty())
+        };
+        Ok(Self {
+            reader,
+            part: Some(part),
+            next,
+            ended,
+        })
+    }
+
+    /// Takes the data as a whole, if it fits in a single part and none was taken yet.
+    pub(super) fn take_whole(&mut self) -> Option<Bytes> {
+        if self.ended && self.next.is_none() {
+            self.part.take()
+        } else {
+            None
+        }
+    }
+
+    /// Reads the next part, if any.
+    pub(super) async fn next_part(&mut self) -> Result<Option<Bytes>> {
+        let mut part = match self.part.take() {
+            Some(part) => part,
+            None => return Ok(None),
+        };
+        let mut next = match self.next.take() {
+            Some(next) => next,
+            None if self.ended => Bytes::new(),
+            None => read_part(&mut self.reader).await?,
+        };
+        // A part short of a full one was the last of the data.
+        self.ended = self.ended || next.len() < UPLOAD_PART_SIZE;
+        if !next.is_empty() && next.len() < self_encryption::MIN_ENCRYPTABLE_BYTES {
+            let mut merged = BytesMut::with_capacity(part.len() + next.len());
+            merged.extend_from_slice(&part);
+            merged.extend_from_slice(&next);
+            part = merged.freeze();
+            next = Bytes::new();
+        }
+        if !next.is_empty() {
+            self.part = Some(next);
+        }
+        Ok(Some(part))
+    }
+}
+
+// Reads up to a part's worth of data, less only if the end of `reader` is reached.
+async fn read_part<R>(reader: &mut R) -> Result<Bytes>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut part = BytesMut::with_capacity(UPLOAD_PART_SIZE);
+    while part.len() < UPLOAD_PART_SIZE {
+        let mut limited = (&mut *reader).take((UPLOAD_PART_SIZE - part.len()) as u64);
+        if limited.read_buf(&mut part).await.map_err(Error::IoError)? == 0 {
+            break;
+        }
+    }
+    Ok(part.freeze())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
-    use crate::types::{utils::random_bytes, Keypair};
+    use super::{
+        get_part_chunks, pack_parts, BlobAddress, BlobContent, TransferPhase, UPLOAD_PART_SIZE,
+    };
+    use crate::client::utils::test_utils::{
+        create_test_client, create_test_client_with, run_w_backoff_delayed,
+    };
+    use crate::client::Error;
+    use crate::types::{
+        utils::random_bytes, ChunkAddress, JsonRepr, Keypair, MAX_CHUNK_SIZE_IN_BYTES,
+    };
     use crate::url::Scope;
+    use bincode::serialize;
     use bytes::Bytes;
-    use eyre::Result;
+    use eyre::{bail, Result};
     use futures::future::join_all;
     use rand::rngs::OsRng;
+    use std::{collections::BTreeSet, io::Read};
     use tokio::time::Instant;
+    use xor_name::XorName;
+
+    #[test]
+    fn addresses_round_trip_through_json() -> Result<()> {
+        for address in [
+            BlobAddress::Public(XorName::random()),
+            BlobAddress::Private(XorName::random()),
+        ] {
+            assert_eq!(BlobAddress::from_json(&address.to_json()?)?, address);
+        }
+        Ok(())
+    }
 
     const BLOB_TEST_QUERY_TIMEOUT: u64 = 60;
     const MIN_BLOB_SIZE: usize = self_encryption::MIN_ENCRYPTABLE_BYTES;
@@ -279,15 +983,15 @@ mod tests {
         let blob = random_bytes(MIN_BLOB_SIZE);
 
         use crate::client::client_api::data::get_data_chunks;
-        use crate::client::utils::encryption;
-        let owner = encryption(Scope::Private, keypair.public_key());
-        let (first_address, mut first_chunks) = get_data_chunks(blob.clone(), owner.as_ref())?;
+        use crate::client::{DefaultEncryptionProvider, EncryptionProvider};
+        let owner = DefaultEncryptionProvider.encryption(Scope::Private, keypair.public_key());
+        let (first_address, mut first_chunks) = get_data_chunks(blob.clone(), owner.as_deref())?;
 
         first_chunks.sort();
 
         for _ in 0..100 {
-            let owner = encryption(Scope::Private, keypair.public_key());
-            let (head_address, mut all_chunks) = get_data_chunks(blob.clone(), owner.as_ref())?;
+            let owner = DefaultEncryptionProvider.encryption(Scope::Private, keypair.public_key());
+            let (head_address, mut all_chunks) = get_data_chunks(blob.clone(), owner.as_deref())?;
             assert_eq!(first_address, head_address);
             all_chunks.sort();
             assert_eq!(first_chunks, all_chunks);
@@ -296,6 +1000,37 @@ mod tests {
         Ok(())
     }
 
+    // Test storing and reading blobs small enough to be held inline in their head chunk.
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_and_read_small_inline() -> Result<()> {
+        let client = create_test_client(Some(BLOB_TEST_QUERY_TIMEOUT)).await?;
+
+        for scope in vec![Scope::Private, Scope::Public] {
+            let blob = random_bytes(100);
+            let written = client
+                .write_to_network_with_stats(blob.clone(), scope)
+                .await?;
+            // Only the head chunk is stored, holding the blob inline.
+            assert_eq!(written.stats.chunks_sent, 1);
+
+            let read_data =
+                run_w_backoff_delayed(|| client.read_blob(written.value), 10, 1).await?;
+            compare(blob.clone(), read_data)?;
+
+            let read_data = client.read_blob_from(written.value, 10, 20).await?;
+            compare(blob.slice(10..30), read_data)?;
+            let read_data = client.read_blob_from(written.value, 90, 20).await?;
+            compare(blob.slice(90..), read_data)?;
+        }
+
+        // Empty blobs are held inline too.
+        let address = client.write_to_network(Bytes::new(), Scope::Public).await?;
+        let read_data = run_w_backoff_delayed(|| client.read_blob(address), 10, 1).await?;
+        assert!(read_data.is_empty());
+
+        Ok(())
+    }
+
     // Test storing and reading min size blob.
     #[tokio::test(flavor = "multi_thread")]
     async fn store_and_read_3kb() -> Result<()> {
@@ -336,6 +1071,127 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn share_private_blob() -> Result<()> {
+        let owner = create_test_client(Some(BLOB_TEST_QUERY_TIMEOUT)).await?;
+        let recipient_keypair = Keypair::new_ed25519(&mut OsRng);
+        let recipient = create_test_client_with(
+            Some(recipient_keypair.clone()),
+            Some(BLOB_TEST_QUERY_TIMEOUT),
+        )
+        .await?;
+
+        let blob = random_bytes(MIN_BLOB_SIZE);
+        let address = owner.write_to_network(blob.clone(), Scope::Private).await?;
+        let delay = usize::max(1, blob.len() / DELAY_DIVIDER);
+        let _ = run_w_backoff_delayed(|| owner.read_blob(address), 10, delay).await?;
+
+        let shared_address = owner
+            .share_private_blob(address, recipient_keypair.public_key())
+            .await?;
+        assert!(shared_address.is_private());
+        let read_data =
+            run_w_backoff_delayed(|| recipient.read_blob(shared_address), 10, delay).await?;
+        compare(blob.clone(), read_data)?;
+
+        // Public blobs have nothing to share.
+        let public_address = owner.write_to_network(blob, Scope::Public).await?;
+        assert!(matches!(
+            owner
+                .share_private_blob(public_address, recipient_keypair.public_key())
+                .await,
+            Err(Error::NotPrivateBlob(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_and_read_with_stats() -> Result<()> {
+        let client = create_test_client(Some(BLOB_TEST_QUERY_TIMEOUT)).await?;
+
+        let blob = random_bytes(MIN_BLOB_SIZE);
+        let written = client
+            .write_to_network_with_stats(blob.clone(), Scope::Public)
+            .await?;
+        // A min size blob is self-encrypted into 3 chunks, plus its head chunk.
+        assert_eq!(written.stats.chunks_sent, 4);
+        assert!(written.stats.bytes_sent > blob.len() as u64);
+        assert!(written
+            .stats
+            .phases
+            .contains_key(&TransferPhase::Encryption));
+
+        let delay = usize::max(1, blob.len() / DELAY_DIVIDER);
+        let read =
+            run_w_backoff_delayed(|| client.read_blob_with_stats(written.value), 10, delay).await?;
+        compare(blob.clone(), read.value)?;
+        assert_eq!(read.stats.chunks_received, 4);
+        assert!(read.stats.bytes_received > blob.len() as u64);
+        assert!(read.stats.bytes_sent > 0);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn read_spilled_to_disk() -> Result<()> {
+        let mut client = create_test_client(Some(BLOB_TEST_QUERY_TIMEOUT)).await?;
+        client.read_memory_limit = Some(MIN_BLOB_SIZE);
+
+        let small_blob = random_bytes(MIN_BLOB_SIZE);
+        let large_blob = random_bytes(3 * MAX_CHUNK_SIZE_IN_BYTES);
+        let small_address = client
+            .write_to_network(small_blob.clone(), Scope::Public)
+            .await?;
+        let large_address = client
+            .write_to_network(large_blob.clone(), Scope::Public)
+            .await?;
+
+        let delay = usize::max(1, large_blob.len() / DELAY_DIVIDER);
+        match run_w_backoff_delayed(|| client.read_blob_spilling(small_address), 10, delay).await? {
+            BlobContent::InMemory(read_data) => compare(small_blob, read_data)?,
+            content => bail!("Small blob unexpectedly spilled: {:?}", content),
+        }
+
+        match run_w_backoff_delayed(|| client.read_blob_spilling(large_address), 10, delay).await? {
+            BlobContent::Spilled(spilled) => {
+                assert_eq!(spilled.len(), large_blob.len());
+                let mut read_data = Vec::new();
+                let _ = spilled.open()?.read_to_end(&mut read_data)?;
+                compare(large_blob, Bytes::from(read_data))?;
+            }
+            content => bail!("Large blob unexpectedly read into memory: {:?}", content),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn read_streamed_to_writer() -> Result<()> {
+        let client = create_test_client(Some(BLOB_TEST_QUERY_TIMEOUT)).await?;
+
+        let blob = random_bytes(3 * MAX_CHUNK_SIZE_IN_BYTES);
+        let address = client
+            .write_to_network(blob.clone(), Scope::Private)
+            .await?;
+
+        let delay = usize::max(1, blob.len() / DELAY_DIVIDER);
+        let read_data = run_w_backoff_delayed(
+            || async {
+                let mut read_data = Vec::new();
+                let written = client.read_blob_to(address, &mut read_data).await?;
+                assert_eq!(written, blob.len() as u64);
+                Ok(read_data)
+            },
+            10,
+            delay,
+        )
+        .await?;
+        compare(blob, Bytes::from(read_data))?;
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn seek_in_data() -> Result<()> {
         for i in 1..5 {
@@ -393,6 +1249,72 @@ mod tests {
         store_and_read(40 * 1024 * 1024, Scope::Private).await
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "too heavy for CI"]
+    async fn seek_across_parts() -> Result<()> {
+        let client = create_test_client(Some(BLOB_TEST_QUERY_TIMEOUT)).await?;
+        let data = random_bytes(UPLOAD_PART_SIZE + 3 * MIN_BLOB_SIZE);
+        let address = client.write_stream(&data[..], Scope::Public).await?;
+
+        let (pos, len) = (UPLOAD_PART_SIZE - MIN_BLOB_SIZE, 2 * MIN_BLOB_SIZE);
+        let delay = usize::max(1, len / DELAY_DIVIDER);
+        let read_data =
+            run_w_backoff_delayed(|| client.read_blob_from(address, pos, len), 10, delay).await?;
+        compare(data.slice(pos..(pos + len)), read_data)?;
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "too heavy for CI"]
+    async fn only_large_streams_are_stored_in_parts() -> Result<()> {
+        let client = create_test_client(Some(BLOB_TEST_QUERY_TIMEOUT)).await?;
+
+        let small = random_bytes(MIN_BLOB_SIZE);
+        let streamed = client.write_stream(&small[..], Scope::Public).await?;
+        let written = client.write_to_network(small, Scope::Public).await?;
+        assert_eq!(streamed, written);
+
+        // Two full parts, and a tail too small to be a part of its own.
+        let blob = random_bytes(2 * UPLOAD_PART_SIZE + MIN_BLOB_SIZE / 2);
+        let streamed = client.write_stream(&blob[..], Scope::Public).await?;
+        let written = client.write_to_network(blob.clone(), Scope::Public).await?;
+        assert_ne!(streamed, written);
+
+        let delay = usize::max(1, blob.len() / DELAY_DIVIDER);
+        for address in vec![streamed, written] {
+            let read_data = run_w_backoff_delayed(|| client.read_blob(address), 10, delay).await?;
+            compare(blob.clone(), read_data)?;
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn chunk_addresses_include_additional_levels() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        // A data map only outgrows its head chunk with gigabytes of content, so the same part
+        // is listed over and over instead, for its secret keys to take an additional level.
+        let (part, part_chunks) = get_part_chunks(random_bytes(3 * 1024), None)?;
+        let count = MAX_CHUNK_SIZE_IN_BYTES / serialize(&part)?.len() + 1;
+        let (address, head_chunks) = pack_parts(vec![part; count], None)?;
+        assert!(head_chunks.len() > 1);
+        for chunk in part_chunks.iter().chain(&head_chunks) {
+            let _ = client.store_chunk(chunk.clone()).await?;
+        }
+
+        let chunks = run_w_backoff_delayed(|| client.chunk_addresses(address), 10, 1).await?;
+        let expected: BTreeSet<_> = part_chunks
+            .iter()
+            .chain(&head_chunks)
+            .map(|chunk| ChunkAddress(*chunk.name()))
+            .collect();
+        assert_eq!(chunks.into_iter().collect::<BTreeSet<_>>(), expected);
+
+        Ok(())
+    }
+
     // Essentially a load test, seeing how much parallel batting the nodes can take.
     #[tokio::test(flavor = "multi_thread")]
     #[ignore = "too heavy for CI"]
@@ -0,0 +1,177 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::Error;
+use crate::messaging::data::{
+    CatalogPage, DataCmd, DataQuery, QueryResponse, MAX_CATALOG_PAGE_SIZE,
+};
+use crate::types::{DataAddress, PublicKey};
+use tracing::{trace, warn};
+
+impl Client {
+    /// List a page of the catalog of the data this client owns, in no meaningful order: the
+    /// blobs, archives and Registers it stored, and the data whose storage it recorded a payment
+    /// for with [`Client::record_payment`]. A client acting for a user, as per
+    /// [`Client::with_delegation`], lists the user's catalog.
+    ///
+    /// Up to `limit` addresses are listed, and no more than [`MAX_CATALOG_PAGE_SIZE`], following
+    /// `start_after`. Passing the [`CatalogPage::next`] of a page lists the next one, which can
+    /// be done any time later on, e.g. to resume a listing which was interrupted.
+    pub async fn list_owned_data(
+        &self,
+        start_after: Option<DataAddress>,
+        limit: u32,
+    ) -> Result<CatalogPage, Error> {
+        trace!("List owned data after {:?}", start_after);
+        let query = DataQuery::ListOwnedData {
+            owner: self.owner(),
+            start_after,
+            limit,
+        };
+        let query_result = self.send_query(query).await?;
+        match query_result.response {
+            QueryResponse::ListOwnedData((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })
+            }
+            _ => Err(Error::ReceivedUnexpectedEvent),
+        }
+    }
+
+    // Adds the data at `address`, just stored, to the catalog of the data this client owns.
+    // The data being stored already, failing to catalogue it doesn't fail storing it.
+    pub(crate) async fn catalog(&self, address: DataAddress) {
+        trace!("Catalog {:?}", address);
+        let cmd = DataCmd::CatalogData {
+            owner: self.owner(),
+            address: address.clone(),
+        };
+        if let Err(error) = self.send_cmd(cmd).await {
+            warn!("Failed to catalogue {:?}: {}", address, error);
+        }
+    }
+
+    // Key of the owner of the data this client stores: the user who delegated to it, if any.
+    pub(super) fn owner(&self) -> PublicKey {
+        self.delegation
+            .as_ref()
+            .map(|delegation| delegation.user)
+            .unwrap_or_else(|| self.public_key())
+    }
+
+    /// List the whole catalog of the data this client owns, as per [`Client::list_owned_data`],
+    /// a page after another.
+    pub async fn owned_data(&self) -> Result<Vec<DataAddress>, Error> {
+        let mut addresses = Vec::new();
+        let mut start_after = None;
+        loop {
+            let page = self
+                .list_owned_data(start_after, MAX_CATALOG_PAGE_SIZE)
+                .await?;
+            addresses.extend(page.addresses);
+            match page.next {
+                Some(next) => start_after = Some(next),
+                None => return Ok(addresses),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::client::Error;
+    use crate::types::{ChunkAddress, DataAddress, Token};
+    use crate::url::Scope;
+    use bytes::Bytes;
+    use eyre::Result;
+    use std::collections::{BTreeMap, BTreeSet};
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stored_data_is_listed() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let blob = client
+            .write_to_network(Bytes::from(vec![1; 4096]), Scope::Public)
+            .await?;
+        let register = client
+            .store_public_register(
+                XorName::random(),
+                15000,
+                client.public_key(),
+                BTreeMap::new(),
+            )
+            .await?;
+        let stored: BTreeSet<_> = vec![
+            DataAddress::Chunk(ChunkAddress(*blob.name())),
+            DataAddress::Register(register),
+        ]
+        .into_iter()
+        .collect();
+
+        let listed = run_w_backoff_delayed(
+            || async {
+                let listed: BTreeSet<_> = client.owned_data().await?.into_iter().collect();
+                if !listed.is_superset(&stored) {
+                    return Err(Error::Generic("Not all data is catalogued yet".to_string()));
+                }
+                Ok(listed)
+            },
+            10,
+            1,
+        )
+        .await?;
+        assert_eq!(listed, stored);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn paid_data_is_listed_in_pages() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let mut paid = BTreeSet::new();
+        for _ in 0..3 {
+            let address = DataAddress::Chunk(ChunkAddress(XorName::random()));
+            let _ = client
+                .record_payment(address.clone(), Token::from_nano(10))
+                .await?;
+            let _ = paid.insert(address);
+        }
+
+        let listed = run_w_backoff_delayed(
+            || async {
+                let listed = client.owned_data().await?;
+                if listed.len() < paid.len() {
+                    return Err(Error::Generic("Not all data is catalogued yet".to_string()));
+                }
+                Ok(listed)
+            },
+            10,
+            1,
+        )
+        .await?;
+        assert_eq!(listed.into_iter().collect::<BTreeSet<_>>(), paid);
+
+        let mut resumed = BTreeSet::new();
+        let mut start_after = None;
+        loop {
+            let page = client.list_owned_data(start_after, 1).await?;
+            assert!(page.addresses.len() <= 1);
+            resumed.extend(page.addresses);
+            match page.next {
+                Some(next) => start_after = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(resumed, paid);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::Result;
+use crate::types::Chunk;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Mutex, PoisonError},
+};
+use tracing::trace;
+use xor_name::XorName;
+
+/// Hits and misses of the chunk cache, as returned by [`Client::chunk_cache_stats`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ChunkCacheStats {
+    /// Chunks read from the cache.
+    pub hits: u64,
+    /// Chunks fetched from the network, not being in the cache.
+    pub misses: u64,
+    /// Chunks evicted to keep the cache within its budget.
+    pub evictions: u64,
+    /// Chunks in the cache.
+    pub chunks: usize,
+    /// Bytes of the chunks in the cache.
+    pub bytes: usize,
+    /// Max bytes of the chunks in the cache.
+    pub budget: usize,
+}
+
+/// Chunks read recently, so blobs read again aren't fetched from the network again.
+/// The least recently read chunks are evicted once the chunks exceed the budget, in bytes.
+#[derive(Debug)]
+pub(super) struct ChunkCache {
+    budget: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    // Chunks, with the tick they were last read at.
+    chunks: HashMap<XorName, (Chunk, u64)>,
+    // Names of the chunks, by the tick they were last read at, from the least recent.
+    recency: BTreeMap<u64, XorName>,
+    tick: u64,
+    stats: ChunkCacheStats,
+}
+
+impl ChunkCache {
+    pub(super) fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            state: Mutex::new(CacheState {
+                stats: ChunkCacheStats {
+                    budget,
+                    ..ChunkCacheStats::default()
+                },
+                ..CacheState::default()
+            }),
+        }
+    }
+
+    // Returns the chunk if it's cached, counting a hit or a miss.
+    fn get(&self, name: &XorName) -> Option<Chunk> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.tick += 1;
+        let tick = state.tick;
+        let (chunk, last_read) = match state.chunks.get_mut(name) {
+            Some((chunk, last_read)) => (chunk.clone(), std::mem::replace(last_read, tick)),
+            None => {
+                state.stats.misses += 1;
+                return None;
+            }
+        };
+        let _ = state.recency.remove(&last_read);
+        let _ = state.recency.insert(tick, *name);
+        state.stats.hits += 1;
+        Some(chunk)
+    }
+
+    fn insert(&self, name: XorName, chunk: Chunk) {
+        let size = chunk.payload_size();
+        if size > self.budget {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.tick += 1;
+        let tick = state.tick;
+        if let Some((_, last_read)) = state.chunks.insert(name, (chunk, tick)) {
+            let _ = state.recency.remove(&last_read);
+        } else {
+            state.stats.bytes += size;
+        }
+        let _ = state.recency.insert(tick, name);
+
+        while state.stats.bytes > self.budget {
+            let least_recent = match state.recency.keys().next() {
+                Some(tick) => *tick,
+                None => break,
+            };
+            let evicted = state
+                .recency
+                .remove(&least_recent)
+                .and_then(|name| state.chunks.remove(&name));
+            if let Some((chunk, _)) = evicted {
+                trace!("Evicting chunk {:?} from the cache", chunk.name());
+                state.stats.bytes -= chunk.payload_size();
+                state.stats.evictions += 1;
+            }
+        }
+        state.stats.chunks = state.chunks.len();
+    }
+
+    fn clear(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.chunks.clear();
+        state.recency.clear();
+        state.stats.chunks = 0;
+        state.stats.bytes = 0;
+    }
+
+    fn stats(&self) -> ChunkCacheStats {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.stats.clone()
+    }
+}
+
+impl Client {
+    /// Returns the hits and misses of the chunk cache, or `None` if the client wasn't configured
+    /// with one, see [`Config::chunk_cache_budget`].
+    ///
+    /// [`Config::chunk_cache_budget`]: crate::client::Config::chunk_cache_budget
+    pub fn chunk_cache_stats(&self) -> Option<ChunkCacheStats> {
+        self.chunk_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Drops all the chunks in the cache, if the client was configured with one. Counts of hits
+    /// and misses are kept.
+    pub fn clear_chunk_cache(&self) {
+        if let Some(cache) = &self.chunk_cache {
+            cache.clear()
+        }
+    }
+
+    // Reads a chunk from the cache if it's there, from the network otherwise, caching it.
+    pub(super) async fn read_chunk(&self, name: &XorName) -> Result<Chunk> {
+        let cache = match &self.chunk_cache {
+            Some(cache) => cache,
+            None => return self.read_from_network(name).await,
+        };
+        if let Some(chunk) = cache.get(name) {
+            trace!("Using cached chunk: {:?}", name);
+            return Ok(chunk);
+        }
+
+        let chunk = self.read_from_network(name).await?;
+        cache.insert(*name, chunk.clone());
+        Ok(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkCache;
+    use crate::types::{utils::random_bytes, Chunk};
+    use eyre::Result;
+
+    fn chunk(size: usize) -> Chunk {
+        Chunk::new(random_bytes(size))
+    }
+
+    #[test]
+    fn least_recently_read_chunks_are_evicted() -> Result<()> {
+        let cache = ChunkCache::new(300);
+        let chunks: Vec<_> = (0..3).map(|_| chunk(100)).collect();
+        for chunk in &chunks {
+            cache.insert(*chunk.name(), chunk.clone());
+        }
+        assert!(cache.get(chunks[0].name()).is_some());
+
+        // The second chunk is now the least recently read.
+        let newest = chunk(100);
+        cache.insert(*newest.name(), newest.clone());
+        assert!(cache.get(chunks[1].name()).is_none());
+        assert!(cache.get(chunks[0].name()).is_some());
+        assert!(cache.get(newest.name()).is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.chunks, 3);
+        assert_eq!(stats.bytes, 300);
+
+        // Chunks bigger than the whole budget aren't cached.
+        let too_big = chunk(400);
+        cache.insert(*too_big.name(), too_big.clone());
+        assert!(cache.get(too_big.name()).is_none());
+
+        cache.clear();
+        assert!(cache.get(chunks[0].name()).is_none());
+        assert_eq!(cache.stats().bytes, 0);
+
+        Ok(())
+    }
+}
@@ -6,17 +6,31 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Client;
-use crate::client::Error;
+use super::{Client, OperationKind};
+use crate::client::{
+    connections::{Budget, CmdHandle, SentMsg},
+    Error,
+};
 use crate::messaging::{
     data::{DataCmd, ServiceMsg},
     ServiceAuth, WireMsg,
 };
 use crate::types::{PublicKey, Signature};
 use bytes::Bytes;
+use tokio::time::Instant;
 use xor_name::XorName;
 
 impl Client {
+    /// Send a command to the network as is, signed by the client's signer.
+    ///
+    /// This is a low-level API for tools exercising the protocol directly, e.g. debuggers and
+    /// migration scripts. No checks are made on the command beforehand, e.g. of the size of
+    /// the data, and errors the Elders respond with are received via the [`CmdHandle`]
+    /// returned, as well as per [`Client::next_cmd_error`].
+    pub async fn send_raw_cmd(&self, cmd: DataCmd) -> Result<CmdHandle, Error> {
+        self.send_cmd(cmd).await
+    }
+
     /// Send a signed DataCmd to the network.
     /// This is to be part of a public API, for the user to
     /// provide the serialised and already signed command.
@@ -28,19 +42,60 @@ impl Client {
         signature: Signature,
         targets: usize,
     ) -> Result<(), Error> {
+        let _ = self
+            .send_signed_command_within(
+                Budget::Unlimited,
+                dst_address,
+                client_pk,
+                serialised_cmd,
+                signature,
+                targets,
+            )
+            .await?;
+        Ok(())
+    }
+
+    // Sends a signed command, counting against the given budget while it's in flight.
+    async fn send_signed_command_within(
+        &self,
+        budget: Budget,
+        dst_address: XorName,
+        client_pk: PublicKey,
+        serialised_cmd: Bytes,
+        signature: Signature,
+        targets: usize,
+    ) -> Result<(SentMsg, CmdHandle), Error> {
         let auth = ServiceAuth {
             public_key: client_pk,
             signature,
+            delegation: self.delegation.clone(),
+        };
+
+        // Commands bounced for our knowledge of the section being outdated are resent once,
+        // within the time a query would be given.
+        let retry_within = match &self.retry_budget {
+            Some(budget) => {
+                budget.check_deadline()?;
+                budget.bound(self.query_timeout)
+            }
+            None => self.query_timeout,
         };
 
-        self.session
-            .send_cmd(dst_address, auth, serialised_cmd, targets)
-            .await
+        let _ticket = self.session.ticket(self.priority, budget).await?;
+        let started = Instant::now();
+        let result = self
+            .session
+            .send_cmd(dst_address, auth, serialised_cmd, targets, retry_within)
+            .await;
+
+        let elapsed = result.as_ref().ok().map(|_| started.elapsed());
+        self.latency.record(OperationKind::Write, elapsed).await;
+        result
     }
 
-    // Send a DataCmd to the network without awaiting for a response.
-    // This function is a helper private to this module.
-    pub(crate) async fn send_cmd(&self, cmd: DataCmd) -> Result<(), Error> {
+    // Send a DataCmd to the network without awaiting for a response, returning the handle to
+    // await an error for it with.
+    pub(crate) async fn send_cmd(&self, cmd: DataCmd) -> Result<CmdHandle, Error> {
         let client_pk = self.public_key();
         let dst_name = cmd.dst_name();
 
@@ -53,16 +108,44 @@ impl Client {
         // With 3 we are "guaranteed" 1 correctly functioning Elder.
         let targets = match &cmd {
             DataCmd::StoreChunk(_) => 3, // stored at Adults, so only 1 correctly functioning Elder need to relay
-            DataCmd::Register(_) => 7,   // only stored at Elders, all need a copy
+            DataCmd::Register(_)
+            | DataCmd::RecordPayment(_)
+            | DataCmd::CatalogPayment { .. }
+            | DataCmd::CatalogData { .. } => 7, // only stored at Elders, all need a copy
         };
+        let budget = match &cmd {
+            DataCmd::StoreChunk(_) => Budget::ChunkWrites,
+            DataCmd::Register(_) => Budget::RegisterOps,
+            DataCmd::RecordPayment(_)
+            | DataCmd::CatalogPayment { .. }
+            | DataCmd::CatalogData { .. } => Budget::Unlimited,
+        };
+
+        let chunk = matches!(cmd, DataCmd::StoreChunk(_));
 
         let serialised_cmd = {
             let msg = ServiceMsg::Cmd(cmd);
             WireMsg::serialize_msg_payload(&msg)?
         };
-        let signature = self.keypair.sign(&serialised_cmd);
+        let signature = self.signer.sign(&ServiceAuth::bytes_to_sign(
+            &serialised_cmd,
+            self.delegation.as_ref(),
+        )?)?;
 
-        self.send_signed_command(dst_name, client_pk, serialised_cmd, signature, targets)
-            .await
+        let len = serialised_cmd.len();
+        let (sent, handle) = self
+            .send_signed_command_within(
+                budget,
+                dst_name,
+                client_pk,
+                serialised_cmd,
+                signature,
+                targets,
+            )
+            .await?;
+        if let Some(recorder) = &self.stats {
+            recorder.sent(sent, len, chunk);
+        }
+        Ok(handle)
     }
 }
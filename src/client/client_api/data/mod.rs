@@ -8,4 +8,7 @@
 
 mod pac_man;
 
-pub(crate) use pac_man::{get_data_chunks, SecretKey};
+pub(crate) use pac_man::{
+    check_parts_version, get_data_chunks, get_part_chunks, pack_inline, pack_keys, pack_parts,
+    part_ranges, SecretKey, UPLOAD_PART_SIZE,
+};
@@ -13,7 +13,21 @@ use bytes::Bytes;
 use rayon::prelude::*;
 use self_encryption::{EncryptedChunk, SecretKey as BlobSecretKey};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::{ops::Range, path::Path};
+
+/// Size of the parts data streamed is split into when larger than this, each self-encrypted on
+/// its own, so that it's never held whole in memory. Data written whole is self-encrypted whole,
+/// whatever its size.
+pub(crate) const UPLOAD_PART_SIZE: usize = 16 * 1024 * 1024;
+/// Version of the layout of blobs stored in parts, recorded in their head chunk.
+pub(crate) const PARTS_FORMAT_VERSION: u16 = 1;
+
+/// Blobs smaller than this are too small to be self-encrypted, so their content is held inline
+/// in their head chunk instead.
+pub(crate) const MAX_INLINE_SIZE: usize = self_encryption::MIN_ENCRYPTABLE_BYTES - 1;
+// Size the head chunks of inline blobs are padded to before being encrypted, that of the
+// largest inline content once serialised, so they don't give away how small the content is.
+const INLINE_HEAD_SIZE: usize = MAX_INLINE_SIZE + 16;
 
 #[derive(Serialize, Deserialize)]
 pub(crate) enum SecretKey {
@@ -23,12 +37,22 @@ pub(crate) enum SecretKey {
     // resulting from chunking up a previous level secret key.
     // This happens when that previous level secret key was too big to fit in a chunk itself.
     AdditionalLevel(BlobSecretKey),
+    // Holds the secret keys to the parts the source data was split into, in order, for data
+    // larger than `UPLOAD_PART_SIZE` which was streamed, with the version of the layout of the
+    // parts. Each part being self-encrypted on its own, such data gets other chunks, and another
+    // address, than if it was self-encrypted whole: clients predating this variant can't read it.
+    Parts {
+        version: u16,
+        parts: Vec<BlobSecretKey>,
+    },
+    // Holds the source data itself, as it's too small to be self-encrypted.
+    Inline(Bytes),
 }
 
 #[allow(unused)]
 pub(crate) fn get_file_chunks(
     path: &Path,
-    encryption: Option<&impl Encryption>,
+    encryption: Option<&dyn Encryption>,
 ) -> Result<(BlobAddress, Vec<Chunk>)> {
     let (secret_key, encrypted_chunks) = encrypt_file(path)?;
     pack(secret_key, encrypted_chunks, encryption)
@@ -36,12 +60,95 @@ pub(crate) fn get_file_chunks(
 
 pub(crate) fn get_data_chunks(
     data: Bytes,
-    encryption: Option<&impl Encryption>,
+    encryption: Option<&dyn Encryption>,
 ) -> Result<(BlobAddress, Vec<Chunk>)> {
+    if data.len() <= MAX_INLINE_SIZE {
+        return pack_inline(data, encryption);
+    }
     let (secret_key, encrypted_chunks) = encrypt_data(data)?;
     pack(secret_key, encrypted_chunks, encryption)
 }
 
+/// Packs data too small to be self-encrypted into the head chunk of a blob, as is. Returns its
+/// address, and the head chunk as the only chunk of the blob.
+pub(crate) fn pack_inline(
+    data: Bytes,
+    encryption: Option<&dyn Encryption>,
+) -> Result<(BlobAddress, Vec<Chunk>)> {
+    pack_key(SecretKey::Inline(data), encryption)
+}
+
+/// Splits data of `len` bytes into the ranges of the parts it's uploaded as,
+/// a single one unless it's larger than [`UPLOAD_PART_SIZE`].
+pub(crate) fn part_ranges(len: usize) -> Vec<Range<usize>> {
+    let mut ranges: Vec<_> = (0..len)
+        .step_by(UPLOAD_PART_SIZE)
+        .map(|start| start..len.min(start + UPLOAD_PART_SIZE))
+        .collect();
+    // Parts too small to be self-encrypted are merged into the previous one.
+    if ranges.len() > 1 && ranges[ranges.len() - 1].len() < self_encryption::MIN_ENCRYPTABLE_BYTES {
+        if let Some(last) = ranges.pop() {
+            let index = ranges.len() - 1;
+            ranges[index].end = last.end;
+        }
+    }
+    ranges
+}
+
+/// Self-encrypts one of the parts of a blob, returning its secret key and its chunks.
+pub(crate) fn get_part_chunks(
+    part: Bytes,
+    encryption: Option<&dyn Encryption>,
+) -> Result<(BlobSecretKey, Vec<Chunk>)> {
+    let (secret_key, encrypted_chunks) = encrypt_data(part)?;
+    let chunks = encrypted_chunks
+        .par_iter()
+        .map(|c| to_chunk(c.content.clone(), encryption))
+        .collect::<Result<_>>()?;
+    Ok((secret_key, chunks))
+}
+
+/// Returns the top-most chunk address through which the parts of a blob can be accessed,
+/// and the chunks the parts' secret keys were packed into.
+pub(crate) fn pack_parts(
+    parts: Vec<BlobSecretKey>,
+    encryption: Option<&dyn Encryption>,
+) -> Result<(BlobAddress, Vec<Chunk>)> {
+    pack_key(
+        SecretKey::Parts {
+            version: PARTS_FORMAT_VERSION,
+            parts,
+        },
+        encryption,
+    )
+}
+
+/// Refuses blobs stored in a version of the parts format this client doesn't know of.
+pub(crate) fn check_parts_version(version: u16) -> Result<()> {
+    if version != PARTS_FORMAT_VERSION {
+        return Err(Error::UnsupportedPartsFormat(version));
+    }
+    Ok(())
+}
+
+/// Packs the secret keys of the parts of a blob, as extracted from its head chunk, into a new
+/// head chunk, e.g. encrypted for another user than the owner. Returns its address, and the
+/// chunks of the additional levels of secret keys this required.
+pub(crate) fn pack_keys(
+    mut secret_keys: Vec<BlobSecretKey>,
+    encryption: Option<&dyn Encryption>,
+) -> Result<(BlobAddress, Vec<Chunk>)> {
+    let secret_key = if secret_keys.len() == 1 {
+        SecretKey::FirstLevel(secret_keys.remove(0))
+    } else {
+        SecretKey::Parts {
+            version: PARTS_FORMAT_VERSION,
+            parts: secret_keys,
+        }
+    };
+    pack_key(secret_key, encryption)
+}
+
 /// Returns the top-most chunk address through which the entire
 /// data tree can be accessed, and all the other encrypted chunks.
 /// If encryption is provided, the additional secret key level chunks are encrypted with it.
@@ -49,7 +156,25 @@ pub(crate) fn get_data_chunks(
 pub(crate) fn pack(
     secret_key: BlobSecretKey,
     encrypted_chunks: Vec<EncryptedChunk>,
-    encryption: Option<&impl Encryption>,
+    encryption: Option<&dyn Encryption>,
+) -> Result<(BlobAddress, Vec<Chunk>)> {
+    let (address, additional_chunks) = pack_key(SecretKey::FirstLevel(secret_key), encryption)?;
+
+    let all_chunks: Vec<_> = encrypted_chunks
+        .par_iter()
+        .map(|c| to_chunk(c.content.clone(), encryption))
+        .flatten() // swallows errors!
+        .chain(additional_chunks) // drops errors
+        .collect();
+
+    Ok((address, all_chunks))
+}
+
+// Packs a secret key into the head chunk of a blob, returning its address along with
+// the chunks of the additional levels of secret keys this required.
+fn pack_key(
+    secret_key: SecretKey,
+    encryption: Option<&dyn Encryption>,
 ) -> Result<(BlobAddress, Vec<Chunk>)> {
     // Produces a chunk out of the first secret key, which is validated for its size.
     // If the chunk is too big, it is self-encrypted and the resulting (additional level) secret key is put into a chunk.
@@ -58,9 +183,9 @@ pub(crate) fn pack(
     // self encrypted into additional chunks, and now we have a new secret key
     // which points to all of those additional chunks.. and so on.
     let mut chunks = vec![];
-    let mut chunk_content = pack_secret_key(SecretKey::FirstLevel(secret_key), encryption)?;
+    let mut chunk_content = pack_secret_key(secret_key, encryption)?;
 
-    let (address, additional_chunks) = loop {
+    loop {
         let chunk = to_chunk(chunk_content, encryption)?;
         // If secret key chunk is less that 1MB return it so it can be directly sent to the network
         if chunk.validate_size() {
@@ -73,7 +198,7 @@ pub(crate) fn pack(
             } else {
                 BlobAddress::Public(name)
             };
-            break (address, chunks);
+            break Ok((address, chunks));
         } else {
             let serialized_chunk = Bytes::from(serialize(&chunk)?);
             let (secret_key, next_encrypted_chunks) =
@@ -86,20 +211,18 @@ pub(crate) fn pack(
                 .collect();
             chunk_content = pack_secret_key(SecretKey::AdditionalLevel(secret_key), encryption)?;
         }
-    };
-
-    let all_chunks: Vec<_> = encrypted_chunks
-        .par_iter()
-        .map(|c| to_chunk(c.content.clone(), encryption))
-        .flatten() // swallows errors!
-        .chain(additional_chunks) // drops errors
-        .collect();
-
-    Ok((address, all_chunks))
+    }
 }
 
-fn pack_secret_key(secret_key: SecretKey, encryption: Option<&impl Encryption>) -> Result<Bytes> {
-    let raw_bytes = Bytes::from(serialize(&secret_key)?);
+fn pack_secret_key(secret_key: SecretKey, encryption: Option<&dyn Encryption>) -> Result<Bytes> {
+    let mut raw_bytes = serialize(&secret_key)?;
+    // Padding is ignored when deserialising.
+    if let SecretKey::Inline(_) = secret_key {
+        if raw_bytes.len() < INLINE_HEAD_SIZE {
+            raw_bytes.resize(INLINE_HEAD_SIZE, 0);
+        }
+    }
+    let raw_bytes = Bytes::from(raw_bytes);
     if let Some(encryption) = encryption {
         // strictly, we do not need to encrypt this if it's not going to be the
         // last level, since it will then instead be self-encrypted.
@@ -119,7 +242,7 @@ fn encrypt_data(bytes: Bytes) -> Result<(BlobSecretKey, Vec<EncryptedChunk>)> {
     self_encryption::encrypt(bytes).map_err(Error::SelfEncryption)
 }
 
-fn to_chunk(chunk_content: Bytes, encryption: Option<&impl Encryption>) -> Result<Chunk> {
+fn to_chunk(chunk_content: Bytes, encryption: Option<&dyn Encryption>) -> Result<Chunk> {
     let chunk: Chunk = if let Some(encryption) = encryption {
         // strictly, we do not need to encrypt this if it's not going to be the
         // last level, since it will then instead be self-encrypted.
@@ -132,3 +255,75 @@ fn to_chunk(chunk_content: Bytes, encryption: Option<&impl Encryption>) -> Resul
 
     Ok(chunk)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_parts_version, get_data_chunks, get_part_chunks, pack_parts, part_ranges, SecretKey,
+        INLINE_HEAD_SIZE, MAX_INLINE_SIZE, PARTS_FORMAT_VERSION, UPLOAD_PART_SIZE,
+    };
+    use crate::types::utils::random_bytes;
+    use bincode::deserialize;
+    use eyre::{bail, eyre, Result};
+
+    #[test]
+    fn large_data_is_split_into_parts() {
+        assert_eq!(part_ranges(UPLOAD_PART_SIZE), vec![0..UPLOAD_PART_SIZE]);
+        assert_eq!(
+            part_ranges(2 * UPLOAD_PART_SIZE + 1024),
+            vec![
+                0..UPLOAD_PART_SIZE,
+                UPLOAD_PART_SIZE..2 * UPLOAD_PART_SIZE,
+                2 * UPLOAD_PART_SIZE..2 * UPLOAD_PART_SIZE + 1024
+            ]
+        );
+
+        // A trailing part too small to be self-encrypted goes with the previous one.
+        assert_eq!(
+            part_ranges(UPLOAD_PART_SIZE + 1),
+            vec![0..UPLOAD_PART_SIZE + 1]
+        );
+    }
+
+    #[test]
+    fn parts_are_versioned() -> Result<()> {
+        let mut parts = vec![];
+        for _ in 0..2 {
+            let (secret_key, _) = get_part_chunks(random_bytes(MAX_INLINE_SIZE + 1), None)?;
+            parts.push(secret_key);
+        }
+        let (_, chunks) = pack_parts(parts, None)?;
+        let head = chunks.last().ok_or_else(|| eyre!("No head chunk"))?;
+        match deserialize(head.value())? {
+            SecretKey::Parts { version, parts } => {
+                assert_eq!(version, PARTS_FORMAT_VERSION);
+                assert_eq!(parts.len(), 2);
+            }
+            _ => bail!("Parts weren't packed in the head chunk"),
+        }
+
+        check_parts_version(PARTS_FORMAT_VERSION)?;
+        assert!(check_parts_version(PARTS_FORMAT_VERSION + 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn small_data_is_held_inline() -> Result<()> {
+        for size in vec![0, 1, MAX_INLINE_SIZE] {
+            let data = random_bytes(size);
+            let (_, chunks) = get_data_chunks(data.clone(), None)?;
+            assert_eq!(chunks.len(), 1);
+            assert_eq!(chunks[0].value().len(), INLINE_HEAD_SIZE);
+            match deserialize(chunks[0].value())? {
+                SecretKey::Inline(inline) => assert_eq!(inline, data),
+                _ => bail!("Data of {} bytes wasn't held inline", size),
+            }
+        }
+
+        let (_, chunks) = get_data_chunks(random_bytes(MAX_INLINE_SIZE + 1), None)?;
+        assert!(chunks.len() > 1);
+
+        Ok(())
+    }
+}
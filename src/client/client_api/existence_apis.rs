@@ -0,0 +1,160 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{BlobAddress, Client};
+use crate::client::Error;
+use crate::messaging::data::{DataQuery, QueryResponse};
+use crate::types::ChunkAddress;
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{trace, warn};
+
+// Max number of chunks checked at any one time for a blob.
+const MAX_CONCURRENT_CHUNK_CHECKS: usize = 8;
+
+/// Which of the chunks of a blob are stored, as returned by [`Client::blob_presence`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlobPresence {
+    /// Address of the blob.
+    pub address: BlobAddress,
+    /// Whether each chunk of the blob is stored, by address, its head chunk included. Only the
+    /// head chunk is listed if it isn't stored, the others being listed in it.
+    pub chunks: BTreeMap<ChunkAddress, bool>,
+    /// The chunks which couldn't be checked, of which the network couldn't tell whether it
+    /// holds them.
+    pub unknown: BTreeSet<ChunkAddress>,
+}
+
+impl BlobPresence {
+    /// The chunks which aren't stored.
+    pub fn missing(&self) -> impl Iterator<Item = &ChunkAddress> {
+        self.chunks
+            .iter()
+            .filter(|(_, stored)| !**stored)
+            .map(|(address, _)| address)
+    }
+
+    /// Whether all the chunks of the blob are known to be stored.
+    pub fn is_complete(&self) -> bool {
+        self.missing().next().is_none() && self.unknown.is_empty()
+    }
+}
+
+impl Client {
+    /// Find out whether the chunk at `address` is stored, without retrieving it, e.g. for
+    /// uploads to be resumed from the chunks missing.
+    ///
+    /// The Elders responsible for it ask the Adults meant to hold it, the chunk being deemed
+    /// stored if any of them holds it.
+    pub async fn chunk_exists(&self, address: ChunkAddress) -> Result<bool, Error> {
+        trace!("Check chunk {:?} exists", address);
+        let query_result = self.send_query(DataQuery::ChunkExists(address)).await?;
+        match query_result.response {
+            QueryResponse::ChunkExists((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })
+            }
+            _ => Err(Error::ReceivedUnexpectedEvent),
+        }
+    }
+
+    /// Find out whether all the chunks of the blob at `address` are stored, as per
+    /// [`Client::blob_presence`].
+    pub async fn blob_exists(&self, address: BlobAddress) -> Result<bool, Error> {
+        Ok(self.blob_presence(address).await?.is_complete())
+    }
+
+    /// Find out which of the chunks of the blob at `address` are stored, e.g. for integrity
+    /// checkers to tell which are lost.
+    ///
+    /// Only the head chunk is retrieved, for the chunks of the blob to be listed: the others are
+    /// checked without being downloaded. Chunks which couldn't be checked are listed as unknown,
+    /// rather than missing.
+    pub async fn blob_presence(&self, address: BlobAddress) -> Result<BlobPresence, Error> {
+        let head = ChunkAddress(*address.name());
+        let mut presence = BlobPresence {
+            address,
+            chunks: BTreeMap::new(),
+            unknown: BTreeSet::new(),
+        };
+        if !self.chunk_exists(head).await? {
+            let _ = presence.chunks.insert(head, false);
+            return Ok(presence);
+        }
+
+        let chunks = self.chunk_addresses(address).await?;
+        let checks: Vec<_> = stream::iter(chunks)
+            .map(|chunk| async move {
+                if chunk == head {
+                    return (chunk, Some(true));
+                }
+                match self.chunk_exists(chunk).await {
+                    Ok(exists) => (chunk, Some(exists)),
+                    Err(error) => {
+                        warn!("Couldn't check chunk {:?} exists: {}", chunk, error);
+                        (chunk, None)
+                    }
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_CHUNK_CHECKS)
+            .collect()
+            .await;
+        for (chunk, exists) in checks {
+            match exists {
+                Some(exists) => {
+                    let _ = presence.chunks.insert(chunk, exists);
+                }
+                None => {
+                    let _ = presence.unknown.insert(chunk);
+                }
+            }
+        }
+
+        Ok(presence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::client_api::BlobAddress;
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::types::{utils::random_bytes, ChunkAddress};
+    use crate::url::Scope;
+    use eyre::{eyre, Result};
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stored_chunks_exist() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let blob = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let address = client.write_to_network(blob, Scope::Public).await?;
+        let _ = run_w_backoff_delayed(|| client.read_blob(address), 10, 1).await?;
+
+        let presence = client.blob_presence(address).await?;
+        assert!(presence.is_complete());
+        assert!(presence.unknown.is_empty());
+        // A min size blob is self-encrypted into 3 chunks, plus its head chunk.
+        assert_eq!(presence.chunks.len(), 4);
+        assert!(client.blob_exists(address).await?);
+
+        let missing = ChunkAddress(XorName::random());
+        assert!(!client.chunk_exists(missing).await?);
+        let presence = client
+            .blob_presence(BlobAddress::Public(*missing.name()))
+            .await?;
+        assert_eq!(
+            presence
+                .missing()
+                .next()
+                .ok_or_else(|| eyre!("No chunk missing"))?,
+            &missing
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::Error;
+use crate::messaging::data::{DataQuery, QueryResponse};
+
+use futures::{
+    future::Future,
+    stream::{self, StreamExt},
+};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{debug, warn};
+use xor_name::{Prefix, XorName};
+
+/// Outcome of a query fanned out across sections, by [`Client::fan_out`], each section's
+/// result or error being kept apart, so that a few failing sections don't void the others'.
+#[derive(Debug)]
+pub struct FanOutResult<T> {
+    /// Results of the sections which answered, by prefix.
+    pub results: BTreeMap<Prefix, T>,
+    /// Errors of the sections which didn't, by prefix.
+    pub errors: BTreeMap<Prefix, Error>,
+}
+
+impl<T> FanOutResult<T> {
+    /// Whether every section queried answered.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Share of the address space covered by the sections which answered, between 0 and 1,
+    /// e.g. to scale up counts sampled from them.
+    pub fn coverage(&self) -> f64 {
+        self.results
+            .keys()
+            .map(|prefix| 0.5f64.powi(prefix.bit_count() as i32))
+            .sum()
+    }
+}
+
+impl Client {
+    /// Prefixes of the sections this client knows about, e.g. to fan a query out across them.
+    ///
+    /// Sections are only known once the client has dealt with them: use
+    /// [`Client::connect_to_sections`] with names spread across the address space to discover
+    /// more of them first.
+    pub fn known_sections(&self) -> BTreeSet<Prefix> {
+        self.session.known_sections()
+    }
+
+    /// Run `query` for each of the given sections, with up to `max_concurrent` of them at a
+    /// time, for tools sampling or enumerating data across the address space, e.g. health
+    /// monitors and indexers.
+    ///
+    /// The query is given the prefix of its section, within which it picks the names to query,
+    /// e.g. with `prefix.substituted_in(XorName::random())`. The results of the sections which
+    /// answered are returned along with the errors of those which didn't.
+    pub async fn fan_out<T, F, Fut>(
+        &self,
+        prefixes: impl IntoIterator<Item = Prefix>,
+        max_concurrent: usize,
+        query: F,
+    ) -> FanOutResult<T>
+    where
+        F: Fn(Prefix) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let query = &query;
+        let outcomes: Vec<_> = stream::iter(prefixes)
+            .map(|prefix| async move { (prefix, query(prefix).await) })
+            .buffer_unordered(max_concurrent.max(1))
+            .collect()
+            .await;
+
+        let mut fan_out = FanOutResult {
+            results: BTreeMap::new(),
+            errors: BTreeMap::new(),
+        };
+        for (prefix, outcome) in outcomes {
+            match outcome {
+                Ok(result) => {
+                    let _ = fan_out.results.insert(prefix, result);
+                }
+                Err(error) => {
+                    warn!("Fanned out query to section {:?} failed: {}", prefix, error);
+                    let _ = fan_out.errors.insert(prefix, error);
+                }
+            }
+        }
+        debug!(
+            "Fanned out query answered by {} sections, failed at {}",
+            fan_out.results.len(),
+            fan_out.errors.len()
+        );
+        fan_out
+    }
+
+    /// Send the query `template` builds for a random name in each of the sections this client
+    /// knows about, as per [`Client::fan_out`], returning the responses the Elders of each
+    /// section agreed on, as per [`Client::send_raw_query`].
+    pub async fn fan_out_query(
+        &self,
+        max_concurrent: usize,
+        template: impl Fn(XorName) -> DataQuery,
+    ) -> FanOutResult<QueryResponse> {
+        self.fan_out(self.known_sections(), max_concurrent, |prefix| {
+            self.send_raw_query(template(prefix.substituted_in(XorName::random())))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FanOutResult;
+    use crate::client::utils::test_utils::create_test_client;
+    use crate::client::Error;
+    use crate::messaging::data::{DataQuery, QueryResponse, RegisterRead};
+    use crate::types::RegisterAddress;
+    use eyre::Result;
+    use std::collections::BTreeMap;
+    use xor_name::Prefix;
+
+    #[test]
+    fn coverage_is_that_of_the_sections_which_answered() {
+        let zero = Prefix::default().pushed(false);
+        let mut fan_out = FanOutResult {
+            results: vec![(zero.pushed(false), ()), (zero.pushed(true), ())]
+                .into_iter()
+                .collect(),
+            errors: BTreeMap::new(),
+        };
+        assert!(fan_out.is_complete());
+        assert!((fan_out.coverage() - 0.5).abs() < f64::EPSILON);
+
+        let _ = fan_out
+            .errors
+            .insert(Prefix::default().pushed(true), Error::NoResponse);
+        assert!(!fan_out.is_complete());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn queries_fan_out_to_all_known_sections() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let sections = client.known_sections();
+        assert!(!sections.is_empty());
+
+        let fan_out = client
+            .fan_out_query(4, |name| {
+                DataQuery::Register(RegisterRead::GetOwner(RegisterAddress::Public {
+                    name,
+                    tag: 0,
+                }))
+            })
+            .await;
+        assert_eq!(fan_out.results.len() + fan_out.errors.len(), sections.len());
+        // No Register is stored at random names, which each section answers with an error.
+        assert!(fan_out
+            .results
+            .values()
+            .all(|response| matches!(response, QueryResponse::GetRegisterOwner((Err(_), _)))));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{BlobAddress, Client, DirEntry, NrsTarget};
+use crate::client::{Error, Result};
+use crate::types::register::{Address, Entry, EntryHash};
+use crate::url::{ContentType, DataType, Scope, Url};
+
+use bytes::Bytes;
+use std::collections::BTreeSet;
+use tracing::trace;
+
+/// What a `safe://` URL was fetched to, by [`Client::fetch`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FetchedContent {
+    /// The content of a blob, or of a file of a files container.
+    Blob {
+        /// Address of the blob.
+        address: BlobAddress,
+        /// Its content.
+        content: Bytes,
+    },
+    /// The entries of a Register: its latest ones, or the one at the version in the URL.
+    Register {
+        /// Address of the Register.
+        address: Address,
+        /// The entries fetched.
+        entries: BTreeSet<(EntryHash, Entry)>,
+    },
+    /// The listing of a directory of a files container.
+    Container {
+        /// Address of the Register the container is kept in.
+        address: Address,
+        /// Path of the directory, `/` for the root.
+        path: String,
+        /// Its files and subdirectories, by name.
+        entries: Vec<DirEntry>,
+    },
+}
+
+impl Client {
+    /// Fetch what a `safe://` URL points to, whichever kind of URL it is: an NRS URL is
+    /// resolved to its target first, and a path is followed within the files container the
+    /// URL points to, to a file or a directory.
+    ///
+    /// A version in the URL of a Register, of a files container, or of an NRS name, fetches it
+    /// as it was at that version.
+    pub async fn fetch(&self, url: &Url) -> Result<FetchedContent> {
+        trace!("Fetch {}", url);
+        let path = url.path_decoded()?;
+
+        if url.is_nrsurl() {
+            return match self.nrs_target(url).await? {
+                NrsTarget::Blob(address) => self.fetch_blob(address, url, &path).await,
+                NrsTarget::FilesContainer(address) => {
+                    self.fetch_in_container(address, None, path).await
+                }
+            };
+        }
+
+        match url.data_type() {
+            DataType::Blob => {
+                let address = match url.scope() {
+                    Scope::Public => BlobAddress::Public(url.xorname()),
+                    Scope::Private => BlobAddress::Private(url.xorname()),
+                };
+                self.fetch_blob(address, url, &path).await
+            }
+            DataType::Register if url.content_type() == ContentType::FilesContainer => {
+                let entries = self.read_register_at_url(url).await?;
+                self.fetch_in_container(url.register_address()?, Some(entries), path)
+                    .await
+            }
+            DataType::Register => {
+                if !is_root(&path) {
+                    return Err(Error::UnsupportedUrl(format!(
+                        "{} points to a Register, which has no path",
+                        url
+                    )));
+                }
+                Ok(FetchedContent::Register {
+                    address: url.register_address()?,
+                    entries: self.read_register_at_url(url).await?,
+                })
+            }
+            DataType::SafeKey => Err(Error::UnsupportedUrl(format!(
+                "{} points to a SafeKey, which has no content",
+                url
+            ))),
+        }
+    }
+
+    async fn fetch_blob(
+        &self,
+        address: BlobAddress,
+        url: &Url,
+        path: &str,
+    ) -> Result<FetchedContent> {
+        if !is_root(path) {
+            return Err(Error::UnsupportedUrl(format!(
+                "{} points to a blob, which has no path",
+                url
+            )));
+        }
+        Ok(FetchedContent::Blob {
+            address,
+            content: self.read_blob(address).await?,
+        })
+    }
+
+    // Fetches the file at `path` in the container, or lists the directory there, as of the
+    // given entries of its Register, if any, otherwise as of its latest ones.
+    async fn fetch_in_container(
+        &self,
+        address: Address,
+        entries: Option<BTreeSet<(EntryHash, Entry)>>,
+        path: String,
+    ) -> Result<FetchedContent> {
+        let container = self.files_container(address);
+        let files = match entries {
+            Some(entries) => container.files_as_of(entries).await?,
+            None => container.files().await?,
+        };
+        match files.get(&path) {
+            Ok(item) => Ok(FetchedContent::Blob {
+                address: item.blob,
+                content: self.read_blob(item.blob).await?,
+            }),
+            Err(Error::FilesContainerPathNotFound(_)) => {
+                let entries = files.list(&path)?;
+                self.prefetch_listed_files(&entries);
+                Ok(FetchedContent::Container {
+                    address,
+                    entries,
+                    path: if path.is_empty() {
+                        "/".to_string()
+                    } else {
+                        path
+                    },
+                })
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+fn is_root(path: &str) -> bool {
+    path.is_empty() || path == "/"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FetchedContent;
+    use crate::client::client_api::{DirEntry, NrsTarget};
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::types::utils::random_bytes;
+    use crate::url::{ContentType, Scope, Url, XorUrlBase};
+    use eyre::{bail, Result};
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fetch_follows_names_and_paths() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let content = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+
+        let container = client
+            .create_files_container(XorName::random(), 15_000, Scope::Public)
+            .await?;
+        let _ = run_w_backoff_delayed(|| container.list("/"), 10, 1).await?;
+        let file = container.add("/docs/notes.txt", content.clone()).await?;
+
+        let xorurl = Url::encode_register(
+            *container.address().name(),
+            container.address().tag(),
+            Scope::Public,
+            ContentType::FilesContainer,
+            XorUrlBase::Base32z,
+        )?;
+        let mut url = Url::from_url(&xorurl)?;
+        url.set_path("/docs");
+        match run_w_backoff_delayed(|| client.fetch(&url), 10, 1).await? {
+            FetchedContent::Container { path, entries, .. } => {
+                assert_eq!(path, "/docs");
+                assert_eq!(
+                    entries,
+                    vec![DirEntry::File {
+                        name: "notes.txt".to_string(),
+                        item: file
+                    }]
+                );
+            }
+            other => bail!("Unexpected content fetched: {:?}", other),
+        }
+
+        // A version fetches the container as it was then.
+        let _ = container.add("/docs/todo.txt", content.clone()).await?;
+        url.set_content_version_number(Some(0));
+        match run_w_backoff_delayed(|| client.fetch(&url), 10, 1).await? {
+            FetchedContent::Container { entries, .. } => assert_eq!(entries.len(), 1),
+            other => bail!("Unexpected content fetched: {:?}", other),
+        }
+
+        let top_name = format!("fetch-test-{}", rand::random::<u64>());
+        let _ = client
+            .nrs_register(&top_name, NrsTarget::FilesContainer(*container.address()))
+            .await?;
+        let url = Url::from_url(&format!("safe://{}/docs/notes.txt", top_name))?;
+        match run_w_backoff_delayed(|| client.fetch(&url), 10, 1).await? {
+            FetchedContent::Blob {
+                address,
+                content: fetched,
+            } => {
+                assert_eq!(address, file.blob);
+                assert_eq!(fetched, content);
+            }
+            other => bail!("Unexpected content fetched: {:?}", other),
+        }
+
+        Ok(())
+    }
+}
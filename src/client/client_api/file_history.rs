@@ -0,0 +1,280 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{BlobAddress, Client};
+use crate::client::{Error, ErrorMessage, Result};
+use crate::messaging::data::CmdError;
+use crate::types::register::{
+    Address, Entry, EntryHash, PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy,
+    Register, User,
+};
+use crate::url::{ContentType, Scope, Url, XorUrlBase};
+use bytes::Bytes;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
+use tracing::debug;
+use xor_name::XorName;
+
+// How long Elders are given to reject storing a Register at an address which must not be taken.
+const CLAIM_ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A version of a file kept in a [`FileHistory`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FileVersion {
+    /// Number of the version, the first one written being 0.
+    pub version: u64,
+    /// Hash of the Register entry recording the version.
+    pub hash: EntryHash,
+    /// Address of the blob holding the content of the file at this version.
+    pub blob: BlobAddress,
+}
+
+/// A file whose content changes over time, kept as a Register to which the address of a new
+/// blob is appended for every version. Blobs being immutable, every version stays readable.
+///
+/// Versions written concurrently, e.g. from two devices, are all kept, at the same version
+/// number. The one with the lowest entry hash is deemed the latest, so all readers agree on
+/// it, and the next version written supersedes them all.
+#[derive(Clone, Debug)]
+pub struct FileHistory {
+    client: Client,
+    address: Address,
+}
+
+impl FileHistory {
+    /// Address of the Register the history is kept in.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Store `content` as a blob, private or public as the history is, and append it to the
+    /// history as its new latest version.
+    pub async fn write_new_version(&self, content: Bytes) -> Result<FileVersion> {
+        let scope = scope_of(&self.address);
+        let blob = self.client.write_to_network(content, scope).await?;
+        let url = Url::encode_blob(*blob.name(), scope, ContentType::Raw, XorUrlBase::Base32z)?;
+
+        // The new version supersedes all the latest ones, merging any concurrent versions.
+        let register = self.client.get_register(self.address).await?;
+        let mut version = 0;
+        let mut children = BTreeSet::new();
+        for (hash, _) in register.read(None)? {
+            if let Some(latest) = register.version(hash, None)? {
+                version = version.max(latest + 1);
+            }
+            let _ = children.insert(hash);
+        }
+
+        let hash = self
+            .client
+            .write_to_register(self.address, Url::from_url(&url)?, children)
+            .await?;
+        debug!(
+            "Wrote version {} of file history {:?}, at {:?}",
+            version, self.address, blob
+        );
+
+        Ok(FileVersion {
+            version,
+            hash,
+            blob,
+        })
+    }
+
+    /// The latest version of the file, if any was written yet, without reading its content.
+    pub async fn latest_version(&self) -> Result<Option<FileVersion>> {
+        let register = self.client.get_register(self.address).await?;
+        let mut latest = BTreeMap::new();
+        for (hash, entry) in register.read(None)? {
+            if let Some(version) = register.version(hash, None)? {
+                let _ = latest.entry(version).or_insert((hash, entry));
+            }
+        }
+        Ok(latest
+            .into_iter()
+            .next_back()
+            .map(|(version, (hash, entry))| FileVersion {
+                version,
+                hash,
+                blob: blob_of(&entry),
+            }))
+    }
+
+    /// The latest version of the file, and its content, if any was written yet.
+    pub async fn get_latest(&self) -> Result<Option<(FileVersion, Bytes)>> {
+        let version = match self.latest_version().await? {
+            Some(version) => version,
+            None => return Ok(None),
+        };
+        let content = self.client.read_blob(version.blob).await?;
+        Ok(Some((version, content)))
+    }
+
+    /// The given version of the file, and its content, the first one written being 0.
+    pub async fn get_version(&self, version: u64) -> Result<(FileVersion, Bytes)> {
+        let register = self.client.get_register(self.address).await?;
+        let (hash, entry) = entry_at(&register, version)?;
+        let version = FileVersion {
+            version,
+            hash,
+            blob: blob_of(&entry),
+        };
+        let content = self.client.read_blob(version.blob).await?;
+        Ok((version, content))
+    }
+}
+
+pub(super) fn scope_of(address: &Address) -> Scope {
+    if address.is_public() {
+        Scope::Public
+    } else {
+        Scope::Private
+    }
+}
+
+pub(super) fn blob_of(entry: &Entry) -> BlobAddress {
+    match entry.scope() {
+        Scope::Public => BlobAddress::Public(entry.xorname()),
+        Scope::Private => BlobAddress::Private(entry.xorname()),
+    }
+}
+
+fn entry_at(register: &Register, version: u64) -> Result<(EntryHash, Entry)> {
+    register
+        .get_by_version(version, None)?
+        .map(|(hash, entry)| (hash, entry.clone()))
+        .ok_or_else(|| Error::from(crate::types::Error::NoSuchEntry))
+}
+
+impl Client {
+    /// Create a history for a file, kept in a new Register of the given name and tag, owned by
+    /// this client, which alone can write new versions. Its versions are private or public as
+    /// per `scope`.
+    pub async fn create_file_history(
+        &self,
+        name: XorName,
+        tag: u64,
+        scope: Scope,
+    ) -> Result<FileHistory> {
+        let address = self.store_owned_register(name, tag, scope).await?;
+        Ok(self.file_history(address))
+    }
+
+    // Store a Register only this client can write to, readable by anyone if it's public.
+    pub(super) async fn store_owned_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        scope: Scope,
+    ) -> Result<Address> {
+        let register = self.owned_register(name, tag, scope);
+        let address = *register.address();
+        let _ = self.pay_and_write_register_to_network(register).await?;
+        Ok(address)
+    }
+
+    // Store a Register only this client can write to, as per `store_owned_register`, at an
+    // address which mustn't be taken, e.g. that of a public name. Fails with
+    // `Error::RegisterTaken` if a Register is found there, or if Elders reject storing ours for
+    // there being one, e.g. stored by another client in the meantime.
+    pub(super) async fn claim_owned_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        scope: Scope,
+    ) -> Result<Address> {
+        let register = self.owned_register(name, tag, scope);
+        let address = *register.address();
+        match self.get_register(address).await {
+            Ok(_) => return Err(Error::RegisterTaken(address)),
+            Err(Error::ErrorMessage {
+                source: ErrorMessage::DataNotFound(_),
+                ..
+            }) => {}
+            Err(error) => return Err(error),
+        }
+
+        let handle = self.pay_and_write_register_to_network(register).await?;
+        let op_id = format!("{:?}", handle.msg_id());
+        match handle.await_ack(CLAIM_ACK_TIMEOUT).await {
+            Ok(()) => Ok(address),
+            Err(CmdError::Data(ErrorMessage::DataExists)) => Err(Error::RegisterTaken(address)),
+            Err(error) => Err(Error::from((error, op_id))),
+        }
+    }
+
+    fn owned_register(&self, name: XorName, tag: u64, scope: Scope) -> Register {
+        let owner = self.public_key();
+        match scope {
+            Scope::Private => {
+                let mut permissions = BTreeMap::new();
+                let _ = permissions.insert(owner, PrivatePermissions::new(true, true));
+                let policy = PrivatePolicy { owner, permissions };
+                Register::new_private(owner, name, tag, Some(policy))
+            }
+            Scope::Public => {
+                let mut permissions = BTreeMap::new();
+                let _ = permissions.insert(User::Key(owner), PublicPermissions::new(true));
+                let policy = PublicPolicy { owner, permissions };
+                Register::new_public(owner, name, tag, Some(policy))
+            }
+        }
+    }
+
+    /// The history of a file kept in the Register at `address`.
+    pub fn file_history(&self, address: Address) -> FileHistory {
+        FileHistory {
+            client: self.clone(),
+            address,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::types::utils::random_bytes;
+    use crate::url::Scope;
+    use eyre::{eyre, Result};
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn file_history_keeps_every_version() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let history = client
+            .create_file_history(XorName(rand::random()), 15_000, Scope::Private)
+            .await?;
+        let _ = run_w_backoff_delayed(|| client.get_register(*history.address()), 10, 1).await?;
+        assert!(history.get_latest().await?.is_none());
+
+        let first = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let second = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let written = history.write_new_version(first.clone()).await?;
+        assert_eq!(written.version, 0);
+        let _ = run_w_backoff_delayed(|| history.get_version(0), 10, 1).await?;
+        let written = history.write_new_version(second.clone()).await?;
+        assert_eq!(written.version, 1);
+
+        let _ = run_w_backoff_delayed(|| history.get_version(1), 10, 1).await?;
+        let (latest, content) = history
+            .get_latest()
+            .await?
+            .ok_or_else(|| eyre!("no version"))?;
+        assert_eq!(latest, written);
+        assert_eq!(content, second);
+
+        let (version, content) = history.get_version(0).await?;
+        assert_eq!(version.version, 0);
+        assert_eq!(content, first);
+        assert!(history.get_version(2).await.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,482 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::file_history::scope_of;
+use super::{BlobAddress, Client, Migrations, StoredDoc};
+use crate::client::{Error, Result};
+use crate::types::register::{Address, Entry, EntryHash};
+use crate::url::Scope;
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::debug;
+use xor_name::XorName;
+
+// Version of the schema files maps are stored with.
+const FILES_MAP_VERSION: u32 = 0;
+
+/// A file in a [`FilesContainer`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FileItem {
+    /// Blob the content of the file is stored in.
+    pub blob: BlobAddress,
+    /// Size of the content, in bytes.
+    pub size: u64,
+}
+
+/// An entry of a directory of a [`FilesContainer`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DirEntry {
+    /// A file, by its name in the directory.
+    File {
+        /// Name of the file.
+        name: String,
+        /// Where its content is stored.
+        item: FileItem,
+    },
+    /// A subdirectory, by its name in the directory.
+    Dir {
+        /// Name of the subdirectory.
+        name: String,
+    },
+}
+
+/// The files of a container, by absolute path, e.g. `/docs/notes.txt`.
+///
+/// Directories aren't stored as such: they are there as long as there are files under them.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FilesMap {
+    /// Where each file's content is stored, by path.
+    pub files: BTreeMap<String, FileItem>,
+}
+
+impl FilesMap {
+    /// Returns the file at `path`.
+    pub fn get(&self, path: &str) -> Result<&FileItem> {
+        let path = normalise_path(path)?;
+        self.files
+            .get(&path)
+            .ok_or(Error::FilesContainerPathNotFound(path))
+    }
+
+    /// Whether there's a directory at `path`, i.e. files under it. The root always is one.
+    pub fn is_dir(&self, path: &str) -> Result<bool> {
+        let path = normalise_path(path)?;
+        Ok(path == "/" || self.files_under(&path).next().is_some())
+    }
+
+    /// Adds a file at `path`, which must be free, and not under another file.
+    pub fn insert(&mut self, path: &str, item: FileItem) -> Result<()> {
+        let path = normalise_file_path(path)?;
+        self.check_free(&path)?;
+        let _ = self.files.insert(path, item);
+        Ok(())
+    }
+
+    /// Removes the file at `path`, or the directory and all the files under it, returning the
+    /// files removed, by path.
+    pub fn remove(&mut self, path: &str) -> Result<BTreeMap<String, FileItem>> {
+        let path = normalise_file_path(path)?;
+        if let Some(item) = self.files.remove(&path) {
+            return Ok(vec![(path, item)].into_iter().collect());
+        }
+        let paths: Vec<_> = self
+            .files_under(&path)
+            .map(|(path, _)| path.clone())
+            .collect();
+        if paths.is_empty() {
+            return Err(Error::FilesContainerPathNotFound(path));
+        }
+        Ok(paths
+            .into_iter()
+            .filter_map(|path| self.files.remove(&path).map(|item| (path, item)))
+            .collect())
+    }
+
+    /// Moves the file at `from`, or the directory and all the files under it, to `to`, which
+    /// must be free, and not under another file.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        let from = normalise_file_path(from)?;
+        let to = normalise_file_path(to)?;
+        if to.starts_with(&format!("{}/", from)) {
+            return Err(Error::InvalidFilesContainerPath(format!(
+                "can't move {} under itself, to {}",
+                from, to
+            )));
+        }
+        if !self.files.contains_key(&from) && self.files_under(&from).next().is_none() {
+            return Err(Error::FilesContainerPathNotFound(from));
+        }
+        self.check_free(&to)?;
+
+        for (path, item) in self.remove(&from)? {
+            let _ = self
+                .files
+                .insert(format!("{}{}", to, &path[from.len()..]), item);
+        }
+        Ok(())
+    }
+
+    /// Lists the files and subdirectories of the directory at `path`, by name.
+    pub fn list(&self, path: &str) -> Result<Vec<DirEntry>> {
+        let path = normalise_path(path)?;
+        let prefix = if path == "/" {
+            path.clone()
+        } else {
+            format!("{}/", path)
+        };
+
+        let mut entries = Vec::new();
+        for (file, item) in self
+            .files
+            .range(prefix.clone()..)
+            .take_while(|(file, _)| file.starts_with(&prefix))
+        {
+            let rest = &file[prefix.len()..];
+            match rest.find('/') {
+                None => entries.push(DirEntry::File {
+                    name: rest.to_string(),
+                    item: *item,
+                }),
+                Some(end) => {
+                    // Files under a subdirectory are next to each other, so it's listed once.
+                    let name = &rest[..end];
+                    let listed = match entries.last() {
+                        Some(DirEntry::Dir { name: last }) => last == name,
+                        _ => false,
+                    };
+                    if !listed {
+                        entries.push(DirEntry::Dir {
+                            name: name.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if entries.is_empty() && path != "/" {
+            return Err(Error::FilesContainerPathNotFound(path));
+        }
+        Ok(entries)
+    }
+
+    // Files under the directory at `path`, which isn't the root.
+    fn files_under<'a>(
+        &'a self,
+        path: &str,
+    ) -> impl Iterator<Item = (&'a String, &'a FileItem)> + 'a {
+        let prefix = format!("{}/", path);
+        self.files
+            .range(prefix.clone()..)
+            .take_while(move |(file, _)| file.starts_with(&prefix))
+    }
+
+    // Fails if there's a file or directory at `path`, or a file at any of its parents.
+    fn check_free(&self, path: &str) -> Result<()> {
+        if self.files.contains_key(path) || self.files_under(path).next().is_some() {
+            return Err(Error::FilesContainerPathExists(path.to_string()));
+        }
+        for (end, _) in path.rmatch_indices('/').filter(|(end, _)| *end > 0) {
+            if self.files.contains_key(&path[..end]) {
+                return Err(Error::FilesContainerPathExists(path[..end].to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Makes `path` absolute, without empty segments nor trailing slash, e.g. `a//b/` is `/a/b`.
+fn normalise_path(path: &str) -> Result<String> {
+    let mut normalised = String::new();
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        if segment == "." || segment == ".." {
+            return Err(Error::InvalidFilesContainerPath(path.to_string()));
+        }
+        normalised.push('/');
+        normalised.push_str(segment);
+    }
+    if normalised.is_empty() {
+        normalised.push('/');
+    }
+    Ok(normalised)
+}
+
+// Like `normalise_path`, but fails on the root, as there can't be a file there.
+fn normalise_file_path(path: &str) -> Result<String> {
+    let normalised = normalise_path(path)?;
+    if normalised == "/" {
+        return Err(Error::InvalidFilesContainerPath(path.to_string()));
+    }
+    Ok(normalised)
+}
+
+/// A tree of files, kept as a Register to which a new [`FilesMap`], stored as a blob, is
+/// appended on every change. Every earlier state of the container thus stays readable.
+///
+/// Changes made concurrently, e.g. from two devices, are merged by the next one: the files of
+/// all the latest maps are kept, the map with the lowest entry hash winning for paths they
+/// disagree on. A file removed in one of them may hence come back.
+#[derive(Clone, Debug)]
+pub struct FilesContainer {
+    client: Client,
+    address: Address,
+}
+
+impl FilesContainer {
+    /// Address of the Register the container is kept in.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// The files of the container, as per its latest maps, merged.
+    pub async fn files(&self) -> Result<FilesMap> {
+        Ok(self.latest().await?.1)
+    }
+
+    /// Store `content` as a blob, private or public as the container is, and add it to the
+    /// container at `path`, which must be free.
+    pub async fn add(&self, path: &str, content: Bytes) -> Result<FileItem> {
+        // Fail early, before storing the content, if the path is taken.
+        let (children, mut files) = self.latest().await?;
+        files.check_free(&normalise_file_path(path)?)?;
+
+        let item = FileItem {
+            size: content.len() as u64,
+            blob: self
+                .client
+                .write_to_network(content, scope_of(&self.address))
+                .await?,
+        };
+        files.insert(path, item)?;
+        self.write(&files, children).await?;
+        Ok(item)
+    }
+
+    /// Removes the file at `path`, or the directory and all the files under it, returning the
+    /// files removed, by path. Their content stays on the network.
+    pub async fn remove(&self, path: &str) -> Result<BTreeMap<String, FileItem>> {
+        let (children, mut files) = self.latest().await?;
+        let removed = files.remove(path)?;
+        self.write(&files, children).await?;
+        Ok(removed)
+    }
+
+    /// Moves the file at `from`, or the directory and all the files under it, to `to`.
+    pub async fn rename(&self, from: &str, to: &str) -> Result<()> {
+        let (children, mut files) = self.latest().await?;
+        files.rename(from, to)?;
+        self.write(&files, children).await?;
+        Ok(())
+    }
+
+    /// Lists the files and subdirectories of the directory at `path`, by name.
+    ///
+    /// The head chunks of the files listed are prefetched in the background, for them to be
+    /// opened quickly, unless prefetching was disabled in the client's config.
+    pub async fn list(&self, path: &str) -> Result<Vec<DirEntry>> {
+        let entries = self.files().await?.list(path)?;
+        self.client.prefetch_listed_files(&entries);
+        Ok(entries)
+    }
+
+    /// The file at `path`, and its content.
+    pub async fn get(&self, path: &str) -> Result<(FileItem, Bytes)> {
+        let item = *self.files().await?.get(path)?;
+        let content = self.client.read_blob(item.blob).await?;
+        Ok((item, content))
+    }
+
+    // The files of the container as of the given entries of its Register, e.g. those of an
+    // earlier version of it, merged.
+    pub(super) async fn files_as_of(
+        &self,
+        entries: BTreeSet<(EntryHash, Entry)>,
+    ) -> Result<FilesMap> {
+        let docs = self
+            .client
+            .read_docs_from_entries(self.address, entries, &migrations())
+            .await?;
+        Ok(merge(docs).1)
+    }
+
+    // The latest maps merged, along with their entries, for the next change to supersede them.
+    async fn latest(&self) -> Result<(BTreeSet<EntryHash>, FilesMap)> {
+        let docs = self
+            .client
+            .read_docs_from_register(self.address, &migrations())
+            .await?;
+        Ok(merge(docs))
+    }
+
+    async fn write(&self, files: &FilesMap, children: BTreeSet<EntryHash>) -> Result<()> {
+        let hash = self
+            .client
+            .write_doc_to_register(self.address, files, &migrations(), children)
+            .await?;
+        debug!(
+            "Wrote {} files to files container {:?}, at {:?}",
+            files.files.len(),
+            self.address,
+            hash
+        );
+        Ok(())
+    }
+}
+
+// Merges the maps of the given entries, returning them along with their entries.
+fn merge(docs: Vec<(EntryHash, StoredDoc<FilesMap>)>) -> (BTreeSet<EntryHash>, FilesMap) {
+    let mut children = BTreeSet::new();
+    let mut merged = FilesMap::default();
+    // Entries come by ascending hash, so the lowest one wins on conflicting paths.
+    for (hash, doc) in docs {
+        let _ = children.insert(hash);
+        for (path, item) in doc.doc.files {
+            let _ = merged.files.entry(path).or_insert(item);
+        }
+    }
+    (children, merged)
+}
+
+fn migrations() -> Migrations<FilesMap> {
+    Migrations::new(FILES_MAP_VERSION)
+}
+
+impl Client {
+    /// Create an empty files container, kept in a new Register of the given name and tag, owned
+    /// by this client, which alone can change it. Its files are private or public as per
+    /// `scope`.
+    pub async fn create_files_container(
+        &self,
+        name: XorName,
+        tag: u64,
+        scope: Scope,
+    ) -> Result<FilesContainer> {
+        let address = self.store_owned_register(name, tag, scope).await?;
+        Ok(self.files_container(address))
+    }
+
+    /// The files container kept in the Register at `address`.
+    pub fn files_container(&self, address: Address) -> FilesContainer {
+        FilesContainer {
+            client: self.clone(),
+            address,
+        }
+    }
+
+    // Prefetches the head chunks of the files among `entries` listed.
+    pub(super) fn prefetch_listed_files(&self, entries: &[DirEntry]) {
+        self.prefetch_head_chunks(entries.iter().filter_map(|entry| match entry {
+            DirEntry::File { item, .. } => Some(item.blob),
+            DirEntry::Dir { .. } => None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DirEntry, FileItem, FilesMap};
+    use crate::client::client_api::BlobAddress;
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::client::Error;
+    use crate::types::utils::random_bytes;
+    use crate::url::Scope;
+    use eyre::Result;
+    use xor_name::XorName;
+
+    fn item() -> FileItem {
+        FileItem {
+            blob: BlobAddress::Public(XorName(rand::random())),
+            size: 1,
+        }
+    }
+
+    #[test]
+    fn files_map_keeps_a_tree_of_paths() -> Result<()> {
+        let mut files = FilesMap::default();
+        let notes = item();
+        files.insert("docs/notes.txt", notes)?;
+        files.insert("/docs/old/a.txt", item())?;
+        files.insert("/docs//old/b.txt/", item())?;
+        files.insert("/readme", item())?;
+
+        assert_eq!(files.get("/docs/notes.txt")?, &notes);
+        assert!(files.is_dir("/docs/old")?);
+        assert!(matches!(
+            files.insert("/docs/old", item()),
+            Err(Error::FilesContainerPathExists(_))
+        ));
+        assert!(matches!(
+            files.insert("/readme/a", item()),
+            Err(Error::FilesContainerPathExists(path)) if path == "/readme"
+        ));
+        assert!(matches!(
+            files.insert("/docs/../etc", item()),
+            Err(Error::InvalidFilesContainerPath(_))
+        ));
+
+        assert_eq!(
+            files.list("/docs")?,
+            vec![
+                DirEntry::File {
+                    name: "notes.txt".to_string(),
+                    item: notes
+                },
+                DirEntry::Dir {
+                    name: "old".to_string()
+                },
+            ]
+        );
+
+        files.rename("/docs/old", "/archive")?;
+        assert_eq!(files.list("/archive")?.len(), 2);
+        assert!(matches!(
+            files.list("/docs/old"),
+            Err(Error::FilesContainerPathNotFound(_))
+        ));
+        assert!(files.rename("/archive", "/archive/nested").is_err());
+
+        assert_eq!(files.remove("/archive")?.len(), 2);
+        assert_eq!(files.list("/")?.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn files_container_stores_files_by_path() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let container = client
+            .create_files_container(XorName(rand::random()), 15_000, Scope::Private)
+            .await?;
+        let _ = run_w_backoff_delayed(|| container.list("/"), 10, 1).await?;
+
+        let content = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let item = container.add("/docs/notes.txt", content.clone()).await?;
+        assert!(matches!(item.blob, BlobAddress::Private(_)));
+
+        let _ = run_w_backoff_delayed(|| container.get("/docs/notes.txt"), 10, 1).await?;
+        container.rename("/docs", "/papers").await?;
+        let (read, read_content) =
+            run_w_backoff_delayed(|| container.get("/papers/notes.txt"), 10, 1).await?;
+        assert_eq!(read, item);
+        assert_eq!(read_content, content);
+        assert!(container.get("/docs/notes.txt").await.is_err());
+
+        // Listing files prefetches their head chunks.
+        let _ = container.list("/papers").await?;
+        let head = *item.blob.name();
+        let _ = run_w_backoff_delayed(
+            || async { client.head_chunks.get(&head).await.ok_or(Error::NoResponse) },
+            10,
+            1,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{BlobAddress, Client};
+use crate::client::Error;
+use crate::messaging::data::DataCmd;
+use crate::types::{utils::random_bytes, Chunk};
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{debug, warn};
+use xor_name::XorName;
+
+// Size of the chunk written to check writes work.
+const PROBE_CHUNK_SIZE: usize = 32;
+// The chunk written takes a moment to be stored, so reading it back is retried a few times.
+const READ_ATTEMPTS: usize = 10;
+const READ_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Part of a [`Client::health_check`], reported when it fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HealthCheckStage {
+    /// Storing a chunk.
+    Write,
+    /// Reading a chunk.
+    Read,
+}
+
+/// Timing breakdown of a successful [`Client::health_check`].
+#[derive(Clone, Debug)]
+pub struct HealthReport {
+    /// Name of the chunk which was read.
+    pub chunk_name: XorName,
+    /// Time taken to send the chunk to be stored, if writes were checked.
+    pub write: Option<Duration>,
+    /// Time taken to read the chunk, including any retries.
+    pub read: Duration,
+    /// Number of attempts it took to read the chunk.
+    pub read_attempts: usize,
+}
+
+impl Client {
+    /// Check that the client works end-to-end, e.g. at application startup.
+    ///
+    /// Unless a `canary` blob is given, a tiny chunk of random data is stored, then read back.
+    /// Read-only clients should pass the address of a blob known to be stored instead, whose
+    /// head chunk is then read. Either way, a timing breakdown is returned on success, and
+    /// [`Error::HealthCheck`] on failure, telling which part of the check failed.
+    pub async fn health_check(&self, canary: Option<BlobAddress>) -> Result<HealthReport, Error> {
+        let (chunk_name, write) = match canary {
+            Some(address) => (*address.name(), None),
+            None => {
+                let chunk = Chunk::new(random_bytes(PROBE_CHUNK_SIZE));
+                let chunk_name = *chunk.name();
+                let start = Instant::now();
+                let _ = self
+                    .send_cmd(DataCmd::StoreChunk(chunk))
+                    .await
+                    .map_err(|error| Error::HealthCheck {
+                        stage: HealthCheckStage::Write,
+                        source: Box::new(error),
+                    })?;
+                (chunk_name, Some(start.elapsed()))
+            }
+        };
+
+        let start = Instant::now();
+        let mut read_attempts = 0;
+        loop {
+            read_attempts += 1;
+            match self.read_from_network(&chunk_name).await {
+                Ok(_) => break,
+                Err(error) if read_attempts < READ_ATTEMPTS => {
+                    debug!(
+                        "Health check read of {:?} failed, retrying: {:?}",
+                        chunk_name, error
+                    );
+                    sleep(READ_RETRY_INTERVAL).await;
+                }
+                Err(error) => {
+                    warn!("Health check read of {:?} failed: {:?}", chunk_name, error);
+                    return Err(Error::HealthCheck {
+                        stage: HealthCheckStage::Read,
+                        source: Box::new(error),
+                    });
+                }
+            }
+        }
+
+        Ok(HealthReport {
+            chunk_name,
+            write,
+            read: start.elapsed(),
+            read_attempts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::utils::test_utils::create_test_client;
+    use eyre::Result;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn health_check_writes_and_reads() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let report = client.health_check(None).await?;
+        assert!(report.write.is_some());
+        assert!(report.read_attempts >= 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    blob_apis::decrypt_head_level,
+    data::{check_parts_version, SecretKey},
+    BlobAddress, Client,
+};
+use crate::client::Result;
+use crate::types::{Chunk, ChunkAddress};
+use futures::stream::{self, StreamExt};
+use self_encryption::EncryptedChunk;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{trace, warn};
+
+// Max number of chunks read at any one time when verifying a blob.
+const MAX_CONCURRENT_CHUNK_READS: usize = 8;
+
+/// Outcome of verifying the chunks of a blob are stored intact, as returned by
+/// [`Client::verify_blob`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlobVerification {
+    /// Address of the blob.
+    pub address: BlobAddress,
+    /// Size of the content of the blob, in bytes, or `None` if its head chunks, which list its
+    /// other chunks, couldn't all be read.
+    pub size: Option<usize>,
+    /// Number of distinct chunks checked, the head chunks included.
+    pub total_chunks: usize,
+    /// The chunks the network doesn't hold.
+    pub missing: BTreeSet<ChunkAddress>,
+    /// The chunks the network holds, but which couldn't be read back matching their hash.
+    pub corrupted: BTreeSet<ChunkAddress>,
+    /// The chunks which couldn't be read, and of which the network couldn't tell whether it
+    /// holds them.
+    pub unknown: BTreeSet<ChunkAddress>,
+}
+
+impl BlobVerification {
+    /// Whether all the chunks of the blob are stored intact.
+    pub fn is_intact(&self) -> bool {
+        self.size.is_some()
+            && self.missing.is_empty()
+            && self.corrupted.is_empty()
+            && self.unknown.is_empty()
+    }
+
+    // Records the outcome of verifying `chunk`, returning its content if it's intact.
+    fn record(&mut self, chunk: ChunkAddress, audit: ChunkAudit) -> Option<Chunk> {
+        let failed = match audit {
+            ChunkAudit::Intact(content) => return Some(content),
+            ChunkAudit::Missing => &mut self.missing,
+            ChunkAudit::Corrupted => &mut self.corrupted,
+            ChunkAudit::Unknown => &mut self.unknown,
+        };
+        let _ = failed.insert(chunk);
+        None
+    }
+}
+
+// Outcome of verifying a chunk.
+enum ChunkAudit {
+    Intact(Chunk),
+    Missing,
+    Corrupted,
+    Unknown,
+}
+
+impl Client {
+    /// Verify the blob at `address` is stored intact, e.g. for backup operators to audit the
+    /// data they stored, without decrypting it.
+    ///
+    /// The head chunks are read, for the chunks of the blob to be listed, then each of them is
+    /// read and checked against the hash it's listed with. Chunks are read from the network,
+    /// not from the client's caches. Those which can't be read are told apart by asking the
+    /// network whether it holds them: corrupted if it does, missing if not, unknown if it
+    /// can't tell.
+    pub async fn verify_blob(&self, address: BlobAddress) -> Result<BlobVerification> {
+        trace!("Verify blob {:?}", address);
+        let head = ChunkAddress(*address.name());
+        let mut verification = BlobVerification {
+            address,
+            size: None,
+            total_chunks: 0,
+            missing: BTreeSet::new(),
+            corrupted: BTreeSet::new(),
+            unknown: BTreeSet::new(),
+        };
+        let mut checked = BTreeSet::new();
+
+        let mut chunk = match self
+            .audit_chunks(Some(head), &mut checked, &mut verification, true)
+            .await
+            .remove(&head)
+        {
+            Some(chunk) => chunk,
+            None => return Ok(verification),
+        };
+
+        // Data maps too large for the head chunk are self-encrypted in turn, over as many
+        // levels as it takes, each of which is checked before reading the one below.
+        let parts = loop {
+            match self.head_level(address, &chunk)? {
+                SecretKey::FirstLevel(secret_key) => break vec![secret_key],
+                SecretKey::Parts { version, parts } => {
+                    check_parts_version(version)?;
+                    break parts;
+                }
+                SecretKey::Inline(data) => {
+                    verification.size = Some(data.len());
+                    return Ok(verification);
+                }
+                SecretKey::AdditionalLevel(secret_key) => {
+                    let keys = secret_key.keys();
+                    let chunks = keys.iter().map(|key| ChunkAddress(key.dst_hash));
+                    let intact = self
+                        .audit_chunks(chunks, &mut checked, &mut verification, true)
+                        .await;
+                    let mut encrypted_chunks = vec![];
+                    for key in keys {
+                        match intact.get(&ChunkAddress(key.dst_hash)) {
+                            Some(chunk) => encrypted_chunks.push(EncryptedChunk {
+                                index: key.index,
+                                content: chunk.value().clone(),
+                            }),
+                            // The chunks below can't be listed without the whole level.
+                            None => return Ok(verification),
+                        }
+                    }
+                    chunk = decrypt_head_level(&secret_key, &encrypted_chunks)?;
+                }
+            }
+        };
+        verification.size = Some(parts.iter().map(|part| part.file_size()).sum());
+
+        let chunks = parts
+            .iter()
+            .flat_map(|secret_key| secret_key.keys())
+            .map(|key| ChunkAddress(key.dst_hash));
+        let _ = self
+            .audit_chunks(chunks, &mut checked, &mut verification, false)
+            .await;
+
+        Ok(verification)
+    }
+
+    // Verifies the `chunks` not `checked` yet, a bounded number at a time, recording the
+    // outcome in `verification`. Returns the chunks found intact if `keep` is set, so the
+    // content of all the chunks of a blob isn't held at once otherwise.
+    async fn audit_chunks(
+        &self,
+        chunks: impl IntoIterator<Item = ChunkAddress>,
+        checked: &mut BTreeSet<ChunkAddress>,
+        verification: &mut BlobVerification,
+        keep: bool,
+    ) -> BTreeMap<ChunkAddress, Chunk> {
+        let chunks: Vec<_> = chunks
+            .into_iter()
+            .filter(|chunk| checked.insert(*chunk))
+            .collect();
+        verification.total_chunks = checked.len();
+
+        let mut audits = stream::iter(chunks)
+            .map(|chunk| async move { (chunk, self.audit_chunk(chunk).await) })
+            .buffer_unordered(MAX_CONCURRENT_CHUNK_READS);
+        let mut intact = BTreeMap::new();
+        while let Some((chunk, audit)) = audits.next().await {
+            if let Some(content) = verification.record(chunk, audit) {
+                if keep {
+                    let _ = intact.insert(chunk, content);
+                }
+            }
+        }
+        intact
+    }
+
+    // Reads the chunk at `address` from the network, checking it matches its hash.
+    //
+    // Responses not matching the hash are discarded as they come in, so a chunk which can't
+    // be read is deemed corrupted if the network holds it, missing otherwise.
+    async fn audit_chunk(&self, address: ChunkAddress) -> ChunkAudit {
+        match self.read_from_network(address.name()).await {
+            Ok(chunk) if chunk.name() == address.name() => return ChunkAudit::Intact(chunk),
+            Ok(_) => {
+                warn!("Chunk {:?} read doesn't match its hash", address);
+                return ChunkAudit::Corrupted;
+            }
+            Err(error) => trace!("Couldn't read chunk {:?}: {}", address, error),
+        }
+        match self.chunk_exists(address).await {
+            Ok(true) => {
+                warn!("Chunk {:?} is stored, but couldn't be read intact", address);
+                ChunkAudit::Corrupted
+            }
+            Ok(false) => ChunkAudit::Missing,
+            Err(error) => {
+                warn!("Couldn't check chunk {:?} exists: {}", address, error);
+                ChunkAudit::Unknown
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::client_api::BlobAddress;
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::types::{utils::random_bytes, ChunkAddress};
+    use crate::url::Scope;
+    use eyre::Result;
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stored_blobs_are_verified_intact() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let size = 5 * 1024 * 1024;
+        let address = client
+            .write_to_network(random_bytes(size), Scope::Private)
+            .await?;
+        let _ = run_w_backoff_delayed(|| client.read_blob(address), 10, 1).await?;
+        let verification = client.verify_blob(address).await?;
+        assert!(verification.is_intact());
+        assert!(verification.unknown.is_empty());
+        assert_eq!(verification.size, Some(size));
+        assert!(verification.total_chunks > 1);
+
+        let missing = BlobAddress::Public(XorName::random());
+        let verification = client.verify_blob(missing).await?;
+        assert!(!verification.is_intact());
+        assert_eq!(verification.size, None);
+        assert!(verification
+            .missing
+            .contains(&ChunkAddress(*missing.name())));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+use tokio::sync::{broadcast, RwLock};
+
+// Number of most recent operations of each kind compliance is tracked over.
+const WINDOW_SIZE: usize = 20;
+// Number of operations, within the window, missing their objective for it to be deemed degraded.
+const DEGRADED_MISSES: usize = WINDOW_SIZE / 2;
+// Number of operations, within the window, missing their objective for it to be deemed recovered.
+const RECOVERED_MISSES: usize = WINDOW_SIZE / 10;
+// Number of latency events kept for subscribers lagging behind.
+const EVENT_CHANNEL_CAPACITY: usize = 16;
+
+/// How long the client's operations are expected to take when the network is healthy.
+///
+/// Operations failing, or taking longer than their objective, count as missing it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct LatencyObjectives {
+    /// Objective for reads, i.e. from sending a query to getting its response.
+    pub reads: Option<Duration>,
+    /// Objective for writes, i.e. from sending a command to having it delivered to the Elders.
+    pub writes: Option<Duration>,
+}
+
+/// Kind of operation a latency objective applies to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum OperationKind {
+    /// Queries.
+    Read,
+    /// Commands.
+    Write,
+}
+
+/// Change in the client's compliance with a latency objective.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LatencyEvent {
+    /// The network has been consistently missing the objective for this kind of operation,
+    /// e.g. so apps can switch to a degraded mode.
+    Degraded {
+        /// Kind of operation missing its objective.
+        kind: OperationKind,
+        /// The objective missed.
+        objective: Duration,
+        /// Number of the most recent operations of this kind which missed it.
+        missed: usize,
+        /// Number of the most recent operations of this kind considered.
+        out_of: usize,
+    },
+    /// The network is meeting the objective for this kind of operation again.
+    Recovered {
+        /// Kind of operation meeting its objective again.
+        kind: OperationKind,
+        /// The objective met.
+        objective: Duration,
+    },
+}
+
+/// Tracks whether operations meet their latency objective, notifying subscribers when
+/// the network starts, or stops, consistently missing it.
+#[derive(Clone, Debug)]
+pub(super) struct LatencyTracker {
+    objectives: LatencyObjectives,
+    reads: Arc<RwLock<Compliance>>,
+    writes: Arc<RwLock<Compliance>>,
+    event_sender: broadcast::Sender<LatencyEvent>,
+}
+
+// Whether each of the most recent operations of a kind met its objective.
+#[derive(Debug, Default)]
+struct Compliance {
+    window: VecDeque<bool>,
+    degraded: bool,
+}
+
+impl Compliance {
+    // Records whether an operation met its objective, returning whether this
+    // changed the kind of operation from compliant to degraded or the other way round.
+    fn record(&mut self, met: bool) -> bool {
+        if self.window.len() == WINDOW_SIZE {
+            let _ = self.window.pop_front();
+        }
+        self.window.push_back(met);
+
+        let misses = self.misses();
+        if !self.degraded && misses >= DEGRADED_MISSES {
+            self.degraded = true;
+            true
+        } else if self.degraded && self.window.len() == WINDOW_SIZE && misses <= RECOVERED_MISSES {
+            self.degraded = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn misses(&self) -> usize {
+        self.window.iter().filter(|met| !**met).count()
+    }
+}
+
+impl LatencyTracker {
+    pub(super) fn new(objectives: LatencyObjectives) -> Self {
+        Self {
+            objectives,
+            reads: Arc::new(RwLock::new(Compliance::default())),
+            writes: Arc::new(RwLock::new(Compliance::default())),
+            event_sender: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Records an operation of the given kind which took `elapsed`, or failed if `None`.
+    pub(super) async fn record(&self, kind: OperationKind, elapsed: Option<Duration>) {
+        let (objective, compliance) = match kind {
+            OperationKind::Read => (self.objectives.reads, &self.reads),
+            OperationKind::Write => (self.objectives.writes, &self.writes),
+        };
+        let objective = match objective {
+            Some(objective) => objective,
+            None => return,
+        };
+
+        let met = elapsed.map(|elapsed| elapsed <= objective).unwrap_or(false);
+        let mut compliance = compliance.write().await;
+        if !compliance.record(met) {
+            return;
+        }
+
+        let event = if compliance.degraded {
+            warn!(
+                "{:?} operations are missing their {:?} objective",
+                kind, objective
+            );
+            LatencyEvent::Degraded {
+                kind,
+                objective,
+                missed: compliance.misses(),
+                out_of: compliance.window.len(),
+            }
+        } else {
+            info!(
+                "{:?} operations are meeting their {:?} objective again",
+                kind, objective
+            );
+            LatencyEvent::Recovered { kind, objective }
+        };
+        // Nobody listening is fine, the app just isn't interested.
+        let _ = self.event_sender.send(event);
+    }
+
+    /// Subscribes to changes in compliance with the latency objectives.
+    pub(super) fn subscribe(&self) -> broadcast::Receiver<LatencyEvent> {
+        self.event_sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LatencyEvent, LatencyObjectives, LatencyTracker, OperationKind, WINDOW_SIZE};
+    use eyre::Result;
+    use std::time::Duration;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn degrades_and_recovers_once_each() -> Result<()> {
+        let objective = Duration::from_secs(2);
+        let tracker = LatencyTracker::new(LatencyObjectives {
+            reads: Some(objective),
+            writes: None,
+        });
+        let mut events = tracker.subscribe();
+
+        let slow = Some(Duration::from_secs(3));
+        let fast = Some(Duration::from_millis(500));
+        for _ in 0..WINDOW_SIZE {
+            tracker.record(OperationKind::Read, slow).await;
+            tracker.record(OperationKind::Write, None).await;
+        }
+        assert_eq!(
+            events.try_recv()?,
+            LatencyEvent::Degraded {
+                kind: OperationKind::Read,
+                objective,
+                missed: WINDOW_SIZE / 2,
+                out_of: WINDOW_SIZE / 2,
+            }
+        );
+        assert!(events.try_recv().is_err());
+
+        for _ in 0..WINDOW_SIZE {
+            tracker.record(OperationKind::Read, fast).await;
+        }
+        assert_eq!(
+            events.try_recv()?,
+            LatencyEvent::Recovered {
+                kind: OperationKind::Read,
+                objective,
+            }
+        );
+        assert!(events.try_recv().is_err());
+
+        Ok(())
+    }
+}
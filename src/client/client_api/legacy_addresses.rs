@@ -0,0 +1,209 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{data::part_ranges, BlobAddress, Client};
+use crate::client::{Error, Result};
+use crate::types::{utils, MAX_CHUNK_SIZE_IN_BYTES};
+use crate::url::{ContentType, DataType, Scope, Url, XorUrlBase};
+use self_encryption::SecretKey as BlobSecretKey;
+use tracing::info;
+
+/// The formats blob addresses have been encoded in, from the oldest.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum BlobAddressFormat {
+    /// The address serialised with bincode, as applications stored it before addresses had a
+    /// format of their own.
+    Serialised,
+    /// The serialised address encoded in z-base-32, as other addresses are by `encode_to_zbase32`.
+    ZBase32,
+    /// A XOR-URL: the current format.
+    Url,
+}
+
+impl BlobAddressFormat {
+    /// Whether addresses are no longer encoded in this format.
+    pub fn is_legacy(self) -> bool {
+        self != Self::Url
+    }
+}
+
+/// A blob address parsed from any of the formats it's ever been encoded in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParsedBlobAddress {
+    /// The address.
+    pub address: BlobAddress,
+    /// The format it was encoded in.
+    pub format: BlobAddressFormat,
+}
+
+impl BlobAddress {
+    /// Encodes the address in the current format, as a XOR-URL.
+    pub fn encode(&self) -> Result<String> {
+        Ok(Url::encode_blob(
+            *self.name(),
+            self.scope(),
+            ContentType::Raw,
+            XorUrlBase::Base32z,
+        )?)
+    }
+
+    /// Parses an address encoded in any of the formats in [`BlobAddressFormat`], so links stored
+    /// in older formats keep working. Use [`Client::migrate_blob_address`] to upgrade them.
+    pub fn parse(encoded: &str) -> Result<ParsedBlobAddress> {
+        let encoded = encoded.trim();
+        if encoded.starts_with("safe://") {
+            let url = Url::from_xorurl(encoded)?;
+            if url.data_type() != DataType::Blob {
+                return Err(Error::InvalidBlobAddress(format!(
+                    "{} points to a {}, not a blob",
+                    encoded,
+                    url.data_type()
+                )));
+            }
+            let address = match url.scope() {
+                Scope::Public => BlobAddress::Public(url.xorname()),
+                Scope::Private => BlobAddress::Private(url.xorname()),
+            };
+            return Ok(ParsedBlobAddress {
+                address,
+                format: BlobAddressFormat::Url,
+            });
+        }
+
+        let address = utils::decode(encoded).map_err(|error| {
+            Error::InvalidBlobAddress(format!("{} isn't a blob address: {}", encoded, error))
+        })?;
+        Ok(ParsedBlobAddress {
+            address,
+            format: BlobAddressFormat::ZBase32,
+        })
+    }
+
+    /// Parses an address serialised with bincode, as per [`BlobAddressFormat::Serialised`].
+    pub fn from_serialised(bytes: &[u8]) -> Result<ParsedBlobAddress> {
+        let address = bincode::deserialize(bytes).map_err(|error| {
+            Error::InvalidBlobAddress(format!("not a serialised blob address: {}", error))
+        })?;
+        Ok(ParsedBlobAddress {
+            address,
+            format: BlobAddressFormat::Serialised,
+        })
+    }
+}
+
+/// The outcome of migrating a blob address with [`Client::migrate_blob_address`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MigratedBlobAddress {
+    /// The address of the blob as it's stored now.
+    pub address: BlobAddress,
+    /// The address, encoded in the current format.
+    pub encoded: String,
+    /// The format the address was encoded in.
+    pub from_format: BlobAddressFormat,
+    /// Whether the blob had to be stored again, as it was stored in a layout it isn't anymore,
+    /// getting a new address.
+    pub reuploaded: bool,
+}
+
+impl MigratedBlobAddress {
+    /// Whether links to the blob should be replaced by `encoded`.
+    pub fn changed(&self) -> bool {
+        self.reuploaded || self.from_format.is_legacy()
+    }
+}
+
+impl Client {
+    /// Re-derive the current-format address of a blob from one encoded in any format it's ever
+    /// been, as per [`BlobAddress::parse`].
+    ///
+    /// Blobs stored in a layout they no longer would be, i.e. in parts split up otherwise than
+    /// [`Client::write_stream`] splits data up, are read and stored again, their address being
+    /// the one they'd get if streamed now. They're streamed through, rather than held in memory.
+    /// Blobs self-encrypted whole are left as they are, whatever their size. If reading them fails partway through, the chunks already stored
+    /// again are left behind, unreachable.
+    pub async fn migrate_blob_address(&self, encoded: &str) -> Result<MigratedBlobAddress> {
+        let ParsedBlobAddress { address, format } = BlobAddress::parse(encoded)?;
+
+        let parts = self.blob_parts(address).await?;
+        let (address, reuploaded) = if is_current_layout(&parts) {
+            (address, false)
+        } else {
+            info!("Storing blob at {:?} again in the current layout", address);
+            (self.restore_blob(address).await?, true)
+        };
+
+        Ok(MigratedBlobAddress {
+            address,
+            encoded: address.encode()?,
+            from_format: format,
+            reuploaded,
+        })
+    }
+
+    // Stores a blob again, streaming its content from where it's stored now.
+    async fn restore_blob(&self, address: BlobAddress) -> Result<BlobAddress> {
+        let (sink, source) = tokio::io::duplex(MAX_CHUNK_SIZE_IN_BYTES);
+        let (read, written) = tokio::join!(
+            async move {
+                let mut sink = sink;
+                // Dropping the sink once done lets the upload know it's read everything.
+                self.read_blob_to(address, &mut sink).await
+            },
+            self.write_stream(source, address.scope())
+        );
+        let _ = read?;
+        written
+    }
+}
+
+// Whether the blob is self-encrypted whole, or split up into parts as it would be if streamed now.
+fn is_current_layout(parts: &[BlobSecretKey]) -> bool {
+    // Content held inline, in the head chunk, has no parts, and content encrypted whole one.
+    if parts.len() <= 1 {
+        return true;
+    }
+    let size: usize = parts.iter().map(BlobSecretKey::file_size).sum();
+    let ranges = part_ranges(size);
+    ranges.len() == parts.len()
+        && ranges
+            .iter()
+            .zip(parts)
+            .all(|(range, part)| range.len() == part.file_size())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlobAddress, BlobAddressFormat};
+    use crate::types::utils;
+    use eyre::Result;
+    use xor_name::XorName;
+
+    #[test]
+    fn legacy_formats_are_parsed() -> Result<()> {
+        for address in vec![
+            BlobAddress::Public(XorName::random()),
+            BlobAddress::Private(XorName::random()),
+        ] {
+            let url = BlobAddress::parse(&address.encode()?)?;
+            assert_eq!(url.address, address);
+            assert_eq!(url.format, BlobAddressFormat::Url);
+
+            let zbase32 = BlobAddress::parse(&utils::encode(&address)?)?;
+            assert_eq!(zbase32.address, address);
+            assert!(zbase32.format.is_legacy());
+
+            let serialised = BlobAddress::from_serialised(&bincode::serialize(&address)?)?;
+            assert_eq!(serialised.address, address);
+            assert_eq!(serialised.format, BlobAddressFormat::Serialised);
+        }
+
+        assert!(BlobAddress::parse("not an address").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::{Error, Result};
+use crate::messaging::data::{DataLimits, DataQuery, QueryResponse};
+use crate::types::register::Entry;
+use std::sync::PoisonError;
+use tracing::{trace, warn};
+use xor_name::XorName;
+
+impl Client {
+    /// Retrieve the limits on the size of the data the network accepts, as advertised by
+    /// the Elders of the client's section.
+    ///
+    /// They're retrieved once, then kept for the lifetime of the client.
+    pub async fn data_limits(&self) -> Result<DataLimits> {
+        if let Some(limits) = *self
+            .data_limits
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            return Ok(limits);
+        }
+
+        trace!("Get data limits of the network");
+        let query = DataQuery::GetDataLimits(XorName::from(self.public_key()));
+        let query_result = self.send_query(query).await?;
+        let limits = match query_result.response {
+            QueryResponse::GetDataLimits((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })?
+            }
+            _ => return Err(Error::ReceivedUnexpectedEvent),
+        };
+
+        *self
+            .data_limits
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(limits);
+        Ok(limits)
+    }
+
+    // Limits to check data against before uploading it: the network's,
+    // or the defaults if the network couldn't tell.
+    pub(super) async fn upload_limits(&self) -> DataLimits {
+        self.data_limits().await.unwrap_or_else(|error| {
+            warn!("Couldn't get the data limits of the network: {}", error);
+            DataLimits::default()
+        })
+    }
+
+    // Limits known without asking the network, for checks which can't wait for it.
+    pub(super) fn known_upload_limits(&self) -> DataLimits {
+        self.data_limits
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .unwrap_or_default()
+    }
+}
+
+/// Refuses blobs larger than the network accepts, before they're self-encrypted.
+pub(super) fn check_blob_size(size: usize, limits: &DataLimits) -> Result<()> {
+    check_size(size as u64, limits.max_blob_size)
+}
+
+/// Refuses Register entries larger than the network accepts.
+pub(super) fn check_entry_size(entry: &Entry, limits: &DataLimits) -> Result<()> {
+    check_size(
+        bincode::serialized_size(entry)?,
+        limits.max_register_entry_size,
+    )
+}
+
+/// Refuses batches of Register edits with more operations than the network accepts.
+pub(super) fn check_batch_len(len: usize, limits: &DataLimits) -> Result<()> {
+    let actual = len as u64;
+    if actual > limits.max_register_batch_ops {
+        return Err(Error::TooManyOps {
+            max: limits.max_register_batch_ops,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+fn check_size(actual: u64, max: u64) -> Result<()> {
+    if actual > max {
+        return Err(Error::DataTooLarge { max, actual });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_batch_len, check_blob_size, check_entry_size};
+    use crate::client::Error;
+    use crate::messaging::data::DataLimits;
+    use crate::url::{ContentType, Scope, Url, XorUrlBase};
+    use eyre::Result;
+    use xor_name::XorName;
+
+    #[test]
+    fn refuses_oversized_data() -> Result<()> {
+        let limits = DataLimits {
+            max_blob_size: 1024,
+            max_register_entry_size: 16,
+            max_register_batch_ops: 2,
+        };
+
+        check_blob_size(1024, &limits)?;
+        assert!(matches!(
+            check_blob_size(1025, &limits),
+            Err(Error::DataTooLarge {
+                max: 1024,
+                actual: 1025
+            })
+        ));
+
+        let url = Url::encode_blob(
+            XorName::random(),
+            Scope::Public,
+            ContentType::Raw,
+            XorUrlBase::Base32z,
+        )?;
+        let entry = Url::from_url(&url)?;
+        assert!(matches!(
+            check_entry_size(&entry, &limits),
+            Err(Error::DataTooLarge { max: 16, .. })
+        ));
+        check_entry_size(&entry, &DataLimits::default())?;
+
+        check_batch_len(2, &limits)?;
+        assert!(matches!(
+            check_batch_len(3, &limits),
+            Err(Error::TooManyOps { max: 2, actual: 3 })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{BlobAddress, Client};
+use crate::client::Result;
+use crate::types::{Chunk, ChunkAddress, JsonRepr};
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::{trace, warn};
+
+// Max number of chunks read at any one time when exporting or verifying a manifest.
+const MAX_CONCURRENT_CHUNK_READS: usize = 8;
+
+/// A ciphertext chunk of a blob, as listed in its manifest.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ManifestChunk {
+    /// Address of the chunk, which is the hash of its ciphertext.
+    pub address: ChunkAddress,
+    /// Size of the ciphertext, in bytes.
+    pub size: usize,
+}
+
+impl ManifestChunk {
+    pub(super) fn of(chunk: &Chunk) -> Self {
+        Self {
+            address: *chunk.address(),
+            size: chunk.payload_size(),
+        }
+    }
+
+    /// Checks a chunk read from the network against the manifest.
+    ///
+    /// A chunk's address is always derived from its content, including upon deserialisation,
+    /// so one read from its address matches its hash.
+    pub fn check(&self, chunk: &Chunk) -> ChunkCheck {
+        if chunk.address() != &self.address {
+            ChunkCheck::Missing
+        } else if chunk.payload_size() != self.size {
+            ChunkCheck::SizeMismatch {
+                actual: chunk.payload_size(),
+            }
+        } else {
+            ChunkCheck::Verified
+        }
+    }
+}
+
+/// The ciphertext chunks a blob is stored as, for anyone to verify they are on the network
+/// without being able to decrypt them, e.g. an auditor of archived or escrowed data.
+///
+/// Chunks are content-addressed, so one read from the network from the address listed is
+/// known to be the very ciphertext the manifest was exported for.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BlobManifest {
+    /// Blob the manifest was exported for.
+    pub blob: BlobAddress,
+    /// The head chunk of the blob, whose address is the blob's name.
+    pub head: ManifestChunk,
+    /// The chunks the content of the blob is stored in, none if it's held inline in its head.
+    pub chunks: Vec<ManifestChunk>,
+}
+
+impl BlobManifest {
+    /// Returns the chunk listed at `address`, if any.
+    pub fn get(&self, address: &ChunkAddress) -> Option<&ManifestChunk> {
+        self.all().find(|chunk| &chunk.address == address)
+    }
+
+    /// Total size of the chunks listed, in bytes.
+    pub fn total_size(&self) -> usize {
+        self.all().map(|chunk| chunk.size).sum()
+    }
+
+    /// The head chunk, followed by the others.
+    pub fn all(&self) -> impl Iterator<Item = &ManifestChunk> {
+        std::iter::once(&self.head).chain(self.chunks.iter())
+    }
+}
+
+impl JsonRepr for BlobManifest {
+    const JSON_TYPE: &'static str = "BlobManifest";
+}
+
+/// Outcome of checking a chunk against a [`BlobManifest`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChunkCheck {
+    /// The chunk is on the network, as listed.
+    Verified,
+    /// The chunk couldn't be read from the network.
+    Missing,
+    /// The chunk read from the network isn't of the size listed.
+    SizeMismatch {
+        /// Size of the chunk read, in bytes.
+        actual: usize,
+    },
+    /// The chunk isn't listed in the manifest.
+    NotInManifest,
+}
+
+/// Outcome of checking chunks against a [`BlobManifest`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManifestVerification {
+    /// Outcome of the check of each chunk, by address.
+    pub checks: BTreeMap<ChunkAddress, ChunkCheck>,
+}
+
+impl ManifestVerification {
+    /// Whether every chunk checked is on the network, as listed.
+    pub fn is_verified(&self) -> bool {
+        self.checks
+            .values()
+            .all(|check| *check == ChunkCheck::Verified)
+    }
+
+    /// The chunks which didn't check out, by address.
+    pub fn failures(&self) -> impl Iterator<Item = (&ChunkAddress, &ChunkCheck)> {
+        self.checks
+            .iter()
+            .filter(|(_, check)| **check != ChunkCheck::Verified)
+    }
+}
+
+impl Client {
+    /// Export the manifest of the blob at `address`, listing the ciphertext chunks it's stored
+    /// as, for a third party without read access to it to verify they are on the network.
+    ///
+    /// Every chunk of the blob is read, for its size to be listed.
+    pub async fn export_blob_manifest(&self, address: BlobAddress) -> Result<BlobManifest> {
+        trace!("Export manifest of blob {:?}", address);
+        let mut chunks: Vec<_> = stream::iter(self.chunk_addresses(address).await?)
+            .map(|chunk| async move { self.read_chunk(chunk.name()).await })
+            .buffered(MAX_CONCURRENT_CHUNK_READS)
+            .map_ok(|chunk| ManifestChunk::of(&chunk))
+            .try_collect()
+            .await?;
+        // The head chunk always comes first.
+        let head = chunks.remove(0);
+
+        Ok(BlobManifest {
+            blob: address,
+            head,
+            chunks,
+        })
+    }
+
+    /// Check every chunk listed in `manifest` is on the network, as listed.
+    pub async fn verify_blob_manifest(
+        &self,
+        manifest: &BlobManifest,
+    ) -> Result<ManifestVerification> {
+        let chunks: Vec<_> = manifest.all().map(|chunk| chunk.address).collect();
+        self.verify_manifest_chunks(manifest, chunks).await
+    }
+
+    /// Check the given chunks, e.g. a random sample of a large blob's, are on the network, as
+    /// listed in `manifest`. Neither the blob, nor access to it, is needed.
+    pub async fn verify_manifest_chunks(
+        &self,
+        manifest: &BlobManifest,
+        chunks: impl IntoIterator<Item = ChunkAddress>,
+    ) -> Result<ManifestVerification> {
+        let checks = stream::iter(chunks)
+            .map(|address| async move {
+                let listed = match manifest.get(&address) {
+                    Some(listed) => listed,
+                    None => return (address, ChunkCheck::NotInManifest),
+                };
+                let check = match self.read_chunk(address.name()).await {
+                    Ok(chunk) => listed.check(&chunk),
+                    Err(error) => {
+                        warn!("Couldn't read chunk {:?} of manifest: {}", address, error);
+                        ChunkCheck::Missing
+                    }
+                };
+                (address, check)
+            })
+            .buffer_unordered(MAX_CONCURRENT_CHUNK_READS)
+            .collect()
+            .await;
+
+        Ok(ManifestVerification { checks })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlobManifest, ChunkCheck};
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::types::{utils::random_bytes, ChunkAddress, JsonRepr};
+    use crate::url::Scope;
+    use eyre::Result;
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn manifest_is_verified_without_read_access() -> Result<()> {
+        let owner = create_test_client(None).await?;
+        let address = owner
+            .write_to_network(random_bytes(5 * 1024 * 1024), Scope::Private)
+            .await?;
+        let manifest = run_w_backoff_delayed(|| owner.export_blob_manifest(address), 10, 1).await?;
+        assert!(!manifest.chunks.is_empty());
+        assert_eq!(manifest.head.address.name(), address.name());
+
+        // The auditor only gets the manifest, as JSON.
+        let auditor = create_test_client(None).await?;
+        let manifest = BlobManifest::from_json(&manifest.to_json()?)?;
+        assert!(auditor.read_blob(address).await.is_err());
+        let verification = auditor.verify_blob_manifest(&manifest).await?;
+        assert!(verification.is_verified());
+        assert_eq!(verification.checks.len(), manifest.chunks.len() + 1);
+
+        let mut tampered = manifest.clone();
+        tampered.chunks[0].size += 1;
+        let unknown = ChunkAddress(XorName::random());
+        let verification = auditor
+            .verify_manifest_chunks(&tampered, vec![tampered.chunks[0].address, unknown])
+            .await?;
+        assert!(!verification.is_verified());
+        assert_eq!(
+            verification.checks[&tampered.chunks[0].address],
+            ChunkCheck::SizeMismatch {
+                actual: manifest.chunks[0].size
+            }
+        );
+        assert_eq!(verification.checks[&unknown], ChunkCheck::NotInManifest);
+
+        Ok(())
+    }
+}
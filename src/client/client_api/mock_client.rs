@@ -0,0 +1,348 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    limits_apis::{check_blob_size, check_entry_size},
+    upload_report::encrypt,
+    BlobAddress, SafeClient,
+};
+use crate::client::{DefaultEncryptionProvider, EncryptionProvider, Error, ErrorMessage, Result};
+use crate::messaging::data::{DataLimits, OperationId};
+use crate::types::{
+    register::{
+        Action, Address, Entry, EntryHash, PrivatePermissions, PrivatePolicy, PublicPermissions,
+        PublicPolicy, Register, User,
+    },
+    ChunkAddress, DataAddress, Error as DtError, Keypair, PublicKey,
+};
+use crate::url::Scope;
+use bytes::Bytes;
+use futures::future::{self, BoxFuture, FutureExt};
+use rand::rngs::OsRng;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, PoisonError, RwLock},
+};
+use xor_name::XorName;
+
+/// An in-memory stand-in for a [`Client`](super::Client), for unit testing code written
+/// against [`SafeClient`] without a network, or building and demoing applications offline.
+///
+/// Data is kept for as long as any clone of the mock is around. Mocks made with
+/// [`MockClient::with_keypair`] from the same mock share its data, acting as different users.
+/// Blobs are self-encrypted as they are for the network, so they get the same addresses, and
+/// private ones can only be read by the user who wrote them. Registers are the network's own,
+/// merging concurrent writes the same way. Applications holding an `Arc<dyn SafeClient>` move
+/// to the network by being handed a `Client` instead.
+#[derive(Clone, Debug)]
+pub struct MockClient {
+    keypair: Keypair,
+    // The content of each blob, with the key of its owner if it's private.
+    blobs: Arc<RwLock<HashMap<BlobAddress, (Bytes, Option<PublicKey>)>>>,
+    registers: Arc<RwLock<BTreeMap<Address, Register>>>,
+}
+
+impl Default for MockClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClient {
+    /// Create an empty mock, with a random keypair.
+    pub fn new() -> Self {
+        Self {
+            keypair: Keypair::new_ed25519(&mut OsRng),
+            blobs: Arc::default(),
+            registers: Arc::default(),
+        }
+    }
+
+    /// Return a mock sharing this one's data, whose operations are signed with `keypair`.
+    pub fn with_keypair(&self, keypair: Keypair) -> Self {
+        let mut client = self.clone();
+        client.keypair = keypair;
+        client
+    }
+
+    fn store_register(&self, register: Register) -> Result<Address> {
+        let address = *register.address();
+        let mut registers = self
+            .registers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if registers.contains_key(&address) {
+            return Err(network_error(ErrorMessage::DataExists, &address));
+        }
+        let _ = registers.insert(address, register);
+        Ok(address)
+    }
+
+    fn register(&self, address: Address) -> Result<Register> {
+        let registers = self
+            .registers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        let register = registers.get(&address).ok_or_else(|| {
+            network_error(
+                ErrorMessage::DataNotFound(DataAddress::Register(address)),
+                &address,
+            )
+        })?;
+        register.check_permissions(Action::Read, Some(self.keypair.public_key()))?;
+        Ok(register.clone())
+    }
+
+    fn write_register(
+        &self,
+        address: Address,
+        entry: Entry,
+        children: BTreeSet<EntryHash>,
+    ) -> Result<EntryHash> {
+        check_entry_size(&entry, &DataLimits::default())?;
+        let mut registers = self
+            .registers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let register = registers.get_mut(&address).ok_or_else(|| {
+            network_error(
+                ErrorMessage::DataNotFound(DataAddress::Register(address)),
+                &address,
+            )
+        })?;
+        register.check_permissions(Action::Write, Some(self.keypair.public_key()))?;
+        let (hash, _) = register.write(entry, children)?;
+        Ok(hash)
+    }
+
+    fn remove_register(&self, address: Address) -> Result<()> {
+        let mut registers = self
+            .registers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let register = registers.get(&address).ok_or_else(|| {
+            network_error(
+                ErrorMessage::DataNotFound(DataAddress::Register(address)),
+                &address,
+            )
+        })?;
+        if register.is_public() {
+            return Err(DtError::InvalidOperation.into());
+        }
+        if register.owner() != self.keypair.public_key() {
+            return Err(DtError::AccessDenied(self.keypair.public_key()).into());
+        }
+        let _ = registers.remove(&address);
+        Ok(())
+    }
+
+    fn store_blob(&self, data: Bytes, scope: Scope) -> Result<BlobAddress> {
+        check_blob_size(data.len(), &DataLimits::default())?;
+        let owner = self.public_key();
+        let encryption = DefaultEncryptionProvider.encryption(scope, owner);
+        let (address, _) = encrypt(data.clone(), encryption)?;
+        let owner = match scope {
+            Scope::Public => None,
+            Scope::Private => Some(owner),
+        };
+        let mut blobs = self.blobs.write().unwrap_or_else(PoisonError::into_inner);
+        let _ = blobs.insert(address, (data, owner));
+        Ok(address)
+    }
+
+    fn blob(&self, address: BlobAddress) -> Result<Bytes> {
+        let blobs = self.blobs.read().unwrap_or_else(PoisonError::into_inner);
+        match blobs.get(&address) {
+            Some((data, None)) => Ok(data.clone()),
+            Some((data, Some(owner))) if *owner == self.public_key() => Ok(data.clone()),
+            Some((_, Some(_))) => Err(DtError::AccessDenied(self.public_key()).into()),
+            None => Err(network_error(
+                ErrorMessage::DataNotFound(DataAddress::Chunk(ChunkAddress(*address.name()))),
+                &address,
+            )),
+        }
+    }
+}
+
+// Builds the error the network would have responded with.
+fn network_error(source: ErrorMessage, address: &impl std::fmt::Debug) -> Error {
+    let op_id: OperationId = format!("{:?}", address);
+    Error::ErrorMessage { source, op_id }
+}
+
+impl SafeClient for MockClient {
+    fn public_key(&self) -> PublicKey {
+        self.keypair.public_key()
+    }
+
+    fn write_to_network(&self, data: Bytes, scope: Scope) -> BoxFuture<'_, Result<BlobAddress>> {
+        future::ready(self.store_blob(data, scope)).boxed()
+    }
+
+    fn read_blob(&self, address: BlobAddress) -> BoxFuture<'_, Result<Bytes>> {
+        future::ready(self.blob(address)).boxed()
+    }
+
+    fn read_blob_from(
+        &self,
+        address: BlobAddress,
+        position: usize,
+        length: usize,
+    ) -> BoxFuture<'_, Result<Bytes>> {
+        let result = self.blob(address).map(|data| {
+            let start = position.min(data.len());
+            let end = position.saturating_add(length).min(data.len());
+            data.slice(start..end)
+        });
+        future::ready(result).boxed()
+    }
+
+    fn store_private_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        owner: PublicKey,
+        permissions: BTreeMap<PublicKey, PrivatePermissions>,
+    ) -> BoxFuture<'_, Result<Address>> {
+        let policy = PrivatePolicy { owner, permissions };
+        let register = Register::new_private(self.public_key(), name, tag, Some(policy));
+        future::ready(self.store_register(register)).boxed()
+    }
+
+    fn store_public_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        owner: PublicKey,
+        permissions: BTreeMap<User, PublicPermissions>,
+    ) -> BoxFuture<'_, Result<Address>> {
+        let policy = PublicPolicy { owner, permissions };
+        let register = Register::new_public(self.public_key(), name, tag, Some(policy));
+        future::ready(self.store_register(register)).boxed()
+    }
+
+    fn delete_register(&self, address: Address) -> BoxFuture<'_, Result<()>> {
+        future::ready(self.remove_register(address)).boxed()
+    }
+
+    fn write_to_register(
+        &self,
+        address: Address,
+        entry: Entry,
+        children: BTreeSet<EntryHash>,
+    ) -> BoxFuture<'_, Result<EntryHash>> {
+        future::ready(self.write_register(address, entry, children)).boxed()
+    }
+
+    fn get_register(&self, address: Address) -> BoxFuture<'_, Result<Register>> {
+        future::ready(self.register(address)).boxed()
+    }
+
+    fn read_register(
+        &self,
+        address: Address,
+    ) -> BoxFuture<'_, Result<BTreeSet<(EntryHash, Entry)>>> {
+        let result = self
+            .register(address)
+            .and_then(|register| Ok(register.read(Some(self.public_key()))?));
+        future::ready(result).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encrypt, BlobAddress, MockClient};
+    use crate::client::client_api::SafeClient;
+    use crate::client::{DefaultEncryptionProvider, EncryptionProvider, Error, ErrorMessage};
+    use crate::types::{
+        register::{PublicPermissions, User},
+        Keypair,
+    };
+    use crate::url::{ContentType, Scope, Url, XorUrlBase};
+    use bytes::Bytes;
+    use eyre::Result;
+    use rand::rngs::OsRng;
+    use std::collections::{BTreeMap, BTreeSet};
+    use xor_name::XorName;
+
+    // Business logic written against the trait, as downstream applications would.
+    async fn publish(client: &dyn SafeClient, content: Bytes) -> Result<BlobAddress> {
+        Ok(client.write_to_network(content, Scope::Public).await?)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn behaves_like_the_network() -> Result<()> {
+        let client = MockClient::new();
+
+        let content = Bytes::from("hello");
+        let address = publish(&client, content.clone()).await?;
+        assert_eq!(client.read_blob(address).await?, content);
+        assert_eq!(client.read_blob_from(address, 1, 3).await?, "ell");
+
+        let owner = client.public_key();
+        let mut perms = BTreeMap::new();
+        let _ = perms.insert(User::Key(owner), PublicPermissions::new(true));
+        let address = client
+            .store_public_register(XorName::random(), 15000, owner, perms)
+            .await?;
+
+        let entry = Url::from_url(&Url::encode_blob(
+            XorName::random(),
+            Scope::Public,
+            ContentType::Raw,
+            XorUrlBase::Base32z,
+        )?)?;
+        let hash = client
+            .write_to_register(address, entry.clone(), BTreeSet::new())
+            .await?;
+        assert_eq!(
+            client.read_register(address).await?,
+            vec![(hash, entry.clone())].into_iter().collect()
+        );
+
+        // Others aren't allowed to write to it.
+        let other = client.with_keypair(Keypair::new_ed25519(&mut OsRng));
+        assert!(other
+            .write_to_register(address, entry, BTreeSet::new())
+            .await
+            .is_err());
+
+        assert!(matches!(
+            client
+                .read_blob(BlobAddress::Public(XorName::random()))
+                .await,
+            Err(Error::ErrorMessage {
+                source: ErrorMessage::DataNotFound(_),
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn blobs_are_addressed_and_owned_as_on_the_network() -> Result<()> {
+        let client = MockClient::new();
+        let other = client.with_keypair(Keypair::new_ed25519(&mut OsRng));
+        let content = Bytes::from("hello");
+
+        for scope in [Scope::Public, Scope::Private].iter().copied() {
+            let address = client.write_to_network(content.clone(), scope).await?;
+            let encryption = DefaultEncryptionProvider.encryption(scope, client.public_key());
+            let (expected, _) = encrypt(content.clone(), encryption)?;
+            assert_eq!(address, expected);
+            assert_eq!(client.read_blob(address).await?, content);
+            assert_eq!(
+                other.read_blob(address).await.is_ok(),
+                scope == Scope::Public
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -6,45 +6,157 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod archive_apis;
+mod batch_upload;
 mod blob_apis;
+mod catalog_apis;
+mod chunk_cache;
 mod commands;
-mod data;
+pub(super) mod data;
+mod existence_apis;
+mod fan_out_apis;
+mod fetch_apis;
+mod file_history;
+mod files_container;
+mod health_apis;
+mod integrity_apis;
+mod latency;
+mod legacy_addresses;
+mod limits_apis;
+mod manifest_apis;
+mod mock_client;
+mod multimap;
+mod nrs_apis;
+mod offline_chunking;
+mod payment_apis;
+mod pointer_apis;
+mod proof_apis;
+mod publish_apis;
 mod queries;
 mod register_apis;
+mod register_coalescing;
+mod register_replica;
+mod replication_apis;
+mod retry_policy;
+mod safe_client;
+mod section_apis;
+mod stored_doc;
+mod transfer_limits;
+mod transfer_stats;
+mod upload_report;
+mod upload_session;
 
-pub use self::blob_apis::BlobAddress;
-use crate::client::{connections::Session, errors::Error, Config};
-use crate::messaging::data::CmdError;
-use crate::types::{Keypair, PublicKey};
+pub use self::archive_apis::{ArchiveEntry, ArchiveIndex};
+pub use self::batch_upload::BatchUpload;
+pub use self::blob_apis::{BlobAddress, BlobContent, SpilledBlob};
+use self::chunk_cache::ChunkCache;
+pub use self::chunk_cache::ChunkCacheStats;
+pub use self::existence_apis::BlobPresence;
+pub use self::fan_out_apis::FanOutResult;
+pub use self::fetch_apis::FetchedContent;
+pub use self::file_history::{FileHistory, FileVersion};
+pub use self::files_container::{DirEntry, FileItem, FilesContainer, FilesMap};
+pub use self::health_apis::{HealthCheckStage, HealthReport};
+pub use self::integrity_apis::BlobVerification;
+use self::latency::LatencyTracker;
+pub use self::latency::{LatencyEvent, LatencyObjectives, OperationKind};
+pub use self::legacy_addresses::{BlobAddressFormat, MigratedBlobAddress, ParsedBlobAddress};
+pub use self::manifest_apis::{BlobManifest, ChunkCheck, ManifestChunk, ManifestVerification};
+pub use self::mock_client::MockClient;
+pub use self::multimap::{Multimap, MultimapEntry};
+pub use self::nrs_apis::{NrsMap, NrsTarget};
+pub use self::offline_chunking::{
+    calculate_blob_address, calculate_blob_chunks, calculate_blob_manifest,
+};
+pub use self::pointer_apis::{Pointer, PointerTarget};
+pub use self::proof_apis::DataProofBundle;
+pub use self::register_apis::RegisterSpec;
+use self::register_coalescing::LiveWriters;
+pub use self::register_coalescing::{CoalescingRegisterWriter, CoalescingStats};
+pub use self::register_replica::{LocalRegisterReplica, SyncStatus};
+pub use self::replication_apis::{ChunkReplication, ReplicationHealth};
+pub use self::retry_policy::{RetryBudget, RetryPolicy};
+pub use self::safe_client::SafeClient;
+pub use self::stored_doc::{Migrations, StoredDoc};
+use self::transfer_limits::TransferLimiter;
+use self::transfer_stats::StatsRecorder;
+pub use self::transfer_stats::{TransferPhase, TransferStats, WithStats};
+pub use self::upload_report::{ChunkUpload, UploadEvent, UploadProgress, UploadReport};
+pub use self::upload_session::UploadSession;
+use crate::client::{
+    connections::{ErrorChannel, ProgressReporter, Session},
+    errors::Error,
+    genesis_sources::check_genesis_key,
+    proxy::check_proxy,
+    AntiEntropyEvent, BootstrapProgress, ClientEvent, ConcurrencyLimits, Config,
+    ConnectionRotation, ConnectionStats, DefaultEncryptionProvider, Diagnostics,
+    EncryptionProvider, NetworkInfo, OperationPriority, Profile, PublishHook, QueryTrace,
+    ReadConsistency, ResponseDivergence,
+};
+use crate::messaging::{
+    data::{CmdError, DataLimits},
+    Delegation,
+};
+use crate::types::{set_chunk_buffers_memory_cap, Cache, Chunk, Keypair, PublicKey, Signer};
 
 use rand::rngs::OsRng;
 use std::collections::BTreeSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::{
-    sync::{mpsc::Receiver, RwLock},
-    time::Duration,
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+    time::{Duration, Instant},
 };
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+use xor_name::XorName;
+
+// Number of prefetched head chunks kept around, and for how long.
+const HEAD_CHUNKS_CACHE_CAPACITY: usize = 500;
+const HEAD_CHUNKS_CACHE_DURATION: Duration = Duration::from_secs(5 * 60);
 
 /// Client object
 #[derive(Clone, Debug)]
 pub struct Client {
-    keypair: Keypair,
-    incoming_errors: Arc<RwLock<Receiver<CmdError>>>,
+    signer: Arc<dyn Signer>,
+    incoming_errors: ErrorChannel,
     session: Session,
     pub(crate) query_timeout: Duration,
+    retry_policy: RetryPolicy,
+    retry_budget: Option<RetryBudget>,
+    transfer: TransferLimiter,
+    priority: OperationPriority,
+    read_consistency: Option<ReadConsistency>,
+    trace_queries: bool,
+    prefetch_head_chunks: bool,
+    read_memory_limit: Option<usize>,
+    head_chunks: Arc<Cache<XorName, Chunk>>,
+    chunk_cache: Option<Arc<ChunkCache>>,
+    encryption_provider: Arc<dyn EncryptionProvider>,
+    publish_hook: Option<Arc<dyn PublishHook>>,
+    latency: LatencyTracker,
+    delegation: Option<Delegation>,
+    data_limits: Arc<std::sync::RwLock<Option<DataLimits>>>,
+    coalescing_writers: Arc<LiveWriters>,
+    stats: Option<StatsRecorder>,
+    profile: Option<Profile>,
 }
 
 /// Easily manage connections to/from The Safe Network with the client and its APIs.
 /// Use a random client for read-only or one-time operations.
-/// Supply an existing, SecretKey which holds a SafeCoin balance to be able to perform
-/// write operations.
+/// Supply an existing keypair, or a [`Signer`] holding one, to own the data written, and keep
+/// access to it across sessions.
+///
+/// There are no token balances or transfers yet: the network doesn't handle them. Payments
+/// for storing data are recorded alongside it with [`Client::record_payment`].
 impl Client {
-    /// Create a Safe Network client instance. Either for an existing SecretKey (in which case) the client will attempt
-    /// to retrieve the history of the key's balance in order to be ready for any token operations. Or if no SecreteKey
-    /// is passed, a random keypair will be used, which provides a client that can only perform Read operations (at
-    /// least until the client's SecretKey receives some token).
+    /// Create a Safe Network client instance, either for an existing keypair, which owns the
+    /// data it writes, or, if none is passed, for a random one, e.g. for read-only or one-time
+    /// operations.
+    ///
+    /// The keypair is passed as the [`Signer`] all the client's commands and queries are signed
+    /// by, so that its secret key may be held outside of the process, e.g. by a hardware security
+    /// module. A [`Keypair`] is a `Signer` too.
     ///
     /// # Examples
     ///
@@ -53,14 +165,87 @@ impl Client {
     pub async fn new(
         config: Config,
         bootstrap_nodes: BTreeSet<SocketAddr>,
-        optional_keypair: Option<Keypair>,
+        optional_signer: Option<Arc<dyn Signer>>,
+    ) -> Result<Self, Error> {
+        Self::create(
+            config,
+            bootstrap_nodes,
+            optional_signer,
+            ProgressReporter::default(),
+        )
+        .await
+    }
+
+    /// Create a Safe Network client instance as per [`Client::new`], in the background,
+    /// notifying each step of bootstrapping to the network on the returned channel as it
+    /// happens, e.g. for applications to show meaningful status while starting up.
+    ///
+    /// Once bootstrapped, the client also discovers and connects to the Elders of its section
+    /// before being returned, so that it's ready to go. Failing to do so isn't an error, as that
+    /// is retried on the client's first operations: the client is still returned, and the
+    /// failure logged, without a [`BootstrapProgress::EldersConnected`] being notified.
+    pub fn new_with_progress(
+        config: Config,
+        bootstrap_nodes: BTreeSet<SocketAddr>,
+        optional_signer: Option<Arc<dyn Signer>>,
+    ) -> (
+        mpsc::UnboundedReceiver<BootstrapProgress>,
+        JoinHandle<Result<Self, Error>>,
+    ) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let handle = tokio::spawn(Self::create(
+            config,
+            bootstrap_nodes,
+            optional_signer,
+            ProgressReporter::new(sender),
+        ));
+        (receiver, handle)
+    }
+
+    /// Create a Safe Network client instance for the profile named `name`, e.g. of one of the
+    /// accounts of a multi-account application, as per [`Profile::open`] within
+    /// [`Config::root_dir`], creating it if there's none.
+    ///
+    /// The client uses the profile's keypair, bootstraps with the contacts set for it, and
+    /// persists its state in the profile's directory, isolated from other profiles' even
+    /// within the same process. The profile is bound to the network of the genesis key
+    /// configured the first time it's used, and refuses to be used on any other.
+    pub async fn with_profile(name: &str, mut config: Config) -> Result<Self, Error> {
+        let profile = Profile::open(&config.root_dir, name)?;
+        profile.check_genesis_key(&config.genesis_key)?;
+        let keypair = profile.keypair()?;
+        let bootstrap_nodes = profile.contacts()?;
+        config.root_dir = profile.dir().to_path_buf();
+
+        let mut client = Self::create(
+            config,
+            bootstrap_nodes,
+            Some(Arc::new(keypair)),
+            ProgressReporter::default(),
+        )
+        .await?;
+        info!("Client started for profile {}", profile.name());
+        client.profile = Some(profile);
+        Ok(client)
+    }
+
+    /// Return the profile this client was created for, if any.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    async fn create(
+        config: Config,
+        bootstrap_nodes: BTreeSet<SocketAddr>,
+        optional_signer: Option<Arc<dyn Signer>>,
+        progress: ProgressReporter,
     ) -> Result<Self, Error> {
         let mut rng = OsRng;
 
-        let keypair = match optional_keypair {
-            Some(id) => {
-                info!("Client started for specific pk: {:?}", id.public_key());
-                id
+        let signer = match optional_signer {
+            Some(signer) => {
+                info!("Client started for specific pk: {:?}", signer.public_key());
+                signer
             }
             None => {
                 let keypair = Keypair::new_ed25519(&mut rng);
@@ -68,14 +253,25 @@ impl Client {
                     "Client started for new randomly created pk: {:?}",
                     keypair.public_key()
                 );
-                keypair
+                Arc::new(keypair)
             }
         };
 
+        // Refuse to bypass a proxy the user relies on.
+        check_proxy(config.proxy.as_ref())?;
+        // And to bootstrap to a network whose genesis key isn't confirmed by all the sources.
+        check_genesis_key(&config.genesis_key, &config.genesis_key_sources).await?;
+
+        if let Some(memory_cap) = config.buffer_memory_cap {
+            if !set_chunk_buffers_memory_cap(memory_cap) {
+                warn!("Chunk buffers are in use already, their memory cap is left as it was");
+            }
+        }
+
         // Incoming error notifiers
-        let (err_sender, err_receiver) = tokio::sync::mpsc::channel::<CmdError>(10);
+        let incoming_errors = ErrorChannel::new(config.error_channel);
 
-        let client_pk = keypair.public_key();
+        let client_pk = signer.public_key();
 
         // Bootstrap to the network, connecting to a section based
         // on a public key of our choice.
@@ -90,30 +286,68 @@ impl Client {
             config.qp2p,
             bootstrap_nodes.clone(),
             config.local_addr,
-            err_sender,
+            incoming_errors.clone(),
+            config.concurrency_limits,
+            &progress,
         )
         .await?;
 
         let client = Self {
-            keypair,
+            signer,
             session,
-            incoming_errors: Arc::new(RwLock::new(err_receiver)),
+            incoming_errors,
             query_timeout: config.query_timeout,
+            retry_policy: config.retry_policy,
+            retry_budget: None,
+            transfer: TransferLimiter::new(config.concurrency_limits),
+            priority: OperationPriority::default(),
+            read_consistency: None,
+            trace_queries: false,
+            prefetch_head_chunks: config.prefetch_head_chunks,
+            read_memory_limit: config.read_memory_limit,
+            head_chunks: Arc::new(Cache::with_expiry_duration_and_capacity(
+                HEAD_CHUNKS_CACHE_DURATION,
+                HEAD_CHUNKS_CACHE_CAPACITY,
+            )),
+            chunk_cache: config
+                .chunk_cache_budget
+                .map(|budget| Arc::new(ChunkCache::new(budget))),
+            encryption_provider: Arc::new(DefaultEncryptionProvider),
+            publish_hook: None,
+            latency: LatencyTracker::new(config.latency_objectives),
+            delegation: None,
+            data_limits: Arc::new(std::sync::RwLock::new(None)),
+            coalescing_writers: Arc::new(LiveWriters::default()),
+            stats: None,
+            profile: None,
         };
 
+        if progress.is_enabled() {
+            if let Err(err) = client.connect_to_own_section(&progress).await {
+                warn!("Failed to connect to the Elders of our section: {:?}", err);
+            }
+        }
+
         Ok(client)
     }
 
-    /// Return the client's keypair.
+    /// Return the client's signer.
     ///
-    /// Useful for retrieving the PublicKey or KeyPair in the event you need to _sign_ something
+    /// Useful in the event you need to _sign_ something as the client
     ///
     /// # Examples
     ///
     /// TODO: update once data types are crdt compliant
     ///
-    pub fn keypair(&self) -> Keypair {
-        self.keypair.clone()
+    pub fn signer(&self) -> Arc<dyn Signer> {
+        self.signer.clone()
+    }
+
+    /// Return the client's keypair, unless it signs with a [`Signer`] holding its secret key
+    /// outside of the process.
+    #[deprecated(note = "the secret key may not be held by the client, use `Client::signer`")]
+    pub fn keypair(&self) -> Option<Keypair> {
+        self.signer.as_keypair().cloned()
     }
 
     /// Return the client's PublicKey.
@@ -123,20 +357,267 @@ impl Client {
     /// TODO: update once data types are crdt compliant
     ///
     pub fn public_key(&self) -> PublicKey {
-        self.keypair().public_key()
+        self.signer.public_key()
+    }
+
+    /// Return a client sharing this client's session, whose operations run with the given priority.
+    ///
+    /// Operations are run as [`OperationPriority::Foreground`] unless specified otherwise.
+    /// Use [`OperationPriority::Background`] for bulk jobs, e.g. backups, so they hold off
+    /// from using the network while interactive operations are in flight.
+    pub fn with_priority(&self, priority: OperationPriority) -> Self {
+        let mut client = self.clone();
+        client.priority = priority;
+        client
+    }
+
+    /// Return the priority this client's operations run with.
+    pub fn priority(&self) -> OperationPriority {
+        self.priority
+    }
+
+    /// Return a client sharing this client's session, whose queries are retried as per the
+    /// given policy rather than [`Config::retry_policy`], e.g. to fail fast on latency-sensitive
+    /// reads, or to persevere with those of batch jobs.
+    ///
+    /// [`Config::retry_policy`]: crate::client::Config::retry_policy
+    pub fn with_retry_policy(&self, retry_policy: RetryPolicy) -> Self {
+        let mut client = self.clone();
+        client.retry_policy = retry_policy;
+        client
+    }
+
+    /// Return the policy this client's queries are retried as per.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Return a client sharing this client's session, whose queries only return once as many
+    /// of the Elders they're sent to as `consistency` requires agree on the response, e.g.
+    /// [`ReadConsistency::Fastest`] for latency-sensitive reads, or [`ReadConsistency::All`]
+    /// to check the Elders of the section all return the same data. Agreement is only
+    /// required between Elders, not between the Adults holding a chunk, see
+    /// [`ReadConsistency`].
+    ///
+    /// Unless specified otherwise, a chunk is returned as soon as one matching its address is
+    /// received, and any other data once a majority of the Elders agree on it.
+    pub fn with_read_consistency(&self, consistency: ReadConsistency) -> Self {
+        let mut client = self.clone();
+        client.read_consistency = Some(consistency);
+        client
+    }
+
+    /// Return the read consistency this client's queries require, if specified.
+    pub fn read_consistency(&self) -> Option<ReadConsistency> {
+        self.read_consistency
+    }
+
+    /// Return a client sharing this client's session, whose requests all draw from the given
+    /// budget, e.g. to bound the retries and time of reading a whole archive, however many
+    /// chunks it takes to.
+    pub fn with_retry_budget(&self, budget: RetryBudget) -> Self {
+        let mut client = self.clone();
+        client.retry_budget = Some(budget);
+        client
+    }
+
+    /// Return a client sharing this client's session, whose blob reads and writes are held
+    /// back to the given chunk read and write limits, and rate, e.g. to keep a background
+    /// upload from saturating the connection. Those of [`Config::concurrency_limits`] still
+    /// apply to the whole session.
+    ///
+    /// The limits are shared by the client returned and any it's cloned into, but not by this
+    /// client, which keeps its own.
+    ///
+    /// [`Config::concurrency_limits`]: crate::client::Config::concurrency_limits
+    pub fn with_concurrency_limits(&self, limits: ConcurrencyLimits) -> Self {
+        let mut client = self.clone();
+        client.transfer = TransferLimiter::new(limits);
+        client
+    }
+
+    /// Return the limits this client's blob reads and writes are held back to.
+    pub fn concurrency_limits(&self) -> ConcurrencyLimits {
+        self.transfer.limits()
+    }
+
+    /// Return a client sharing this client's session, whose queries record the path
+    /// they take through the network, as notified to [`Client::subscribe_to_traces`].
+    ///
+    /// Meant for diagnosing slow or failing queries, as every node on the way signs a record of it.
+    pub fn with_message_tracing(&self, enabled: bool) -> Self {
+        let mut client = self.clone();
+        client.trace_queries = enabled;
+        client
+    }
+
+    /// Return a client sharing this client's session, whose operations are issued on behalf of
+    /// the user who granted this client's key the given capability.
+    ///
+    /// Meant for server-side apps acting for many users: Elders authorise the operations
+    /// as the user's, while the app never holds the users' keys.
+    pub fn with_delegation(&self, delegation: Delegation) -> Self {
+        let mut client = self.clone();
+        client.delegation = Some(delegation);
+        client
+    }
+
+    /// Return a client sharing this client's session, which encrypts the private data it
+    /// stores, and decrypts the private data it reads, with the given provider's encryption.
+    ///
+    /// [`DefaultEncryptionProvider`] is used unless specified otherwise.
+    pub fn with_encryption_provider(&self, provider: Arc<dyn EncryptionProvider>) -> Self {
+        let mut client = self.clone();
+        client.encryption_provider = provider;
+        client
+    }
+
+    /// Return a client sharing this client's session, which checks the content it's about to
+    /// publish with the given hook, refusing with [`Error::PublishRejected`] to store the
+    /// public data the hook rejects.
+    ///
+    /// No hook is set unless specified otherwise.
+    pub fn with_publish_hook(&self, hook: Arc<dyn PublishHook>) -> Self {
+        let mut client = self.clone();
+        client.publish_hook = Some(hook);
+        client
+    }
+
+    /// Close the client, and any other client sharing its session.
+    ///
+    /// The entries [`CoalescingRegisterWriter`]s hold back are sent first. New operations are
+    /// then refused with [`Error::ClientClosed`] straight away, while those in flight get what's
+    /// left of `graceful_timeout` to be done. Cached data is then dropped and all connections
+    /// torn down, rather than leaving it to `Drop`, which may cut off pending writes.
+    /// Returns [`Error::OperationsCutOff`] if some operations weren't done in time.
+    pub async fn close(&self, graceful_timeout: Duration) -> Result<(), Error> {
+        info!("Closing client {:?}", self.public_key());
+        let deadline = Instant::now() + graceful_timeout;
+        if tokio::time::timeout_at(deadline, self.coalescing_writers.flush(self))
+            .await
+            .is_err()
+        {
+            warn!("Closing client before coalesced Register entries were all sent");
+        }
+        let cut_off = self
+            .session
+            .close(deadline.saturating_duration_since(Instant::now()))
+            .await;
+        self.head_chunks.clear().await;
+        self.clear_chunk_cache();
+
+        if cut_off > 0 {
+            Err(Error::OperationsCutOff(cut_off))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Subscribe to changes in this client's view of the network and its connections to it:
+    /// errors received in response to its commands, sections' keys changing, connections to
+    /// nodes being lost and re-established, the network becoming unreachable until the client
+    /// bootstraps again, and queries being retried with other Elders.
+    ///
+    /// Each subscriber gets all events notified after it subscribed. Those lagging too far
+    /// behind miss the oldest ones, as per [`broadcast::Receiver::recv`].
+    pub fn subscribe(&self) -> broadcast::Receiver<ClientEvent> {
+        self.session.subscribe()
+    }
+
+    /// Subscribe to notifications of Elders returning conflicting responses to this client's queries.
+    ///
+    /// Queries are answered with the response a majority of the Elders agree on, or fail with
+    /// [`Error::ConflictingResponses`] when there is no such majority. Either way, a
+    /// [`ResponseDivergence`] is notified, e.g. for monitoring faulty or byzantine Elders.
+    pub fn subscribe_to_divergences(&self) -> broadcast::Receiver<ResponseDivergence> {
+        self.session.subscribe_to_divergences()
+    }
+
+    /// Subscribe to the paths taken by the queries of clients with message tracing enabled,
+    /// one [`QueryTrace`] per Elder responding, whose signed hops have been verified.
+    pub fn subscribe_to_traces(&self) -> broadcast::Receiver<QueryTrace> {
+        self.session.subscribe_to_traces()
+    }
+
+    /// Subscribe to the anti-entropy responses to this client's messages, received when
+    /// the client's knowledge of the network was outdated, and how they were handled.
+    ///
+    /// Meant for logging and reasoning about how the client's view of the network converges.
+    pub fn subscribe_to_anti_entropy(&self) -> broadcast::Receiver<AntiEntropyEvent> {
+        self.session.subscribe_to_anti_entropy()
+    }
+
+    /// Subscribe to the rotations of the client's connections to nodes: connections whose
+    /// round trips get far slower than the others', or whose sends keep failing, e.g. on flaky
+    /// Wi-Fi or mobile links, are closed for new ones to be established on their next use.
+    pub fn subscribe_to_connection_rotations(&self) -> broadcast::Receiver<ConnectionRotation> {
+        self.session.subscribe_to_connection_rotations()
+    }
+
+    /// Statistics of the client's connections to nodes: their round-trip times, last activity
+    /// and failed sends, as observed from the messages exchanged over them.
+    pub fn connection_stats(&self) -> Vec<ConnectionStats> {
+        self.session.connection_stats()
+    }
+
+    /// Subscribe to changes in compliance with the latency objectives set in [`Config`].
+    ///
+    /// A [`LatencyEvent::Degraded`] is notified once the network consistently misses an
+    /// objective, and a [`LatencyEvent::Recovered`] once it consistently meets it again.
+    pub fn subscribe_to_latency_events(&self) -> broadcast::Receiver<LatencyEvent> {
+        self.latency.subscribe()
+    }
+
+    /// Wait for the next error received from Elders in response to this client's commands.
+    ///
+    /// Errors are queued up until taken, as per the client's [`ErrorChannelConfig`]. How many
+    /// were dropped as the queue overflowed is part of the client's [`Client::diagnostics`].
+    ///
+    /// [`ErrorChannelConfig`]: crate::client::ErrorChannelConfig
+    pub async fn next_cmd_error(&self) -> CmdError {
+        self.incoming_errors.recv().await
+    }
+
+    /// Take the oldest error received from Elders in response to this client's commands,
+    /// if any is queued, as per [`Client::next_cmd_error`] but without waiting for one.
+    pub fn try_next_cmd_error(&self) -> Option<CmdError> {
+        self.incoming_errors.try_recv()
+    }
+
+    /// Take a snapshot of what this client is up to: its internal tasks running, the
+    /// operations in flight or queued, and the state of its connections to Elders.
+    ///
+    /// Meant for debugging applications which seem stuck. Build with the `tokio-console`
+    /// feature to also inspect the client's tasks, by name, in tokio-console.
+    pub async fn diagnostics(&self) -> Diagnostics {
+        self.session.diagnostics().await
+    }
+
+    /// Take a snapshot of this client's view of its section, i.e. its prefix and Elders, and
+    /// of the health of its session: the round-trip times of its connections to the Elders,
+    /// how many queries were retried or timed out, and how long the proof chain the section's
+    /// key was verified with is.
+    ///
+    /// Meant for operators debugging flaky clients. The counts are those of all the clients
+    /// sharing this client's session.
+    pub async fn network_info(&self) -> NetworkInfo {
+        self.session.network_info().await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::client::utils::test_utils::{create_test_client, create_test_client_with};
-    use crate::types::utils::random_bytes;
+    use crate::client::utils::test_utils::{
+        create_test_client, create_test_client_with, read_network_conn_info,
+    };
+    use crate::types::{utils::random_bytes, Signature};
     use crate::url::Scope;
     use eyre::Result;
     use std::{
         collections::HashSet,
         net::{IpAddr, Ipv4Addr, SocketAddr},
+        sync::atomic::{AtomicUsize, Ordering},
     };
 
     #[tokio::test(flavor = "multi_thread")]
@@ -171,6 +652,90 @@ mod tests {
         Ok(())
     }
 
+    // Signs as an external signer would, counting the signatures made.
+    #[derive(Debug)]
+    struct CountingSigner {
+        keypair: Keypair,
+        signatures: AtomicUsize,
+    }
+
+    impl Signer for CountingSigner {
+        fn public_key(&self) -> PublicKey {
+            self.keypair.public_key()
+        }
+
+        fn sign(&self, data: &[u8]) -> crate::types::Result<Signature> {
+            let _ = self.signatures.fetch_add(1, Ordering::Relaxed);
+            Ok(self.keypair.sign(data))
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn client_creation_with_external_signer() -> Result<()> {
+        let signer = Arc::new(CountingSigner {
+            keypair: Keypair::new_ed25519(&mut OsRng),
+            signatures: AtomicUsize::new(0),
+        });
+        let root_dir = tempfile::tempdir()?;
+        let (genesis_key, bootstrap_nodes) = read_network_conn_info()?;
+        let config = Config::new(Some(root_dir.path()), None, genesis_key, None, None).await;
+
+        let optional_signer: Option<Arc<dyn Signer>> = Some(signer.clone());
+        let client = Client::new(config, bootstrap_nodes, optional_signer).await?;
+        assert_eq!(client.public_key(), signer.keypair.public_key());
+
+        let data = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let _ = client.write_to_network(data, Scope::Public).await?;
+        assert!(signer.signatures.load(Ordering::Relaxed) > 0);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn client_creation_notifies_progress() -> Result<()> {
+        let root_dir = tempfile::tempdir()?;
+        let (genesis_key, bootstrap_nodes) = read_network_conn_info()?;
+        let config = Config::new(Some(root_dir.path()), None, genesis_key, None, None).await;
+
+        let (mut progress, handle) = Client::new_with_progress(config, bootstrap_nodes, None);
+        let _client = handle.await??;
+
+        let mut steps = vec![];
+        while let Some(step) = progress.recv().await {
+            steps.push(step);
+        }
+        assert!(matches!(
+            steps.first(),
+            Some(BootstrapProgress::ContactTried { attempt: 1, .. })
+        ));
+        assert!(steps
+            .iter()
+            .any(|step| matches!(step, BootstrapProgress::HandshakeOk(_))));
+        assert!(matches!(
+            steps.last(),
+            Some(BootstrapProgress::EldersConnected { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn network_info_lists_own_section() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let data = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let _ = client.write_to_network(data, Scope::Public).await?;
+
+        let info = client.network_info().await;
+        let prefix = info
+            .prefix
+            .ok_or_else(|| eyre::eyre!("Own section isn't known"))?;
+        assert!(prefix.matches(&XorName::from(client.public_key())));
+        assert!(!info.elders.is_empty());
+        assert!(info.elders.iter().all(|elder| prefix.matches(&elder.name)));
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn long_lived_connection_survives() -> Result<()> {
         let client = create_test_client(None).await?;
@@ -0,0 +1,273 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::file_history::{blob_of, scope_of};
+use super::{Client, Migrations};
+use crate::client::Result;
+use crate::types::register::{Address, Entry, EntryHash};
+use crate::url::{ContentType, Scope, Url, XorUrlBase};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use tracing::debug;
+use xor_name::{XorName, XOR_NAME_LEN};
+
+// Version of the schema key-value pairs are stored with.
+const KEY_VALUE_VERSION: u32 = 0;
+// Query parameter of entries holding the hash of their key.
+const KEY_HASH_QUERY: &str = "key";
+// Query parameter marking entries which remove their key.
+const REMOVED_QUERY: &str = "removed";
+
+/// A key-value pair of a [`Multimap`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MultimapEntry {
+    /// Hash of the Register entry recording the pair.
+    pub hash: EntryHash,
+    /// The key.
+    pub key: Bytes,
+    /// The value.
+    pub value: Bytes,
+}
+
+// How key-value pairs are stored, as a blob each.
+#[derive(Serialize, Deserialize)]
+struct KeyValue {
+    key: Bytes,
+    value: Bytes,
+}
+
+/// A key-value store kept as a Register, each pair being stored as a blob, and appended to the
+/// Register along with the hash of its key, so the pairs of a key are found without reading
+/// the others.
+///
+/// Inserting a value for a key supersedes its values, and removing a key appends a tombstone
+/// superseding them. Values inserted concurrently for the same key, e.g. from two devices,
+/// are all kept, a key thus mapping to several values until a value inserted after them
+/// supersedes them all.
+#[derive(Clone, Debug)]
+pub struct Multimap {
+    client: Client,
+    address: Address,
+}
+
+impl Multimap {
+    /// Address of the Register the multimap is kept in.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Insert `value` for `key`, superseding the values the key had, returning the hash of
+    /// the entry recording the pair.
+    pub async fn insert(&self, key: Bytes, value: Bytes) -> Result<EntryHash> {
+        let key_hash = hash_key(&key);
+        let children = self.entries_of(&key_hash).await?;
+
+        let scope = scope_of(&self.address);
+        let blob = self
+            .client
+            .write_doc(&KeyValue { key, value }, &migrations(), scope)
+            .await?;
+        let url = Url::encode_blob(*blob.name(), scope, ContentType::Raw, XorUrlBase::Base32z)?;
+        let mut entry = Url::from_url(&url)?;
+        entry.set_query_key(KEY_HASH_QUERY, Some(&hex::encode(key_hash.0)))?;
+
+        let hash = self
+            .client
+            .write_to_register(self.address, entry, children)
+            .await?;
+        debug!("Inserted into multimap {:?}, at {:?}", self.address, hash);
+        Ok(hash)
+    }
+
+    /// Remove `key` along with its values, returning the hash of the entry recording the
+    /// removal, if the key was in the multimap.
+    pub async fn remove(&self, key: &[u8]) -> Result<Option<EntryHash>> {
+        let key_hash = hash_key(key);
+        let register = self.client.get_register(self.address).await?;
+        let mut children = BTreeSet::new();
+        let mut found = false;
+        for (hash, entry) in register.read(None)? {
+            if key_hash_of(&entry).as_ref() == Some(&key_hash) {
+                found |= !is_removal(&entry);
+                let _ = children.insert(hash);
+            }
+        }
+        if !found {
+            return Ok(None);
+        }
+
+        // Tombstones point at no blob, but still need to be a blob URL to be an entry.
+        let url = Url::encode_blob(
+            key_hash,
+            scope_of(&self.address),
+            ContentType::Raw,
+            XorUrlBase::Base32z,
+        )?;
+        let mut entry = Url::from_url(&url)?;
+        entry.set_query_key(KEY_HASH_QUERY, Some(&hex::encode(key_hash.0)))?;
+        entry.set_query_key(REMOVED_QUERY, Some(""))?;
+
+        let hash = self
+            .client
+            .write_to_register(self.address, entry, children)
+            .await?;
+        debug!(
+            "Removed a key from multimap {:?}, at {:?}",
+            self.address, hash
+        );
+        Ok(Some(hash))
+    }
+
+    /// The values of `key`, several ones if they were inserted concurrently, along with the
+    /// hashes of the entries recording them.
+    pub async fn get_by_key(&self, key: &[u8]) -> Result<Vec<MultimapEntry>> {
+        let key_hash = hash_key(key);
+        let register = self.client.get_register(self.address).await?;
+        let mut pairs = Vec::new();
+        for (hash, entry) in register.read(None)? {
+            if key_hash_of(&entry).as_ref() == Some(&key_hash) && !is_removal(&entry) {
+                let pair = self.read_pair(hash, &entry).await?;
+                // Keys of different content could, in theory, hash the same.
+                if pair.key == key {
+                    pairs.push(pair);
+                }
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// All the key-value pairs of the multimap, in no particular order.
+    pub async fn entries(&self) -> Result<Vec<MultimapEntry>> {
+        let register = self.client.get_register(self.address).await?;
+        let mut pairs = Vec::new();
+        for (hash, entry) in register.read(None)? {
+            if key_hash_of(&entry).is_some() && !is_removal(&entry) {
+                pairs.push(self.read_pair(hash, &entry).await?);
+            }
+        }
+        Ok(pairs)
+    }
+
+    // Hashes of the latest entries of the key of the given hash, values or tombstone.
+    async fn entries_of(&self, key_hash: &XorName) -> Result<BTreeSet<EntryHash>> {
+        let register = self.client.get_register(self.address).await?;
+        Ok(register
+            .read(None)?
+            .into_iter()
+            .filter(|(_, entry)| key_hash_of(entry).as_ref() == Some(key_hash))
+            .map(|(hash, _)| hash)
+            .collect())
+    }
+
+    async fn read_pair(&self, hash: EntryHash, entry: &Entry) -> Result<MultimapEntry> {
+        let KeyValue { key, value } = self
+            .client
+            .read_doc(blob_of(entry), &migrations())
+            .await?
+            .doc;
+        Ok(MultimapEntry { hash, key, value })
+    }
+}
+
+fn migrations() -> Migrations<KeyValue> {
+    Migrations::new(KEY_VALUE_VERSION)
+}
+
+fn hash_key(key: &[u8]) -> XorName {
+    XorName::from_content(key)
+}
+
+fn key_hash_of(entry: &Entry) -> Option<XorName> {
+    let bytes = hex::decode(entry.query_key_first(KEY_HASH_QUERY)?).ok()?;
+    if bytes.len() != XOR_NAME_LEN {
+        return None;
+    }
+    let mut name = [0; XOR_NAME_LEN];
+    name.copy_from_slice(&bytes);
+    Some(XorName(name))
+}
+
+fn is_removal(entry: &Entry) -> bool {
+    entry.query_key_first(REMOVED_QUERY).is_some()
+}
+
+impl Client {
+    /// Create an empty multimap, kept in a new Register of the given name and tag, owned by
+    /// this client, which alone can change it. Its pairs are private or public as per `scope`.
+    pub async fn create_multimap(&self, name: XorName, tag: u64, scope: Scope) -> Result<Multimap> {
+        let address = self.store_owned_register(name, tag, scope).await?;
+        Ok(self.multimap(address))
+    }
+
+    /// The multimap kept in the Register at `address`.
+    pub fn multimap(&self, address: Address) -> Multimap {
+        Multimap {
+            client: self.clone(),
+            address,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::url::Scope;
+    use bytes::Bytes;
+    use eyre::{eyre, Result};
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn multimap_inserts_replaces_and_removes() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let multimap = client
+            .create_multimap(XorName::random(), 15_000, Scope::Private)
+            .await?;
+        let _ = run_w_backoff_delayed(|| multimap.entries(), 10, 1).await?;
+
+        let key = Bytes::from_static(b"colour");
+        let _ = multimap
+            .insert(key.clone(), Bytes::from_static(b"red"))
+            .await?;
+        let _ = multimap
+            .insert(Bytes::from_static(b"size"), Bytes::from_static(b"large"))
+            .await?;
+        let _ = run_w_backoff_delayed(|| multimap.get_by_key(b"size"), 10, 1).await?;
+        let hash = multimap
+            .insert(key.clone(), Bytes::from_static(b"blue"))
+            .await?;
+
+        let _ = run_w_backoff_delayed(
+            || client.get_register_entry(*multimap.address(), hash),
+            10,
+            1,
+        )
+        .await?;
+        let values = multimap.get_by_key(&key).await?;
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].value, Bytes::from_static(b"blue"));
+        assert_eq!(multimap.entries().await?.len(), 2);
+
+        let removal = multimap
+            .remove(&key)
+            .await?
+            .ok_or_else(|| eyre!("key not found"))?;
+        let _ = run_w_backoff_delayed(
+            || client.get_register_entry(*multimap.address(), removal),
+            10,
+            1,
+        )
+        .await?;
+        assert!(multimap.get_by_key(&key).await?.is_empty());
+        assert_eq!(multimap.entries().await?.len(), 1);
+        assert_eq!(multimap.remove(&key).await?, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::file_history::blob_of;
+use super::{BlobAddress, Client, Migrations};
+use crate::client::{Error, Result};
+use crate::types::register::{Address, EntryHash};
+use crate::url::{Scope, Url, NRS_MAP_TYPE_TAG};
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::debug;
+
+// Version of the schema NRS maps are stored with.
+const NRS_MAP_VERSION: u32 = 0;
+
+/// What a public name resolves to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum NrsTarget {
+    /// A blob.
+    Blob(BlobAddress),
+    /// A [`FilesContainer`](super::FilesContainer), whose files are resolved by path, e.g.
+    /// `safe://name/docs/notes.txt`.
+    FilesContainer(Address),
+}
+
+/// The targets of a top name and its subnames, e.g. of `alice`, `blog.alice` and
+/// `www.blog.alice`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NrsMap {
+    /// Targets by subnames, e.g. `www.blog`, the top name's own being under the empty string.
+    pub targets: BTreeMap<String, NrsTarget>,
+}
+
+impl NrsMap {
+    /// Returns the target of the given subnames, the empty string for the top name's own.
+    pub fn get(&self, sub_names: &str) -> Option<&NrsTarget> {
+        self.targets.get(sub_names)
+    }
+}
+
+impl Client {
+    /// Register the public name `top_name`, e.g. `alice`, for it to resolve to `target`.
+    ///
+    /// The name is kept in a public Register, whose address is derived from the name, owned
+    /// by this client, which alone can then set its subnames and change its targets. Names are
+    /// first come, first served: registering one which is taken, even by this client, fails
+    /// with [`Error::RegisterTaken`], its map being left as it is.
+    pub async fn nrs_register(&self, top_name: &str, target: NrsTarget) -> Result<Url> {
+        let url = parse_name(top_name)?;
+        if !url.sub_names().is_empty() {
+            return Err(Error::InvalidNrsName(format!(
+                "{} has subnames, register its top name {} first",
+                top_name,
+                url.top_name()
+            )));
+        }
+
+        let address = url.register_address()?;
+        let _ = self
+            .claim_owned_register(*address.name(), NRS_MAP_TYPE_TAG, Scope::Public)
+            .await?;
+        let mut map = NrsMap::default();
+        let _ = map.targets.insert(String::new(), target);
+        self.write_nrs_map(address, &map, BTreeSet::new()).await?;
+        Ok(url)
+    }
+
+    /// Set the public name `name`, a registered top name, or one of its subnames, e.g.
+    /// `blog.alice`, to resolve to `target`, superseding any target it had.
+    pub async fn nrs_set(&self, name: &str, target: NrsTarget) -> Result<Url> {
+        let url = parse_name(name)?;
+        let address = url.register_address()?;
+        let (children, mut map) = self.latest_nrs_map(address).await?;
+        let _ = map.targets.insert(url.sub_names().to_string(), target);
+        self.write_nrs_map(address, &map, children).await?;
+        Ok(url)
+    }
+
+    /// Remove the public name `name`, a subname of a registered top name, or the top name
+    /// itself, which then resolves to nothing but stays registered.
+    pub async fn nrs_remove(&self, name: &str) -> Result<NrsTarget> {
+        let url = parse_name(name)?;
+        let address = url.register_address()?;
+        let (children, mut map) = self.latest_nrs_map(address).await?;
+        let target = map
+            .targets
+            .remove(url.sub_names())
+            .ok_or_else(|| Error::NrsNameNotFound(url.public_name().to_string()))?;
+        self.write_nrs_map(address, &map, children).await?;
+        Ok(target)
+    }
+
+    /// The targets of the registered top name `top_name` and its subnames.
+    pub async fn nrs_map(&self, top_name: &str) -> Result<NrsMap> {
+        let address = parse_name(top_name)?.register_address()?;
+        Ok(self.latest_nrs_map(address).await?.1)
+    }
+
+    /// Resolve an NRS URL, e.g. `safe://blog.alice/posts/first.md`, to what its name targets.
+    ///
+    /// The path, if any, is resolved in the files container the name targets, to the file at
+    /// that path. A version, e.g. `safe://alice?v=3`, resolves the name as it was at it, the
+    /// first one written when the name was registered being 0, as does a version hash.
+    pub async fn nrs_resolve(&self, url: &str) -> Result<NrsTarget> {
+        let url = Url::from_nrsurl(url)?;
+        let target = self.nrs_target(&url).await?;
+
+        let path = url.path_decoded()?;
+        if path.is_empty() || path == "/" {
+            return Ok(target);
+        }
+        match target {
+            NrsTarget::FilesContainer(address) => {
+                let files = self.files_container(address).files().await?;
+                Ok(NrsTarget::Blob(files.get(&path)?.blob))
+            }
+            NrsTarget::Blob(_) => Err(Error::InvalidNrsName(format!(
+                "{} targets a blob, which has no path {}",
+                url.public_name(),
+                path
+            ))),
+        }
+    }
+
+    // What the name of an NRS URL targets, at the version it has if any, ignoring its path.
+    pub(super) async fn nrs_target(&self, url: &Url) -> Result<NrsTarget> {
+        let address = url.register_address()?;
+        let entry = match (url.content_version(), url.content_version_number()) {
+            (Some(version), _) => Some(
+                self.get_register_entry(address, version.entry_hash())
+                    .await?,
+            ),
+            (None, Some(version)) => Some(
+                self.get_register_entry_by_version(address, version)
+                    .await?
+                    .1,
+            ),
+            (None, None) => None,
+        };
+        let map = match entry {
+            Some(entry) => self.read_doc(blob_of(&entry), &migrations()).await?.doc,
+            None => self.latest_nrs_map(address).await?.1,
+        };
+        map.get(url.sub_names())
+            .copied()
+            .ok_or_else(|| Error::NrsNameNotFound(url.public_name().to_string()))
+    }
+
+    // The latest maps of a name merged, along with their entries, for the next change to
+    // supersede them. The map with the lowest entry hash wins on conflicting subnames.
+    async fn latest_nrs_map(&self, address: Address) -> Result<(BTreeSet<EntryHash>, NrsMap)> {
+        let mut children = BTreeSet::new();
+        let mut merged = NrsMap::default();
+        for (hash, doc) in self.read_docs_from_register(address, &migrations()).await? {
+            let _ = children.insert(hash);
+            for (sub_names, target) in doc.doc.targets {
+                let _ = merged.targets.entry(sub_names).or_insert(target);
+            }
+        }
+        Ok((children, merged))
+    }
+
+    async fn write_nrs_map(
+        &self,
+        address: Address,
+        map: &NrsMap,
+        children: BTreeSet<EntryHash>,
+    ) -> Result<()> {
+        let hash = self
+            .write_doc_to_register(address, map, &migrations(), children)
+            .await?;
+        debug!(
+            "Wrote NRS map of {} names to {:?}, at {:?}",
+            map.targets.len(),
+            address,
+            hash
+        );
+        Ok(())
+    }
+}
+
+fn migrations() -> Migrations<NrsMap> {
+    Migrations::new(NRS_MAP_VERSION)
+}
+
+// Parses a public name, e.g. `blog.alice`, which can't have a path, query or fragment.
+fn parse_name(name: &str) -> Result<Url> {
+    let url = Url::from_nrsurl(&format!("safe://{}", name))?;
+    if url.public_name() != name {
+        return Err(Error::InvalidNrsName(name.to_string()));
+    }
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_name, NrsTarget};
+    use crate::client::client_api::BlobAddress;
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::client::Error;
+    use crate::types::utils::random_bytes;
+    use crate::url::Scope;
+    use eyre::Result;
+    use xor_name::XorName;
+
+    #[test]
+    fn names_are_parsed_to_their_register() -> Result<()> {
+        let top = parse_name("alice")?;
+        let sub = parse_name("www.blog.alice")?;
+        assert_eq!(sub.sub_names(), "www.blog");
+        assert_eq!(sub.register_address()?, top.register_address()?);
+
+        assert!(matches!(
+            parse_name("alice/path"),
+            Err(Error::InvalidNrsName(_))
+        ));
+        assert!(parse_name("a..alice").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn names_resolve_to_their_targets() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let top_name = format!("nrs-test-{}", rand::random::<u64>());
+
+        let container = client
+            .create_files_container(XorName::random(), 15_000, Scope::Public)
+            .await?;
+        let _ = run_w_backoff_delayed(|| container.list("/"), 10, 1).await?;
+        let file = container
+            .add(
+                "/index.html",
+                random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES),
+            )
+            .await?;
+        let other = BlobAddress::Public(XorName::random());
+
+        let _ = client
+            .nrs_register(&top_name, NrsTarget::FilesContainer(*container.address()))
+            .await?;
+        let url = format!("safe://{}", top_name);
+        let _ = run_w_backoff_delayed(|| client.nrs_resolve(&url), 10, 1).await?;
+        let _ = client
+            .nrs_set(&format!("www.{}", top_name), NrsTarget::Blob(other))
+            .await?;
+
+        let sub_url = format!("safe://www.{}", top_name);
+        let target = run_w_backoff_delayed(|| client.nrs_resolve(&sub_url), 10, 1).await?;
+        assert_eq!(target, NrsTarget::Blob(other));
+        assert_eq!(
+            client
+                .nrs_resolve(&format!("safe://{}/index.html", top_name))
+                .await?,
+            NrsTarget::Blob(file.blob)
+        );
+        // The name as first registered, before its subname was set.
+        assert!(matches!(
+            client.nrs_resolve(&format!("{}?v=0", sub_url)).await,
+            Err(Error::NrsNameNotFound(_))
+        ));
+
+        // Names taken can't be registered again, by anyone.
+        let squatter = create_test_client(None).await?;
+        for registrant in [&squatter, &client] {
+            assert!(matches!(
+                registrant
+                    .nrs_register(&top_name, NrsTarget::Blob(other))
+                    .await,
+                Err(Error::RegisterTaken(_))
+            ));
+        }
+        assert_eq!(
+            client.nrs_resolve(&url).await?,
+            NrsTarget::FilesContainer(*container.address())
+        );
+
+        Ok(())
+    }
+}
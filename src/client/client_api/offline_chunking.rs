@@ -0,0 +1,128 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{upload_report::encrypt, BlobAddress, BlobManifest, ManifestChunk};
+use crate::client::{DefaultEncryptionProvider, EncryptionProvider, Error, Result};
+use crate::types::{Chunk, PublicKey};
+use crate::url::Scope;
+use bytes::Bytes;
+use std::collections::BTreeMap;
+
+/// Compute the address `data` is stored at when written with `scope` by the client of public
+/// key `owner`, without a client or any network connection, e.g. for tooling to precompute
+/// addresses or deduplicate content before uploading it.
+///
+/// The data is self-encrypted as [`Client::write_to_network`] does, with the
+/// [`DefaultEncryptionProvider`], so it gets the same address. Clients using another provider
+/// store private data at other addresses. The network's size limits aren't checked.
+///
+/// [`Client::write_to_network`]: crate::client::Client::write_to_network
+pub fn calculate_blob_address(data: Bytes, scope: Scope, owner: PublicKey) -> Result<BlobAddress> {
+    Ok(calculate_blob_chunks(data, scope, owner)?.0)
+}
+
+/// Compute the address `data` is stored at, as per [`calculate_blob_address`], along with the
+/// chunks it's stored as, each once, its head chunk included.
+pub fn calculate_blob_chunks(
+    data: Bytes,
+    scope: Scope,
+    owner: PublicKey,
+) -> Result<(BlobAddress, Vec<Chunk>)> {
+    let encryption = DefaultEncryptionProvider.encryption(scope, owner);
+    let (address, chunks) = encrypt(data, encryption)?;
+    let chunks: BTreeMap<_, _> = chunks
+        .into_iter()
+        .map(|chunk| (*chunk.name(), chunk))
+        .collect();
+    Ok((
+        address,
+        chunks.into_iter().map(|(_, chunk)| chunk).collect(),
+    ))
+}
+
+/// Compute the manifest of the blob `data` is stored as, as per [`calculate_blob_address`], for
+/// it to be handed to a third party before, or without, the blob being read back.
+///
+/// The manifest lists every chunk the blob is stored as, the others than its head chunk in the
+/// order of their addresses.
+pub fn calculate_blob_manifest(
+    data: Bytes,
+    scope: Scope,
+    owner: PublicKey,
+) -> Result<BlobManifest> {
+    let (address, chunks) = calculate_blob_chunks(data, scope, owner)?;
+    let (head, chunks): (Vec<_>, Vec<_>) = chunks
+        .iter()
+        .map(ManifestChunk::of)
+        .partition(|chunk| chunk.address.name() == address.name());
+
+    let head = head
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::Generic("The blob has no head chunk".to_string()))?;
+
+    Ok(BlobManifest {
+        blob: address,
+        head,
+        chunks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{calculate_blob_address, calculate_blob_chunks, calculate_blob_manifest};
+    use crate::client::client_api::{BlobAddress, MockClient, SafeClient};
+    use crate::types::{utils::random_bytes, Keypair};
+    use crate::url::Scope;
+    use eyre::Result;
+    use rand::rngs::OsRng;
+    use std::collections::BTreeSet;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn addresses_are_computed_as_the_network_does() -> Result<()> {
+        let client = MockClient::new();
+        let owner = client.public_key();
+        let other = Keypair::new_ed25519(&mut OsRng).public_key();
+
+        let data = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let public = calculate_blob_address(data.clone(), Scope::Public, owner)?;
+        assert!(matches!(public, BlobAddress::Public(_)));
+        assert_eq!(
+            client.write_to_network(data.clone(), Scope::Public).await?,
+            public
+        );
+        // Public blobs are deduplicated across owners.
+        assert_eq!(
+            calculate_blob_address(data.clone(), Scope::Public, other)?,
+            public
+        );
+
+        let private = calculate_blob_address(data.clone(), Scope::Private, owner)?;
+        assert!(matches!(private, BlobAddress::Private(_)));
+        assert_eq!(
+            client
+                .write_to_network(data.clone(), Scope::Private)
+                .await?,
+            private
+        );
+
+        let (address, chunks) = calculate_blob_chunks(data.clone(), Scope::Public, owner)?;
+        assert_eq!(address, public);
+        // A min size blob is self-encrypted into 3 chunks, plus its head chunk.
+        assert_eq!(chunks.len(), 4);
+
+        let manifest = calculate_blob_manifest(data, Scope::Public, owner)?;
+        assert_eq!(manifest.blob, public);
+        assert_eq!(manifest.head.address.name(), public.name());
+        let listed: BTreeSet<_> = manifest.all().map(|chunk| chunk.address).collect();
+        let expected: BTreeSet<_> = chunks.iter().map(|chunk| *chunk.address()).collect();
+        assert_eq!(listed, expected);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::Error;
+use crate::messaging::data::{DataCmd, DataQuery, QueryResponse};
+use crate::types::{DataAddress, PaymentProof, Token};
+use tracing::{debug, trace};
+use xor_name::XorName;
+
+impl Client {
+    /// Record that storing the data at `address` was paid for.
+    ///
+    /// The proof is signed by the client's signer and kept by the network alongside the data,
+    /// so it can later be retrieved with [`Client::get_payment_proofs`], e.g. for disputes or accounting.
+    /// The data is also added to the catalog of the data the client owns, kept by the section of
+    /// the client's key, which is listed with [`Client::list_owned_data`].
+    ///
+    /// The amount is the payer's own: Elders don't quote a price for storing data, nor check
+    /// anything was spent, and the commands storing data carry no payment.
+    pub async fn record_payment(
+        &self,
+        address: DataAddress,
+        amount: Token,
+    ) -> Result<PaymentProof, Error> {
+        debug!("Recording payment of {} for {:?}", amount, address);
+        let proof = PaymentProof::new(address, amount, self.signer.as_ref())?;
+
+        let _ = self.send_cmd(DataCmd::RecordPayment(proof.clone())).await?;
+        let _ = self
+            .send_cmd(DataCmd::CatalogPayment {
+                owner: self.owner(),
+                proof: proof.clone(),
+            })
+            .await?;
+
+        Ok(proof)
+    }
+
+    /// Retrieve the proofs that this client paid for storing the data at `address`.
+    pub async fn get_payment_proofs(
+        &self,
+        address: DataAddress,
+    ) -> Result<Vec<PaymentProof>, Error> {
+        trace!("Get payment proofs for {:?}", address);
+        let query = DataQuery::GetPaymentProof(address);
+        let query_result = self.send_query(query).await?;
+        match query_result.response {
+            QueryResponse::GetPaymentProof((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })
+            }
+            _ => Err(Error::ReceivedUnexpectedEvent),
+        }
+    }
+
+    /// Retrieve the number of copies of each chunk kept by the section `name` belongs to.
+    ///
+    /// The replication factor is set by the section, and storing a chunk there means paying
+    /// for that many copies of it, so this is what the cost of storing data is based on.
+    pub async fn replication_factor(&self, name: XorName) -> Result<usize, Error> {
+        trace!("Get replication factor of the section of {:?}", name);
+        let query = DataQuery::GetReplicationFactor(name);
+        let query_result = self.send_query(query).await?;
+        match query_result.response {
+            QueryResponse::GetReplicationFactor((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })
+            }
+            _ => Err(Error::ReceivedUnexpectedEvent),
+        }
+    }
+}
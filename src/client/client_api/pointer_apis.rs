@@ -0,0 +1,244 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::file_history::{blob_of, scope_of};
+use super::{BlobAddress, Client};
+use crate::client::{Error, Result};
+use crate::types::{
+    register::{Address, Entry, EntryHash},
+    PublicKey,
+};
+use crate::url::{ContentType, DataType, Scope, Url, XorUrlBase};
+
+use std::collections::BTreeSet;
+use tracing::debug;
+use xor_name::XorName;
+
+/// What a [`Pointer`] points to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PointerTarget {
+    /// A blob, e.g. a profile document.
+    Blob(BlobAddress),
+    /// A Register, e.g. the [`FilesContainer`](super::FilesContainer) of a website.
+    Register(Address),
+}
+
+/// A record signed by its owner, pointing to the latest version of some data, e.g. the root of
+/// a website, found by the owner's public key and a type tag alone.
+///
+/// Pointers are kept in a public Register named after the owner's public key, each update being
+/// an entry superseding the previous ones, so only the latest entries are read, never the
+/// history of the pointer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pointer {
+    /// Address of the Register the pointer is kept in.
+    pub address: Address,
+    /// The owner, who alone can update the pointer.
+    pub owner: PublicKey,
+    /// What the pointer points to.
+    pub target: PointerTarget,
+    /// Hash of the entry the target was set at.
+    pub version: EntryHash,
+}
+
+impl Pointer {
+    /// Address of the Register the pointer of the given owner and tag is kept in.
+    pub fn address_of(owner: PublicKey, tag: u64) -> Address {
+        Address::Public {
+            name: XorName::from(owner),
+            tag,
+        }
+    }
+}
+
+impl Client {
+    /// Create a pointer to `target`, owned by this client, found by its public key and `tag`.
+    /// There's one pointer per owner and tag, so creating one whose Register exists fails with
+    /// [`Error::RegisterTaken`], whoever stored it.
+    pub async fn create_pointer(&self, tag: u64, target: PointerTarget) -> Result<Pointer> {
+        let address = Pointer::address_of(self.public_key(), tag);
+        let _ = self
+            .claim_owned_register(*address.name(), tag, Scope::Public)
+            .await?;
+        self.write_pointer(address, target, BTreeSet::new()).await
+    }
+
+    /// Update the pointer of the given tag owned by this client to point to `target`.
+    pub async fn set_pointer(&self, tag: u64, target: PointerTarget) -> Result<Pointer> {
+        let address = Pointer::address_of(self.public_key(), tag);
+        let children = self
+            .get_register(address)
+            .await?
+            .read(None)?
+            .into_iter()
+            .map(|(hash, _)| hash)
+            .collect();
+        self.write_pointer(address, target, children).await
+    }
+
+    /// The latest target of the pointer of `owner` and `tag`.
+    ///
+    /// The Register the pointer is kept in is checked to be owned by `owner`, so no one else can
+    /// take the pointer over by storing a Register at its address first. Should updates have
+    /// been made concurrently, e.g. from two devices, the one with the lowest entry hash wins.
+    pub async fn get_pointer(&self, owner: PublicKey, tag: u64) -> Result<Pointer> {
+        let address = Pointer::address_of(owner, tag);
+        let register = self.get_register(address).await?;
+        if register.owner() != owner {
+            return Err(Error::InvalidPointer(format!(
+                "the Register at {:?} isn't owned by {:?}",
+                address, owner
+            )));
+        }
+        let (version, entry) =
+            register.read(None)?.into_iter().next().ok_or_else(|| {
+                Error::InvalidPointer(format!("no target is set at {:?}", address))
+            })?;
+
+        Ok(Pointer {
+            address,
+            owner,
+            target: target_of(&entry)?,
+            version,
+        })
+    }
+
+    async fn write_pointer(
+        &self,
+        address: Address,
+        target: PointerTarget,
+        children: BTreeSet<EntryHash>,
+    ) -> Result<Pointer> {
+        let entry = entry_of(&target)?;
+        let version = self.write_to_register(address, entry, children).await?;
+        debug!("Pointed {:?} to {:?}, at {:?}", address, target, version);
+        Ok(Pointer {
+            address,
+            owner: self.public_key(),
+            target,
+            version,
+        })
+    }
+}
+
+fn entry_of(target: &PointerTarget) -> Result<Entry> {
+    let url = match target {
+        PointerTarget::Blob(address) => Url::encode_blob(
+            *address.name(),
+            match address {
+                BlobAddress::Public(_) => Scope::Public,
+                BlobAddress::Private(_) => Scope::Private,
+            },
+            ContentType::Raw,
+            XorUrlBase::Base32z,
+        )?,
+        PointerTarget::Register(address) => Url::encode_register(
+            *address.name(),
+            address.tag(),
+            scope_of(address),
+            ContentType::Raw,
+            XorUrlBase::Base32z,
+        )?,
+    };
+    Ok(Url::from_url(&url)?)
+}
+
+fn target_of(entry: &Entry) -> Result<PointerTarget> {
+    match entry.data_type() {
+        DataType::Blob => Ok(PointerTarget::Blob(blob_of(entry))),
+        DataType::Register => Ok(PointerTarget::Register(entry.register_address()?)),
+        DataType::SafeKey => Err(Error::InvalidPointer(format!(
+            "{} doesn't point to any data",
+            entry
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{entry_of, target_of, Pointer, PointerTarget};
+    use crate::client::client_api::BlobAddress;
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::client::Error;
+    use crate::types::register::Address;
+    use crate::url::Scope;
+    use eyre::Result;
+    use xor_name::XorName;
+
+    #[test]
+    fn targets_round_trip_through_entries() -> Result<()> {
+        let targets = vec![
+            PointerTarget::Blob(BlobAddress::Public(XorName::random())),
+            PointerTarget::Blob(BlobAddress::Private(XorName::random())),
+            PointerTarget::Register(Address::Private {
+                name: XorName::random(),
+                tag: 15_000,
+            }),
+        ];
+        for target in targets {
+            assert_eq!(target_of(&entry_of(&target)?)?, target);
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pointers_are_found_by_their_owner() -> Result<()> {
+        let owner = create_test_client(None).await?;
+        let tag = 15_000;
+        let first = PointerTarget::Blob(BlobAddress::Public(XorName::random()));
+        let created = owner.create_pointer(tag, first).await?;
+        assert_eq!(
+            created.address,
+            Pointer::address_of(owner.public_key(), tag)
+        );
+
+        let reader = create_test_client(None).await?;
+        let pointer =
+            run_w_backoff_delayed(|| reader.get_pointer(owner.public_key(), tag), 10, 1).await?;
+        assert_eq!(pointer.target, first);
+
+        let second = PointerTarget::Register(Address::Public {
+            name: XorName::random(),
+            tag: 15_001,
+        });
+        let updated = owner.set_pointer(tag, second).await?;
+        let _ = run_w_backoff_delayed(
+            || owner.get_register_entry(updated.address, updated.version),
+            10,
+            1,
+        )
+        .await?;
+        let pointer = reader.get_pointer(owner.public_key(), tag).await?;
+        assert_eq!(pointer.target, second);
+        assert_eq!(pointer.version, updated.version);
+
+        // Pointers can't be created twice.
+        assert!(matches!(
+            owner.create_pointer(tag, first).await,
+            Err(Error::RegisterTaken(_))
+        ));
+
+        // Anyone can store a Register at another's pointer address, which isn't taken as theirs,
+        // nor can the owner create the pointer there anymore.
+        let squatter = create_test_client(None).await?;
+        let address = squatter
+            .store_owned_register(XorName::from(owner.public_key()), tag + 1, Scope::Public)
+            .await?;
+        let _ = run_w_backoff_delayed(|| squatter.get_register(address), 10, 1).await?;
+        assert!(matches!(
+            reader.get_pointer(owner.public_key(), tag + 1).await,
+            Err(Error::InvalidPointer(_))
+        ));
+        assert!(matches!(
+            owner.create_pointer(tag + 1, first).await,
+            Err(Error::RegisterTaken(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::Error;
+use crate::messaging::data::{DataProof, DataQuery, NetworkTime, QueryResponse};
+use crate::types::{Chunk, ChunkAddress, JsonRepr};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+use xor_name::XorName;
+
+/// A chunk bundled with the proof that the network vouched for it.
+///
+/// Once obtained, it can be archived and verified by anyone knowing the network's genesis key,
+/// without any connection to the network.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DataProofBundle {
+    /// The chunk vouched for.
+    pub chunk: Chunk,
+    /// The section's signature over the chunk's address, and its key's proof chain.
+    pub proof: DataProof,
+}
+
+impl DataProofBundle {
+    /// Verifies the chunk's content matches the address vouched for, and that it was
+    /// vouched for by a section of the network with the given genesis key.
+    ///
+    /// A chunk's address is always derived from its content, including upon deserialisation.
+    pub fn verify(&self, genesis_key: &bls::PublicKey) -> bool {
+        self.chunk.address() == &self.proof.address && self.proof.verify(genesis_key)
+    }
+}
+
+impl JsonRepr for DataProofBundle {
+    const JSON_TYPE: &'static str = "DataProofBundle";
+}
+
+impl Client {
+    /// Retrieve the public chunk at `address` bundled with the section's signature over it,
+    /// and the section keys chain proving that signature back to the network's genesis key.
+    pub async fn get_data_proof_bundle(
+        &self,
+        address: ChunkAddress,
+    ) -> Result<DataProofBundle, Error> {
+        trace!("Get data proof bundle for {:?}", address);
+        let chunk = self.read_from_network(address.name()).await?;
+
+        let query_result = self.send_query(DataQuery::GetDataProof(address)).await?;
+        let proof = match query_result.response {
+            QueryResponse::GetDataProof((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })?
+            }
+            _ => return Err(Error::ReceivedUnexpectedEvent),
+        };
+
+        let bundle = DataProofBundle { chunk, proof };
+        if !bundle.verify(self.session.genesis_key()) {
+            return Err(Error::InvalidDataProof(address));
+        }
+
+        Ok(bundle)
+    }
+
+    /// Retrieve a coarse timestamp, to the minute, the median of the clocks of the Elders of a
+    /// random section which a supermajority of them vouched for, verified back to the network's
+    /// genesis key.
+    ///
+    /// Use it for TTLs, receipts and ordering hints, rather than trusting the local clock alone.
+    pub async fn network_time(&self) -> Result<NetworkTime, Error> {
+        let query_result = self
+            .send_query(DataQuery::GetNetworkTime(XorName::random()))
+            .await?;
+        let network_time = match query_result.response {
+            QueryResponse::GetNetworkTime((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })?
+            }
+            _ => return Err(Error::ReceivedUnexpectedEvent),
+        };
+
+        if !network_time.verify(self.session.genesis_key()) {
+            return Err(Error::InvalidNetworkTime(network_time.timestamp));
+        }
+
+        Ok(network_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::messaging::data::NETWORK_TIME_GRANULARITY;
+    use crate::types::{utils::random_bytes, ChunkAddress, JsonRepr};
+    use crate::url::Scope;
+    use eyre::Result;
+    use std::time::SystemTime;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn bundle_verifies_offline() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let address = client
+            .write_to_network(
+                random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES),
+                Scope::Public,
+            )
+            .await?;
+        let head = ChunkAddress(*address.name());
+
+        let bundle = run_w_backoff_delayed(|| client.get_data_proof_bundle(head), 10, 1).await?;
+
+        // Only the genesis key is needed, no connection to the network.
+        let serialised = bincode::serialize(&bundle)?;
+        let archived: super::DataProofBundle = bincode::deserialize(&serialised)?;
+        assert!(archived.verify(client.session.genesis_key()));
+        assert!(!archived.verify(&bls::SecretKey::random().public_key()));
+        assert_eq!(
+            super::DataProofBundle::from_json(&bundle.to_json()?)?,
+            bundle
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn network_time_is_close_to_ours() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let network_time = run_w_backoff_delayed(|| client.network_time(), 10, 1).await?;
+        let ours = SystemTime::now();
+        let drift = ours
+            .duration_since(network_time.time())
+            .unwrap_or_else(|err| err.duration());
+        assert!(drift < 3 * NETWORK_TIME_GRANULARITY);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::{Error, PublishVerdict, PublishedContent, Result};
+use crate::url::Scope;
+use bytes::{Buf, Bytes};
+use tracing::{debug, info};
+
+impl Client {
+    // Checks `data` may be published with the client's publish hook, if any, when it's to be
+    // stored as public data.
+    pub(super) async fn check_publish(&self, data: &Bytes, scope: Scope) -> Result<()> {
+        let hook = match (&self.publish_hook, scope) {
+            (Some(hook), Scope::Public) => hook.clone(),
+            _ => return Ok(()),
+        };
+        let content = PublishedContent {
+            size: data.len(),
+            publisher: self.public_key(),
+        };
+        debug!("Checking {:?} may be published", content);
+
+        let data = data.clone();
+        let verdict = tokio::task::spawn_blocking(move || hook.check(&content, &mut data.reader()))
+            .await
+            .map_err(|err| Error::Generic(format!("Checking the content failed: {}", err)))?;
+
+        match verdict {
+            PublishVerdict::Allow => Ok(()),
+            PublishVerdict::Reject(reason) => {
+                info!("Publishing content was refused: {}", reason);
+                Err(Error::PublishRejected(reason))
+            }
+        }
+    }
+
+    // Whether public data can only be published once checked, which requires it to be read
+    // in full beforehand.
+    pub(super) fn checks_publish(&self, scope: Scope) -> bool {
+        self.publish_hook.is_some() && scope == Scope::Public
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::client::{Error, PublishHook, PublishVerdict, PublishedContent};
+    use crate::types::utils::random_bytes;
+    use crate::url::Scope;
+    use eyre::{eyre, Result};
+    use std::{io::Read, sync::Arc};
+
+    // Rejects content larger than a given size, reading it all as a scanner would.
+    #[derive(Debug)]
+    struct SizeHook(usize);
+
+    impl PublishHook for SizeHook {
+        fn check(&self, content: &PublishedContent, reader: &mut dyn Read) -> PublishVerdict {
+            let mut read = Vec::new();
+            match reader.read_to_end(&mut read) {
+                Ok(size) if size == content.size && size <= self.0 => PublishVerdict::Allow,
+                Ok(_) => PublishVerdict::Reject("Content is too large".to_string()),
+                Err(error) => PublishVerdict::Reject(error.to_string()),
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn public_content_is_published_once_checked() -> Result<()> {
+        let max = self_encryption::MIN_ENCRYPTABLE_BYTES;
+        let client = create_test_client(None)
+            .await?
+            .with_publish_hook(Arc::new(SizeHook(max)));
+
+        let allowed = random_bytes(max);
+        let address = client
+            .write_to_network(allowed.clone(), Scope::Public)
+            .await?;
+        let read = run_w_backoff_delayed(|| client.read_blob(address), 10, 1).await?;
+        assert_eq!(read, allowed);
+
+        let rejected = random_bytes(max + 1);
+        match client
+            .write_to_network(rejected.clone(), Scope::Public)
+            .await
+        {
+            Err(Error::PublishRejected(_)) => {}
+            result => return Err(eyre!("Unexpected result: {:?}", result)),
+        }
+        // Private data isn't published, so isn't checked.
+        let _ = client.write_to_network(rejected, Scope::Private).await?;
+
+        Ok(())
+    }
+}
@@ -6,31 +6,120 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Client;
-use crate::client::{connections::QueryResult, errors::Error};
+use super::{Client, OperationKind, RetryPolicy};
+use crate::client::{
+    connections::{Budget, QueryResult},
+    errors::Error,
+};
 use crate::messaging::{
-    data::{DataQuery, ServiceMsg},
+    data::{DataQuery, QueryResponse, ServiceMsg},
     ServiceAuth, WireMsg,
 };
 use crate::types::{PublicKey, Signature};
 use bytes::Bytes;
+use tokio::time::{sleep, Instant};
 use tracing::debug;
 
 impl Client {
-    // Send a Query to the network and await a response.
-    // This function is a helper private to this module.
+    /// Send a query to the network as is, returning the response the Elders agreed on,
+    /// whichever it is, errors included.
+    ///
+    /// This is a low-level API for tools exercising the protocol directly, e.g. debuggers and
+    /// migration scripts. The query is signed by the client's signer, and subject to the
+    /// client's priority, concurrency limits and query timeout like any other, but the
+    /// response isn't checked to be of the variant matching the query, nor verified any further.
+    pub async fn send_raw_query(&self, query: DataQuery) -> Result<QueryResponse, Error> {
+        let query_result = self.send_query(query).await?;
+        Ok(query_result.response)
+    }
+
+    // Send a Query to the network and await a response, retrying as per the client's policy.
     pub(crate) async fn send_query(&self, query: DataQuery) -> Result<QueryResult, Error> {
+        self.send_query_with_policy(query, &self.retry_policy).await
+    }
+
+    // Send a Query to the network and await a response, retrying as per the given policy
+    // instead of the client's.
+    pub(crate) async fn send_query_with_policy(
+        &self,
+        query: DataQuery,
+        policy: &RetryPolicy,
+    ) -> Result<QueryResult, Error> {
         let client_pk = self.public_key();
         let msg = ServiceMsg::Query(query.clone());
         let serialised_query = WireMsg::serialize_msg_payload(&msg)?;
-        let signature = self.keypair.sign(&serialised_query);
-
-        tokio::time::timeout(
-            self.query_timeout,
-            self.send_signed_query(query, client_pk, serialised_query, signature),
-        )
-        .await
-        .map_err(|_| Error::NoResponse)?
+        let signature = self.signer.sign(&ServiceAuth::bytes_to_sign(
+            &serialised_query,
+            self.delegation.as_ref(),
+        )?)?;
+
+        // Time spent yielding to higher priority operations doesn't count towards the timeout.
+        let budget = match &query {
+            DataQuery::GetChunk(_) => Budget::ChunkReads,
+            DataQuery::Register(_) => Budget::RegisterOps,
+            _ => Budget::Unlimited,
+        };
+        let _ticket = self.session.ticket(self.priority, budget).await?;
+        let started = Instant::now();
+        let len = serialised_query.len();
+        let mut attempt = 1;
+        let result = loop {
+            let timeout = match &self.retry_budget {
+                Some(budget) => {
+                    budget.check_deadline()?;
+                    budget.bound(self.query_timeout)
+                }
+                None => self.query_timeout,
+            };
+            let result = tokio::time::timeout(
+                timeout,
+                self.send_signed_query(
+                    query.clone(),
+                    client_pk,
+                    serialised_query.clone(),
+                    signature.clone(),
+                ),
+            )
+            .await
+            .map_err(|_| {
+                self.session.health().query_timed_out();
+                Error::NoResponse
+            })
+            .and_then(|result| result);
+
+            match result {
+                Err(error) if policy.retries(attempt, &error) => {
+                    let delay = policy.delay(attempt);
+                    if let Some(budget) = &self.retry_budget {
+                        if !budget.take_retry(delay) {
+                            debug!("Retry budget spent, not retrying query: {:?}", error);
+                            break Err(error);
+                        }
+                    }
+                    debug!(
+                        "Query attempt {} of {} failed, retrying in {:?}: {:?}",
+                        attempt, policy.max_attempts, delay, error
+                    );
+                    self.session.health().query_retried();
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        };
+
+        if let (Some(recorder), Ok(query_result)) = (&self.stats, &result) {
+            recorder.sent(query_result.sent, len, false);
+            recorder.received(
+                bincode::serialized_size(&query_result.response).unwrap_or_default(),
+                query_result.responses,
+                matches!(query_result.response, QueryResponse::GetChunk(Ok(_))),
+            );
+        }
+
+        let elapsed = result.as_ref().ok().map(|_| started.elapsed());
+        self.latency.record(OperationKind::Read, elapsed).await;
+        result
     }
 
     /// Send a Query to the network and await a response
@@ -47,8 +136,17 @@ impl Client {
         let auth = ServiceAuth {
             public_key: client_pk,
             signature,
+            delegation: self.delegation.clone(),
         };
 
-        self.session.send_query(query, auth, serialised_query).await
+        self.session
+            .send_query(
+                query,
+                auth,
+                serialised_query,
+                self.trace_queries,
+                self.read_consistency,
+            )
+            .await
     }
 }
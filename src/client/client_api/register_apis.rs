@@ -6,20 +6,78 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Client;
-use crate::client::Error;
+use super::{
+    limits_apis::{check_batch_len, check_entry_size},
+    Client,
+};
+use crate::client::{CmdHandle, Error};
 use crate::messaging::data::{DataCmd, DataQuery, QueryResponse, RegisterRead, RegisterWrite};
 use crate::types::{
     register::{
-        Address, Entry, EntryHash, Permissions, Policy, PrivatePermissions, PrivatePolicy,
-        PublicPermissions, PublicPolicy, Register, User,
+        Address, Entry, EntryHash, OwnershipTransfer, Permissions, Policy, PolicyTemplate,
+        PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, Register, RegisterDag,
+        User,
     },
-    PublicKey,
+    DataAddress, PublicKey,
 };
+use crate::url::Url;
+use futures::future::join_all;
 use std::collections::{BTreeMap, BTreeSet};
 use tracing::{debug, trace};
 use xor_name::XorName;
 
+/// What a Register to be created by [`Client::create_registers`] is to be like.
+#[derive(Clone, Debug)]
+pub enum RegisterSpec {
+    /// A Private Register, as created by [`Client::store_private_register`].
+    Private {
+        /// Name of the Register.
+        name: XorName,
+        /// Tag of the Register.
+        tag: u64,
+        /// Owner of the Register.
+        owner: PublicKey,
+        /// Permissions of the users of the Register.
+        permissions: BTreeMap<PublicKey, PrivatePermissions>,
+    },
+    /// A Public Register, as created by [`Client::store_public_register`].
+    Public {
+        /// Name of the Register.
+        name: XorName,
+        /// Tag of the Register.
+        tag: u64,
+        /// Owner of the Register.
+        owner: PublicKey,
+        /// Permissions of the users of the Register.
+        permissions: BTreeMap<User, PublicPermissions>,
+    },
+}
+
+impl RegisterSpec {
+    /// A Register owned by `owner`, with the policy of the given template.
+    pub fn from_template(
+        name: XorName,
+        tag: u64,
+        owner: PublicKey,
+        template: &PolicyTemplate,
+    ) -> Self {
+        match template.policy(owner) {
+            Policy::Private(policy) => RegisterSpec::Private {
+                name,
+                tag,
+                owner,
+                permissions: policy.permissions,
+            },
+            Policy::Public(policy) => RegisterSpec::Public {
+                name,
+                tag,
+                owner,
+                permissions: policy.permissions,
+            },
+        }
+    }
+}
+
 impl Client {
     //----------------------
     // Write Operations
@@ -45,7 +103,8 @@ impl Client {
         let priv_register = Register::new_private(pk, name, tag, Some(policy));
         let address = *priv_register.address();
 
-        self.pay_and_write_register_to_network(priv_register)
+        let _ = self
+            .pay_and_write_register_to_network(priv_register)
             .await?;
 
         Ok(address)
@@ -71,17 +130,89 @@ impl Client {
         let pub_register = Register::new_public(pk, name, tag, Some(policy));
         let address = *pub_register.address();
 
-        self.pay_and_write_register_to_network(pub_register).await?;
+        let _ = self.pay_and_write_register_to_network(pub_register).await?;
+
+        Ok(address)
+    }
+
+    /// Create a Register onto the Network, with the policy of the given template.
+    ///
+    /// Whether the Register is private or public is up to the template.
+    pub async fn store_register_from_template(
+        &self,
+        name: XorName,
+        tag: u64,
+        owner: PublicKey,
+        template: &PolicyTemplate,
+    ) -> Result<Address, Error> {
+        trace!("Store {} Register data {:?}", template, name);
+        let pk = self.public_key();
+        let register = match template.policy(owner) {
+            Policy::Private(policy) => Register::new_private(pk, name, tag, Some(policy)),
+            Policy::Public(policy) => Register::new_public(pk, name, tag, Some(policy)),
+        };
+        let address = *register.address();
+
+        let _ = self.pay_and_write_register_to_network(register).await?;
 
         Ok(address)
     }
 
+    /// Create many Registers at once, e.g. the root, indexes and mailboxes of an application
+    /// on its first run.
+    ///
+    /// The creation commands are all sent concurrently rather than one after the other.
+    /// Results are returned in the order of `specs`, a failure to create one Register
+    /// not affecting the creation of the others.
+    pub async fn create_registers(
+        &self,
+        specs: impl IntoIterator<Item = RegisterSpec>,
+    ) -> Vec<Result<Address, Error>> {
+        let pk = self.public_key();
+        let registers = specs.into_iter().map(|spec| match spec {
+            RegisterSpec::Private {
+                name,
+                tag,
+                owner,
+                permissions,
+            } => {
+                let policy = PrivatePolicy { owner, permissions };
+                Register::new_private(pk, name, tag, Some(policy))
+            }
+            RegisterSpec::Public {
+                name,
+                tag,
+                owner,
+                permissions,
+            } => {
+                let policy = PublicPolicy { owner, permissions };
+                Register::new_public(pk, name, tag, Some(policy))
+            }
+        });
+
+        let tasks = registers.map(|register| async move {
+            let address = *register.address();
+            self.pay_and_write_register_to_network(register)
+                .await
+                .map(|_| address)
+        });
+
+        let results = join_all(tasks).await;
+        debug!(
+            "Created {} of {} Registers",
+            results.iter().filter(|result| result.is_ok()).count(),
+            results.len()
+        );
+        results
+    }
+
     /// Delete Register
     ///
     /// You're only able to delete a PrivateRegister. Public data can no be removed from the network.
     pub async fn delete_register(&self, address: Address) -> Result<(), Error> {
         let cmd = DataCmd::Register(RegisterWrite::Delete(address));
-        self.send_cmd(cmd).await
+        let _ = self.send_cmd(cmd).await?;
+        Ok(())
     }
 
     /// Write to Register
@@ -94,6 +225,8 @@ impl Client {
         entry: Entry,
         children: BTreeSet<EntryHash>,
     ) -> Result<EntryHash, Error> {
+        check_entry_size(&entry, &self.upload_limits().await)?;
+
         // First we fetch it so we can get the causality info,
         // either from local CRDT replica or from the network if not found
         let mut register = self.get_register(address).await?;
@@ -101,26 +234,105 @@ impl Client {
         // We can now write the entry to the Register
         let (hash, mut op) = register.write(entry, children)?;
         let bytes = bincode::serialize(&op.crdt_op)?;
-        let signature = self.keypair.sign(&bytes);
+        let signature = self.signer.sign(&bytes)?;
         op.signature = Some(signature);
 
         // Finally we can send the mutation to the network's replicas
         let cmd = DataCmd::Register(RegisterWrite::Edit(op));
-        self.send_cmd(cmd).await?;
+        let _ = self.send_cmd(cmd).await?;
 
         Ok(hash)
     }
 
+    /// Write several entries to a Register with a single command, e.g. to append many messages
+    /// of a chat log at once, returning the hashes of the entries in the order given.
+    ///
+    /// The entries are chained: the first one is written with `children` as its children, as
+    /// per [`Client::write_to_register`], and each of the others with the previous one as its
+    /// only child. The network applies them all, or none of them if any can't be.
+    ///
+    /// Batches of more entries than the network accepts, as per
+    /// [`DataLimits`](crate::messaging::data::DataLimits), are refused with
+    /// [`Error::TooManyOps`].
+    pub async fn write_to_register_batch(
+        &self,
+        address: Address,
+        entries: Vec<Entry>,
+        children: BTreeSet<EntryHash>,
+    ) -> Result<Vec<EntryHash>, Error> {
+        let limits = self.upload_limits().await;
+        check_batch_len(entries.len(), &limits)?;
+        for entry in &entries {
+            check_entry_size(entry, &limits)?;
+        }
+        if entries.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut register = self.get_register(address).await?;
+
+        let mut hashes = Vec::with_capacity(entries.len());
+        let mut ops = Vec::with_capacity(entries.len());
+        let mut children = children;
+        for entry in entries {
+            let (hash, mut op) = register.write(entry, children)?;
+            let bytes = bincode::serialize(&op.crdt_op)?;
+            op.signature = Some(self.signer.sign(&bytes)?);
+            ops.push(op);
+            hashes.push(hash);
+            children = std::iter::once(hash).collect();
+        }
+        trace!(
+            "Writing {} entries to Register at {:?} in one batch",
+            ops.len(),
+            address
+        );
+
+        let cmd = DataCmd::Register(RegisterWrite::EditBatch { address, ops });
+        let _ = self.send_cmd(cmd).await?;
+
+        Ok(hashes)
+    }
+
+    /// Transfer the ownership of a Register to `new_owner`
+    ///
+    /// Only the current owner can transfer the ownership, after which they lose their
+    /// owner rights over the Register. Containers built on top of Registers are transferred
+    /// by transferring each of the Registers they are made of.
+    pub async fn transfer_register_ownership(
+        &self,
+        address: Address,
+        new_owner: PublicKey,
+    ) -> Result<(), Error> {
+        debug!(
+            "Transferring ownership of Register at {:?} to {:?}",
+            address, new_owner
+        );
+        // The transfer is bound to the current position in the Register's policy history
+        let register = self.get_register(address).await?;
+        let index = register.policy_history(Some(self.public_key()))?.len() as u64;
+        let transfer = OwnershipTransfer::new(address, index, new_owner, self.signer.as_ref())?;
+
+        let cmd = DataCmd::Register(RegisterWrite::TransferOwnership(transfer));
+        let _ = self.send_cmd(cmd).await?;
+        Ok(())
+    }
+
     /// Store a new Register data object
-    /// Wraps msg_contents for payment validation and mutation
+    /// Wraps msg_contents for payment validation and mutation,
+    /// and adds the Register to the catalog of the data the client owns.
+    /// Returns the handle of the command storing it.
     pub(crate) async fn pay_and_write_register_to_network(
         &self,
         data: Register,
-    ) -> Result<(), Error> {
+    ) -> Result<CmdHandle, Error> {
         debug!("Attempting to pay and write a Register to the network");
 
+        let address = DataAddress::Register(*data.address());
         let cmd = DataCmd::Register(RegisterWrite::New(data));
-        self.send_cmd(cmd).await
+        let handle = self.send_cmd(cmd).await?;
+        self.catalog(address).await;
+        Ok(handle)
     }
 
     //----------------------
@@ -177,6 +389,61 @@ impl Client {
         Ok(entry.to_owned())
     }
 
+    /// Get the entry at a specific version of a Register's history from the Network.
+    ///
+    /// The first entry ever written to the Register is version 0.
+    pub async fn get_register_entry_by_version(
+        &self,
+        address: Address,
+        version: u64,
+    ) -> Result<(EntryHash, Entry), Error> {
+        trace!(
+            "Get entry at version {} from Register data {:?}",
+            version,
+            address.name()
+        );
+
+        let register = self.get_register(address).await?;
+        let (hash, entry) = register
+            .get_by_version(version, None)?
+            .ok_or_else(|| Error::from(crate::types::Error::NoSuchEntry))?;
+
+        Ok((hash, entry.to_owned()))
+    }
+
+    /// Get the graph of a Register's history from the Network, e.g. for applications to show
+    /// users the branches concurrent writes created, and which entries they must merge.
+    pub async fn get_register_dag(&self, address: Address) -> Result<RegisterDag, Error> {
+        trace!("Get history graph of Register data {:?}", address.name());
+
+        let register = self.get_register(address).await?;
+        let dag = register.dag(None)?;
+
+        Ok(dag)
+    }
+
+    /// Read the entries a Register's Safe URL resolves to.
+    ///
+    /// If the URL pins a version, with either `?v=<N>` or `?v=<VersionHash>`, only that
+    /// entry is returned, otherwise the last entry, or entries when there are branches.
+    pub async fn read_register_at_url(
+        &self,
+        url: &Url,
+    ) -> Result<BTreeSet<(EntryHash, Entry)>, Error> {
+        let address = url.register_address()?;
+
+        if let Some(version) = url.content_version_number() {
+            let entry = self.get_register_entry_by_version(address, version).await?;
+            Ok(vec![entry].into_iter().collect())
+        } else if let Some(version) = url.content_version() {
+            let hash = version.entry_hash();
+            let entry = self.get_register_entry(address, hash).await?;
+            Ok(vec![(hash, entry)].into_iter().collect())
+        } else {
+            self.read_register(address).await
+        }
+    }
+
     //----------------------
     // Ownership
     //---------------------
@@ -221,10 +488,39 @@ impl Client {
 
         Ok(policy.clone())
     }
+
+    /// Get the template the Policy of a Register is equivalent to, if any.
+    ///
+    /// Use [`Policy::describe`] and [`Policy::validate`] on [`Client::get_register_policy`]
+    /// to inspect Registers whose Policy doesn't match any template.
+    pub async fn get_register_policy_template(
+        &self,
+        address: Address,
+    ) -> Result<Option<PolicyTemplate>, Error> {
+        let policy = self.get_register_policy(address).await?;
+        Ok(PolicyTemplate::of(&policy))
+    }
+
+    /// Get the policies a Register had before its current one, e.g. its previous owners.
+    pub async fn get_register_policy_history(
+        &self,
+        address: Address,
+    ) -> Result<Vec<Policy>, Error> {
+        trace!(
+            "Get Policy history from Register data at {:?}",
+            address.name()
+        );
+
+        let register = self.get_register(address).await?;
+        let history = register.policy_history(Some(self.public_key()))?;
+
+        Ok(history.to_vec())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::RegisterSpec;
     use crate::messaging::data::Error as ErrorMessage;
     use crate::retry_loop_for_pattern;
     use crate::types::{
@@ -329,6 +625,47 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn create_many_registers() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let owner = client.public_key();
+
+        let mut private_perms = BTreeMap::<PublicKey, PrivatePermissions>::new();
+        let _ = private_perms.insert(owner, PrivatePermissions::new(true, true));
+        let mut public_perms = BTreeMap::<User, PublicPermissions>::new();
+        let _ = public_perms.insert(User::Anyone, PublicPermissions::new(true));
+
+        let specs = (0..5).map(|i| {
+            if i % 2 == 0 {
+                RegisterSpec::Private {
+                    name: XorName::random(),
+                    tag: 15000,
+                    owner,
+                    permissions: private_perms.clone(),
+                }
+            } else {
+                RegisterSpec::Public {
+                    name: XorName::random(),
+                    tag: 15000,
+                    owner,
+                    permissions: public_perms.clone(),
+                }
+            }
+        });
+
+        let results = client.create_registers(specs).await;
+        assert_eq!(results.len(), 5);
+
+        for (i, result) in results.into_iter().enumerate() {
+            let address = result?;
+            let register = run_w_backoff_delayed(|| client.get_register(address), 10, 1).await?;
+            assert_eq!(register.is_private(), i % 2 == 0);
+            assert_eq!(register.owner(), owner);
+        }
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn register_private_permissions() -> Result<()> {
         let client = create_test_client(None).await?;
@@ -483,6 +820,45 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_batch_write() -> Result<()> {
+        let name = XorName(rand::random());
+        let tag = 10;
+        let client = create_test_client(None).await?;
+
+        let owner = client.public_key();
+        let mut perms = BTreeMap::<User, PublicPermissions>::new();
+        let _ = perms.insert(User::Key(owner), PublicPermissions::new(true));
+
+        let address = client
+            .store_public_register(name, tag, owner, perms)
+            .await?;
+
+        let values = vec![random_url()?, random_url()?, random_url()?];
+        let hashes = run_w_backoff_delayed(
+            || client.write_to_register_batch(address, values.clone(), BTreeSet::new()),
+            10,
+            1,
+        )
+        .await?;
+        assert_eq!(hashes.len(), values.len());
+
+        // The entries are chained, so only the last one is left as the latest.
+        let latest = retry_loop_for_pattern!(client.read_register(address), Ok(latest) if !latest.is_empty())?;
+        assert_eq!(
+            latest,
+            vec![(hashes[2], values[2].clone())].into_iter().collect()
+        );
+
+        for (hash, value) in hashes.iter().zip(&values) {
+            let retrieved =
+                run_w_backoff_delayed(|| client.get_register_entry(address, *hash), 10, 1).await?;
+            assert_eq!(&retrieved, value);
+        }
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn register_owner() -> Result<()> {
         let name = XorName(rand::random());
@@ -0,0 +1,403 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{limits_apis::check_entry_size, Client};
+use crate::client::Result;
+use crate::messaging::data::{DataCmd, RegisterWrite};
+use crate::types::register::{Address, Entry, EntryHash, RegisterOp};
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex, PoisonError, Weak},
+    time::Duration,
+};
+use tokio::sync::{Mutex as AsyncMutex, Notify};
+use tracing::{debug, warn};
+
+/// Counts of the writes made through a [`CoalescingRegisterWriter`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CoalescingStats {
+    /// Number of entries written.
+    pub writes: u64,
+    /// Number of entries superseded by a later one before being sent, and never sent.
+    pub coalesced: u64,
+    /// Number of operations sent to the network.
+    pub ops_sent: u64,
+    /// What the last send failed with, if it did.
+    pub last_error: Option<String>,
+}
+
+/// Writes to a Register which is updated many times a second, e.g. with cursor positions or
+/// autosaves, sending at most one operation to the network per window.
+///
+/// The first write is sent right away. Those made within the window after it are coalesced:
+/// only the last of them is sent, once the window is over, as an entry superseding all the
+/// Register's current entries, including those we wrote before. So the Register ends up with
+/// the last entry written, as it would have had every write been sent, for a fraction of the
+/// operations. Entries still to be sent when the writer is dropped are sent in the background,
+/// and those still to be sent when the client is closed, before [`Client::close`] cuts off
+/// operations.
+#[derive(Debug)]
+pub struct CoalescingRegisterWriter {
+    client: Client,
+    address: Address,
+    shared: Arc<Shared>,
+}
+
+/// The coalescing writers of a client's session still in use, for their entries to be sent
+/// before the session is closed.
+#[derive(Debug, Default)]
+pub(super) struct LiveWriters(Mutex<Vec<Weak<Shared>>>);
+
+impl LiveWriters {
+    fn add(&self, shared: &Arc<Shared>) {
+        let mut writers = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        writers.retain(|writer| writer.strong_count() > 0);
+        writers.push(Arc::downgrade(shared));
+    }
+
+    /// Sends the entries the live writers hold back, one writer after the other.
+    pub(super) async fn flush(&self, client: &Client) {
+        let writers: Vec<_> = self
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+        for shared in writers {
+            if let Err(err) = send_staged(client, shared.address, &shared).await {
+                warn!(
+                    "Failed to send the last entry written to Register {:?}: {:?}",
+                    shared.address, err
+                );
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    address: Address,
+    state: Mutex<WriterState>,
+    // Wakes the background task up on entries being staged, or the writer being dropped.
+    notify: Notify,
+    // Held while sending, so operations are sent one at a time, with the last one sent.
+    last_op: AsyncMutex<Option<RegisterOp<Entry>>>,
+}
+
+#[derive(Debug, Default)]
+struct WriterState {
+    // The last entry written, if it's not been sent yet.
+    staged: Option<Entry>,
+    stats: CoalescingStats,
+    closed: bool,
+}
+
+impl CoalescingRegisterWriter {
+    /// Address of the Register written to.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Write an entry to the Register, superseding any written before it which hasn't been
+    /// sent yet. It's sent once the current window is over.
+    pub fn write(&self, entry: Entry) -> Result<()> {
+        check_entry_size(&entry, &self.client.known_upload_limits())?;
+
+        let mut state = self
+            .shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        state.stats.writes += 1;
+        if state.staged.replace(entry).is_some() {
+            state.stats.coalesced += 1;
+        }
+        drop(state);
+
+        self.shared.notify.notify_one();
+        Ok(())
+    }
+
+    /// Send the last entry written right away, rather than at the end of the current window,
+    /// returning its hash. Returns `None` if there was no entry left to send.
+    pub async fn flush(&self) -> Result<Option<EntryHash>> {
+        send_staged(&self.client, self.address, &self.shared).await
+    }
+
+    /// Returns the counts of the writes made so far.
+    pub fn stats(&self) -> CoalescingStats {
+        let state = self
+            .shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        state.stats.clone()
+    }
+}
+
+impl Drop for CoalescingRegisterWriter {
+    fn drop(&mut self) {
+        let mut state = self
+            .shared
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        state.closed = true;
+        drop(state);
+        self.shared.notify.notify_one();
+    }
+}
+
+impl Client {
+    /// Create a writer to the Register at `address` coalescing the entries written within
+    /// `window` of each other into a single operation, as per [`CoalescingRegisterWriter`].
+    pub fn coalescing_register_writer(
+        &self,
+        address: Address,
+        window: Duration,
+    ) -> CoalescingRegisterWriter {
+        let shared = Arc::new(Shared {
+            address,
+            state: Mutex::new(WriterState::default()),
+            notify: Notify::new(),
+            last_op: AsyncMutex::new(None),
+        });
+        self.coalescing_writers.add(&shared);
+
+        let client = self.clone();
+        let task_shared = shared.clone();
+        let _ = self.session.spawn("coalesce_register_writes", async move {
+            send_in_background(client, address, task_shared, window).await
+        });
+
+        CoalescingRegisterWriter {
+            client: self.clone(),
+            address,
+            shared,
+        }
+    }
+}
+
+async fn send_in_background(
+    client: Client,
+    address: Address,
+    shared: Arc<Shared>,
+    window: Duration,
+) {
+    loop {
+        shared.notify.notified().await;
+        let closed = {
+            let state = shared.state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.closed
+        };
+
+        let result = send_staged(&client, address, &shared).await;
+        if closed {
+            if let Err(err) = result {
+                warn!(
+                    "Failed to send the last entry written to Register {:?}: {:?}",
+                    address, err
+                );
+            }
+            debug!("Writer to Register {:?} dropped, stopping", address);
+            break;
+        }
+        if let Err(err) = result {
+            warn!(
+                "Failed to send entry to Register {:?}, retrying: {:?}",
+                address, err
+            );
+            // The entry was staged again, to be retried once the window is over.
+            shared.notify.notify_one();
+        }
+
+        // Writes made in the meantime will have left a permit to send them right after.
+        tokio::time::sleep(window).await;
+    }
+}
+
+// Sends the staged entry, if any, restaging it if sending fails and none was written since.
+async fn send_staged(
+    client: &Client,
+    address: Address,
+    shared: &Shared,
+) -> Result<Option<EntryHash>> {
+    let mut last_op = shared.last_op.lock().await;
+    let entry = {
+        let mut state = shared.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match state.staged.take() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        }
+    };
+
+    let result = write_superseding(client, address, entry.clone(), last_op.as_ref()).await;
+
+    let mut state = shared.state.lock().unwrap_or_else(PoisonError::into_inner);
+    match result {
+        Ok(op) => {
+            let hash = op.crdt_op.hash();
+            *last_op = Some(op);
+            state.stats.ops_sent += 1;
+            state.stats.last_error = None;
+            Ok(Some(hash))
+        }
+        Err(err) => {
+            if state.staged.is_none() {
+                state.staged = Some(entry);
+            } else {
+                state.stats.coalesced += 1;
+            }
+            state.stats.last_error = Some(err.to_string());
+            Err(err)
+        }
+    }
+}
+
+// Writes an entry with all the Register's current entries as children, those we wrote before
+// included, even if the network hasn't stored them yet.
+async fn write_superseding(
+    client: &Client,
+    address: Address,
+    entry: Entry,
+    last_op: Option<&RegisterOp<Entry>>,
+) -> Result<RegisterOp<Entry>> {
+    let mut register = client.get_register(address).await?;
+    if let Some(op) = last_op {
+        if register.get(op.crdt_op.hash(), None)?.is_none() {
+            register.apply_op(op.clone())?;
+        }
+    }
+
+    let children: BTreeSet<_> = register
+        .read(None)?
+        .into_iter()
+        .map(|(hash, _)| hash)
+        .collect();
+    let (_, mut op) = register.write(entry, children)?;
+    let bytes = bincode::serialize(&op.crdt_op)?;
+    op.signature = Some(client.signer.sign(&bytes)?);
+
+    let _ = client
+        .send_cmd(DataCmd::Register(RegisterWrite::Edit(op.clone())))
+        .await?;
+    Ok(op)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::client::Error;
+    use crate::types::register::{PublicPermissions, User};
+    use crate::url::{ContentType, Scope, Url, XorUrlBase};
+    use eyre::{eyre, Result};
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rapid_writes_are_coalesced() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let owner = client.public_key();
+        let mut perms = BTreeMap::new();
+        let _ = perms.insert(User::Key(owner), PublicPermissions::new(true));
+        let address = client
+            .store_public_register(XorName::random(), 15000, owner, perms)
+            .await?;
+        let _ = run_w_backoff_delayed(|| client.get_register(address), 10, 1).await?;
+
+        let writer = client.coalescing_register_writer(address, Duration::from_secs(60));
+        let mut last = None;
+        for _ in 0..20 {
+            let entry = Url::from_url(&Url::encode_blob(
+                XorName::random(),
+                Scope::Public,
+                ContentType::Raw,
+                XorUrlBase::Base32z,
+            )?)?;
+            writer.write(entry.clone())?;
+            last = Some(entry);
+        }
+        let last = last.ok_or_else(|| eyre!("No entry written"))?;
+        let hash = writer
+            .flush()
+            .await?
+            .ok_or_else(|| eyre!("No entry left to send"))?;
+
+        let stats = writer.stats();
+        assert_eq!(stats.writes, 20);
+        assert!(stats.ops_sent <= 2);
+        assert_eq!(stats.coalesced + stats.ops_sent, stats.writes);
+
+        // Only the last entry is left, superseding any sent before it.
+        let entries = run_w_backoff_delayed(
+            || async {
+                let entries = client.read_register(address).await?;
+                if entries.iter().any(|(entry_hash, _)| *entry_hash == hash) {
+                    Ok(entries)
+                } else {
+                    Err(Error::Generic("Last entry not stored yet".to_string()))
+                }
+            },
+            10,
+            1,
+        )
+        .await?;
+        assert_eq!(entries, vec![(hash, last)].into_iter().collect());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn entries_held_back_are_sent_on_close() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let owner = client.public_key();
+        let mut perms = BTreeMap::new();
+        let _ = perms.insert(User::Key(owner), PublicPermissions::new(true));
+        let address = client
+            .store_public_register(XorName::random(), 15000, owner, perms)
+            .await?;
+        let _ = run_w_backoff_delayed(|| client.get_register(address), 10, 1).await?;
+
+        let writer = client.coalescing_register_writer(address, Duration::from_secs(600));
+        let mut last = None;
+        for _ in 0..2 {
+            let entry = Url::from_url(&Url::encode_blob(
+                XorName::random(),
+                Scope::Public,
+                ContentType::Raw,
+                XorUrlBase::Base32z,
+            )?)?;
+            writer.write(entry.clone())?;
+            last = Some(entry);
+        }
+        let last = last.ok_or_else(|| eyre!("No entry written"))?;
+
+        // The second entry is held back for the window, but closing doesn't wait for it.
+        client.close(Duration::from_secs(30)).await?;
+        assert_eq!(writer.stats().ops_sent, 2);
+
+        let reader = create_test_client(None).await?;
+        let _ = run_w_backoff_delayed(
+            || async {
+                let entries = reader.read_register(address).await?;
+                if entries.iter().any(|(_, entry)| *entry == last) {
+                    Ok(entries)
+                } else {
+                    Err(Error::Generic("Last entry not stored yet".to_string()))
+                }
+            },
+            10,
+            1,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{limits_apis::check_entry_size, Client};
+use crate::client::Result;
+use crate::messaging::data::{DataCmd, RegisterWrite};
+use crate::types::register::{Address, Entry, EntryHash, Register, RegisterOp};
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, PoisonError, RwLock, Weak},
+    time::{Duration, SystemTime},
+};
+use tracing::{debug, warn};
+
+/// State of a local Register replica's sync with the network.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SyncStatus {
+    /// Number of local writes not yet seen stored on the network.
+    pub pending_ops: usize,
+    /// When the replica last synced successfully with the network.
+    pub last_synced: Option<SystemTime>,
+    /// What the last sync failed with, if it did.
+    pub last_error: Option<String>,
+}
+
+/// A local replica of a Register, which is written to and read from synchronously, in memory,
+/// while a background task syncs it with the network.
+///
+/// Local writes are sent to the network, and writes made by others are merged in, every sync.
+/// As Registers are CRDTs, the merged state is conflict-free: concurrent writes show up as
+/// multiple entries when reading, to be resolved by the application writing a new entry.
+/// The background task stops once the replica is dropped.
+#[derive(Debug)]
+pub struct LocalRegisterReplica {
+    client: Client,
+    address: Address,
+    state: Arc<RwLock<ReplicaState>>,
+}
+
+#[derive(Debug)]
+struct ReplicaState {
+    register: Register,
+    // Local writes, in the order they were made, not yet seen stored on the network.
+    pending: Vec<RegisterOp<Entry>>,
+    status: SyncStatus,
+}
+
+impl LocalRegisterReplica {
+    /// Address of the Register replicated.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Write an entry to the replica, to be sent to the network upon the next sync.
+    pub fn write(&self, entry: Entry, children: BTreeSet<EntryHash>) -> Result<EntryHash> {
+        check_entry_size(&entry, &self.client.known_upload_limits())?;
+
+        let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+        let (hash, mut op) = state.register.write(entry, children)?;
+        let bytes = bincode::serialize(&op.crdt_op)?;
+        op.signature = Some(self.client.signer.sign(&bytes)?);
+
+        state.pending.push(op);
+        state.status.pending_ops = state.pending.len();
+        Ok(hash)
+    }
+
+    /// Read the last entry, or entries if there are branches, of the replica.
+    pub fn read(&self) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+        Ok(state.register.read(None)?)
+    }
+
+    /// Returns a copy of the replica's current state.
+    pub fn register(&self) -> Register {
+        let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+        state.register.clone()
+    }
+
+    /// Returns the state of the replica's sync with the network.
+    pub fn sync_status(&self) -> SyncStatus {
+        let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+        state.status.clone()
+    }
+
+    /// Sync the replica with the network right away, rather than waiting for the background task.
+    pub async fn sync(&self) -> Result<()> {
+        sync(&self.client, self.address, &self.state).await
+    }
+}
+
+impl Client {
+    /// Create a local replica of the Register at `address`, synced with the network
+    /// every `sync_interval` in the background.
+    pub async fn local_register_replica(
+        &self,
+        address: Address,
+        sync_interval: Duration,
+    ) -> Result<LocalRegisterReplica> {
+        let register = self.get_register(address).await?;
+        let state = Arc::new(RwLock::new(ReplicaState {
+            register,
+            pending: Vec::new(),
+            status: SyncStatus {
+                last_synced: Some(SystemTime::now()),
+                ..SyncStatus::default()
+            },
+        }));
+
+        let client = self.clone();
+        let weak_state = Arc::downgrade(&state);
+        let _ = self.session.spawn("sync_register_replica", async move {
+            sync_in_background(client, address, weak_state, sync_interval).await
+        });
+
+        Ok(LocalRegisterReplica {
+            client: self.clone(),
+            address,
+            state,
+        })
+    }
+}
+
+async fn sync_in_background(
+    client: Client,
+    address: Address,
+    state: Weak<RwLock<ReplicaState>>,
+    sync_interval: Duration,
+) {
+    loop {
+        tokio::time::sleep(sync_interval).await;
+        let state = match state.upgrade() {
+            Some(state) => state,
+            None => {
+                debug!("Replica of Register {:?} dropped, stopping sync", address);
+                break;
+            }
+        };
+        if let Err(err) = sync(&client, address, &state).await {
+            warn!(
+                "Failed to sync replica of Register {:?}: {:?}",
+                address, err
+            );
+        }
+    }
+}
+
+// Sends the pending local writes to the network, then merges them into the network's state.
+async fn sync(client: &Client, address: Address, state: &RwLock<ReplicaState>) -> Result<()> {
+    let result = push_and_merge(client, address, state).await;
+
+    let mut state = state.write().unwrap_or_else(PoisonError::into_inner);
+    match &result {
+        Ok(()) => {
+            state.status.last_synced = Some(SystemTime::now());
+            state.status.last_error = None;
+        }
+        Err(err) => state.status.last_error = Some(err.to_string()),
+    }
+    result
+}
+
+async fn push_and_merge(
+    client: &Client,
+    address: Address,
+    state: &RwLock<ReplicaState>,
+) -> Result<()> {
+    let pending = {
+        let state = state.read().unwrap_or_else(PoisonError::into_inner);
+        state.pending.clone()
+    };
+    // Ops are idempotent, so those which had already been sent and just
+    // weren't stored yet as of the last sync can be sent again.
+    for op in pending {
+        let _ = client
+            .send_cmd(DataCmd::Register(RegisterWrite::Edit(op)))
+            .await?;
+    }
+
+    let mut register = client.get_register(address).await?;
+
+    // Writes made while we were talking to the network are pending too, so they're
+    // merged into the network's state along with the ones just sent.
+    let mut state = state.write().unwrap_or_else(PoisonError::into_inner);
+    let mut still_pending = Vec::new();
+    for op in state.pending.drain(..) {
+        let hash = op.crdt_op.hash();
+        if register.get(hash, None)?.is_some() {
+            continue;
+        }
+        match register.apply_op(op.clone()) {
+            Ok(()) => still_pending.push(op),
+            Err(err) => warn!(
+                "Dropping local write {:?} to Register {:?}: {:?}",
+                hash, address, err
+            ),
+        }
+    }
+
+    state.register = register;
+    state.status.pending_ops = still_pending.len();
+    state.pending = still_pending;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::types::register::{PublicPermissions, User};
+    use crate::url::{ContentType, Scope, Url, XorUrlBase};
+    use eyre::Result;
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::time::Duration;
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn local_writes_sync_to_network() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let owner = client.public_key();
+        let mut perms = BTreeMap::new();
+        let _ = perms.insert(User::Key(owner), PublicPermissions::new(true));
+        let address = client
+            .store_public_register(XorName::random(), 15000, owner, perms)
+            .await?;
+
+        let replica = run_w_backoff_delayed(
+            || client.local_register_replica(address, Duration::from_secs(60)),
+            10,
+            1,
+        )
+        .await?;
+
+        let entry = Url::from_url(&Url::encode_blob(
+            XorName::random(),
+            Scope::Public,
+            ContentType::Raw,
+            XorUrlBase::Base32z,
+        )?)?;
+        let hash = replica.write(entry.clone(), BTreeSet::new())?;
+        assert_eq!(
+            replica.read()?,
+            vec![(hash, entry.clone())].into_iter().collect()
+        );
+        assert_eq!(replica.sync_status().pending_ops, 1);
+
+        // Synced once the network has stored the write.
+        loop {
+            replica.sync().await?;
+            if replica.sync_status().pending_ops == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+
+        let entries = client.read_register(address).await?;
+        assert_eq!(entries, vec![(hash, entry)].into_iter().collect());
+        Ok(())
+    }
+}
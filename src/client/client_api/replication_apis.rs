@@ -0,0 +1,187 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{BlobAddress, Client};
+use crate::client::Error;
+use crate::messaging::data::{ChunkHolders, DataQuery, QueryResponse};
+use crate::types::ChunkAddress;
+use futures::future::{join, join_all};
+use rand::seq::SliceRandom;
+use std::collections::BTreeSet;
+use tracing::{trace, warn};
+
+/// How well one of the chunks sampled by [`Client::sample_replication`] is replicated.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChunkReplication {
+    /// Address of the chunk.
+    pub address: ChunkAddress,
+    /// The Adults vouched for holding it, or `None` if they couldn't be retrieved.
+    pub holders: Option<ChunkHolders>,
+    /// Number of copies the section responsible for it keeps of each chunk,
+    /// or `None` if it couldn't be retrieved.
+    pub replication_factor: Option<usize>,
+}
+
+impl ChunkReplication {
+    /// Share of the copies the section is meant to keep which it vouched for,
+    /// between 0 and 1. Chunks whose holders couldn't be retrieved count as not replicated.
+    pub fn ratio(&self) -> f64 {
+        match (&self.holders, self.replication_factor) {
+            (Some(holders), Some(factor)) if factor > 0 => {
+                holders.holders.len().min(factor) as f64 / factor as f64
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// An estimate of how well a blob is replicated, from a random sample of its chunks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplicationHealth {
+    /// Number of distinct chunks the blob is made of, including its head chunk and those of
+    /// the additional levels its data map was self-encrypted over, if any.
+    pub chunk_count: usize,
+    /// The chunks sampled.
+    pub samples: Vec<ChunkReplication>,
+}
+
+impl ReplicationHealth {
+    /// Confidence the blob is fully replicated, between 0 and 1: the average share of the
+    /// copies meant to be kept of the sampled chunks which the network vouched for.
+    ///
+    /// As only a sample of the chunks is checked, this is an estimate, the more accurate
+    /// the more of the blob's chunks were sampled.
+    pub fn confidence(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples
+            .iter()
+            .map(ChunkReplication::ratio)
+            .sum::<f64>()
+            / self.samples.len() as f64
+    }
+}
+
+impl Client {
+    /// Retrieve the Adults currently holding copies of the chunk at `address`, as vouched
+    /// for by the section responsible for it, verified back to the network's genesis key.
+    pub async fn chunk_holders(&self, address: ChunkAddress) -> Result<ChunkHolders, Error> {
+        trace!("Get holders of chunk {:?}", address);
+        let query_result = self.send_query(DataQuery::GetChunkHolders(address)).await?;
+        let holders = match query_result.response {
+            QueryResponse::GetChunkHolders((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMessage { source: err, op_id })?
+            }
+            _ => return Err(Error::ReceivedUnexpectedEvent),
+        };
+
+        if holders.address != address || !holders.verify(self.session.genesis_key()) {
+            return Err(Error::InvalidChunkHolders(address));
+        }
+
+        Ok(holders)
+    }
+
+    /// Estimate how well the blob at `address` is replicated, by checking the holders of up to
+    /// `sample_size` of its chunks, picked at random, against their sections' replication factor.
+    /// The chunks of all the levels of its data map are sampled from, along with its content's.
+    ///
+    /// This is meant as a light way for applications to tell their users whether their data
+    /// is safely stored, without checking every chunk of large blobs.
+    pub async fn sample_replication(
+        &self,
+        address: BlobAddress,
+        sample_size: usize,
+    ) -> Result<ReplicationHealth, Error> {
+        // Chunks with the same content, e.g. those of repeated parts, are only sampled once.
+        let chunks: Vec<_> = self
+            .chunk_addresses(address)
+            .await?
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let sampled: Vec<_> = chunks
+            .choose_multiple(&mut rand::thread_rng(), sample_size)
+            .copied()
+            .collect();
+
+        let samples = join_all(sampled.into_iter().map(|address| async move {
+            let (holders, replication_factor) = join(
+                self.chunk_holders(address),
+                self.replication_factor(*address.name()),
+            )
+            .await;
+            let holders = holders
+                .map_err(|error| warn!("Couldn't get holders of {:?}: {}", address, error))
+                .ok();
+            ChunkReplication {
+                address,
+                holders,
+                replication_factor: replication_factor.ok(),
+            }
+        }))
+        .await;
+
+        Ok(ReplicationHealth {
+            chunk_count: chunks.len(),
+            samples,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkReplication, ReplicationHealth};
+    use crate::messaging::{data::ChunkHolders, system::KeyedSig};
+    use crate::types::ChunkAddress;
+    use secured_linked_list::SecuredLinkedList;
+    use xor_name::XorName;
+
+    fn sample(holders: Option<usize>, replication_factor: usize) -> ChunkReplication {
+        let address = ChunkAddress(XorName::random());
+        let sk = bls::SecretKey::random();
+        let holders = holders.map(|count| ChunkHolders {
+            address,
+            holders: (0..count).map(|_| XorName::random()).collect(),
+            sig: KeyedSig {
+                public_key: sk.public_key(),
+                signature: sk.sign(b"holders"),
+            },
+            proof_chain: SecuredLinkedList::new(sk.public_key()),
+        });
+        ChunkReplication {
+            address,
+            holders,
+            replication_factor: Some(replication_factor),
+        }
+    }
+
+    #[test]
+    fn confidence_averages_sampled_chunks() {
+        let health = ReplicationHealth {
+            chunk_count: 10,
+            samples: vec![sample(Some(4), 4), sample(Some(2), 4), sample(None, 4)],
+        };
+        assert!((health.confidence() - 0.5).abs() < f64::EPSILON);
+
+        // Extra holders, e.g. full Adults close to the chunk, don't count for more.
+        let health = ReplicationHealth {
+            chunk_count: 1,
+            samples: vec![sample(Some(6), 4)],
+        };
+        assert!((health.confidence() - 1.0).abs() < f64::EPSILON);
+
+        let nothing_sampled = ReplicationHealth {
+            chunk_count: 10,
+            samples: vec![],
+        };
+        assert!(nothing_sampled.confidence().abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::{Error, ErrorMessage};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::time::Instant;
+
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_FACTOR: f64 = 2.0;
+const DEFAULT_JITTER: f64 = 0.2;
+
+/// How queries failing for reasons which may not last, e.g. timing out or too few Elders being
+/// connected to, are retried.
+///
+/// Each attempt is given the client's query timeout. The delay before the n-th retry is
+/// `base_delay * factor^(n - 1)`, randomised by up to `jitter` of it either way, so clients
+/// failing at once don't all retry at once.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RetryPolicy {
+    /// Max number of times a query is sent, the first one included. Queries aren't retried
+    /// unless it's set above 1.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Fraction of the delay, between 0 and 1, it's randomly shortened or lengthened by.
+    pub jitter: f64,
+    /// Factor the delay is multiplied by from one retry to the next.
+    pub factor: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: DEFAULT_BASE_DELAY,
+            jitter: DEFAULT_JITTER,
+            factor: DEFAULT_FACTOR,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy not retrying queries at all.
+    pub fn no_retries() -> Self {
+        Self::default()
+    }
+
+    /// A policy retrying queries up to `max_attempts` times in all, with the default delays.
+    pub fn with_max_attempts(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before sending a query again, after `attempt` attempts failed.
+    pub(super) fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.base_delay.as_secs_f64() * self.factor.max(1.0).powi(exponent);
+        let jitter = self.jitter.max(0.0).min(1.0);
+        let scale = if jitter > 0.0 {
+            rand::thread_rng().gen_range(1.0 - jitter, 1.0 + jitter)
+        } else {
+            1.0
+        };
+        Duration::from_secs_f64((delay * scale).min(u32::MAX as f64))
+    }
+
+    /// Whether a query failing with the error is sent again, after `attempt` attempts.
+    pub(super) fn retries(&self, attempt: u32, error: &Error) -> bool {
+        attempt < self.max_attempts && is_transient(error)
+    }
+}
+
+/// Retries and time shared by all the requests an operation is made of, e.g. all the chunk
+/// reads of a blob, so however many there are, the operation as a whole is bounded
+/// predictably, rather than each request retrying on its own.
+///
+/// Set it with [`Client::with_retry_budget`]: the client returned, and any it's cloned into by
+/// the operations it runs, draw from the same budget. Requests retry as per their
+/// [`RetryPolicy`] only while the budget has retries left, and fail with
+/// [`Error::DeadlineExceeded`] once the deadline has passed.
+///
+/// [`Client::with_retry_budget`]: crate::client::Client::with_retry_budget
+#[derive(Clone, Debug)]
+pub struct RetryBudget {
+    retries: Arc<AtomicU32>,
+    deadline: Option<Instant>,
+}
+
+impl RetryBudget {
+    /// A budget of `max_retries` retries in all, and `within` from now for the operation to be
+    /// done, if given.
+    pub fn new(max_retries: u32, within: Option<Duration>) -> Self {
+        Self {
+            retries: Arc::new(AtomicU32::new(max_retries)),
+            deadline: within.map(|within| Instant::now() + within),
+        }
+    }
+
+    /// Number of retries left.
+    pub fn retries_left(&self) -> u32 {
+        self.retries.load(Ordering::Acquire)
+    }
+
+    /// Time left until the deadline, if there's one. Zero once it has passed.
+    pub fn time_left(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Fails if the deadline has passed.
+    pub(super) fn check_deadline(&self) -> Result<(), Error> {
+        match self.time_left() {
+            Some(left) if left == Duration::ZERO => Err(Error::DeadlineExceeded),
+            _ => Ok(()),
+        }
+    }
+
+    /// Takes a retry off the budget, if there's any left and a retry after `delay` would still
+    /// be within the deadline.
+    pub(super) fn take_retry(&self, delay: Duration) -> bool {
+        if matches!(self.time_left(), Some(left) if left <= delay) {
+            return false;
+        }
+        self.retries
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |left| {
+                left.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    /// Shortens `timeout` so it doesn't go past the deadline.
+    pub(super) fn bound(&self, timeout: Duration) -> Duration {
+        self.time_left().map_or(timeout, |left| left.min(timeout))
+    }
+}
+
+// Errors which may not happen again if the query is sent again, e.g. once Elders are reachable
+// again, the client learnt of the current Elders through anti-entropy, or they no longer
+// throttle requests.
+fn is_transient(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::NoResponse
+            | Error::ElderQuery
+            | Error::ElderConnection
+            | Error::InsufficientElderConnections(_)
+            | Error::ReceivingQuery
+            | Error::SendingQuery
+            | Error::QueryReceiverError
+            | Error::ConflictingResponses(_)
+            | Error::QuicP2p(_)
+            | Error::ErrorMessage {
+                source: ErrorMessage::Throttled,
+                ..
+            }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RetryBudget, RetryPolicy};
+    use crate::client::{Error, ErrorMessage};
+    use std::time::Duration;
+
+    #[test]
+    fn delays_grow_exponentially_within_jitter() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(100),
+            jitter: 0.0,
+            factor: 3.0,
+        };
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(300));
+        assert_eq!(policy.delay(3), Duration::from_millis(900));
+
+        let jittered = RetryPolicy {
+            jitter: 0.5,
+            ..policy
+        };
+        for _ in 0..100 {
+            let delay = jittered.delay(2);
+            assert!(delay >= Duration::from_millis(150) && delay <= Duration::from_millis(450));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn budget_is_shared_by_clones() {
+        let budget = RetryBudget::new(2, Some(Duration::from_secs(60)));
+        let shared = budget.clone();
+        assert!(budget.take_retry(Duration::from_secs(1)));
+        assert!(shared.take_retry(Duration::from_secs(1)));
+        assert!(!budget.take_retry(Duration::from_secs(1)));
+        assert_eq!(shared.retries_left(), 0);
+        assert!(budget.check_deadline().is_ok());
+        assert!(budget.bound(Duration::from_secs(90)) <= Duration::from_secs(60));
+
+        // Retries which would go past the deadline aren't taken.
+        let budget = RetryBudget::new(5, Some(Duration::from_millis(50)));
+        assert!(!budget.take_retry(Duration::from_secs(1)));
+        assert_eq!(budget.retries_left(), 5);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(matches!(
+            budget.check_deadline(),
+            Err(Error::DeadlineExceeded)
+        ));
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let policy = RetryPolicy::with_max_attempts(3);
+        assert!(policy.retries(1, &Error::NoResponse));
+        assert!(policy.retries(2, &Error::InsufficientElderConnections(1)));
+        assert!(!policy.retries(3, &Error::NoResponse));
+        assert!(policy.retries(
+            1,
+            &Error::ErrorMessage {
+                source: ErrorMessage::Throttled,
+                op_id: "op".to_string(),
+            }
+        ));
+        assert!(!policy.retries(1, &Error::ClientClosed));
+        assert!(!RetryPolicy::no_retries().retries(1, &Error::NoResponse));
+    }
+}
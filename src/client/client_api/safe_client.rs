@@ -0,0 +1,148 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{BlobAddress, Client};
+use crate::client::Result;
+use crate::types::{
+    register::{Address, Entry, EntryHash, PrivatePermissions, PublicPermissions, Register, User},
+    PublicKey,
+};
+use crate::url::Scope;
+use bytes::Bytes;
+use futures::future::{BoxFuture, FutureExt};
+use std::collections::{BTreeMap, BTreeSet};
+use xor_name::XorName;
+
+/// The data operations of a [`Client`], for applications to depend on rather than on `Client`
+/// itself, so they can be handed a [`MockClient`](super::MockClient) in their unit tests.
+///
+/// Futures are boxed so that the trait is object-safe, e.g. to be held as `Arc<dyn SafeClient>`.
+/// See the same-named methods of `Client` for what each operation does.
+pub trait SafeClient: Send + Sync {
+    /// The public key operations are signed with.
+    fn public_key(&self) -> PublicKey;
+
+    /// Store `data` as a blob, returning its address.
+    fn write_to_network(&self, data: Bytes, scope: Scope) -> BoxFuture<'_, Result<BlobAddress>>;
+
+    /// Read the whole blob at `address`.
+    fn read_blob(&self, address: BlobAddress) -> BoxFuture<'_, Result<Bytes>>;
+
+    /// Read `length` bytes of the blob at `address`, starting at `position`.
+    fn read_blob_from(
+        &self,
+        address: BlobAddress,
+        position: usize,
+        length: usize,
+    ) -> BoxFuture<'_, Result<Bytes>>;
+
+    /// Create a Private Register, returning its address.
+    fn store_private_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        owner: PublicKey,
+        permissions: BTreeMap<PublicKey, PrivatePermissions>,
+    ) -> BoxFuture<'_, Result<Address>>;
+
+    /// Create a Public Register, returning its address.
+    fn store_public_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        owner: PublicKey,
+        permissions: BTreeMap<User, PublicPermissions>,
+    ) -> BoxFuture<'_, Result<Address>>;
+
+    /// Delete the Private Register at `address`.
+    fn delete_register(&self, address: Address) -> BoxFuture<'_, Result<()>>;
+
+    /// Write `entry` to the Register at `address`, superseding the `children` entries.
+    fn write_to_register(
+        &self,
+        address: Address,
+        entry: Entry,
+        children: BTreeSet<EntryHash>,
+    ) -> BoxFuture<'_, Result<EntryHash>>;
+
+    /// Get the Register at `address`.
+    fn get_register(&self, address: Address) -> BoxFuture<'_, Result<Register>>;
+
+    /// Read the last entry, or entries if there are branches, of the Register at `address`.
+    fn read_register(
+        &self,
+        address: Address,
+    ) -> BoxFuture<'_, Result<BTreeSet<(EntryHash, Entry)>>>;
+}
+
+impl SafeClient for Client {
+    fn public_key(&self) -> PublicKey {
+        Client::public_key(self)
+    }
+
+    fn write_to_network(&self, data: Bytes, scope: Scope) -> BoxFuture<'_, Result<BlobAddress>> {
+        Client::write_to_network(self, data, scope).boxed()
+    }
+
+    fn read_blob(&self, address: BlobAddress) -> BoxFuture<'_, Result<Bytes>> {
+        Client::read_blob(self, address).boxed()
+    }
+
+    fn read_blob_from(
+        &self,
+        address: BlobAddress,
+        position: usize,
+        length: usize,
+    ) -> BoxFuture<'_, Result<Bytes>> {
+        Client::read_blob_from(self, address, position, length).boxed()
+    }
+
+    fn store_private_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        owner: PublicKey,
+        permissions: BTreeMap<PublicKey, PrivatePermissions>,
+    ) -> BoxFuture<'_, Result<Address>> {
+        Client::store_private_register(self, name, tag, owner, permissions).boxed()
+    }
+
+    fn store_public_register(
+        &self,
+        name: XorName,
+        tag: u64,
+        owner: PublicKey,
+        permissions: BTreeMap<User, PublicPermissions>,
+    ) -> BoxFuture<'_, Result<Address>> {
+        Client::store_public_register(self, name, tag, owner, permissions).boxed()
+    }
+
+    fn delete_register(&self, address: Address) -> BoxFuture<'_, Result<()>> {
+        Client::delete_register(self, address).boxed()
+    }
+
+    fn write_to_register(
+        &self,
+        address: Address,
+        entry: Entry,
+        children: BTreeSet<EntryHash>,
+    ) -> BoxFuture<'_, Result<EntryHash>> {
+        Client::write_to_register(self, address, entry, children).boxed()
+    }
+
+    fn get_register(&self, address: Address) -> BoxFuture<'_, Result<Register>> {
+        Client::get_register(self, address).boxed()
+    }
+
+    fn read_register(
+        &self,
+        address: Address,
+    ) -> BoxFuture<'_, Result<BTreeSet<(EntryHash, Entry)>>> {
+        Client::read_register(self, address).boxed()
+    }
+}
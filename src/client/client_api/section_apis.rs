@@ -0,0 +1,187 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::{
+    connections::{ProgressReporter, NUM_OF_ELDERS_SUBSET_FOR_QUERIES},
+    BootstrapProgress, Error,
+};
+use crate::messaging::ELDER_SIZE;
+use crate::messaging::{
+    data::{DataQuery, RegisterRead, ServiceMsg},
+    ServiceAuth, WireMsg,
+};
+use crate::types::{DataAddress, RegisterAddress};
+use futures::future::join_all;
+use std::collections::BTreeSet;
+use tokio::{task::JoinHandle, time::Duration};
+use tracing::{debug, warn};
+use xor_name::{Prefix, XorName};
+
+// How often to check whether a section we are probing has become known.
+const SECTION_DISCOVERY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+impl Client {
+    /// Establish connections with the sections the given names belong to, discovering any of
+    /// those sections we don't know about yet, with up to `connections_per_section` of their
+    /// Elders each.
+    ///
+    /// Bulk workloads spread across the address space can then reach all the sections they use
+    /// straight away, rather than each section being discovered the first time it's used.
+    /// Returns the prefixes of the sections connected to.
+    pub async fn connect_to_sections(
+        &self,
+        names: impl IntoIterator<Item = XorName>,
+        connections_per_section: usize,
+    ) -> Result<BTreeSet<Prefix>, Error> {
+        // Names in the same section only need connecting to once.
+        let mut names_by_section = Vec::new();
+        let mut known = BTreeSet::new();
+        for name in names {
+            match self.session.known_section(&name) {
+                Some(prefix) if !known.insert(prefix) => {}
+                _ => names_by_section.push(name),
+            }
+        }
+
+        let results = join_all(
+            names_by_section
+                .into_iter()
+                .map(|name| self.connect_to_section(name, connections_per_section)),
+        )
+        .await;
+
+        results.into_iter().collect()
+    }
+
+    /// Warm the client up for operations on the given addresses, e.g. the user's known
+    /// containers when an app is launched: the sections responsible for them are resolved,
+    /// and connections established with the Elders their data will be queried from.
+    ///
+    /// This way the first operations on them don't have to wait for either.
+    /// Returns the prefixes of the sections connected to.
+    pub async fn prepare(
+        &self,
+        addresses: impl IntoIterator<Item = DataAddress>,
+    ) -> Result<BTreeSet<Prefix>, Error> {
+        let names: BTreeSet<_> = addresses
+            .into_iter()
+            .map(|address| *address.name())
+            .collect();
+
+        let prefixes = self
+            .connect_to_sections(names.iter().copied(), NUM_OF_ELDERS_SUBSET_FOR_QUERIES)
+            .await?;
+
+        // Queries go to the Elders closest to the data, which differ between addresses
+        // of the same section, so the Elders of each address need connecting to.
+        let results = join_all(names.iter().map(|name| {
+            self.session
+                .connect_to_section(*name, NUM_OF_ELDERS_SUBSET_FOR_QUERIES)
+        }))
+        .await;
+        for (name, result) in names.iter().zip(results) {
+            if let Err(err) = result {
+                warn!(
+                    "Failed to connect to the Elders holding {:?}: {:?}",
+                    name, err
+                );
+            }
+        }
+
+        Ok(prefixes)
+    }
+
+    /// Keep connections with the sections the given names belong to, as per
+    /// [`Client::connect_to_sections`], re-establishing them every `interval` so that
+    /// new Elders and sections are connected to as the network churns and splits.
+    ///
+    /// This carries on in the background until the returned handle is aborted.
+    pub fn maintain_section_connections(
+        &self,
+        names: Vec<XorName>,
+        connections_per_section: usize,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        let client = self.clone();
+        self.session
+            .spawn("maintain_section_connections", async move {
+                loop {
+                    if let Err(err) = client
+                        .connect_to_sections(names.iter().copied(), connections_per_section)
+                        .await
+                    {
+                        warn!("Failed to maintain connections with sections: {:?}", err);
+                    }
+                    tokio::time::sleep(interval).await;
+                }
+            })
+    }
+
+    // Connect to the section `name` belongs to, probing the network for it if we don't know it.
+    async fn connect_to_section(&self, name: XorName, budget: usize) -> Result<Prefix, Error> {
+        if let Some((prefix, _)) = self.session.connect_to_section(name, budget).await? {
+            return Ok(prefix);
+        }
+        self.discover_section(name).await?;
+
+        self.session
+            .connect_to_section(name, budget)
+            .await?
+            .map(|(prefix, _)| prefix)
+            .ok_or(Error::NoSectionPrefixKnown)
+    }
+
+    // Connects to the Elders of the client's own section once bootstrapped, discovering it
+    // first, so that the client is ready to go. Each step is notified to `progress`.
+    pub(super) async fn connect_to_own_section(
+        &self,
+        progress: &ProgressReporter,
+    ) -> Result<(), Error> {
+        let name = XorName::from(self.public_key());
+        if self.session.known_section(&name).is_none() {
+            self.discover_section(name).await?;
+        }
+        if let Some((prefix, elders)) = self.session.known_section_elders(&name) {
+            progress.report(BootstrapProgress::SectionInfoReceived { prefix, elders });
+        }
+
+        let (prefix, elders) = self
+            .session
+            .connect_to_section(name, ELDER_SIZE)
+            .await?
+            .ok_or(Error::NoSectionPrefixKnown)?;
+        progress.report(BootstrapProgress::EldersConnected { prefix, elders });
+        Ok(())
+    }
+
+    // Probes the network for the section `name` belongs to, until we know about it.
+    async fn discover_section(&self, name: XorName) -> Result<(), Error> {
+        debug!("Probing the network for the section of {:?}", name);
+        // Registers are held by Elders, so this is answered without involving any Adults.
+        let query = DataQuery::Register(RegisterRead::GetOwner(RegisterAddress::Public {
+            name,
+            tag: 0,
+        }));
+        let payload = WireMsg::serialize_msg_payload(&ServiceMsg::Query(query))?;
+        let auth = ServiceAuth {
+            public_key: self.public_key(),
+            signature: self.signer.sign(&payload)?,
+            delegation: None,
+        };
+        self.session.probe_section(name, auth, payload).await?;
+
+        tokio::time::timeout(self.query_timeout, async {
+            while self.session.known_section(&name).is_none() {
+                tokio::time::sleep(SECTION_DISCOVERY_POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .map_err(|_| Error::NoResponse)
+    }
+}
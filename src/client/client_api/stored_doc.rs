@@ -0,0 +1,278 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{BlobAddress, Client};
+use crate::client::{Error, Result};
+use crate::types::register::{Address, Entry, EntryHash};
+use crate::url::{ContentType, Scope, Url, XorUrlBase};
+
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+use tracing::debug;
+
+/// A document read from the network, migrated to the current version of its schema.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StoredDoc<T> {
+    /// Version of the schema the document was stored with.
+    pub stored_version: u32,
+    /// The document, as per the current version of its schema.
+    pub doc: T,
+}
+
+impl<T> StoredDoc<T> {
+    /// Whether the document was stored with an older schema, and migrated when read.
+    ///
+    /// Migrations happen lazily on every read, so applications may want to store such
+    /// documents again, for them not to be migrated anymore.
+    pub fn was_migrated(&self, migrations: &Migrations<T>) -> bool {
+        self.stored_version < migrations.current_version
+    }
+}
+
+// How documents are stored: their serialised form, tagged with their schema version.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    schema_version: u32,
+    payload: Bytes,
+}
+
+type Migration = Box<dyn Fn(&[u8]) -> Result<Vec<u8>> + Send + Sync>;
+
+/// The versions of a document's schema, and how documents stored with each are
+/// migrated to the next, up to the current one, `T`.
+pub struct Migrations<T> {
+    current_version: u32,
+    steps: BTreeMap<u32, Migration>,
+    doc: PhantomData<fn() -> T>,
+}
+
+impl<T> Debug for Migrations<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Migrations")
+            .field("current_version", &self.current_version)
+            .field("from_versions", &self.steps.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Migrations<T> {
+    /// Documents of schema `T`, whose version is `current_version`, without any migrations yet.
+    pub fn new(current_version: u32) -> Self {
+        Self {
+            current_version,
+            steps: BTreeMap::new(),
+            doc: PhantomData,
+        }
+    }
+
+    /// Version of the schema documents are stored with.
+    pub fn current_version(&self) -> u32 {
+        self.current_version
+    }
+
+    /// Register how documents of schema version `from_version`, of type `Old`, are migrated
+    /// to the next version, of type `New`.
+    pub fn register<Old, New>(
+        mut self,
+        from_version: u32,
+        migrate: impl Fn(Old) -> New + Send + Sync + 'static,
+    ) -> Self
+    where
+        Old: DeserializeOwned,
+        New: Serialize,
+    {
+        let step = move |bytes: &[u8]| -> Result<Vec<u8>> {
+            let old: Old = bincode::deserialize(bytes)?;
+            Ok(bincode::serialize(&migrate(old))?)
+        };
+        let _ = self.steps.insert(from_version, Box::new(step));
+        self
+    }
+
+    /// Serialise `doc` along with the current schema version.
+    pub fn encode(&self, doc: &T) -> Result<Bytes> {
+        let envelope = Envelope {
+            schema_version: self.current_version,
+            payload: Bytes::from(bincode::serialize(doc)?),
+        };
+        Ok(Bytes::from(bincode::serialize(&envelope)?))
+    }
+
+    /// Deserialise a document, migrating it to the current schema version if it's older.
+    pub fn decode(&self, bytes: &[u8]) -> Result<StoredDoc<T>> {
+        let envelope: Envelope = bincode::deserialize(bytes)?;
+        if envelope.schema_version > self.current_version {
+            return Err(Error::NoSchemaMigration(envelope.schema_version));
+        }
+
+        let mut payload = envelope.payload.to_vec();
+        for version in envelope.schema_version..self.current_version {
+            let step = self
+                .steps
+                .get(&version)
+                .ok_or(Error::NoSchemaMigration(version))?;
+            payload = step(&payload)?;
+        }
+
+        Ok(StoredDoc {
+            stored_version: envelope.schema_version,
+            doc: bincode::deserialize(&payload)?,
+        })
+    }
+}
+
+impl Client {
+    /// Store `doc` as a blob, tagged with the current version of its schema.
+    pub async fn write_doc<T: Serialize + DeserializeOwned>(
+        &self,
+        doc: &T,
+        migrations: &Migrations<T>,
+        scope: Scope,
+    ) -> Result<BlobAddress> {
+        let bytes = migrations.encode(doc)?;
+        self.write_to_network(bytes, scope).await
+    }
+
+    /// Read the document stored at `address`, migrating it to the current version of its schema.
+    pub async fn read_doc<T: Serialize + DeserializeOwned>(
+        &self,
+        address: BlobAddress,
+        migrations: &Migrations<T>,
+    ) -> Result<StoredDoc<T>> {
+        let bytes = self.read_blob(address).await?;
+        migrations.decode(&bytes)
+    }
+
+    /// Store `doc` as a blob, and write an entry pointing to it to the Register at `address`,
+    /// superseding the `children` entries.
+    pub async fn write_doc_to_register<T: Serialize + DeserializeOwned>(
+        &self,
+        address: Address,
+        doc: &T,
+        migrations: &Migrations<T>,
+        children: BTreeSet<EntryHash>,
+    ) -> Result<EntryHash> {
+        let scope = if address.is_public() {
+            Scope::Public
+        } else {
+            Scope::Private
+        };
+        let blob = self.write_doc(doc, migrations, scope).await?;
+        let url = Url::encode_blob(*blob.name(), scope, ContentType::Raw, XorUrlBase::Base32z)?;
+        self.write_to_register(address, Url::from_url(&url)?, children)
+            .await
+    }
+
+    /// Read the documents the last entry, or entries if there are branches, of the Register
+    /// at `address` point to, migrating them to the current version of their schema.
+    pub async fn read_docs_from_register<T: Serialize + DeserializeOwned>(
+        &self,
+        address: Address,
+        migrations: &Migrations<T>,
+    ) -> Result<Vec<(EntryHash, StoredDoc<T>)>> {
+        let entries = self.read_register(address).await?;
+        self.read_docs_from_entries(address, entries, migrations)
+            .await
+    }
+
+    /// Read the documents the given entries of the Register at `address` point to, e.g. those
+    /// of an earlier version of it, migrating them to the current version of their schema.
+    pub async fn read_docs_from_entries<T: Serialize + DeserializeOwned>(
+        &self,
+        address: Address,
+        entries: BTreeSet<(EntryHash, Entry)>,
+        migrations: &Migrations<T>,
+    ) -> Result<Vec<(EntryHash, StoredDoc<T>)>> {
+        let mut docs = Vec::new();
+        for (hash, entry) in entries {
+            let blob = match entry.scope() {
+                Scope::Public => BlobAddress::Public(entry.xorname()),
+                Scope::Private => BlobAddress::Private(entry.xorname()),
+            };
+            let doc = self.read_doc(blob, migrations).await?;
+            if doc.was_migrated(migrations) {
+                debug!(
+                    "Migrated doc of Register {:?} from schema version {}",
+                    address, doc.stored_version
+                );
+            }
+            docs.push((hash, doc));
+        }
+        Ok(docs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Migrations;
+    use crate::client::Error;
+    use eyre::Result;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct ProfileV0 {
+        name: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct ProfileV1 {
+        name: String,
+        age: u8,
+    }
+
+    #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+    struct Profile {
+        first_name: String,
+        last_name: String,
+        age: u8,
+    }
+
+    #[test]
+    fn old_docs_are_migrated_on_read() -> Result<()> {
+        let v0 = Migrations::<ProfileV0>::new(0).encode(&ProfileV0 {
+            name: "Ada Lovelace".to_string(),
+        })?;
+
+        let migrations = Migrations::<Profile>::new(2)
+            .register(0, |old: ProfileV0| ProfileV1 {
+                name: old.name,
+                age: 0,
+            })
+            .register(1, |old: ProfileV1| {
+                let mut names = old.name.splitn(2, ' ');
+                Profile {
+                    first_name: names.next().unwrap_or_default().to_string(),
+                    last_name: names.next().unwrap_or_default().to_string(),
+                    age: old.age,
+                }
+            });
+
+        let read = migrations.decode(&v0)?;
+        assert_eq!(read.stored_version, 0);
+        assert!(read.was_migrated(&migrations));
+        assert_eq!(read.doc.last_name, "Lovelace");
+
+        let current = migrations.decode(&migrations.encode(&read.doc)?)?;
+        assert!(!current.was_migrated(&migrations));
+        assert_eq!(current.doc, read.doc);
+
+        // Docs stored by a newer version of the application can't be read.
+        let newer = Migrations::<Profile>::new(3).encode(&read.doc)?;
+        assert!(matches!(
+            migrations.decode(&newer),
+            Err(Error::NoSchemaMigration(3))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::{ConcurrencyLimits, Error, Result};
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::{sleep_until, Instant},
+};
+
+/// Holds the chunk transfers of blob reads and writes back to a client's concurrency limits,
+/// on top of the session's, shared by the clones of the client.
+#[derive(Clone, Debug)]
+pub(super) struct TransferLimiter {
+    limits: ConcurrencyLimits,
+    read_permits: Arc<Semaphore>,
+    write_permits: Arc<Semaphore>,
+    // When the transfers done so far are due to be done at the rate limit, if any.
+    next_slot: Arc<Mutex<Instant>>,
+}
+
+impl TransferLimiter {
+    pub(super) fn new(limits: ConcurrencyLimits) -> Self {
+        Self {
+            limits,
+            read_permits: Arc::new(Semaphore::new(limits.chunk_reads.max(1))),
+            write_permits: Arc::new(Semaphore::new(limits.chunk_writes.max(1))),
+            next_slot: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub(super) fn limits(&self) -> ConcurrencyLimits {
+        self.limits
+    }
+
+    /// Max number of chunks being uploaded at once, never zero.
+    pub(super) fn max_writes_in_flight(&self) -> usize {
+        self.limits.chunk_writes.max(1)
+    }
+
+    /// Max number of chunks being downloaded at once, never zero.
+    pub(super) fn max_reads_in_flight(&self) -> usize {
+        self.limits.chunk_reads.max(1)
+    }
+
+    /// Waits for a chunk upload to be allowed to start. The upload holds on to the
+    /// returned permit until it's done.
+    pub(super) async fn acquire_write(&self) -> Result<OwnedSemaphorePermit> {
+        acquire(&self.write_permits).await
+    }
+
+    /// Waits for a chunk download to be allowed to start. The download holds on to the
+    /// returned permit until it's done.
+    pub(super) async fn acquire_read(&self) -> Result<OwnedSemaphorePermit> {
+        acquire(&self.read_permits).await
+    }
+
+    /// Waits for `bytes` more to be within the rate limit, if any.
+    pub(super) async fn throttle(&self, bytes: usize) {
+        let rate = match self.limits.max_bytes_per_sec {
+            Some(rate) if rate > 0 => rate,
+            _ => return,
+        };
+        let start = {
+            let mut next_slot = self
+                .next_slot
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let start = (*next_slot).max(Instant::now());
+            *next_slot = start + Duration::from_secs_f64(bytes as f64 / rate as f64);
+            start
+        };
+        sleep_until(start).await;
+    }
+}
+
+// The permits are never closed, but this doesn't count on it.
+async fn acquire(permits: &Arc<Semaphore>) -> Result<OwnedSemaphorePermit> {
+    permits
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|_| Error::ClientClosed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransferLimiter;
+    use crate::client::ConcurrencyLimits;
+    use eyre::Result;
+    use std::time::Duration;
+    use tokio::time::{timeout, Instant};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn transfers_are_held_back_to_the_limits() -> Result<()> {
+        let limiter = TransferLimiter::new(ConcurrencyLimits {
+            chunk_writes: 2,
+            max_bytes_per_sec: Some(10_000),
+            ..ConcurrencyLimits::default()
+        });
+        let first = limiter.acquire_write().await?;
+        let _second = limiter.clone().acquire_write().await?;
+        assert!(timeout(Duration::from_millis(50), limiter.acquire_write())
+            .await
+            .is_err());
+        // Reads have their own budget.
+        let _read = timeout(Duration::from_millis(50), limiter.acquire_read()).await??;
+        drop(first);
+        let _third = timeout(Duration::from_millis(50), limiter.acquire_write()).await??;
+
+        // 2,000 bytes at 10,000 per second take 200ms, the first 1,000 going through at once.
+        let started = Instant::now();
+        limiter.throttle(1_000).await;
+        limiter.throttle(1_000).await;
+        limiter.throttle(1_000).await;
+        assert!(started.elapsed() >= Duration::from_millis(190));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::client::{connections::SentMsg, AntiEntropyEvent, AntiEntropyOutcome, Result};
+use crate::messaging::MessageId;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use tokio::{
+    sync::broadcast::{self, error::TryRecvError},
+    time::Instant,
+};
+use tracing::warn;
+
+/// What an operation cost in bandwidth, as returned along with its result by the `_with_stats`
+/// variants of the read and write APIs, e.g. [`Client::write_to_network_with_stats`].
+///
+/// Bytes are counted from the payloads of the messages exchanged, times the number of Elders
+/// each was sent to or received from, so they're close to, but short of, the bytes on the wire.
+/// Messages which didn't go through, e.g. queries which timed out, aren't counted.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TransferStats {
+    /// Bytes sent to the network.
+    pub bytes_sent: u64,
+    /// Bytes received from the network.
+    pub bytes_received: u64,
+    /// Chunks stored.
+    pub chunks_sent: u64,
+    /// Chunks fetched.
+    pub chunks_received: u64,
+    /// Messages resent after being bounced by Elders with anti-entropy responses.
+    pub retries: u64,
+    /// Time spent in each phase of the operation. Phases of a blob upload overlap, as the chunks
+    /// of its first parts are uploaded while later ones are being encrypted.
+    pub phases: BTreeMap<TransferPhase, Duration>,
+}
+
+/// A phase of reading or writing data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum TransferPhase {
+    /// Self-encrypting data into chunks.
+    Encryption,
+    /// Storing chunks.
+    Upload,
+    /// Fetching chunks.
+    Download,
+    /// Decrypting chunks back into data.
+    Decryption,
+}
+
+/// The result of an operation, with what it cost in bandwidth.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WithStats<T> {
+    /// The result of the operation.
+    pub value: T,
+    /// What the operation cost.
+    pub stats: TransferStats,
+}
+
+// Records the transfers of an operation, as it's carried out by a client sharing
+// the session of the one the operation was called on.
+#[derive(Clone, Debug)]
+pub(super) struct StatsRecorder {
+    recorded: Arc<Mutex<Recorded>>,
+    // Anti-entropy responses are matched against the messages sent once the operation is done.
+    anti_entropy: Arc<Mutex<broadcast::Receiver<AntiEntropyEvent>>>,
+}
+
+#[derive(Debug, Default)]
+struct Recorded {
+    stats: TransferStats,
+    msg_ids: BTreeSet<MessageId>,
+}
+
+impl StatsRecorder {
+    fn new(anti_entropy: broadcast::Receiver<AntiEntropyEvent>) -> Self {
+        Self {
+            recorded: Arc::new(Mutex::new(Recorded::default())),
+            anti_entropy: Arc::new(Mutex::new(anti_entropy)),
+        }
+    }
+
+    /// Records a message of `len` bytes being sent, storing a chunk if `chunk`.
+    pub(super) fn sent(&self, msg: SentMsg, len: usize, chunk: bool) {
+        let mut recorded = self.lock();
+        let _ = recorded.msg_ids.insert(msg.msg_id);
+        recorded.stats.bytes_sent += (len * msg.recipients) as u64;
+        if chunk {
+            recorded.stats.chunks_sent += 1;
+        }
+    }
+
+    /// Records responses of `len` bytes being received, fetching a chunk if `chunk`.
+    pub(super) fn received(&self, len: u64, responses: usize, chunk: bool) {
+        let mut recorded = self.lock();
+        recorded.stats.bytes_received += len * responses as u64;
+        if chunk {
+            recorded.stats.chunks_received += 1;
+        }
+    }
+
+    /// Records time spent in `phase`, since `started`.
+    pub(super) fn phase(&self, phase: TransferPhase, started: Instant) {
+        *self.lock().stats.phases.entry(phase).or_default() += started.elapsed();
+    }
+
+    fn finish(&self) -> TransferStats {
+        let mut recorded = self.lock();
+        let mut anti_entropy = self
+            .anti_entropy
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        loop {
+            match anti_entropy.try_recv() {
+                Ok(event) => {
+                    if recorded.msg_ids.contains(&event.msg_id)
+                        && matches!(event.outcome, AntiEntropyOutcome::Resent(_))
+                    {
+                        recorded.stats.retries += 1;
+                    }
+                }
+                Err(TryRecvError::Lagged(missed)) => {
+                    warn!(
+                        "Missed {} anti-entropy responses, retries may be undercounted",
+                        missed
+                    );
+                }
+                Err(_) => break,
+            }
+        }
+        recorded.stats.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Recorded> {
+        self.recorded.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Client {
+    // Carries out an operation with a client recording its transfers, returning them along
+    // with its result.
+    pub(super) async fn recording_stats<T, F, Fut>(&self, operation: F) -> Result<WithStats<T>>
+    where
+        F: FnOnce(Client) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let recorder = StatsRecorder::new(self.session.subscribe_to_anti_entropy());
+        let mut client = self.clone();
+        client.stats = Some(recorder.clone());
+
+        let value = operation(client).await?;
+        Ok(WithStats {
+            value,
+            stats: recorder.finish(),
+        })
+    }
+
+    // Records time spent in `phase` since `started`, if the client is recording its transfers.
+    pub(super) fn record_phase(&self, phase: TransferPhase, started: Instant) {
+        if let Some(recorder) = &self.stats {
+            recorder.phase(phase, started);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StatsRecorder, TransferPhase};
+    use crate::client::{connections::SentMsg, AntiEntropyEvent};
+    use crate::messaging::MessageId;
+    use tokio::{sync::broadcast, time::Instant};
+
+    #[test]
+    fn transfers_are_totalled() {
+        let (_sender, receiver) = broadcast::channel::<AntiEntropyEvent>(1);
+        let recorder = StatsRecorder::new(receiver);
+
+        let msg = SentMsg {
+            msg_id: MessageId::new(),
+            recipients: 3,
+        };
+        recorder.sent(msg, 100, true);
+        recorder.sent(msg, 10, false);
+        recorder.received(50, 2, true);
+        recorder.phase(TransferPhase::Upload, Instant::now());
+
+        let stats = recorder.finish();
+        assert_eq!(stats.bytes_sent, 330);
+        assert_eq!(stats.bytes_received, 100);
+        assert_eq!(stats.chunks_sent, 1);
+        assert_eq!(stats.chunks_received, 1);
+        assert_eq!(stats.retries, 0);
+        assert!(stats.phases.contains_key(&TransferPhase::Upload));
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    data::get_data_chunks, limits_apis::check_blob_size, BlobAddress, Client, TransferPhase,
+};
+use crate::client::{Error, Result};
+use crate::types::{Chunk, ChunkAddress, Encryption};
+use crate::url::Scope;
+use bytes::Bytes;
+use exponential_backoff::Backoff;
+use futures::stream::{self, StreamExt};
+use std::{
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+use tokio::{sync::mpsc::UnboundedSender, time::Instant};
+use tracing::{debug, warn};
+
+// Attempts made at storing a chunk, the first one included, before giving up on it.
+const MAX_CHUNK_UPLOAD_ATTEMPTS: u32 = 4;
+// Bounds of the delays between attempts, which grow exponentially.
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// The outcome of storing each chunk of a blob, as returned by
+/// [`Client::write_to_network_with_report`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UploadReport {
+    /// Address of the blob.
+    pub address: BlobAddress,
+    /// The chunks of the blob, in no particular order.
+    pub chunks: Vec<ChunkUpload>,
+}
+
+impl UploadReport {
+    /// The chunks which couldn't be stored.
+    pub fn failed(&self) -> impl Iterator<Item = &ChunkUpload> {
+        self.chunks.iter().filter(|chunk| chunk.error.is_some())
+    }
+
+    /// Whether all the chunks of the blob were stored.
+    pub fn is_complete(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+/// The outcome of storing a chunk.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChunkUpload {
+    /// Address of the chunk.
+    pub address: ChunkAddress,
+    /// Attempts made at storing it.
+    pub attempts: u32,
+    /// What the last attempt failed with, if the chunk couldn't be stored.
+    pub error: Option<String>,
+}
+
+/// Progress of an upload, as notified to those writing with
+/// [`Client::write_to_network_with_report`], on each chunk being stored, retried or given up on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UploadProgress {
+    /// Number of chunks of the blob.
+    pub total: usize,
+    /// Number of chunks stored so far.
+    pub uploaded: usize,
+    /// Number of chunks given up on so far.
+    pub failed: usize,
+    /// What just happened.
+    pub event: UploadEvent,
+}
+
+/// Something which happened to a chunk of an upload.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UploadEvent {
+    /// The chunk was stored.
+    Uploaded(ChunkAddress),
+    /// An attempt at storing the chunk failed, and it's about to be retried.
+    Retrying {
+        /// Address of the chunk.
+        address: ChunkAddress,
+        /// The attempt which failed, from 1.
+        attempt: u32,
+        /// What it failed with.
+        error: String,
+    },
+    /// The chunk was given up on, after as many attempts as allowed.
+    Failed {
+        /// Address of the chunk.
+        address: ChunkAddress,
+        /// What the last attempt failed with.
+        error: String,
+    },
+}
+
+// Keeps count of the chunks stored, notifying the progress to anybody who asked for it.
+pub(super) struct ProgressTracker {
+    total: usize,
+    counts: Mutex<(usize, usize)>,
+    sender: Option<UnboundedSender<UploadProgress>>,
+}
+
+impl ProgressTracker {
+    pub(super) fn new(total: usize, sender: Option<UnboundedSender<UploadProgress>>) -> Self {
+        Self {
+            total,
+            counts: Mutex::new((0, 0)),
+            sender,
+        }
+    }
+
+    fn report(&self, event: UploadEvent) {
+        let mut counts = self.counts.lock().unwrap_or_else(PoisonError::into_inner);
+        match event {
+            UploadEvent::Uploaded(_) => counts.0 += 1,
+            UploadEvent::Failed { .. } => counts.1 += 1,
+            UploadEvent::Retrying { .. } => {}
+        }
+        if let Some(sender) = &self.sender {
+            // The receiver being dropped is fine, the upload carries on regardless.
+            let _ = sender.send(UploadProgress {
+                total: self.total,
+                uploaded: counts.0,
+                failed: counts.1,
+                event,
+            });
+        }
+    }
+}
+
+impl Client {
+    /// Write raw data to the network, as per [`Client::write_to_network`], retrying with
+    /// exponential backoff the chunks which fail to be sent, and reporting the outcome for
+    /// each chunk rather than swallowing failures.
+    ///
+    /// The progress of the upload is sent to `progress`, if given, as chunks are stored,
+    /// retried or given up on. The data is self-encrypted in full before any chunk is sent.
+    /// A chunk is deemed stored once the Elders of its section have been sent the command to
+    /// store it; errors they later reply with are received via [`Client::next_cmd_error`].
+    pub async fn write_to_network_with_report(
+        &self,
+        data: Bytes,
+        scope: Scope,
+        progress: Option<UnboundedSender<UploadProgress>>,
+    ) -> Result<UploadReport> {
+        check_blob_size(data.len(), &self.upload_limits().await)?;
+        self.check_publish(&data, scope).await?;
+
+        let owner = self
+            .encryption_provider
+            .encryption(scope, self.public_key());
+        let started = Instant::now();
+        let (address, chunks) = tokio::task::spawn_blocking(move || encrypt(data, owner))
+            .await
+            .map_err(|err| Error::Generic(format!("Encrypting the blob failed: {}", err)))??;
+        self.record_phase(TransferPhase::Encryption, started);
+
+        let tracker = ProgressTracker::new(chunks.len(), progress);
+
+        let started = Instant::now();
+        let chunks = stream::iter(chunks)
+            .map(|chunk| self.store_chunk_with_retries(chunk, &tracker))
+            .buffer_unordered(self.transfer.max_writes_in_flight())
+            .collect()
+            .await;
+        self.record_phase(TransferPhase::Upload, started);
+
+        Ok(UploadReport { address, chunks })
+    }
+
+    // Stores a chunk, retrying with exponential backoff until it's sent or attempts run out.
+    pub(super) async fn store_chunk_with_retries(
+        &self,
+        chunk: Chunk,
+        tracker: &ProgressTracker,
+    ) -> ChunkUpload {
+        let address = *chunk.address();
+        let backoff = Backoff::new(MAX_CHUNK_UPLOAD_ATTEMPTS, MIN_RETRY_DELAY, MAX_RETRY_DELAY);
+
+        let mut attempts = 0;
+        let mut delays = (&backoff).into_iter();
+        loop {
+            attempts += 1;
+            let error = match self.store_chunk(chunk.clone()).await {
+                Ok(_) => {
+                    tracker.report(UploadEvent::Uploaded(address));
+                    return ChunkUpload {
+                        address,
+                        attempts,
+                        error: None,
+                    };
+                }
+                Err(error) => error.to_string(),
+            };
+
+            let within_budget = |delay| {
+                self.retry_budget
+                    .as_ref()
+                    .map_or(true, |budget| budget.take_retry(delay))
+            };
+            match delays.next() {
+                Some(delay) if attempts < MAX_CHUNK_UPLOAD_ATTEMPTS && within_budget(delay) => {
+                    debug!(
+                        "Storing chunk {:?} failed on attempt {}, retrying in {:?}: {}",
+                        address, attempts, delay, error
+                    );
+                    tracker.report(UploadEvent::Retrying {
+                        address,
+                        attempt: attempts,
+                        error,
+                    });
+                    tokio::time::sleep(delay).await;
+                }
+                _ => {
+                    warn!(
+                        "Giving up on storing chunk {:?} after {} attempts: {}",
+                        address, attempts, error
+                    );
+                    tracker.report(UploadEvent::Failed {
+                        address,
+                        error: error.clone(),
+                    });
+                    return ChunkUpload {
+                        address,
+                        attempts,
+                        error: Some(error),
+                    };
+                }
+            }
+        }
+    }
+}
+
+// Self-encrypts data into the chunks of a blob, as `Client::write_to_network` does, so it
+// gets the same address.
+pub(super) fn encrypt(
+    data: Bytes,
+    owner: Option<Box<dyn Encryption>>,
+) -> Result<(BlobAddress, Vec<Chunk>)> {
+    get_data_chunks(data, owner.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UploadEvent;
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::types::utils::random_bytes;
+    use crate::url::Scope;
+    use eyre::Result;
+    use tokio::sync::mpsc;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn upload_is_reported() -> Result<()> {
+        let client = create_test_client(None).await?;
+
+        let blob = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let report = client
+            .write_to_network_with_report(blob.clone(), Scope::Public, Some(sender))
+            .await?;
+
+        assert!(report.is_complete());
+        // A min size blob is self-encrypted into 3 chunks, plus its head chunk.
+        assert_eq!(report.chunks.len(), 4);
+
+        let mut last = None;
+        while let Some(progress) = receiver.recv().await {
+            assert!(!matches!(progress.event, UploadEvent::Failed { .. }));
+            last = Some(progress);
+        }
+        let last = last.ok_or_else(|| eyre::eyre!("No progress notified"))?;
+        assert_eq!(last.uploaded, last.total);
+
+        let read = run_w_backoff_delayed(|| client.read_blob(report.address), 10, 1).await?;
+        assert_eq!(read, blob);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,379 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    blob_apis::PartReader,
+    data::{get_data_chunks, get_part_chunks, pack_parts},
+    limits_apis::check_blob_size,
+    upload_report::ProgressTracker,
+    BlobAddress, Client, TransferPhase,
+};
+use crate::client::{Error, Result};
+use crate::types::{Chunk, ChunkAddress, Encryption, JsonRepr};
+use crate::url::Scope;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{io::AsyncRead, time::Instant};
+use tracing::{debug, info};
+
+/// An upload of a blob which can be resumed where it was left off if it fails, e.g. halfway
+/// through a large file, recording which of the blob's chunks were stored.
+///
+/// Sessions are serialisable, and can be saved to and loaded from a manifest file, which
+/// [`Client::resume_upload`] keeps up to date as chunks are stored if the session was loaded
+/// with [`UploadSession::persisted_at`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct UploadSession {
+    scope: Scope,
+    // Known once the data has been self-encrypted, on the first attempt.
+    address: Option<BlobAddress>,
+    // All the chunks of the blob, and whether each was stored.
+    #[serde(with = "crate::types::map_entries")]
+    chunks: BTreeMap<ChunkAddress, bool>,
+    #[serde(skip)]
+    manifest: Option<PathBuf>,
+}
+
+impl UploadSession {
+    /// A session for uploading data of the given scope, none of it uploaded yet.
+    pub fn new(scope: Scope) -> Self {
+        Self {
+            scope,
+            address: None,
+            chunks: BTreeMap::new(),
+            manifest: None,
+        }
+    }
+
+    /// The session saved in the manifest at `path`, or a new one if there's no such file,
+    /// saved there as chunks are stored.
+    pub fn persisted_at(path: impl AsRef<Path>, scope: Scope) -> Result<Self> {
+        let path = path.as_ref();
+        let mut session = if path.exists() {
+            let session = Self::load(path)?;
+            if session.scope != scope {
+                return Err(Error::UploadSessionMismatch(format!(
+                    "the manifest at {} is for {:?} data",
+                    path.display(),
+                    session.scope
+                )));
+            }
+            session
+        } else {
+            Self::new(scope)
+        };
+        session.manifest = Some(path.to_path_buf());
+        Ok(session)
+    }
+
+    /// Loads a session from the manifest at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        Ok(bincode::deserialize(&bytes)?)
+    }
+
+    /// Saves the session to a manifest at `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, bincode::serialize(self)?)?;
+        Ok(())
+    }
+
+    /// Address of the blob, once known.
+    pub fn address(&self) -> Option<BlobAddress> {
+        self.address
+    }
+
+    /// Number of chunks of the blob, once known.
+    pub fn total(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Chunks of the blob yet to be stored.
+    pub fn missing(&self) -> impl Iterator<Item = &ChunkAddress> {
+        self.chunks
+            .iter()
+            .filter(|(_, stored)| !**stored)
+            .map(|(address, _)| address)
+    }
+
+    /// Whether all the chunks of the blob were stored.
+    pub fn is_complete(&self) -> bool {
+        self.address.is_some() && self.missing().next().is_none()
+    }
+
+    fn checkpoint(&self) -> Result<()> {
+        match &self.manifest {
+            Some(path) => self.save(path),
+            None => Ok(()),
+        }
+    }
+}
+
+impl JsonRepr for UploadSession {
+    const JSON_TYPE: &'static str = "UploadSession";
+    // The chunks are a list of pairs since version 2.
+    const JSON_VERSION: u32 = 2;
+}
+
+impl Client {
+    /// Upload the data read from `reader` as a blob, as per
+    /// [`Client::write_to_network_with_report`], skipping the chunks `session` records as
+    /// stored by earlier attempts, and recording those stored by this one. Returns the address
+    /// of the blob once all its chunks are stored.
+    ///
+    /// The data is read a part at a time, as per [`Client::write_stream`], so it's never all
+    /// held in memory, e.g. to upload multi-GB files from disk. The same data must be read on
+    /// every attempt: it's self-encrypted again each time, only the chunks not stored yet being
+    /// sent, which requires the encryption of private data to be deterministic, as the default
+    /// one is. Data found to have chunks the session doesn't record is refused. The head chunk
+    /// is stored last, so the blob isn't reachable until all its content is. Chunks deemed
+    /// stored are those the Elders of their section were sent the command to store.
+    ///
+    /// Public data larger than a part can't be checked with the client's publish hook, as per
+    /// [`Client::write_stream`].
+    pub async fn resume_upload<R>(
+        &self,
+        session: &mut UploadSession,
+        reader: R,
+    ) -> Result<BlobAddress>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let owner: Option<Arc<dyn Encryption>> = self
+            .encryption_provider
+            .encryption(session.scope, self.public_key())
+            .map(Arc::from);
+        let tracker = ProgressTracker::new(session.total(), None);
+        let mut seen = BTreeSet::new();
+
+        let mut parts = PartReader::new(reader).await?;
+        let (address, head_chunks) = if let Some(data) = parts.take_whole() {
+            check_blob_size(data.len(), &self.upload_limits().await)?;
+            self.check_publish(&data, session.scope).await?;
+            let data_owner = owner.clone();
+            self.encrypt_blocking(move || get_data_chunks(data, data_owner.as_deref()))
+                .await?
+        } else {
+            if self.checks_publish(session.scope) {
+                return Err(Error::PublishRejected(
+                    "Public data streamed can't be checked before it's published".to_string(),
+                ));
+            }
+            let mut secret_keys = vec![];
+            while let Some(part) = parts.next_part().await? {
+                let part_owner = owner.clone();
+                let (secret_key, chunks) = self
+                    .encrypt_blocking(move || get_part_chunks(part, part_owner.as_deref()))
+                    .await?;
+                self.resume_chunks(session, chunks, &mut seen, &tracker)
+                    .await?;
+                secret_keys.push(secret_key);
+            }
+            pack_parts(secret_keys, owner.as_deref())?
+        };
+
+        let head = ChunkAddress(*address.name());
+        let (mut heads, content): (Vec<_>, Vec<_>) = head_chunks
+            .into_iter()
+            .partition(|chunk| *chunk.address() == head);
+        self.resume_chunks(session, content, &mut seen, &tracker)
+            .await?;
+
+        match session.address {
+            None => {
+                // Chunks recorded by earlier attempts interrupted before the blob's address was
+                // known, which aren't the data's, are dropped.
+                let _ = seen.insert(head);
+                session.chunks.retain(|chunk, _| seen.contains(chunk));
+                let _ = session.chunks.entry(head).or_insert(false);
+                session.address = Some(address);
+            }
+            Some(recorded) if recorded != address => {
+                return Err(Error::UploadSessionMismatch(format!(
+                    "the data is for the blob at {:?}, while the session is for the one at {:?}",
+                    address, recorded
+                )));
+            }
+            Some(_) => {}
+        }
+
+        let content_missing = session.missing().filter(|name| **name != head).count();
+        let head_missing = session.chunks.get(&head) == Some(&false);
+        if content_missing == 0 && head_missing {
+            if let Some(chunk) = heads.pop() {
+                let started = Instant::now();
+                let upload = self.store_chunk_with_retries(chunk, &tracker).await;
+                if upload.error.is_none() {
+                    let _ = session.chunks.insert(upload.address, true);
+                }
+                self.record_phase(TransferPhase::Upload, started);
+            }
+        }
+        session.checkpoint()?;
+
+        let missing = session.missing().count();
+        if missing > 0 {
+            return Err(Error::UploadIncomplete {
+                missing,
+                total: session.total(),
+            });
+        }
+
+        info!("Completed upload of {:?}", address);
+        Ok(address)
+    }
+
+    // Stores those of `chunks` which `session` doesn't record as stored, recording them as
+    // they are, and recording the chunks of the data in `seen`. Once the blob's address is
+    // known, the session records all the chunks of its data, so others mean the data isn't
+    // that of the session.
+    async fn resume_chunks(
+        &self,
+        session: &mut UploadSession,
+        chunks: Vec<Chunk>,
+        seen: &mut BTreeSet<ChunkAddress>,
+        tracker: &ProgressTracker,
+    ) -> Result<()> {
+        let mut pending = vec![];
+        for chunk in chunks {
+            let address = *chunk.address();
+            let _ = seen.insert(address);
+            match session.chunks.get(&address) {
+                Some(true) => {}
+                Some(false) => pending.push(chunk),
+                None => {
+                    if let Some(recorded) = session.address {
+                        return Err(Error::UploadSessionMismatch(format!(
+                            "the data isn't that of the blob at {:?} the session is for",
+                            recorded
+                        )));
+                    }
+                    let _ = session.chunks.insert(address, false);
+                    pending.push(chunk);
+                }
+            }
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+        debug!(
+            "Storing {} chunks, {} of {} recorded left",
+            pending.len(),
+            session.missing().count(),
+            session.total()
+        );
+
+        let started = Instant::now();
+        let mut uploads = stream::iter(pending)
+            .map(|chunk| self.store_chunk_with_retries(chunk, tracker))
+            .buffer_unordered(self.transfer.max_writes_in_flight());
+        let mut since_checkpoint = 0;
+        while let Some(upload) = uploads.next().await {
+            if upload.error.is_none() {
+                let _ = session.chunks.insert(upload.address, true);
+                since_checkpoint += 1;
+                if since_checkpoint >= self.transfer.max_writes_in_flight() {
+                    session.checkpoint()?;
+                    since_checkpoint = 0;
+                }
+            }
+        }
+        drop(uploads);
+        session.checkpoint()?;
+        self.record_phase(TransferPhase::Upload, started);
+        Ok(())
+    }
+
+    // Runs `encrypt` on a blocking thread, recording the time it took.
+    async fn encrypt_blocking<T, F>(&self, encrypt: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let started = Instant::now();
+        let encrypted = tokio::task::spawn_blocking(encrypt)
+            .await
+            .map_err(|err| Error::Generic(format!("Encrypting the blob failed: {}", err)))??;
+        self.record_phase(TransferPhase::Encryption, started);
+        Ok(encrypted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlobAddress, UploadSession};
+    use crate::client::utils::test_utils::{create_test_client, run_w_backoff_delayed};
+    use crate::types::{utils::random_bytes, ChunkAddress, JsonRepr};
+    use crate::url::Scope;
+    use eyre::Result;
+    use tempfile::tempdir;
+    use xor_name::XorName;
+
+    #[test]
+    fn sessions_round_trip_through_json_and_manifests() -> Result<()> {
+        let mut session = UploadSession::new(Scope::Private);
+        session.address = Some(BlobAddress::Private(XorName::random()));
+        session.chunks = vec![
+            (ChunkAddress(XorName::random()), true),
+            (ChunkAddress(XorName::random()), false),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(UploadSession::from_json(&session.to_json()?)?, session);
+
+        let dir = tempdir()?;
+        let manifest = dir.path().join("upload.manifest");
+        session.save(&manifest)?;
+        assert_eq!(UploadSession::load(&manifest)?, session);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn upload_is_resumed_from_manifest() -> Result<()> {
+        let client = create_test_client(None).await?;
+        let dir = tempdir()?;
+        let manifest = dir.path().join("upload.manifest");
+
+        let blob = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES);
+        let mut session = UploadSession::persisted_at(&manifest, Scope::Public)?;
+        // Chunks recorded by an attempt interrupted before the address was known, which
+        // aren't the data's, are dropped.
+        let stale = ChunkAddress(XorName::random());
+        let _ = session.chunks.insert(stale, false);
+        let address = client.resume_upload(&mut session, &blob[..]).await?;
+        assert!(session.is_complete());
+        assert!(!session.chunks.contains_key(&stale));
+
+        // Resuming a completed upload has nothing left to store.
+        let mut session = UploadSession::persisted_at(&manifest, Scope::Public)?;
+        assert_eq!(session.address(), Some(address));
+        assert_eq!(
+            client.resume_upload(&mut session, &blob[..]).await?,
+            address
+        );
+
+        // Nor can it be resumed with other data.
+        assert!(client
+            .resume_upload(&mut session, &random_bytes(blob.len())[..])
+            .await
+            .is_err());
+
+        let read = run_w_backoff_delayed(|| client.read_blob(address), 10, 1).await?;
+        assert_eq!(read, blob);
+
+        Ok(())
+    }
+}
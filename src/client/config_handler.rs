@@ -6,7 +6,10 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::client::{Error, Result};
+use crate::client::{
+    client_api::{LatencyObjectives, RetryPolicy},
+    ConcurrencyLimits, Error, ErrorChannelConfig, GenesisKeySource, ProxyConfig, Result,
+};
 use qp2p::Config as QuicP2pConfig;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -36,10 +39,68 @@ pub struct Config {
     pub root_dir: PathBuf,
     /// Network's genesis key
     pub genesis_key: bls::PublicKey,
+    /// Independent sources the genesis key is checked against before bootstrapping, which all
+    /// have to agree with it. Bootstrapping is refused otherwise, in case the config was
+    /// tampered with to point at a fake network.
+    #[serde(default)]
+    pub genesis_key_sources: Vec<GenesisKeySource>,
     /// QuicP2p options.
     pub qp2p: QuicP2pConfig,
     /// The amount of time to wait for responses to queries before giving up and returning an error.
     pub query_timeout: Duration,
+    /// How queries failing for reasons which may not last are retried, each attempt being
+    /// given `query_timeout`. Queries aren't retried by default.
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    /// Whether blobs' head chunks are prefetched in the background when requested, e.g. when
+    /// listing the contents of a container. Disable it on metered connections.
+    pub prefetch_head_chunks: bool,
+    /// Size in bytes above which blobs read with [`Client::read_blob_spilling`] are written
+    /// to a temporary file as they're read, instead of being held in memory.
+    /// Set it in memory-constrained environments, e.g. on mobile or in containers.
+    ///
+    /// [`Client::read_blob_spilling`]: crate::client::Client::read_blob_spilling
+    pub read_memory_limit: Option<usize>,
+    /// Max bytes of the chunks read kept in memory, so blobs read again aren't fetched from the
+    /// network again, the least recently read chunks being evicted first. No chunks are kept
+    /// if not set. See [`Client::chunk_cache_stats`] for how effective it is.
+    ///
+    /// [`Client::chunk_cache_stats`]: crate::client::Client::chunk_cache_stats
+    #[serde(default)]
+    pub chunk_cache_budget: Option<usize>,
+    /// How long operations are expected to take when the network is healthy. The client
+    /// notifies when they're consistently missed, see [`Client::subscribe_to_latency_events`].
+    ///
+    /// [`Client::subscribe_to_latency_events`]: crate::client::Client::subscribe_to_latency_events
+    #[serde(default)]
+    pub latency_objectives: LatencyObjectives,
+    /// Max number of chunk reads, chunk writes, and Register operations in flight at any one
+    /// time, and max bytes per second of the chunks of blob reads and writes, if any. Each has
+    /// its own budget, so bulk transfers don't hold back Register operations. Blob reads and
+    /// writes can be held back further per client with [`Client::with_concurrency_limits`].
+    ///
+    /// [`Client::with_concurrency_limits`]: crate::client::Client::with_concurrency_limits
+    #[serde(default)]
+    pub concurrency_limits: ConcurrencyLimits,
+    /// Max bytes the buffers chunks are serialised into to be sent may take up, shared by all
+    /// the clients and nodes of the process, and set by the first one started. Defaults to
+    /// [`DEFAULT_BUFFER_MEMORY_CAP`], unless set with the `SAFE_BUFFER_MEMORY_CAP` env var.
+    ///
+    /// [`DEFAULT_BUFFER_MEMORY_CAP`]: crate::types::DEFAULT_BUFFER_MEMORY_CAP
+    #[serde(default)]
+    pub buffer_memory_cap: Option<usize>,
+    /// How many errors received in response to commands are queued up, until taken with
+    /// [`Client::next_cmd_error`], and what to do with those received once the queue is full.
+    ///
+    /// [`Client::next_cmd_error`]: crate::client::Client::next_cmd_error
+    #[serde(default)]
+    pub error_channel: ErrorChannelConfig,
+    /// Proxy to reach the network through, if any. Clients refuse to bootstrap when they
+    /// can't connect through it, rather than bypassing it, which is always the case for now:
+    /// the network is reached over QUIC, which neither kind of proxy can relay yet, so setting
+    /// one fails with [`Error::ProxyNotSupported`].
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
 }
 
 impl Config {
@@ -77,8 +138,18 @@ impl Config {
             local_addr: local_addr.unwrap_or_else(|| SocketAddr::from(DEFAULT_LOCAL_ADDR)),
             root_dir: root_dir.clone(),
             genesis_key,
+            genesis_key_sources: vec![],
             qp2p,
             query_timeout: query_timeout.unwrap_or(DEFAULT_QUERY_TIMEOUT),
+            retry_policy: RetryPolicy::default(),
+            prefetch_head_chunks: true,
+            read_memory_limit: None,
+            chunk_cache_budget: None,
+            latency_objectives: LatencyObjectives::default(),
+            concurrency_limits: ConcurrencyLimits::default(),
+            buffer_memory_cap: None,
+            error_channel: ErrorChannelConfig::default(),
+            proxy: None,
         }
     }
 }
@@ -170,8 +241,18 @@ mod tests {
             local_addr: (Ipv4Addr::UNSPECIFIED, 0).into(),
             root_dir: root_dir.clone(),
             genesis_key,
+            genesis_key_sources: vec![],
             qp2p: QuicP2pConfig::default(),
             query_timeout: DEFAULT_QUERY_TIMEOUT,
+            retry_policy: RetryPolicy::default(),
+            prefetch_head_chunks: true,
+            read_memory_limit: None,
+            chunk_cache_budget: None,
+            latency_objectives: LatencyObjectives::default(),
+            concurrency_limits: ConcurrencyLimits::default(),
+            buffer_memory_cap: None,
+            error_channel: ErrorChannelConfig::default(),
+            proxy: None,
         };
         assert_eq!(serialize(&config)?, serialize(&expected_config)?);
 
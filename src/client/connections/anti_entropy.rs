@@ -0,0 +1,79 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::{MessageId, SectionAuthorityProvider};
+use std::{collections::BTreeMap, net::SocketAddr};
+use xor_name::{Prefix, XorName};
+
+/// An anti-entropy (AE) response to one of the client's messages, received from Elders whose
+/// knowledge of the network differed from the client's, and what the client did about it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AntiEntropyEvent {
+    /// Id of the message which was bounced.
+    pub msg_id: MessageId,
+    /// Elder the response was received from.
+    pub src: SocketAddr,
+    /// Why the message was bounced.
+    pub reason: AntiEntropyReason,
+    /// Prefix of the section the response pointed to.
+    pub prefix: Prefix,
+    /// Current key of that section.
+    pub section_key: bls::PublicKey,
+    /// Current Elders of that section.
+    pub elders: BTreeMap<XorName, SocketAddr>,
+    /// What the client did about it.
+    pub outcome: AntiEntropyOutcome,
+}
+
+/// Why Elders bounced a message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AntiEntropyReason {
+    /// The message was sent to Elders who aren't responsible for its destination, or who
+    /// couldn't tell which section the client knows about. It's redirected to the Elders given.
+    Redirect,
+    /// The client's knowledge of the destination section was outdated. The message is retried
+    /// with the section's current key, once its proof chain checked out.
+    Retry,
+}
+
+/// What the client did about a bounced message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AntiEntropyOutcome {
+    /// The message was resent to these Elders.
+    Resent(Vec<SocketAddr>),
+    /// The message had already been resent to the same Elders, so it was dropped.
+    AlreadyResent,
+    /// The command had already been resent once, or the deadline of the operation it's part of
+    /// had passed, so it was dropped.
+    RetryExpired,
+    /// The section info received wasn't signed by the section, so the message was dropped.
+    InvalidSignature,
+    /// The section info received couldn't be trusted from what the client knows of the network,
+    /// so the message was dropped.
+    UntrustedSection(String),
+}
+
+impl AntiEntropyEvent {
+    pub(super) fn new(
+        msg_id: MessageId,
+        src: SocketAddr,
+        reason: AntiEntropyReason,
+        section_auth: &SectionAuthorityProvider,
+        outcome: AntiEntropyOutcome,
+    ) -> Self {
+        Self {
+            msg_id,
+            src,
+            reason,
+            prefix: section_auth.prefix,
+            section_key: section_auth.public_key_set.public_key(),
+            elders: section_auth.elders.clone(),
+            outcome,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::net::SocketAddr;
+use tokio::sync::mpsc::UnboundedSender;
+use xor_name::Prefix;
+
+/// A step of the client bootstrapping to the network, as notified to those creating it
+/// with [`Client::new_with_progress`].
+///
+/// [`Client::new_with_progress`]: crate::client::Client::new_with_progress
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BootstrapProgress {
+    /// Connecting to any of the given contacts is being tried, on this attempt, from 1.
+    ContactTried {
+        /// Attempt number, from 1.
+        attempt: u8,
+        /// Bootstrap nodes tried.
+        contacts: Vec<SocketAddr>,
+    },
+    /// The attempt failed, the error given, and the bootstrap is retried unless it was the last.
+    AttemptFailed {
+        /// Attempt number, from 1.
+        attempt: u8,
+        /// Why the attempt failed.
+        error: String,
+    },
+    /// A connection was established with the given contact.
+    HandshakeOk(SocketAddr),
+    /// The client's section was found out about, and its info trusted.
+    SectionInfoReceived {
+        /// Prefix of the section.
+        prefix: Prefix,
+        /// Number of Elders of the section.
+        elders: usize,
+    },
+    /// Connections were established with Elders of the client's section: the client is ready.
+    EldersConnected {
+        /// Prefix of the section.
+        prefix: Prefix,
+        /// Number of Elders connected to.
+        elders: usize,
+    },
+}
+
+/// Notifies the steps of bootstrapping, if anybody asked for them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProgressReporter(Option<UnboundedSender<BootstrapProgress>>);
+
+impl ProgressReporter {
+    pub(crate) fn new(sender: UnboundedSender<BootstrapProgress>) -> Self {
+        Self(Some(sender))
+    }
+
+    /// Whether anybody asked for the steps of bootstrapping.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub(crate) fn report(&self, progress: BootstrapProgress) {
+        if let Some(sender) = &self.0 {
+            // The receiver being dropped is fine, bootstrapping carries on regardless.
+            let _ = sender.send(progress);
+        }
+    }
+}
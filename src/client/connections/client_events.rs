@@ -0,0 +1,78 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{AntiEntropyReason, RotationReason};
+use crate::messaging::{data::CmdError, MessageId};
+use std::net::SocketAddr;
+use xor_name::Prefix;
+
+/// A change in the client's view of the network, or in its connections to it, as notified to
+/// subscribers of [`Client::subscribe`], so applications can react to it rather than poll.
+///
+/// [`Client::subscribe`]: crate::client::Client::subscribe
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClientEvent {
+    /// Elders rejected one of the client's commands. It's also queued up for
+    /// [`Client::next_cmd_error`].
+    ///
+    /// [`Client::next_cmd_error`]: crate::client::Client::next_cmd_error
+    CmdError {
+        /// Id of the message carrying the command.
+        correlation_id: MessageId,
+        /// Why the command was rejected.
+        error: CmdError,
+    },
+    /// The client learnt of a new key of a section, after checking it against the proof chain
+    /// received, e.g. as the section's Elders changed or the section split.
+    SectionKeyUpdated {
+        /// Prefix of the section.
+        prefix: Prefix,
+        /// The section's new key.
+        key: bls::PublicKey,
+    },
+    /// The connection to a node was closed, as its quality degraded. A new one is established
+    /// the next time a message is sent to the node.
+    ConnectionLost {
+        /// Address of the node.
+        addr: SocketAddr,
+        /// Why the connection was closed.
+        reason: RotationReason,
+    },
+    /// A message made it to a node the connection to was lost, over a new connection, or
+    /// the client bootstrapped again to a node once connectivity to the network was lost
+    /// altogether.
+    Reconnected {
+        /// Address of the node.
+        addr: SocketAddr,
+    },
+    /// Connectivity to the network was lost altogether: the connections to the nodes the
+    /// client exchanged messages with were closed for failing, and sending to any other node
+    /// fails too. The client keeps trying to bootstrap again, notifying
+    /// [`ClientEvent::Reconnected`] once it does.
+    NetworkUnreachable,
+    /// One of the client's queries was bounced by the Elders it was sent to, and resent to
+    /// other Elders, or with the destination section's current key.
+    QueryRetried {
+        /// Id of the message carrying the query.
+        msg_id: MessageId,
+        /// Why the query was bounced.
+        reason: AntiEntropyReason,
+        /// Elders the query was resent to.
+        elders: Vec<SocketAddr>,
+    },
+    /// One of the client's commands was bounced by the Elders it was sent to, and resent to
+    /// other Elders, or with the destination section's current key.
+    CmdRetried {
+        /// Id of the message carrying the command.
+        msg_id: MessageId,
+        /// Why the command was bounced.
+        reason: AntiEntropyReason,
+        /// Elders the command was resent to.
+        elders: Vec<SocketAddr>,
+    },
+}
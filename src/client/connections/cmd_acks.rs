@@ -0,0 +1,294 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::{data::CmdError, MessageId, WireMsg};
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use tokio::{sync::oneshot, time::Instant};
+
+/// Commands sent whose handles are still around, by the id of the message carrying them, so the
+/// errors Elders respond with get to the handle of the command which caused them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PendingCmds {
+    senders: Arc<Mutex<HashMap<MessageId, oneshot::Sender<CmdError>>>>,
+}
+
+impl PendingCmds {
+    /// Registers a command about to be sent in the message of the given id.
+    pub(crate) fn register(&self, msg_id: MessageId) -> CmdHandle {
+        let (sender, receiver) = oneshot::channel();
+        let _ = self
+            .senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(msg_id, sender);
+        CmdHandle {
+            msg_id,
+            receiver,
+            pending: self.clone(),
+        }
+    }
+
+    /// Passes the error on to the handle of the command, returning whether there was one.
+    /// Only the first error received for a command is passed on, the others Elders respond
+    /// with being the same.
+    pub(crate) fn resolve(&self, msg_id: &MessageId, error: CmdError) -> bool {
+        let sender = self
+            .senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(msg_id);
+        sender.map_or(false, |sender| sender.send(error).is_ok())
+    }
+
+    /// Number of commands whose handles are still around.
+    pub(crate) fn len(&self) -> usize {
+        self.senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Drops all the commands, their handles resolving at once.
+    pub(crate) fn clear(&self) {
+        self.senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear()
+    }
+
+    fn remove(&self, msg_id: &MessageId) {
+        let _ = self
+            .senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(msg_id);
+    }
+}
+
+/// Handle of a command sent to the network, to find out whether Elders rejected it.
+///
+/// Elders only respond to commands they fail to carry out, so a command is deemed acknowledged
+/// once no error was received for it within the time given to [`CmdHandle::await_ack`].
+/// Errors received are still queued up for [`Client::next_cmd_error`] as well.
+///
+/// [`Client::next_cmd_error`]: crate::client::Client::next_cmd_error
+#[derive(Debug)]
+pub struct CmdHandle {
+    msg_id: MessageId,
+    receiver: oneshot::Receiver<CmdError>,
+    pending: PendingCmds,
+}
+
+impl CmdHandle {
+    /// Id of the message carrying the command, as in [`ClientEvent::CmdError`].
+    ///
+    /// [`ClientEvent::CmdError`]: crate::client::ClientEvent::CmdError
+    pub fn msg_id(&self) -> MessageId {
+        self.msg_id
+    }
+
+    /// Waits up to `timeout` for Elders to respond to the command with an error, returning it
+    /// if they did. Returns `Ok` if no error was received within the timeout, or before the
+    /// client was closed.
+    pub async fn await_ack(mut self, timeout: Duration) -> Result<(), CmdError> {
+        match tokio::time::timeout(timeout, &mut self.receiver).await {
+            Ok(Ok(error)) => Err(error),
+            Ok(Err(_)) | Err(_) => Ok(()),
+        }
+    }
+}
+
+impl Drop for CmdHandle {
+    fn drop(&mut self) {
+        self.pending.remove(&self.msg_id);
+    }
+}
+
+/// Commands which may still be resent once, when Elders bounce them for the client's knowledge
+/// of the destination section being outdated, by the id of the message carrying them, with the
+/// deadline of the operation they're part of.
+///
+/// Unlike queries, whose callers await a response, commands are sent and forgotten, so those
+/// bounced are resent as they are: the signature of the command still holds, only the
+/// destination section key of the message changing.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CmdRetries {
+    deadlines: Arc<Mutex<HashMap<MessageId, Instant>>>,
+}
+
+impl CmdRetries {
+    /// Allows the command sent in the message of the given id to be resent once, until
+    /// `deadline`. Commands past their deadlines are forgotten.
+    pub(crate) fn register(&self, msg_id: MessageId, deadline: Instant) {
+        let now = Instant::now();
+        let mut deadlines = self
+            .deadlines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        deadlines.retain(|_, deadline| *deadline > now);
+        let _ = deadlines.insert(msg_id, deadline);
+    }
+
+    /// Takes the one resend of the command, returning whether it was still to be taken,
+    /// before the deadline of the command.
+    pub(crate) fn take(&self, msg_id: &MessageId) -> bool {
+        self.deadlines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(msg_id)
+            .map_or(false, |deadline| Instant::now() < deadline)
+    }
+
+    /// Forgets all the commands, none of them being resent anymore.
+    pub(crate) fn clear(&self) {
+        self.deadlines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear()
+    }
+}
+
+// For how long the messages carrying commands are kept, to be sent again to Elders telling us
+// they came in corrupted, which they do as soon as they get them.
+const CMD_COPY_DURATION: Duration = Duration::from_secs(10);
+
+// A message carrying a command, with when it was sent, and the Elders it was sent again to.
+type CmdCopy = (WireMsg, Instant, BTreeSet<SocketAddr>);
+
+/// Messages carrying the commands sent lately, by their ids, to send them again, once, to the
+/// Elders telling us they came in corrupted.
+///
+/// The payload of a message is shared with its copy, so chunks sent aren't held twice, only a
+/// little longer.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CmdCopies {
+    copies: Arc<Mutex<HashMap<MessageId, CmdCopy>>>,
+}
+
+impl CmdCopies {
+    /// Keeps a copy of the message carrying a command, those kept for too long being dropped.
+    pub(crate) fn keep(&self, wire_msg: WireMsg) {
+        let mut copies = self.copies.lock().unwrap_or_else(PoisonError::into_inner);
+        copies.retain(|_, (_, sent, _)| sent.elapsed() < CMD_COPY_DURATION);
+        let _ = copies.insert(
+            wire_msg.msg_id(),
+            (wire_msg, Instant::now(), BTreeSet::new()),
+        );
+    }
+
+    /// Returns the message of the given id to send again to `elder`, unless it already was,
+    /// or was sent too long ago.
+    pub(crate) fn resend_to(&self, msg_id: &MessageId, elder: SocketAddr) -> Option<WireMsg> {
+        let mut copies = self.copies.lock().unwrap_or_else(PoisonError::into_inner);
+        let (wire_msg, sent, resent_to) = copies.get_mut(msg_id)?;
+        if sent.elapsed() < CMD_COPY_DURATION && resent_to.insert(elder) {
+            Some(wire_msg.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Drops all the copies, none of the commands being sent again anymore.
+    pub(crate) fn clear(&self) {
+        self.copies
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CmdCopies, CmdRetries, PendingCmds};
+    use crate::messaging::{
+        data::{CmdError, Error as ErrorMessage},
+        DstLocation, MessageId, MsgKind, ServiceAuth, WireMsg,
+    };
+    use crate::types::Keypair;
+    use bytes::Bytes;
+    use eyre::Result;
+    use rand::rngs::OsRng;
+    use std::{net::SocketAddr, time::Duration};
+    use tokio::time::Instant;
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn errors_resolve_the_handle_of_their_command() -> Result<()> {
+        let pending = PendingCmds::default();
+        let rejected = pending.register(MessageId::new());
+        let accepted = pending.register(MessageId::new());
+        assert_eq!(pending.len(), 2);
+
+        let error = CmdError::Data(ErrorMessage::DataExists);
+        assert!(pending.resolve(&rejected.msg_id(), error.clone()));
+        // Only the first error received for a command is passed on.
+        assert!(!pending.resolve(&rejected.msg_id(), error.clone()));
+        assert!(!pending.resolve(&MessageId::new(), error.clone()));
+
+        assert_eq!(
+            rejected.await_ack(Duration::from_millis(100)).await,
+            Err(error)
+        );
+        assert_eq!(accepted.await_ack(Duration::from_millis(100)).await, Ok(()));
+        assert_eq!(pending.len(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn commands_are_resent_once_within_their_deadline() -> Result<()> {
+        let retries = CmdRetries::default();
+        let bounced = MessageId::new();
+        retries.register(bounced, Instant::now() + Duration::from_secs(60));
+        assert!(retries.take(&bounced));
+        assert!(!retries.take(&bounced));
+
+        let late = MessageId::new();
+        retries.register(late, Instant::now());
+        assert!(!retries.take(&late));
+        assert!(!retries.take(&MessageId::new()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn copies_are_sent_again_once_to_each_elder() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let payload = Bytes::from_static(b"command");
+        let auth = ServiceAuth {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(&payload),
+            delegation: None,
+        };
+        let dst_location = DstLocation::Section {
+            name: XorName::random(),
+            section_pk: bls::SecretKey::random().public_key(),
+        };
+        let msg_id = MessageId::new();
+        let wire_msg = WireMsg::new_msg(msg_id, payload, MsgKind::ServiceMsg(auth), dst_location)?;
+
+        let copies = CmdCopies::default();
+        copies.keep(wire_msg.clone());
+
+        let (elder, other) = (
+            SocketAddr::from(([10, 0, 0, 1], 12000)),
+            SocketAddr::from(([10, 0, 0, 2], 12000)),
+        );
+        assert_eq!(copies.resend_to(&msg_id, elder), Some(wire_msg.clone()));
+        assert_eq!(copies.resend_to(&msg_id, elder), None);
+        assert_eq!(copies.resend_to(&msg_id, other), Some(wire_msg));
+        assert_eq!(copies.resend_to(&MessageId::new(), elder), None);
+
+        Ok(())
+    }
+}
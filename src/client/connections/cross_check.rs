@@ -0,0 +1,229 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::data::{OperationId, QueryResponse};
+use std::net::SocketAddr;
+
+/// Conflicting responses to a query, received from different Elders.
+///
+/// Honest Elders may briefly disagree while data is being replicated, but persistent
+/// divergence is a sign of faulty or byzantine Elders, so it's worth monitoring.
+#[derive(Clone, Debug)]
+pub struct ResponseDivergence {
+    /// Id of the operation that was queried.
+    pub operation_id: OperationId,
+    /// Response received from each Elder.
+    pub responses: Vec<(SocketAddr, QueryResponse)>,
+    /// Whether a majority of the queried Elders agreed on a response, which was then used.
+    pub resolved: bool,
+}
+
+/// How many of the Elders a query is sent to must agree on a response for it to be returned.
+///
+/// Each Elder responds with the data it holds or, for chunks, which are held by Adults, with
+/// that returned by the first of the Adults holding it to respond. Waiting for more Elders to
+/// agree trades latency for a stronger guarantee that the response is what the network holds.
+///
+/// Queries aren't fanned out to the holders of chunks: that every holder of a chunk still has
+/// it isn't checked by reads, whatever the consistency, but by the replication checks Elders
+/// run.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ReadConsistency {
+    /// The first response is returned, once checked against its address for chunks.
+    Fastest,
+    /// The response a majority of the Elders agree on is returned.
+    Quorum,
+    /// The response is only returned if all the Elders agree on it.
+    All,
+}
+
+/// What the responses received so far to a query add up to.
+#[derive(Debug)]
+pub(super) enum Verdict {
+    /// One response was given by more Elders than any other.
+    Agreed(QueryResponse),
+    /// Several responses were given by the same, highest, number of Elders.
+    Conflicting,
+    /// No responses were received.
+    Empty,
+}
+
+/// Tallies the (non-error) responses to a query from each Elder, to cross-check them.
+///
+/// Responses reaching us have had their signature verified upon deserialisation,
+/// and only the first response from each Elder is counted.
+#[derive(Debug)]
+pub(super) struct ResponseTally {
+    queried: usize,
+    responses: Vec<(SocketAddr, QueryResponse)>,
+}
+
+impl ResponseTally {
+    /// A tally for a query sent to `queried` Elders.
+    pub(super) fn new(queried: usize) -> Self {
+        Self {
+            queried,
+            responses: Vec::new(),
+        }
+    }
+
+    /// Records the response from `src`. Returns false if `src` had already responded.
+    pub(super) fn add(&mut self, src: SocketAddr, response: QueryResponse) -> bool {
+        if self.responses.iter().any(|(elder, _)| *elder == src) {
+            return false;
+        }
+        self.responses.push((src, response));
+        true
+    }
+
+    /// Number of responses recorded.
+    pub(super) fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Returns the response given by a majority of the queried Elders, if any.
+    pub(super) fn majority(&self) -> Option<&QueryResponse> {
+        self.votes()
+            .into_iter()
+            .find(|(_, count)| *count > self.queried / 2)
+            .map(|(response, _)| response)
+    }
+
+    /// Returns the response agreed on by as many of the queried Elders as `consistency`
+    /// requires, if any.
+    pub(super) fn satisfying(&self, consistency: ReadConsistency) -> Option<&QueryResponse> {
+        match consistency {
+            ReadConsistency::Fastest => self.agreed_by(1),
+            ReadConsistency::Quorum => self.majority(),
+            ReadConsistency::All => self.agreed_by(self.queried),
+        }
+    }
+
+    // Returns the response given by at least `count` Elders, if any.
+    fn agreed_by(&self, count: usize) -> Option<&QueryResponse> {
+        self.votes()
+            .into_iter()
+            .find(|(_, votes)| *votes >= count)
+            .map(|(response, _)| response)
+    }
+
+    /// Returns what the responses received so far add up to.
+    pub(super) fn verdict(&self) -> Verdict {
+        let mut votes = self.votes();
+        votes.sort_by(|(_, lhs), (_, rhs)| rhs.cmp(lhs));
+        match votes.as_slice() {
+            [] => Verdict::Empty,
+            [(_, first), (_, second), ..] if first == second => Verdict::Conflicting,
+            [(response, _), ..] => Verdict::Agreed((*response).clone()),
+        }
+    }
+
+    /// Returns the divergence between the responses, if they weren't all the same.
+    pub(super) fn divergence(
+        &self,
+        operation_id: OperationId,
+        resolved: bool,
+    ) -> Option<ResponseDivergence> {
+        if self.votes().len() < 2 {
+            return None;
+        }
+        Some(ResponseDivergence {
+            operation_id,
+            responses: self.responses.clone(),
+            resolved,
+        })
+    }
+
+    // Distinct responses, and how many Elders gave each of them.
+    fn votes(&self) -> Vec<(&QueryResponse, usize)> {
+        let mut votes: Vec<(&QueryResponse, usize)> = Vec::new();
+        for (_, response) in &self.responses {
+            match votes.iter_mut().find(|(voted, _)| *voted == response) {
+                Some((_, count)) => *count += 1,
+                None => votes.push((response, 1)),
+            }
+        }
+        votes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReadConsistency, ResponseTally, Verdict};
+    use crate::messaging::data::QueryResponse;
+    use crate::types::{Keypair, PublicKey};
+    use eyre::{bail, Result};
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    fn owner_response(owner: PublicKey) -> QueryResponse {
+        QueryResponse::GetRegisterOwner((Ok(owner), "op".to_string()))
+    }
+
+    fn elder(port: u16) -> SocketAddr {
+        SocketAddr::from((Ipv4Addr::LOCALHOST, port))
+    }
+
+    #[test]
+    fn majority_wins_and_divergence_is_reported() -> Result<()> {
+        let mut rng = rand::thread_rng();
+        let honest = owner_response(Keypair::new_ed25519(&mut rng).public_key());
+        let byzantine = owner_response(Keypair::new_ed25519(&mut rng).public_key());
+
+        let mut tally = ResponseTally::new(3);
+        assert!(tally.add(elder(1), byzantine.clone()));
+        assert!(tally.majority().is_none());
+        assert!(tally.divergence("op".to_string(), false).is_none());
+
+        assert!(tally.add(elder(2), honest.clone()));
+        assert!(tally.majority().is_none());
+        assert!(matches!(tally.verdict(), Verdict::Conflicting));
+
+        // Responding twice doesn't count twice.
+        assert!(!tally.add(elder(1), byzantine));
+        assert!(tally.majority().is_none());
+
+        assert!(tally.add(elder(3), honest.clone()));
+        assert_eq!(tally.majority(), Some(&honest));
+        match tally.verdict() {
+            Verdict::Agreed(response) => assert_eq!(response, honest),
+            verdict => bail!("Unexpected verdict {:?}", verdict),
+        }
+
+        let divergence = tally.divergence("op".to_string(), true);
+        assert_eq!(divergence.map(|d| d.responses.len()), Some(3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn responses_are_returned_once_consistent_enough() {
+        let mut rng = rand::thread_rng();
+        let honest = owner_response(Keypair::new_ed25519(&mut rng).public_key());
+        let stale = owner_response(Keypair::new_ed25519(&mut rng).public_key());
+
+        let mut tally = ResponseTally::new(3);
+        assert!(tally.satisfying(ReadConsistency::Fastest).is_none());
+
+        assert!(tally.add(elder(1), honest.clone()));
+        assert_eq!(tally.satisfying(ReadConsistency::Fastest), Some(&honest));
+        assert!(tally.satisfying(ReadConsistency::Quorum).is_none());
+
+        assert!(tally.add(elder(2), honest.clone()));
+        assert_eq!(tally.satisfying(ReadConsistency::Quorum), Some(&honest));
+        assert!(tally.satisfying(ReadConsistency::All).is_none());
+
+        let mut all = ResponseTally::new(3);
+        for port in 1..=3 {
+            assert!(all.add(elder(port), honest.clone()));
+        }
+        assert_eq!(all.satisfying(ReadConsistency::All), Some(&honest));
+
+        assert!(tally.add(elder(3), stale));
+        assert!(tally.satisfying(ReadConsistency::All).is_none());
+    }
+}
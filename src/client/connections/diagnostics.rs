@@ -0,0 +1,167 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::types::BufferPoolStats;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    net::SocketAddr,
+    sync::{Arc, Mutex, PoisonError},
+};
+use tokio::task::JoinHandle;
+use xor_name::Prefix;
+
+/// A snapshot of what a client is up to, as returned by [`Client::diagnostics`], for
+/// debugging applications which seem stuck.
+///
+/// [`Client::diagnostics`]: crate::client::Client::diagnostics
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Diagnostics {
+    /// Number of the client's internal tasks running, by name.
+    pub active_tasks: BTreeMap<&'static str, usize>,
+    /// Number of operations using the session.
+    pub in_flight_ops: usize,
+    /// Number of those run as [`OperationPriority::Foreground`].
+    ///
+    /// [`OperationPriority::Foreground`]: crate::client::OperationPriority::Foreground
+    pub foreground_ops: usize,
+    /// Number of background operations queued behind foreground ones, or other background ones.
+    pub queued_background_ops: usize,
+    /// Number of queries waiting for responses from Elders.
+    pub pending_queries: usize,
+    /// Number of commands sent whose [`CmdHandle`]s are still around, awaiting errors.
+    ///
+    /// [`CmdHandle`]: crate::client::CmdHandle
+    pub pending_cmds: usize,
+    /// Number of errors received in response to commands, waiting to be taken by
+    /// [`Client::next_cmd_error`].
+    ///
+    /// [`Client::next_cmd_error`]: crate::client::Client::next_cmd_error
+    pub queued_cmd_errors: usize,
+    /// Number of errors received in response to commands which were dropped, as more were
+    /// received than could be queued. See [`ErrorChannelConfig`].
+    ///
+    /// [`ErrorChannelConfig`]: crate::client::ErrorChannelConfig
+    pub dropped_cmd_errors: u64,
+    /// Use of the buffers chunks are serialised into, shared by all clients in the process.
+    /// Operations waiting for buffers are held back by transfers already in flight.
+    pub chunk_buffers: BufferPoolStats,
+    /// State of the connections to the Elders the client knows of.
+    pub connections: Vec<ConnectionState>,
+}
+
+/// State of the connection to a node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConnectionState {
+    /// Address of the node.
+    pub addr: SocketAddr,
+    /// Prefix of the section the node is an Elder of, if known.
+    pub prefix: Option<Prefix>,
+    /// Whether the client bootstrapped off the node.
+    pub bootstrap: bool,
+    /// Whether there's an open connection to the node.
+    pub connected: bool,
+}
+
+/// Spawns the client's internal tasks, keeping count of those running.
+///
+/// With the `tokio-console` feature, and `--cfg tokio_unstable` in `RUSTFLAGS`, tasks are
+/// named after what they do, for them to be told apart in tokio-console.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TaskTracker {
+    active: Arc<Mutex<BTreeMap<&'static str, usize>>>,
+}
+
+impl TaskTracker {
+    pub(crate) fn spawn<F>(&self, name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let running = self.enter(name);
+        let task = async move {
+            let _running = running;
+            future.await
+        };
+
+        #[cfg(all(feature = "tokio-console", tokio_unstable))]
+        {
+            tokio::task::Builder::new().name(name).spawn(task)
+        }
+        #[cfg(not(all(feature = "tokio-console", tokio_unstable)))]
+        {
+            tokio::spawn(task)
+        }
+    }
+
+    pub(crate) fn active(&self) -> BTreeMap<&'static str, usize> {
+        self.active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn enter(&self, name: &'static str) -> Running {
+        let mut active = self.active.lock().unwrap_or_else(PoisonError::into_inner);
+        *active.entry(name).or_default() += 1;
+        Running {
+            name,
+            tracker: self.clone(),
+        }
+    }
+}
+
+// Held by a task for as long as it's running, including when it's aborted.
+struct Running {
+    name: &'static str,
+    tracker: TaskTracker,
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let mut active = self
+            .tracker
+            .active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(count) = active.get_mut(self.name) {
+            *count -= 1;
+            if *count == 0 {
+                let _ = active.remove(self.name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TaskTracker;
+    use eyre::Result;
+    use tokio::sync::oneshot;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn counts_running_tasks() -> Result<()> {
+        let tracker = TaskTracker::default();
+
+        let (sender, receiver) = oneshot::channel::<()>();
+        let waiting = tracker.spawn("waiting", async move {
+            let _ = receiver.await;
+        });
+        let stuck = tracker.spawn("stuck", futures::future::pending::<()>());
+        assert_eq!(tracker.active().get("waiting"), Some(&1));
+        assert_eq!(tracker.active().get("stuck"), Some(&1));
+
+        sender.send(()).map_err(|_| eyre::eyre!("task gone"))?;
+        waiting.await?;
+        stuck.abort();
+        let _ = stuck.await;
+        assert!(tracker.active().is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::data::CmdError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+};
+use tokio::sync::Notify;
+use tracing::warn;
+
+// Default number of errors queued up before the overflow policy kicks in.
+const DEFAULT_CAPACITY: usize = 10;
+
+/// What to do with errors received from Elders in response to commands, once the client's
+/// queue of them is full.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum OverflowPolicy {
+    /// Drop the oldest error queued, to make room for the new one.
+    DropOldest,
+    /// Drop the new error.
+    DropNewest,
+    /// Hold off handling further messages from Elders until an error is taken off the queue.
+    Backpressure,
+}
+
+impl Default for OverflowPolicy {
+    fn default() -> Self {
+        Self::DropOldest
+    }
+}
+
+/// Configuration of the queue of errors received from Elders in response to commands.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ErrorChannelConfig {
+    /// Number of errors queued up before `overflow` kicks in.
+    pub capacity: usize,
+    /// What to do with errors received once the queue is full.
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for ErrorChannelConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+/// Queue of the errors received from Elders in response to commands, keeping count of
+/// those dropped as it overflowed.
+#[derive(Clone, Debug)]
+pub(crate) struct ErrorChannel {
+    config: ErrorChannelConfig,
+    queue: Arc<Mutex<VecDeque<CmdError>>>,
+    queued: Arc<Notify>,
+    taken: Arc<Notify>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl ErrorChannel {
+    pub(crate) fn new(config: ErrorChannelConfig) -> Self {
+        Self {
+            config: ErrorChannelConfig {
+                // A queue which can't hold any error would never be drained.
+                capacity: config.capacity.max(1),
+                ..config
+            },
+            queue: Arc::new(Mutex::new(VecDeque::new())),
+            queued: Arc::new(Notify::new()),
+            taken: Arc::new(Notify::new()),
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Queues an error, as per the overflow policy if the queue is full.
+    pub(crate) async fn send(&self, error: CmdError) {
+        loop {
+            {
+                let mut queue = self.lock();
+                if queue.len() < self.config.capacity {
+                    queue.push_back(error);
+                    self.queued.notify_one();
+                    return;
+                }
+                match self.config.overflow {
+                    OverflowPolicy::DropOldest => {
+                        if let Some(dropped) = queue.pop_front() {
+                            warn!("Error channel full, dropping oldest error: {:?}", dropped);
+                        }
+                        let _ = self.dropped.fetch_add(1, Ordering::SeqCst);
+                        queue.push_back(error);
+                        self.queued.notify_one();
+                        return;
+                    }
+                    OverflowPolicy::DropNewest => {
+                        warn!("Error channel full, dropping error: {:?}", error);
+                        let _ = self.dropped.fetch_add(1, Ordering::SeqCst);
+                        return;
+                    }
+                    OverflowPolicy::Backpressure => {}
+                }
+            }
+            self.taken.notified().await;
+        }
+    }
+
+    /// Waits for an error to be queued, and takes it off the queue.
+    pub(crate) async fn recv(&self) -> CmdError {
+        loop {
+            if let Some(error) = self.try_recv() {
+                return error;
+            }
+            self.queued.notified().await;
+        }
+    }
+
+    /// Takes the oldest error off the queue, if there's any.
+    pub(crate) fn try_recv(&self) -> Option<CmdError> {
+        let error = self.lock().pop_front()?;
+        self.taken.notify_one();
+        Some(error)
+    }
+
+    /// Number of errors queued.
+    pub(crate) fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Number of errors dropped as the queue overflowed.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::SeqCst)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<CmdError>> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorChannel, ErrorChannelConfig, OverflowPolicy};
+    use crate::messaging::data::{CmdError, Error as ErrorMessage};
+    use eyre::Result;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    fn error(n: u64) -> CmdError {
+        CmdError::Data(ErrorMessage::InvalidOperation(n.to_string()))
+    }
+
+    fn channel(overflow: OverflowPolicy) -> ErrorChannel {
+        ErrorChannel::new(ErrorChannelConfig {
+            capacity: 2,
+            overflow,
+        })
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn overflow_follows_policy() -> Result<()> {
+        let oldest_dropped = channel(OverflowPolicy::DropOldest);
+        let newest_dropped = channel(OverflowPolicy::DropNewest);
+        for n in 0..3 {
+            oldest_dropped.send(error(n)).await;
+            newest_dropped.send(error(n)).await;
+        }
+        assert_eq!(oldest_dropped.dropped(), 1);
+        assert_eq!(oldest_dropped.recv().await, error(1));
+        assert_eq!(newest_dropped.dropped(), 1);
+        assert_eq!(newest_dropped.recv().await, error(0));
+        assert_eq!(newest_dropped.recv().await, error(1));
+        assert_eq!(newest_dropped.try_recv(), None);
+
+        let backpressured = channel(OverflowPolicy::Backpressure);
+        backpressured.send(error(0)).await;
+        backpressured.send(error(1)).await;
+        let mut sending = {
+            let backpressured = backpressured.clone();
+            tokio::spawn(async move { backpressured.send(error(2)).await })
+        };
+        assert!(timeout(Duration::from_millis(100), &mut sending)
+            .await
+            .is_err());
+        assert_eq!(backpressured.recv().await, error(0));
+        timeout(Duration::from_millis(100), sending).await??;
+        assert_eq!(backpressured.len(), 2);
+        assert_eq!(backpressured.dropped(), 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{ClientEvent, Session};
+use std::{collections::BTreeSet, net::SocketAddr, sync::PoisonError, time::Duration};
+use tokio::time::{sleep, Instant};
+use tracing::{debug, info, warn};
+
+// How often connectivity to the network is checked.
+const CONNECTIVITY_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+// How long to wait before bootstrapping again, after failing to, doubling on each failure...
+const INITIAL_REBOOTSTRAP_DELAY: Duration = Duration::from_secs(1);
+// ...up to this.
+const MAX_REBOOTSTRAP_DELAY: Duration = Duration::from_secs(60);
+
+impl Session {
+    /// The node we bootstrapped to, last.
+    pub(crate) fn bootstrap_peer(&self) -> SocketAddr {
+        *self
+            .bootstrap_peer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Spawns the task checking on our connectivity to the network, bootstrapping again
+    /// should it be lost altogether, until the session is closed.
+    pub(crate) fn spawn_connectivity_watchdog(&self) {
+        let session = self.clone();
+        let _ = self.spawn("connectivity_watchdog", async move {
+            while !session.scheduler.is_closed() {
+                sleep(CONNECTIVITY_CHECK_INTERVAL).await;
+                if session.links.isolated() {
+                    session.rebootstrap().await;
+                }
+            }
+        });
+    }
+
+    // Bootstraps again to any of the nodes we were given to bootstrap to, or know are Elders,
+    // with exponential backoff, until it succeeds or the session is closed.
+    async fn rebootstrap(&self) {
+        warn!("Connectivity to the network was lost, bootstrapping again");
+        self.notify(ClientEvent::NetworkUnreachable);
+
+        let mut delay = INITIAL_REBOOTSTRAP_DELAY;
+        while !self.scheduler.is_closed() {
+            let mut contacts: BTreeSet<_> = self.bootstrap_nodes.iter().copied().collect();
+            for section_auth in self.network.all() {
+                contacts.extend(section_auth.elders.values().copied());
+            }
+            let contacts: Vec<_> = contacts.into_iter().collect();
+
+            let started = Instant::now();
+            if let Some(peer) = self.endpoint.connect_to_any(&contacts).await {
+                info!("Bootstrapped again to {}", peer);
+                *self
+                    .bootstrap_peer
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = peer;
+                self.links.restored(peer, started.elapsed());
+                self.notify(ClientEvent::Reconnected { addr: peer });
+                return;
+            }
+
+            debug!(
+                "Couldn't bootstrap again to any of {} contacts, retrying in {:?}",
+                contacts.len(),
+                delay
+            );
+            sleep(delay).await;
+            delay = (delay * 2).min(MAX_REBOOTSTRAP_DELAY);
+        }
+    }
+}
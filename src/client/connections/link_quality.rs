@@ -0,0 +1,433 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::ClientEvent;
+use qp2p::Endpoint;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+use xor_name::XorName;
+
+// Weight of each new sample in a connection's smoothed round-trip time.
+const RTT_SMOOTHING: f64 = 0.125;
+// Size in bytes up to which messages are sent in a single flight of packets, so that how long
+// sending them takes to be acknowledged is a round trip, rather than depending on the bandwidth.
+const MAX_RTT_SAMPLE_LEN: usize = 4 * 1024;
+// Number of round trips timed before a connection's round-trip time is judged.
+const MIN_RTT_SAMPLES: u32 = 5;
+// A connection is degraded once its round-trip time is this many times the typical one...
+const DEGRADED_RTT_FACTOR: u32 = 4;
+// ...as long as it's also above this, so that fast connections aren't rotated over a few ms.
+const MIN_DEGRADED_RTT: Duration = Duration::from_millis(500);
+// Number of sends in a row failing for a connection to be degraded.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+// Number of rotation notifications kept for subscribers lagging behind.
+const ROTATION_CHANNEL_CAPACITY: usize = 16;
+// Number of corrupted message notifications kept for subscribers lagging behind.
+const CORRUPTION_CHANNEL_CAPACITY: usize = 64;
+
+/// Statistics of the connection to a node, gathered from the messages the client exchanged
+/// with it, as returned by [`Client::connection_stats`].
+///
+/// [`Client::connection_stats`]: crate::client::Client::connection_stats
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConnectionStats {
+    /// Address of the node.
+    pub addr: SocketAddr,
+    /// Smoothed round-trip time, i.e. how long sending a small message takes until the node
+    /// acknowledged it, or `None` if no small message made it yet. Larger messages aren't timed,
+    /// as how long they take depends on the bandwidth more than on the round trips.
+    pub rtt: Option<Duration>,
+    /// Time since a message was last sent to, or received from, the node.
+    pub idle: Duration,
+    /// Number of messages sent to the node.
+    pub sent: u64,
+    /// Number of those which couldn't be delivered, even after being retransmitted.
+    pub failed: u64,
+    /// Number of messages received from the node whose payload didn't match its checksum,
+    /// as they were corrupted in transit.
+    pub corrupted: u64,
+}
+
+/// Notification of the connection to a node being closed, as its quality degraded, for
+/// a new one to be established the next time a message is sent to the node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConnectionRotation {
+    /// Why the connection was rotated.
+    pub reason: RotationReason,
+    /// Statistics of the connection closed.
+    pub stats: ConnectionStats,
+}
+
+/// Why a connection was rotated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RotationReason {
+    /// Round trips over the connection got far slower than over the others.
+    SlowRoundTrips {
+        /// Smoothed round-trip time of the connection.
+        rtt: Duration,
+        /// Median smoothed round-trip time of the other connections.
+        typical: Duration,
+    },
+    /// Sends over the connection failed, this many times in a row.
+    FailedSends(u32),
+}
+
+/// Keeps statistics of the connections to the nodes the client exchanges messages with,
+/// rotating those whose quality degrades, e.g. after switching Wi-Fi or mobile networks.
+#[derive(Clone, Debug)]
+pub(crate) struct LinkMonitor {
+    links: Arc<Mutex<BTreeMap<SocketAddr, Link>>>,
+    rotation_sender: broadcast::Sender<ConnectionRotation>,
+    corruption_sender: broadcast::Sender<SocketAddr>,
+    event_sender: broadcast::Sender<ClientEvent>,
+    // Nodes whose connection was rotated, and no message made it to since.
+    lost: Arc<Mutex<BTreeSet<SocketAddr>>>,
+}
+
+#[derive(Debug)]
+struct Link {
+    rtt: Option<Duration>,
+    rtt_samples: u32,
+    last_activity: Instant,
+    sent: u64,
+    failed: u64,
+    consecutive_failures: u32,
+    corrupted: u64,
+}
+
+impl Link {
+    fn new() -> Self {
+        Self {
+            rtt: None,
+            rtt_samples: 0,
+            last_activity: Instant::now(),
+            sent: 0,
+            failed: 0,
+            consecutive_failures: 0,
+            corrupted: 0,
+        }
+    }
+
+    fn stats(&self, addr: SocketAddr) -> ConnectionStats {
+        ConnectionStats {
+            addr,
+            rtt: self.rtt,
+            idle: self.last_activity.elapsed(),
+            sent: self.sent,
+            failed: self.failed,
+            corrupted: self.corrupted,
+        }
+    }
+
+    // The round-trip time, once enough round trips were timed for it to be judged.
+    fn judged_rtt(&self) -> Option<Duration> {
+        self.rtt.filter(|_| self.rtt_samples >= MIN_RTT_SAMPLES)
+    }
+}
+
+impl LinkMonitor {
+    pub(crate) fn new(event_sender: broadcast::Sender<ClientEvent>) -> Self {
+        Self {
+            links: Arc::new(Mutex::new(BTreeMap::new())),
+            rotation_sender: broadcast::channel(ROTATION_CHANNEL_CAPACITY).0,
+            corruption_sender: broadcast::channel(CORRUPTION_CHANNEL_CAPACITY).0,
+            event_sender,
+            lost: Arc::new(Mutex::new(BTreeSet::new())),
+        }
+    }
+
+    /// Records a message being received from `addr`.
+    pub(crate) fn received(&self, addr: SocketAddr) {
+        let mut links = self.links.lock().unwrap_or_else(PoisonError::into_inner);
+        links.entry(addr).or_insert_with(Link::new).last_activity = Instant::now();
+    }
+
+    /// Records a message received from `addr` being corrupted in transit, notifying
+    /// subscribers so that whatever awaits a response from it can be asked for again.
+    pub(crate) fn corrupted(&self, addr: SocketAddr) {
+        {
+            let mut links = self.links.lock().unwrap_or_else(PoisonError::into_inner);
+            let link = links.entry(addr).or_insert_with(Link::new);
+            link.last_activity = Instant::now();
+            link.corrupted += 1;
+        }
+        // Nobody awaiting a response from the node is fine.
+        let _ = self.corruption_sender.send(addr);
+    }
+
+    /// Records a message of `len` bytes being sent to `addr`, which took `elapsed` to be
+    /// acknowledged, or failed. If that shows the connection degraded, it's closed and
+    /// subscribers notified.
+    pub(crate) async fn sent(
+        &self,
+        endpoint: &Endpoint<XorName>,
+        addr: SocketAddr,
+        len: usize,
+        elapsed: Duration,
+        delivered: bool,
+    ) {
+        if let Some(rotation) = self.record_send(addr, len, elapsed, delivered) {
+            warn!(
+                "Rotating the connection to {}, as its quality degraded: {:?}",
+                addr, rotation.reason
+            );
+            endpoint.disconnect_from(&addr).await;
+            self.notify_lost(addr, rotation.reason);
+            // Nobody listening is fine, the connection is rotated regardless.
+            let _ = self.rotation_sender.send(rotation);
+        } else if delivered {
+            self.notify_reconnected(addr);
+        }
+    }
+
+    /// Whether connectivity to the network seems lost altogether: connections were rotated
+    /// for failing, and sending to any other node fails as well.
+    pub(crate) fn isolated(&self) -> bool {
+        let lost = self.lost.lock().unwrap_or_else(PoisonError::into_inner);
+        let links = self.links.lock().unwrap_or_else(PoisonError::into_inner);
+        !lost.is_empty() && links.values().all(|link| link.consecutive_failures > 0)
+    }
+
+    /// Records connectivity being restored by connecting to `addr` afresh, which took
+    /// `elapsed`, the connections lost being deemed gone for good.
+    pub(crate) fn restored(&self, addr: SocketAddr, elapsed: Duration) {
+        self.lost
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        // The handshake is a round trip.
+        let _ = self.record_send(addr, 0, elapsed, true);
+    }
+
+    /// Statistics of all the connections messages were exchanged over.
+    pub(crate) fn stats(&self) -> Vec<ConnectionStats> {
+        self.links
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(addr, link)| link.stats(*addr))
+            .collect()
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<ConnectionRotation> {
+        self.rotation_sender.subscribe()
+    }
+
+    /// Subscribes to the addresses of the nodes messages were received corrupted from.
+    pub(crate) fn subscribe_to_corruptions(&self) -> broadcast::Receiver<SocketAddr> {
+        self.corruption_sender.subscribe()
+    }
+
+    // Notifies of the connection to `addr` being lost.
+    fn notify_lost(&self, addr: SocketAddr, reason: RotationReason) {
+        let _ = self
+            .lost
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(addr);
+        let _ = self
+            .event_sender
+            .send(ClientEvent::ConnectionLost { addr, reason });
+    }
+
+    // Notifies of the connection to `addr` being back, if it was lost.
+    fn notify_reconnected(&self, addr: SocketAddr) {
+        let was_lost = self
+            .lost
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&addr);
+        if was_lost {
+            info!("Reconnected to {}", addr);
+            let _ = self.event_sender.send(ClientEvent::Reconnected { addr });
+        }
+    }
+
+    // Updates the statistics of the connection to `addr`, returning the rotation due if it
+    // degraded. Its statistics are then started afresh, for the new connection.
+    fn record_send(
+        &self,
+        addr: SocketAddr,
+        len: usize,
+        elapsed: Duration,
+        delivered: bool,
+    ) -> Option<ConnectionRotation> {
+        let mut links = self.links.lock().unwrap_or_else(PoisonError::into_inner);
+        let link = links.entry(addr).or_insert_with(Link::new);
+        link.last_activity = Instant::now();
+        link.sent += 1;
+        if delivered {
+            link.consecutive_failures = 0;
+        } else {
+            link.failed += 1;
+            link.consecutive_failures += 1;
+        }
+        if delivered && len <= MAX_RTT_SAMPLE_LEN {
+            link.rtt_samples += 1;
+            link.rtt = Some(match link.rtt {
+                Some(rtt) => rtt.mul_f64(1.0 - RTT_SMOOTHING) + elapsed.mul_f64(RTT_SMOOTHING),
+                None => elapsed,
+            });
+        }
+
+        let reason = if link.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+            RotationReason::FailedSends(link.consecutive_failures)
+        } else {
+            let rtt = link.judged_rtt()?;
+            let mut others: Vec<_> = links
+                .iter()
+                .filter(|(other, _)| **other != addr)
+                .filter_map(|(_, link)| link.judged_rtt())
+                .collect();
+            if others.is_empty() {
+                return None;
+            }
+            others.sort();
+            let typical = others[others.len() / 2];
+            if rtt < MIN_DEGRADED_RTT || rtt < typical * DEGRADED_RTT_FACTOR {
+                return None;
+            }
+            RotationReason::SlowRoundTrips { rtt, typical }
+        };
+
+        let stats = links.remove(&addr)?.stats(addr);
+        Some(ConnectionRotation { reason, stats })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClientEvent, LinkMonitor, RotationReason, MAX_CONSECUTIVE_FAILURES, MAX_RTT_SAMPLE_LEN,
+        MIN_RTT_SAMPLES,
+    };
+    use std::{net::SocketAddr, time::Duration};
+    use tokio::sync::broadcast;
+
+    const SMALL: usize = 100;
+
+    #[test]
+    fn degraded_connections_are_rotated() {
+        let monitor = LinkMonitor::new(broadcast::channel(1).0);
+        let fast = SocketAddr::from(([10, 0, 0, 1], 12000));
+        let slow = SocketAddr::from(([10, 0, 0, 2], 12000));
+        let flaky = SocketAddr::from(([10, 0, 0, 3], 12000));
+
+        for _ in 0..MIN_RTT_SAMPLES {
+            assert!(monitor
+                .record_send(fast, SMALL, Duration::from_millis(50), true)
+                .is_none());
+        }
+        // Large messages taking long aren't a sign of slow round trips.
+        for _ in 0..MIN_RTT_SAMPLES {
+            assert!(monitor
+                .record_send(slow, MAX_RTT_SAMPLE_LEN + 1, Duration::from_secs(10), true)
+                .is_none());
+        }
+        for _ in 1..MIN_RTT_SAMPLES {
+            assert!(monitor
+                .record_send(slow, SMALL, Duration::from_secs(2), true)
+                .is_none());
+        }
+        let rotation = monitor.record_send(slow, SMALL, Duration::from_secs(2), true);
+        assert!(matches!(
+            rotation.map(|rotation| rotation.reason),
+            Some(RotationReason::SlowRoundTrips { .. })
+        ));
+
+        for _ in 1..MAX_CONSECUTIVE_FAILURES {
+            assert!(monitor
+                .record_send(flaky, SMALL, Duration::from_secs(1), false)
+                .is_none());
+        }
+        let rotation = monitor.record_send(flaky, SMALL, Duration::from_secs(1), false);
+        assert_eq!(
+            rotation.map(|rotation| rotation.reason),
+            Some(RotationReason::FailedSends(MAX_CONSECUTIVE_FAILURES))
+        );
+
+        // Rotated connections start afresh.
+        let stats = monitor.stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].addr, fast);
+        assert_eq!(stats[0].sent, u64::from(MIN_RTT_SAMPLES));
+        assert_eq!(stats[0].rtt, Some(Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn isolation_is_detected_until_restored() {
+        let monitor = LinkMonitor::new(broadcast::channel(4).0);
+        let gone = SocketAddr::from(([10, 0, 0, 1], 12000));
+        let failing = SocketAddr::from(([10, 0, 0, 2], 12000));
+        let new = SocketAddr::from(([10, 0, 0, 3], 12000));
+        assert!(!monitor.isolated());
+
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            if let Some(rotation) = monitor.record_send(gone, SMALL, Duration::from_secs(1), false)
+            {
+                monitor.notify_lost(gone, rotation.reason);
+            }
+        }
+        assert!(monitor.isolated());
+
+        let _ = monitor.record_send(failing, SMALL, Duration::from_millis(50), true);
+        assert!(!monitor.isolated());
+        let _ = monitor.record_send(failing, SMALL, Duration::from_secs(1), false);
+        assert!(monitor.isolated());
+
+        monitor.restored(new, Duration::from_millis(50));
+        assert!(!monitor.isolated());
+    }
+
+    #[test]
+    fn corrupted_messages_are_counted_and_notified() {
+        let monitor = LinkMonitor::new(broadcast::channel(1).0);
+        let mut corruptions = monitor.subscribe_to_corruptions();
+        let addr = SocketAddr::from(([10, 0, 0, 1], 12000));
+
+        monitor.corrupted(addr);
+        monitor.corrupted(addr);
+        assert_eq!(corruptions.try_recv().ok(), Some(addr));
+        assert_eq!(corruptions.try_recv().ok(), Some(addr));
+        let stats = monitor.stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].corrupted, 2);
+        assert_eq!(stats[0].sent, 0);
+    }
+
+    #[test]
+    fn lost_connections_are_notified_until_back() {
+        let (sender, mut events) = broadcast::channel(8);
+        let monitor = LinkMonitor::new(sender);
+        let addr = SocketAddr::from(([10, 0, 0, 1], 12000));
+        let reason = RotationReason::FailedSends(MAX_CONSECUTIVE_FAILURES);
+
+        // Sends to nodes whose connection wasn't lost aren't notified.
+        monitor.notify_reconnected(addr);
+        assert!(events.try_recv().is_err());
+
+        monitor.notify_lost(addr, reason);
+        assert_eq!(
+            events.try_recv().ok(),
+            Some(ClientEvent::ConnectionLost { addr, reason })
+        );
+        monitor.notify_reconnected(addr);
+        assert_eq!(
+            events.try_recv().ok(),
+            Some(ClientEvent::Reconnected { addr })
+        );
+        monitor.notify_reconnected(addr);
+        assert!(events.try_recv().is_err());
+    }
+}
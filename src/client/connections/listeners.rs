@@ -6,16 +6,20 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Session;
+use super::{
+    AntiEntropyEvent, AntiEntropyOutcome, AntiEntropyReason, ClientEvent, LinkMonitor, QueryTrace,
+    Session,
+};
 use crate::client::connections::messaging::NUM_OF_ELDERS_SUBSET_FOR_QUERIES;
 use crate::client::{connections::messaging::send_message, Error};
 use crate::messaging::data::DataCmd;
+use crate::messaging::ELDER_SIZE;
 use crate::messaging::{
-    data::{CmdError, ServiceMsg},
+    data::{CmdError, Error as ErrorMessage, ServiceMsg},
     system::{KeyedSig, SectionAuth, SystemMsg},
-    DstLocation, MessageId, MessageType, MsgKind, SectionAuthorityProvider, WireMsg,
+    DstLocation, Error as MessagingError, MessageId, MessageType, MsgKind, MsgTrace,
+    SectionAuthorityProvider, WireMsg,
 };
-use crate::routing::ELDER_SIZE;
 use crate::types::PublicKey;
 use bytes::Bytes;
 use itertools::Itertools;
@@ -31,16 +35,21 @@ impl Session {
         mut incoming_messages: IncomingMessages,
     ) {
         debug!("Listening for incoming messages");
-        let _ = tokio::spawn(async move {
+        let tasks = session.tasks.clone();
+        let _ = tasks.spawn("message_listener", async move {
             loop {
-                session = match Self::get_incoming_message(&mut incoming_messages).await {
-                    Ok((src, msg)) => match Self::handle_msg(msg, src, session.clone()).await {
-                        Ok(session) => session,
-                        Err(err) => {
-                            error!("Error while processing incoming message: {:?}. Listening for next message...", err);
-                            session
+                session = match Self::get_incoming_message(&mut incoming_messages, &session.links)
+                    .await
+                {
+                    Ok((src, msg, trace)) => {
+                        match Self::handle_msg(msg, src, trace, session.clone()).await {
+                            Ok(session) => session,
+                            Err(err) => {
+                                error!("Error while processing incoming message: {:?}. Listening for next message...", err);
+                                session
+                            }
                         }
-                    },
+                    }
                     Err(Error::Generic(_)) => {
                         // TODO: FIX error type
                         info!("IncomingMessages listener has closed.");
@@ -57,11 +66,22 @@ impl Session {
 
     pub(crate) async fn get_incoming_message(
         incoming_messages: &mut IncomingMessages,
-    ) -> Result<(SocketAddr, MessageType), Error> {
+        links: &LinkMonitor,
+    ) -> Result<(SocketAddr, MessageType, Option<MsgTrace>), Error> {
         if let Some((src, message)) = incoming_messages.next().await {
-            let msg_type = WireMsg::deserialize(message)?;
+            let wire_msg = match WireMsg::from(message) {
+                Ok(wire_msg) => wire_msg,
+                Err(err @ MessagingError::CorruptedPayload(_)) => {
+                    warn!("Message received from {} was corrupted in transit", src);
+                    links.corrupted(src);
+                    return Err(err.into());
+                }
+                Err(err) => return Err(err.into()),
+            };
+            let trace = wire_msg.trace().cloned();
+            let msg_type = wire_msg.into_message()?;
             trace!("Incoming message from {:?}", &src);
-            Ok((src, msg_type))
+            Ok((src, msg_type, trace))
         } else {
             Err(Error::Generic("Nothing..".to_string())) // TODO: FIX error type
         }
@@ -70,11 +90,13 @@ impl Session {
     pub(crate) async fn handle_msg(
         msg: MessageType,
         src: SocketAddr,
+        trace: Option<MsgTrace>,
         session: Session,
     ) -> Result<Session, Error> {
+        session.links.received(src);
         match msg {
             MessageType::Service { msg_id, msg, .. } => {
-                Self::handle_client_msg(session, msg_id, msg, src).await
+                Self::handle_client_msg(session, msg_id, msg, src, trace).await
             }
             MessageType::System {
                 msg:
@@ -114,6 +136,7 @@ impl Session {
                     section_signed,
                     bounced_msg,
                     proof_chain,
+                    src,
                 )
                 .await;
                 if result.is_err() {
@@ -134,12 +157,26 @@ impl Session {
         msg_id: MessageId,
         msg: ServiceMsg,
         src: SocketAddr,
+        trace: Option<MsgTrace>,
     ) -> Result<Session, Error> {
         debug!("ServiceMsg with id {:?} received from {:?}", msg_id, src);
+        let msg = match msg {
+            ServiceMsg::CmdError {
+                error,
+                correlation_id,
+                ..
+            } => {
+                // Handled before listening for the next message, so that a full error channel
+                // holds off reading further messages when set to backpressure.
+                Self::handle_cmd_error(&session, error, correlation_id, src).await;
+                return Ok(session);
+            }
+            msg => msg,
+        };
         let queries = session.pending_queries.clone();
-        let error_sender = session.incoming_err_sender.clone();
+        let trace_sender = session.trace_sender.clone();
 
-        let _ = tokio::spawn(async move {
+        let _ = session.spawn("handle_service_msg", async move {
             match msg {
                 ServiceMsg::QueryResponse { response, .. } => {
                     // Note that this doesn't remove the sender from here since multiple
@@ -148,9 +185,22 @@ impl Session {
                     // ConnectionManager::send_query
 
                     if let Ok(op_id) = response.operation_id() {
+                        if let Some(trace) = trace {
+                            if !trace.verify() {
+                                warn!("Ignoring invalid trace of query {}", op_id);
+                            } else {
+                                // Nobody listening is fine, tracing is only for diagnostics.
+                                let _ = trace_sender.send(QueryTrace {
+                                    operation_id: op_id.clone(),
+                                    elder: src,
+                                    trace,
+                                });
+                            }
+                        }
+
                         if let Some(sender) = &queries.read().await.get(&op_id) {
                             trace!("Sending response for query w/{} via channel.", op_id);
-                            let _ = sender.send(response).await;
+                            let _ = sender.send((src, response)).await;
                         } else {
                             // TODO: The trace is only needed when we have an identified case of not finding a channel, but expecting one.
                             // When expecting one, we can log "No channel found for operation", (and then probably at warn or error level).
@@ -165,24 +215,6 @@ impl Session {
                         warn!("Ignoring query response without operation id");
                     }
                 }
-                ServiceMsg::CmdError {
-                    error,
-                    correlation_id,
-                    ..
-                } => {
-                    debug!(
-                        "CmdError was received for Message w/ID: {:?}, sending on error channel",
-                        correlation_id
-                    );
-                    warn!("CmdError received is: {:?}", error);
-                    let _ = error_sender.send(error.clone()).await;
-
-                    match error {
-                        CmdError::Data(_error) => {
-                            // do nothing just yet
-                        }
-                    }
-                }
                 msg => {
                     warn!("Ignoring unexpected message type received: {:?}", msg);
                 }
@@ -192,6 +224,44 @@ impl Session {
         Ok(session)
     }
 
+    // Passes an error received in response to a command on to its handle and the error channel,
+    // or sends the command again if it came in corrupted.
+    async fn handle_cmd_error(
+        session: &Session,
+        error: CmdError,
+        correlation_id: MessageId,
+        src: SocketAddr,
+    ) {
+        // Commands which came in corrupted are sent again, once, to the Elder,
+        // unless they were sent too long ago to still be around.
+        if error == CmdError::Data(ErrorMessage::CorruptedMessage) {
+            if let Some(wire_msg) = session.cmd_copies.resend_to(&correlation_id, src) {
+                warn!(
+                    "Command {:?} came in corrupted to {}, sending it again",
+                    correlation_id, src
+                );
+                match wire_msg.serialize() {
+                    Ok(msg_bytes) => session.resend(msg_bytes, src, wire_msg.msg_kind().priority()),
+                    Err(err) => error!("Failed to serialize command: {:?}", err),
+                }
+                return;
+            }
+        }
+        debug!(
+            "CmdError was received for Message w/ID: {:?}, sending on error channel",
+            correlation_id
+        );
+        warn!("CmdError received is: {:?}", error);
+        if session.pending_cmds.resolve(&correlation_id, error.clone()) {
+            trace!("CmdError passed on to the handle of {:?}", correlation_id);
+        }
+        session.incoming_errors.send(error.clone()).await;
+        session.notify(ClientEvent::CmdError {
+            correlation_id,
+            error,
+        });
+    }
+
     // Handle Anti-Entropy Redirect messages
     async fn handle_ae_redirect_msg(
         session: Session,
@@ -200,26 +270,20 @@ impl Session {
         bounced_msg: Bytes,
         sender: SocketAddr,
     ) -> Result<Session, Error> {
-        // Check if SAP signature is valid
-        if !bincode::serialize(&section_auth)
-            .map(|bytes| section_signed.verify(&bytes))
-            .unwrap_or(false)
-        {
-            warn!(
-                "Signature returned with SAP in AE-Redirect response is invalid: {:?}",
-                section_auth
-            );
-            return Ok(session);
-        }
-
         let mut num_of_elders_for_query = ELDER_SIZE;
+        let mut is_query = false;
 
-        let (msg_id, service_msg, auth) = match WireMsg::deserialize(bounced_msg)? {
+        let bounced_msg = WireMsg::from(bounced_msg)?;
+        // Section probes are sent with the genesis key as destination section key, so that
+        // the section answers with an AE-Retry we learn about it from.
+        let is_probe = bounced_msg.dst_section_pk() == Some(session.genesis_key);
+        let (msg_id, service_msg, auth) = match bounced_msg.into_message()? {
             MessageType::Service {
                 msg_id, msg, auth, ..
             } => {
                 if let ServiceMsg::Query(_) = msg {
                     num_of_elders_for_query = NUM_OF_ELDERS_SUBSET_FOR_QUERIES;
+                    is_query = true;
                 }
                 (msg_id, msg, auth)
             }
@@ -231,6 +295,26 @@ impl Session {
                 return Ok(session);
             }
         };
+
+        // Check if SAP signature is valid
+        if !bincode::serialize(&section_auth)
+            .map(|bytes| section_signed.verify(&bytes))
+            .unwrap_or(false)
+        {
+            warn!(
+                "Signature returned with SAP in AE-Redirect response is invalid: {:?}",
+                section_auth
+            );
+            session.notify_anti_entropy(AntiEntropyEvent::new(
+                msg_id,
+                sender,
+                AntiEntropyReason::Redirect,
+                &section_auth,
+                AntiEntropyOutcome::InvalidSignature,
+            ));
+            return Ok(session);
+        }
+
         debug!(
             "Received AE-Redirect for {:?}, from {}, with SAP: {:?}",
             msg_id, sender, section_auth
@@ -262,11 +346,43 @@ impl Session {
             MsgKind::ServiceMsg(auth.into_inner()),
             DstLocation::Section {
                 name: XorName::from(PublicKey::Bls(section_pk)),
-                section_pk,
+                section_pk: if is_probe {
+                    session.genesis_key
+                } else {
+                    section_pk
+                },
             },
         )?;
 
-        send_message(elders, wire_msg, session.endpoint.clone(), msg_id).await?;
+        send_message(
+            elders.clone(),
+            wire_msg,
+            session.endpoint.clone(),
+            msg_id,
+            &session.tasks,
+            &session.links,
+        )
+        .await?;
+        if is_query {
+            session.notify(ClientEvent::QueryRetried {
+                msg_id,
+                reason: AntiEntropyReason::Redirect,
+                elders: elders.clone(),
+            });
+        } else {
+            session.notify(ClientEvent::CmdRetried {
+                msg_id,
+                reason: AntiEntropyReason::Redirect,
+                elders: elders.clone(),
+            });
+        }
+        session.notify_anti_entropy(AntiEntropyEvent::new(
+            msg_id,
+            sender,
+            AntiEntropyReason::Redirect,
+            &section_auth,
+            AntiEntropyOutcome::Resent(elders),
+        ));
 
         Ok(session)
     }
@@ -278,6 +394,7 @@ impl Session {
         section_signed: KeyedSig,
         bounced_msg: Bytes,
         proof_chain: SecuredLinkedList,
+        sender: SocketAddr,
     ) -> Result<Session, Error> {
         // Remove expired items from ae_cache before checking.
         // It might be late to not retry now.
@@ -305,7 +422,12 @@ impl Session {
             ServiceMsg::Cmd(cmd) => {
                 match &cmd {
                     DataCmd::StoreChunk(_) => (3, cmd.dst_name()), // stored at Adults, so only 1 correctly functioning Elder need to relay
-                    DataCmd::Register(_) => (7, cmd.dst_name()), // only stored at Elders, all need a copy
+                    DataCmd::Register(_)
+                    | DataCmd::RecordPayment(_)
+                    | DataCmd::CatalogPayment { .. }
+                    | DataCmd::CatalogData { .. } => {
+                        (7, cmd.dst_name()) // only stored at Elders, all need a copy
+                    }
                 }
             }
             ServiceMsg::Query(query) => (NUM_OF_ELDERS_SUBSET_FOR_QUERIES, query.dst_name()),
@@ -318,7 +440,24 @@ impl Session {
             }
         };
 
-        if let Some(old_elders) = session.ae_cache.get(&dst_address_of_bounced_msg).await {
+        // Commands are resent once within the deadline of their operation, whether or not a
+        // message to the same destination was just resent, as they aren't resent otherwise.
+        if let ServiceMsg::Cmd(_) = service_msg {
+            if !session.cmd_retries.take(&msg_id) {
+                debug!(
+                    "Command {:?} was already resent, or is past its deadline. Dropping it",
+                    msg_id
+                );
+                session.notify_anti_entropy(AntiEntropyEvent::new(
+                    msg_id,
+                    sender,
+                    AntiEntropyReason::Retry,
+                    &section_auth,
+                    AntiEntropyOutcome::RetryExpired,
+                ));
+                return Ok(session);
+            }
+        } else if let Some(old_elders) = session.ae_cache.get(&dst_address_of_bounced_msg).await {
             let received_elders = section_auth
                 .elders
                 .values()
@@ -326,6 +465,13 @@ impl Session {
                 .collect::<Vec<SocketAddr>>();
             if old_elders == received_elders {
                 debug!("We have already resent this message on a AE-Retry. Dropping this instance");
+                session.notify_anti_entropy(AntiEntropyEvent::new(
+                    msg_id,
+                    sender,
+                    AntiEntropyReason::Retry,
+                    &section_auth,
+                    AntiEntropyOutcome::AlreadyResent,
+                ));
                 return Ok(session);
             }
         }
@@ -349,6 +495,13 @@ impl Session {
                         "Anti-Entropy: updated remote section SAP updated for {:?}",
                         section_auth.prefix
                     );
+                    session
+                        .health
+                        .chain_verified(section_auth.prefix, proof_chain.len());
+                    session.notify(ClientEvent::SectionKeyUpdated {
+                        prefix: section_auth.prefix,
+                        key: section_auth.public_key_set.public_key(),
+                    });
                 } else {
                     debug!(
                         "Anti-Entropy: discarded SAP for {:?} since it's the same as the one in our records: {:?}",
@@ -361,6 +514,13 @@ impl Session {
                     "Anti-Entropy: failed to update remote section SAP, bounced msg dropped: {:?}",
                     err
                 );
+                session.notify_anti_entropy(AntiEntropyEvent::new(
+                    msg_id,
+                    sender,
+                    AntiEntropyReason::Retry,
+                    &section_auth,
+                    AntiEntropyOutcome::UntrustedSection(err.to_string()),
+                ));
                 return Ok(session);
             }
         }
@@ -374,6 +534,7 @@ impl Session {
         // Let's rebuild the message with the updated destination details
         let elders = section_auth
             .elders
+            .clone()
             .into_iter()
             .sorted_by(|(lhs_name, _), (rhs_name, _)| {
                 dst_address_of_bounced_msg.cmp_distance(lhs_name, rhs_name)
@@ -391,7 +552,35 @@ impl Session {
             dst_location,
         )?;
 
-        send_message(elders.clone(), wire_msg, session.endpoint.clone(), msg_id).await?;
+        send_message(
+            elders.clone(),
+            wire_msg,
+            session.endpoint.clone(),
+            msg_id,
+            &session.tasks,
+            &session.links,
+        )
+        .await?;
+        if let ServiceMsg::Query(_) = service_msg {
+            session.notify(ClientEvent::QueryRetried {
+                msg_id,
+                reason: AntiEntropyReason::Retry,
+                elders: elders.clone(),
+            });
+        } else {
+            session.notify(ClientEvent::CmdRetried {
+                msg_id,
+                reason: AntiEntropyReason::Retry,
+                elders: elders.clone(),
+            });
+        }
+        session.notify_anti_entropy(AntiEntropyEvent::new(
+            msg_id,
+            sender,
+            AntiEntropyReason::Retry,
+            &section_auth,
+            AntiEntropyOutcome::Resent(elders.clone()),
+        ));
         if let Some(old_elders) = session
             .ae_cache
             .set(dst_address_of_bounced_msg, elders.clone(), None)
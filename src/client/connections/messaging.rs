@@ -6,16 +6,23 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{QueryResult, Session};
+use super::{
+    cross_check::{ReadConsistency, ResponseTally, Verdict},
+    AntiEntropyEvent, BootstrapProgress, Budget, ClientEvent, CmdCopies, CmdHandle, CmdRetries,
+    ConcurrencyLimits, ConnectionRotation, ConnectionState, ConnectionStats, Diagnostics,
+    ElderInfo, ErrorChannel, LinkMonitor, NetworkInfo, OperationPriority, PendingCmds,
+    ProgressReporter, QueryResult, QueryTrace, ResponseDivergence, Scheduler, SentMsg, Session,
+    SessionHealth, TaskTracker, Ticket,
+};
 
 use crate::client::Error;
 use crate::messaging::{
-    data::{CmdError, DataQuery, QueryResponse},
+    data::{DataQuery, QueryResponse},
     signature_aggregator::SignatureAggregator,
     DstLocation, MessageId, MsgKind, ServiceAuth, WireMsg,
 };
 use crate::prefix_map::NetworkPrefixMap;
-use crate::types::{Cache, PublicKey};
+use crate::types::{chunk_buffers, Cache, PublicKey};
 
 use bytes::Bytes;
 use futures::{future::join_all, stream::FuturesUnordered};
@@ -24,21 +31,38 @@ use qp2p::{Config as QuicP2pConfig, Endpoint};
 use std::time::Duration;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
+    future::Future,
     net::SocketAddr,
     sync::Arc,
 };
 use tokio::{
-    sync::mpsc::{channel, Sender},
-    sync::RwLock,
+    sync::mpsc::channel,
+    sync::{broadcast, RwLock},
     task::JoinHandle,
+    time::Instant,
 };
 use tracing::{debug, error, trace, warn};
 use xor_name::XorName;
 
 // Number of Elders subset to send queries to
 pub(crate) const NUM_OF_ELDERS_SUBSET_FOR_QUERIES: usize = 3;
+// Size of the payloads up to which messages are sent on the small-message fast path, i.e.
+// serialised and sent from the calling task, rather than into pooled buffers by spawned tasks,
+// which only pays off for chunk sized messages. Register ops and queries fit well within it.
+const MAX_SMALL_MSG_SIZE: usize = 4 * 1024;
 // Number of attempts to make when trying to bootstrap to a section
 const NUM_OF_BOOTSTRAPPING_ATTEMPTS: u8 = 3;
+// How long to wait for the rest of the Elders to respond to a query, once the first one did,
+// to cross-check their responses
+const QUERY_CROSS_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+// Number of divergence notifications kept for subscribers lagging behind
+const DIVERGENCE_CHANNEL_CAPACITY: usize = 16;
+// Number of query traces kept for subscribers lagging behind
+const TRACE_CHANNEL_CAPACITY: usize = 64;
+// Number of anti-entropy notifications kept for subscribers lagging behind
+const AE_CHANNEL_CAPACITY: usize = 64;
+// Number of client events kept for subscribers lagging behind
+const EVENT_CHANNEL_CAPACITY: usize = 64;
 
 impl Session {
     /// Acquire a session by bootstrapping to a section, maintaining connections to several nodes.
@@ -46,9 +70,12 @@ impl Session {
         client_pk: PublicKey,
         genesis_key: bls::PublicKey,
         qp2p_config: QuicP2pConfig,
-        err_sender: Sender<CmdError>,
+        incoming_errors: ErrorChannel,
         bootstrap_nodes: BTreeSet<SocketAddr>,
         local_addr: SocketAddr,
+        concurrency_limits: ConcurrencyLimits,
+        attempt: u8,
+        progress: &ProgressReporter,
     ) -> Result<Session, Error> {
         trace!(
             "Trying to bootstrap to the network with public_key: {:?}",
@@ -58,24 +85,43 @@ impl Session {
 
         let (endpoint, incoming_messages, _) = Endpoint::new_client(local_addr, qp2p_config)?;
         let bootstrap_nodes = bootstrap_nodes.iter().copied().collect_vec();
+        progress.report(BootstrapProgress::ContactTried {
+            attempt,
+            contacts: bootstrap_nodes.clone(),
+        });
         let bootstrap_peer = endpoint
             .connect_to_any(&bootstrap_nodes)
             .await
             .ok_or(Error::NotBootstrapped)?;
+        progress.report(BootstrapProgress::HandshakeOk(bootstrap_peer));
 
+        let event_sender = broadcast::channel(EVENT_CHANNEL_CAPACITY).0;
         let session = Session {
             client_pk,
             pending_queries: Arc::new(RwLock::new(HashMap::default())),
-            incoming_err_sender: Arc::new(err_sender),
+            pending_cmds: PendingCmds::default(),
+            cmd_retries: CmdRetries::default(),
+            cmd_copies: CmdCopies::default(),
+            incoming_errors,
             endpoint,
             network: Arc::new(NetworkPrefixMap::new(genesis_key)),
             ae_cache: Arc::new(Cache::with_expiry_duration(Duration::from_secs(5))),
             aggregator: Arc::new(RwLock::new(SignatureAggregator::new())),
-            bootstrap_peer,
+            bootstrap_peer: Arc::new(std::sync::Mutex::new(bootstrap_peer)),
+            bootstrap_nodes: Arc::new(bootstrap_nodes.into_iter().collect()),
             genesis_key,
+            scheduler: Scheduler::new(concurrency_limits),
+            divergence_sender: broadcast::channel(DIVERGENCE_CHANNEL_CAPACITY).0,
+            trace_sender: broadcast::channel(TRACE_CHANNEL_CAPACITY).0,
+            ae_sender: broadcast::channel(AE_CHANNEL_CAPACITY).0,
+            event_sender: event_sender.clone(),
+            tasks: TaskTracker::default(),
+            links: LinkMonitor::new(event_sender),
+            health: SessionHealth::default(),
         };
 
         Self::spawn_message_listener_thread(session.clone(), incoming_messages).await;
+        session.spawn_connectivity_watchdog();
 
         Ok(session)
     }
@@ -83,13 +129,17 @@ impl Session {
     /// Tries to bootstrap a client to a section. If there is a failure then it retries.
     /// After a maximum of three attempts if the boostrap process still fails, the unresponsive
     /// node is removed from the list and an error is returned.
+    ///
+    /// Each step is notified to `progress`.
     pub(crate) async fn attempt_bootstrap(
         client_pk: PublicKey,
         genesis_key: bls::PublicKey,
         qp2p_config: qp2p::Config,
         mut bootstrap_nodes: BTreeSet<SocketAddr>,
         local_addr: SocketAddr,
-        err_sender: Sender<CmdError>,
+        incoming_errors: ErrorChannel,
+        concurrency_limits: ConcurrencyLimits,
+        progress: &ProgressReporter,
     ) -> Result<Session, Error> {
         let mut attempts = 0;
         loop {
@@ -97,15 +147,22 @@ impl Session {
                 client_pk,
                 genesis_key,
                 qp2p_config.clone(),
-                err_sender.clone(),
+                incoming_errors.clone(),
                 bootstrap_nodes.clone(),
                 local_addr,
+                concurrency_limits,
+                attempts + 1,
+                progress,
             )
             .await
             {
                 Ok(session) => break Ok(session),
                 Err(err) => {
                     attempts += 1;
+                    progress.report(BootstrapProgress::AttemptFailed {
+                        attempt: attempts,
+                        error: err.to_string(),
+                    });
                     if let Error::BootstrapToPeerFailed(failed_peer) = err {
                         // Remove the unresponsive peer we boostrapped to and bootstrap again
                         let _ = bootstrap_nodes.remove(&failed_peer);
@@ -124,6 +181,11 @@ impl Session {
         }
     }
 
+    /// Returns the network's genesis key.
+    pub(crate) fn genesis_key(&self) -> &bls::PublicKey {
+        &self.genesis_key
+    }
+
     /// Send a `ServiceMsg` to the network without awaiting for a response.
     pub(crate) async fn send_cmd(
         &self,
@@ -131,7 +193,8 @@ impl Session {
         auth: ServiceAuth,
         payload: Bytes,
         targets: usize,
-    ) -> Result<(), Error> {
+        retry_within: Duration,
+    ) -> Result<(SentMsg, CmdHandle), Error> {
         let endpoint = self.endpoint.clone();
 
         // TODO: Consider other approach: Keep a session per section!
@@ -150,7 +213,7 @@ impl Session {
             )
         } else {
             // Send message to our bootstrap peer with network's genesis PK.
-            (vec![self.bootstrap_peer], self.genesis_key)
+            (vec![self.bootstrap_peer()], self.genesis_key)
         };
 
         let msg_id = MessageId::new();
@@ -176,24 +239,51 @@ impl Session {
         let msg_kind = MsgKind::ServiceMsg(auth);
         let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
 
-        return match send_message(elders.clone(), wire_msg, self.endpoint.clone(), msg_id).await {
+        // Registered before sending, so errors can't arrive before the handle is there.
+        let handle = self.pending_cmds.register(msg_id);
+        self.cmd_retries
+            .register(msg_id, Instant::now() + retry_within);
+        self.cmd_copies.keep(wire_msg.clone());
+        return match send_message(
+            elders.clone(),
+            wire_msg,
+            self.endpoint.clone(),
+            msg_id,
+            &self.tasks,
+            &self.links,
+        )
+        .await
+        {
             Ok(()) => {
                 if let Some(old_elders) = self.ae_cache.set(dst_address, elders.clone(), None).await
                 {
                     warn!("We have already sent this cmd to Elders {:?} Updating cache with latest elders {:?}", old_elders, &elders);
                 }
-                Ok(())
+                Ok((
+                    SentMsg {
+                        msg_id,
+                        recipients: elders.len(),
+                    },
+                    handle,
+                ))
             }
             Err(e) => Err(e),
         };
     }
 
     /// Send a `ServiceMsg` to the network awaiting for the response.
+    ///
+    /// If `traced`, the path it takes to each Elder is notified to trace subscribers.
+    /// The response is returned once as many Elders as `consistency` requires agree on it,
+    /// the first chunk matching its address for chunks and a majority's response otherwise,
+    /// if none is given.
     pub(crate) async fn send_query(
         &self,
         query: DataQuery,
         auth: ServiceAuth,
         payload: Bytes,
+        traced: bool,
+        consistency: Option<ReadConsistency>,
     ) -> Result<QueryResult, Error> {
         let endpoint = self.endpoint.clone();
         let pending_queries = self.pending_queries.clone();
@@ -203,6 +293,11 @@ impl Session {
         } else {
             None
         };
+        let consistency = consistency.unwrap_or(if chunk_addr.is_some() {
+            ReadConsistency::Fastest
+        } else {
+            ReadConsistency::Quorum
+        });
 
         let dst = query.dst_name();
 
@@ -211,7 +306,7 @@ impl Session {
             (sap.value.elders, sap.value.public_key_set.public_key())
         } else {
             let mut bootstrapped_peer = BTreeMap::new();
-            let _ = bootstrapped_peer.insert(XorName::random(), self.bootstrap_peer);
+            let _ = bootstrapped_peer.insert(XorName::random(), self.bootstrap_peer());
             // Send message to our bootstrap peer with the network's genesis PK.
             (bootstrapped_peer, self.genesis_key)
         };
@@ -244,20 +339,28 @@ impl Session {
             chosen_elders
         );
 
+        // Small queries take the fast path, being sent from this task rather than spawned ones.
+        let small = payload.len() <= MAX_SMALL_MSG_SIZE;
+
         // We send the same message to all Elders concurrently
         let tasks = FuturesUnordered::new();
-        let (sender, mut receiver) = channel::<QueryResponse>(7);
+        let (sender, mut receiver) = channel::<(SocketAddr, QueryResponse)>(7);
 
         let pending_queries_for_thread = pending_queries.clone();
         if let Ok(op_id) = query.operation_id() {
-            let _ = tokio::spawn(async move {
-                // Insert the response sender
+            if small {
                 trace!("Inserting channel for {:?}", op_id);
-                let _ = pending_queries_for_thread
-                    .write()
-                    .await
-                    .insert(op_id, sender);
-            });
+                let _ = pending_queries.write().await.insert(op_id, sender);
+            } else {
+                let _ = self.spawn("insert_pending_query", async move {
+                    // Insert the response sender
+                    trace!("Inserting channel for {:?}", op_id);
+                    let _ = pending_queries_for_thread
+                        .write()
+                        .await
+                        .insert(op_id, sender);
+                });
+            }
         }
 
         let discarded_responses = std::sync::Arc::new(tokio::sync::Mutex::new(0_usize));
@@ -267,50 +370,74 @@ impl Session {
             section_pk,
         };
         let msg_kind = MsgKind::ServiceMsg(auth);
-        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
+        let mut wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
+        if traced {
+            wire_msg.enable_trace();
+        }
         let priority = wire_msg.msg_kind().priority();
         let msg_bytes = wire_msg.serialize()?;
 
+        // Responses corrupted in transit are dropped, the query being sent again, once, to the
+        // Elders they came from. A corrupted response can't be told apart from that to another
+        // query, so all the queries awaiting a response from the Elder are sent again.
+        let mut corruptions = self.links.subscribe_to_corruptions();
+        let mut resent_to = BTreeSet::new();
+
         // Set up response listeners
-        for socket in chosen_elders.clone() {
-            let endpoint = endpoint.clone();
-            let msg_bytes = msg_bytes.clone();
-            let counter_clone = discarded_responses.clone();
-            let task_handle = tokio::spawn(async move {
-                let result = endpoint.send_message(msg_bytes, &socket, priority).await;
-                match &result {
-                    Err(err) => {
-                        error!("Error sending Query to elder: {:?} ", err);
-                        let mut a = counter_clone.lock().await;
-                        *a += 1;
+        if !small {
+            for socket in chosen_elders.clone() {
+                let endpoint = endpoint.clone();
+                let msg_bytes = msg_bytes.clone();
+                let counter_clone = discarded_responses.clone();
+                let links = self.links.clone();
+                let task_handle = self.spawn("send_query", async move {
+                    let len = msg_bytes.len();
+                    let started = Instant::now();
+                    let result = endpoint.send_message(msg_bytes, &socket, priority).await;
+                    links
+                        .sent(&endpoint, socket, len, started.elapsed(), result.is_ok())
+                        .await;
+                    match &result {
+                        Err(err) => {
+                            error!("Error sending Query to elder: {:?} ", err);
+                            let mut a = counter_clone.lock().await;
+                            *a += 1;
+                        }
+                        Ok(()) => trace!("ServiceMsg with id: {:?}, sent to {}", &msg_id, &socket),
                     }
-                    Ok(()) => trace!("ServiceMsg with id: {:?}, sent to {}", &msg_id, &socket),
-                }
-                result
-            });
+                    result
+                });
 
-            tasks.push(task_handle);
+                tasks.push(task_handle);
+            }
         }
 
-        // TODO:
-        // We are now simply accepting the very first valid response we receive,
-        // but we may want to revisit this to compare multiple responses and validate them,
-        // similar to what we used to do up to the following commit:
-        // https://github.com/maidsafe/sn_client/blob/9091a4f1f20565f25d3a8b00571cc80751918928/src/connection_manager.rs#L328
-        //
-        // For Chunk responses we already validate its hash matches the xorname requested from,
+        // For Chunk responses we validate its hash matches the xorname requested from,
         // so we don't need more than one valid response to prevent from accepting invalid responses
-        // from byzantine nodes, however for mutable data (non-Chunk responses) we will
-        // have to review the approach.
+        // from byzantine nodes. For mutable data (non-Chunk responses) we cross-check the
+        // responses from the Elders, and accept the one enough of them agree on for the read
+        // consistency required, a majority by default.
         let mut discarded_responses: usize = 0;
 
-        // Send all queries concurrently
-        let results = join_all(tasks).await;
+        if small {
+            discarded_responses += send_inline(
+                &endpoint,
+                &self.links,
+                &chosen_elders,
+                msg_bytes.clone(),
+                priority,
+                msg_id,
+            )
+            .await;
+        } else {
+            // Send all queries concurrently
+            let results = join_all(tasks).await;
 
-        for result in results {
-            if let Err(err) = result {
-                error!("Error spawning task to send query: {:?} ", err);
-                discarded_responses += 1;
+            for result in results {
+                if let Err(err) = result {
+                    error!("Error spawning task to send query: {:?} ", err);
+                    discarded_responses += 1;
+                }
             }
         }
 
@@ -318,51 +445,111 @@ impl Session {
             warn!("We have already sent this query to Elders {:?} Updating cache with latest elders {:?}", old_elders, &chosen_elders);
         }
 
+        let mut tally = ResponseTally::new(elders_len);
+        let mut error_response = None;
+        let mut responses = 0;
+        let mut cross_check_deadline = None;
+
         let response = loop {
-            let mut error_response = None;
-            match (receiver.recv().await, chunk_addr) {
-                (Some(QueryResponse::GetChunk(Ok(chunk))), Some(chunk_addr)) => {
-                    // We are dealing with Chunk query responses, thus we validate its hash
-                    // matches its xorname, if so, we don't need to await for more responses
-                    debug!("Chunk QueryResponse received is: {:#?}", chunk);
-
-                    if chunk_addr.name() == chunk.name() {
-                        trace!("Valid Chunk received for {}", msg_id);
-                        break Some(QueryResponse::GetChunk(Ok(chunk)));
-                    } else {
-                        // the Chunk content doesn't match its XorName,
-                        // this is suspicious and it could be a byzantine node
-                        warn!("We received an invalid Chunk response from one of the nodes");
-                        discarded_responses += 1;
+            let next_response = async {
+                match cross_check_deadline {
+                    Some(deadline) => tokio::time::timeout_at(deadline, receiver.recv())
+                        .await
+                        .unwrap_or_else(|_| {
+                            debug!(
+                                "Timed out waiting for more responses to cross-check for {}",
+                                msg_id
+                            );
+                            None
+                        }),
+                    None => receiver.recv().await,
+                }
+            };
+            let received = tokio::select! {
+                received = next_response => received,
+                Ok(src) = corruptions.recv() => {
+                    if chosen_elders.contains(&src) && resent_to.insert(src) {
+                        warn!(
+                            "Response from {} may be to query {} and corrupted, sending it again",
+                            src, msg_id
+                        );
+                        self.resend(msg_bytes.clone(), src, priority);
                     }
+                    continue;
+                }
+            };
+            if received.is_some() {
+                responses += 1;
+            }
+            match (received, chunk_addr) {
+                (Some((_, QueryResponse::GetChunk(Ok(chunk)))), Some(chunk_addr))
+                    if chunk_addr.name() != chunk.name() =>
+                {
+                    // the Chunk content doesn't match its XorName,
+                    // this is suspicious and it could be a byzantine node
+                    warn!("We received an invalid Chunk response from one of the nodes");
+                    discarded_responses += 1;
                 }
                 // Erring on the side of positivity. \
                 // Saving error, but not returning until we have more responses in
                 // (note, this will overwrite prior errors, so we'll just return whichever was last received)
-                (response @ Some(QueryResponse::GetChunk(Err(_))), Some(_))
-                | (response @ Some(QueryResponse::GetRegister((Err(_), _))), None)
-                | (response @ Some(QueryResponse::GetRegisterPolicy((Err(_), _))), None)
-                | (response @ Some(QueryResponse::GetRegisterOwner((Err(_), _))), None)
-                | (response @ Some(QueryResponse::GetRegisterUserPermissions((Err(_), _))), None) =>
-                {
+                (Some((_, response @ QueryResponse::GetChunk(Err(_)))), Some(_))
+                | (Some((_, response @ QueryResponse::ChunkExists((Err(_), _)))), None)
+                | (Some((_, response @ QueryResponse::GetRegister((Err(_), _)))), None)
+                | (Some((_, response @ QueryResponse::GetRegisterPolicy((Err(_), _)))), None)
+                | (Some((_, response @ QueryResponse::GetRegisterOwner((Err(_), _)))), None)
+                | (
+                    Some((_, response @ QueryResponse::GetRegisterUserPermissions((Err(_), _)))),
+                    None,
+                )
+                | (Some((_, response @ QueryResponse::GetPaymentProof((Err(_), _)))), None)
+                | (Some((_, response @ QueryResponse::ListOwnedData((Err(_), _)))), None)
+                | (Some((_, response @ QueryResponse::GetReplicationFactor((Err(_), _)))), None)
+                | (Some((_, response @ QueryResponse::GetDataLimits((Err(_), _)))), None)
+                | (Some((_, response @ QueryResponse::GetDataProof((Err(_), _)))), None)
+                | (Some((_, response @ QueryResponse::GetNetworkTime((Err(_), _)))), None)
+                | (Some((_, response @ QueryResponse::GetChunkHolders((Err(_), _)))), None) => {
                     debug!("QueryResponse error received (but may be overridden by a non-error response from another elder): {:#?}", &response);
-                    error_response = response;
+                    error_response = Some(response);
                     discarded_responses += 1;
                 }
-                (Some(response), _) => {
-                    debug!("QueryResponse received is: {:#?}", response);
-                    break Some(response);
+                (Some((src, response)), _) => {
+                    debug!("QueryResponse received from {} is: {:#?}", src, response);
+                    if !tally.add(src, response) {
+                        warn!(
+                            "Ignoring repeated response from {} to query {}",
+                            src, msg_id
+                        );
+                        continue;
+                    }
+                    if let Some(response) = tally.satisfying(consistency) {
+                        break Some(response.clone());
+                    }
+                    if cross_check_deadline.is_none() {
+                        cross_check_deadline = Some(Instant::now() + QUERY_CROSS_CHECK_TIMEOUT);
+                    }
                 }
                 (None, _) => {
                     debug!("QueryResponse channel closed.");
-                    break None;
+                    break Self::settle(&tally, error_response, consistency);
                 }
             }
-            if discarded_responses == elders_len {
-                break error_response;
+            if tally.len() + discarded_responses >= elders_len {
+                break Self::settle(&tally, error_response, consistency);
             }
         };
 
+        let op_id = query
+            .operation_id()
+            .map_err(|_| Error::UnknownOperationId)?;
+        if let Some(divergence) = tally.divergence(op_id.clone(), response.is_some()) {
+            warn!(
+                "Elders returned conflicting responses to query {}: {:?}",
+                msg_id, divergence.responses
+            );
+            let _ = self.divergence_sender.send(divergence);
+        }
+
         debug!(
             "Response obtained for query w/id {:?}: {:?}",
             msg_id, response
@@ -370,11 +557,16 @@ impl Session {
 
         if let Some(query) = &response {
             if let Ok(query_op_id) = query.operation_id() {
-                let _ = tokio::spawn(async move {
-                    // Remove the response sender
+                if small {
                     trace!("Removing channel for {:?}", query_op_id);
-                    let _ = pending_queries.clone().write().await.remove(&query_op_id);
-                });
+                    let _ = pending_queries.write().await.remove(&query_op_id);
+                } else {
+                    let _ = self.spawn("remove_pending_query", async move {
+                        // Remove the response sender
+                        trace!("Removing channel for {:?}", query_op_id);
+                        let _ = pending_queries.clone().write().await.remove(&query_op_id);
+                    });
+                }
             }
         }
 
@@ -386,12 +578,220 @@ impl Session {
                 Ok(QueryResult {
                     response,
                     operation_id,
+                    sent: SentMsg {
+                        msg_id,
+                        recipients: elders_len,
+                    },
+                    responses,
                 })
             }
+            None if matches!(tally.verdict(), Verdict::Conflicting) => {
+                Err(Error::ConflictingResponses(op_id))
+            }
+            None if matches!(tally.verdict(), Verdict::Agreed(_)) => {
+                Err(Error::InsufficientAgreement(op_id))
+            }
             None => Err(Error::NoResponse),
         }
     }
 
+    // Sends the message in `msg_bytes` to the Elder at `socket` again.
+    pub(super) fn resend(&self, msg_bytes: Bytes, socket: SocketAddr, priority: i32) {
+        let endpoint = self.endpoint.clone();
+        let links = self.links.clone();
+        let _ = self.spawn("resend", async move {
+            let len = msg_bytes.len();
+            let started = Instant::now();
+            let result = endpoint.send_message(msg_bytes, &socket, priority).await;
+            links
+                .sent(&endpoint, socket, len, started.elapsed(), result.is_ok())
+                .await;
+            if let Err(err) = result {
+                error!("Error sending message to elder again: {:?} ", err);
+            }
+        });
+    }
+
+    // Picks the response to go with once no more responses are expected. Unless only the
+    // fastest response is required, that given by the most Elders will only do if as many
+    // agree on it as `consistency` requires.
+    fn settle(
+        tally: &ResponseTally,
+        error_response: Option<QueryResponse>,
+        consistency: ReadConsistency,
+    ) -> Option<QueryResponse> {
+        match tally.verdict() {
+            Verdict::Agreed(_) if consistency != ReadConsistency::Fastest => {
+                tally.satisfying(consistency).cloned()
+            }
+            Verdict::Agreed(response) => Some(response),
+            Verdict::Conflicting => None,
+            Verdict::Empty => error_response,
+        }
+    }
+
+    /// Subscribes to notifications of Elders returning conflicting responses to queries.
+    pub(crate) fn subscribe_to_divergences(&self) -> broadcast::Receiver<ResponseDivergence> {
+        self.divergence_sender.subscribe()
+    }
+
+    /// Subscribes to the paths traced queries took.
+    pub(crate) fn subscribe_to_traces(&self) -> broadcast::Receiver<QueryTrace> {
+        self.trace_sender.subscribe()
+    }
+
+    /// Subscribes to the anti-entropy responses to our messages.
+    pub(crate) fn subscribe_to_anti_entropy(&self) -> broadcast::Receiver<AntiEntropyEvent> {
+        self.ae_sender.subscribe()
+    }
+
+    /// Subscribes to the rotations of degraded connections.
+    pub(crate) fn subscribe_to_connection_rotations(
+        &self,
+    ) -> broadcast::Receiver<ConnectionRotation> {
+        self.links.subscribe()
+    }
+
+    /// Subscribes to changes in our view of the network and our connections to it.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<ClientEvent> {
+        self.event_sender.subscribe()
+    }
+
+    /// Statistics of the connections we exchanged messages over.
+    pub(crate) fn connection_stats(&self) -> Vec<ConnectionStats> {
+        self.links.stats()
+    }
+
+    pub(super) fn notify_anti_entropy(&self, event: AntiEntropyEvent) {
+        // Nobody listening is fine, these are only for diagnostics.
+        let _ = self.ae_sender.send(event);
+    }
+
+    pub(super) fn notify(&self, event: ClientEvent) {
+        // Nobody listening is fine, applications subscribe if they care.
+        let _ = self.event_sender.send(event);
+    }
+
+    /// Spawns one of our internal tasks, named after what it does.
+    pub(crate) fn spawn<F>(&self, name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.tasks.spawn(name, future)
+    }
+
+    /// Takes a snapshot of what the session is up to.
+    pub(crate) async fn diagnostics(&self) -> Diagnostics {
+        let mut elders = BTreeMap::new();
+        for section_auth in self.network.all() {
+            for addr in section_auth.elders.values() {
+                let _ = elders.insert(*addr, section_auth.prefix);
+            }
+        }
+
+        let mut addrs: BTreeSet<_> = elders.keys().copied().collect();
+        let bootstrap_peer = self.bootstrap_peer();
+        let _ = addrs.insert(bootstrap_peer);
+
+        let mut connections = Vec::with_capacity(addrs.len());
+        for addr in addrs {
+            connections.push(ConnectionState {
+                addr,
+                prefix: elders.get(&addr).copied(),
+                bootstrap: addr == bootstrap_peer,
+                connected: self.endpoint.get_connection_id(&addr).await.is_some(),
+            });
+        }
+
+        Diagnostics {
+            active_tasks: self.tasks.active(),
+            in_flight_ops: self.scheduler.in_flight(),
+            foreground_ops: self.scheduler.foreground(),
+            queued_background_ops: self.scheduler.queued_background(),
+            pending_queries: self.pending_queries.read().await.len(),
+            pending_cmds: self.pending_cmds.len(),
+            queued_cmd_errors: self.incoming_errors.len(),
+            dropped_cmd_errors: self.incoming_errors.dropped(),
+            chunk_buffers: chunk_buffers().stats(),
+            connections,
+        }
+    }
+
+    /// Takes a snapshot of our view of our section and of the health of the session.
+    pub(crate) async fn network_info(&self) -> NetworkInfo {
+        let section = self
+            .network
+            .section_by_name(&XorName::from(self.client_pk))
+            .ok();
+        let stats = self.links.stats();
+
+        let mut elders = Vec::new();
+        for (name, addr) in section.iter().flat_map(|sap| sap.elders.iter()) {
+            elders.push(ElderInfo {
+                name: *name,
+                addr: *addr,
+                connected: self.endpoint.get_connection_id(addr).await.is_some(),
+                rtt: stats
+                    .iter()
+                    .find(|stats| stats.addr == *addr)
+                    .and_then(|stats| stats.rtt),
+            });
+        }
+
+        let prefix = section.map(|sap| sap.prefix);
+        NetworkInfo {
+            prefix,
+            elders,
+            section_chain_len: prefix.and_then(|prefix| self.health.chain_len(&prefix)),
+            query_retries: self.health.query_retries(),
+            query_timeouts: self.health.query_timeouts(),
+        }
+    }
+
+    /// Keeps count of the retries and timeouts of the operations using this session.
+    pub(crate) fn health(&self) -> &SessionHealth {
+        &self.health
+    }
+
+    /// Waits until an operation of the given priority can go ahead using this session, within
+    /// the budget of the type of data it's on. The operation holds on to the returned ticket
+    /// until it's done.
+    pub(crate) async fn ticket(
+        &self,
+        priority: OperationPriority,
+        budget: Budget,
+    ) -> Result<Ticket, Error> {
+        self.scheduler.ticket(priority, budget).await
+    }
+
+    /// Stops accepting new operations, and waits up to `graceful_timeout` for those in flight to
+    /// be done, before dropping any responses still awaited and closing all connections.
+    ///
+    /// Returns the number of operations which were still in flight.
+    pub(crate) async fn close(&self, graceful_timeout: Duration) -> usize {
+        if tokio::time::timeout(graceful_timeout, self.scheduler.close())
+            .await
+            .is_err()
+        {
+            warn!(
+                "Closing session with {} operations still in flight",
+                self.scheduler.in_flight()
+            );
+        }
+        let in_flight = self.scheduler.in_flight();
+
+        // Queries still awaiting responses see their channel closed and give up.
+        self.pending_queries.write().await.clear();
+        self.pending_cmds.clear();
+        self.cmd_retries.clear();
+        self.cmd_copies.clear();
+        self.ae_cache.clear().await;
+        self.endpoint.close();
+
+        in_flight
+    }
+
     #[allow(unused)]
     pub(crate) async fn disconnect_from_peers(&self, peers: Vec<SocketAddr>) -> Result<(), Error> {
         for elder in peers {
@@ -406,31 +806,53 @@ pub(crate) async fn send_message(
     wire_msg: WireMsg,
     endpoint: Endpoint<XorName>,
     msg_id: MessageId,
+    tasks: &TaskTracker,
+    links: &LinkMonitor,
 ) -> Result<(), Error> {
     let priority = wire_msg.msg_kind().priority();
-    let msg_bytes = wire_msg.serialize()?;
+
+    // Small messages, e.g. register ops, take the fast path.
+    if wire_msg.payload.len() <= MAX_SMALL_MSG_SIZE {
+        let msg_bytes = wire_msg.serialize()?;
+        let failures = send_inline(&endpoint, links, &elders, msg_bytes, priority, msg_id).await;
+        if failures > 0 {
+            error!("Sending the message to {} Elders failed", failures);
+        }
+        return Ok(());
+    }
+
+    // Chunks are serialised into pooled buffers, held until sent to all Elders, so parallel
+    // uploads wait for buffers to be released rather than take up ever more memory.
+    let msg_bytes = wire_msg.serialize_pooled(chunk_buffers()).await?;
 
     // Send message to all Elders concurrently
-    let mut tasks = Vec::default();
+    let mut handles = Vec::default();
 
     // clone elders as we want to update them in this process
     for socket in elders {
         let msg_bytes_clone = msg_bytes.clone();
         let endpoint = endpoint.clone();
-        let task_handle: JoinHandle<Result<(), Error>> = tokio::spawn(async move {
+        let links = links.clone();
+        let task_handle: JoinHandle<Result<(), Error>> = tasks.spawn("send_cmd", async move {
             trace!("About to send cmd message {:?} to {:?}", msg_id, &socket);
-            endpoint
+            let len = msg_bytes_clone.len();
+            let started = Instant::now();
+            let result = endpoint
                 .send_message(msg_bytes_clone, &socket, priority)
-                .await?;
+                .await;
+            links
+                .sent(&endpoint, socket, len, started.elapsed(), result.is_ok())
+                .await;
+            result?;
 
             trace!("Sent cmd with MsgId {:?} to {:?}", msg_id, &socket);
             Ok(())
         });
-        tasks.push(task_handle);
+        handles.push(task_handle);
     }
 
     // Let's await for all messages to be sent
-    let results = join_all(tasks).await;
+    let results = join_all(handles).await;
 
     let mut failures = 0;
     results.iter().for_each(|res| {
@@ -445,3 +867,46 @@ pub(crate) async fn send_message(
 
     Ok(())
 }
+
+// Sends a small message to all the given Elders concurrently from the calling task, sparing
+// the task spawns messages are otherwise sent with. Returns the number of Elders it couldn't
+// be sent to.
+async fn send_inline(
+    endpoint: &Endpoint<XorName>,
+    links: &LinkMonitor,
+    elders: &[SocketAddr],
+    msg_bytes: Bytes,
+    priority: i32,
+    msg_id: MessageId,
+) -> usize {
+    let sends = elders.iter().map(|socket| {
+        let msg_bytes = msg_bytes.clone();
+        async move {
+            let len = msg_bytes.len();
+            let started = Instant::now();
+            let result = endpoint.send_message(msg_bytes, socket, priority).await;
+            links
+                .sent(endpoint, *socket, len, started.elapsed(), result.is_ok())
+                .await;
+            match result {
+                Ok(()) => {
+                    trace!("Sent message with MsgId {:?} to {:?}", msg_id, socket);
+                    false
+                }
+                Err(err) => {
+                    error!(
+                        "Error sending message {:?} to {}: {:?}",
+                        msg_id, socket, err
+                    );
+                    true
+                }
+            }
+        }
+    });
+
+    join_all(sends)
+        .await
+        .into_iter()
+        .filter(|failed| *failed)
+        .count()
+}
@@ -6,28 +6,79 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod anti_entropy;
+mod bootstrap_progress;
+mod client_events;
+mod cmd_acks;
+mod cross_check;
+mod diagnostics;
+mod error_channel;
+mod failover;
+mod link_quality;
 mod listeners;
 mod messaging;
+mod network_info;
+mod query_trace;
+mod scheduler;
+mod sections;
+
+pub use anti_entropy::{AntiEntropyEvent, AntiEntropyOutcome, AntiEntropyReason};
+pub use bootstrap_progress::BootstrapProgress;
+pub use client_events::ClientEvent;
+pub use cmd_acks::CmdHandle;
+pub use cross_check::{ReadConsistency, ResponseDivergence};
+pub use diagnostics::{ConnectionState, Diagnostics};
+pub use error_channel::{ErrorChannelConfig, OverflowPolicy};
+pub use link_quality::{ConnectionRotation, ConnectionStats, RotationReason};
+pub use network_info::{ElderInfo, NetworkInfo};
+pub use query_trace::QueryTrace;
+pub use scheduler::{ConcurrencyLimits, OperationPriority};
+
+pub(crate) use bootstrap_progress::ProgressReporter;
+pub(crate) use error_channel::ErrorChannel;
+pub(crate) use messaging::NUM_OF_ELDERS_SUBSET_FOR_QUERIES;
+pub(crate) use scheduler::Budget;
 
 use crate::messaging::{
-    data::{CmdError, OperationId, QueryResponse},
+    data::{OperationId, QueryResponse},
     signature_aggregator::SignatureAggregator,
+    MessageId,
 };
 use crate::prefix_map::NetworkPrefixMap;
 use crate::types::{Cache, PublicKey};
 
+use cmd_acks::{CmdCopies, CmdRetries, PendingCmds};
+use diagnostics::TaskTracker;
+use link_quality::LinkMonitor;
+use network_info::SessionHealth;
 use qp2p::Endpoint;
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
-use tokio::sync::{mpsc::Sender, RwLock};
+use scheduler::{Scheduler, Ticket};
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
+    sync::Arc,
+};
+use tokio::sync::{broadcast, mpsc::Sender, RwLock};
 use xor_name::XorName;
 
-type QueryResponseSender = Sender<QueryResponse>;
+type QueryResponseSender = Sender<(SocketAddr, QueryResponse)>;
 type PendingQueryResponses = Arc<RwLock<HashMap<OperationId, QueryResponseSender>>>;
 
 pub(crate) struct QueryResult {
     pub(super) response: QueryResponse,
     // TODO: unify this
     pub(super) operation_id: OperationId,
+    pub(super) sent: SentMsg,
+    // Number of responses received from Elders, including those discarded.
+    pub(super) responses: usize,
+}
+
+/// A message sent to Elders.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SentMsg {
+    pub(crate) msg_id: MessageId,
+    // Number of Elders the message was sent to.
+    pub(crate) recipients: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -38,16 +89,40 @@ pub(super) struct Session {
     endpoint: Endpoint<XorName>,
     // Channels for sending responses to upper layers
     pending_queries: PendingQueryResponses,
-    // Channels for sending errors to upper layer
-    incoming_err_sender: Arc<Sender<CmdError>>,
+    // Handles of the commands sent, awaiting errors in response to them
+    pending_cmds: PendingCmds,
+    // Commands which may still be resent once after being bounced by anti-entropy
+    cmd_retries: CmdRetries,
+    // Messages carrying the commands sent lately, to send again if they come in corrupted
+    cmd_copies: CmdCopies,
+    // Queue of errors for the upper layer
+    incoming_errors: ErrorChannel,
     /// All elders we know about from AE messages
     network: Arc<NetworkPrefixMap>,
     /// Message resending cache
     ae_cache: Arc<Cache<XorName, Vec<SocketAddr>>>,
-    /// Our initial bootstrap node
-    bootstrap_peer: SocketAddr,
+    /// The node we bootstrapped to, last
+    bootstrap_peer: Arc<std::sync::Mutex<SocketAddr>>,
+    /// The nodes we were given to bootstrap to
+    bootstrap_nodes: Arc<BTreeSet<SocketAddr>>,
     /// BLS Signature aggregator for aggregating network messages
     aggregator: Arc<RwLock<SignatureAggregator>>,
     /// Network's genesis key
     genesis_key: bls::PublicKey,
+    /// Gives foreground operations precedence over background ones
+    scheduler: Scheduler,
+    /// Notifies of conflicting responses received from Elders
+    divergence_sender: broadcast::Sender<ResponseDivergence>,
+    /// Notifies of the paths traced queries took
+    trace_sender: broadcast::Sender<QueryTrace>,
+    /// Notifies of the anti-entropy responses to our messages
+    ae_sender: broadcast::Sender<AntiEntropyEvent>,
+    /// Notifies applications of changes in our view of the network and our connections to it
+    event_sender: broadcast::Sender<ClientEvent>,
+    /// Spawns our internal tasks, keeping count of them
+    tasks: TaskTracker,
+    /// Keeps statistics of our connections, rotating degraded ones
+    links: LinkMonitor,
+    /// Keeps count of our retries and timeouts, and of the proof chains we verified
+    health: SessionHealth,
 }
@@ -0,0 +1,116 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use dashmap::DashMap;
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use xor_name::{Prefix, XorName};
+
+/// A snapshot of the client's view of its section and of the health of its session, as
+/// returned by [`Client::network_info`], for debugging flaky clients without trawling logs.
+///
+/// [`Client::network_info`]: crate::client::Client::network_info
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NetworkInfo {
+    /// Prefix of the client's section, i.e. that its public key belongs in, if known yet.
+    pub prefix: Option<Prefix>,
+    /// The Elders of the client's section.
+    pub elders: Vec<ElderInfo>,
+    /// Number of keys of the proof chain the current key of the client's section was last
+    /// verified with, if it was.
+    pub section_chain_len: Option<usize>,
+    /// Number of times queries were retried, as per the clients' retry policies, since the
+    /// session was started.
+    pub query_retries: u64,
+    /// Number of query attempts which timed out since the session was started.
+    pub query_timeouts: u64,
+}
+
+/// An Elder of the client's section, as listed in [`NetworkInfo`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ElderInfo {
+    /// Name of the Elder.
+    pub name: XorName,
+    /// Address of the Elder.
+    pub addr: SocketAddr,
+    /// Whether there's an open connection to the Elder.
+    pub connected: bool,
+    /// Smoothed round-trip time of the connection to the Elder, or `None` if no message made
+    /// it yet.
+    pub rtt: Option<Duration>,
+}
+
+/// Keeps count of what the health of a session is summed up with in [`NetworkInfo`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SessionHealth {
+    query_retries: Arc<AtomicU64>,
+    query_timeouts: Arc<AtomicU64>,
+    chain_lens: Arc<DashMap<Prefix, usize>>,
+}
+
+impl SessionHealth {
+    /// Records that a query is being retried.
+    pub(crate) fn query_retried(&self) {
+        let _ = self.query_retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records that a query attempt timed out.
+    pub(crate) fn query_timed_out(&self) {
+        let _ = self.query_timeouts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records that the key of the section of `prefix` was verified with a proof chain of
+    /// `len` keys.
+    pub(crate) fn chain_verified(&self, prefix: Prefix, len: usize) {
+        let _ = self.chain_lens.insert(prefix, len);
+    }
+
+    /// Length of the proof chain the key of the section of `prefix` was last verified with.
+    pub(crate) fn chain_len(&self, prefix: &Prefix) -> Option<usize> {
+        self.chain_lens.get(prefix).map(|len| *len)
+    }
+
+    pub(crate) fn query_retries(&self) -> u64 {
+        self.query_retries.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn query_timeouts(&self) -> u64 {
+        self.query_timeouts.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SessionHealth;
+    use xor_name::{Prefix, XorName};
+
+    #[test]
+    fn counts_are_shared_across_clones() {
+        let health = SessionHealth::default();
+        let clone = health.clone();
+
+        health.query_retried();
+        clone.query_retried();
+        clone.query_timed_out();
+        assert_eq!(health.query_retries(), 2);
+        assert_eq!(health.query_timeouts(), 1);
+
+        let prefix = Prefix::default().pushed(XorName::random().bit(0));
+        assert_eq!(health.chain_len(&prefix), None);
+        clone.chain_verified(prefix, 3);
+        clone.chain_verified(prefix, 4);
+        assert_eq!(health.chain_len(&prefix), Some(4));
+        assert_eq!(health.chain_len(&Prefix::default()), None);
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::{data::OperationId, MsgTrace};
+use std::net::SocketAddr;
+
+/// The path a traced query took through the network, as returned with a response to it.
+#[derive(Clone, Debug)]
+pub struct QueryTrace {
+    /// Id of the operation that was queried.
+    pub operation_id: OperationId,
+    /// Elder the response was received from.
+    pub elder: SocketAddr,
+    /// Nodes the query went through on its way to that Elder, which is the last of them.
+    pub trace: MsgTrace,
+}
@@ -0,0 +1,384 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::Error;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+
+// Max number of background operations sending messages at any one time.
+const MAX_CONCURRENT_BACKGROUND_OPS: usize = 4;
+// Default max number of operations of each kind in flight at any one time.
+const DEFAULT_MAX_CHUNK_READS: usize = 64;
+const DEFAULT_MAX_CHUNK_WRITES: usize = 32;
+const DEFAULT_MAX_REGISTER_OPS: usize = 32;
+
+/// Max number of operations on each type of data in flight at any one time, and max rate of
+/// the chunks transferred.
+///
+/// Each type of data has its own budget, so that a heavy blob upload or download can't
+/// starve latency-sensitive Register operations in the same process. Limits of 0 are taken
+/// as 1, as operations would otherwise wait forever.
+///
+/// The limits set in [`Config::concurrency_limits`] apply to all the clients sharing a session.
+/// A client's blob reads and writes can be held back further with
+/// [`Client::with_concurrency_limits`].
+///
+/// [`Config::concurrency_limits`]: crate::client::Config::concurrency_limits
+/// [`Client::with_concurrency_limits`]: crate::client::Client::with_concurrency_limits
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ConcurrencyLimits {
+    /// Max number of chunks being read.
+    pub chunk_reads: usize,
+    /// Max number of chunks being written.
+    pub chunk_writes: usize,
+    /// Max number of Register reads and writes.
+    pub register_ops: usize,
+    /// Max number of bytes of chunks read or written per second by blob reads and writes,
+    /// if any.
+    #[serde(default)]
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl Default for ConcurrencyLimits {
+    fn default() -> Self {
+        Self {
+            chunk_reads: DEFAULT_MAX_CHUNK_READS,
+            chunk_writes: DEFAULT_MAX_CHUNK_WRITES,
+            register_ops: DEFAULT_MAX_REGISTER_OPS,
+            max_bytes_per_sec: None,
+        }
+    }
+}
+
+/// Budget an operation counts against, as per the type of data it's on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Budget {
+    ChunkReads,
+    ChunkWrites,
+    RegisterOps,
+    /// Operations which aren't limited, e.g. those on section metadata.
+    Unlimited,
+}
+
+/// Priority of a client operation.
+///
+/// Background operations only use the session's connections while no
+/// foreground operation is in flight, so that interactive reads are not
+/// queued behind bulk uploads of the same application.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum OperationPriority {
+    /// Interactive operation, which is never held back.
+    Foreground,
+    /// Bulk operation, which yields to any foreground operation.
+    Background,
+}
+
+impl Default for OperationPriority {
+    fn default() -> Self {
+        Self::Foreground
+    }
+}
+
+/// Hands out tickets to operations according to their priority.
+#[derive(Clone, Debug)]
+pub(crate) struct Scheduler {
+    operations: Counter,
+    foreground: Counter,
+    queued_background: Counter,
+    background_permits: Arc<Semaphore>,
+    chunk_read_permits: Arc<Semaphore>,
+    chunk_write_permits: Arc<Semaphore>,
+    register_permits: Arc<Semaphore>,
+    closed: Arc<AtomicBool>,
+}
+
+/// Held by an operation for as long as it's using the session.
+#[derive(Debug)]
+pub(crate) struct Ticket {
+    _operation: InFlight,
+    _priority: PriorityHold,
+    _budget: Option<OwnedSemaphorePermit>,
+}
+
+#[derive(Debug)]
+enum PriorityHold {
+    Foreground(InFlight),
+    Background(OwnedSemaphorePermit),
+}
+
+// Counts operations in flight, notifying waiters whenever there are none left.
+#[derive(Clone, Debug, Default)]
+struct Counter {
+    count: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+}
+
+impl Counter {
+    fn enter(&self) -> InFlight {
+        let _ = self.count.fetch_add(1, Ordering::SeqCst);
+        InFlight(self.clone())
+    }
+
+    fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    async fn wait_idle(&self) {
+        loop {
+            // Register interest before checking, so we don't miss a notification.
+            let notified = self.idle.notified();
+            if self.count() == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct InFlight(Counter);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl Scheduler {
+    pub(crate) fn new(limits: ConcurrencyLimits) -> Self {
+        // A budget without any room would never let an operation through.
+        let permits = |limit: usize| Arc::new(Semaphore::new(limit.max(1)));
+        Self {
+            operations: Counter::default(),
+            foreground: Counter::default(),
+            queued_background: Counter::default(),
+            background_permits: Arc::new(Semaphore::new(MAX_CONCURRENT_BACKGROUND_OPS)),
+            chunk_read_permits: permits(limits.chunk_reads),
+            chunk_write_permits: permits(limits.chunk_writes),
+            register_permits: permits(limits.register_ops),
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Waits until an operation of the given priority is allowed to proceed,
+    /// and there's room for it in the budget it counts against.
+    pub(crate) async fn ticket(
+        &self,
+        priority: OperationPriority,
+        budget: Budget,
+    ) -> Result<Ticket, Error> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(Error::ClientClosed);
+        }
+        let operation = self.operations.enter();
+
+        let priority = match priority {
+            OperationPriority::Foreground => PriorityHold::Foreground(self.foreground.enter()),
+            OperationPriority::Background => {
+                let _queued = self.queued_background.enter();
+                let permit = self
+                    .background_permits
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|_| Error::ClientClosed)?;
+                if self.foreground.count() > 0 {
+                    trace!("Background operation yielding to foreground operations");
+                    self.foreground.wait_idle().await;
+                }
+                PriorityHold::Background(permit)
+            }
+        };
+
+        // Taken last, so that background operations yielding to foreground ones
+        // don't hold on to room in the budget meanwhile.
+        let budget = match self.permits(budget) {
+            Some(permits) => Some(
+                permits
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|_| Error::ClientClosed)?,
+            ),
+            None => None,
+        };
+
+        Ok(Ticket {
+            _operation: operation,
+            _priority: priority,
+            _budget: budget,
+        })
+    }
+
+    fn permits(&self, budget: Budget) -> Option<&Arc<Semaphore>> {
+        match budget {
+            Budget::ChunkReads => Some(&self.chunk_read_permits),
+            Budget::ChunkWrites => Some(&self.chunk_write_permits),
+            Budget::RegisterOps => Some(&self.register_permits),
+            Budget::Unlimited => None,
+        }
+    }
+
+    /// Stops handing out tickets, failing any operation waiting for one with
+    /// [`Error::ClientClosed`], then waits for the operations in flight to be done.
+    pub(crate) async fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.background_permits.close();
+        self.chunk_read_permits.close();
+        self.chunk_write_permits.close();
+        self.register_permits.close();
+        self.operations.wait_idle().await
+    }
+
+    /// Whether the scheduler was closed.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Number of operations in flight.
+    pub(crate) fn in_flight(&self) -> usize {
+        self.operations.count()
+    }
+
+    /// Number of foreground operations in flight.
+    pub(crate) fn foreground(&self) -> usize {
+        self.foreground.count()
+    }
+
+    /// Number of background operations waiting for their turn.
+    pub(crate) fn queued_background(&self) -> usize {
+        self.queued_background.count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Budget, ConcurrencyLimits, OperationPriority, Scheduler};
+    use crate::client::Error;
+    use eyre::Result;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn background_waits_for_foreground() -> Result<()> {
+        let scheduler = Scheduler::new(ConcurrencyLimits::default());
+
+        let foreground = scheduler
+            .ticket(OperationPriority::Foreground, Budget::Unlimited)
+            .await?;
+        let waiting = timeout(
+            Duration::from_millis(100),
+            scheduler.ticket(OperationPriority::Background, Budget::Unlimited),
+        )
+        .await;
+        assert!(waiting.is_err());
+
+        drop(foreground);
+        let background = timeout(
+            Duration::from_millis(100),
+            scheduler.ticket(OperationPriority::Background, Budget::Unlimited),
+        )
+        .await;
+        assert!(matches!(background, Ok(Ok(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn close_waits_for_operations_in_flight() -> Result<()> {
+        let scheduler = Scheduler::new(ConcurrencyLimits::default());
+
+        let ticket = scheduler
+            .ticket(OperationPriority::Foreground, Budget::Unlimited)
+            .await?;
+        let mut closing = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move { scheduler.close().await })
+        };
+        assert!(timeout(Duration::from_millis(100), &mut closing)
+            .await
+            .is_err());
+        assert!(matches!(
+            scheduler
+                .ticket(OperationPriority::Foreground, Budget::Unlimited)
+                .await,
+            Err(Error::ClientClosed)
+        ));
+
+        drop(ticket);
+        timeout(Duration::from_millis(100), closing).await??;
+        assert_eq!(scheduler.in_flight(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn budgets_are_separate() -> Result<()> {
+        let scheduler = Scheduler::new(ConcurrencyLimits {
+            chunk_reads: 1,
+            chunk_writes: 1,
+            register_ops: 1,
+            max_bytes_per_sec: None,
+        });
+
+        let chunk_read = scheduler
+            .ticket(OperationPriority::Foreground, Budget::ChunkReads)
+            .await?;
+        let waiting = timeout(
+            Duration::from_millis(100),
+            scheduler.ticket(OperationPriority::Foreground, Budget::ChunkReads),
+        )
+        .await;
+        assert!(waiting.is_err());
+
+        // Chunk reads using up their budget doesn't hold back Register operations.
+        let register_op = timeout(
+            Duration::from_millis(100),
+            scheduler.ticket(OperationPriority::Foreground, Budget::RegisterOps),
+        )
+        .await;
+        assert!(matches!(register_op, Ok(Ok(_))));
+
+        drop(chunk_read);
+        let chunk_read = timeout(
+            Duration::from_millis(100),
+            scheduler.ticket(OperationPriority::Foreground, Budget::ChunkReads),
+        )
+        .await;
+        assert!(matches!(chunk_read, Ok(Ok(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn zero_limits_let_operations_through() -> Result<()> {
+        let scheduler = Scheduler::new(ConcurrencyLimits {
+            chunk_reads: 0,
+            chunk_writes: 0,
+            register_ops: 0,
+            max_bytes_per_sec: None,
+        });
+
+        for budget in [Budget::ChunkReads, Budget::ChunkWrites, Budget::RegisterOps] {
+            let ticket = timeout(
+                Duration::from_millis(100),
+                scheduler.ticket(OperationPriority::Foreground, budget),
+            )
+            .await;
+            assert!(matches!(ticket, Ok(Ok(_))));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{messaging::send_message, Session};
+use crate::client::Error;
+use crate::messaging::ELDER_SIZE;
+use crate::messaging::{DstLocation, MessageId, MsgKind, ServiceAuth, WireMsg};
+
+use bytes::Bytes;
+use futures::future::join_all;
+use itertools::Itertools;
+use std::{collections::BTreeSet, net::SocketAddr};
+use tracing::{debug, warn};
+use xor_name::{Prefix, XorName};
+
+impl Session {
+    /// Returns the prefix of the section `name` belongs to, if we know about it.
+    pub(crate) fn known_section(&self, name: &XorName) -> Option<Prefix> {
+        self.known_section_elders(name).map(|(prefix, _)| prefix)
+    }
+
+    /// Returns the prefixes of the sections we know about, leaving out those we know have split.
+    pub(crate) fn known_sections(&self) -> BTreeSet<Prefix> {
+        let prefixes: BTreeSet<_> = self
+            .network
+            .all()
+            .into_iter()
+            .map(|sap| sap.prefix)
+            .collect();
+        prefixes
+            .iter()
+            .filter(|prefix| !prefixes.iter().any(|other| other.is_extension_of(prefix)))
+            .copied()
+            .collect()
+    }
+
+    /// Returns the prefix of the section `name` belongs to, and its number of Elders,
+    /// if we know about it.
+    pub(crate) fn known_section_elders(&self, name: &XorName) -> Option<(Prefix, usize)> {
+        self.network
+            .closest_or_opposite(name)
+            .filter(|sap| sap.value.prefix.matches(name))
+            .map(|sap| (sap.value.prefix, sap.value.elders.len()))
+    }
+
+    /// Connects to up to `budget` of the Elders of the section `name` belongs to,
+    /// those closest to `name` first, as those are the ones queried for data at `name`.
+    ///
+    /// Returns the prefix of the section and the number of Elders connected to,
+    /// or `None` if we don't know about the section yet.
+    pub(crate) async fn connect_to_section(
+        &self,
+        name: XorName,
+        budget: usize,
+    ) -> Result<Option<(Prefix, usize)>, Error> {
+        let sap = match self.network.closest_or_opposite(&name) {
+            Some(sap) if sap.value.prefix.matches(&name) => sap.value,
+            _ => return Ok(None),
+        };
+
+        let elders = sap
+            .elders
+            .iter()
+            .sorted_by(|(lhs_name, _), (rhs_name, _)| name.cmp_distance(lhs_name, rhs_name))
+            .map(|(_, addr)| *addr)
+            .take(budget.min(ELDER_SIZE))
+            .collect::<Vec<SocketAddr>>();
+
+        let results = join_all(elders.iter().map(|addr| self.endpoint.connect_to(addr))).await;
+        let failures = results
+            .into_iter()
+            .zip(elders.iter())
+            .filter_map(|(result, addr)| result.err().map(|err| (addr, err)))
+            .inspect(|(addr, err)| warn!("Failed to connect to Elder {}: {:?}", addr, err))
+            .count();
+
+        if failures == elders.len() {
+            return Err(Error::ElderConnection);
+        }
+
+        let connected = elders.len() - failures;
+        debug!(
+            "Connected to {} Elders of section {:?}",
+            connected, sap.prefix
+        );
+        Ok(Some((sap.prefix, connected)))
+    }
+
+    /// Sends the given query towards the section `name` belongs to, without awaiting any response,
+    /// so we get to know about the section from the Anti-Entropy messages it triggers.
+    pub(crate) async fn probe_section(
+        &self,
+        name: XorName,
+        auth: ServiceAuth,
+        payload: Bytes,
+    ) -> Result<(), Error> {
+        let elders = match self.network.closest_or_opposite(&name) {
+            Some(sap) => sap.value.elders.values().copied().collect(),
+            None => vec![self.bootstrap_peer()],
+        };
+
+        // With the genesis key as destination section key, the section will respond
+        // with an AE-Retry carrying the proof chain we need to trust its SAP.
+        let msg_id = MessageId::new();
+        let wire_msg = WireMsg::new_msg(
+            msg_id,
+            payload,
+            MsgKind::ServiceMsg(auth),
+            DstLocation::Section {
+                name,
+                section_pk: self.genesis_key,
+            },
+        )?;
+
+        send_message(
+            elders,
+            wire_msg,
+            self.endpoint.clone(),
+            msg_id,
+            &self.tasks,
+            &self.links,
+        )
+        .await
+    }
+}
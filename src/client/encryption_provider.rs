@@ -0,0 +1,98 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::utils::encryption;
+use crate::types::{Encryption, PublicKey};
+use crate::url::Scope;
+use std::fmt::Debug;
+
+/// Provides the encryption applied to the data a client stores, and removed from the data it reads.
+///
+/// Private data is encrypted for its owner before it's sent to the network. Plugging in a
+/// provider with [`Client::with_encryption_provider`] allows deployments to use an alternative
+/// scheme, e.g. a hybrid post-quantum KEM, without changing the blob APIs.
+///
+/// The same provider must be used to read data as was used to store it.
+///
+/// [`Client::with_encryption_provider`]: crate::client::Client::with_encryption_provider
+pub trait EncryptionProvider: Debug + Send + Sync {
+    /// Returns the encryption for data of the given `scope` owned by `public_key`,
+    /// or `None` if such data is not to be encrypted.
+    fn encryption(&self, scope: Scope, public_key: PublicKey) -> Option<Box<dyn Encryption>>;
+}
+
+/// The encryption provider used by clients unless specified otherwise.
+///
+/// Public data is not encrypted, private data is encrypted as per [`encryption`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultEncryptionProvider;
+
+impl EncryptionProvider for DefaultEncryptionProvider {
+    fn encryption(&self, scope: Scope, public_key: PublicKey) -> Option<Box<dyn Encryption>> {
+        encryption(scope, public_key).map(|encryption| Box::new(encryption) as Box<dyn Encryption>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DefaultEncryptionProvider, EncryptionProvider};
+    use crate::types::{utils::random_bytes, Encryption, Keypair, PublicKey, Result};
+    use crate::url::Scope;
+    use bytes::Bytes;
+    use eyre::{eyre, Result as EyreResult};
+    use rand::rngs::OsRng;
+
+    // Flips all the bits of the data, standing in for an alternative scheme.
+    #[derive(Debug)]
+    struct FlippingProvider;
+
+    struct Flipping(PublicKey);
+
+    impl Encryption for Flipping {
+        fn public_key(&self) -> &PublicKey {
+            &self.0
+        }
+        fn encrypt(&self, data: Bytes) -> Result<Bytes> {
+            Ok(data.iter().map(|byte| !byte).collect())
+        }
+        fn decrypt(&self, encrypted_data: Bytes) -> Result<Bytes> {
+            self.encrypt(encrypted_data)
+        }
+    }
+
+    impl EncryptionProvider for FlippingProvider {
+        fn encryption(&self, scope: Scope, public_key: PublicKey) -> Option<Box<dyn Encryption>> {
+            match scope {
+                Scope::Public => None,
+                Scope::Private => Some(Box::new(Flipping(public_key))),
+            }
+        }
+    }
+
+    #[test]
+    fn only_private_data_is_encrypted() -> EyreResult<()> {
+        let public_key = Keypair::new_ed25519(&mut OsRng).public_key();
+        let providers: [&dyn EncryptionProvider; 2] =
+            [&DefaultEncryptionProvider, &FlippingProvider];
+
+        for provider in providers.iter() {
+            assert!(provider.encryption(Scope::Public, public_key).is_none());
+
+            let encryption = provider
+                .encryption(Scope::Private, public_key)
+                .ok_or_else(|| eyre!("No encryption for private data"))?;
+            assert_eq!(encryption.public_key(), &public_key);
+
+            let data = random_bytes(32);
+            let encrypted = encryption.encrypt(data.clone())?;
+            assert_eq!(encryption.decrypt(encrypted)?, data);
+        }
+
+        Ok(())
+    }
+}
@@ -6,12 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use crate::client::client_api::{BlobAddress, HealthCheckStage};
 pub use crate::messaging::data::Error as ErrorMessage;
 use crate::messaging::{
     data::{CmdError, OperationId, QueryResponse},
     Error as MessagingError,
 };
-use crate::types::Error as DtError;
+use crate::types::{register::Address as RegisterAddress, ChunkAddress, Error as DtError};
 use std::{io, net::SocketAddr};
 use thiserror::Error;
 
@@ -79,6 +80,9 @@ pub enum Error {
     /// Permission set provided is not a PrivatePermissionSet.
     #[error("Expected private permission set")]
     NotPrivatePermissions,
+    /// Blob expected to be private is public.
+    #[error("Expected a private blob, but {0:?} is public")]
+    NotPrivateBlob(BlobAddress),
     /// Did not receive an incoming connection listener from qp2p
     #[error("Could not listen on elder connection")]
     NoElderListenerEstablished,
@@ -88,9 +92,140 @@ pub enum Error {
     /// No operation Id could be found
     #[error("Could not retrieve the operation id of a query response")]
     UnknownOperationId,
+    /// Elders returned conflicting responses, none of which a majority of them agreed on
+    #[error("Elders returned conflicting responses to operation {0}")]
+    ConflictingResponses(OperationId),
+    /// Not as many Elders as the read consistency required agreed on a response
+    #[error("Not enough Elders agreed on a response to operation {0}")]
+    InsufficientAgreement(OperationId),
+    /// The client was closed, so no more operations can be carried out with it
+    #[error("The client was closed")]
+    ClientClosed,
+    /// The deadline of the operation's retry budget passed before it was done
+    #[error("The deadline of the operation passed before it was done")]
+    DeadlineExceeded,
+    /// The client was closed before some operations in flight were done
+    #[error("The client was closed with {0} operations still in flight")]
+    OperationsCutOff(usize),
+    /// A health check of the client failed
+    #[error("Health check failed at the {stage:?} stage: {source}")]
+    HealthCheck {
+        /// Part of the health check which failed
+        stage: HealthCheckStage,
+        /// What it failed with
+        source: Box<Error>,
+    },
+    /// The proof received for a chunk doesn't verify against the network's genesis key
+    #[error("Invalid proof received for chunk at {0:?}")]
+    InvalidDataProof(ChunkAddress),
+    /// The archive has no file at the given path
+    #[error("No file at {0} in the archive")]
+    ArchiveFileNotFound(String),
+    /// The files container has no file or directory at the given path
+    #[error("No file or directory at {0} in the files container")]
+    FilesContainerPathNotFound(String),
+    /// The files container already has a file or directory at the given path
+    #[error("There's a file or directory at {0} in the files container already")]
+    FilesContainerPathExists(String),
+    /// The path isn't valid in a files container
+    #[error("Invalid files container path: {0}")]
+    InvalidFilesContainerPath(String),
+    /// No target is set for the public name
+    #[error("No target is set for the public name {0}")]
+    NrsNameNotFound(String),
+    /// The public name, or the URL it's in, isn't valid
+    #[error("Invalid public name: {0}")]
+    InvalidNrsName(String),
+    /// The pointer has no valid target
+    #[error("Invalid pointer: {0}")]
+    InvalidPointer(String),
+    /// A Register which must not exist yet, e.g. that of a public name, is already stored
+    #[error("A Register is already stored at {0:?}")]
+    RegisterTaken(RegisterAddress),
+    /// The URL can't be fetched
+    #[error("Unsupported URL: {0}")]
+    UnsupportedUrl(String),
+    /// The network time received doesn't verify against the network's genesis key
+    #[error("Invalid network time received: {0}")]
+    InvalidNetworkTime(u64),
+    /// The document was stored with a schema version it can't be migrated from
+    #[error("No migration from schema version {0}")]
+    NoSchemaMigration(u32),
+    /// The data is larger than the network accepts
+    #[error(
+        "Data of {actual} bytes exceeds the max of {max} bytes the network accepts. Split it up, \
+        e.g. by storing it as an archive, or as several blobs referenced from a Register."
+    )]
+    DataTooLarge {
+        /// Max size accepted, in bytes
+        max: u64,
+        /// Size of the data, in bytes
+        actual: u64,
+    },
+    /// The batch of Register edits has more operations than the network accepts
+    #[error(
+        "Batch of {actual} Register operations exceeds the max of {max} the network accepts. \
+        Split it up into several batches."
+    )]
+    TooManyOps {
+        /// Max number of operations accepted in a batch
+        max: u64,
+        /// Number of operations in the batch
+        actual: u64,
+    },
+    /// The blob was stored in parts, in a version of their layout this client doesn't support
+    #[error("Blob stored in version {0} of the parts format, which this client doesn't support")]
+    UnsupportedPartsFormat(u16),
+    /// The holders received for a chunk don't verify against the network's genesis key
+    #[error("Invalid holders received for chunk at {0:?}")]
+    InvalidChunkHolders(ChunkAddress),
     /// Unexpected response received
     #[error("Unexpected response received when querying {0:?}")]
     UnexpectedQueryResponse(QueryResponse),
+    /// The client can't reach the network through the proxy configured
+    #[error("Connecting through the proxy configured isn't supported: {0}")]
+    ProxyNotSupported(String),
+    /// The blob address isn't encoded in any format blob addresses have been encoded in
+    #[error("Invalid blob address: {0}")]
+    InvalidBlobAddress(String),
+    /// The publish hook of the client refused the content to be published
+    #[error("Publishing the content was refused: {0}")]
+    PublishRejected(String),
+    /// The upload session can't be resumed with the data given
+    #[error("The upload session doesn't match: {0}")]
+    UploadSessionMismatch(String),
+    /// Some of the chunks of an upload couldn't be stored, and it has to be resumed
+    #[error(
+        "{missing} of the {total} chunks of the upload couldn't be stored, resume it to retry"
+    )]
+    UploadIncomplete {
+        /// Number of chunks not stored
+        missing: usize,
+        /// Number of chunks of the blob
+        total: usize,
+    },
+    /// The name of a profile, or of a journal in one, isn't valid
+    #[error("Invalid profile or journal name: {0}")]
+    InvalidProfileName(String),
+    /// The contacts to bootstrap to the network with weren't set for the profile
+    #[error("No contacts to bootstrap with were set for the profile {0}")]
+    ProfileContactsNotSet(String),
+    /// The genesis key configured doesn't match the one held by an independent source
+    #[error("The genesis key configured doesn't match the one from the {origin}, which is {key}")]
+    GenesisKeyMismatch {
+        /// The source the genesis key was checked against
+        origin: String,
+        /// The genesis key held by the source, hex encoded
+        key: String,
+    },
+    /// The genesis key couldn't be checked against an independent source
+    #[error("Failed to get the genesis key from the {origin}: {error}")]
+    GenesisKeySourceUnavailable {
+        /// The source the genesis key was to be checked against
+        origin: String,
+        /// What getting the genesis key from it failed with
+        error: String,
+    },
     /// Not in testnet "simulated payout" mode
     #[error("Simulated payouts unavailable without 'simualted-payouts' feature flag at build")]
     NotBuiltWithSimulatedPayouts,
@@ -131,11 +266,16 @@ pub enum Error {
     #[error(transparent)]
     Serialisation(#[from] Box<bincode::ErrorKind>),
     /// Sled error.
+    #[cfg(feature = "node")]
     #[error("Sled error:: {0}")]
     Sled(#[from] sled::Error),
     /// Database error.
+    #[cfg(feature = "node")]
     #[error("Database error:: {0}")]
     Database(#[from] crate::dbs::Error),
+    /// Safe URL error.
+    #[error(transparent)]
+    Url(#[from] crate::url::Error),
     /// Generic Error
     #[error("Generic error")]
     Generic(String),
@@ -0,0 +1,199 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::{Error, Result};
+use crate::types::PublicKey;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+    time::Duration,
+};
+use tracing::{debug, info};
+use trust_dns_resolver::TokioAsyncResolver;
+
+// Prefix of the TXT records holding the genesis key, telling them apart from other TXT records.
+const DNS_RECORD_PREFIX: &str = "sn-genesis=";
+// How long fetching the key from a URL may take.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An independent source of the network's genesis key, which the genesis key configured is
+/// checked against before bootstrapping, so a tampered config can't point the client at a
+/// fake network unnoticed.
+///
+/// Each source holds the key hex encoded, as in the node connection info file.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum GenesisKeySource {
+    /// A file holding the key, e.g. on removable media or a share managed separately.
+    File(PathBuf),
+    /// An https:// URL serving the key, e.g. on the network's website.
+    Url(String),
+    /// A domain name with a TXT record holding the key, prefixed with `sn-genesis=`.
+    Dns(String),
+}
+
+impl Display for GenesisKeySource {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(formatter, "file {}", path.display()),
+            Self::Url(url) => write!(formatter, "URL {}", url),
+            Self::Dns(domain) => write!(formatter, "TXT record of {}", domain),
+        }
+    }
+}
+
+impl GenesisKeySource {
+    /// Fetches the genesis key from the source.
+    pub async fn fetch(&self) -> Result<bls::PublicKey> {
+        let unavailable = |error: String| Error::GenesisKeySourceUnavailable {
+            origin: self.to_string(),
+            error,
+        };
+
+        let encoded = match self {
+            Self::File(path) => tokio::fs::read_to_string(path)
+                .await
+                .map_err(|err| unavailable(err.to_string()))?,
+            Self::Url(url) => fetch_url(url).await.map_err(unavailable)?,
+            Self::Dns(domain) => fetch_txt_record(domain).await.map_err(unavailable)?,
+        };
+
+        PublicKey::bls_from_hex(encoded.trim())
+            .ok()
+            .and_then(|key| key.bls())
+            .ok_or_else(|| unavailable("it doesn't hold a BLS public key".to_string()))
+    }
+}
+
+async fn fetch_url(url: &str) -> Result<String, String> {
+    let parsed = url::Url::parse(url).map_err(|err| err.to_string())?;
+    if parsed.scheme() != "https" {
+        return Err("only https:// URLs can be trusted as sources".to_string());
+    }
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    client
+        .get(parsed)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?
+        .text()
+        .await
+        .map_err(|err| err.to_string())
+}
+
+async fn fetch_txt_record(domain: &str) -> Result<String, String> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf().map_err(|err| err.to_string())?;
+    let records = resolver
+        .txt_lookup(domain)
+        .await
+        .map_err(|err| err.to_string())?;
+    records
+        .iter()
+        .map(|record| {
+            record
+                .txt_data()
+                .iter()
+                .map(|data| String::from_utf8_lossy(data))
+                .collect::<String>()
+        })
+        .find_map(|record| {
+            record
+                .strip_prefix(DNS_RECORD_PREFIX)
+                .map(ToString::to_string)
+        })
+        .ok_or_else(|| format!("no TXT record starting with `{}`", DNS_RECORD_PREFIX))
+}
+
+/// Checks the genesis key configured against each of the sources, all of which must be
+/// reachable and agree with it.
+pub(crate) async fn check_genesis_key(
+    genesis_key: &bls::PublicKey,
+    sources: &[GenesisKeySource],
+) -> Result<()> {
+    if sources.is_empty() {
+        return Ok(());
+    }
+    debug!(
+        "Checking genesis key against {} independent sources",
+        sources.len()
+    );
+
+    let keys = join_all(sources.iter().map(GenesisKeySource::fetch)).await;
+    for (source, key) in sources.iter().zip(keys) {
+        let key = key?;
+        if key != *genesis_key {
+            return Err(Error::GenesisKeyMismatch {
+                origin: source.to_string(),
+                key: hex::encode(key.to_bytes()),
+            });
+        }
+    }
+
+    info!(
+        "Genesis key confirmed by all {} independent sources",
+        sources.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_genesis_key, GenesisKeySource};
+    use crate::client::Error;
+    use eyre::Result;
+    use tempfile::tempdir;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn genesis_key_is_checked_against_sources() -> Result<()> {
+        let dir = tempdir()?;
+        let genesis_key = bls::SecretKey::random().public_key();
+        let fake_key = bls::SecretKey::random().public_key();
+
+        let matching = dir.path().join("genesis_key");
+        std::fs::write(
+            &matching,
+            format!("{}\n", hex::encode(genesis_key.to_bytes())),
+        )?;
+        let tampered = dir.path().join("tampered_genesis_key");
+        std::fs::write(&tampered, hex::encode(fake_key.to_bytes()))?;
+
+        check_genesis_key(&genesis_key, &[]).await?;
+        check_genesis_key(&genesis_key, &[GenesisKeySource::File(matching.clone())]).await?;
+
+        let mismatch = check_genesis_key(
+            &genesis_key,
+            &[
+                GenesisKeySource::File(matching.clone()),
+                GenesisKeySource::File(tampered),
+            ],
+        )
+        .await;
+        assert!(matches!(mismatch, Err(Error::GenesisKeyMismatch { .. })));
+
+        let unavailable = check_genesis_key(
+            &genesis_key,
+            &[
+                GenesisKeySource::File(matching),
+                GenesisKeySource::File(dir.path().join("missing")),
+                GenesisKeySource::Url("http://example.com/genesis_key".to_string()),
+            ],
+        )
+        .await;
+        assert!(matches!(
+            unavailable,
+            Err(Error::GenesisKeySourceUnavailable { .. })
+        ));
+
+        Ok(())
+    }
+}
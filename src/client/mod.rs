@@ -15,7 +15,7 @@
 //! With these APIs you can easily:
 //! - Connect to The Safe Network
 //! - Read Public data from the network
-//! - Write data to the network (assuming you have a SafeCoin balance)
+//! - Write data to the network, owned by the client's keypair
 //!
 //! ## Basic Usage
 //!
@@ -28,14 +28,32 @@
 
 mod config_handler;
 mod connections;
+mod encryption_provider;
 mod errors;
+mod genesis_sources;
+mod profiles;
+mod proxy;
+mod publish_hook;
 
 // Export public API.
 
-pub use client_api::Client;
+pub use client_api::{
+    calculate_blob_address, calculate_blob_chunks, calculate_blob_manifest, Client,
+};
 pub use config_handler::{Config, DEFAULT_QUERY_TIMEOUT};
+pub use connections::{
+    AntiEntropyEvent, AntiEntropyOutcome, AntiEntropyReason, BootstrapProgress, ClientEvent,
+    CmdHandle, ConcurrencyLimits, ConnectionRotation, ConnectionState, ConnectionStats,
+    Diagnostics, ElderInfo, ErrorChannelConfig, NetworkInfo, OperationPriority, OverflowPolicy,
+    QueryTrace, ReadConsistency, ResponseDivergence, RotationReason,
+};
+pub use encryption_provider::{DefaultEncryptionProvider, EncryptionProvider};
 pub use errors::ErrorMessage;
 pub use errors::{Error, Result};
+pub use genesis_sources::GenesisKeySource;
+pub use profiles::Profile;
+pub use proxy::{ProxyAuth, ProxyConfig};
+pub use publish_hook::{PublishHook, PublishVerdict, PublishedContent};
 pub use qp2p::Config as QuicP2pConfig;
 
 /// Client trait and related constants.
@@ -0,0 +1,269 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::client_api::UploadSession;
+use crate::client::{Error, Result};
+use crate::types::Keypair;
+use crate::url::Scope;
+
+use rand::{rngs::OsRng, Rng};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    collections::BTreeSet,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+// Directory profiles are kept in, within the root directory of the config.
+const PROFILES_DIR_NAME: &str = "profiles";
+const KEYPAIR_FILE_NAME: &str = "keypair";
+const CONTACTS_FILE_NAME: &str = "contacts";
+const GENESIS_KEY_FILE_NAME: &str = "genesis_key";
+const JOURNALS_DIR_NAME: &str = "journals";
+const SPILL_DIR_NAME: &str = "spill";
+const MAX_NAME_LEN: usize = 64;
+
+/// A named profile, e.g. one per account of a desktop application, with its own keypair,
+/// network, and directory for all the state clients persist, which no other profile shares.
+///
+/// Profiles are kept in the `profiles` directory within [`Config::root_dir`], and opened by
+/// [`Client::with_profile`]. A profile is bound to the network it's first used with.
+///
+/// [`Config::root_dir`]: crate::client::Config::root_dir
+/// [`Client::with_profile`]: crate::client::Client::with_profile
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Profile {
+    name: String,
+    dir: PathBuf,
+}
+
+impl Profile {
+    /// Opens the profile named `name` within `root_dir`, creating it if there's none.
+    ///
+    /// Names are made of up to 64 ASCII letters, digits, `-` and `_`.
+    pub fn open(root_dir: &Path, name: &str) -> Result<Self> {
+        check_name(name)?;
+        let profile = Self {
+            name: name.to_string(),
+            dir: root_dir.join(PROFILES_DIR_NAME).join(name),
+        };
+        fs::create_dir_all(profile.journal_dir())?;
+        fs::create_dir_all(profile.spill_dir())?;
+        Ok(profile)
+    }
+
+    /// Names of the profiles within `root_dir`.
+    pub fn list(root_dir: &Path) -> Result<Vec<String>> {
+        let dir = root_dir.join(PROFILES_DIR_NAME);
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut names = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                names.extend(entry.file_name().to_str().map(str::to_string));
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Name of the profile.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Directory the profile's state is kept in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Directory the profile's upload journals are kept in.
+    pub fn journal_dir(&self) -> PathBuf {
+        self.dir.join(JOURNALS_DIR_NAME)
+    }
+
+    /// Directory blobs read by the profile's clients are spilled to, when larger than their
+    /// read memory limit.
+    pub fn spill_dir(&self) -> PathBuf {
+        self.dir.join(SPILL_DIR_NAME)
+    }
+
+    /// The upload session journaled under `name` in the profile, or a new one if there's none,
+    /// as per [`UploadSession::persisted_at`]. Journal names follow the rules of profile names.
+    pub fn upload_journal(&self, name: &str, scope: Scope) -> Result<UploadSession> {
+        check_name(name)?;
+        UploadSession::persisted_at(self.journal_dir().join(name), scope)
+    }
+
+    /// The profile's keypair, generated and saved on first use.
+    ///
+    /// The keypair is saved readable by the owner only, where the platform supports it, and
+    /// in full or not at all. Should another process generate one at the same time, whichever
+    /// is saved first is used by both.
+    pub fn keypair(&self) -> Result<Keypair> {
+        let path = self.dir.join(KEYPAIR_FILE_NAME);
+        if path.exists() {
+            return Ok(bincode::deserialize(&fs::read(path)?)?);
+        }
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let bytes = bincode::serialize(&keypair)?;
+
+        // Written in full to a file of our own first, then linked to, so the keypair is never
+        // read partly written, nor one saved in the meantime overwritten.
+        let tmp_path = self.dir.join(format!(
+            "{}.{:016x}.tmp",
+            KEYPAIR_FILE_NAME,
+            OsRng.gen::<u64>()
+        ));
+        let mut options = OpenOptions::new();
+        let _ = options.write(true).create_new(true);
+        #[cfg(unix)]
+        let _ = options.mode(0o600);
+        let written = options.open(&tmp_path).and_then(|mut file| {
+            file.write_all(&bytes)?;
+            file.sync_all()?;
+            fs::hard_link(&tmp_path, &path)
+        });
+        let _ = fs::remove_file(&tmp_path);
+        match written {
+            Ok(()) => Ok(keypair),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+                Ok(bincode::deserialize(&fs::read(path)?)?)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Saves the contacts the profile's clients bootstrap to the network with.
+    pub fn set_contacts(&self, contacts: &BTreeSet<SocketAddr>) -> Result<()> {
+        fs::write(
+            self.dir.join(CONTACTS_FILE_NAME),
+            bincode::serialize(contacts)?,
+        )?;
+        Ok(())
+    }
+
+    /// The contacts the profile's clients bootstrap to the network with.
+    pub fn contacts(&self) -> Result<BTreeSet<SocketAddr>> {
+        let path = self.dir.join(CONTACTS_FILE_NAME);
+        if !path.exists() {
+            return Err(Error::ProfileContactsNotSet(self.name.clone()));
+        }
+        Ok(bincode::deserialize(&fs::read(path)?)?)
+    }
+
+    /// Binds the profile to the network of the given genesis key on first use, and checks it's
+    /// the same network afterwards, so a profile's keys and state aren't used on another one.
+    pub(crate) fn check_genesis_key(&self, genesis_key: &bls::PublicKey) -> Result<()> {
+        let path = self.dir.join(GENESIS_KEY_FILE_NAME);
+        if !path.exists() {
+            fs::write(path, genesis_key.to_bytes())?;
+            return Ok(());
+        }
+        let bound = fs::read(path)?;
+        if bound != genesis_key.to_bytes() {
+            return Err(Error::GenesisKeyMismatch {
+                origin: format!("profile {}", self.name),
+                key: hex::encode(bound),
+            });
+        }
+        Ok(())
+    }
+}
+
+// Names can't be paths, so profiles and their journals stay within their directories.
+fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(Error::InvalidProfileName(name.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Profile;
+    use crate::client::Error;
+    use eyre::Result;
+    use std::collections::BTreeSet;
+    use tempfile::tempdir;
+
+    #[test]
+    fn profiles_keep_their_state_apart() -> Result<()> {
+        let root_dir = tempdir()?;
+        let alice = Profile::open(root_dir.path(), "alice")?;
+        let bob = Profile::open(root_dir.path(), "bob")?;
+        assert_eq!(Profile::list(root_dir.path())?, vec!["alice", "bob"]);
+        assert!(matches!(
+            Profile::open(root_dir.path(), "../alice"),
+            Err(Error::InvalidProfileName(_))
+        ));
+
+        let key = alice.keypair()?.public_key();
+        assert_eq!(
+            Profile::open(root_dir.path(), "alice")?
+                .keypair()?
+                .public_key(),
+            key
+        );
+        assert_ne!(bob.keypair()?.public_key(), key);
+
+        let contacts: BTreeSet<_> = vec!["127.0.0.1:12000".parse()?].into_iter().collect();
+        alice.set_contacts(&contacts)?;
+        assert_eq!(alice.contacts()?, contacts);
+        assert!(matches!(
+            bob.contacts(),
+            Err(Error::ProfileContactsNotSet(_))
+        ));
+        assert_ne!(alice.journal_dir(), bob.journal_dir());
+
+        let genesis_key = bls::SecretKey::random().public_key();
+        alice.check_genesis_key(&genesis_key)?;
+        alice.check_genesis_key(&genesis_key)?;
+        bob.check_genesis_key(&bls::SecretKey::random().public_key())?;
+        assert!(matches!(
+            alice.check_genesis_key(&bls::SecretKey::random().public_key()),
+            Err(Error::GenesisKeyMismatch { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keypair_is_only_readable_by_the_owner() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root_dir = tempdir()?;
+        let profile = Profile::open(root_dir.path(), "alice")?;
+        let _ = profile.keypair()?;
+        let mode = std::fs::metadata(profile.dir().join(super::KEYPAIR_FILE_NAME))?
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // Nothing but the keypair is left behind.
+        assert_eq!(
+            std::fs::read_dir(profile.dir())?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .count(),
+            1
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::client::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Debug, Formatter};
+use std::net::SocketAddr;
+
+/// A proxy to reach the network through, e.g. from behind a corporate firewall.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum ProxyConfig {
+    /// A SOCKS5 proxy, relaying the client's UDP traffic with `UDP ASSOCIATE`.
+    Socks5 {
+        /// Address of the proxy.
+        addr: SocketAddr,
+        /// Credentials to authenticate with, if the proxy requires them.
+        auth: Option<ProxyAuth>,
+    },
+    /// An HTTP proxy, tunnelling the client's traffic with `CONNECT`.
+    Http {
+        /// Address of the proxy.
+        addr: SocketAddr,
+        /// Credentials to authenticate with, if the proxy requires them.
+        auth: Option<ProxyAuth>,
+    },
+}
+
+/// Username and password to authenticate with a proxy.
+#[derive(Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ProxyAuth {
+    /// Username to authenticate with.
+    pub username: String,
+    /// Password to authenticate with.
+    pub password: String,
+}
+
+// Keeps the password out of logs.
+impl Debug for ProxyAuth {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("ProxyAuth")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+impl ProxyConfig {
+    /// Address of the proxy.
+    pub fn addr(&self) -> SocketAddr {
+        match self {
+            Self::Socks5 { addr, .. } | Self::Http { addr, .. } => *addr,
+        }
+    }
+}
+
+/// Checks the client's connections can be made through the proxy configured.
+///
+/// Connections to the network are made over QUIC, i.e. over UDP, by the qp2p endpoint, which
+/// binds its own socket and sends to nodes directly. HTTP `CONNECT` tunnels only carry TCP, and
+/// the endpoint has no hook yet for wrapping its datagrams in SOCKS5 `UDP ASSOCIATE` headers.
+/// Rather than bypassing a proxy the user relies on, e.g. for privacy, bootstrapping is refused.
+pub(crate) fn check_proxy(proxy: Option<&ProxyConfig>) -> Result<()> {
+    match proxy {
+        None => Ok(()),
+        Some(ProxyConfig::Socks5 { addr, .. }) => Err(Error::ProxyNotSupported(format!(
+            "SOCKS5 proxy at {}: relaying QUIC datagrams isn't supported by the transport yet",
+            addr
+        ))),
+        Some(ProxyConfig::Http { addr, .. }) => Err(Error::ProxyNotSupported(format!(
+            "HTTP proxy at {}: HTTP proxies can only tunnel TCP, while the network is reached \
+            over QUIC, i.e. UDP",
+            addr
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_proxy, ProxyAuth, ProxyConfig};
+    use crate::client::Error;
+    use eyre::Result;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn proxies_are_never_bypassed() -> Result<()> {
+        check_proxy(None)?;
+
+        let auth = ProxyAuth {
+            username: "alice".to_string(),
+            password: "hunter2".to_string(),
+        };
+        assert!(!format!("{:?}", auth).contains("hunter2"));
+
+        let addr = SocketAddr::from(([127, 0, 0, 1], 1080));
+        for proxy in vec![
+            ProxyConfig::Socks5 {
+                addr,
+                auth: Some(auth.clone()),
+            },
+            ProxyConfig::Http { addr, auth: None },
+        ] {
+            assert!(matches!(
+                check_proxy(Some(&proxy)),
+                Err(Error::ProxyNotSupported(_))
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::types::PublicKey;
+use std::{fmt::Debug, io::Read};
+
+/// What's known of content about to be published, as passed to a [`PublishHook`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PublishedContent {
+    /// Size of the content, in bytes.
+    pub size: usize,
+    /// Key of the client publishing the content, which owns it.
+    pub publisher: PublicKey,
+}
+
+/// Whether content may be published, as decided by a [`PublishHook`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PublishVerdict {
+    /// The content may be published.
+    Allow,
+    /// The content may not be published, for the given reason.
+    Reject(String),
+}
+
+/// Checks the content a client is about to publish, i.e. store as public data, before any of
+/// it is uploaded.
+///
+/// Plugging in a hook with [`Client::with_publish_hook`] allows integrators, e.g. public
+/// gateways or enterprises, to enforce their content policies, such as scanning for malware,
+/// without changing the blob APIs. Public data can't be deleted once stored, so it's checked
+/// before rather than after the fact. Private data isn't checked.
+///
+/// The hook is run on a blocking thread, so it may do blocking I/O, e.g. to call out to an
+/// external scanner.
+///
+/// [`Client::with_publish_hook`]: crate::client::Client::with_publish_hook
+pub trait PublishHook: Debug + Send + Sync {
+    /// Returns whether the content described by `content`, read from `reader`, may be
+    /// published.
+    fn check(&self, content: &PublishedContent, reader: &mut dyn Read) -> PublishVerdict;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PublishHook, PublishVerdict, PublishedContent};
+    use crate::client::client_api::data::UPLOAD_PART_SIZE;
+    use crate::client::utils::test_utils::create_test_client;
+    use crate::client::Error;
+    use crate::types::utils::random_bytes;
+    use crate::url::Scope;
+    use bytes::{BufMut, BytesMut};
+    use eyre::{eyre, Result};
+    use std::{
+        io::{Cursor, Read},
+        sync::Arc,
+    };
+
+    // Rejects content containing a given pattern, standing in for a scanner.
+    #[derive(Debug)]
+    struct PatternHook(&'static [u8]);
+
+    impl PublishHook for PatternHook {
+        fn check(&self, _: &PublishedContent, reader: &mut dyn Read) -> PublishVerdict {
+            let mut content = Vec::new();
+            if let Err(error) = reader.read_to_end(&mut content) {
+                return PublishVerdict::Reject(error.to_string());
+            }
+            if content.windows(self.0.len()).any(|window| window == self.0) {
+                PublishVerdict::Reject("Content matches a blocked pattern".to_string())
+            } else {
+                PublishVerdict::Allow
+            }
+        }
+    }
+
+    const BLOCKED: &[u8] = b"EICAR";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn write_many_refuses_rejected_public_content() -> Result<()> {
+        let client = create_test_client(None)
+            .await?
+            .with_publish_hook(Arc::new(PatternHook(BLOCKED)));
+
+        let size = self_encryption::MIN_ENCRYPTABLE_BYTES;
+        let mut blocked = BytesMut::from(&random_bytes(size)[..]);
+        blocked.put_slice(BLOCKED);
+        let blocked = blocked.freeze();
+        let items = vec![
+            (random_bytes(size), Scope::Public),
+            (blocked.clone(), Scope::Public),
+        ];
+        match client.write_many(items, false).await {
+            Err(Error::PublishRejected(_)) => {}
+            result => return Err(eyre!("Unexpected result: {:?}", result)),
+        }
+
+        // Private data isn't published, so isn't checked.
+        let upload = client
+            .write_many(vec![(blocked, Scope::Private)], false)
+            .await?;
+        assert!(upload.is_complete());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn write_stream_refuses_rejected_public_content() -> Result<()> {
+        let client = create_test_client(None)
+            .await?
+            .with_publish_hook(Arc::new(PatternHook(BLOCKED)));
+
+        // Within a part, the content is checked as a whole.
+        let mut blocked = BytesMut::from(&random_bytes(1024)[..]);
+        blocked.put_slice(BLOCKED);
+        match client
+            .write_stream(Cursor::new(blocked.freeze()), Scope::Public)
+            .await
+        {
+            Err(Error::PublishRejected(_)) => {}
+            result => return Err(eyre!("Unexpected result: {:?}", result)),
+        }
+
+        // Over a part, it can't be checked before being uploaded, so it's refused even though
+        // the hook would allow it.
+        let allowed = random_bytes(UPLOAD_PART_SIZE + 1);
+        match client
+            .write_stream(Cursor::new(allowed), Scope::Public)
+            .await
+        {
+            Err(Error::PublishRejected(_)) => {}
+            result => return Err(eyre!("Unexpected result: {:?}", result)),
+        }
+
+        Ok(())
+    }
+}
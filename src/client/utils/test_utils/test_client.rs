@@ -8,9 +8,12 @@
 
 use super::read_network_conn_info;
 use crate::client::{Client, Config};
-use crate::types::Keypair;
+use crate::types::{Keypair, Signer};
 use eyre::Result;
-use std::{sync::Once, time::Duration};
+use std::{
+    sync::{Arc, Once},
+    time::Duration,
+};
 use tempfile::tempdir;
 use tracing_subscriber::{fmt, EnvFilter};
 
@@ -47,7 +50,8 @@ pub async fn create_test_client_with(
     let (genesis_key, bootstrap_nodes) = read_network_conn_info()?;
 
     let config = Config::new(Some(root_dir.path()), None, genesis_key, None, timeout).await;
-    let client = Client::new(config, bootstrap_nodes, optional_keypair.clone()).await?;
+    let optional_signer = optional_keypair.map(|keypair| Arc::new(keypair) as Arc<dyn Signer>);
+    let client = Client::new(config, bootstrap_nodes, optional_signer).await?;
 
     Ok(client)
 }
@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::messaging::data::Error as ErrorMessage;
-use crate::types::{convert_dt_error_to_error_message, DataAddress, PublicKey};
+use crate::types::{convert_dt_error_to_error_message, ChunkAddress, DataAddress, PublicKey};
 use std::io;
 use thiserror::Error;
 
@@ -46,6 +46,33 @@ pub enum Error {
     /// Chunk already exists for this node
     #[error("Data already exists at this node")]
     DataExists,
+    /// Chunk is bigger than the maximum size allowed.
+    #[error("Chunk of {size} bytes exceeds the max allowed size of {max} bytes")]
+    ChunkTooLarge {
+        /// Size of the chunk.
+        size: usize,
+        /// Max size allowed for a chunk.
+        max: usize,
+    },
+    /// Register entry is bigger than the maximum size allowed.
+    #[error("Register entry of {size} bytes exceeds the max allowed size of {max} bytes")]
+    EntryTooLarge {
+        /// Size of the entry.
+        size: u64,
+        /// Max size allowed for an entry.
+        max: u64,
+    },
+    /// Batch of Register edits has more operations than allowed.
+    #[error("Batch of {ops} Register operations exceeds the max of {max} allowed")]
+    TooManyOps {
+        /// Number of operations in the batch.
+        ops: u64,
+        /// Max number of operations allowed in a batch.
+        max: u64,
+    },
+    /// Chunk content doesn't hash to its address, as it was tampered with or corrupted.
+    #[error("Chunk content does not match its address: {0:?}")]
+    ChunkAddressMismatch(ChunkAddress),
     /// Data owner provided is invalid.
     #[error("Provided PublicKey is not a valid owner. Provided PublicKey: {0}")]
     InvalidOwner(PublicKey),
@@ -95,6 +122,13 @@ pub(crate) fn convert_to_error_message(error: Error) -> ErrorMessage {
         Error::NoSuchData(address) => ErrorMessage::DataNotFound(address),
         Error::TempDirCreationFailed(_) => ErrorMessage::FailedToWriteFile,
         Error::DataExists => ErrorMessage::DataExists,
+        Error::ChunkTooLarge { size, max } => ErrorMessage::ChunkTooLarge {
+            size: size as u64,
+            max: max as u64,
+        },
+        Error::EntryTooLarge { size, max } => ErrorMessage::EntryTooLarge { size, max },
+        Error::TooManyOps { ops, max } => ErrorMessage::TooManyOps { ops, max },
+        Error::ChunkAddressMismatch(address) => ErrorMessage::ChunkAddressMismatch(address),
         Error::NetworkData(error) => convert_dt_error_to_error_message(error),
         other => {
             ErrorMessage::InvalidOperation(format!("Failed to perform operation: {:?}", other))
@@ -7,6 +7,13 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 //! Implementation of the "Node" node for the SAFE Network.
+//!
+//! The client is built with the `client` feature, and the node, along with the routing it's
+//! built on, with the `node` feature. Both are enabled by default, but either can be built on
+//! its own, e.g. by applications only needing the client, with `default-features = false`.
+//!
+//! The types most applications need are exported by the [`prelude`], which is the stable
+//! surface of the crate.
 
 // For quick_error
 #![recursion_limit = "256"]
@@ -41,14 +48,20 @@
 #[macro_use]
 extern crate tracing;
 
+#[cfg(feature = "client")]
 pub mod client;
+#[cfg(feature = "node")]
 mod dbs;
 
+#[cfg(feature = "node")]
 pub use dbs::UsedSpace;
 
 pub mod messaging;
+#[cfg(feature = "node")]
 pub mod node;
 pub mod prefix_map;
+pub mod prelude;
+#[cfg(feature = "node")]
 pub mod routing;
 pub mod types;
 pub mod url;
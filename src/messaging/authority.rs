@@ -8,22 +8,34 @@
 
 use super::{
     system::{KeyedSig, SigShare},
-    Error, Result,
+    Delegation, Error, Result,
 };
+#[cfg(feature = "node")]
+use crate::routing::SectionKeyShare;
 use crate::{
     messaging::signature_aggregator::{Error as AggregatorError, SignatureAggregator},
-    routing::SectionKeyShare,
-    types::{PublicKey, Signature},
+    types::{KeyAlgorithm, PublicKey, Signature},
 };
 use bls::PublicKey as BlsPublicKey;
 use ed25519_dalek::{
     Keypair as EdKeypair, PublicKey as EdPublicKey, Signature as EdSignature, Signer as _,
     Verifier as _,
 };
-use std::sync::Arc;
+use std::{borrow::Cow, sync::Arc};
 use tokio::sync::RwLock;
 use xor_name::XorName;
 
+/// Signature schemes clients' messages can be signed with.
+///
+/// Messages signed with any other scheme are rejected with [`Error::UnsupportedKeyAlgorithm`]
+/// when deserialised, rather than as having an invalid signature, so peers still running
+/// an older version can tell these apart as new schemes are rolled out.
+pub const SUPPORTED_KEY_ALGORITHMS: &[KeyAlgorithm] = &[
+    KeyAlgorithm::Ed25519,
+    KeyAlgorithm::Bls,
+    KeyAlgorithm::BlsShare,
+];
+
 /// Authority of a network peer.
 #[derive(Clone, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct ServiceAuth {
@@ -31,6 +43,36 @@ pub struct ServiceAuth {
     pub public_key: PublicKey,
     /// Peer's signature.
     pub signature: Signature,
+    /// Capability the peer holds to act for a user, if it's an app doing so. It's signed over
+    /// along with the message, see [`ServiceAuth::bytes_to_sign`].
+    #[serde(default)]
+    pub delegation: Option<Delegation>,
+}
+
+impl ServiceAuth {
+    /// Key of whom the message is sent for: the user the peer acts for, if it holds
+    /// a delegation, otherwise the peer itself.
+    pub fn requester(&self) -> PublicKey {
+        self.delegation
+            .as_ref()
+            .map(|delegation| delegation.user)
+            .unwrap_or(self.public_key)
+    }
+
+    /// The bytes a peer signs to send a message with `payload`: the payload itself, or the
+    /// payload along with the delegation the peer holds, if any, for it not to be swapped
+    /// for another one in transit.
+    pub fn bytes_to_sign<'a>(
+        payload: &'a [u8],
+        delegation: Option<&Delegation>,
+    ) -> Result<Cow<'a, [u8]>> {
+        match delegation {
+            None => Ok(Cow::Borrowed(payload)),
+            Some(delegation) => bincode::serialize(&(payload, delegation))
+                .map(Cow::Owned)
+                .map_err(|err| Error::Serialisation(err.to_string())),
+        }
+    }
 }
 
 /// Authority of a single peer.
@@ -75,6 +117,7 @@ pub struct BlsShareAuth {
 
 impl BlsShareAuth {
     /// Construct verified authority of a single node's share of section authority.
+    #[cfg(feature = "node")]
     pub(crate) fn authorize(
         section_pk: BlsPublicKey,
         src_name: XorName,
@@ -172,9 +215,21 @@ pub trait VerifyAuthority: Sized + sealed::Sealed {
 
 impl VerifyAuthority for ServiceAuth {
     fn verify_authority(self, payload: impl AsRef<[u8]>) -> Result<Self> {
+        let algorithm = self.public_key.algorithm();
+        if !SUPPORTED_KEY_ALGORITHMS.contains(&algorithm) {
+            return Err(Error::UnsupportedKeyAlgorithm(algorithm));
+        }
+        if self.signature.algorithm() != algorithm {
+            return Err(Error::InvalidSignature);
+        }
+
+        let bytes = Self::bytes_to_sign(payload.as_ref(), self.delegation.as_ref())?;
         self.public_key
-            .verify(&self.signature, payload)
+            .verify(&self.signature, bytes)
             .map_err(|_| Error::InvalidSignature)?;
+        if let Some(delegation) = &self.delegation {
+            delegation.verify(&self.public_key)?;
+        }
         Ok(self)
     }
 }
@@ -0,0 +1,24 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::types::DataAddress;
+use serde::{Deserialize, Serialize};
+
+/// Max number of addresses listed in a page of a catalog.
+pub const MAX_CATALOG_PAGE_SIZE: u32 = 1_000;
+
+/// A page of the catalog of the data a key owns, i.e. paid for, as kept by the Elders of the
+/// section of the key.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CatalogPage {
+    /// Addresses of the data, in the order of the catalog.
+    pub addresses: Vec<DataAddress>,
+    /// The address to list the next page after, or `None` if this is the last page. It can be
+    /// kept around for the listing to be resumed later on.
+    pub next: Option<DataAddress>,
+}
@@ -0,0 +1,85 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::system::KeyedSig;
+use crate::types::ChunkAddress;
+use secured_linked_list::SecuredLinkedList;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use xor_name::XorName;
+
+/// The Adults currently holding copies of a chunk, as vouched for by the Elders of the section
+/// responsible for it, together with the chain of section keys leading from the network's
+/// genesis key to the key they signed with.
+///
+/// Elders only vouch for the Adults, out of those they'd pick to hold the chunk, which answered
+/// their own check that they do, so this reflects which copies were held at the time it was
+/// signed.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChunkHolders {
+    /// Address of the chunk.
+    pub address: ChunkAddress,
+    /// Names of the Adults holding it.
+    pub holders: BTreeSet<XorName>,
+    /// Section signature over the address and holders.
+    pub sig: KeyedSig,
+    /// Section keys from the genesis key to the one `sig` was made with.
+    pub proof_chain: SecuredLinkedList,
+}
+
+impl ChunkHolders {
+    /// Verifies the address and holders were signed by a section key which `proof_chain`
+    /// proves was endorsed, directly or not, by `genesis_key`.
+    pub fn verify(&self, genesis_key: &bls::PublicKey) -> bool {
+        super::verify_section_sig(
+            &(&self.address, &self.holders),
+            &self.sig,
+            &self.proof_chain,
+            genesis_key,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkHolders;
+    use crate::messaging::system::KeyedSig;
+    use crate::types::ChunkAddress;
+    use eyre::Result;
+    use secured_linked_list::SecuredLinkedList;
+    use std::collections::BTreeSet;
+    use xor_name::XorName;
+
+    #[test]
+    fn holders_are_vouched_for() -> Result<()> {
+        let genesis_sk = bls::SecretKey::random();
+        let address = ChunkAddress(XorName::random());
+        let holders: BTreeSet<_> = (0..4).map(|_| XorName::random()).collect();
+
+        let holders_proof = ChunkHolders {
+            address,
+            holders: holders.clone(),
+            sig: KeyedSig {
+                public_key: genesis_sk.public_key(),
+                signature: genesis_sk.sign(&bincode::serialize(&(&address, &holders))?),
+            },
+            proof_chain: SecuredLinkedList::new(genesis_sk.public_key()),
+        };
+        assert!(holders_proof.verify(&genesis_sk.public_key()));
+
+        // Claiming an extra holder the section didn't vouch for.
+        let mut padded = holders_proof.clone();
+        let _ = padded.holders.insert(XorName::random());
+        assert!(!padded.verify(&genesis_sk.public_key()));
+
+        // Not from this network.
+        assert!(!holders_proof.verify(&bls::SecretKey::random().public_key()));
+
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{register::RegisterWrite, CmdError, Error};
-use crate::types::Chunk;
+use crate::types::{Chunk, DataAddress, PaymentProof, PublicKey};
 use serde::{Deserialize, Serialize};
 use xor_name::XorName;
 
@@ -28,6 +28,26 @@ pub enum DataCmd {
     ///
     /// [`Register`]: crate::types::register::Register
     Register(RegisterWrite),
+    /// Records the proof that storing some data was paid for, so
+    /// the payer can later prove ownership of it.
+    RecordPayment(PaymentProof),
+    /// Adds the data a proof of payment is for to the catalog of the data `owner` owns, kept
+    /// by the section of the owner's key, so the owner can list it. Only the owner can send it,
+    /// or an app acting for them, which is then the payer.
+    CatalogPayment {
+        /// Owner of the catalog.
+        owner: PublicKey,
+        /// The proof of payment.
+        proof: PaymentProof,
+    },
+    /// Adds the data at `address`, which the sender stored, to the catalog of the data `owner`
+    /// owns, kept by the section of the owner's key. Only the owner can send it.
+    CatalogData {
+        /// Owner of the catalog, the sender.
+        owner: PublicKey,
+        /// Address of the data stored.
+        address: DataAddress,
+    },
 }
 
 impl DataCmd {
@@ -38,6 +58,7 @@ impl DataCmd {
         match self {
             StoreChunk(_) => CmdError::Data(error),
             Register(c) => c.error(error),
+            RecordPayment(_) | CatalogPayment { .. } | CatalogData { .. } => CmdError::Data(error),
         }
     }
 
@@ -47,6 +68,9 @@ impl DataCmd {
         match self {
             StoreChunk(c) => *c.name(),
             Register(c) => c.dst_name(),
+            RecordPayment(proof) => *proof.address.name(),
+            CatalogPayment { owner, .. } => XorName::from(*owner),
+            CatalogData { owner, .. } => XorName::from(*owner),
         }
     }
 }
@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::RegisterCmd;
-use crate::types::{ChunkAddress, Error, PublicKey, Result};
+use crate::types::{ChunkAddress, DataAddress, Error, PublicKey, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use xor_name::XorName;
@@ -36,6 +36,9 @@ pub struct DataExchange {
     pub chunk_data: ChunkDataExchange,
     /// Register data exchange.
     pub reg_data: RegisterDataExchange,
+    /// Catalogs of owned data exchange, absent from the exchanges of older nodes.
+    #[serde(default)]
+    pub catalog_data: CatalogDataExchange,
 }
 
 /// Chunk data exchange.
@@ -49,6 +52,10 @@ pub struct ChunkDataExchange {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RegisterDataExchange(pub BTreeMap<XorName, Vec<RegisterCmd>>);
 
+/// Catalogs of owned data exchange: the addresses of the data each owner catalogued.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogDataExchange(pub BTreeMap<PublicKey, BTreeSet<DataAddress>>);
+
 /// The degree to which storage has been used.
 /// Expressed in values between 0-10, where each unit represents 10-percentage points.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -0,0 +1,40 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use serde::{Deserialize, Serialize};
+
+/// Max size of a blob, in bytes, unless the section advertises otherwise: none. Elders only
+/// ever see the chunks blobs are self-encrypted into, whose size is bounded whatever that of
+/// the blob, so sections only cap blobs if their operators choose to.
+pub const DEFAULT_MAX_BLOB_SIZE: u64 = u64::MAX;
+/// Max size of a Register entry, in bytes, unless the section advertises otherwise.
+pub const DEFAULT_MAX_REGISTER_ENTRY_SIZE: u64 = 1024;
+/// Max number of operations in a batch of Register edits, unless the section advertises otherwise.
+pub const DEFAULT_MAX_REGISTER_BATCH_OPS: u64 = 100;
+
+/// Limits on the size of the data a section accepts, advertised by its Elders
+/// for clients to check their data against before uploading it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DataLimits {
+    /// Max size of a blob, in bytes, before it's self-encrypted into chunks.
+    pub max_blob_size: u64,
+    /// Max size of a Register entry, in bytes, once serialised.
+    pub max_register_entry_size: u64,
+    /// Max number of operations in a batch of Register edits.
+    pub max_register_batch_ops: u64,
+}
+
+impl Default for DataLimits {
+    fn default() -> Self {
+        Self {
+            max_blob_size: DEFAULT_MAX_BLOB_SIZE,
+            max_register_entry_size: DEFAULT_MAX_REGISTER_ENTRY_SIZE,
+            max_register_batch_ops: DEFAULT_MAX_REGISTER_BATCH_OPS,
+        }
+    }
+}
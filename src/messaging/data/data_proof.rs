@@ -0,0 +1,92 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::system::KeyedSig;
+use crate::types::ChunkAddress;
+use secured_linked_list::SecuredLinkedList;
+use serde::{Deserialize, Serialize};
+
+/// A section's signature over the address of a chunk, together with the chain of section keys
+/// leading from the network's genesis key to the key it was signed with.
+///
+/// As chunks are content-addressed, this vouches for the chunk's content as well, and it can be
+/// verified with nothing more than the genesis key, i.e. fully offline.
+///
+/// Elders only sign the addresses of chunks which their own check shows Adults of their
+/// section to hold.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DataProof {
+    /// Address of the chunk vouched for.
+    pub address: ChunkAddress,
+    /// Section signature over the address.
+    pub sig: KeyedSig,
+    /// Section keys from the genesis key to the one `sig` was made with.
+    pub proof_chain: SecuredLinkedList,
+}
+
+impl DataProof {
+    /// Verifies the address was signed by a section key which `proof_chain` proves
+    /// was endorsed, directly or not, by `genesis_key`.
+    pub fn verify(&self, genesis_key: &bls::PublicKey) -> bool {
+        super::verify_section_sig(&self.address, &self.sig, &self.proof_chain, genesis_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataProof;
+    use crate::messaging::system::KeyedSig;
+    use crate::types::ChunkAddress;
+    use eyre::Result;
+    use secured_linked_list::SecuredLinkedList;
+    use xor_name::XorName;
+
+    fn sign(sk: &bls::SecretKey, address: &ChunkAddress) -> Result<KeyedSig> {
+        Ok(KeyedSig {
+            public_key: sk.public_key(),
+            signature: sk.sign(&bincode::serialize(address)?),
+        })
+    }
+
+    #[test]
+    fn verifies_back_to_genesis() -> Result<()> {
+        let genesis_sk = bls::SecretKey::random();
+        let section_sk = bls::SecretKey::random();
+        let mut proof_chain = SecuredLinkedList::new(genesis_sk.public_key());
+        let section_pk = section_sk.public_key();
+        let endorsement = genesis_sk.sign(&bincode::serialize(&section_pk)?);
+        proof_chain.insert(&genesis_sk.public_key(), section_pk, endorsement)?;
+
+        let address = ChunkAddress(XorName::random());
+        let proof = DataProof {
+            address,
+            sig: sign(&section_sk, &address)?,
+            proof_chain,
+        };
+        assert!(proof.verify(&genesis_sk.public_key()));
+
+        // Not from this network.
+        assert!(!proof.verify(&bls::SecretKey::random().public_key()));
+
+        // Not vouching for this chunk.
+        let other = DataProof {
+            address: ChunkAddress(XorName::random()),
+            ..proof.clone()
+        };
+        assert!(!other.verify(&genesis_sk.public_key()));
+
+        // Signed with a key the chain doesn't lead to.
+        let rogue = DataProof {
+            sig: sign(&bls::SecretKey::random(), &address)?,
+            ..proof
+        };
+        assert!(!rogue.verify(&genesis_sk.public_key()));
+
+        Ok(())
+    }
+}
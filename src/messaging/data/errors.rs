@@ -6,8 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::types::DataAddress;
 use crate::types::PublicKey;
+use crate::types::{ChunkAddress, DataAddress};
 use serde::{Deserialize, Serialize};
 use std::result;
 use thiserror::Error;
@@ -62,4 +62,38 @@ pub enum Error {
     /// Destination is either outdated or incorrect
     #[error("Destination is either outdated or wrong")]
     WrongDestination,
+    /// Chunk is bigger than the maximum size allowed
+    #[error("Chunk of {size} bytes exceeds the max allowed size of {max} bytes")]
+    ChunkTooLarge {
+        /// Size of the chunk
+        size: u64,
+        /// Max size allowed for a chunk
+        max: u64,
+    },
+    /// Register entry is bigger than the maximum size allowed
+    #[error("Register entry of {size} bytes exceeds the max allowed size of {max} bytes")]
+    EntryTooLarge {
+        /// Size of the entry
+        size: u64,
+        /// Max size allowed for an entry
+        max: u64,
+    },
+    /// Batch of Register edits has more operations than allowed
+    #[error("Batch of {ops} Register operations exceeds the max of {max} allowed")]
+    TooManyOps {
+        /// Number of operations in the batch
+        ops: u64,
+        /// Max number of operations allowed in a batch
+        max: u64,
+    },
+    /// Chunk content doesn't hash to the address it's claimed to be stored at, or held at
+    #[error("Chunk content does not match its address: {0:?}")]
+    ChunkAddressMismatch(ChunkAddress),
+    /// The node is short of resources and sheds load, the request can be sent again later
+    #[error("Node is under resource pressure and throttling requests, try again later")]
+    Throttled,
+    /// The message of the id correlated with the error came in corrupted, and was dropped. The
+    /// sender can send it again
+    #[error("Message was corrupted in transit")]
+    CorruptedMessage,
 }
@@ -8,29 +8,43 @@
 
 //! Data messages and their possible responses.
 
+mod catalog;
+mod chunk_holders;
 mod cmd;
 mod data_exchange;
+mod data_limits;
+mod data_proof;
 mod errors;
+mod network_time;
 mod query;
 mod register;
 
 pub use self::{
+    catalog::{CatalogPage, MAX_CATALOG_PAGE_SIZE},
+    chunk_holders::ChunkHolders,
     cmd::DataCmd,
     data_exchange::{
-        ChunkDataExchange, ChunkMetadata, DataExchange, HolderMetadata, RegisterDataExchange,
-        StorageLevel,
+        CatalogDataExchange, ChunkDataExchange, ChunkMetadata, DataExchange, HolderMetadata,
+        RegisterDataExchange, StorageLevel,
     },
+    data_limits::{
+        DataLimits, DEFAULT_MAX_BLOB_SIZE, DEFAULT_MAX_REGISTER_BATCH_OPS,
+        DEFAULT_MAX_REGISTER_ENTRY_SIZE,
+    },
+    data_proof::DataProof,
     errors::{Error, Result},
+    network_time::{NetworkTime, NETWORK_TIME_GRANULARITY},
     query::DataQuery,
     register::{RegisterCmd, RegisterRead, RegisterWrite},
 };
 
-use crate::messaging::{data::Error as ErrorMessage, MessageId};
+use crate::messaging::{data::Error as ErrorMessage, system::KeyedSig, MessageId};
 use crate::types::{
     register::{Entry, EntryHash, Permissions, Policy, Register},
-    Chunk, ChunkAddress, DataAddress, PublicKey,
+    Chunk, ChunkAddress, DataAddress, PaymentProof, PublicKey,
 };
 use bytes::Bytes;
+use secured_linked_list::SecuredLinkedList;
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeSet, convert::TryFrom};
 use xor_name::XorName;
@@ -46,6 +60,26 @@ pub fn operation_id(address: &ChunkAddress) -> Result<OperationId> {
         .map_err(|_| Error::NoOperationId)
 }
 
+// Verifies `sig` is over `signed`, and was made with a section key which `proof_chain` proves
+// was endorsed, directly or not, by `genesis_key`.
+fn verify_section_sig<T: Serialize>(
+    signed: &T,
+    sig: &KeyedSig,
+    proof_chain: &SecuredLinkedList,
+    genesis_key: &bls::PublicKey,
+) -> bool {
+    if proof_chain.root_key() != genesis_key
+        || !proof_chain.self_verify()
+        || !proof_chain.has_key(&sig.public_key)
+    {
+        return false;
+    }
+
+    bincode::serialize(signed)
+        .map(|bytes| sig.verify(&bytes))
+        .unwrap_or(false)
+}
+
 /// A message indicating that an error occurred as a node was handling a client's message.
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -130,6 +164,8 @@ pub enum QueryResponse {
     //
     /// Response to [`ChunkRead::Get`].
     GetChunk(Result<Chunk>),
+    /// Response to [`DataQuery::ChunkExists`].
+    ChunkExists((Result<bool>, OperationId)),
     //
     // ===== Register Data =====
     //
@@ -143,6 +179,29 @@ pub enum QueryResponse {
     GetRegisterPolicy((Result<Policy>, OperationId)),
     /// Response to [`RegisterRead::GetUserPermissions`].
     GetRegisterUserPermissions((Result<Permissions>, OperationId)),
+    //
+    // ===== Payment =====
+    //
+    /// Response to [`DataQuery::GetPaymentProof`].
+    GetPaymentProof((Result<Vec<PaymentProof>>, OperationId)),
+    /// Response to [`DataQuery::ListOwnedData`].
+    ListOwnedData((Result<CatalogPage>, OperationId)),
+    //
+    // ===== Section parameters =====
+    //
+    /// Response to [`DataQuery::GetReplicationFactor`].
+    GetReplicationFactor((Result<usize>, OperationId)),
+    /// Response to [`DataQuery::GetDataLimits`].
+    GetDataLimits((Result<DataLimits>, OperationId)),
+    //
+    // ===== Proofs =====
+    //
+    /// Response to [`DataQuery::GetDataProof`].
+    GetDataProof((Result<DataProof>, OperationId)),
+    /// Response to [`DataQuery::GetNetworkTime`].
+    GetNetworkTime((Result<NetworkTime>, OperationId)),
+    /// Response to [`DataQuery::GetChunkHolders`].
+    GetChunkHolders((Result<ChunkHolders>, OperationId)),
 }
 
 impl QueryResponse {
@@ -151,11 +210,19 @@ impl QueryResponse {
         use QueryResponse::*;
         match self {
             GetChunk(result) => result.is_ok(),
+            ChunkExists((result, _op_id)) => result.is_ok(),
             GetRegister((result, _op_id)) => result.is_ok(),
             GetRegisterOwner((result, _op_id)) => result.is_ok(),
             ReadRegister((result, _op_id)) => result.is_ok(),
             GetRegisterPolicy((result, _op_id)) => result.is_ok(),
             GetRegisterUserPermissions((result, _op_id)) => result.is_ok(),
+            GetPaymentProof((result, _op_id)) => result.is_ok(),
+            ListOwnedData((result, _op_id)) => result.is_ok(),
+            GetReplicationFactor((result, _op_id)) => result.is_ok(),
+            GetDataLimits((result, _op_id)) => result.is_ok(),
+            GetDataProof((result, _op_id)) => result.is_ok(),
+            GetNetworkTime((result, _op_id)) => result.is_ok(),
+            GetChunkHolders((result, _op_id)) => result.is_ok(),
         }
     }
 
@@ -168,6 +235,7 @@ impl QueryResponse {
                 Ok(_) => false,
                 Err(error) => matches!(*error, ErrorMessage::DataNotFound(_)),
             },
+            ChunkExists(_) => false,
             GetRegister((result, _op_id)) => match result {
                 Ok(_) => false,
                 Err(error) => matches!(*error, ErrorMessage::DataNotFound(_)),
@@ -188,6 +256,22 @@ impl QueryResponse {
                 Ok(_) => false,
                 Err(error) => matches!(*error, ErrorMessage::DataNotFound(_)),
             },
+            GetPaymentProof((result, _op_id)) => match result {
+                Ok(_) => false,
+                Err(error) => matches!(*error, ErrorMessage::DataNotFound(_)),
+            },
+            ListOwnedData(_) => false,
+            GetReplicationFactor(_) => false,
+            GetDataLimits(_) => false,
+            GetDataProof((result, _op_id)) => match result {
+                Ok(_) => false,
+                Err(error) => matches!(*error, ErrorMessage::DataNotFound(_)),
+            },
+            GetNetworkTime(_) => false,
+            GetChunkHolders((result, _op_id)) => match result {
+                Ok(_) => false,
+                Err(error) => matches!(*error, ErrorMessage::DataNotFound(_)),
+            },
         }
     }
 
@@ -215,11 +299,19 @@ impl QueryResponse {
                 },
             },
 
-            GetRegister((_, operation_id))
+            ChunkExists((_, operation_id))
+            | GetRegister((_, operation_id))
             | GetRegisterOwner((_, operation_id))
             | ReadRegister((_, operation_id))
             | GetRegisterPolicy((_, operation_id))
-            | GetRegisterUserPermissions((_, operation_id)) => Ok(operation_id.clone()),
+            | GetRegisterUserPermissions((_, operation_id))
+            | GetPaymentProof((_, operation_id))
+            | ListOwnedData((_, operation_id))
+            | GetReplicationFactor((_, operation_id))
+            | GetDataLimits((_, operation_id))
+            | GetDataProof((_, operation_id))
+            | GetNetworkTime((_, operation_id))
+            | GetChunkHolders((_, operation_id)) => Ok(operation_id.clone()),
         }
     }
 }
@@ -265,11 +357,19 @@ impl TryFrom<QueryResponse> for Chunk {
     }
 }
 
+try_from!(bool, ChunkExists);
 try_from!(Register, GetRegister);
 try_from!(PublicKey, GetRegisterOwner);
 try_from!(BTreeSet<(EntryHash, Entry)>, ReadRegister);
 try_from!(Policy, GetRegisterPolicy);
 try_from!(Permissions, GetRegisterUserPermissions);
+try_from!(Vec<PaymentProof>, GetPaymentProof);
+try_from!(CatalogPage, ListOwnedData);
+try_from!(usize, GetReplicationFactor);
+try_from!(DataLimits, GetDataLimits);
+try_from!(DataProof, GetDataProof);
+try_from!(NetworkTime, GetNetworkTime);
+try_from!(ChunkHolders, GetChunkHolders);
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,93 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::system::KeyedSig;
+use secured_linked_list::SecuredLinkedList;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Granularity of network timestamps. Elders only vouch for the time to this precision,
+/// so that a supermajority of their clocks agree on it.
+pub const NETWORK_TIME_GRANULARITY: Duration = Duration::from_secs(60);
+
+/// A coarse timestamp, the median of the clocks of a section's Elders, which a supermajority of
+/// them vouched for as per their own clocks, together with the chain of section keys leading from the network's genesis key to the
+/// key it was signed with.
+///
+/// Meant for TTLs, receipts and ordering hints, without trusting the local clock alone.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NetworkTime {
+    /// Seconds since the Unix epoch, a multiple of [`NETWORK_TIME_GRANULARITY`].
+    pub timestamp: u64,
+    /// Section signature over the timestamp.
+    pub sig: KeyedSig,
+    /// Section keys from the genesis key to the one `sig` was made with.
+    pub proof_chain: SecuredLinkedList,
+}
+
+impl NetworkTime {
+    /// Rounds `time` down to the granularity of network timestamps.
+    pub fn coarse_timestamp(time: SystemTime) -> u64 {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        secs - secs % NETWORK_TIME_GRANULARITY.as_secs()
+    }
+
+    /// The timestamp as a `SystemTime`.
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.timestamp)
+    }
+
+    /// Verifies the timestamp was signed by a section key which `proof_chain` proves
+    /// was endorsed, directly or not, by `genesis_key`.
+    pub fn verify(&self, genesis_key: &bls::PublicKey) -> bool {
+        super::verify_section_sig(&self.timestamp, &self.sig, &self.proof_chain, genesis_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NetworkTime, NETWORK_TIME_GRANULARITY};
+    use crate::messaging::system::KeyedSig;
+    use eyre::Result;
+    use secured_linked_list::SecuredLinkedList;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn coarse_and_verifiable() -> Result<()> {
+        let time = UNIX_EPOCH + Duration::from_secs(1_000_000_019);
+        let timestamp = NetworkTime::coarse_timestamp(time);
+        assert_eq!(timestamp % NETWORK_TIME_GRANULARITY.as_secs(), 0);
+        assert!(
+            time.duration_since(UNIX_EPOCH + Duration::from_secs(timestamp))?
+                < NETWORK_TIME_GRANULARITY
+        );
+
+        let genesis_sk = bls::SecretKey::random();
+        let network_time = NetworkTime {
+            timestamp,
+            sig: KeyedSig {
+                public_key: genesis_sk.public_key(),
+                signature: genesis_sk.sign(&bincode::serialize(&timestamp)?),
+            },
+            proof_chain: SecuredLinkedList::new(genesis_sk.public_key()),
+        };
+        assert!(network_time.verify(&genesis_sk.public_key()));
+        assert!(!network_time.verify(&bls::SecretKey::random().public_key()));
+
+        let tampered = NetworkTime {
+            timestamp: timestamp + NETWORK_TIME_GRANULARITY.as_secs(),
+            ..network_time
+        };
+        assert!(!tampered.verify(&genesis_sk.public_key()));
+
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{operation_id, register::RegisterRead, Error, OperationId, QueryResponse, Result};
-use crate::types::ChunkAddress;
+use crate::types::{ChunkAddress, DataAddress, PublicKey};
 use serde::{Deserialize, Serialize};
 use xor_name::XorName;
 
@@ -26,10 +26,68 @@ pub enum DataQuery {
     /// [`Chunk`]: crate::types::Chunk
     /// [`GetChunk`]: QueryResponse::GetChunk
     GetChunk(ChunkAddress),
+    /// Find out whether a [`Chunk`] is stored at the given address, without retrieving it.
+    ///
+    /// This should eventually lead to a [`ChunkExists`] response.
+    /// [`Chunk`]: crate::types::Chunk
+    /// [`ChunkExists`]: QueryResponse::ChunkExists
+    ChunkExists(ChunkAddress),
     /// [`Register`] read operation.
     ///
     /// [`Register`]: crate::types::register::Register
     Register(RegisterRead),
+    /// Retrieve the proofs of payment for storing the data at the given address.
+    ///
+    /// This should eventually lead to a [`GetPaymentProof`] response.
+    /// [`GetPaymentProof`]: QueryResponse::GetPaymentProof
+    GetPaymentProof(DataAddress),
+    /// List the data the given key owns, i.e. paid for, from the catalog kept by the section of
+    /// the key. Only the owner can list it.
+    ///
+    /// This should eventually lead to a [`ListOwnedData`] response.
+    /// [`ListOwnedData`]: QueryResponse::ListOwnedData
+    ListOwnedData {
+        /// The owner.
+        owner: PublicKey,
+        /// The address to list the data after, as returned with the previous page, or `None`
+        /// to list it from the start.
+        start_after: Option<DataAddress>,
+        /// Max number of addresses to list, up to [`MAX_CATALOG_PAGE_SIZE`].
+        ///
+        /// [`MAX_CATALOG_PAGE_SIZE`]: super::MAX_CATALOG_PAGE_SIZE
+        limit: u32,
+    },
+    /// Retrieve the number of copies of each chunk maintained by the section
+    /// the given name belongs to, which is what storing a chunk there costs.
+    ///
+    /// This should eventually lead to a [`GetReplicationFactor`] response.
+    /// [`GetReplicationFactor`]: QueryResponse::GetReplicationFactor
+    GetReplicationFactor(XorName),
+    /// Retrieve the limits on the size of the data accepted by the section
+    /// the given name belongs to.
+    ///
+    /// This should eventually lead to a [`GetDataLimits`] response.
+    /// [`GetDataLimits`]: QueryResponse::GetDataLimits
+    GetDataLimits(XorName),
+    /// Retrieve the section's signature over the address of a chunk, with the section keys
+    /// proving it back to the genesis key, so the chunk can be verified offline.
+    ///
+    /// This should eventually lead to a [`GetDataProof`] response.
+    /// [`GetDataProof`]: QueryResponse::GetDataProof
+    GetDataProof(ChunkAddress),
+    /// Retrieve a coarse timestamp signed by the section closest to the given name,
+    /// as per the clocks of a supermajority of its Elders.
+    ///
+    /// This should eventually lead to a [`GetNetworkTime`] response.
+    /// [`GetNetworkTime`]: QueryResponse::GetNetworkTime
+    GetNetworkTime(XorName),
+    /// Retrieve the Adults currently holding copies of a [`Chunk`], signed by the section
+    /// responsible for it.
+    ///
+    /// This should eventually lead to a [`GetChunkHolders`] response.
+    /// [`Chunk`]: crate::types::Chunk
+    /// [`GetChunkHolders`]: QueryResponse::GetChunkHolders
+    GetChunkHolders(ChunkAddress),
 }
 
 impl DataQuery {
@@ -39,7 +97,39 @@ impl DataQuery {
         use DataQuery::*;
         match self {
             GetChunk(_) => Ok(QueryResponse::GetChunk(Err(error))),
+            ChunkExists(_) => Ok(QueryResponse::ChunkExists((
+                Err(error),
+                self.operation_id()?,
+            ))),
             Register(q) => q.error(error),
+            GetPaymentProof(_) => Ok(QueryResponse::GetPaymentProof((
+                Err(error),
+                self.operation_id()?,
+            ))),
+            ListOwnedData { .. } => Ok(QueryResponse::ListOwnedData((
+                Err(error),
+                self.operation_id()?,
+            ))),
+            GetReplicationFactor(_) => Ok(QueryResponse::GetReplicationFactor((
+                Err(error),
+                self.operation_id()?,
+            ))),
+            GetDataLimits(_) => Ok(QueryResponse::GetDataLimits((
+                Err(error),
+                self.operation_id()?,
+            ))),
+            GetDataProof(_) => Ok(QueryResponse::GetDataProof((
+                Err(error),
+                self.operation_id()?,
+            ))),
+            GetNetworkTime(_) => Ok(QueryResponse::GetNetworkTime((
+                Err(error),
+                self.operation_id()?,
+            ))),
+            GetChunkHolders(_) => Ok(QueryResponse::GetChunkHolders((
+                Err(error),
+                self.operation_id()?,
+            ))),
         }
     }
 
@@ -48,7 +138,15 @@ impl DataQuery {
         use DataQuery::*;
         match self {
             GetChunk(address) => *address.name(),
+            ChunkExists(address) => *address.name(),
             Register(q) => q.dst_name(),
+            GetPaymentProof(address) => *address.name(),
+            ListOwnedData { owner, .. } => XorName::from(*owner),
+            GetReplicationFactor(name) => *name,
+            GetDataLimits(name) => *name,
+            GetDataProof(address) => *address.name(),
+            GetNetworkTime(name) => *name,
+            GetChunkHolders(address) => *address.name(),
         }
     }
 
@@ -59,7 +157,42 @@ impl DataQuery {
     pub fn operation_id(&self) -> Result<OperationId> {
         match self {
             DataQuery::GetChunk(address) => operation_id(address),
+            DataQuery::ChunkExists(address) => Ok(format!(
+                "ChunkExists-{:?}",
+                address
+                    .encode_to_zbase32()
+                    .map_err(|_| Error::NoOperationId)?
+            )),
             DataQuery::Register(read) => read.operation_id(),
+            DataQuery::GetPaymentProof(address) => Ok(format!(
+                "GetPaymentProof-{:?}",
+                address
+                    .encode_to_zbase32()
+                    .map_err(|_| Error::NoOperationId)?
+            )),
+            DataQuery::ListOwnedData {
+                owner,
+                start_after,
+                limit,
+            } => Ok(format!(
+                "ListOwnedData-{:?}-{:?}-{}",
+                owner, start_after, limit
+            )),
+            DataQuery::GetReplicationFactor(name) => Ok(format!("GetReplicationFactor-{:?}", name)),
+            DataQuery::GetDataLimits(name) => Ok(format!("GetDataLimits-{:?}", name)),
+            DataQuery::GetDataProof(address) => Ok(format!(
+                "GetDataProof-{:?}",
+                address
+                    .encode_to_zbase32()
+                    .map_err(|_| Error::NoOperationId)?
+            )),
+            DataQuery::GetNetworkTime(name) => Ok(format!("GetNetworkTime-{:?}", name)),
+            DataQuery::GetChunkHolders(address) => Ok(format!(
+                "GetChunkHolders-{:?}",
+                address
+                    .encode_to_zbase32()
+                    .map_err(|_| Error::NoOperationId)?
+            )),
         }
     }
 }
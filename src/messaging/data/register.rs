@@ -9,7 +9,7 @@
 use super::{CmdError, Error, QueryResponse, Result};
 use crate::messaging::data::OperationId;
 use crate::types::{
-    register::{Address, Entry, Register, RegisterOp, User},
+    register::{Address, Entry, OwnershipTransfer, Register, RegisterOp, User},
     PublicKey,
 };
 use serde::{Deserialize, Serialize};
@@ -75,11 +75,23 @@ pub enum RegisterWrite {
     New(Register),
     /// Edit a [`Register`].
     Edit(RegisterOp<Entry>),
+    /// Edit a [`Register`] with several operations at once, applied in order, all or none.
+    EditBatch {
+        /// Address of the Register, which all the operations target.
+        address: Address,
+        /// The operations.
+        ops: Vec<RegisterOp<Entry>>,
+    },
     /// Delete a private [`Register`].
     ///
     /// This operation will result in an error if applied to a public register. Only private
     /// registers can be deleted, and only by their current owner(s).
     Delete(Address),
+    /// Transfer the ownership of a [`Register`] to a new owner.
+    ///
+    /// Only the current owner can transfer the ownership, the replaced policy
+    /// is kept in the register's policy history.
+    TransferOwnership(OwnershipTransfer),
 }
 
 impl RegisterRead {
@@ -184,6 +196,8 @@ impl RegisterWrite {
             RegisterWrite::New(ref data) => *data.name(),
             RegisterWrite::Delete(ref address) => *address.name(),
             RegisterWrite::Edit(ref op) => *op.address.name(),
+            RegisterWrite::EditBatch { ref address, .. } => *address.name(),
+            RegisterWrite::TransferOwnership(ref transfer) => *transfer.address.name(),
         }
     }
 
@@ -193,6 +207,8 @@ impl RegisterWrite {
             Self::New(map) => map.address(),
             Self::Delete(address) => address,
             Self::Edit(ref op) => &op.address,
+            Self::EditBatch { ref address, .. } => address,
+            Self::TransferOwnership(ref transfer) => &transfer.address,
         }
     }
 
@@ -0,0 +1,167 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Error, Result};
+use crate::types::{Keypair, PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A capability a user grants an app, for the app to issue operations on the user's behalf,
+/// signing them with its own key rather than holding the user's.
+///
+/// Messages carrying a delegation are authorised as if sent by the user, once Elders have
+/// verified the user signed it for the key the message is signed with, and that it hasn't expired.
+/// Apps only allowed to read have their commands rejected.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Delegation {
+    /// Key of the user the app acts for.
+    pub user: PublicKey,
+    /// Key of the app the capability is granted to.
+    pub delegate: PublicKey,
+    /// Whether the app may write data, rather than only read it.
+    pub allows_writes: bool,
+    /// Seconds since the Unix epoch after which the capability is void.
+    pub expires: u64,
+    /// User's signature over the other fields.
+    pub signature: Signature,
+}
+
+impl Delegation {
+    /// Grant the holder of `delegate` the capability to act as the owner of `user_keypair`,
+    /// for `duration`.
+    pub fn new(
+        user_keypair: &Keypair,
+        delegate: PublicKey,
+        allows_writes: bool,
+        duration: Duration,
+    ) -> Result<Self> {
+        let user = user_keypair.public_key();
+        let expires = (SystemTime::now() + duration)
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or(0);
+        let bytes = Self::bytes_to_sign(&user, &delegate, allows_writes, expires)?;
+        Ok(Self {
+            user,
+            delegate,
+            allows_writes,
+            expires,
+            signature: user_keypair.sign(&bytes),
+        })
+    }
+
+    /// Verifies the user granted the capability to `delegate`.
+    ///
+    /// Expiry isn't checked, as messages are verified again when replicated, long after
+    /// they were sent. Elders check it upon receiving messages from clients instead.
+    pub fn verify(&self, delegate: &PublicKey) -> Result<()> {
+        if self.delegate != *delegate {
+            return Err(Error::InvalidDelegation(
+                "granted to another key".to_string(),
+            ));
+        }
+        let bytes =
+            Self::bytes_to_sign(&self.user, &self.delegate, self.allows_writes, self.expires)?;
+        self.user
+            .verify(&self.signature, bytes)
+            .map_err(|_| Error::InvalidDelegation("invalid signature".to_string()))
+    }
+
+    /// Whether the capability is past its expiry time.
+    pub fn is_expired(&self) -> bool {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs() > self.expires)
+            .unwrap_or(true)
+    }
+
+    fn bytes_to_sign(
+        user: &PublicKey,
+        delegate: &PublicKey,
+        allows_writes: bool,
+        expires: u64,
+    ) -> Result<Vec<u8>> {
+        bincode::serialize(&(user, delegate, allows_writes, expires))
+            .map_err(|err| Error::Serialisation(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Delegation;
+    use crate::messaging::{Error, ServiceAuth, VerifyAuthority};
+    use crate::types::Keypair;
+    use eyre::Result;
+    use rand::rngs::OsRng;
+    use std::time::Duration;
+
+    #[test]
+    fn verified_only_for_its_delegate() -> Result<()> {
+        let user = Keypair::new_ed25519(&mut OsRng);
+        let app = Keypair::new_ed25519(&mut OsRng);
+        let other = Keypair::new_ed25519(&mut OsRng);
+
+        let delegation =
+            Delegation::new(&user, app.public_key(), false, Duration::from_secs(3600))?;
+        delegation.verify(&app.public_key())?;
+        assert!(matches!(
+            delegation.verify(&other.public_key()),
+            Err(Error::InvalidDelegation(_))
+        ));
+
+        let escalated = Delegation {
+            allows_writes: true,
+            ..delegation
+        };
+        assert!(escalated.verify(&app.public_key()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn covered_by_the_message_signature() -> Result<()> {
+        let user = Keypair::new_ed25519(&mut OsRng);
+        let other_user = Keypair::new_ed25519(&mut OsRng);
+        let app = Keypair::new_ed25519(&mut OsRng);
+        let payload = b"payload";
+
+        let delegation = Delegation::new(&user, app.public_key(), true, Duration::from_secs(60))?;
+        let bytes = ServiceAuth::bytes_to_sign(payload, Some(&delegation))?;
+        let auth = ServiceAuth {
+            public_key: app.public_key(),
+            signature: app.sign(&bytes),
+            delegation: Some(delegation),
+        };
+        let auth = auth.verify_authority(payload)?;
+
+        // Another valid delegation to the same app can't be swapped in, nor the one dropped.
+        let swapped = ServiceAuth {
+            delegation: Some(Delegation::new(
+                &other_user,
+                app.public_key(),
+                true,
+                Duration::from_secs(60),
+            )?),
+            ..auth.clone()
+        };
+        assert!(matches!(
+            swapped.verify_authority(payload),
+            Err(Error::InvalidSignature)
+        ));
+        let dropped = ServiceAuth {
+            delegation: None,
+            ..auth
+        };
+        assert!(matches!(
+            dropped.verify_authority(payload),
+            Err(Error::InvalidSignature)
+        ));
+
+        Ok(())
+    }
+}
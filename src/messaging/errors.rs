@@ -6,6 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use super::MessageId;
+use crate::types::KeyAlgorithm;
 use std::result;
 use thiserror::Error;
 
@@ -39,4 +41,18 @@ pub enum Error {
     /// Message read was built with an unsupported version.
     #[error("Unsupported messaging protocol version: {0}")]
     UnsupportedVersion(u16),
+
+    /// Message read was signed with a signature scheme we don't support (yet).
+    #[error("Unsupported signature scheme: {0}")]
+    UnsupportedKeyAlgorithm(KeyAlgorithm),
+
+    /// Message read was signed by an app acting for a user, with a capability which
+    /// doesn't hold.
+    #[error("Invalid delegation: {0}")]
+    InvalidDelegation(String),
+
+    /// Message read has a payload not matching the checksum in its header, as it was
+    /// corrupted in transit.
+    #[error("Payload of message {0:?} doesn't match its checksum")]
+    CorruptedPayload(MessageId),
 }
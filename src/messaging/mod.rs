@@ -29,6 +29,8 @@ pub mod system;
 
 // Message authority - keys and signatures.
 mod authority;
+// Capabilities apps are granted to act for users
+mod delegation;
 // Error types definitions
 mod errors;
 // Source and destination structs for messages
@@ -39,15 +41,20 @@ mod msg_id;
 mod msg_kind;
 // SectionAuthorityProvider
 mod sap;
+// Per-hop path recording of messages
+mod trace;
 
 pub use self::{
     authority::{
         AuthorityProof, BlsShareAuth, NodeAuth, SectionAuth, ServiceAuth, VerifyAuthority,
+        SUPPORTED_KEY_ALGORITHMS,
     },
+    delegation::Delegation,
     errors::{Error, Result},
     location::{DstLocation, EndUser, SrcLocation},
     msg_id::{MessageId, MESSAGE_ID_LEN},
     msg_kind::MsgKind,
-    sap::SectionAuthorityProvider,
+    sap::{SectionAuthorityProvider, ELDER_SIZE},
     serialisation::{MessageType, NodeMsgAuthority, WireMsg},
+    trace::{Hop, MsgTrace},
 };
@@ -17,6 +17,9 @@ use std::{
 };
 use xor_name::{Prefix, XorName};
 
+/// Number of elders per section.
+pub const ELDER_SIZE: usize = 7;
+
 /// Details of section authority.
 ///
 /// A new `SectionAuthorityProvider` is created whenever the elders change, due to an elder being
@@ -10,14 +10,15 @@ use super::wire_msg_header::WireMsgHeader;
 use crate::messaging::{
     data::{ServiceError, ServiceMsg},
     system::SystemMsg,
-    AuthorityProof, DstLocation, Error, MessageId, MessageType, MsgKind, NodeMsgAuthority, Result,
-    ServiceAuth,
+    AuthorityProof, DstLocation, Error, Hop, MessageId, MessageType, MsgKind, MsgTrace,
+    NodeMsgAuthority, Result, ServiceAuth,
 };
+use crate::types::{BufferPool, PooledBytes, MIN_POOLED_SIZE};
 use bls::PublicKey as BlsPublicKey;
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use custom_debug::Debug;
 use serde::Serialize;
-use std::io::Write;
+use std::{io::Write, mem::size_of};
 use xor_name::XorName;
 
 /// In order to send a message over the wire, it needs to be serialized
@@ -50,24 +51,33 @@ impl WireMsg {
     }
 
     /// Creates a new `WireMsg` with the provided serialized payload and `MsgKind`.
+    /// A checksum of the payload is set in the header, for the recipient to tell whether it
+    /// was corrupted in transit.
     pub fn new_msg(
         msg_id: MessageId,
         payload: Bytes,
         msg_kind: MsgKind,
         dst_location: DstLocation,
     ) -> Result<Self> {
-        Ok(Self {
-            header: WireMsgHeader::new(msg_id, msg_kind, dst_location),
-            payload,
-        })
+        let mut header = WireMsgHeader::new(msg_id, msg_kind, dst_location);
+        header.msg_envelope.checksum = Some(crc32fast::hash(&payload));
+        Ok(Self { header, payload })
     }
 
     /// Attempts to create an instance of WireMsg by deserialising the bytes provided.
-    /// To succeed, the bytes should contain at least a valid WireMsgHeader.
+    /// To succeed, the bytes should contain at least a valid WireMsgHeader, and a payload
+    /// matching the checksum in it, if any.
     pub fn from(bytes: Bytes) -> Result<Self> {
         // Deserialize the header bytes first
         let (header, payload) = WireMsgHeader::from(bytes)?;
 
+        // Corrupted payloads are rejected before any attempt to deserialise them
+        if let Some(checksum) = header.msg_envelope.checksum {
+            if crc32fast::hash(&payload) != checksum {
+                return Err(Error::CorruptedPayload(header.msg_envelope.msg_id));
+            }
+        }
+
         // We can now create a deserialized WireMsg using the read bytes
         Ok(Self { header, payload })
     }
@@ -75,13 +85,40 @@ impl WireMsg {
     /// Return the serialized WireMsg, which contains the WireMsgHeader bytes,
     /// followed by the payload bytes, i.e. the serialized Message.
     pub fn serialize(&self) -> Result<Bytes> {
+        let mut buffer = BytesMut::new();
+        self.write_into(&mut buffer)?;
+        Ok(buffer.freeze())
+    }
+
+    /// Serializes the WireMsg as per [`WireMsg::serialize`], into a buffer from `pool` if it's
+    /// carrying a chunk sized payload, waiting for one to be free if they're all in use.
+    ///
+    /// The buffer is returned to the pool once the bytes returned are dropped, so the memory
+    /// taken up by large messages in flight stays within the pool's cap.
+    pub async fn serialize_pooled(&self, pool: &BufferPool) -> Result<PooledBytes> {
+        if self.payload.len() < MIN_POOLED_SIZE {
+            return self.serialize().map(PooledBytes::from);
+        }
+        let mut buffer = pool.acquire().await;
+        self.write_into(&mut buffer)?;
+        Ok(buffer.freeze())
+    }
+
+    // Writes the serialized WireMsg to `buffer`, reusing its memory if it's large enough.
+    fn write_into(&self, buffer: &mut BytesMut) -> Result<()> {
         // First we create a buffer with the capacity
         // needed to serialize the wire msg
-        // FIXME: don't multiplying the max size by a factor of 10 and calculate the correct size.
-        let max_length = 10 * (WireMsgHeader::max_size() as usize + self.payload.len());
-        let mut buffer = vec![0u8; max_length];
-
-        let (mut buf_at_payload, bytes_written) = self.header.write(&mut buffer)?;
+        // FIXME: don't multiplying the max size of the header by a factor of 10 and calculate
+        // the correct size.
+        let max_length = 10 * WireMsgHeader::max_size() as usize
+            + self.payload.len()
+            + self
+                .trace()
+                .map_or(0, |trace| trace.hops.len() * size_of::<Hop>());
+        buffer.clear();
+        buffer.resize(max_length, 0);
+
+        let (mut buf_at_payload, bytes_written) = self.header.write(&mut buffer[..])?;
 
         // ...and finally we write the bytes of the serialized payload to the original buffer
         buf_at_payload.write_all(&self.payload).map_err(|err| {
@@ -94,7 +131,7 @@ impl WireMsg {
 
         // We can now return the buffer containing the written bytes
         buffer.truncate(bytes_written as usize + self.payload.len());
-        Ok(Bytes::from(buffer))
+        Ok(())
     }
 
     /// Deserialize the payload from this WireMsg returning a MessageType instance.
@@ -211,6 +248,34 @@ impl WireMsg {
         &self.header.msg_envelope.dst_location
     }
 
+    /// Opt this message into tracing, so the nodes it goes through record themselves in it.
+    pub fn enable_trace(&mut self) {
+        let msg_id = self.msg_id();
+        let _ = self
+            .header
+            .msg_envelope
+            .trace
+            .get_or_insert_with(|| MsgTrace::new(msg_id));
+    }
+
+    /// Return the path this message took so far, if it's traced.
+    pub fn trace(&self) -> Option<&MsgTrace> {
+        self.header.msg_envelope.trace.as_ref()
+    }
+
+    /// Set the path to carry on with this message, e.g. that of the message it responds to.
+    pub fn set_trace(&mut self, trace: MsgTrace) {
+        self.header.msg_envelope.trace = Some(trace);
+    }
+
+    /// Record this message went through the node with the given keypair, if it's traced.
+    pub fn add_hop(&mut self, keypair: &ed25519_dalek::Keypair) -> Result<()> {
+        match &mut self.header.msg_envelope.trace {
+            Some(trace) => trace.add_hop(keypair),
+            None => Ok(()),
+        }
+    }
+
     /// Return the source section PublicKey for this
     /// message if it's a NodeMsg
     pub fn src_section_pk(&self) -> Option<BlsPublicKey> {
@@ -322,6 +387,7 @@ mod tests {
         let auth = ServiceAuth {
             public_key: src_client_keypair.public_key(),
             signature: src_client_keypair.sign(&payload),
+            delegation: None,
         };
         let auth_proof = AuthorityProof::verify(auth.clone(), &payload).unwrap();
 
@@ -351,4 +417,41 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn corrupted_payloads_are_rejected() -> Result<()> {
+        let mut rng = OsRng;
+        let keypair = Keypair::new_ed25519(&mut rng);
+        let dst_location = DstLocation::Section {
+            name: XorName::random(),
+            section_pk: SecretKey::random().public_key(),
+        };
+        let msg_id = MessageId::new();
+
+        let msg = ServiceMsg::Query(DataQuery::GetChunk(ChunkAddress(XorName::random())));
+        let payload = WireMsg::serialize_msg_payload(&msg)?;
+        let auth = ServiceAuth {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(&payload),
+            delegation: None,
+        };
+        let wire_msg = WireMsg::new_msg(msg_id, payload, MsgKind::ServiceMsg(auth), dst_location)?;
+        let serialized = wire_msg.serialize()?;
+
+        let mut corrupted = serialized.to_vec();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0x01;
+        assert!(matches!(
+            WireMsg::from(Bytes::from(corrupted)),
+            Err(Error::CorruptedPayload(id)) if id == msg_id
+        ));
+
+        // Messages without a checksum, from peers not setting it, aren't checked.
+        let mut unchecked = wire_msg;
+        unchecked.header.msg_envelope.checksum = None;
+        let deserialized = WireMsg::from(unchecked.serialize()?)?;
+        assert_eq!(deserialized, unchecked);
+
+        Ok(())
+    }
 }
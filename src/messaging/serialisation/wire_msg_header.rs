@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::messaging::{DstLocation, Error, MessageId, MsgKind, Result};
+use crate::messaging::{DstLocation, Error, MessageId, MsgKind, MsgTrace, Result};
 use bincode::{
     config::{BigEndian, FixintEncoding, WithOtherEndian, WithOtherIntEncoding},
     Options,
@@ -40,6 +40,13 @@ pub struct MsgEnvelope {
     pub msg_id: MessageId,
     pub msg_kind: MsgKind,
     pub dst_location: DstLocation,
+    // Only present on messages whose sender opted into tracing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<MsgTrace>,
+    // CRC32 of the payload, for corruption in transit to be caught before the payload is
+    // deserialised. Absent from messages of peers not setting it yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<u32>,
 }
 
 // The first two fields in the header. This is not part of the public interface.
@@ -81,6 +88,8 @@ impl WireMsgHeader {
                 msg_id,
                 msg_kind,
                 dst_location,
+                trace: None,
+                checksum: None,
             },
         }
     }
@@ -8,6 +8,7 @@
 
 use super::{section::NodeState, signed::KeyedSig};
 use crate::messaging::{MessageId, SectionAuthorityProvider};
+use crate::types::ChunkAddress;
 use bls::PublicKey as BlsPublicKey;
 use ed25519_dalek::{PublicKey, Signature};
 use hex_fmt::HexFmt;
@@ -26,6 +27,9 @@ pub struct DkgKey {
     pub hash: Digest256,
     /// The generation, as in the length of the section chain main branch.
     pub generation: u64,
+    /// How many times the session was restarted, with the same peers and generation,
+    /// because its outcome was corrupted. Restarts supersede the sessions they replace.
+    pub attempt: u32,
 }
 
 impl DkgKey {
@@ -108,4 +112,20 @@ pub enum Proposal {
     OurElders(SectionAuth<SectionAuthorityProvider>),
     /// Proposal to change whether new nodes are allowed to join our section.
     JoinsAllowed((MessageId, bool)),
+    /// Proposal to vouch for the chunk at the given address, so the section's signature over it
+    /// can be handed out as a [`DataProof`](crate::messaging::data::DataProof).
+    DataProof(ChunkAddress),
+    /// Proposal to vouch for a coarse timestamp, in seconds since the Unix epoch, so the
+    /// section's signature over it can be handed out as a
+    /// [`NetworkTime`](crate::messaging::data::NetworkTime).
+    NetworkTime(u64),
+    /// Proposal to vouch for the Adults holding the chunk at the given address, so the section's
+    /// signature over them can be handed out as
+    /// [`ChunkHolders`](crate::messaging::data::ChunkHolders).
+    ChunkHolders {
+        /// Address of the chunk.
+        address: ChunkAddress,
+        /// Names of the Adults holding it.
+        holders: BTreeSet<XorName>,
+    },
 }
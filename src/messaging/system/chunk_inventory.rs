@@ -0,0 +1,210 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    convert::TryInto,
+    fmt::{self, Debug, Formatter},
+};
+use xor_name::{XorName, XOR_NAME_LEN};
+
+// Number of cells each name is added to.
+const HASH_COUNT: usize = 3;
+
+/// A compact digest of a set of chunk names, exchanged by Adults with Elders so the chunks one
+/// Adult holds and another misses can be found out about without listing either set in full.
+///
+/// This is an invertible Bloom lookup table: subtracting the inventory of one Adult from that of
+/// another cancels out the names both hold, and the few left over can be listed back, provided
+/// there are fewer of them than about two thirds of the number of cells.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChunkInventory {
+    cells: Vec<Cell>,
+}
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+struct Cell {
+    // Names added, less names subtracted.
+    count: i32,
+    // XOR of the names.
+    name_sum: XorName,
+    // XOR of the check hashes of the names, telling a cell holding a single name apart.
+    hash_sum: u64,
+}
+
+impl ChunkInventory {
+    /// Creates an empty inventory, of at least the given number of cells.
+    pub fn new(cells: usize) -> Self {
+        // Each name goes into one cell of each of `HASH_COUNT` equal subtables.
+        let subtable_len = ((cells + HASH_COUNT - 1) / HASH_COUNT).max(1);
+        Self {
+            cells: vec![Cell::default(); subtable_len * HASH_COUNT],
+        }
+    }
+
+    /// Number of cells of the inventory.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Whether the inventory has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Adds a chunk name to the inventory.
+    pub fn insert(&mut self, name: &XorName) {
+        self.toggle(name, 1)
+    }
+
+    /// Subtracts another inventory, of the same size, from this one, leaving the names only this
+    /// one has with a positive count and those only the other has with a negative one.
+    ///
+    /// Returns `None` if the inventories are of different sizes.
+    pub fn subtract(&self, other: &Self) -> Option<Self> {
+        if self.cells.len() != other.cells.len() {
+            return None;
+        }
+        let cells = self
+            .cells
+            .iter()
+            .zip(&other.cells)
+            .map(|(ours, theirs)| Cell {
+                count: ours.count - theirs.count,
+                name_sum: xor(&ours.name_sum, &theirs.name_sum),
+                hash_sum: ours.hash_sum ^ theirs.hash_sum,
+            })
+            .collect();
+        Some(Self { cells })
+    }
+
+    /// Lists the names left in an inventory resulting from a subtraction: those only the
+    /// inventory subtracted from had, and those only the one subtracted had.
+    ///
+    /// Returns `None` if there are too many of them to be listed back with this many cells.
+    pub fn decode(mut self) -> Option<(BTreeSet<XorName>, BTreeSet<XorName>)> {
+        let mut ours = BTreeSet::new();
+        let mut theirs = BTreeSet::new();
+
+        loop {
+            let pure = self.cells.iter().find(|cell| {
+                (cell.count == 1 || cell.count == -1) && check_hash(&cell.name_sum) == cell.hash_sum
+            });
+            let (name, count) = match pure {
+                Some(cell) => (cell.name_sum, cell.count),
+                None => break,
+            };
+            if count == 1 {
+                let _ = ours.insert(name);
+            } else {
+                let _ = theirs.insert(name);
+            }
+            self.toggle(&name, -count);
+        }
+
+        if self.cells.iter().all(|cell| *cell == Cell::default()) {
+            Some((ours, theirs))
+        } else {
+            None
+        }
+    }
+
+    fn toggle(&mut self, name: &XorName, count: i32) {
+        let hash = check_hash(name);
+        let subtable_len = self.cells.len() / HASH_COUNT;
+        if subtable_len == 0 {
+            return;
+        }
+        for subtable in 0..HASH_COUNT {
+            let index = subtable * subtable_len + position(name, subtable) % subtable_len;
+            let cell = &mut self.cells[index];
+            cell.count += count;
+            cell.name_sum = xor(&cell.name_sum, name);
+            cell.hash_sum ^= hash;
+        }
+    }
+}
+
+impl Debug for ChunkInventory {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "ChunkInventory({} cells)", self.cells.len())
+    }
+}
+
+// Chunk names are hashes already, so the positions of a name are read off its bytes.
+fn position(name: &XorName, subtable: usize) -> usize {
+    let offset = subtable * 8;
+    let bytes: [u8; 8] = name.0[offset..offset + 8].try_into().unwrap_or_default();
+    u64::from_le_bytes(bytes) as usize
+}
+
+// While the check hash has to be independent of them.
+fn check_hash(name: &XorName) -> u64 {
+    let hash = XorName::from_content(&name.0);
+    let bytes: [u8; 8] = hash.0[..8].try_into().unwrap_or_default();
+    u64::from_le_bytes(bytes)
+}
+
+fn xor(lhs: &XorName, rhs: &XorName) -> XorName {
+    let mut name = [0; XOR_NAME_LEN];
+    for (byte, (lhs, rhs)) in name.iter_mut().zip(lhs.0.iter().zip(&rhs.0)) {
+        *byte = lhs ^ rhs;
+    }
+    XorName(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkInventory;
+    use eyre::{eyre, Result};
+    use std::collections::BTreeSet;
+    use xor_name::XorName;
+
+    #[test]
+    fn differences_are_listed_back() -> Result<()> {
+        let shared: Vec<_> = (0..1000).map(|_| XorName::random()).collect();
+        let only_ours: BTreeSet<_> = (0..20).map(|_| XorName::random()).collect();
+        let only_theirs: BTreeSet<_> = (0..20).map(|_| XorName::random()).collect();
+
+        let mut ours = ChunkInventory::new(120);
+        let mut theirs = ChunkInventory::new(120);
+        for name in &shared {
+            ours.insert(name);
+            theirs.insert(name);
+        }
+        only_ours.iter().for_each(|name| ours.insert(name));
+        only_theirs.iter().for_each(|name| theirs.insert(name));
+
+        let (missing_theirs, missing_ours) = ours
+            .subtract(&theirs)
+            .ok_or_else(|| eyre!("inventories of different sizes"))?
+            .decode()
+            .ok_or_else(|| eyre!("failed to decode inventory"))?;
+        assert_eq!(missing_theirs, only_ours);
+        assert_eq!(missing_ours, only_theirs);
+
+        Ok(())
+    }
+
+    #[test]
+    fn too_many_differences_are_reported() -> Result<()> {
+        let mut ours = ChunkInventory::new(30);
+        (0..200).for_each(|_| ours.insert(&XorName::random()));
+
+        let empty = ChunkInventory::new(30);
+        assert!(ours
+            .subtract(&empty)
+            .ok_or_else(|| eyre!("inventories of different sizes"))?
+            .decode()
+            .is_none());
+        assert!(ours.subtract(&ChunkInventory::new(60)).is_none());
+
+        Ok(())
+    }
+}
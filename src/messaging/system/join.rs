@@ -12,7 +12,11 @@ use bls::PublicKey as BlsPublicKey;
 use ed25519_dalek::Signature;
 use secured_linked_list::SecuredLinkedList;
 use serde::{Deserialize, Serialize};
-use std::{collections::VecDeque, net::SocketAddr};
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 /// Request to join a section
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -21,6 +25,39 @@ pub struct JoinRequest {
     pub section_key: BlsPublicKey,
     /// Proof of the resouce proofing.
     pub resource_proof_response: Option<ResourceProofResponse>,
+    /// Seconds since the Unix epoch as per the joining node's clock, for Elders
+    /// to turn away nodes whose clock is too far off.
+    #[serde(default)]
+    pub timestamp: u64,
+    /// Storage capacity the joining node commits to, in bytes, which Elders spot-check once it
+    /// joined.
+    #[serde(default)]
+    pub committed_capacity: Option<u64>,
+}
+
+impl JoinRequest {
+    /// Request to join the section of `section_key`, timestamped with our clock.
+    pub fn new(
+        section_key: BlsPublicKey,
+        resource_proof_response: Option<ResourceProofResponse>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or(0);
+        Self {
+            section_key,
+            resource_proof_response,
+            timestamp,
+            committed_capacity: None,
+        }
+    }
+
+    /// Advertises the storage capacity the joining node commits to, in bytes.
+    pub fn with_committed_capacity(mut self, capacity: u64) -> Self {
+        self.committed_capacity = Some(capacity);
+        self
+    }
 }
 
 /// Joining peer's proof of resolvement of given resource proofing challenge.
@@ -83,4 +120,11 @@ pub enum JoinRejectionReason {
     JoinsDisallowed,
     /// The requesting node is not externally reachable
     NodeNotReachable(SocketAddr),
+    /// The requesting node's clock is too far off the Elders'
+    ClockSkew {
+        /// Seconds since the Unix epoch as per the Elder's clock
+        elder_timestamp: u64,
+    },
+    /// A node with the same name is already a member of the section, at another address
+    DuplicateIdentity(SocketAddr),
 }
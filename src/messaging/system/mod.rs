@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod agreement;
+mod chunk_inventory;
 mod join;
 mod join_as_relocated;
 mod node_msgs;
@@ -19,15 +20,16 @@ pub use agreement::{DkgFailureSig, DkgFailureSigSet, DkgKey, Proposal, SectionAu
 use bls::PublicKey as BlsPublicKey;
 use bls_dkg::key_gen::message::Message as DkgMessage;
 use bytes::Bytes;
+pub use chunk_inventory::ChunkInventory;
 pub use join::{JoinRejectionReason, JoinRequest, JoinResponse, ResourceProofResponse};
 pub use join_as_relocated::{JoinAsRelocatedRequest, JoinAsRelocatedResponse};
-pub use node_msgs::{NodeCmd, NodeQuery, NodeQueryResponse};
+pub use node_msgs::{NodeCmd, NodeQuery, NodeQueryResponse, ReplicationTarget};
 pub use relocation::{RelocateDetails, RelocatePayload, RelocatePromise};
 pub use section::ElderCandidates;
 pub use section::MembershipState;
 pub use section::NodeState;
 pub use section::Peer;
-pub use section::{Section, SectionPeers};
+pub use section::{Section, SectionPeers, SectionPeersUpdate};
 use secured_linked_list::SecuredLinkedList;
 use serde::{Deserialize, Serialize};
 pub use signed::{KeyedSig, SigShare};
@@ -76,7 +78,14 @@ pub enum SystemMsg {
         /// Our section chain truncated from the triggering msg's dst section_key (or genesis key for full proof)
         proof_chain: SecuredLinkedList,
         /// Optional section members if we're updating our own section adults
-        members: Option<SectionPeers>,
+        members: Option<SectionPeersUpdate>,
+    },
+    /// Sent to an elder by a member of its section which couldn't apply a delta of the section
+    /// members received in an AE-Update, since it didn't know the members it was based on.
+    /// The elder then responds with an AE-Update carrying all the members.
+    AntiEntropyMembersResync {
+        /// Digest of the set of members the sender currently knows.
+        known_members: XorName,
     },
     /// Send from a section to the node to be immediately relocated.
     Relocate(RelocateDetails),
@@ -125,7 +134,12 @@ pub enum SystemMsg {
     },
     /// Sent to the current elders by the DKG participants when at least majority of them observe
     /// a DKG failure.
-    DkgFailureAgreement(DkgFailureSigSet),
+    DkgFailureAgreement {
+        /// The identifier of the DKG session which failed.
+        dkg_key: DkgKey,
+        /// Signatures over the failure
+        failure_set: DkgFailureSigSet,
+    },
     /// Message containing a single `Proposal` to be aggregated in the proposal aggregator.
     Propose {
         /// The content of the proposal
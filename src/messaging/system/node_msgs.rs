@@ -6,13 +6,15 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use super::ChunkInventory;
 use crate::messaging::{
-    data::{DataCmd, DataExchange, DataQuery, Result, StorageLevel},
+    data::{DataCmd, DataExchange, DataQuery, RegisterDataExchange, Result, StorageLevel},
     EndUser, ServiceAuth,
 };
 use crate::types::{Chunk, ChunkAddress, PublicKey};
 use serde::{Deserialize, Serialize};
-use xor_name::XorName;
+use std::collections::BTreeSet;
+use xor_name::{Prefix, XorName};
 
 /// Command message sent among nodes
 #[allow(clippy::large_enum_variant)]
@@ -49,6 +51,110 @@ pub enum NodeCmd {
     ReplicateChunk(Chunk),
     /// Tells the Elders to re-publish a chunk in the data section
     RepublishChunk(Chunk),
+    /// Asks an Adult for an inventory of the chunks it holds which the partner Adult should hold
+    /// as well, sent to both Adults of the pair at once
+    ReportChunkInventory {
+        /// The other Adult of the pair
+        partner: XorName,
+        /// The Adults chunk holders are picked among, so both Adults of the pair agree on them
+        adults: BTreeSet<XorName>,
+        /// Number of cells of the inventory
+        cells: u32,
+    },
+    /// An Adult's inventory of its chunks, reported back to the Elder which asked for it
+    ChunkInventoryReport {
+        /// The inventory
+        inventory: ChunkInventory,
+        /// The other Adult of the pair
+        partner: XorName,
+        /// Number of chunks held by the Adult which it isn't a holder of anymore
+        excess: u64,
+    },
+    /// Replicate the given chunks held by an Adult at another Adult missing them
+    ReplicateChunks {
+        /// The chunk names
+        names: BTreeSet<XorName>,
+        /// The Adult missing them
+        to: XorName,
+    },
+    /// Registers handed off to the Elders of its section by an Elder demoted to Adult, which
+    /// drops its copies once they're verified held by all the Elders
+    HandOffRegisters(RegisterDataExchange),
+    /// Asks a node which of the given data it holds, after it was handed off to it by a node
+    /// whose role changed
+    CheckHandoff {
+        /// Names of the chunks
+        chunks: BTreeSet<XorName>,
+        /// Names of the registers
+        registers: BTreeSet<XorName>,
+    },
+    /// The data of a `CheckHandoff` the node holds, reported back to the node which asked
+    HandoffChecked {
+        /// Names of the chunks held
+        chunks: BTreeSet<XorName>,
+        /// Names of the registers held
+        registers: BTreeSet<XorName>,
+    },
+    /// Asks an Adult which of the given chunks it holds, for an operator to verify they are
+    /// replicated at all their holders
+    CheckReplication(ReplicationTarget),
+    /// The chunks of a `CheckReplication` the Adult holds, reported back to the Elder which asked
+    ReplicationChecked {
+        /// Names of the chunks held
+        held: BTreeSet<XorName>,
+    },
+    /// Asks an Adult for the storage capacity it commits to, for an Elder which didn't get it
+    /// from its join request to spot-check it
+    ReportCapacity,
+    /// The storage capacity an Adult commits to, in bytes, reported back to the Elder which
+    /// asked for it
+    CapacityReported {
+        /// The capacity
+        capacity: u64,
+    },
+    /// Challenges an Adult to store pseudo-random filler data derived from a seed, for the Elder
+    /// to spot-check the storage capacity the Adult advertised on joining
+    StoreCapacityFiller {
+        /// Identifies the challenge
+        id: XorName,
+        /// The filler is derived from
+        seed: XorName,
+        /// Size of the filler, in bytes
+        size: u64,
+    },
+    /// Whether the Adult stored the filler of a `StoreCapacityFiller`, reported back to the Elder
+    /// which challenged it
+    CapacityFillerStored {
+        /// Identifies the challenge
+        id: XorName,
+        /// Whether the filler is being stored, or was refused for lack of space
+        stored: bool,
+    },
+    /// Challenges an Adult to read back samples of the filler it stored, at the given offsets,
+    /// hashed with the given salt so it can't be answered from earlier reads. The filler is
+    /// dropped once read.
+    ReadCapacityFiller {
+        /// Identifies the challenge
+        id: XorName,
+        /// Hashed ahead of the samples
+        salt: XorName,
+        /// Offsets of the samples in the filler
+        offsets: Vec<u64>,
+    },
+    /// The hash of the salt and filler samples of a `ReadCapacityFiller`, reported back to the
+    /// Elder which challenged the Adult, or `None` if the filler couldn't be read
+    CapacityFillerRead {
+        /// Identifies the challenge
+        id: XorName,
+        /// The hash
+        proof: Option<XorName>,
+    },
+    /// An Elder's clock reading, in seconds since the Unix epoch, shared with the other Elders
+    /// of its section for them to propose the median of their readings as the network time
+    ClockReading {
+        /// The reading
+        time: u64,
+    },
     /// Sent to all promoted nodes (also sibling if any) after
     /// a completed transition to a new constellation.
     ReceiveExistingData {
@@ -57,6 +163,25 @@ pub enum NodeCmd {
     },
 }
 
+/// Chunks whose replication is verified, e.g. after an Adult's disk was found faulty.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub enum ReplicationTarget {
+    /// The chunks whose names are within the prefix, as held by the Adults.
+    Range(Prefix),
+    /// The given chunks.
+    Chunks(BTreeSet<XorName>),
+}
+
+impl ReplicationTarget {
+    /// Whether the chunk of the given name is targeted.
+    pub fn contains(&self, name: &XorName) -> bool {
+        match self {
+            Self::Range(prefix) => prefix.matches(name),
+            Self::Chunks(names) => names.contains(name),
+        }
+    }
+}
+
 /// Query originating at a node
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub enum NodeQuery {
@@ -76,6 +201,13 @@ pub enum NodeQuery {
         /// The user that has initiated this query
         origin: EndUser,
     },
+    /// Whether chunks are stored is answered by Adults too
+    ChunkExists {
+        /// The chunk address
+        address: ChunkAddress,
+        /// The user that has initiated this query
+        origin: EndUser,
+    },
 }
 
 ///
@@ -84,4 +216,11 @@ pub enum NodeQuery {
 pub enum NodeQueryResponse {
     /// Elder to Adult Get.
     GetChunk(Result<Chunk>),
+    /// Whether the Adult holds the chunk.
+    ChunkExists {
+        /// The chunk address
+        address: ChunkAddress,
+        /// Whether it's held
+        exists: bool,
+    },
 }
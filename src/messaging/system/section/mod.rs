@@ -58,6 +58,24 @@ impl Hash for SectionPeers {
     }
 }
 
+/// Members of a section, as sent along Anti-Entropy updates.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SectionPeersUpdate {
+    /// All the members of the section.
+    Full(SectionPeers),
+    /// Only the members which changed since the set of members with the given digest,
+    /// which the recipient is expected to know already.
+    Delta {
+        /// Digest of the set of members the changes apply on top of.
+        base: XorName,
+        /// Members which joined, left or otherwise changed since `base`.
+        changes: SectionPeers,
+        /// Digest of the set of members once the changes are applied, for the recipient to
+        /// tell whether it's up to date with the sender.
+        digest: XorName,
+    },
+}
+
 #[derive(Debug)]
 pub struct IntoIter(btree_map::IntoIter<XorName, SectionAuth<NodeState>>);
 
@@ -0,0 +1,118 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Error, MessageId, Result};
+use crate::types::{PublicKey, Signature};
+use ed25519_dalek::{
+    Keypair as EdKeypair, PublicKey as EdPublicKey, Signature as EdSignature, Signer as _,
+    Verifier as _,
+};
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// The path a traced message took through the network, for diagnostics.
+///
+/// Tracing is opted into by the sender of a message, after which each node handling the message
+/// appends a [`Hop`] to it. The path is carried on in the header of the messages a node sends as
+/// a result, e.g. the queries an Elder relays to Adults, and their responses, so it makes it back
+/// to the sender with the response.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MsgTrace {
+    /// Id of the message traced.
+    pub msg_id: MessageId,
+    /// The nodes the message went through, in order.
+    pub hops: Vec<Hop>,
+}
+
+/// A node a traced message went through.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, custom_debug::Debug)]
+pub struct Hop {
+    /// Public key of the node.
+    #[debug(with = "PublicKey::fmt_ed25519")]
+    pub public_key: EdPublicKey,
+    /// When the node received the message, as per its clock.
+    pub received_at: SystemTime,
+    /// Node's signature over the message id, the hops before this one, and the fields above.
+    #[debug(with = "Signature::fmt_ed25519")]
+    #[serde(with = "serde_bytes")]
+    pub signature: EdSignature,
+}
+
+impl MsgTrace {
+    /// A trace of the message `msg_id`, yet to go through any node.
+    pub fn new(msg_id: MessageId) -> Self {
+        Self {
+            msg_id,
+            hops: Vec::new(),
+        }
+    }
+
+    /// Records the message went through the node with the given keypair.
+    pub fn add_hop(&mut self, keypair: &EdKeypair) -> Result<()> {
+        let received_at = SystemTime::now();
+        let bytes = self.bytes_to_sign(&self.hops, &keypair.public, &received_at)?;
+        self.hops.push(Hop {
+            public_key: keypair.public,
+            received_at,
+            signature: keypair.sign(&bytes),
+        });
+        Ok(())
+    }
+
+    /// Verifies the signature of every hop, so none of them was altered, reordered or removed
+    /// other than from the end.
+    pub fn verify(&self) -> bool {
+        self.hops.iter().enumerate().all(|(index, hop)| {
+            self.bytes_to_sign(&self.hops[..index], &hop.public_key, &hop.received_at)
+                .map(|bytes| hop.public_key.verify(&bytes, &hop.signature).is_ok())
+                .unwrap_or(false)
+        })
+    }
+
+    fn bytes_to_sign(
+        &self,
+        previous_hops: &[Hop],
+        public_key: &EdPublicKey,
+        received_at: &SystemTime,
+    ) -> Result<Vec<u8>> {
+        bincode::serialize(&(self.msg_id, previous_hops, public_key, received_at))
+            .map_err(|err| Error::Serialisation(format!("could not serialize hop: {}", err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MsgTrace;
+    use crate::messaging::MessageId;
+    use ed25519_dalek::Keypair;
+    use eyre::Result;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn hops_are_verifiable_in_order() -> Result<()> {
+        let mut trace = MsgTrace::new(MessageId::new());
+        trace.add_hop(&Keypair::generate(&mut OsRng))?;
+        trace.add_hop(&Keypair::generate(&mut OsRng))?;
+        trace.add_hop(&Keypair::generate(&mut OsRng))?;
+        assert!(trace.verify());
+
+        let mut reordered = trace.clone();
+        reordered.hops.swap(0, 1);
+        assert!(!reordered.verify());
+
+        let mut truncated = trace.clone();
+        let _ = truncated.hops.pop();
+        assert!(truncated.verify());
+
+        let mut other_msg = trace;
+        other_msg.msg_id = MessageId::new();
+        assert!(!other_msg.verify());
+
+        Ok(())
+    }
+}
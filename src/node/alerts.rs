@@ -0,0 +1,352 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::dbs::UsedSpace;
+use crate::node::{network::Network, Config};
+use crate::routing::DkgSessionStatus;
+use serde::Serialize;
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Write,
+    net::{TcpStream, ToSocketAddrs},
+    process::Command,
+    time::Duration,
+};
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+use url::Url;
+use xor_name::XorName;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// Percentage of the max capacity used from which storage is deemed nearly full.
+const STORAGE_ALERT_PERCENT: u64 = 90;
+// Failed DKG sessions from which failures are deemed repeated.
+const DKG_FAILURES_ALERT: usize = 3;
+// Consecutive checks finding no connection to any Elder from which connectivity is deemed lost.
+const DISCONNECTED_CHECKS_ALERT: usize = 3;
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A critical event operators are alerted of, via the hooks set up with `--alert-webhook` and
+/// `--alert-exec`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "alert", rename_all = "snake_case")]
+pub(crate) enum Alert {
+    /// The node was demoted from Elder to Adult.
+    Demoted,
+    /// The storage used is close to the max capacity.
+    StorageNearlyFull { used: u64, max_capacity: u64 },
+    /// DKG sessions the node took part in keep failing, so its section may not get new keys.
+    RepeatedDkgFailure { failures: usize },
+    /// The node lost its connections to all the Elders of its section.
+    ConnectivityLost { elders: usize },
+}
+
+impl Alert {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Demoted => "demoted",
+            Self::StorageNearlyFull { .. } => "storage_nearly_full",
+            Self::RepeatedDkgFailure { .. } => "repeated_dkg_failure",
+            Self::ConnectivityLost { .. } => "connectivity_lost",
+        }
+    }
+}
+
+impl Display for Alert {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Demoted => write!(formatter, "Node was demoted from Elder to Adult"),
+            Self::StorageNearlyFull { used, max_capacity } => write!(
+                formatter,
+                "Storage nearly full: {} of {} bytes used",
+                used, max_capacity
+            ),
+            Self::RepeatedDkgFailure { failures } => write!(
+                formatter,
+                "{} DKG sessions the node took part in failed",
+                failures
+            ),
+            Self::ConnectivityLost { elders } => write!(
+                formatter,
+                "Lost connectivity to all {} Elders of the section",
+                elders
+            ),
+        }
+    }
+}
+
+/// The hooks operators are alerted through.
+#[derive(Clone, Debug)]
+pub(crate) struct AlertHooks {
+    webhook: Option<Url>,
+    exec: Option<String>,
+}
+
+impl AlertHooks {
+    /// The hooks set up in the config, if any.
+    pub(crate) fn from_config(config: &Config) -> Option<Self> {
+        let webhook = config
+            .alert_webhook
+            .as_deref()
+            .and_then(|url| Url::parse(url).ok());
+        if webhook.is_none() && config.alert_exec.is_none() {
+            return None;
+        }
+        Some(Self {
+            webhook,
+            exec: config.alert_exec.clone(),
+        })
+    }
+
+    async fn fire(&self, node: XorName, alert: Alert) {
+        warn!("Alert: {}", alert);
+        let hooks = self.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            if let Some(url) = &hooks.webhook {
+                if let Err(error) = post(url, node, &alert) {
+                    warn!("Failed to post alert to {}: {}", url, error);
+                }
+            }
+            if let Some(command) = &hooks.exec {
+                if let Err(error) = exec(command, node, &alert) {
+                    warn!("Failed to run alert command `{}`: {}", command, error);
+                }
+            }
+        })
+        .await;
+        if let Err(error) = result {
+            warn!("Failed to run alert hooks: {}", error);
+        }
+    }
+}
+
+/// Validates the webhook alerts are posted to.
+pub(crate) fn check_alert_webhook(url: &str) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|err| format!("Invalid --alert-webhook: {}", err))?;
+    if url.scheme() != "http" || url.host_str().is_none() {
+        return Err("The --alert-webhook must be a plain http:// URL.".to_string());
+    }
+    Ok(())
+}
+
+// Posts the alert to the webhook, as JSON.
+fn post(url: &Url, node: XorName, alert: &Alert) -> std::io::Result<()> {
+    let body = serde_json::json!({
+        "node": hex::encode(node.0),
+        "message": alert.to_string(),
+        "details": alert,
+    })
+    .to_string();
+
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addr = (host, port).to_socket_addrs()?.next().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "webhook host not found")
+    })?;
+    let mut stream = TcpStream::connect_timeout(&addr, WEBHOOK_TIMEOUT)?;
+    stream.set_write_timeout(Some(WEBHOOK_TIMEOUT))?;
+
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+        Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// Runs the command via the shell, with the alert in its env.
+fn exec(command: &str, node: XorName, alert: &Alert) -> std::io::Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        let _ = shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        let _ = shell.arg("-c");
+        shell
+    };
+    let status = shell
+        .arg(command)
+        .env("SN_ALERT", alert.kind())
+        .env("SN_ALERT_MESSAGE", alert.to_string())
+        .env("SN_NODE_NAME", hex::encode(node.0))
+        .status()?;
+    if !status.success() {
+        warn!("Alert command `{}` exited with {}", command, status);
+    }
+    Ok(())
+}
+
+// What the node looked like on a check.
+#[derive(Clone, Debug, Default)]
+struct Snapshot {
+    is_elder: bool,
+    used_space: u64,
+    max_capacity: u64,
+    failed_dkg_sessions: usize,
+    elders: usize,
+    connected_elders: usize,
+}
+
+// Works out which alerts to fire from one check to the next, each once until its cause clears.
+#[derive(Debug, Default)]
+struct AlertState {
+    was_elder: bool,
+    storage_alerted: bool,
+    dkg_failures_alerted: usize,
+    disconnected_checks: usize,
+}
+
+impl AlertState {
+    fn check(&mut self, snapshot: &Snapshot) -> Vec<Alert> {
+        let mut alerts = vec![];
+
+        if self.was_elder && !snapshot.is_elder {
+            alerts.push(Alert::Demoted);
+        }
+        self.was_elder = snapshot.is_elder;
+
+        let nearly_full = snapshot.max_capacity > 0
+            && snapshot.used_space.saturating_mul(100)
+                >= snapshot.max_capacity.saturating_mul(STORAGE_ALERT_PERCENT);
+        if nearly_full && !self.storage_alerted {
+            alerts.push(Alert::StorageNearlyFull {
+                used: snapshot.used_space,
+                max_capacity: snapshot.max_capacity,
+            });
+        }
+        self.storage_alerted = nearly_full;
+
+        if snapshot.failed_dkg_sessions >= DKG_FAILURES_ALERT
+            && snapshot.failed_dkg_sessions > self.dkg_failures_alerted
+        {
+            alerts.push(Alert::RepeatedDkgFailure {
+                failures: snapshot.failed_dkg_sessions,
+            });
+            self.dkg_failures_alerted = snapshot.failed_dkg_sessions;
+        } else if snapshot.failed_dkg_sessions < self.dkg_failures_alerted {
+            // Old sessions were pruned.
+            self.dkg_failures_alerted = snapshot.failed_dkg_sessions;
+        }
+
+        if snapshot.elders > 0 && snapshot.connected_elders == 0 {
+            self.disconnected_checks += 1;
+            if self.disconnected_checks == DISCONNECTED_CHECKS_ALERT {
+                alerts.push(Alert::ConnectivityLost {
+                    elders: snapshot.elders,
+                });
+            }
+        } else {
+            self.disconnected_checks = 0;
+        }
+
+        alerts
+    }
+}
+
+/// Checks the node for critical events every minute, alerting the operator through the hooks.
+pub(crate) async fn run_alert_monitor(
+    network_api: Network,
+    used_space: UsedSpace,
+    hooks: AlertHooks,
+) {
+    info!("Alerting on critical events via {:?}", hooks);
+    let _ = tokio::task::spawn(async move {
+        let mut state = AlertState::default();
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            let _ = interval.tick().await;
+            let our_name = network_api.our_name().await;
+            let snapshot = Snapshot {
+                is_elder: network_api.is_elder().await,
+                used_space: used_space.total().await,
+                max_capacity: used_space.max_capacity(),
+                failed_dkg_sessions: network_api
+                    .dkg_sessions()
+                    .await
+                    .iter()
+                    .filter(|session| session.status == DkgSessionStatus::Failed)
+                    .count(),
+                elders: network_api
+                    .our_elder_names()
+                    .await
+                    .iter()
+                    .filter(|name| **name != our_name)
+                    .count(),
+                connected_elders: network_api.connected_elders().await.len(),
+            };
+            for alert in state.check(&snapshot) {
+                hooks.fire(our_name, alert).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_alert_webhook, Alert, AlertState, Snapshot, DISCONNECTED_CHECKS_ALERT};
+
+    #[test]
+    fn alerts_fire_once_until_cleared() {
+        let mut state = AlertState::default();
+        let mut snapshot = Snapshot {
+            is_elder: true,
+            used_space: 10,
+            max_capacity: 100,
+            elders: 4,
+            connected_elders: 4,
+            ..Snapshot::default()
+        };
+        assert!(state.check(&snapshot).is_empty());
+
+        snapshot.is_elder = false;
+        snapshot.used_space = 95;
+        assert_eq!(
+            state.check(&snapshot),
+            vec![
+                Alert::Demoted,
+                Alert::StorageNearlyFull {
+                    used: 95,
+                    max_capacity: 100
+                }
+            ]
+        );
+        assert!(state.check(&snapshot).is_empty());
+
+        snapshot.failed_dkg_sessions = 3;
+        snapshot.connected_elders = 0;
+        assert_eq!(
+            state.check(&snapshot),
+            vec![Alert::RepeatedDkgFailure { failures: 3 }]
+        );
+        for _ in 2..DISCONNECTED_CHECKS_ALERT {
+            assert!(state.check(&snapshot).is_empty());
+        }
+        assert_eq!(
+            state.check(&snapshot),
+            vec![Alert::ConnectivityLost { elders: 4 }]
+        );
+        assert!(state.check(&snapshot).is_empty());
+    }
+
+    #[test]
+    fn only_http_webhooks_are_accepted() {
+        assert!(check_alert_webhook("http://localhost:8080/alerts").is_ok());
+        assert!(check_alert_webhook("https://example.com/alerts").is_err());
+        assert!(check_alert_webhook("not a url").is_err());
+    }
+}
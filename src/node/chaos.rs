@@ -37,3 +37,102 @@ macro_rules! with_chaos {
         }
     }};
 }
+
+#[cfg(feature = "chaos")]
+pub(crate) use self::injector::Chaos;
+
+#[cfg(feature = "chaos")]
+mod injector {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::{env, sync::Mutex, time::Duration};
+    use tracing::warn;
+
+    // Longest delay injected before handling a duty.
+    const MAX_DELAY: Duration = Duration::from_millis(500);
+    // Restarts are this many times less likely than the other faults, so the node gets to
+    // handle a decent number of duties between them.
+    const RESTART_RARITY: u32 = 1000;
+
+    /// Randomised fault injection, for soak tests to shake out ordering assumptions and deadlocks.
+    ///
+    /// Faults are drawn from a generator seeded with the given seed, so a run which turned out
+    /// badly can be replayed. How often they happen is set via the "SAFE_CHAOS_LEVEL" env var,
+    /// as a percentage, like for [`with_chaos`](crate::with_chaos).
+    #[derive(Debug)]
+    pub(crate) struct Chaos {
+        rng: Mutex<StdRng>,
+        level: u32,
+        restarts: bool,
+    }
+
+    impl Chaos {
+        /// Restarts are only forced if `restarts` is true, as e.g. the first node
+        /// of a network can't rejoin it.
+        pub(crate) fn new(seed: u64, restarts: bool) -> Self {
+            let level = env::var("SAFE_CHAOS_LEVEL")
+                .ok()
+                .and_then(|level| level.parse().ok())
+                .unwrap_or(20)
+                .min(100);
+            warn!("Chaos mode on, with seed {} and level {}%", seed, level);
+            Self::with_level(seed, level, restarts)
+        }
+
+        fn with_level(seed: u64, level: u32, restarts: bool) -> Self {
+            Self {
+                rng: Mutex::new(StdRng::seed_from_u64(seed)),
+                level,
+                restarts,
+            }
+        }
+
+        /// Returns how long to hold off handling the next duty for, if at all.
+        pub(crate) fn delay(&self) -> Option<Duration> {
+            let mut rng = self.rng.lock().ok()?;
+            if !rng.gen_ratio(self.level, 100) {
+                return None;
+            }
+            Some(MAX_DELAY.mul_f64(rng.gen_range(0.0, 1.0)))
+        }
+
+        /// Returns true if the next duty is to be dropped.
+        pub(crate) fn drop_duty(&self) -> bool {
+            self.roll(100)
+        }
+
+        /// Returns true if the node is to be restarted.
+        pub(crate) fn restart(&self) -> bool {
+            self.restarts && self.roll(100 * RESTART_RARITY)
+        }
+
+        fn roll(&self, denominator: u32) -> bool {
+            match self.rng.lock() {
+                Ok(mut rng) => rng.gen_ratio(self.level, denominator),
+                Err(_) => false,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::Chaos;
+
+        #[test]
+        fn faults_replay_with_the_same_seed() {
+            let faults = |chaos: &Chaos| {
+                (0..1000)
+                    .map(|_| (chaos.delay(), chaos.drop_duty()))
+                    .collect::<Vec<_>>()
+            };
+
+            let first = faults(&Chaos::with_level(7, 50, true));
+            assert_eq!(first, faults(&Chaos::with_level(7, 50, true)));
+            assert!(first.iter().any(|(delay, _)| delay.is_some()));
+            assert!(first.iter().any(|(_, dropped)| *dropped));
+
+            let calm = Chaos::with_level(7, 0, true);
+            assert_eq!(faults(&calm), vec![(None, false); 1000]);
+            assert!(!calm.restart());
+        }
+    }
+}
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{Error, Result};
+use crate::node::{alerts::check_alert_webhook, spec::NodeSpec, Error, Result};
 use crate::routing::NetworkConfig;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -69,6 +69,10 @@ pub struct Config {
     /// print node resourse usage to stdout
     #[structopt(long)]
     pub resource_logs: bool,
+    /// Keep carrying out all work while CPU or memory use is high, rather than shedding the
+    /// work which can wait and throttling clients
+    #[structopt(long)]
+    pub no_load_shedding: bool,
     /// Delete all data from a previous node running on the same PC
     #[structopt(long)]
     pub clear_data: bool,
@@ -127,6 +131,52 @@ pub struct Config {
     /// Duration of a UPnP port mapping.
     #[structopt(long)]
     pub upnp_lease_duration: Option<u32>,
+    /// Number of copies of each chunk to keep among the Adults of a section. If none supplied
+    /// we'll default to the documented constant. Smaller values are mostly useful for testing.
+    #[structopt(long)]
+    pub replication_factor: Option<usize>,
+    /// Max size of the blobs the section accepts, in bytes, advertised to clients. If none
+    /// supplied, blobs aren't capped.
+    #[structopt(long)]
+    pub max_blob_size: Option<u64>,
+    /// Max size of the Register entries the section accepts, in bytes once serialised. If none
+    /// supplied we'll default to the documented constant.
+    #[structopt(long)]
+    pub max_register_entry_size: Option<u64>,
+    /// Max number of operations in a batch of Register edits the section accepts. If none
+    /// supplied we'll default to the documented constant.
+    #[structopt(long)]
+    pub max_register_batch_ops: Option<u64>,
+    /// Seed for the faults randomly injected in chaos mode, which is on when this is supplied.
+    /// How often faults happen is set via the "SAFE_CHAOS_LEVEL" env var, as a percentage.
+    #[cfg(feature = "chaos")]
+    #[structopt(long)]
+    pub chaos_seed: Option<u64>,
+    /// Path to a JSON node spec to read the settings from, see `NodeSpec`. Any other argument
+    /// supplied takes precedence over the spec, which takes precedence over the connection info
+    /// file.
+    #[structopt(long, parse(from_os_str))]
+    pub spec: Option<PathBuf>,
+    /// Describe the settings the node would run with, and exit without starting it.
+    #[structopt(long)]
+    pub describe: bool,
+    /// URL to POST a JSON description of critical events to, e.g. the node being demoted or its
+    /// storage being nearly full. Only plain http:// URLs are supported.
+    #[structopt(long)]
+    pub alert_webhook: Option<String>,
+    /// Command to run via the shell on critical events, which are described to it in the
+    /// SN_ALERT and SN_ALERT_MESSAGE env vars.
+    #[structopt(long)]
+    pub alert_exec: Option<String>,
+    /// Port on localhost to serve operator commands on, e.g. to start a replication check,
+    /// sent one per line. None are served if this isn't supplied.
+    #[structopt(long)]
+    pub control_port: Option<u16>,
+    /// Max bytes the buffers chunks are serialised into to be sent may take up. If none
+    /// supplied we'll default to the documented constant, unless set with the
+    /// SAFE_BUFFER_MEMORY_CAP env var.
+    #[structopt(long)]
+    pub buffer_memory_cap: Option<usize>,
     #[structopt(skip)]
     #[allow(missing_docs)]
     pub network_config: NetworkConfig,
@@ -144,24 +194,30 @@ impl Config {
 
         let mut config = Config::default();
 
-        let mut command_line_args = Config::from_args();
-        command_line_args.validate().map_err(Error::Configuration)?;
+        // Settings are taken from the command line first, then the node spec, and the contacts
+        // from the connection info file only when neither supplied them.
+        let command_line_args = Config::from_args();
+        if let Some(path) = &command_line_args.spec {
+            debug!("Reading node spec from {}", path.display());
+            config.merge(NodeSpec::from_file(path).await?.to_config());
+        }
+        config.merge(command_line_args);
 
-        if command_line_args.hard_coded_contacts.is_empty() {
-            debug!("Using node connection config file as no hard coded contacts were passed in");
+        if config.hard_coded_contacts.is_empty() {
+            debug!("Using node connection config file as no hard coded contacts were supplied");
             if let Ok((_, info)) = read_conn_info_from_file().await {
-                command_line_args.hard_coded_contacts = info;
+                config.hard_coded_contacts = info;
             }
         }
 
-        if command_line_args.genesis_key.is_none() {
-            debug!("Using node connection config file as no genesis key was passed in");
+        if config.genesis_key.is_none() {
+            debug!("Using node connection config file as no genesis key was supplied");
             if let Ok((genesis_key, _)) = read_conn_info_from_file().await {
-                command_line_args.genesis_key = Some(genesis_key);
+                config.genesis_key = Some(genesis_key);
             }
         }
 
-        config.merge(command_line_args);
+        config.validate().map_err(Error::Configuration)?;
 
         config.clear_data_from_disk().await.unwrap_or_else(|_| {
             tracing::error!("Error deleting data file from disk");
@@ -171,10 +227,10 @@ impl Config {
         Ok(config)
     }
 
-    /// Validate configuration that came from the command line.
+    /// Validate configuration that came from the command line and node spec.
     ///
     /// `StructOpt` doesn't support validation that crosses multiple field values.
-    fn validate(&self) -> Result<(), String> {
+    pub(crate) fn validate(&self) -> Result<(), String> {
         if let Some(local_addr) = self.local_addr {
             if local_addr.ip().is_loopback() && self.public_addr.is_some() {
                 return Err(
@@ -199,6 +255,23 @@ impl Config {
                 .to_string());
         }
 
+        if self.replication_factor == Some(0) {
+            return Err("The --replication-factor must be at least 1.".to_string());
+        }
+
+        if self.max_blob_size == Some(0)
+            || self.max_register_entry_size == Some(0)
+            || self.max_register_batch_ops == Some(0)
+        {
+            return Err("The --max-blob-size, --max-register-entry-size and \
+                --max-register-batch-ops must be at least 1."
+                .to_string());
+        }
+
+        if let Some(url) = &self.alert_webhook {
+            check_alert_webhook(url)?;
+        }
+
         Ok(())
     }
 
@@ -216,8 +289,9 @@ impl Config {
             self.root_dir = Some(root_dir.clone());
         }
 
-        self.json_logs = config.json_logs;
-        self.resource_logs = config.resource_logs;
+        self.json_logs = config.json_logs || self.json_logs;
+        self.resource_logs = config.resource_logs || self.resource_logs;
+        self.no_load_shedding = config.no_load_shedding || self.no_load_shedding;
 
         if config.verbose > 0 {
             self.verbose = config.verbose;
@@ -235,6 +309,11 @@ impl Config {
         self.update_only = config.update_only || self.update_only;
         self.clear_data = config.clear_data || self.clear_data;
         self.first = config.first || self.first;
+        self.describe = config.describe || self.describe;
+
+        if let Some(spec) = config.spec {
+            self.spec = Some(spec);
+        }
 
         if let Some(local_addr) = config.local_addr {
             self.local_addr = Some(local_addr);
@@ -246,7 +325,8 @@ impl Config {
             self.network_config.external_ip = Some(public_addr.ip());
         }
 
-        self.network_config.forward_port = !config.skip_igd;
+        self.skip_igd = config.skip_igd || self.skip_igd;
+        self.network_config.forward_port = !self.skip_igd;
 
         if !config.hard_coded_contacts.is_empty() {
             self.hard_coded_contacts = config.hard_coded_contacts;
@@ -272,6 +352,43 @@ impl Config {
             self.network_config.upnp_lease_duration =
                 Some(Duration::from_millis(upnp_lease_duration as u64));
         }
+
+        if let Some(replication_factor) = config.replication_factor {
+            self.replication_factor = Some(replication_factor);
+        }
+
+        if let Some(max_blob_size) = config.max_blob_size {
+            self.max_blob_size = Some(max_blob_size);
+        }
+
+        if let Some(max_register_entry_size) = config.max_register_entry_size {
+            self.max_register_entry_size = Some(max_register_entry_size);
+        }
+
+        if let Some(max_register_batch_ops) = config.max_register_batch_ops {
+            self.max_register_batch_ops = Some(max_register_batch_ops);
+        }
+
+        if config.alert_webhook.is_some() {
+            self.alert_webhook = config.alert_webhook;
+        }
+
+        if config.alert_exec.is_some() {
+            self.alert_exec = config.alert_exec;
+        }
+
+        if config.control_port.is_some() {
+            self.control_port = config.control_port;
+        }
+
+        if config.buffer_memory_cap.is_some() {
+            self.buffer_memory_cap = config.buffer_memory_cap;
+        }
+
+        #[cfg(feature = "chaos")]
+        if let Some(chaos_seed) = config.chaos_seed {
+            self.chaos_seed = Some(chaos_seed);
+        }
     }
 
     /// The address to be credited when this node farms SafeCoin.
@@ -350,6 +467,110 @@ impl Config {
         self.update_only
     }
 
+    /// Describe the settings, with defaults resolved, rather than starting the node?
+    pub fn describe(&self) -> bool {
+        self.describe
+    }
+
+    /// Human readable description of the settings the node runs with, defaults resolved.
+    pub fn description(&self) -> Result<String> {
+        let or_default =
+            |value: Option<String>, default: &str| value.unwrap_or_else(|| default.to_string());
+        let lines = vec![
+            format!(
+                "Role:               {}",
+                if self.first { "genesis" } else { "member" }
+            ),
+            format!(
+                "Wallet:             {}",
+                or_default(self.wallet_id.clone(), "none")
+            ),
+            format!("Root dir:           {}", self.root_dir()?.display()),
+            format!("Max capacity:       {} bytes", self.max_capacity()),
+            format!(
+                "Buffer memory cap:  {}",
+                or_default(
+                    self.buffer_memory_cap.map(|cap| format!("{} bytes", cap)),
+                    "default"
+                )
+            ),
+            format!(
+                "Replication factor: {}",
+                or_default(self.replication_factor.map(|f| f.to_string()), "default")
+            ),
+            format!(
+                "Data limits:        blobs {}, Register entries {}, Register batches {}",
+                or_default(
+                    self.max_blob_size.map(|size| format!("{} bytes", size)),
+                    "uncapped"
+                ),
+                or_default(
+                    self.max_register_entry_size
+                        .map(|size| format!("{} bytes", size)),
+                    "default"
+                ),
+                or_default(
+                    self.max_register_batch_ops
+                        .map(|ops| format!("{} operations", ops)),
+                    "default"
+                )
+            ),
+            format!(
+                "Local address:      {}",
+                or_default(self.local_addr.map(|a| a.to_string()), "0.0.0.0:<random>")
+            ),
+            format!(
+                "Public address:     {}",
+                or_default(
+                    self.public_addr.map(|a| a.to_string()),
+                    "queried from peers"
+                )
+            ),
+            format!("Port forwarding:    {}", !self.skip_igd),
+            format!(
+                "Contacts:           {}",
+                if self.hard_coded_contacts.is_empty() {
+                    "from connection info file".to_string()
+                } else {
+                    format!("{:?}", self.hard_coded_contacts)
+                }
+            ),
+            format!(
+                "Genesis key:        {}",
+                or_default(self.genesis_key.clone(), "from connection info file")
+            ),
+            format!(
+                "Logs:               {} ({:?}{})",
+                or_default(
+                    self.log_dir.as_ref().map(|d| d.display().to_string()),
+                    "stdout"
+                ),
+                self.verbose(),
+                if self.json_logs { ", json" } else { "" }
+            ),
+            format!(
+                "Alerts:             {}",
+                match (&self.alert_webhook, &self.alert_exec) {
+                    (None, None) => "none".to_string(),
+                    (webhook, exec) => webhook
+                        .iter()
+                        .map(|url| format!("POST to {}", url))
+                        .chain(exec.iter().map(|command| format!("run `{}`", command)))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                }
+            ),
+            format!(
+                "Control endpoint:   {}",
+                or_default(
+                    self.control_port.map(|port| format!("127.0.0.1:{}", port)),
+                    "none"
+                )
+            ),
+        ];
+        Ok(lines.join("\n"))
+    }
+
     // Clear data from of a previous node running on the same PC
     async fn clear_data_from_disk(&self) -> Result<()> {
         if self.clear_data {
@@ -397,7 +618,12 @@ impl Config {
 
 fn parse_public_addr(public_addr: &str) -> Result<SocketAddr, String> {
     let public_addr: SocketAddr = public_addr.parse().map_err(|err| format!("{}", err))?;
+    check_public_addr(public_addr)?;
+    Ok(public_addr)
+}
 
+/// Checks `public_addr` is one the node can be reached at.
+pub(crate) fn check_public_addr(public_addr: SocketAddr) -> Result<(), String> {
     if public_addr.ip().is_unspecified() {
         return Err("Cannot use unspecified IP for public address. \
             You can drop this option to query the public IP from a peer instead."
@@ -414,7 +640,7 @@ fn parse_public_addr(public_addr: &str) -> Result<SocketAddr, String> {
             .to_string());
     }
 
-    Ok(public_addr)
+    Ok(())
 }
 
 /// Overwrites connection info at file.
@@ -483,7 +709,7 @@ fn smoke() {
     // NOTE: IF this value is being changed due to a change in the config,
     // the change in config also be handled in Config::merge()
     // and in examples/config_handling.rs
-    let expected_size = 440;
+    let expected_size = 472;
 
     assert_eq!(std::mem::size_of::<Config>(), expected_size);
 }
@@ -0,0 +1,192 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::system::ReplicationTarget;
+use crate::node::network::Network;
+use crate::routing::{Prefix, ReplicationReport, ReplicationStatus, XorName};
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+/// A command operators send to the control endpoint, one per line:
+///
+/// - `check-replication range <bits>`, e.g. `check-replication range 0110`, to check the chunks
+///   of the range of names starting with those bits, or `check-replication chunks <name>...`,
+///   names in hex, to check those chunks.
+/// - `replication-report`, to get the outcome of the replication check last started.
+#[derive(Debug, Eq, PartialEq)]
+enum ControlCmd {
+    CheckReplication(ReplicationTarget),
+    ReplicationReport,
+}
+
+impl ControlCmd {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("check-replication"), Some("range")) => {
+                let bits = words.next().unwrap_or_default();
+                let mut prefix = Prefix::default();
+                for bit in bits.chars() {
+                    match bit {
+                        '0' => prefix = prefix.pushed(false),
+                        '1' => prefix = prefix.pushed(true),
+                        _ => return Err(format!("Invalid range bits: {}", bits)),
+                    }
+                }
+                Ok(Self::CheckReplication(ReplicationTarget::Range(prefix)))
+            }
+            (Some("check-replication"), Some("chunks")) => {
+                let chunks = words
+                    .map(|name| {
+                        let bytes = hex::decode(name)
+                            .map_err(|_| format!("Invalid chunk name: {}", name))?;
+                        if bytes.len() != xor_name::XOR_NAME_LEN {
+                            return Err(format!("Invalid chunk name: {}", name));
+                        }
+                        let mut array = [0; xor_name::XOR_NAME_LEN];
+                        array.copy_from_slice(&bytes);
+                        Ok(XorName(array))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Self::CheckReplication(ReplicationTarget::Chunks(chunks)))
+            }
+            (Some("replication-report"), None) => Ok(Self::ReplicationReport),
+            _ => Err(format!("Unknown command: {}", line.trim())),
+        }
+    }
+}
+
+// Lines describing `report`, one per chunk which isn't fully replicated.
+fn describe(report: &ReplicationReport) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{} chunks checked, {} Adults pending, {}",
+        report.chunks.len(),
+        report.pending.len(),
+        if report.complete {
+            "complete"
+        } else {
+            "under way"
+        }
+    )];
+    lines.extend(report.failures().map(|(name, status)| match status {
+        ReplicationStatus::Repairing { missing } => format!(
+            "{} repairing, missing at {} holders",
+            hex::encode(name.0),
+            missing.len()
+        ),
+        ReplicationStatus::Lost => format!("{} lost", hex::encode(name.0)),
+        ReplicationStatus::Replicated => format!("{} replicated", hex::encode(name.0)),
+    }));
+    lines
+}
+
+/// The control endpoint of a node, served until dropped.
+#[derive(Debug)]
+pub(crate) struct ControlEndpoint(JoinHandle<()>);
+
+impl Drop for ControlEndpoint {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Serves operator commands, e.g. starting a replication check, on `port` of localhost, until
+/// the endpoint returned is dropped. Each command is answered with lines ending with `ok` or
+/// starting with `error:`.
+pub(crate) async fn run_control_endpoint(
+    network_api: Network,
+    port: u16,
+) -> std::io::Result<ControlEndpoint> {
+    let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port))).await?;
+    info!("Serving operator commands on {:?}", listener.local_addr()?);
+    Ok(ControlEndpoint(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let _ = tokio::spawn(serve(stream, network_api.clone()));
+                }
+                Err(error) => warn!("Failed to accept a control connection: {:?}", error),
+            }
+        }
+    })))
+}
+
+async fn serve(stream: TcpStream, network_api: Network) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let answer = match ControlCmd::parse(&line) {
+            Ok(ControlCmd::CheckReplication(target)) => {
+                info!("Operator started a replication check of {:?}", target);
+                match network_api.start_replication_check(target).await {
+                    Ok(()) => vec!["ok".to_string()],
+                    Err(error) => vec![format!("error: {}", error)],
+                }
+            }
+            Ok(ControlCmd::ReplicationReport) => match network_api.replication_check().await {
+                Some(report) => describe(&report)
+                    .into_iter()
+                    .chain(Some("ok".to_string()))
+                    .collect(),
+                None => vec!["error: no replication check was started".to_string()],
+            },
+            Err(error) => vec![format!("error: {}", error)],
+        };
+        for line in answer {
+            if writer
+                .write_all(format!("{}\n", line).as_bytes())
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ControlCmd;
+    use crate::messaging::system::ReplicationTarget;
+    use crate::routing::{Prefix, XorName};
+    use eyre::Result;
+
+    #[test]
+    fn commands_are_parsed() -> Result<()> {
+        assert_eq!(
+            ControlCmd::parse("check-replication range 01").map_err(|error| eyre::eyre!(error))?,
+            ControlCmd::CheckReplication(ReplicationTarget::Range(
+                Prefix::default().pushed(false).pushed(true)
+            ))
+        );
+
+        let name = XorName::random();
+        let line = format!("check-replication chunks {}", hex::encode(name.0));
+        assert_eq!(
+            ControlCmd::parse(&line).map_err(|error| eyre::eyre!(error))?,
+            ControlCmd::CheckReplication(ReplicationTarget::Chunks(
+                vec![name].into_iter().collect()
+            ))
+        );
+
+        assert_eq!(
+            ControlCmd::parse(" replication-report "),
+            Ok(ControlCmd::ReplicationReport)
+        );
+        assert!(ControlCmd::parse("check-replication range 012").is_err());
+        assert!(ControlCmd::parse("check-replication chunks 00").is_err());
+        assert!(ControlCmd::parse("shutdown").is_err());
+
+        Ok(())
+    }
+}
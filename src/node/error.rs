@@ -6,11 +6,12 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use super::{Misconfiguration, Reachability};
 use crate::dbs;
 use crate::messaging::{data::Error as ErrorMessage, MessageId};
 use crate::routing::Prefix;
 use crate::types::{convert_dt_error_to_error_message, DataAddress, PublicKey};
-use std::io;
+use std::{io, net::SocketAddr};
 use thiserror::Error;
 use xor_name::XorName;
 
@@ -97,6 +98,21 @@ pub enum Error {
     /// Configuration error.
     #[error("Configuration error: {0}")]
     Configuration(String),
+    /// Peers couldn't reach the node when it tried to join the network.
+    #[error("Peers could not reach us at {addr}. {}", .reachability.diagnosis())]
+    NodeNotReachable {
+        /// Address the node was advertised at.
+        addr: SocketAddr,
+        /// How the node expected to be reached.
+        reachability: Reachability,
+    },
+    /// The node is configured in a way it refuses to run with.
+    #[error("Misconfiguration: {0}")]
+    Misconfiguration(Misconfiguration),
+    /// The node was stopped by chaos mode, to be restarted.
+    #[cfg(feature = "chaos")]
+    #[error("Restart forced by chaos mode")]
+    ChaosRestart,
     /// Sled error.
     #[error("Sled error:: {0}")]
     Sled(#[from] sled::Error),
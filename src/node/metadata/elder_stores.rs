@@ -30,9 +30,13 @@ impl ElderStores {
         let register_storage = self.network.get_register_storage().await;
         let reg_data = register_storage.get_data_of(prefix).await?;
 
+        let payment_store = self.network.get_payment_store().await;
+        let catalog_data = payment_store.get_catalog_of(&prefix)?;
+
         Ok(DataExchange {
             chunk_data,
             reg_data,
+            catalog_data,
         })
     }
 
@@ -42,6 +46,8 @@ impl ElderStores {
         let register_storage = self.network.get_register_storage().await;
 
         register_storage.update(data.reg_data)?;
+        let payment_store = self.network.get_payment_store().await;
+        payment_store.update_catalog(data.catalog_data).await?;
         let _chunks = self.network.update_chunks(data.chunk_data).await;
         Ok(())
     }
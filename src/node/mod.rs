@@ -8,16 +8,24 @@
 
 //! Implementation of the "Node" node for the SAFE Network.
 
+mod alerts;
 mod chaos;
 /// Configuration handling
 pub mod config_handler;
+mod control;
 mod error;
 mod event_mapping;
 mod logging;
 mod metadata;
+mod nat_pmp;
 mod network;
 mod node_api;
+mod node_events;
 mod node_ops;
+mod reachability;
+mod resource_monitor;
+mod safeguards;
+mod spec;
 
 /// Docs
 pub mod state_db;
@@ -26,4 +34,11 @@ pub use crate::node::{
     config_handler::{add_connection_info, set_connection_info, Config},
     error::{Error, Result},
     node_api::Node,
+    node_events::{NodeEvent, NODE_EVENTS_VERSION},
+    reachability::{PortMappingProtocol, Reachability},
+    safeguards::Misconfiguration,
+    spec::{
+        AlertsSpec, ContactsSpec, EndpointsSpec, LoggingSpec, NetworkSpec, NodeSpec, RoleSpec,
+        StorageSpec,
+    },
 };
@@ -0,0 +1,224 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Minimal NAT-PMP client (RFC 6886), to map the listening port on routers which don't do UPnP.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::Duration,
+};
+use tokio::{net::UdpSocket, task::JoinHandle, time::timeout};
+use tracing::{debug, warn};
+
+const NAT_PMP_PORT: u16 = 5351;
+// Lifetime requested for mappings, as recommended by the RFC. They're renewed halfway through.
+const MAPPING_LIFETIME_SECS: u32 = 7200;
+// The RFC retries up to 9 times, which would hold startup for over a minute.
+const REQUEST_ATTEMPTS: u32 = 4;
+const FIRST_REQUEST_TIMEOUT: Duration = Duration::from_millis(250);
+
+const OP_EXTERNAL_ADDR: u8 = 0;
+const OP_MAP_UDP: u8 = 1;
+const RESPONSE_OP_OFFSET: u8 = 128;
+
+/// A port mapped on the router, renewed until dropped.
+#[derive(Debug)]
+pub(crate) struct PortMapping {
+    pub(crate) external_addr: SocketAddr,
+    renewal: JoinHandle<()>,
+}
+
+impl Drop for PortMapping {
+    fn drop(&mut self) {
+        self.renewal.abort();
+    }
+}
+
+/// Maps UDP `local_port` on the default gateway, returning the external address peers can reach
+/// us at. The mapping is renewed until the returned `PortMapping` is dropped.
+pub(crate) async fn map_port(local_port: u16) -> Result<PortMapping, String> {
+    let gateway = default_gateway().await.ok_or("no gateway found")?;
+    let external_ip =
+        parse_external_addr_response(&request(gateway, &[0, OP_EXTERNAL_ADDR]).await?)?;
+    let (external_port, lifetime) = map(gateway, local_port, local_port).await?;
+    let external_addr = SocketAddr::V4(SocketAddrV4::new(external_ip, external_port));
+
+    let renewal = tokio::spawn(async move {
+        let mut lifetime = lifetime;
+        loop {
+            tokio::time::sleep(Duration::from_secs(u64::from(lifetime.max(2)) / 2)).await;
+            match map(gateway, local_port, external_port).await {
+                Ok((port, renewed)) if port == external_port => lifetime = renewed,
+                Ok((port, _)) => {
+                    warn!(
+                        "The router moved our NAT-PMP mapping from port {} to {}, peers may not reach us",
+                        external_port, port
+                    );
+                    return;
+                }
+                Err(error) => {
+                    // Keep trying until the mapping expires, the router may just be restarting.
+                    warn!("Failed to renew our NAT-PMP mapping: {}", error);
+                    lifetime = lifetime.min(60);
+                }
+            }
+        }
+    });
+
+    Ok(PortMapping {
+        external_addr,
+        renewal,
+    })
+}
+
+async fn map(gateway: Ipv4Addr, local_port: u16, external_port: u16) -> Result<(u16, u32), String> {
+    let mut msg = vec![0, OP_MAP_UDP, 0, 0];
+    msg.extend_from_slice(&local_port.to_be_bytes());
+    msg.extend_from_slice(&external_port.to_be_bytes());
+    msg.extend_from_slice(&MAPPING_LIFETIME_SECS.to_be_bytes());
+    parse_mapping_response(&request(gateway, &msg).await?, local_port)
+}
+
+// Sends `msg` to the gateway, retrying with doubling timeouts until it answers.
+async fn request(gateway: Ipv4Addr, msg: &[u8]) -> Result<Vec<u8>, String> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .await
+        .map_err(|error| error.to_string())?;
+    socket
+        .connect((gateway, NAT_PMP_PORT))
+        .await
+        .map_err(|error| error.to_string())?;
+
+    let mut wait = FIRST_REQUEST_TIMEOUT;
+    let mut buf = [0; 16];
+    for _ in 0..REQUEST_ATTEMPTS {
+        let _ = socket.send(msg).await.map_err(|error| error.to_string())?;
+        match timeout(wait, socket.recv(&mut buf)).await {
+            Ok(Ok(len)) => return Ok(buf[..len].to_vec()),
+            Ok(Err(error)) => return Err(format!("NAT-PMP request failed: {}", error)),
+            Err(_) => wait *= 2,
+        }
+    }
+    Err(format!("no NAT-PMP answer from gateway {}", gateway))
+}
+
+fn check_header(response: &[u8], op: u8, len: usize) -> Result<(), String> {
+    if response.len() < len || response[0] != 0 || response[1] != RESPONSE_OP_OFFSET + op {
+        return Err("invalid NAT-PMP response".to_string());
+    }
+    match u16::from_be_bytes([response[2], response[3]]) {
+        0 => Ok(()),
+        1 => Err("NAT-PMP version not supported by the router".to_string()),
+        2 => Err("NAT-PMP is disabled on the router".to_string()),
+        3 => Err("the router isn't connected to the internet".to_string()),
+        4 => Err("the router is out of port mappings".to_string()),
+        code => Err(format!("NAT-PMP request refused with code {}", code)),
+    }
+}
+
+fn parse_external_addr_response(response: &[u8]) -> Result<Ipv4Addr, String> {
+    check_header(response, OP_EXTERNAL_ADDR, 12)?;
+    Ok(Ipv4Addr::new(
+        response[8],
+        response[9],
+        response[10],
+        response[11],
+    ))
+}
+
+// The external port and lifetime of the mapping of `local_port`.
+fn parse_mapping_response(response: &[u8], local_port: u16) -> Result<(u16, u32), String> {
+    check_header(response, OP_MAP_UDP, 16)?;
+    if u16::from_be_bytes([response[8], response[9]]) != local_port {
+        return Err("NAT-PMP response is for another port".to_string());
+    }
+    let external_port = u16::from_be_bytes([response[10], response[11]]);
+    let lifetime = u32::from_be_bytes([response[12], response[13], response[14], response[15]]);
+    Ok((external_port, lifetime))
+}
+
+// The default gateway from the kernel routing table where there's one, or else the first address
+// of the /24 network we reach the internet from, as home routers usually are.
+async fn default_gateway() -> Option<Ipv4Addr> {
+    if let Ok(routes) = tokio::fs::read_to_string("/proc/net/route").await {
+        if let Some(gateway) = parse_default_gateway(&routes) {
+            return Some(gateway);
+        }
+    }
+
+    // Connecting a UDP socket sends nothing, it just picks the local address to route from.
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await.ok()?;
+    socket.connect((Ipv4Addr::new(1, 1, 1, 1), 53)).await.ok()?;
+    match socket.local_addr().ok()? {
+        SocketAddr::V4(addr) if addr.ip().is_private() => {
+            let [a, b, c, _] = addr.ip().octets();
+            let gateway = Ipv4Addr::new(a, b, c, 1);
+            debug!("Guessing our gateway is {}", gateway);
+            Some(gateway)
+        }
+        _ => None,
+    }
+}
+
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [_, "00000000", gateway, ..] => {
+                let gateway = u32::from_str_radix(gateway, 16).ok()?;
+                // The kernel lists addresses in host byte order.
+                Some(Ipv4Addr::from(gateway.to_ne_bytes())).filter(|ip| !ip.is_unspecified())
+            }
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_default_gateway, parse_external_addr_response, parse_mapping_response};
+    use eyre::{eyre, Result};
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn responses_are_parsed() -> Result<()> {
+        let external = [0, 128, 0, 0, 0, 0, 0, 9, 203, 0, 113, 7];
+        assert_eq!(
+            parse_external_addr_response(&external).map_err(|error| eyre!(error))?,
+            Ipv4Addr::new(203, 0, 113, 7)
+        );
+
+        let mut mapping = vec![0, 129, 0, 0, 0, 0, 0, 9];
+        mapping.extend_from_slice(&12000u16.to_be_bytes());
+        mapping.extend_from_slice(&40000u16.to_be_bytes());
+        mapping.extend_from_slice(&3600u32.to_be_bytes());
+        assert_eq!(
+            parse_mapping_response(&mapping, 12000).map_err(|error| eyre!(error))?,
+            (40000, 3600)
+        );
+        assert!(parse_mapping_response(&mapping, 12001).is_err());
+
+        let refused = [0, 128, 0, 2, 0, 0, 0, 9, 0, 0, 0, 0];
+        assert!(parse_external_addr_response(&refused).is_err());
+        assert!(parse_external_addr_response(&external[..8]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn default_gateway_is_read_from_the_routing_table() {
+        let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+            eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\n\
+            eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\n";
+        assert_eq!(
+            parse_default_gateway(routes),
+            Some(Ipv4Addr::new(192, 168, 1, 1))
+        );
+        assert_eq!(parse_default_gateway("Iface\tDestination\n"), None);
+    }
+}
@@ -9,24 +9,37 @@
 use crate::dbs::UsedSpace;
 use crate::messaging::{
     data::{ChunkDataExchange, StorageLevel},
-    system::SystemMsg,
+    system::{ReplicationTarget, SystemMsg},
     DstLocation, WireMsg,
 };
-use crate::node::{state_db::store_network_keypair, Config as NodeConfig, Error, Result};
+use crate::node::{
+    nat_pmp::{self, PortMapping},
+    state_db::store_network_keypair,
+    Config as NodeConfig, Error, Misconfiguration, PortMappingProtocol, Reachability, Result,
+};
 use crate::routing::{
-    ChunkStore, Config as RoutingConfig, Error as RoutingError, EventStream, PeerUtils,
-    RegisterStorage, Routing as RoutingNode, SectionAuthorityProviderUtils,
+    CapacityRecord, ChunkStore, Config as RoutingConfig, DkgSessionInfo, Error as RoutingError,
+    EventStream, PaymentStore, PeerUtils, RegisterStorage, ReplicationReport, ResourcePressure,
+    Routing as RoutingNode, SectionAuthorityProviderUtils,
 };
 use crate::types::PublicKey;
 use bls::{PublicKey as BlsPublicKey, PublicKeySet};
 use secured_linked_list::SecuredLinkedList;
-use std::{collections::BTreeSet, net::SocketAddr, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::{SocketAddr, UdpSocket},
+    path::Path,
+    sync::Arc,
+};
 use xor_name::{Prefix, XorName};
 
 ///
 #[derive(Clone)]
 pub(crate) struct Network {
     routing: Arc<RoutingNode>,
+    reachability: Reachability,
+    // Mapping of our port via NAT-PMP, when UPnP failed, kept for as long as we're running.
+    _port_mapping: Option<Arc<PortMapping>>,
 }
 
 #[allow(missing_docs)]
@@ -36,19 +49,76 @@ impl Network {
         config: &NodeConfig,
         used_space: UsedSpace,
     ) -> Result<(Self, EventStream)> {
-        let mut routing_config = RoutingConfig {
-            first: config.is_first(),
-            bootstrap_nodes: config.hard_coded_contacts.clone(),
-            genesis_key: config.genesis_key.clone(),
-            network_config: config.network_config().clone(),
-            ..Default::default()
+        let forward_port = config.network_config().forward_port;
+        let mut mapping_error = None;
+        let mut port_mapping = None;
+
+        let result = match RoutingNode::new(
+            routing_config(config, forward_port),
+            used_space.clone(),
+            root_dir.to_path_buf(),
+        )
+        .await
+        {
+            Err(RoutingError::CannotConnectEndpoint {
+                err: qp2p::EndpointError::Upnp(error),
+            }) => {
+                // Home routers often have UPnP disabled, which shouldn't stop nodes
+                // from running on machines which are reachable anyway.
+                warn!(
+                    "Failed to map our port via UPnP, carrying on without it: {}",
+                    error
+                );
+                let mut fallback_config = routing_config(config, false);
+                if config.public_addr.is_none() {
+                    match map_port_via_nat_pmp(&mut fallback_config).await {
+                        Ok(mapping) => port_mapping = Some(mapping),
+                        Err(nat_pmp_error) => {
+                            mapping_error =
+                                Some(format!("UPnP: {}, NAT-PMP: {}", error, nat_pmp_error))
+                        }
+                    }
+                } else {
+                    mapping_error = Some(error.to_string());
+                }
+                RoutingNode::new(fallback_config, used_space, root_dir.to_path_buf()).await
+            }
+            result => result,
+        };
+
+        let reachability_at = |addr| match &mapping_error {
+            Some(reason) => Reachability::MappingFailed {
+                addr,
+                reason: reason.clone(),
+            },
+            None if port_mapping.is_some() => Reachability::PortMapped {
+                external_addr: addr,
+                protocol: PortMappingProtocol::NatPmp,
+            },
+            None if forward_port => Reachability::PortMapped {
+                external_addr: addr,
+                protocol: PortMappingProtocol::Upnp,
+            },
+            None if config.public_addr.is_some() => Reachability::Public { public_addr: addr },
+            None => Reachability::Direct { addr },
         };
-        if let Some(local_addr) = config.local_addr {
-            routing_config.local_addr = local_addr;
-        }
 
-        let (routing, event_stream) =
-            RoutingNode::new(routing_config, used_space, root_dir.to_path_buf()).await?;
+        let (routing, event_stream) = result.map_err(|error| match error {
+            RoutingError::NodeNotReachable(addr) => Error::NodeNotReachable {
+                addr,
+                reachability: reachability_at(addr),
+            },
+            RoutingError::ClockSkew { elder_timestamp } => {
+                Error::Misconfiguration(Misconfiguration::clock_skew(elder_timestamp))
+            }
+            RoutingError::DuplicateIdentity(addr) => {
+                Error::Misconfiguration(Misconfiguration::DuplicateIdentity { addr })
+            }
+            error => error.into(),
+        })?;
+
+        let reachability = reachability_at(routing.our_connection_info().await);
+        info!("Our node is {}", reachability);
 
         // Network keypair may have to be changed due to naming criteria or network requirements.
         store_network_keypair(root_dir, routing.keypair_as_bytes().await).await?;
@@ -56,6 +126,8 @@ impl Network {
         Ok((
             Self {
                 routing: Arc::new(routing),
+                reachability,
+                _port_mapping: port_mapping.map(Arc::new),
             },
             event_stream,
         ))
@@ -69,10 +141,18 @@ impl Network {
         self.routing.get_chunk_storage().await
     }
 
+    pub(crate) async fn get_payment_store(&self) -> PaymentStore {
+        self.routing.get_payment_store().await
+    }
+
     pub(crate) async fn get_chunk_data_of(&self, prefix: &Prefix) -> ChunkDataExchange {
         self.routing.get_chunk_data_of(prefix).await
     }
 
+    pub(crate) async fn replication_factor(&self) -> usize {
+        self.routing.replication_factor().await
+    }
+
     /// Returns whether the level changed or not.
     pub(crate) async fn set_storage_level(&self, node_id: &PublicKey, level: StorageLevel) -> bool {
         self.routing.set_storage_level(node_id, level).await
@@ -134,6 +214,14 @@ impl Network {
         self.routing.our_connection_info().await
     }
 
+    pub(crate) fn reachability(&self) -> &Reachability {
+        &self.reachability
+    }
+
+    pub(crate) async fn dkg_sessions(&self) -> Vec<DkgSessionInfo> {
+        self.routing.dkg_sessions().await
+    }
+
     pub(crate) async fn our_prefix(&self) -> Prefix {
         self.routing.our_prefix().await
     }
@@ -169,6 +257,26 @@ impl Network {
             .collect::<BTreeSet<_>>()
     }
 
+    pub(crate) async fn connected_elders(&self) -> BTreeSet<XorName> {
+        self.routing.connected_elders().await
+    }
+
+    pub(crate) async fn set_resource_pressure(&self, pressure: ResourcePressure) {
+        self.routing.set_resource_pressure(pressure).await
+    }
+
+    pub(crate) async fn start_replication_check(&self, target: ReplicationTarget) -> Result<()> {
+        Ok(self.routing.start_replication_check(target).await?)
+    }
+
+    pub(crate) async fn replication_check(&self) -> Option<ReplicationReport> {
+        self.routing.replication_check().await
+    }
+
+    pub(crate) async fn capacity_records(&self) -> BTreeMap<XorName, CapacityRecord> {
+        self.routing.capacity_records().await
+    }
+
     pub(crate) async fn our_adults(&self) -> BTreeSet<XorName> {
         self.routing
             .our_adults()
@@ -199,3 +307,55 @@ impl Network {
         Ok(wire_msg)
     }
 }
+
+// Maps the port `routing_config` listens on via NAT-PMP, and has it advertise the mapping. A free
+// port is picked beforehand when none was set, as the mapping has to be made before binding.
+async fn map_port_via_nat_pmp(
+    routing_config: &mut RoutingConfig,
+) -> std::result::Result<PortMapping, String> {
+    if routing_config.local_addr.port() == 0 {
+        let socket =
+            UdpSocket::bind(routing_config.local_addr).map_err(|error| error.to_string())?;
+        let port = socket
+            .local_addr()
+            .map_err(|error| error.to_string())?
+            .port();
+        routing_config.local_addr.set_port(port);
+    }
+    let mapping = nat_pmp::map_port(routing_config.local_addr.port()).await?;
+    info!(
+        "Mapped our port {} via NAT-PMP, at {}",
+        routing_config.local_addr.port(),
+        mapping.external_addr
+    );
+    routing_config.network_config.external_ip = Some(mapping.external_addr.ip());
+    routing_config.network_config.external_port = Some(mapping.external_addr.port());
+    Ok(mapping)
+}
+
+fn routing_config(config: &NodeConfig, forward_port: bool) -> RoutingConfig {
+    let mut routing_config = RoutingConfig {
+        first: config.is_first(),
+        bootstrap_nodes: config.hard_coded_contacts.clone(),
+        genesis_key: config.genesis_key.clone(),
+        network_config: config.network_config().clone(),
+        ..Default::default()
+    };
+    routing_config.network_config.forward_port = forward_port;
+    if let Some(local_addr) = config.local_addr {
+        routing_config.local_addr = local_addr;
+    }
+    if let Some(replication_factor) = config.replication_factor {
+        routing_config.replication_factor = replication_factor;
+    }
+    if let Some(max_blob_size) = config.max_blob_size {
+        routing_config.data_limits.max_blob_size = max_blob_size;
+    }
+    if let Some(max_register_entry_size) = config.max_register_entry_size {
+        routing_config.data_limits.max_register_entry_size = max_register_entry_size;
+    }
+    if let Some(max_register_batch_ops) = config.max_register_batch_ops {
+        routing_config.data_limits.max_register_batch_ops = max_register_batch_ops;
+    }
+    routing_config
+}
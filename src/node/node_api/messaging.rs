@@ -131,6 +131,7 @@ fn random_client_signature(client_msg: &ServiceMsg) -> Result<(MsgKind, Bytes)>
     let msg_kind = MsgKind::ServiceMsg(ServiceAuth {
         public_key: keypair.public_key(),
         signature,
+        delegation: None,
     });
 
     Ok((msg_kind, payload))
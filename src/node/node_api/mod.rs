@@ -15,30 +15,39 @@ mod split;
 
 use crate::dbs::UsedSpace;
 
+use crate::messaging::system::ReplicationTarget;
+#[cfg(feature = "chaos")]
+use crate::node::chaos::Chaos;
 use crate::node::logging::log_ctx::LogCtx;
 use crate::node::logging::run_system_logger;
 use crate::node::{
+    alerts::{run_alert_monitor, AlertHooks},
+    control::run_control_endpoint,
     event_mapping::{map_routing_event, Mapping, MsgContext},
     network::Network,
+    node_events::{notify, run_storage_monitor, NodeEvent, EVENT_CHANNEL_CAPACITY},
     node_ops::NodeDuty,
+    resource_monitor::run_resource_monitor,
+    safeguards::check_storage,
     state_db::{get_reward_pk, store_new_reward_keypair},
-    Config, Error, Result,
+    Config, Error, Reachability, Result,
 };
 use crate::routing::{
-    EventStream, {Prefix, XorName},
+    CapacityRecord, DkgSessionInfo, EventStream, ReplicationReport, {Prefix, XorName},
 };
-use crate::types::PublicKey;
+use crate::types::{set_chunk_buffers_memory_cap, PublicKey};
 use futures::{future::BoxFuture, lock::Mutex, stream::FuturesUnordered, FutureExt, StreamExt};
 use handle::NodeTask;
 use rand::rngs::OsRng;
 use role::{AdultRole, Role};
 use std::sync::Arc;
 use std::{
+    collections::BTreeMap,
     fmt::{self, Display, Formatter},
     net::SocketAddr,
     path::PathBuf,
 };
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use tracing::{error, warn};
@@ -62,6 +71,10 @@ pub struct Node {
     node_info: NodeInfo,
     used_space: UsedSpace,
     role: Arc<RwLock<Role>>,
+    event_sender: broadcast::Sender<NodeEvent>,
+    control_port: Option<u16>,
+    #[cfg(feature = "chaos")]
+    chaos: Option<Arc<Chaos>>,
 }
 
 impl Node {
@@ -70,6 +83,7 @@ impl Node {
         let root_dir_buf = config.root_dir()?;
         let root_dir = root_dir_buf.as_path();
         tokio::fs::create_dir_all(root_dir).await?;
+        check_storage(root_dir, config.max_capacity())?;
 
         let reward_key = match get_reward_pk(root_dir).await? {
             Some(public_key) => PublicKey::Ed25519(public_key),
@@ -85,6 +99,12 @@ impl Node {
             root_dir: root_dir_buf.clone(),
             reward_key,
         };
+        if let Some(memory_cap) = config.buffer_memory_cap {
+            if !set_chunk_buffers_memory_cap(memory_cap) {
+                warn!("Chunk buffers are in use already, their memory cap is left as it was");
+            }
+        }
+
         let used_space = UsedSpace::new(config.max_capacity());
         let (network_api, network_events) = tokio::time::timeout(
             Duration::from_secs(JOINING_TIMEOUT),
@@ -100,6 +120,12 @@ impl Node {
             node_info,
             used_space,
             network_api: network_api.clone(),
+            event_sender: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            control_port: config.control_port,
+            #[cfg(feature = "chaos")]
+            chaos: config
+                .chaos_seed
+                .map(|seed| Arc::new(Chaos::new(seed, !config.is_first()))),
         };
 
         let our_pid = std::process::id();
@@ -108,15 +134,22 @@ impl Node {
         let our_conn_info = node.our_connection_info().await;
         let our_conn_info_json = serde_json::to_string(&our_conn_info)
             .unwrap_or_else(|_| "Failed to serialize connection info".into());
+        let reachability = node.reachability();
         println!(
-            "Node PID: {:?}, prefix: {:?}, name: {}, connection info:\n{}",
-            our_pid, node_prefix, node_name, our_conn_info_json,
+            "Node PID: {:?}, prefix: {:?}, name: {}, {}, connection info:\n{}",
+            our_pid, node_prefix, node_name, reachability, our_conn_info_json,
         );
         info!(
-            "Node PID: {:?}, prefix: {:?}, name: {}, connection info: {}",
-            our_pid, node_prefix, node_name, our_conn_info_json,
+            "Node PID: {:?}, prefix: {:?}, name: {}, {}, connection info: {}",
+            our_pid, node_prefix, node_name, reachability, our_conn_info_json,
         );
 
+        if let Some(hooks) = AlertHooks::from_config(config) {
+            run_alert_monitor(network_api.clone(), node.used_space.clone(), hooks).await;
+        }
+        if !config.no_load_shedding {
+            run_resource_monitor(network_api.clone()).await;
+        }
         run_system_logger(LogCtx::new(network_api), config.resource_logs).await;
 
         Ok((node, network_events))
@@ -127,6 +160,11 @@ impl Node {
         self.network_api.our_connection_info().await
     }
 
+    /// Returns how our peers are expected to reach us.
+    pub fn reachability(&self) -> &Reachability {
+        self.network_api.reachability()
+    }
+
     /// Returns our name.
     pub async fn our_name(&self) -> XorName {
         self.network_api.our_name().await
@@ -137,17 +175,58 @@ impl Node {
         self.network_api.our_prefix().await
     }
 
+    /// Returns the DKG sessions we take part in, to generate our section's keys.
+    pub async fn dkg_sessions(&self) -> Vec<DkgSessionInfo> {
+        self.network_api.dkg_sessions().await
+    }
+
     /// Returns the network's genesis key.
     pub async fn genesis_key(&self) -> bls::PublicKey {
         self.network_api.genesis_key().await
     }
 
+    /// Starts verifying the given chunks, e.g. those of a range after an Adult's disk was found
+    /// faulty, are held by all their holders, replicating them to those missing them. Only
+    /// Elders can check replication; the outcome of each chunk is then returned by
+    /// [`replication_check`](Self::replication_check).
+    ///
+    /// Operators of `sn_node` start checks via the control endpoint, see `--control-port`.
+    pub async fn start_replication_check(&self, target: ReplicationTarget) -> Result<()> {
+        self.network_api.start_replication_check(target).await
+    }
+
+    /// Returns the outcome of the replication check last started at this node, if any.
+    pub async fn replication_check(&self) -> Option<ReplicationReport> {
+        self.network_api.replication_check().await
+    }
+
+    /// Returns the storage capacity the Adults of our section advertised on joining, and how
+    /// they fared at the spot-checks of it by this node, for capacity planning to go by the
+    /// capacity they're trusted with. Only Elders spot-check capacity.
+    pub async fn capacity_records(&self) -> BTreeMap<XorName, CapacityRecord> {
+        self.network_api.capacity_records().await
+    }
+
+    /// Subscribe to the changes in the state of the node, e.g. it being promoted or its
+    /// section splitting.
+    ///
+    /// Each subscriber gets all events notified after it subscribed, so subscribe before
+    /// [`run`](Self::run) not to miss [`NodeEvent::Joined`]. Those lagging too far behind
+    /// miss the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.event_sender.subscribe()
+    }
+
     // TODO: remove this, and be processed, calling from routing code directly
     async fn process_routing_event(
         network_events: Arc<Mutex<EventStream>>,
         network_api: Network,
+        event_sender: broadcast::Sender<NodeEvent>,
     ) -> Result<NodeTask> {
         let node_task = if let Some(event) = network_events.lock().await.next().await {
+            for node_event in NodeEvent::from_routing_event(&event) {
+                notify(&event_sender, node_event);
+            }
             let Mapping { op, ctx } = map_routing_event(event, &network_api).await;
             NodeTask::Result(Box::new((vec![op], ctx)))
         } else {
@@ -161,10 +240,27 @@ impl Node {
     /// by client sending in a `Command` to free it.
     pub async fn run(&self, network_events: EventStream) -> Result<()> {
         let network_api = self.network_api.clone();
+        notify(
+            &self.event_sender,
+            NodeEvent::Joined {
+                name: network_api.our_name().await,
+                prefix: network_api.our_prefix().await,
+                age: network_api.age().await,
+            },
+        );
+        // Both run until we return.
+        let _storage_monitor =
+            run_storage_monitor(self.used_space.clone(), self.event_sender.clone());
+        let _control_endpoint = match self.control_port {
+            Some(port) => Some(run_control_endpoint(network_api.clone(), port).await?),
+            None => None,
+        };
+
         let event_lock = Arc::new(Mutex::new(network_events));
         let routing_task_handle = tokio::spawn(Self::process_routing_event(
             event_lock.clone(),
             network_api.clone(),
+            self.event_sender.clone(),
         ));
         let mut threads = FuturesUnordered::new();
         threads.push(routing_task_handle);
@@ -174,6 +270,17 @@ impl Node {
                 Ok(Ok(NodeTask::Result(boxed))) => {
                     let (duties, ctx) = *boxed;
                     for duty in duties {
+                        #[cfg(feature = "chaos")]
+                        if let Some(chaos) = &self.chaos {
+                            if chaos.restart() {
+                                warn!("Chaos: restarting the node");
+                                return Err(Error::ChaosRestart);
+                            }
+                            if chaos.drop_duty() {
+                                warn!("Chaos: dropping {:?}", duty);
+                                continue;
+                            }
+                        }
                         let tasks = self.handle_and_get_threads(duty, ctx.clone()).await;
                         threads.extend(tasks.into_iter());
                     }
@@ -194,6 +301,7 @@ impl Node {
                 threads.push(tokio::spawn(Self::process_routing_event(
                     event_lock.clone(),
                     network_api.clone(),
+                    self.event_sender.clone(),
                 )))
             }
         }
@@ -207,6 +315,11 @@ impl Node {
     ) -> BoxFuture<Vec<JoinHandle<Result<NodeTask>>>> {
         async move {
             let mut threads = vec![];
+            #[cfg(feature = "chaos")]
+            if let Some(delay) = self.chaos.as_ref().and_then(|chaos| chaos.delay()) {
+                warn!("Chaos: delaying {:?} by {:?}", op, delay);
+                tokio::time::sleep(delay).await;
+            }
             match self.handle(op).await {
                 Ok(node_task) => match node_task {
                     NodeTask::Result(boxed) => {
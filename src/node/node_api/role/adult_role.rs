@@ -15,7 +15,7 @@ use crate::node::{
     node_ops::{NodeDuties, NodeDuty},
     Result,
 };
-use crate::routing::{XorName, CHUNK_COPY_COUNT};
+use crate::routing::XorName;
 use crate::types::{Chunk, ChunkAddress};
 use itertools::Itertools;
 use std::collections::{BTreeMap, BTreeSet};
@@ -66,11 +66,12 @@ impl AdultRole {
         remaining: &BTreeSet<XorName>,
     ) -> Option<(Chunk, BTreeSet<XorName>)> {
         let chunks = self.network_api.get_chunk_storage().await;
+        let replication_factor = self.network_api.replication_factor().await;
 
         let old_adult_list = remaining.union(lost_adults).copied().collect();
         let new_adult_list = remaining.union(new_adults).copied().collect();
-        let new_holders = self.compute_holders(address, &new_adult_list);
-        let old_holders = self.compute_holders(address, &old_adult_list);
+        let new_holders = self.compute_holders(address, &new_adult_list, replication_factor);
+        let old_holders = self.compute_holders(address, &old_adult_list, replication_factor);
 
         let we_are_not_holder_anymore = !new_holders.contains(our_name);
         let new_adult_is_holder = !new_holders.is_disjoint(new_adults);
@@ -96,11 +97,12 @@ impl AdultRole {
         &self,
         addr: &ChunkAddress,
         adult_list: &BTreeSet<XorName>,
+        replication_factor: usize,
     ) -> BTreeSet<XorName> {
         adult_list
             .iter()
             .sorted_by(|lhs, rhs| addr.name().cmp_distance(lhs, rhs))
-            .take(CHUNK_COPY_COUNT)
+            .take(replication_factor)
             .cloned()
             .collect()
     }
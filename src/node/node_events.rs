@@ -0,0 +1,319 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::dbs::UsedSpace;
+use crate::routing::{Event as RoutingEvent, NodeElderChange, Prefix, XorName};
+use crate::types::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::info;
+
+/// Version of the [`NodeEvent`]s notified, bumped on any change breaking their compatibility.
+///
+/// Within a version, events and their fields are only ever added, never removed, renamed or
+/// given another meaning, so both the enum and its serialised form stay compatible: embedders
+/// matching on it must have a wildcard arm, and those deserialising it must skip fields they
+/// don't know of. Events they don't know of are deserialised as [`NodeEvent::Unknown`].
+pub const NODE_EVENTS_VERSION: u16 = 1;
+
+// Number of node events kept for subscribers lagging behind.
+pub(crate) const EVENT_CHANNEL_CAPACITY: usize = 64;
+const STORAGE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A change in the state of a node, notified to the subscribers of [`Node::subscribe`], e.g. for
+/// supervisors and dashboards of nodes run in-process to follow them without parsing their logs.
+///
+/// Events are serialised tagged with their `event` name, in snake case. See
+/// [`NODE_EVENTS_VERSION`] for the compatibility guaranteed.
+///
+/// [`Node::subscribe`]: crate::node::Node::subscribe
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum NodeEvent {
+    /// The node joined the network, notified once it starts running.
+    #[non_exhaustive]
+    Joined {
+        /// Name of the node.
+        name: XorName,
+        /// Prefix of the section it joined.
+        prefix: Prefix,
+        /// Age of the node.
+        age: u8,
+    },
+    /// The node was promoted from Adult to Elder.
+    #[non_exhaustive]
+    PromotedToElder {
+        /// Prefix of its section.
+        prefix: Prefix,
+        /// Key of its section, it now holds a share of.
+        section_key: bls::PublicKey,
+    },
+    /// The node was demoted from Elder to Adult.
+    #[non_exhaustive]
+    DemotedToAdult {
+        /// Prefix of its section.
+        prefix: Prefix,
+    },
+    /// The node was relocated to another section, under a new name.
+    #[non_exhaustive]
+    Relocated {
+        /// Name of the node before the relocation.
+        previous_name: XorName,
+        /// Name of the node in its new section.
+        name: XorName,
+    },
+    /// The section of the node split in two.
+    #[non_exhaustive]
+    SectionSplit {
+        /// Prefix of the section of the node after the split.
+        prefix: Prefix,
+        /// Prefix of the sibling section.
+        sibling_prefix: Prefix,
+        /// Key of the section of the node after the split.
+        section_key: bls::PublicKey,
+    },
+    /// The storage used by the node reached another tenth of its max capacity, as first
+    /// checked once it starts running, then every minute.
+    #[non_exhaustive]
+    StorageLevelChanged {
+        /// Storage used, in bytes.
+        used: u64,
+        /// Max capacity of the node, in bytes.
+        max_capacity: u64,
+        /// Tenths of the max capacity used, from 0 to 10.
+        level: u8,
+    },
+    /// An event of a later version than this one, which it doesn't know of, when deserialised.
+    /// Never notified by nodes.
+    #[serde(other)]
+    Unknown,
+}
+
+impl NodeEvent {
+    /// The events a routing event notifies, if any.
+    pub(crate) fn from_routing_event(event: &RoutingEvent) -> Vec<Self> {
+        match event {
+            RoutingEvent::SectionSplit {
+                elders,
+                sibling_elders,
+                self_status_change,
+            } => {
+                let mut events = vec![Self::SectionSplit {
+                    prefix: elders.prefix,
+                    sibling_prefix: sibling_elders.prefix,
+                    section_key: elders.key,
+                }];
+                match self_status_change {
+                    NodeElderChange::Promoted => events.push(Self::PromotedToElder {
+                        prefix: elders.prefix,
+                        section_key: elders.key,
+                    }),
+                    NodeElderChange::Demoted => events.push(Self::DemotedToAdult {
+                        prefix: elders.prefix,
+                    }),
+                    NodeElderChange::None => {}
+                }
+                events
+            }
+            RoutingEvent::EldersChanged {
+                elders,
+                self_status_change,
+            } => match self_status_change {
+                NodeElderChange::Promoted => vec![Self::PromotedToElder {
+                    prefix: elders.prefix,
+                    section_key: elders.key,
+                }],
+                NodeElderChange::Demoted => vec![Self::DemotedToAdult {
+                    prefix: elders.prefix,
+                }],
+                NodeElderChange::None => vec![],
+            },
+            RoutingEvent::Relocated {
+                previous_name,
+                new_keypair,
+            } => vec![Self::Relocated {
+                previous_name: *previous_name,
+                name: XorName::from(PublicKey::Ed25519(new_keypair.public)),
+            }],
+            _ => vec![],
+        }
+    }
+}
+
+/// Notifies `event` to the subscribers, if any.
+pub(crate) fn notify(sender: &broadcast::Sender<NodeEvent>, event: NodeEvent) {
+    info!("Node event: {:?}", event);
+    // Fails only when there are no subscribers.
+    let _ = sender.send(event);
+}
+
+// Tenths of `max_capacity` which `used` amounts to, capped at 10.
+fn storage_level(used: u64, max_capacity: u64) -> u8 {
+    if max_capacity == 0 {
+        return 10;
+    }
+    (used.saturating_mul(10) / max_capacity).min(10) as u8
+}
+
+/// The storage monitor of a node, run until dropped.
+#[derive(Debug)]
+pub(crate) struct StorageMonitor(JoinHandle<()>);
+
+impl Drop for StorageMonitor {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Checks the storage used every minute, notifying each change of level to the subscribers,
+/// until the monitor returned is dropped.
+pub(crate) fn run_storage_monitor(
+    used_space: UsedSpace,
+    sender: broadcast::Sender<NodeEvent>,
+) -> StorageMonitor {
+    StorageMonitor(tokio::task::spawn(async move {
+        let mut last_level = None;
+        let mut interval = tokio::time::interval(STORAGE_CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            let _ = interval.tick().await;
+            let used = used_space.total().await;
+            let max_capacity = used_space.max_capacity();
+            let level = storage_level(used, max_capacity);
+            if last_level != Some(level) {
+                last_level = Some(level);
+                notify(
+                    &sender,
+                    NodeEvent::StorageLevelChanged {
+                        used,
+                        max_capacity,
+                        level,
+                    },
+                );
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{storage_level, NodeEvent};
+    use crate::routing::{Elders, Event as RoutingEvent, NodeElderChange, Prefix};
+    use eyre::Result;
+    use std::collections::BTreeSet;
+    use xor_name::XorName;
+
+    #[test]
+    fn events_are_serialised_tagged_with_their_name() -> Result<()> {
+        let name = XorName::random();
+        let event = NodeEvent::StorageLevelChanged {
+            used: 90,
+            max_capacity: 100,
+            level: storage_level(90, 100),
+        };
+        let json = serde_json::to_value(&event)?;
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "event": "storage_level_changed",
+                "used": 90,
+                "max_capacity": 100,
+                "level": 9,
+            })
+        );
+        assert_eq!(serde_json::from_value::<NodeEvent>(json)?, event);
+
+        let event = NodeEvent::Relocated {
+            previous_name: name,
+            name,
+        };
+        let json = serde_json::to_value(&event)?;
+        assert_eq!(json["event"], "relocated");
+        assert_eq!(serde_json::from_value::<NodeEvent>(json)?, event);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_events_are_deserialised() -> Result<()> {
+        let json = serde_json::json!({ "event": "some_later_event", "field": 1 });
+        assert_eq!(
+            serde_json::from_value::<NodeEvent>(json)?,
+            NodeEvent::Unknown
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn splits_notify_elder_changes_too() {
+        let elders = |prefix| Elders {
+            prefix,
+            key: bls::SecretKey::random().public_key(),
+            remaining: BTreeSet::new(),
+            added: BTreeSet::new(),
+            removed: BTreeSet::new(),
+        };
+        let (ours, theirs) = (elders(Prefix::default()), elders(Prefix::default()));
+        let split = RoutingEvent::SectionSplit {
+            elders: ours.clone(),
+            sibling_elders: theirs.clone(),
+            self_status_change: NodeElderChange::Promoted,
+        };
+        assert_eq!(
+            NodeEvent::from_routing_event(&split),
+            vec![
+                NodeEvent::SectionSplit {
+                    prefix: ours.prefix,
+                    sibling_prefix: theirs.prefix,
+                    section_key: ours.key,
+                },
+                NodeEvent::PromotedToElder {
+                    prefix: ours.prefix,
+                    section_key: ours.key,
+                },
+            ]
+        );
+
+        let demoting_split = RoutingEvent::SectionSplit {
+            elders: ours.clone(),
+            sibling_elders: theirs.clone(),
+            self_status_change: NodeElderChange::Demoted,
+        };
+        assert_eq!(
+            NodeEvent::from_routing_event(&demoting_split),
+            vec![
+                NodeEvent::SectionSplit {
+                    prefix: ours.prefix,
+                    sibling_prefix: theirs.prefix,
+                    section_key: ours.key,
+                },
+                NodeEvent::DemotedToAdult {
+                    prefix: ours.prefix
+                },
+            ]
+        );
+
+        let unchanged = RoutingEvent::EldersChanged {
+            elders: ours,
+            self_status_change: NodeElderChange::None,
+        };
+        assert!(NodeEvent::from_routing_event(&unchanged).is_empty());
+    }
+
+    #[test]
+    fn storage_levels_are_tenths_of_the_capacity() {
+        assert_eq!(storage_level(0, 100), 0);
+        assert_eq!(storage_level(19, 100), 1);
+        assert_eq!(storage_level(150, 100), 10);
+        assert_eq!(storage_level(1, 0), 10);
+    }
+}
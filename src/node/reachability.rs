@@ -0,0 +1,155 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+};
+
+/// Protocol a port was mapped on the router with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PortMappingProtocol {
+    /// Universal Plug and Play, via the Internet Gateway Device protocol.
+    Upnp,
+    /// NAT Port Mapping Protocol, tried when UPnP fails.
+    NatPmp,
+}
+
+impl Display for PortMappingProtocol {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Upnp => write!(f, "UPnP"),
+            Self::NatPmp => write!(f, "NAT-PMP"),
+        }
+    }
+}
+
+/// How the node expects to be reached by its peers, as determined at startup.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Reachability {
+    /// The listening port was mapped on the router.
+    PortMapped {
+        /// External address of the mapping, as advertised to peers.
+        external_addr: SocketAddr,
+        /// Protocol the port was mapped with.
+        protocol: PortMappingProtocol,
+    },
+    /// The node was given the public address it's reachable at.
+    Public {
+        /// Address advertised to peers.
+        public_addr: SocketAddr,
+    },
+    /// Port forwarding is disabled, so the machine is expected to be reachable directly.
+    Direct {
+        /// Address advertised to peers.
+        addr: SocketAddr,
+    },
+    /// Mapping the listening port on the router failed, via UPnP and NAT-PMP, so the node fell
+    /// back to being reached directly.
+    MappingFailed {
+        /// Address advertised to peers.
+        addr: SocketAddr,
+        /// Why the mapping failed.
+        reason: String,
+    },
+}
+
+impl Reachability {
+    /// The address advertised to peers.
+    pub fn advertised_addr(&self) -> SocketAddr {
+        match self {
+            Self::PortMapped { external_addr, .. } => *external_addr,
+            Self::Public { public_addr } => *public_addr,
+            Self::Direct { addr } | Self::MappingFailed { addr, .. } => *addr,
+        }
+    }
+
+    /// What to check when peers can't reach the node.
+    pub fn diagnosis(&self) -> String {
+        match self {
+            Self::PortMapped {
+                external_addr,
+                protocol,
+            } => format!(
+                "The port was mapped on your router via {}, yet {} isn't reachable. Your \
+                router may be behind another NAT (e.g. your ISP's), in which case you'll need a \
+                public IP address, or a firewall may be blocking incoming traffic.",
+                protocol, external_addr
+            ),
+            Self::Public { public_addr } => format!(
+                "Make sure {} is the right public address, and that your router forwards that \
+                port to this machine.",
+                public_addr
+            ),
+            Self::Direct { addr } => format!(
+                "Port forwarding is disabled, so this machine must be publicly reachable at {}. \
+                If it's behind a router, either enable UPnP on the router and run without \
+                --skip-igd, or forward a port manually and supply it with --public-addr.",
+                addr
+            ),
+            Self::MappingFailed { addr, reason } => format!(
+                "Mapping the port on your router via UPnP and NAT-PMP failed ({}), so this \
+                machine must be publicly reachable at {}. Either enable UPnP or NAT-PMP on the \
+                router, or forward a port manually and supply it with --public-addr.",
+                reason, addr
+            ),
+        }
+    }
+}
+
+impl Display for Reachability {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::PortMapped {
+                external_addr,
+                protocol,
+            } => write!(
+                f,
+                "reachable at {} via {} port mapping",
+                external_addr, protocol
+            ),
+            Self::Public { public_addr } => {
+                write!(f, "reachable at configured public address {}", public_addr)
+            }
+            Self::Direct { addr } => write!(f, "reachable directly at {}", addr),
+            Self::MappingFailed { addr, reason } => write!(
+                f,
+                "reachable directly at {}, as port mapping failed: {}",
+                addr, reason
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PortMappingProtocol, Reachability};
+    use std::net::SocketAddr;
+
+    #[test]
+    fn diagnosis_points_at_the_fix() {
+        let addr: SocketAddr = ([203, 0, 113, 7], 12000).into();
+
+        let failed = Reachability::MappingFailed {
+            addr,
+            reason: "no gateway found".to_string(),
+        };
+        assert_eq!(failed.advertised_addr(), addr);
+        assert!(failed.diagnosis().contains("no gateway found"));
+        assert!(failed.diagnosis().contains("--public-addr"));
+
+        let direct = Reachability::Direct { addr };
+        assert!(direct.diagnosis().contains("--skip-igd"));
+
+        let mapped = Reachability::PortMapped {
+            external_addr: addr,
+            protocol: PortMappingProtocol::NatPmp,
+        };
+        assert!(mapped.diagnosis().contains("NAT-PMP"));
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::network::Network;
+use crate::routing::ResourcePressure;
+use std::time::Duration;
+use sysinfo::{ProcessorExt, System, SystemExt};
+use tokio::time::MissedTickBehavior;
+use tracing::debug;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+// Percentage of CPU, across all processors, from which its use is deemed high.
+const HIGH_CPU_PERCENT: f32 = 90.0;
+// Percentage of memory from which its use is deemed high.
+const HIGH_MEMORY_PERCENT: f32 = 90.0;
+// Percentage points below the thresholds use has to drop to for the pressure to be relieved,
+// so it doesn't flap back and forth around them.
+const RELIEF_MARGIN_PERCENT: f32 = 15.0;
+// Consecutive checks use has to be high, or low, for the pressure to change, so short spikes
+// aren't reacted to.
+const SUSTAINED_CHECKS: usize = 3;
+
+// CPU and memory use found in a check.
+#[derive(Clone, Copy, Debug)]
+struct Sample {
+    cpu_percent: f32,
+    memory_percent: f32,
+}
+
+// Works out the pressure the node is under from one check to the next.
+#[derive(Debug)]
+struct PressureState {
+    pressure: ResourcePressure,
+    // Consecutive checks calling for the pressure to change.
+    checks: usize,
+}
+
+impl Default for PressureState {
+    fn default() -> Self {
+        Self {
+            pressure: ResourcePressure::Normal,
+            checks: 0,
+        }
+    }
+}
+
+impl PressureState {
+    // Returns the new pressure, if it changed.
+    fn check(&mut self, sample: &Sample) -> Option<ResourcePressure> {
+        let (calls_for_change, other) = match self.pressure {
+            ResourcePressure::Normal => (
+                sample.cpu_percent >= HIGH_CPU_PERCENT
+                    || sample.memory_percent >= HIGH_MEMORY_PERCENT,
+                ResourcePressure::High,
+            ),
+            ResourcePressure::High => (
+                sample.cpu_percent < HIGH_CPU_PERCENT - RELIEF_MARGIN_PERCENT
+                    && sample.memory_percent < HIGH_MEMORY_PERCENT - RELIEF_MARGIN_PERCENT,
+                ResourcePressure::Normal,
+            ),
+        };
+        if !calls_for_change {
+            self.checks = 0;
+            return None;
+        }
+        self.checks += 1;
+        if self.checks < SUSTAINED_CHECKS {
+            return None;
+        }
+        self.checks = 0;
+        self.pressure = other;
+        Some(other)
+    }
+}
+
+fn sample(system: &mut System) -> Sample {
+    system.refresh_cpu();
+    system.refresh_memory();
+    let memory_percent = if system.total_memory() > 0 {
+        100.0 * system.used_memory() as f32 / system.total_memory() as f32
+    } else {
+        0.0
+    };
+    Sample {
+        cpu_percent: system.global_processor_info().cpu_usage(),
+        memory_percent,
+    }
+}
+
+/// Checks the CPU and memory use of the machine every few seconds, telling routing to shed the
+/// work which can wait while it's been high for a while.
+pub(crate) async fn run_resource_monitor(network_api: Network) {
+    let _ = tokio::task::spawn(async move {
+        let mut system = System::new();
+        let mut state = PressureState::default();
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            let _ = interval.tick().await;
+            let sample = sample(&mut system);
+            if let Some(pressure) = state.check(&sample) {
+                debug!("Resource pressure now {:?}, at {:?}", pressure, sample);
+                network_api.set_resource_pressure(pressure).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PressureState, Sample, SUSTAINED_CHECKS};
+    use crate::routing::ResourcePressure;
+
+    fn sample(cpu_percent: f32, memory_percent: f32) -> Sample {
+        Sample {
+            cpu_percent,
+            memory_percent,
+        }
+    }
+
+    #[test]
+    fn pressure_changes_on_sustained_use_only() {
+        let mut state = PressureState::default();
+
+        // A spike isn't reacted to.
+        assert_eq!(state.check(&sample(99.0, 50.0)), None);
+        assert_eq!(state.check(&sample(20.0, 50.0)), None);
+
+        for _ in 1..SUSTAINED_CHECKS {
+            assert_eq!(state.check(&sample(50.0, 95.0)), None);
+        }
+        assert_eq!(
+            state.check(&sample(50.0, 95.0)),
+            Some(ResourcePressure::High)
+        );
+
+        // Dropping just below the thresholds doesn't relieve it.
+        for _ in 0..2 * SUSTAINED_CHECKS {
+            assert_eq!(state.check(&sample(85.0, 85.0)), None);
+        }
+        for _ in 1..SUSTAINED_CHECKS {
+            assert_eq!(state.check(&sample(30.0, 60.0)), None);
+        }
+        assert_eq!(
+            state.check(&sample(30.0, 60.0)),
+            Some(ResourcePressure::Normal)
+        );
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Error, Result};
+use crate::routing::MAX_CLOCK_SKEW;
+use std::{
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use sysinfo::{DiskExt, System, SystemExt};
+
+// File systems whose content is lost when the machine restarts.
+const VOLATILE_FILE_SYSTEMS: &[&str] = &["tmpfs", "ramfs"];
+
+/// A configuration the node refuses to run with, as it would harm its section,
+/// e.g. by the node repeatedly joining and dropping out of it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Misconfiguration {
+    /// The root dir is on a file system held in memory, too small for the committed capacity.
+    /// The node would run out of space, or lose the data it holds when the machine restarts.
+    VolatileStorage {
+        /// The root dir.
+        root_dir: PathBuf,
+        /// Type of the file system it's on.
+        file_system: String,
+        /// Size of the file system, in bytes.
+        total_space: u64,
+        /// Capacity the node was configured to commit, in bytes.
+        max_capacity: u64,
+    },
+    /// Our clock is too far off the Elders' of the section we tried to join.
+    ClockSkew {
+        /// Seconds by which our clock is ahead of the Elders', if positive, or behind.
+        offset: i64,
+    },
+    /// A node with the same identity is already a member of the section we tried to join.
+    DuplicateIdentity {
+        /// Address of that node.
+        addr: SocketAddr,
+    },
+}
+
+impl Display for Misconfiguration {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::VolatileStorage {
+                root_dir,
+                file_system,
+                total_space,
+                max_capacity,
+            } => write!(
+                f,
+                "The root dir {} is on a {} file system of {} bytes, which is held in memory \
+                and smaller than the {} bytes of max capacity. Use a root dir on disk, or \
+                lower the max capacity.",
+                root_dir.display(),
+                file_system,
+                total_space,
+                max_capacity
+            ),
+            Self::ClockSkew { offset } => write!(
+                f,
+                "Our clock is {} seconds {} the network's, more than the {} seconds allowed. \
+                Synchronise your clock, e.g. by enabling NTP.",
+                offset.abs(),
+                if *offset > 0 { "ahead of" } else { "behind" },
+                MAX_CLOCK_SKEW.as_secs()
+            ),
+            Self::DuplicateIdentity { addr } => write!(
+                f,
+                "A node with our identity is already a member of the section, at {}. Make sure \
+                no other node runs off a copy of our root dir.",
+                addr
+            ),
+        }
+    }
+}
+
+impl Misconfiguration {
+    /// Our clock's offset from the Elders', who rejected us at `elder_timestamp`.
+    pub(crate) fn clock_skew(elder_timestamp: u64) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or(0);
+        Self::ClockSkew {
+            offset: now as i64 - elder_timestamp as i64,
+        }
+    }
+}
+
+/// Refuses to run off a root dir on a file system held in memory, smaller than `max_capacity`.
+pub(crate) fn check_storage(root_dir: &Path, max_capacity: u64) -> Result<()> {
+    let root_dir = root_dir.canonicalize()?;
+
+    let mut system = System::new();
+    system.refresh_disks_list();
+    // The disk the root dir is on is the one mounted at its closest ancestor.
+    let disk = system
+        .disks()
+        .iter()
+        .filter(|disk| root_dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().components().count());
+
+    let disk = match disk {
+        Some(disk) => disk,
+        None => {
+            debug!("Couldn't tell which disk {} is on", root_dir.display());
+            return Ok(());
+        }
+    };
+
+    let file_system = String::from_utf8_lossy(disk.file_system()).to_string();
+    volatile_storage(&root_dir, file_system, disk.total_space(), max_capacity)
+        .map_or(Ok(()), |misconfiguration| {
+            Err(Error::Misconfiguration(misconfiguration))
+        })
+}
+
+fn volatile_storage(
+    root_dir: &Path,
+    file_system: String,
+    total_space: u64,
+    max_capacity: u64,
+) -> Option<Misconfiguration> {
+    if !VOLATILE_FILE_SYSTEMS.contains(&file_system.as_str()) || total_space >= max_capacity {
+        return None;
+    }
+    Some(Misconfiguration::VolatileStorage {
+        root_dir: root_dir.to_path_buf(),
+        file_system,
+        total_space,
+        max_capacity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{volatile_storage, Misconfiguration};
+    use std::path::Path;
+
+    #[test]
+    fn refuses_small_in_memory_storage() {
+        let root_dir = Path::new("/tmp/node");
+        let gib = 1024 * 1024 * 1024;
+
+        assert!(matches!(
+            volatile_storage(root_dir, "tmpfs".to_string(), gib, 2 * gib),
+            Some(Misconfiguration::VolatileStorage { .. })
+        ));
+        assert_eq!(
+            volatile_storage(root_dir, "tmpfs".to_string(), 4 * gib, 2 * gib),
+            None
+        );
+        assert_eq!(
+            volatile_storage(root_dir, "ext4".to_string(), gib, 2 * gib),
+            None
+        );
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{config_handler::check_public_addr, Config, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+use tokio::fs;
+
+/// Declarative specification of how a node is to be run, as read from a JSON file.
+///
+/// Every section is optional, unspecified settings taking their usual defaults.
+/// Command line arguments take precedence over the spec, and the spec over the connection info
+/// file.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeSpec {
+    /// Storage committed to the network.
+    pub storage: StorageSpec,
+    /// Interfaces the node listens on, and how it's reached.
+    pub network: NetworkSpec,
+    /// Where the node finds the network it joins.
+    pub contacts: ContactsSpec,
+    /// Where and how the node reports what it's doing.
+    pub logging: LoggingSpec,
+    /// How the node's operator is alerted of critical events.
+    pub alerts: AlertsSpec,
+    /// Endpoints the node serves to its operator.
+    pub endpoints: EndpointsSpec,
+    /// Role the node takes in the network.
+    pub role: RoleSpec,
+}
+
+/// Storage committed to the network.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageSpec {
+    /// Directory for dbs and cached state.
+    pub root_dir: Option<PathBuf>,
+    /// Upper limit in bytes for network storage on this node.
+    pub max_capacity: Option<u64>,
+    /// Number of copies of each chunk kept among the Adults of a section.
+    pub replication_factor: Option<usize>,
+    /// Max size of the blobs the section accepts, in bytes.
+    pub max_blob_size: Option<u64>,
+    /// Max size of the Register entries the section accepts, in bytes once serialised.
+    pub max_register_entry_size: Option<u64>,
+    /// Max number of operations in a batch of Register edits the section accepts.
+    pub max_register_batch_ops: Option<u64>,
+}
+
+/// Interfaces the node listens on, and how it's reached.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkSpec {
+    /// Local address to listen on.
+    pub local_addr: Option<SocketAddr>,
+    /// External address the node is reachable at.
+    pub public_addr: Option<SocketAddr>,
+    /// Whether to forward the port on the router via IGD.
+    pub port_forwarding: bool,
+    /// Duration of a UPnP port mapping, in milliseconds.
+    pub upnp_lease_duration_msec: Option<u32>,
+    /// Maximum size of the messages peers may send.
+    pub max_msg_size_allowed: Option<u32>,
+    /// Interval after which a silent peer is deemed offline, in milliseconds.
+    pub idle_timeout_msec: Option<u64>,
+    /// Interval to send keep-alives at when idling, in milliseconds.
+    pub keep_alive_interval_msec: Option<u32>,
+    /// Max bytes the buffers chunks are serialised into to be sent may take up.
+    pub buffer_memory_cap: Option<usize>,
+}
+
+impl Default for NetworkSpec {
+    fn default() -> Self {
+        Self {
+            local_addr: None,
+            public_addr: None,
+            port_forwarding: true,
+            upnp_lease_duration_msec: None,
+            max_msg_size_allowed: None,
+            idle_timeout_msec: None,
+            keep_alive_interval_msec: None,
+            buffer_memory_cap: None,
+        }
+    }
+}
+
+/// Where the node finds the network it joins.
+///
+/// Contacts supplied here take precedence over the connection info file, which is only read
+/// when neither the spec nor the command line supply them.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContactsSpec {
+    /// Addresses of nodes to bootstrap from.
+    pub hard_coded: BTreeSet<SocketAddr>,
+    /// Genesis key of the network, in hex format.
+    pub genesis_key: Option<String>,
+}
+
+/// Where and how the node reports what it's doing.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingSpec {
+    /// Directory to write logs to, rather than stdout.
+    pub dir: Option<PathBuf>,
+    /// Whether logs are written as json.
+    pub json: bool,
+    /// Log level, from 0 (errors only) to 4 (everything).
+    pub verbosity: u8,
+    /// Whether resource usage is printed to stdout.
+    pub resource_usage: bool,
+}
+
+/// How the node's operator is alerted of critical events.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertsSpec {
+    /// Plain http:// URL to POST critical events to, as JSON.
+    pub webhook: Option<String>,
+    /// Command to run via the shell on critical events.
+    pub exec: Option<String>,
+}
+
+/// Endpoints the node serves to its operator.
+///
+/// Resource usage metrics are reported through the logs, see `LoggingSpec::resource_usage`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EndpointsSpec {
+    /// Port on localhost to serve operator commands on, e.g. to start a replication check.
+    pub control_port: Option<u16>,
+}
+
+/// Role the node takes in the network.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoleSpec {
+    /// Whether the node starts a new network, rather than joining an existing one.
+    pub genesis: bool,
+    /// The address to be credited when this node farms SafeCoin, as a hex formatted BLS public key.
+    pub wallet_id: Option<String>,
+}
+
+impl NodeSpec {
+    /// Reads the spec from the JSON file at `path`.
+    pub async fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read(path).await?;
+        serde_json::from_slice(&content).map_err(|err| {
+            Error::Configuration(format!("Invalid node spec at {}: {}", path.display(), err))
+        })
+    }
+
+    /// Checks the settings are consistent with each other.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(public_addr) = self.network.public_addr {
+            check_public_addr(public_addr).map_err(Error::Configuration)?;
+        }
+        self.to_config().validate().map_err(Error::Configuration)
+    }
+
+    /// The spec as the equivalent command line arguments.
+    pub(crate) fn to_config(&self) -> Config {
+        Config {
+            wallet_id: self.role.wallet_id.clone(),
+            max_capacity: self.storage.max_capacity,
+            root_dir: self.storage.root_dir.clone(),
+            verbose: self.logging.verbosity,
+            log_dir: self.logging.dir.clone(),
+            json_logs: self.logging.json,
+            resource_logs: self.logging.resource_usage,
+            first: self.role.genesis,
+            local_addr: self.network.local_addr,
+            public_addr: self.network.public_addr,
+            skip_igd: !self.network.port_forwarding,
+            hard_coded_contacts: self.contacts.hard_coded.clone(),
+            genesis_key: self.contacts.genesis_key.clone(),
+            max_msg_size_allowed: self.network.max_msg_size_allowed,
+            idle_timeout_msec: self.network.idle_timeout_msec,
+            keep_alive_interval_msec: self.network.keep_alive_interval_msec,
+            upnp_lease_duration: self.network.upnp_lease_duration_msec,
+            replication_factor: self.storage.replication_factor,
+            max_blob_size: self.storage.max_blob_size,
+            max_register_entry_size: self.storage.max_register_entry_size,
+            max_register_batch_ops: self.storage.max_register_batch_ops,
+            alert_webhook: self.alerts.webhook.clone(),
+            alert_exec: self.alerts.exec.clone(),
+            control_port: self.endpoints.control_port,
+            buffer_memory_cap: self.network.buffer_memory_cap,
+            ..Config::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NodeSpec;
+    use crate::node::Error;
+    use eyre::Result;
+
+    #[test]
+    fn partial_spec_takes_defaults() -> Result<()> {
+        let spec: NodeSpec = serde_json::from_str(
+            r#"{
+                "storage": { "max_capacity": 1073741824 },
+                "network": { "local_addr": "0.0.0.0:12000", "port_forwarding": false },
+                "contacts": { "hard_coded": ["203.0.113.7:12000"] },
+                "alerts": { "webhook": "http://localhost:8080/alerts" },
+                "endpoints": { "control_port": 12500 }
+            }"#,
+        )?;
+        spec.validate()?;
+
+        let config = spec.to_config();
+        assert_eq!(config.max_capacity(), 1_073_741_824);
+        assert!(config.skip_igd);
+        assert!(!config.is_first());
+        assert_eq!(config.hard_coded_contacts.len(), 1);
+        assert_eq!(config.control_port, Some(12500));
+        assert_eq!(
+            config.alert_webhook.as_deref(),
+            Some("http://localhost:8080/alerts")
+        );
+        assert_eq!(config.alert_exec, None);
+
+        // The first node can't learn its public address from peers.
+        let genesis: NodeSpec = serde_json::from_str(r#"{ "role": { "genesis": true } }"#)?;
+        assert!(matches!(genesis.validate(), Err(Error::Configuration(_))));
+
+        assert!(serde_json::from_str::<NodeSpec>(r#"{ "storage": { "size": 1 } }"#).is_err());
+
+        let limits: NodeSpec =
+            serde_json::from_str(r#"{ "storage": { "max_register_entry_size": 0 } }"#)?;
+        assert_eq!(limits.to_config().max_register_entry_size, Some(0));
+        assert!(matches!(limits.validate(), Err(Error::Configuration(_))));
+
+        let batches: NodeSpec =
+            serde_json::from_str(r#"{ "storage": { "max_register_batch_ops": 0 } }"#)?;
+        assert_eq!(batches.to_config().max_register_batch_ops, Some(0));
+        assert!(matches!(batches.validate(), Err(Error::Configuration(_))));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use thiserror::Error;
+
+/// The type returned by the prefix map methods.
+pub(crate) type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors updating or looking up the prefix map, shared by the client and the node.
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    /// No section is known to match the name or prefix.
+    #[error("No matching section.")]
+    NoMatchingSection,
+    /// The SAP received can't be trusted.
+    #[error("Section authority provider cannot be trusted: {0}")]
+    UntrustedSectionAuthProvider(String),
+    /// The proof chain received can't be trusted.
+    #[error("Proof chain cannot be trusted: {0}")]
+    UntrustedProofChain(String),
+}
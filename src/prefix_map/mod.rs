@@ -15,11 +15,12 @@
 //! covered and is automatically removed.
 //!
 
+mod errors;
 mod stats;
 
+pub(crate) use self::errors::{Error, Result};
 use self::stats::NetworkStats;
 use crate::messaging::{system::SectionAuth, SectionAuthorityProvider};
-use crate::routing::{Error, Result, SectionAuthUtils, SectionAuthorityProviderUtils};
 use bls::PublicKey as BlsPublicKey;
 use dashmap::{self, mapref::multiple::RefMulti, DashMap};
 use secured_linked_list::SecuredLinkedList;
@@ -54,7 +55,7 @@ impl NetworkPrefixMap {
     // TODO: remove this form public API since we shall not allow any insert/update withot a
     // proof chain, users shall have to call either `update` or `verify_with_chain_and_update` API.
     pub(crate) fn insert(&self, sap: SectionAuth<SectionAuthorityProvider>) -> bool {
-        let prefix = sap.value.prefix();
+        let prefix = sap.value.prefix;
         // Don't insert if any descendant is already present in the map.
         if self.descendants(&prefix).next().is_some() {
             return false;
@@ -114,8 +115,8 @@ impl NetworkPrefixMap {
         signed_section_auth: SectionAuth<SectionAuthorityProvider>,
         proof_chain: &SecuredLinkedList,
     ) -> Result<bool> {
-        let section_key = match self.section_by_prefix(&signed_section_auth.value.prefix()) {
-            Ok(sap) => sap.section_key(),
+        let section_key = match self.section_by_prefix(&signed_section_auth.value.prefix) {
+            Ok(sap) => sap.public_key_set.public_key(),
             Err(_) => self.genesis_pk,
         };
 
@@ -135,7 +136,7 @@ impl NetworkPrefixMap {
         section_chain: &SecuredLinkedList,
     ) -> Result<bool> {
         // Check if SAP signature is valid
-        if !signed_section_auth.self_verify() {
+        if !verify_section_auth(&signed_section_auth) {
             return Err(Error::UntrustedSectionAuthProvider(format!(
                 "invalid signature: {:?}",
                 signed_section_auth.value
@@ -222,7 +223,7 @@ impl NetworkPrefixMap {
     pub(crate) fn section_keys(&self) -> Vec<bls::PublicKey> {
         self.sections
             .iter()
-            .map(|e| e.value().value.section_key())
+            .map(|e| e.value().value.public_key_set.public_key())
             .collect()
     }
 
@@ -261,7 +262,7 @@ impl NetworkPrefixMap {
         let network_elders_count: usize = self
             .sections
             .iter()
-            .map(|e| e.value().value.elder_count())
+            .map(|e| e.value().value.elders.len())
             .sum();
         let total = network_elders_count as f64 / network_fraction;
 
@@ -310,7 +311,14 @@ impl NetworkPrefixMap {
     }
 }
 
-#[cfg(test)]
+// Checks the SAP is signed with the signature it comes with.
+fn verify_section_auth(section_auth: &SectionAuth<SectionAuthorityProvider>) -> bool {
+    bincode::serialize(&section_auth.value)
+        .map(|bytes| section_auth.sig.verify(&bytes))
+        .unwrap_or(false)
+}
+
+#[cfg(all(test, feature = "node"))]
 mod tests {
     use super::*;
     use crate::routing::{gen_section_authority_provider, section_signed};
@@ -0,0 +1,84 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! The types most applications need, to be glob imported: `use safe_network::prelude::*;`.
+//!
+//! What's exported here is the stable surface of the crate: it only changes in a breaking way
+//! along with the major version. Names which clash across subsystems, e.g. the client's and
+//! the node's `Error`, are exported with the subsystem as prefix.
+
+#[cfg(feature = "client")]
+pub use crate::client::client_api::{BlobAddress, FileHistory, FilesContainer};
+#[cfg(feature = "client")]
+pub use crate::client::{
+    Client, ClientEvent, Config as ClientConfig, Error as ClientError, Result as ClientResult,
+};
+
+#[cfg(feature = "node")]
+pub use crate::node::{Config as NodeConfig, Error as NodeError, Node};
+#[cfg(feature = "node")]
+pub use crate::routing::Event as RoutingEvent;
+
+pub use crate::types::{
+    ChunkAddress, DataAddress, Keypair, PublicKey, RegisterAddress, SecretKey, Signature, Token,
+};
+pub use crate::url::{ContentType, Scope, Url, XorUrl, XorUrlBase};
+pub use xor_name::XorName;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::any::type_name;
+
+    // Names every export of the prelude, so that dropping or renaming one, which breaks the
+    // applications glob importing it, fails the build rather than going unnoticed.
+    #[test]
+    fn prelude_exports_are_kept() {
+        #[allow(unused_mut)]
+        let mut exported = vec![
+            type_name::<ChunkAddress>(),
+            type_name::<ContentType>(),
+            type_name::<DataAddress>(),
+            type_name::<Keypair>(),
+            type_name::<PublicKey>(),
+            type_name::<RegisterAddress>(),
+            type_name::<Scope>(),
+            type_name::<SecretKey>(),
+            type_name::<Signature>(),
+            type_name::<Token>(),
+            type_name::<Url>(),
+            type_name::<XorName>(),
+            type_name::<XorUrl>(),
+            type_name::<XorUrlBase>(),
+        ];
+        #[cfg(feature = "client")]
+        exported.extend(vec![
+            type_name::<BlobAddress>(),
+            type_name::<Client>(),
+            type_name::<ClientConfig>(),
+            type_name::<ClientError>(),
+            type_name::<ClientEvent>(),
+            type_name::<ClientResult<()>>(),
+            type_name::<FileHistory>(),
+            type_name::<FilesContainer>(),
+        ]);
+        #[cfg(feature = "node")]
+        exported.extend(vec![
+            type_name::<Node>(),
+            type_name::<NodeConfig>(),
+            type_name::<NodeError>(),
+            type_name::<RoutingEvent>(),
+        ]);
+
+        // Each name is exported from a distinct item, none shadowing another.
+        let count = exported.len();
+        exported.sort_unstable();
+        exported.dedup();
+        assert_eq!(exported.len(), count);
+    }
+}
@@ -7,7 +7,12 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    delivery_group, split_barrier::SplitBarrier, Comm, Core, SignatureAggregator, KEY_CACHE_SIZE,
+    capacity_proofs::CapacityProofs, chunk_inventory::ChunkInventoryRounds,
+    data_migration::DataMigration, data_proofs::DataProofs, delivery_group,
+    existence_checks::ExistenceChecks, holder_proofs::HolderProofs,
+    members_updates::MembersUpdates, network_times::NetworkTimes,
+    replication_check::ReplicationCheck, resends::Resends, split_barrier::SplitBarrier, Comm, Core,
+    MigrationProgress, ReplicationReport, SignatureAggregator, KEY_CACHE_SIZE,
     RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY,
 };
 use crate::dbs::UsedSpace;
@@ -17,8 +22,8 @@ use crate::messaging::{
 };
 use crate::prefix_map::NetworkPrefixMap;
 use crate::routing::{
-    dkg::{DkgVoter, ProposalAggregator},
-    error::Result,
+    dkg::{DkgSessionInfo, DkgVoter, ProposalAggregator},
+    error::{Error, Result},
     node::Node,
     routing_api::command::Command,
     section::{ElderCandidatesUtils, NodeStateUtils, SectionKeyShare, SectionKeysProvider},
@@ -87,8 +92,23 @@ impl Core {
             root_storage_dir: self.root_storage_dir.clone(),
             used_space: self.used_space.clone(),
             capacity: self.capacity.clone(),
+            capacity_proofs: CapacityProofs::new(),
             chunk_storage: self.chunk_storage.clone(),
+            payment_store: self.payment_store.clone(),
+            key_share_backup: self.key_share_backup.clone(),
             liveness: self.liveness.clone(),
+            chunk_inventory: ChunkInventoryRounds::new(),
+            data_migration: DataMigration::new(),
+            replication_check: ReplicationCheck::new(),
+            members_updates: MembersUpdates::new(),
+            data_proofs: DataProofs::new(),
+            holder_proofs: HolderProofs::new(),
+            existence_checks: ExistenceChecks::new(),
+            resends: Resends::new(),
+            network_times: NetworkTimes::new(),
+            load_shedding: self.load_shedding.clone(),
+            replication_factor: self.replication_factor,
+            data_limits: self.data_limits,
         })
     }
 
@@ -141,7 +161,7 @@ impl Core {
         if self.section.prefix().matches(name) {
             Ok(self.section.authority_provider().clone())
         } else {
-            self.network.section_by_name(name)
+            self.network.section_by_name(name).map_err(Error::from)
         }
     }
 
@@ -157,6 +177,21 @@ impl Core {
         self.section_keys_provider.key_share()
     }
 
+    /// Returns the progress of handing off our data since our last change of role, if any.
+    pub(crate) fn data_migration(&self) -> Option<MigrationProgress> {
+        self.data_migration.progress()
+    }
+
+    /// Returns the outcome of the replication check last started at this Elder, if any.
+    pub(crate) fn replication_check(&self) -> Option<ReplicationReport> {
+        self.replication_check.report()
+    }
+
+    /// Returns the DKG sessions we take part in.
+    pub(crate) fn dkg_sessions(&self) -> Vec<DkgSessionInfo> {
+        self.dkg_voter.sessions()
+    }
+
     pub(crate) async fn send_event(&self, event: Event) {
         // Note: cloning the sender to avoid mutable access. Should have negligible cost.
         if self.event_tx.clone().send(event).await.is_err() {
@@ -168,7 +203,19 @@ impl Core {
     //   ---------------------------------- Mut ------------------------------------------
     // ----------------------------------------------------------------------------------------
 
-    pub(crate) fn handle_timeout(&mut self, token: u64) -> Result<Vec<Command>> {
+    pub(crate) async fn handle_timeout(&mut self, token: u64) -> Result<Vec<Command>> {
+        if token == self.chunk_inventory.timer_token() {
+            return self.handle_chunk_inventory_timeout().await;
+        }
+        if token == self.data_migration.timer_token() {
+            return self.handle_data_migration_timeout();
+        }
+        if token == self.replication_check.timer_token() {
+            return self.handle_replication_check_timeout().await;
+        }
+        if token == self.capacity_proofs.timer_token() {
+            return self.handle_capacity_check_timeout().await;
+        }
         self.dkg_voter
             .handle_timeout(&self.node, token, *self.section_chain().last_key())
     }
@@ -20,7 +20,8 @@ use crate::routing::{
     messages::{NodeMsgAuthorityUtils, WireMsgUtils},
     node::Node,
     peer::PeerUtils,
-    SectionAuthorityProviderUtils, FIRST_SECTION_MAX_AGE, FIRST_SECTION_MIN_AGE, MIN_ADULT_AGE,
+    SectionAuthorityProviderUtils, FIRST_SECTION_MAX_AGE, FIRST_SECTION_MIN_AGE, MAX_CLOCK_SKEW,
+    MIN_ADULT_AGE,
 };
 use crate::types::PublicKey;
 
@@ -33,7 +34,7 @@ use tokio::sync::mpsc;
 use tracing::Instrument;
 use xor_name::{Prefix, XorName};
 
-/// Join the network as new node.
+/// Join the network as new node, committing to `committed_capacity` bytes of storage.
 ///
 /// NOTE: It's not guaranteed this function ever returns. This can happen due to messages being
 /// lost in transit or other reasons. It's the responsibility of the caller to handle this case,
@@ -44,12 +45,14 @@ pub(crate) async fn join_network(
     incoming_conns: &mut mpsc::Receiver<ConnectionEvent>,
     bootstrap_addr: SocketAddr,
     genesis_key: BlsPublicKey,
+    committed_capacity: u64,
 ) -> Result<(Node, Section)> {
     let (send_tx, send_rx) = mpsc::channel(1);
 
     let span = trace_span!("bootstrap", name = %node.name());
 
-    let state = Join::new(node, send_tx, incoming_conns);
+    let mut state = Join::new(node, send_tx, incoming_conns);
+    state.committed_capacity = Some(committed_capacity);
 
     future::join(
         state.run(bootstrap_addr, genesis_key),
@@ -66,6 +69,8 @@ struct Join<'a> {
     // Receiver for incoming messages.
     recv_rx: &'a mut mpsc::Receiver<ConnectionEvent>,
     node: Node,
+    // Storage capacity advertised in our join requests, if any.
+    committed_capacity: Option<u64>,
 }
 
 impl<'a> Join<'a> {
@@ -78,6 +83,7 @@ impl<'a> Join<'a> {
             send_tx,
             recv_rx,
             node,
+            committed_capacity: None,
         }
     }
 
@@ -113,10 +119,7 @@ impl<'a> Join<'a> {
         // We send a first join request to obtain the resource challenge, which
         // we will then use to generate the challenge proof and send the
         // `JoinRequest` again with it.
-        let join_request = JoinRequest {
-            section_key,
-            resource_proof_response: None,
-        };
+        let join_request = JoinRequest::new(section_key, None);
 
         self.send_join_requests(join_request, &recipients, section_key)
             .await?;
@@ -141,6 +144,20 @@ impl<'a> Join<'a> {
                     error!("Network is set to not taking any new joining node, try join later.");
                     return Err(Error::TryJoinLater);
                 }
+                JoinResponse::Rejected(JoinRejectionReason::ClockSkew { elder_timestamp }) => {
+                    error!(
+                        "Node cannot join the network since its clock is off by more than {:?}",
+                        MAX_CLOCK_SKEW
+                    );
+                    return Err(Error::ClockSkew { elder_timestamp });
+                }
+                JoinResponse::Rejected(JoinRejectionReason::DuplicateIdentity(addr)) => {
+                    error!(
+                        "Node cannot join the network since a node with its name is already a member, at {}",
+                        addr
+                    );
+                    return Err(Error::DuplicateIdentity(addr));
+                }
                 JoinResponse::Approval {
                     section_auth,
                     genesis_key,
@@ -200,10 +217,7 @@ impl<'a> Join<'a> {
                             section_auth, sender
                         );
                         section_key = section_auth.section_key();
-                        let join_request = JoinRequest {
-                            section_key,
-                            resource_proof_response: None,
-                        };
+                        let join_request = JoinRequest::new(section_key, None);
 
                         recipients = new_recipients;
                         self.send_join_requests(join_request, &recipients, section_key)
@@ -245,10 +259,7 @@ impl<'a> Join<'a> {
                             section_auth, sender
                         );
                         section_key = section_auth.section_key();
-                        let join_request = JoinRequest {
-                            section_key,
-                            resource_proof_response: None,
-                        };
+                        let join_request = JoinRequest::new(section_key, None);
 
                         recipients = new_recipients;
 
@@ -272,15 +283,15 @@ impl<'a> Join<'a> {
                     let mut prover = rp.create_prover(data.clone());
                     let solution = prover.solve();
 
-                    let join_request = JoinRequest {
+                    let join_request = JoinRequest::new(
                         section_key,
-                        resource_proof_response: Some(ResourceProofResponse {
+                        Some(ResourceProofResponse {
                             solution,
                             data,
                             nonce,
                             nonce_signature,
                         }),
-                    };
+                    );
                     let recipients = &[(src_name, sender)];
                     self.send_join_requests(join_request, recipients, section_key)
                         .await?;
@@ -295,6 +306,10 @@ impl<'a> Join<'a> {
         recipients: &[(XorName, SocketAddr)],
         section_key: BlsPublicKey,
     ) -> Result<()> {
+        let join_request = match self.committed_capacity {
+            Some(capacity) => join_request.with_committed_capacity(capacity),
+            None => join_request,
+        };
         info!("Sending {:?} to {:?}", join_request, recipients);
 
         let node_msg = SystemMsg::JoinRequest(Box::new(join_request));
@@ -362,9 +377,7 @@ impl<'a> Join<'a> {
             };
 
             match join_response {
-                JoinResponse::ResourceChallenge { .. }
-                | JoinResponse::Rejected(JoinRejectionReason::NodeNotReachable(_))
-                | JoinResponse::Rejected(JoinRejectionReason::JoinsDisallowed) => {
+                JoinResponse::ResourceChallenge { .. } | JoinResponse::Rejected(_) => {
                     return Ok((join_response, sender, src_name));
                 }
                 JoinResponse::Retry(ref section_auth)
@@ -17,7 +17,7 @@ use std::{
 };
 use tokio::sync::RwLock;
 
-// The number of separate copies of a chunk which should be maintained.
+// The default number of separate copies of a chunk which should be maintained.
 pub(crate) const CHUNK_COPY_COUNT: usize = 4;
 pub(crate) const MIN_LEVEL_WHEN_FULL: u8 = 9; // considered full when >= 90 %.
 
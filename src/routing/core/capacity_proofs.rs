@@ -0,0 +1,680 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Command, Core, ResourcePressure, Result, MIN_LEVEL_WHEN_FULL};
+use crate::messaging::{
+    data::StorageLevel,
+    system::{NodeCmd, SystemMsg},
+    DstLocation,
+};
+use crate::routing::{peer::PeerUtils, routing_api::command::next_timer_token};
+use dashmap::DashMap;
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, SeekFrom, Write},
+    iter,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncSeekExt},
+    task,
+};
+use xor_name::XorName;
+
+/// How often Elders move the capacity spot-checks of their Adults on: fillers stored at one
+/// interval are read back at the next, and challenges not answered by then are failed. As
+/// fillers take up the free capacity Adults advertised, they're only stored every so often.
+const CAPACITY_CHECK_INTERVAL: Duration = Duration::from_secs(3 * 60 * 60);
+// Fillers are derived from their seed block by block, for Elders to derive the samples they
+// check without deriving whole fillers.
+const FILLER_BLOCK_SIZE: u64 = 1024 * 1024;
+// Size of the filler samples Adults are challenged to read back, and how many of them.
+const SAMPLE_SIZE: u64 = 4 * 1024;
+const SAMPLE_COUNT: usize = 16;
+// Share of the capacity the spot-checked Adults advertised they must be trusted with, below
+// which nodes are let in to make up for it.
+const MIN_TRUSTED_SHARE: f64 = 0.9;
+// Directory of the storage root Adults keep the filler they're challenged with in.
+const FILLER_DIR: &str = "capacity_filler";
+
+/// The storage capacity an Adult advertised on joining, and how it fared at the spot-checks of
+/// it by the Elder closest to it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CapacityRecord {
+    /// Capacity advertised, in bytes.
+    pub advertised: u64,
+    /// Number of spot-checks passed.
+    pub passed: u32,
+    /// Number of spot-checks failed: filler refused while not full, read back wrong, or
+    /// challenges left unanswered.
+    pub failed: u32,
+    /// Whether the last spot-check completed was passed, if any completed yet.
+    pub last_passed: Option<bool>,
+}
+
+impl CapacityRecord {
+    /// The capacity the Adult is trusted with: what it advertised once it passed its last
+    /// spot-check, which has it fill its free capacity, and none until then.
+    pub fn trusted_capacity(&self) -> u64 {
+        if self.last_passed == Some(true) {
+            self.advertised
+        } else {
+            0
+        }
+    }
+}
+
+/// The capacity advertised by the Adults of our section, and the spot-checks of it this Elder
+/// has under way. A spot-check has an Adult fill the free capacity it advertised with filler
+/// derived from a seed, then read samples of it back an interval later, hashed with a salt.
+#[derive(Clone, Debug)]
+pub(crate) struct CapacityProofs {
+    timer_token: u64,
+    records: Arc<DashMap<XorName, CapacityRecord>>,
+    // Keyed by the Adult challenged.
+    challenges: Arc<DashMap<XorName, Challenge>>,
+}
+
+#[derive(Debug)]
+struct Challenge {
+    id: XorName,
+    // The filler is derived from the seed, so it isn't kept around until read back.
+    seed: XorName,
+    size: u64,
+    stage: Stage,
+}
+
+#[derive(Debug, PartialEq)]
+enum Stage {
+    Storing,
+    Stored,
+    Reading { salt: XorName, offsets: Vec<u64> },
+}
+
+// Block `index` of the filler derived from `seed`.
+fn filler_block(seed: &XorName, index: u64) -> Vec<u8> {
+    let block_seed = XorName::from_content_parts(&[&seed.0[..], &index.to_be_bytes()[..]]);
+    let mut block = vec![0; FILLER_BLOCK_SIZE as usize];
+    StdRng::from_seed(block_seed.0).fill_bytes(&mut block);
+    block
+}
+
+// The `len` bytes at `offset` of the filler derived from `seed`.
+fn filler_range(seed: &XorName, offset: u64, len: u64) -> Vec<u8> {
+    let end = offset + len;
+    let mut bytes = Vec::with_capacity(len as usize);
+    let mut position = offset;
+    while position < end {
+        let index = position / FILLER_BLOCK_SIZE;
+        let block_start = index * FILLER_BLOCK_SIZE;
+        let block = filler_block(seed, index);
+        let from = (position - block_start) as usize;
+        let to = (end - block_start).min(FILLER_BLOCK_SIZE) as usize;
+        bytes.extend_from_slice(&block[from..to]);
+        position = block_start + FILLER_BLOCK_SIZE;
+    }
+    bytes
+}
+
+// Size of the filler an Adult which advertised `advertised` bytes and reported `level` is
+// challenged with: its free capacity, short of what it'd be deemed full at.
+fn filler_size(advertised: u64, level: u8) -> u64 {
+    advertised / 10 * u64::from(MIN_LEVEL_WHEN_FULL.saturating_sub(level))
+}
+
+fn proof(salt: &XorName, samples: &[Vec<u8>]) -> XorName {
+    let parts: Vec<&[u8]> = iter::once(&salt.0[..])
+        .chain(samples.iter().map(Vec::as_slice))
+        .collect();
+    XorName::from_content_parts(&parts)
+}
+
+impl CapacityProofs {
+    pub(crate) fn new() -> Self {
+        Self {
+            timer_token: next_timer_token(),
+            records: Arc::new(DashMap::new()),
+            challenges: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Token of the timeout spot-checks are moved on on.
+    pub(crate) fn timer_token(&self) -> u64 {
+        self.timer_token
+    }
+
+    /// The capacity advertised by each Adult, and how it fared at the spot-checks of it.
+    pub(crate) fn records(&self) -> BTreeMap<XorName, CapacityRecord> {
+        self.records
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect()
+    }
+
+    /// Records the capacity `adult` advertised, on joining or when asked for it, unless we
+    /// already had.
+    pub(crate) fn advertised(&self, adult: XorName, capacity: u64) {
+        let _ = self.records.entry(adult).or_insert(CapacityRecord {
+            advertised: capacity,
+            ..CapacityRecord::default()
+        });
+    }
+
+    /// Stops tracking the Adults which aren't members anymore.
+    pub(crate) fn retain_members_only(&self, members: &BTreeSet<XorName>) {
+        self.records.retain(|name, _| members.contains(name));
+        self.challenges.retain(|name, _| members.contains(name));
+    }
+
+    // Share of the capacity advertised by the Adults which completed a spot-check they're
+    // trusted with, if any did.
+    fn trusted_share(&self) -> Option<f64> {
+        let (advertised, trusted) = self
+            .records
+            .iter()
+            .filter(|entry| entry.last_passed.is_some())
+            .fold((0_u64, 0_u64), |(advertised, trusted), entry| {
+                (
+                    advertised.saturating_add(entry.advertised),
+                    trusted.saturating_add(entry.trusted_capacity()),
+                )
+            });
+        if advertised == 0 {
+            None
+        } else {
+            Some(trusted as f64 / advertised as f64)
+        }
+    }
+
+    // Moves the spot-check of `adult`, which reported `level`, on, returning the challenge to
+    // send it, if any, and whether it failed the previous one by leaving it unanswered. Adults
+    // we don't know the capacity of are asked for it, and those with no free capacity left
+    // aren't challenged.
+    fn next_challenge(&self, adult: XorName, level: u8) -> (Option<NodeCmd>, bool) {
+        let advertised = match self.records.get(&adult) {
+            Some(record) => record.advertised,
+            None => return (Some(NodeCmd::ReportCapacity), false),
+        };
+
+        if let Some(mut challenge) = self.challenges.get_mut(&adult) {
+            if challenge.stage == Stage::Stored {
+                let salt = XorName::random();
+                let mut rng = rand::thread_rng();
+                let offsets: Vec<_> = (0..SAMPLE_COUNT)
+                    .map(|_| rng.gen_range(0, challenge.size - SAMPLE_SIZE + 1))
+                    .collect();
+                challenge.stage = Stage::Reading {
+                    salt,
+                    offsets: offsets.clone(),
+                };
+                let msg = NodeCmd::ReadCapacityFiller {
+                    id: challenge.id,
+                    salt,
+                    offsets,
+                };
+                return (Some(msg), false);
+            }
+        }
+        let unanswered = self.challenges.remove(&adult).is_some();
+        if unanswered {
+            self.record_outcome(adult, false);
+        }
+
+        let size = filler_size(advertised, level);
+        if size < SAMPLE_SIZE {
+            return (None, unanswered);
+        }
+        let challenge = Challenge {
+            id: XorName::random(),
+            seed: XorName::random(),
+            size,
+            stage: Stage::Storing,
+        };
+        let msg = NodeCmd::StoreCapacityFiller {
+            id: challenge.id,
+            seed: challenge.seed,
+            size,
+        };
+        let _ = self.challenges.insert(adult, challenge);
+        (Some(msg), unanswered)
+    }
+
+    // Records whether `adult` stored the filler of challenge `id`, returning `Some(false)` if
+    // that failed the spot-check: refusing the filler is only fair from a full Adult, whose
+    // challenge is dropped.
+    fn stored(&self, adult: XorName, id: XorName, stored: bool, full: bool) -> Option<bool> {
+        let mut challenge = self.challenges.get_mut(&adult)?;
+        if challenge.id != id || challenge.stage != Stage::Storing {
+            return None;
+        }
+        if stored {
+            challenge.stage = Stage::Stored;
+            return None;
+        }
+        drop(challenge);
+        let _ = self.challenges.remove(&adult);
+        if full {
+            return None;
+        }
+        self.record_outcome(adult, false);
+        Some(false)
+    }
+
+    // Checks the proof `adult` read the filler samples of challenge `id` back with, returning
+    // whether it passed the spot-check.
+    fn read(&self, adult: XorName, id: XorName, proof_read: Option<XorName>) -> Option<bool> {
+        match self.challenges.get(&adult) {
+            Some(challenge) if challenge.id == id => {
+                if !matches!(challenge.stage, Stage::Reading { .. }) {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+        let (_, challenge) = self.challenges.remove(&adult)?;
+        let (salt, offsets) = match challenge.stage {
+            Stage::Reading { salt, offsets } => (salt, offsets),
+            _ => return None,
+        };
+        let samples: Vec<_> = offsets
+            .iter()
+            .map(|offset| filler_range(&challenge.seed, *offset, SAMPLE_SIZE))
+            .collect();
+        let passed = proof_read == Some(proof(&salt, &samples));
+        self.record_outcome(adult, passed);
+        Some(passed)
+    }
+
+    fn record_outcome(&self, adult: XorName, passed: bool) {
+        if let Some(mut record) = self.records.get_mut(&adult) {
+            if passed {
+                record.passed += 1;
+            } else {
+                record.failed += 1;
+            }
+            record.last_passed = Some(passed);
+        }
+    }
+}
+
+impl Core {
+    /// Schedules moving the capacity spot-checks on.
+    pub(crate) fn schedule_capacity_checks(&self) -> Command {
+        Command::ScheduleTimeout {
+            duration: CAPACITY_CHECK_INTERVAL,
+            token: self.capacity_proofs.timer_token(),
+        }
+    }
+
+    /// Returns the capacity advertised by the Adults of our section, and how they fared at the
+    /// spot-checks of it by this Elder.
+    pub(crate) fn capacity_records(&self) -> BTreeMap<XorName, CapacityRecord> {
+        self.capacity_proofs.records()
+    }
+
+    /// Moves the spot-check of each Adult this Elder is the closest Elder to on: those which
+    /// stored their filler are challenged to read samples of it back, the others to store new
+    /// filler.
+    pub(crate) async fn handle_capacity_check_timeout(&self) -> Result<Vec<Command>> {
+        let mut commands = vec![self.schedule_capacity_checks()];
+        if !self.is_elder() {
+            return Ok(commands);
+        }
+        if self.resource_pressure() == ResourcePressure::High {
+            debug!(
+                "Putting capacity spot-checks off to the next interval, under resource pressure"
+            );
+            return Ok(commands);
+        }
+
+        let our_name = self.node().name();
+        let elders = self.section().authority_provider().names();
+        let adults: Vec<_> = self.section().adults().map(|peer| *peer.name()).collect();
+        let levels = self.capacity.levels().await;
+        let mut any_unanswered = false;
+        for adult in adults {
+            let closest_elder = elders
+                .iter()
+                .min_by(|lhs, rhs| adult.cmp_distance(lhs, rhs))
+                .copied();
+            if closest_elder != Some(our_name) {
+                continue;
+            }
+            let level = levels.get(&adult).map_or(0, StorageLevel::value);
+            let (cmd, unanswered) = self.capacity_proofs.next_challenge(adult, level);
+            if unanswered {
+                warn!("Adult {} left its capacity spot-check unanswered", adult);
+                self.capacity_discrepancy(adult).await;
+                any_unanswered = true;
+            }
+            if let Some(cmd) = cmd {
+                commands.extend(self.send_node_msg_to_targets(
+                    SystemMsg::NodeCmd(cmd),
+                    iter::once(adult).collect(),
+                    false,
+                )?);
+            }
+        }
+        if any_unanswered {
+            commands.extend(self.check_trusted_capacity());
+        }
+
+        Ok(commands)
+    }
+
+    /// Records the capacity an Adult reported committing to, when asked for it.
+    pub(crate) fn handle_capacity_reported(&self, adult: XorName, capacity: u64) {
+        debug!("{} commits to {} bytes of storage", adult, capacity);
+        self.capacity_proofs.advertised(adult, capacity);
+    }
+
+    /// Records whether an Adult accepted to store the filler it was challenged with.
+    pub(crate) async fn handle_capacity_filler_stored(
+        &self,
+        adult: XorName,
+        id: XorName,
+        stored: bool,
+    ) -> Result<Vec<Command>> {
+        let full = self.full_adults().await.contains(&adult);
+        match self.capacity_proofs.stored(adult, id, stored, full) {
+            Some(false) => {
+                warn!("Adult {} refused capacity filler while not full", adult);
+                self.capacity_discrepancy(adult).await;
+                Ok(self.check_trusted_capacity())
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Checks the proof an Adult read samples of the filler it was challenged with back with.
+    pub(crate) async fn handle_capacity_filler_read(
+        &self,
+        adult: XorName,
+        id: XorName,
+        proof: Option<XorName>,
+    ) -> Result<Vec<Command>> {
+        match self.capacity_proofs.read(adult, id, proof) {
+            Some(true) => {
+                trace!("Adult {} passed its capacity spot-check", adult);
+                Ok(vec![])
+            }
+            Some(false) => {
+                warn!("Adult {} failed to read its capacity filler back", adult);
+                self.capacity_discrepancy(adult).await;
+                Ok(self.check_trusted_capacity())
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    // An Adult which doesn't have the capacity it advertised counts against its liveness, and
+    // is deemed full, for chunks not to be sent its way.
+    async fn capacity_discrepancy(&self, adult: XorName) {
+        let count = self.liveness.record_capacity_discrepancy(adult);
+        debug!(
+            "Adult {} failed {} capacity spot-checks so far",
+            adult, count
+        );
+
+        if let Ok(level) = StorageLevel::from(MIN_LEVEL_WHEN_FULL) {
+            if self.capacity.set_adult_level(adult, level).await {
+                info!(
+                    "Deeming Adult {} full after failing its capacity spot-check",
+                    adult
+                );
+            }
+        }
+    }
+
+    // Lets nodes in once the Adults spot-checked are trusted with too small a share of the
+    // capacity they advertised, to make up for it.
+    fn check_trusted_capacity(&self) -> Vec<Command> {
+        match self.capacity_proofs.trusted_share() {
+            Some(share) if share < MIN_TRUSTED_SHARE => {
+                info!(
+                    "Adults are trusted with {:.0}% of the capacity they advertised, letting nodes in",
+                    share * 100.0
+                );
+                vec![Command::SetJoinsAllowed(true)]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Reports the capacity we commit to, at an Adult, to the Elder asking for it.
+    pub(crate) fn handle_report_capacity(&self, requesting_elder: XorName) -> Vec<Command> {
+        let capacity = self.used_space.max_capacity();
+        let msg = SystemMsg::NodeCmd(NodeCmd::CapacityReported { capacity });
+        vec![self.capacity_response(msg, requesting_elder)]
+    }
+
+    /// Stores the filler we're challenged with, at an Adult, replacing any we stored before,
+    /// and reports back whether we accepted to. The filler is written in the background, as it
+    /// takes up our free capacity, so writing it failing only shows when it's read back.
+    pub(crate) async fn handle_store_capacity_filler(
+        &self,
+        requesting_elder: XorName,
+        id: XorName,
+        seed: XorName,
+        size: u64,
+    ) -> Result<Vec<Command>> {
+        let stored = self.used_space.can_consume(size).await;
+        if stored {
+            let dir = self.capacity_filler_dir();
+            let _ = task::spawn_blocking(move || {
+                if let Err(error) = write_capacity_filler(&dir, id, &seed, size) {
+                    warn!("Failed to store capacity filler: {:?}", error);
+                }
+            });
+        }
+
+        let msg = SystemMsg::NodeCmd(NodeCmd::CapacityFillerStored { id, stored });
+        Ok(vec![self.capacity_response(msg, requesting_elder)])
+    }
+
+    /// Reads samples of the filler we stored back, at an Adult, dropping it, and reports back
+    /// their hash with the salt.
+    pub(crate) async fn handle_read_capacity_filler(
+        &self,
+        requesting_elder: XorName,
+        id: XorName,
+        salt: XorName,
+        offsets: Vec<u64>,
+    ) -> Result<Vec<Command>> {
+        let path = self.capacity_filler_dir().join(hex::encode(id.0));
+        let proof = match read_samples(&path, &offsets).await {
+            Ok(samples) => Some(proof(&salt, &samples)),
+            Err(error) => {
+                warn!("Failed to read capacity filler back: {:?}", error);
+                None
+            }
+        };
+        let _ = fs::remove_file(&path).await;
+
+        let msg = SystemMsg::NodeCmd(NodeCmd::CapacityFillerRead { id, proof });
+        Ok(vec![self.capacity_response(msg, requesting_elder)])
+    }
+
+    fn capacity_filler_dir(&self) -> PathBuf {
+        self.root_storage_dir.join(FILLER_DIR)
+    }
+
+    fn capacity_response(&self, msg: SystemMsg, requesting_elder: XorName) -> Command {
+        let dst = DstLocation::Node {
+            name: requesting_elder,
+            section_pk: *self.section().chain().last_key(),
+        };
+        Command::PrepareNodeMsgToSend {
+            msg,
+            dst,
+            trace: None,
+        }
+    }
+}
+
+// Writes the filler derived from `seed` to `dir`, replacing any written before.
+fn write_capacity_filler(dir: &Path, id: XorName, seed: &XorName, size: u64) -> io::Result<()> {
+    if dir.exists() {
+        std::fs::remove_dir_all(dir)?;
+    }
+    std::fs::create_dir_all(dir)?;
+    let mut file = std::fs::File::create(dir.join(hex::encode(id.0)))?;
+    let mut written = 0;
+    let mut index = 0;
+    while written < size {
+        let len = (size - written).min(FILLER_BLOCK_SIZE);
+        file.write_all(&filler_block(seed, index)[..len as usize])?;
+        written += len;
+        index += 1;
+    }
+    file.sync_all()
+}
+
+async fn read_samples(path: &Path, offsets: &[u64]) -> io::Result<Vec<Vec<u8>>> {
+    let mut file = fs::File::open(path).await?;
+    let mut samples = Vec::with_capacity(offsets.len());
+    for offset in offsets {
+        let _ = file.seek(SeekFrom::Start(*offset)).await?;
+        let mut sample = vec![0; SAMPLE_SIZE as usize];
+        let _ = file.read_exact(&mut sample).await?;
+        samples.push(sample);
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        filler_block, filler_range, filler_size, proof, CapacityProofs, FILLER_BLOCK_SIZE,
+        SAMPLE_SIZE,
+    };
+    use crate::messaging::system::NodeCmd;
+    use eyre::{bail, Result};
+    use xor_name::XorName;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn fillers_are_sized_against_the_free_capacity() {
+        assert_eq!(filler_size(10 * GB, 0), 9 * GB);
+        assert_eq!(filler_size(10 * GB, 4), 5 * GB);
+        assert_eq!(filler_size(10 * GB, 9), 0);
+        assert_eq!(filler_size(10 * GB, 10), 0);
+    }
+
+    #[test]
+    fn filler_ranges_span_blocks() {
+        let seed = XorName::random();
+        let (first, second) = (filler_block(&seed, 0), filler_block(&seed, 1));
+        let range = filler_range(&seed, FILLER_BLOCK_SIZE - 2, 4);
+        assert_eq!(range[..2], first[first.len() - 2..]);
+        assert_eq!(range[2..], second[..2]);
+    }
+
+    #[test]
+    fn fillers_read_back_pass_the_spot_check() -> Result<()> {
+        let proofs = CapacityProofs::new();
+        let adult = XorName::random();
+        assert!(matches!(
+            proofs.next_challenge(adult, 0),
+            (Some(NodeCmd::ReportCapacity), false)
+        ));
+        proofs.advertised(adult, 10 * GB);
+
+        let (id, seed) = match proofs.next_challenge(adult, 2) {
+            (Some(NodeCmd::StoreCapacityFiller { id, seed, size }), false) => {
+                assert_eq!(size, 7 * GB);
+                (id, seed)
+            }
+            other => bail!("Unexpected challenge: {:?}", other),
+        };
+        // Answers to other challenges are ignored.
+        assert_eq!(proofs.stored(adult, XorName::random(), true, false), None);
+        assert_eq!(proofs.stored(adult, id, true, false), None);
+        assert_eq!(proofs.records()[&adult].trusted_capacity(), 0);
+
+        let (salt, offsets) = match proofs.next_challenge(adult, 2) {
+            (
+                Some(NodeCmd::ReadCapacityFiller {
+                    id: read_id,
+                    salt,
+                    offsets,
+                }),
+                false,
+            ) if read_id == id => (salt, offsets),
+            other => bail!("Unexpected challenge: {:?}", other),
+        };
+        assert!(offsets.iter().all(|offset| offset + SAMPLE_SIZE <= 7 * GB));
+        let samples: Vec<_> = offsets
+            .iter()
+            .map(|offset| filler_range(&seed, *offset, SAMPLE_SIZE))
+            .collect();
+        assert_eq!(
+            proofs.read(adult, id, Some(proof(&salt, &samples))),
+            Some(true)
+        );
+        let record = proofs.records()[&adult];
+        assert_eq!((record.passed, record.failed), (1, 0));
+        assert_eq!(record.trusted_capacity(), record.advertised);
+        assert_eq!(proofs.trusted_share(), Some(1.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn discrepancies_fail_the_spot_check() -> Result<()> {
+        let proofs = CapacityProofs::new();
+        let adult = XorName::random();
+        proofs.advertised(adult, GB);
+        // Advertising again doesn't reset the record.
+        proofs.advertised(adult, 10 * GB);
+
+        // Left unanswered.
+        let _ = proofs.next_challenge(adult, 0);
+        let id = match proofs.next_challenge(adult, 0) {
+            (Some(NodeCmd::StoreCapacityFiller { id, size, .. }), true) => {
+                assert_eq!(size, filler_size(GB, 0));
+                id
+            }
+            other => bail!("Unexpected challenge: {:?}", other),
+        };
+        // Refused while full, which is fair.
+        assert_eq!(proofs.stored(adult, id, false, true), None);
+        // Refused while not full.
+        let id = match proofs.next_challenge(adult, 0) {
+            (Some(NodeCmd::StoreCapacityFiller { id, .. }), false) => id,
+            other => bail!("Unexpected challenge: {:?}", other),
+        };
+        assert_eq!(proofs.stored(adult, id, false, false), Some(false));
+        // Read back wrong.
+        let id = match proofs.next_challenge(adult, 0) {
+            (Some(NodeCmd::StoreCapacityFiller { id, .. }), false) => id,
+            other => bail!("Unexpected challenge: {:?}", other),
+        };
+        assert_eq!(proofs.stored(adult, id, true, false), None);
+        let _ = proofs.next_challenge(adult, 0);
+        assert_eq!(proofs.read(adult, id, Some(XorName::random())), Some(false));
+
+        let record = proofs.records()[&adult];
+        assert_eq!((record.passed, record.failed), (0, 3));
+        assert_eq!(record.trusted_capacity(), 0);
+        assert_eq!(proofs.trusted_share(), Some(0.0));
+
+        // Full Adults aren't challenged.
+        assert!(proofs.next_challenge(adult, 9).0.is_none());
+
+        proofs.retain_members_only(&Default::default());
+        assert!(proofs.records().is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Command, Core, ResourcePressure, Result};
+use crate::messaging::{
+    system::{ChunkInventory, NodeCmd, SystemMsg},
+    DstLocation,
+};
+use crate::routing::{peer::PeerUtils, routing_api::command::next_timer_token};
+use crate::types::ChunkAddress;
+use dashmap::DashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use xor_name::XorName;
+
+/// How often Elders run inventory rounds among their Adults.
+const CHUNK_INVENTORY_INTERVAL: Duration = Duration::from_secs(10 * 60);
+// Cells of the inventories of a first round, enough for about 60 chunks missing between a pair.
+const INITIAL_CELLS: u32 = 96;
+// Inventories are doubled in size on failing to list the differences back, up to this.
+const MAX_CELLS: u32 = 96 * 32;
+// Rounds whose reports haven't all come in by then are dropped.
+const ROUND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Inventory rounds an Elder is waiting on the reports of. A round finds out which chunks an
+/// Adult and its closest Adult miss by comparing compact digests of what they hold, rather
+/// than lists of the chunks, so they can be replicated between them.
+#[derive(Clone, Debug)]
+pub(crate) struct ChunkInventoryRounds {
+    timer_token: u64,
+    // Keyed by the pair of Adults compared, in order.
+    rounds: Arc<DashMap<(XorName, XorName), Round>>,
+}
+
+#[derive(Debug)]
+struct Round {
+    adults: BTreeSet<XorName>,
+    cells: u32,
+    reports: BTreeMap<XorName, ChunkInventory>,
+    started: Instant,
+}
+
+impl ChunkInventoryRounds {
+    pub(crate) fn new() -> Self {
+        Self {
+            timer_token: next_timer_token(),
+            rounds: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Token of the timeout rounds are started on.
+    pub(crate) fn timer_token(&self) -> u64 {
+        self.timer_token
+    }
+}
+
+fn pair(lhs: XorName, rhs: XorName) -> (XorName, XorName) {
+    if lhs < rhs {
+        (lhs, rhs)
+    } else {
+        (rhs, lhs)
+    }
+}
+
+// The holders of a chunk among the given Adults, as both Adults of a pair work them out.
+pub(super) fn holders_of(
+    name: &XorName,
+    adults: &BTreeSet<XorName>,
+    copy_count: usize,
+) -> BTreeSet<XorName> {
+    let mut adults: Vec<_> = adults.iter().copied().collect();
+    adults.sort_by(|lhs, rhs| name.cmp_distance(lhs, rhs));
+    adults.into_iter().take(copy_count).collect()
+}
+
+impl Core {
+    /// Schedules the next inventory rounds.
+    pub(crate) fn schedule_chunk_inventory(&self) -> Command {
+        Command::ScheduleTimeout {
+            duration: CHUNK_INVENTORY_INTERVAL,
+            token: self.chunk_inventory.timer_token(),
+        }
+    }
+
+    /// Starts a round for each Adult this Elder is the closest Elder to, comparing its inventory
+    /// with that of its closest Adult, with which it shares the most chunks.
+    ///
+    /// Holders are worked out among the Adults which aren't full, which are those new chunks go
+    /// to. Chunks held by full Adults aren't repaired through rounds.
+    pub(crate) async fn handle_chunk_inventory_timeout(&self) -> Result<Vec<Command>> {
+        let mut commands = vec![self.schedule_chunk_inventory()];
+        if !self.is_elder() {
+            return Ok(commands);
+        }
+        if self.resource_pressure() == ResourcePressure::High {
+            debug!("Putting inventory rounds off to the next interval, under resource pressure");
+            return Ok(commands);
+        }
+
+        self.chunk_inventory
+            .rounds
+            .retain(|_, round| round.started.elapsed() < ROUND_TIMEOUT);
+
+        let our_name = self.node().name();
+        let elders = self.section().authority_provider().names();
+        let full_adults = self.full_adults().await;
+        let adults: BTreeSet<_> = self
+            .section()
+            .adults()
+            .map(|peer| *peer.name())
+            .filter(|name| !full_adults.contains(name))
+            .collect();
+
+        let mut pairs = BTreeSet::new();
+        for adult in &adults {
+            let closest_elder = elders
+                .iter()
+                .min_by(|lhs, rhs| adult.cmp_distance(lhs, rhs))
+                .copied();
+            if closest_elder != Some(our_name) {
+                continue;
+            }
+            let partner = adults
+                .iter()
+                .filter(|name| *name != adult)
+                .min_by(|lhs, rhs| adult.cmp_distance(lhs, rhs));
+            if let Some(partner) = partner {
+                let _ = pairs.insert(pair(*adult, *partner));
+            }
+        }
+
+        for (adult, partner) in pairs {
+            commands.extend(self.start_chunk_inventory_round(
+                adult,
+                partner,
+                adults.clone(),
+                INITIAL_CELLS,
+            )?);
+        }
+
+        Ok(commands)
+    }
+
+    fn start_chunk_inventory_round(
+        &self,
+        adult: XorName,
+        partner: XorName,
+        adults: BTreeSet<XorName>,
+        cells: u32,
+    ) -> Result<Vec<Command>> {
+        trace!(
+            "Starting inventory round between {} and {} with {} cells",
+            adult,
+            partner,
+            cells
+        );
+        let _ = self.chunk_inventory.rounds.insert(
+            pair(adult, partner),
+            Round {
+                adults: adults.clone(),
+                cells,
+                reports: BTreeMap::new(),
+                started: Instant::now(),
+            },
+        );
+
+        let mut commands = vec![];
+        for (target, partner) in vec![(adult, partner), (partner, adult)] {
+            let msg = SystemMsg::NodeCmd(NodeCmd::ReportChunkInventory {
+                partner,
+                adults: adults.clone(),
+                cells,
+            });
+            commands.extend(self.send_node_msg_to_targets(
+                msg,
+                std::iter::once(target).collect(),
+                false,
+            )?);
+        }
+        Ok(commands)
+    }
+
+    /// Digests the chunks we hold which the partner Adult should hold as well, at an Adult.
+    pub(crate) fn handle_report_chunk_inventory(
+        &self,
+        requesting_elder: XorName,
+        partner: XorName,
+        adults: BTreeSet<XorName>,
+        cells: u32,
+    ) -> Result<Vec<Command>> {
+        let our_name = self.node().name();
+        let copy_count = self.get_copy_count();
+
+        let mut inventory = ChunkInventory::new(cells as usize);
+        let mut excess = 0;
+        for address in self.chunk_storage.keys()? {
+            let holders = holders_of(address.name(), &adults, copy_count);
+            if !holders.contains(&our_name) {
+                excess += 1;
+            } else if holders.contains(&partner) {
+                inventory.insert(address.name());
+            }
+        }
+
+        let msg = SystemMsg::NodeCmd(NodeCmd::ChunkInventoryReport {
+            inventory,
+            partner,
+            excess,
+        });
+        let dst = DstLocation::Node {
+            name: requesting_elder,
+            section_pk: *self.section().chain().last_key(),
+        };
+
+        Ok(vec![Command::PrepareNodeMsgToSend {
+            msg,
+            dst,
+            trace: None,
+        }])
+    }
+
+    /// Records the report of an Adult of a round, at an Elder. Once both Adults of the pair have
+    /// reported, each is told to replicate to the other the chunks it misses.
+    pub(crate) fn handle_chunk_inventory_report(
+        &self,
+        adult: XorName,
+        partner: XorName,
+        inventory: ChunkInventory,
+        excess: u64,
+    ) -> Result<Vec<Command>> {
+        if excess > 0 {
+            info!(
+                "Adult {} holds {} chunks it's no longer a holder of",
+                adult, excess
+            );
+        }
+
+        let key = pair(adult, partner);
+        let round = {
+            let mut round = match self.chunk_inventory.rounds.get_mut(&key) {
+                Some(round) => round,
+                None => {
+                    trace!("Ignoring inventory of {} outside of any round", adult);
+                    return Ok(vec![]);
+                }
+            };
+            if inventory.len() != ChunkInventory::new(round.cells as usize).len() {
+                trace!("Ignoring inventory of {} from an earlier round", adult);
+                return Ok(vec![]);
+            }
+            let _ = round.reports.insert(adult, inventory);
+            if round.reports.len() < 2 {
+                return Ok(vec![]);
+            }
+            drop(round);
+            match self.chunk_inventory.rounds.remove(&key) {
+                Some((_, round)) => round,
+                None => return Ok(vec![]),
+            }
+        };
+
+        let (lhs, rhs) = key;
+        let differences = match (round.reports.get(&lhs), round.reports.get(&rhs)) {
+            (Some(lhs_inventory), Some(rhs_inventory)) => lhs_inventory
+                .subtract(rhs_inventory)
+                .and_then(ChunkInventory::decode),
+            _ => return Ok(vec![]),
+        };
+
+        let (only_lhs, only_rhs) = match differences {
+            Some(differences) => differences,
+            None if round.cells < MAX_CELLS => {
+                debug!(
+                    "Too many differences between {} and {} for {} cells, retrying",
+                    lhs, rhs, round.cells
+                );
+                return self.start_chunk_inventory_round(lhs, rhs, round.adults, round.cells * 2);
+            }
+            None => {
+                warn!(
+                    "Failed to find the chunks missing between {} and {} with {} cells",
+                    lhs, rhs, round.cells
+                );
+                return Ok(vec![]);
+            }
+        };
+
+        let mut commands = vec![];
+        for (holder, names, to) in vec![(lhs, only_lhs, rhs), (rhs, only_rhs, lhs)] {
+            if names.is_empty() {
+                continue;
+            }
+            info!(
+                "Adult {} misses {} chunks held by {}, replicating them",
+                to,
+                names.len(),
+                holder
+            );
+            let msg = SystemMsg::NodeCmd(NodeCmd::ReplicateChunks { names, to });
+            commands.extend(self.send_node_msg_to_targets(
+                msg,
+                std::iter::once(holder).collect(),
+                false,
+            )?);
+        }
+        Ok(commands)
+    }
+
+    /// Replicates the given chunks we hold to an Adult missing them, at an Adult.
+    pub(crate) fn replicate_chunks(
+        &self,
+        names: BTreeSet<XorName>,
+        to: XorName,
+    ) -> Result<Vec<Command>> {
+        let batch = self
+            .load_shedding
+            .replication_batch()
+            .unwrap_or_else(|| names.len());
+        if names.len() > batch {
+            debug!(
+                "Replicating {} of the {} chunks {} misses, under resource pressure",
+                batch,
+                names.len(),
+                to
+            );
+        }
+
+        let mut commands = vec![];
+        for name in names.into_iter().take(batch) {
+            let chunk = match self.chunk_storage.get_chunk(&ChunkAddress(name)) {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    warn!("Failed to read chunk {} to replicate: {:?}", name, error);
+                    continue;
+                }
+            };
+            let msg = SystemMsg::NodeCmd(NodeCmd::ReplicateChunk(chunk));
+            commands.extend(self.send_node_msg_to_targets(
+                msg,
+                std::iter::once(to).collect(),
+                false,
+            )?);
+        }
+        Ok(commands)
+    }
+}
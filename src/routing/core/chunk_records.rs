@@ -6,9 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{capacity::CHUNK_COPY_COUNT, Command, Core, Prefix, Result};
+use super::{ChunkStore, Command, Core, Prefix, Proposal, Result};
+use crate::dbs::convert_to_error_message as convert_db_error_to_error_message;
 use crate::messaging::{
-    data::{operation_id, ChunkDataExchange, CmdError, Error as ErrorMessage, StorageLevel},
+    data::{
+        operation_id, ChunkDataExchange, CmdError, DataLimits, DataQuery, Error as ErrorMessage,
+        StorageLevel,
+    },
     system::{NodeCmd, NodeQuery, SystemMsg},
     AuthorityProof, EndUser, MessageId, ServiceAuth,
 };
@@ -20,7 +24,17 @@ use xor_name::XorName;
 
 impl Core {
     pub(crate) fn get_copy_count(&self) -> usize {
-        CHUNK_COPY_COUNT
+        self.replication_factor
+    }
+
+    /// Sets the number of copies of each chunk to maintain among our Adults.
+    pub(crate) fn set_replication_factor(&mut self, replication_factor: usize) {
+        self.replication_factor = replication_factor;
+    }
+
+    /// Sets the limits on the size of the data our section accepts.
+    pub(crate) fn set_data_limits(&mut self, data_limits: DataLimits) {
+        self.data_limits = data_limits;
     }
 
     pub(crate) async fn get_data_of(&self, prefix: &Prefix) -> ChunkDataExchange {
@@ -40,6 +54,12 @@ impl Core {
         // full adults
         self.capacity.retain_members_only(&members).await;
 
+        // stop spot-checking the capacity of absent members
+        self.capacity_proofs.retain_members_only(&members);
+
+        // stop tracking what absent members know of our section
+        self.members_updates.retain_members_only(&members);
+
         // stop tracking liveness of absent holders
         self.liveness.retain_members_only(members);
 
@@ -76,6 +96,12 @@ impl Core {
     ) -> Result<Vec<Command>> {
         trace!("Sending chunk {:?} to adults", chunk);
 
+        if let Err(error) = ChunkStore::validate(&chunk) {
+            warn!("Rejecting invalid chunk {:?}: {:?}", chunk, error);
+            let error = CmdError::Data(convert_db_error_to_error_message(error));
+            return self.send_cmd_error_response(error, origin, msg_id);
+        }
+
         let target = *chunk.name();
 
         let msg = SystemMsg::NodeCmd(NodeCmd::StoreChunk {
@@ -93,7 +119,15 @@ impl Core {
             return self.send_cmd_error_response(error, origin, msg_id);
         }
 
-        self.send_node_msg_to_targets(msg, targets, aggregation)
+        // Kept for a little while, to be sent again to Adults it comes in corrupted to.
+        let store_id = MessageId::new();
+        for target in &targets {
+            self.resends
+                .store_sent(*target, store_id, msg.clone())
+                .await;
+        }
+
+        self.send_node_msg_to_targets_as(msg, store_id, targets, aggregation)
     }
 
     pub(crate) async fn send_error(
@@ -128,17 +162,117 @@ impl Core {
                 .await;
         }
 
+        let query = NodeQuery::GetChunk { address, origin };
         let mut fresh_targets = BTreeSet::new();
         for target in targets {
             self.liveness
                 .add_a_pending_request_operation(target, operation_id(&address)?)
                 .await;
+            self.resends
+                .query_sent(target, operation_id(&address)?, query.clone())
+                .await;
             let _ = fresh_targets.insert(target);
         }
 
-        let msg = SystemMsg::NodeQuery(NodeQuery::GetChunk { address, origin });
+        let msg = SystemMsg::NodeQuery(query);
         let aggregation = false;
 
         self.send_node_msg_to_targets(msg, fresh_targets, aggregation)
     }
+
+    pub(super) async fn check_chunk_at_adults(
+        &self,
+        address: ChunkAddress,
+        msg_id: MessageId,
+        origin: EndUser,
+    ) -> Result<Vec<Command>> {
+        let targets = self.get_chunk_holder_adults(address.name()).await;
+
+        if targets.is_empty() {
+            return self
+                .send_error(Error::NoAdults(*self.section().prefix()), msg_id, origin)
+                .await;
+        }
+
+        let operation_id = DataQuery::ChunkExists(address).operation_id()?;
+        let query = NodeQuery::ChunkExists { address, origin };
+        for target in &targets {
+            self.liveness
+                .add_a_pending_request_operation(*target, operation_id.clone())
+                .await;
+            self.resends
+                .query_sent(*target, operation_id.clone(), query.clone())
+                .await;
+        }
+        self.existence_checks
+            .start(address, msg_id, origin, targets.clone())
+            .await;
+
+        let msg = SystemMsg::NodeQuery(query);
+        let aggregation = false;
+
+        self.send_node_msg_to_targets(msg, targets, aggregation)
+    }
+
+    /// Checks which of the Adults which should hold the chunk at `address` do, for our section
+    /// to only sign for chunks, and holders, shown to be held. Nothing is sent if we're
+    /// already checking.
+    pub(crate) async fn check_holders(&self, address: ChunkAddress) -> Result<Vec<Command>> {
+        let targets = self.get_chunk_holder_adults(address.name()).await;
+        if targets.is_empty()
+            || !self
+                .existence_checks
+                .start_holding(address, targets.clone())
+                .await
+        {
+            return Ok(vec![]);
+        }
+
+        let operation_id = DataQuery::ChunkExists(address).operation_id()?;
+        let origin = EndUser(self.node().name());
+        let query = NodeQuery::ChunkExists { address, origin };
+        for target in &targets {
+            self.liveness
+                .add_a_pending_request_operation(*target, operation_id.clone())
+                .await;
+            self.resends
+                .query_sent(*target, operation_id.clone(), query.clone())
+                .await;
+        }
+
+        let msg = SystemMsg::NodeQuery(query);
+        let aggregation = false;
+
+        self.send_node_msg_to_targets(msg, targets, aggregation)
+    }
+
+    /// Proposes the signatures over the chunk at `address`, and over its holders, which were
+    /// waited for, now that our check showed it's held by `held_by`, telling the clients
+    /// waiting if it's held by none.
+    pub(crate) async fn holders_checked(
+        &self,
+        address: ChunkAddress,
+        held_by: BTreeSet<XorName>,
+    ) -> Result<Vec<Command>> {
+        let mut commands = vec![];
+        if held_by.is_empty() {
+            for (msg_id, user) in self.data_proofs.unheld(address).await {
+                let query = DataQuery::GetDataProof(address);
+                commands.extend(self.send_chunk_not_held(msg_id, query, user)?);
+            }
+            for (msg_id, user) in self.holder_proofs.unheld(address).await {
+                let query = DataQuery::GetChunkHolders(address);
+                commands.extend(self.send_chunk_not_held(msg_id, query, user)?);
+            }
+            return Ok(commands);
+        }
+
+        if self.data_proofs.checked(address).await {
+            commands.extend(self.propose(Proposal::DataProof(address))?);
+        }
+        for holders in self.holder_proofs.checked(address, &held_by).await {
+            commands.extend(self.propose(Proposal::ChunkHolders { address, holders })?);
+        }
+        Ok(commands)
+    }
 }
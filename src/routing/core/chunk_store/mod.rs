@@ -8,7 +8,7 @@
 
 use crate::dbs::{convert_to_error_message, Error, KvStore, Result, Subdir, UsedSpace};
 use crate::messaging::{data::StorageLevel, system::NodeQueryResponse};
-use crate::types::{Chunk, ChunkAddress, DataAddress};
+use crate::types::{Chunk, ChunkAddress, DataAddress, MAX_CHUNK_SIZE_IN_BYTES};
 
 use std::{
     fmt::{self, Display, Formatter},
@@ -17,6 +17,7 @@ use std::{
 };
 use tokio::sync::RwLock;
 use tracing::info;
+use xor_name::XorName;
 
 type Db = KvStore<ChunkAddress, Chunk>;
 
@@ -45,6 +46,10 @@ impl ChunkStore {
         self.db.keys()
     }
 
+    pub(crate) fn has(&self, address: &ChunkAddress) -> Result<bool> {
+        self.db.has(address)
+    }
+
     pub(crate) fn remove_chunk(&self, address: &ChunkAddress) -> Result<()> {
         trace!("Removing chunk, {:?}", address);
         self.db.delete(address)
@@ -54,7 +59,13 @@ impl ChunkStore {
         debug!("Getting chunk at address {:?}", address);
 
         match self.db.get(address) {
-            Ok(res) => Ok(res),
+            // The address of a chunk is that of its content, so a chunk corrupted on disk
+            // is held at another address than its own.
+            Ok(chunk) if chunk.address() != address => {
+                warn!("{}: Chunk at {:?} is corrupted", self, address);
+                Err(Error::ChunkAddressMismatch(*address))
+            }
+            Ok(chunk) => Ok(chunk),
             Err(error) => match error {
                 Error::KeyNotFound(_) => Err(Error::NoSuchData(DataAddress::Chunk(*address))),
                 something_else => Err(something_else),
@@ -69,7 +80,26 @@ impl ChunkStore {
         ))
     }
 
+    /// Checks the chunk is within the max size allowed and that
+    /// its content hashes to the address it claims.
+    pub(crate) fn validate(chunk: &Chunk) -> Result<()> {
+        if !chunk.validate_size() {
+            return Err(Error::ChunkTooLarge {
+                size: chunk.serialised_size(),
+                max: MAX_CHUNK_SIZE_IN_BYTES,
+            });
+        }
+
+        if XorName::from_content(chunk.value()) != *chunk.name() {
+            return Err(Error::ChunkAddressMismatch(*chunk.address()));
+        }
+
+        Ok(())
+    }
+
     pub(super) async fn store(&self, data: &Chunk) -> Result<Option<StorageLevel>> {
+        Self::validate(data)?;
+
         if self.db.has(data.address())? {
             info!(
                 "{}: Immutable chunk already exists, not storing: {:?}",
@@ -111,3 +141,22 @@ impl Display for ChunkStore {
         write!(formatter, "ChunkStore")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkStore;
+    use crate::dbs::Error;
+    use crate::types::{utils::random_bytes, Chunk, MAX_CHUNK_SIZE_IN_BYTES};
+
+    #[test]
+    fn oversized_chunk_is_rejected() {
+        let chunk = Chunk::new(random_bytes(MAX_CHUNK_SIZE_IN_BYTES));
+        assert!(ChunkStore::validate(&chunk).is_ok());
+
+        let chunk = Chunk::new(random_bytes(MAX_CHUNK_SIZE_IN_BYTES + 1));
+        assert!(matches!(
+            ChunkStore::validate(&chunk),
+            Err(Error::ChunkTooLarge { .. })
+        ));
+    }
+}
@@ -9,6 +9,7 @@
 use super::msg_count::MsgCount;
 use crate::messaging::WireMsg;
 use crate::routing::error::{Error, Result};
+use crate::types::chunk_buffers;
 use bytes::Bytes;
 use futures::stream::{FuturesUnordered, StreamExt};
 use qp2p::Endpoint;
@@ -204,7 +205,12 @@ impl Comm {
             return Err(Error::EmptyRecipientList);
         }
 
-        let msg_bytes = wire_msg.serialize().map_err(Error::Messaging)?;
+        // Chunk sized messages are serialised into pooled buffers, held until sent to all
+        // recipients, so concurrent transfers stay within the memory cap.
+        let msg_bytes = wire_msg
+            .serialize_pooled(chunk_buffers())
+            .await
+            .map_err(Error::Messaging)?;
         let priority = wire_msg.msg_kind().priority();
 
         // Run all the sends concurrently (using `FuturesUnordered`). If any of them fails, pick
@@ -588,6 +594,7 @@ mod tests {
         let auth = ServiceAuth {
             public_key: src_keypair.public_key(),
             signature: src_keypair.sign(&payload),
+            delegation: None,
         };
 
         let wire_msg = WireMsg::new_msg(
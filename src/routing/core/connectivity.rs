@@ -7,7 +7,11 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::Core;
-use crate::messaging::system::Proposal;
+use crate::messaging::{
+    data::{CmdError, Error as ErrorMessage},
+    system::{Proposal, SystemMsg},
+    EndUser, MessageId,
+};
 use crate::routing::{
     error::Result,
     peer::PeerUtils,
@@ -31,6 +35,81 @@ impl Core {
         Ok(vec![])
     }
 
+    /// Handles a message from `addr` whose payload came in corrupted: the sender is told so,
+    /// for it to send the message again if it can. Corruptions from other nodes are recorded
+    /// against them, and the queries awaiting their response sent to them again, once, as the
+    /// message may be the response to any of them.
+    pub(crate) async fn handle_corrupted_msg(
+        &self,
+        addr: SocketAddr,
+        msg_id: MessageId,
+    ) -> Result<Vec<Command>> {
+        let name = if let Some(peer) = self.section.find_joined_member_by_addr(&addr) {
+            *peer.name()
+        } else if let Some(name) = self.comm.get_connection_id(&addr).await {
+            warn!(
+                "Message {:?} from client {} came in corrupted",
+                msg_id, addr
+            );
+            let error = CmdError::Data(ErrorMessage::CorruptedMessage);
+            return self.send_cmd_error_response(error, EndUser(name), msg_id);
+        } else {
+            warn!(
+                "Message {:?} from unknown peer {} came in corrupted",
+                msg_id, addr
+            );
+            return Ok(vec![]);
+        };
+
+        let count = self.liveness.record_corrupted_msg(name);
+        warn!(
+            "Message {:?} from {} came in corrupted, {} did so recently",
+            msg_id, name, count
+        );
+
+        // A notice coming in corrupted is itself answered with a notice, but as each answer
+        // takes another corruption in transit, notices don't keep bouncing between nodes.
+        let notice = SystemMsg::NodeMsgError {
+            error: ErrorMessage::CorruptedMessage,
+            correlation_id: msg_id,
+        };
+        let mut commands =
+            self.send_node_msg_to_targets(notice, iter::once(name).collect(), false)?;
+        for query in self.resends.queries_to_resend(name).await {
+            trace!("Sending {:?} to {} again", query, name);
+            let targets = iter::once(name).collect();
+            commands.extend(self.send_node_msg_to_targets(
+                SystemMsg::NodeQuery(query),
+                targets,
+                false,
+            )?);
+        }
+        Ok(commands)
+    }
+
+    /// Sends the chunk store `adult` told us came in corrupted in the message of `msg_id`
+    /// again, once.
+    pub(crate) async fn resend_corrupted_store(
+        &self,
+        adult: XorName,
+        msg_id: MessageId,
+    ) -> Result<Vec<Command>> {
+        match self.resends.store_to_resend(adult, msg_id).await {
+            Some(msg) => {
+                debug!("Sending chunk store {:?} to {} again", msg_id, adult);
+                self.send_node_msg_to_targets(msg, iter::once(adult).collect(), false)
+            }
+            None => {
+                trace!(
+                    "Message {:?} which {} got corrupted isn't to be sent again",
+                    msg_id,
+                    adult
+                );
+                Ok(vec![])
+            }
+        }
+    }
+
     pub(crate) fn handle_peer_lost(&self, addr: &SocketAddr) -> Result<Vec<Command>> {
         let name = if let Some(peer) = self.section.find_joined_member_by_addr(addr) {
             debug!("Lost known peer {}", peer);
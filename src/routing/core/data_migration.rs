@@ -0,0 +1,439 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Command, Core, Result};
+use crate::messaging::{
+    data::RegisterDataExchange,
+    system::{NodeCmd, SystemMsg},
+    DstLocation,
+};
+use crate::routing::{
+    routing_api::command::next_timer_token, NodeElderChange, SectionAuthorityProviderUtils,
+};
+use crate::types::ChunkAddress;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use xor_name::XorName;
+
+/// How often the data handed off on a change of role is checked for at its new holders.
+const HANDOFF_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+// Data still not held by all its new holders after this many checks is given up on, our copy
+// being kept rather than risking dropping its last one.
+const MAX_HANDOFF_CHECKS: u32 = 10;
+
+/// Progress of handing off the data a node held before its role changed: chunks to the Adults
+/// holding them on being promoted to Elder, and registers to the Elders on being demoted to
+/// Adult. The node's copies are only dropped once verified held by all their new holders.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MigrationProgress {
+    /// The change of role the data is handed off on.
+    pub change: NodeElderChange,
+    /// Number of chunks or registers handed off.
+    pub total: usize,
+    /// Number of them verified held by all their new holders, whose copies were dropped.
+    pub migrated: usize,
+    /// Number of them given up on, whose copies are kept.
+    pub abandoned: usize,
+}
+
+impl MigrationProgress {
+    /// Number of chunks or registers still being handed off.
+    pub fn pending(&self) -> usize {
+        self.total - self.migrated - self.abandoned
+    }
+
+    /// Whether all the data has been handed off, or given up on.
+    pub fn is_done(&self) -> bool {
+        self.pending() == 0
+    }
+}
+
+/// The handing off of our data since our last change of role, if any. Only the last change
+/// is tracked: data of an earlier one still being handed off is kept.
+#[derive(Clone, Debug)]
+pub(crate) struct DataMigration {
+    timer_token: u64,
+    migration: Arc<Mutex<Option<Migration>>>,
+}
+
+#[derive(Debug)]
+struct Migration {
+    progress: MigrationProgress,
+    // The data not verified yet, with its holders which haven't confirmed holding it yet.
+    pending: BTreeMap<XorName, BTreeSet<XorName>>,
+    checks: u32,
+}
+
+impl DataMigration {
+    pub(crate) fn new() -> Self {
+        Self {
+            timer_token: next_timer_token(),
+            migration: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Token of the timeout handed off data is checked on.
+    pub(crate) fn timer_token(&self) -> u64 {
+        self.timer_token
+    }
+
+    /// Progress of the handing off of our data since our last change of role, if any.
+    pub(crate) fn progress(&self) -> Option<MigrationProgress> {
+        self.migration
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map(|migration| migration.progress.clone())
+    }
+}
+
+impl Migration {
+    fn new(change: NodeElderChange, pending: BTreeMap<XorName, BTreeSet<XorName>>) -> Self {
+        Self {
+            progress: MigrationProgress {
+                change,
+                total: pending.len(),
+                migrated: 0,
+                abandoned: 0,
+            },
+            pending,
+            checks: 0,
+        }
+    }
+
+    // Records which of the data handed off to `holder` it holds. Returns the data now held by
+    // all its holders, and the data the holder misses.
+    fn record_held(
+        &mut self,
+        holder: &XorName,
+        held: &BTreeSet<XorName>,
+    ) -> (Vec<XorName>, BTreeSet<XorName>) {
+        let mut verified = vec![];
+        let mut missing = BTreeSet::new();
+        for (name, holders) in self.pending.iter_mut() {
+            if !holders.contains(holder) {
+                continue;
+            }
+            if held.contains(name) {
+                let _ = holders.remove(holder);
+                if holders.is_empty() {
+                    verified.push(*name);
+                }
+            } else {
+                let _ = missing.insert(*name);
+            }
+        }
+        for name in &verified {
+            let _ = self.pending.remove(name);
+        }
+        self.progress.migrated += verified.len();
+        (verified, missing)
+    }
+
+    // The data pending at each holder.
+    fn pending_by_holder(&self) -> BTreeMap<XorName, BTreeSet<XorName>> {
+        let mut by_holder: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for (name, holders) in &self.pending {
+            for holder in holders {
+                let _ = by_holder.entry(*holder).or_default().insert(*name);
+            }
+        }
+        by_holder
+    }
+}
+
+impl Core {
+    /// Starts handing off the data we held before our role changed to its new holders: our
+    /// chunks to the Adults holding them once promoted, and our registers to the Elders once
+    /// demoted.
+    pub(crate) async fn start_data_migration(
+        &self,
+        change: NodeElderChange,
+    ) -> Result<Vec<Command>> {
+        let (pending, mut commands) = match change {
+            NodeElderChange::Promoted => self.hand_off_chunks().await?,
+            NodeElderChange::Demoted => self.hand_off_registers().await?,
+            NodeElderChange::None => return Ok(vec![]),
+        };
+        if pending.is_empty() {
+            return Ok(commands);
+        }
+
+        info!(
+            "{:?}, handing off {} chunks or registers",
+            change,
+            pending.len()
+        );
+        *self
+            .data_migration
+            .migration
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Migration::new(change, pending));
+        commands.push(Command::ScheduleTimeout {
+            duration: HANDOFF_CHECK_INTERVAL,
+            token: self.data_migration.timer_token(),
+        });
+
+        Ok(commands)
+    }
+
+    // Replicates each chunk we hold at the Adults holding it.
+    async fn hand_off_chunks(
+        &self,
+    ) -> Result<(BTreeMap<XorName, BTreeSet<XorName>>, Vec<Command>)> {
+        let mut pending = BTreeMap::new();
+        let mut commands = vec![];
+        for address in self.chunk_storage.keys()? {
+            let holders = self.get_chunk_holder_adults(address.name()).await;
+            if holders.is_empty() {
+                continue;
+            }
+            let chunk = match self.chunk_storage.get_chunk(&address) {
+                Ok(chunk) => chunk,
+                Err(error) => {
+                    warn!(
+                        "Failed to read chunk {:?} to hand off: {:?}",
+                        address, error
+                    );
+                    continue;
+                }
+            };
+            let msg = SystemMsg::NodeCmd(NodeCmd::ReplicateChunk(chunk));
+            commands.extend(self.send_node_msg_to_targets(msg, holders.clone(), false)?);
+            let _ = pending.insert(*address.name(), holders);
+        }
+        Ok((pending, commands))
+    }
+
+    // Sends the registers of our section we hold to its Elders.
+    async fn hand_off_registers(
+        &self,
+    ) -> Result<(BTreeMap<XorName, BTreeSet<XorName>>, Vec<Command>)> {
+        let data = self
+            .register_storage
+            .get_data_of(*self.section.prefix())
+            .await?;
+        let elders = self.section.authority_provider().names();
+        if data.0.is_empty() || elders.is_empty() {
+            return Ok((BTreeMap::new(), vec![]));
+        }
+
+        let pending = data.0.keys().map(|name| (*name, elders.clone())).collect();
+        let msg = SystemMsg::NodeCmd(NodeCmd::HandOffRegisters(data));
+        let commands = self.send_node_msg_to_targets(msg, elders, false)?;
+        Ok((pending, commands))
+    }
+
+    /// Asks the new holders of the data not verified yet which of it they hold, giving up on
+    /// it after too many checks.
+    pub(crate) fn handle_data_migration_timeout(&self) -> Result<Vec<Command>> {
+        let (by_holder, change) = {
+            let mut migration = self
+                .data_migration
+                .migration
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let migration = match migration.as_mut() {
+                Some(migration) if !migration.pending.is_empty() => migration,
+                _ => return Ok(vec![]),
+            };
+            migration.checks += 1;
+            if migration.checks > MAX_HANDOFF_CHECKS {
+                warn!(
+                    "Giving up on handing off {} chunks or registers, keeping our copies",
+                    migration.pending.len()
+                );
+                migration.progress.abandoned += migration.pending.len();
+                migration.pending.clear();
+                return Ok(vec![]);
+            }
+            (migration.pending_by_holder(), migration.progress.change)
+        };
+
+        let mut commands = vec![Command::ScheduleTimeout {
+            duration: HANDOFF_CHECK_INTERVAL,
+            token: self.data_migration.timer_token(),
+        }];
+        for (holder, names) in by_holder {
+            let (chunks, registers) = if change == NodeElderChange::Promoted {
+                (names, BTreeSet::new())
+            } else {
+                (BTreeSet::new(), names)
+            };
+            let msg = SystemMsg::NodeCmd(NodeCmd::CheckHandoff { chunks, registers });
+            commands.extend(self.send_node_msg_to_targets(
+                msg,
+                iter::once(holder).collect(),
+                false,
+            )?);
+        }
+        Ok(commands)
+    }
+
+    /// Reports back which of the data handed off to us we hold.
+    pub(crate) fn handle_check_handoff(
+        &self,
+        requester: XorName,
+        chunks: BTreeSet<XorName>,
+        registers: BTreeSet<XorName>,
+    ) -> Result<Vec<Command>> {
+        let chunks = chunks
+            .into_iter()
+            .filter(|name| {
+                self.chunk_storage
+                    .has(&ChunkAddress(*name))
+                    .unwrap_or(false)
+            })
+            .collect();
+        let registers = registers
+            .into_iter()
+            .filter(|name| self.register_storage.holds(name))
+            .collect();
+
+        let msg = SystemMsg::NodeCmd(NodeCmd::HandoffChecked { chunks, registers });
+        let dst = DstLocation::Node {
+            name: requester,
+            section_pk: *self.section().chain().last_key(),
+        };
+
+        Ok(vec![Command::PrepareNodeMsgToSend {
+            msg,
+            dst,
+            trace: None,
+        }])
+    }
+
+    /// Drops our copies of the data now verified held by all its new holders, and hands the
+    /// data the holder misses off to it again.
+    pub(crate) async fn handle_handoff_checked(
+        &self,
+        holder: XorName,
+        chunks: BTreeSet<XorName>,
+        registers: BTreeSet<XorName>,
+    ) -> Result<Vec<Command>> {
+        let (verified, missing, change) = {
+            let mut migration = self
+                .data_migration
+                .migration
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let migration = match migration.as_mut() {
+                Some(migration) => migration,
+                None => return Ok(vec![]),
+            };
+            let change = migration.progress.change;
+            let held = if change == NodeElderChange::Promoted {
+                chunks
+            } else {
+                registers
+            };
+            let (verified, missing) = migration.record_held(&holder, &held);
+            if !verified.is_empty() && migration.pending.is_empty() {
+                info!(
+                    "Handed off our data: {} chunks or registers migrated, {} given up on",
+                    migration.progress.migrated, migration.progress.abandoned
+                );
+            }
+            (verified, missing, change)
+        };
+
+        for name in verified {
+            let result = if change == NodeElderChange::Promoted {
+                self.chunk_storage.remove_chunk(&ChunkAddress(name))
+            } else {
+                self.register_storage.remove(&name)
+            };
+            if let Err(error) = result {
+                warn!("Failed to drop handed off data {}: {:?}", name, error);
+            }
+        }
+
+        if missing.is_empty() {
+            return Ok(vec![]);
+        }
+        debug!(
+            "{} misses {} of the chunks or registers handed off to it, handing them off again",
+            holder,
+            missing.len()
+        );
+        let targets: BTreeSet<_> = iter::once(holder).collect();
+        let mut commands = vec![];
+        if change == NodeElderChange::Promoted {
+            for name in missing {
+                match self.chunk_storage.get_chunk(&ChunkAddress(name)) {
+                    Ok(chunk) => {
+                        let msg = SystemMsg::NodeCmd(NodeCmd::ReplicateChunk(chunk));
+                        commands.extend(self.send_node_msg_to_targets(
+                            msg,
+                            targets.clone(),
+                            false,
+                        )?);
+                    }
+                    Err(error) => {
+                        warn!("Failed to read chunk {} to hand off: {:?}", name, error);
+                    }
+                }
+            }
+        } else {
+            let RegisterDataExchange(data) = self
+                .register_storage
+                .get_data_of(*self.section.prefix())
+                .await?;
+            let data = data
+                .into_iter()
+                .filter(|(name, _)| missing.contains(name))
+                .collect();
+            let msg = SystemMsg::NodeCmd(NodeCmd::HandOffRegisters(RegisterDataExchange(data)));
+            commands.extend(self.send_node_msg_to_targets(msg, targets, false)?);
+        }
+        Ok(commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Migration;
+    use crate::routing::NodeElderChange;
+    use std::collections::BTreeSet;
+    use xor_name::XorName;
+
+    #[test]
+    fn data_is_migrated_once_held_by_all_its_holders() {
+        let (first, second) = (XorName::random(), XorName::random());
+        let holders: BTreeSet<_> = vec![first, second].into_iter().collect();
+        let (chunk, other_chunk) = (XorName::random(), XorName::random());
+        let mut migration = Migration::new(
+            NodeElderChange::Promoted,
+            vec![(chunk, holders.clone()), (other_chunk, holders)]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(migration.pending_by_holder()[&first].len(), 2);
+
+        let held = std::iter::once(chunk).collect();
+        let (verified, missing) = migration.record_held(&first, &held);
+        assert!(verified.is_empty());
+        assert_eq!(missing, std::iter::once(other_chunk).collect());
+
+        let (verified, missing) = migration.record_held(&second, &held);
+        assert_eq!(verified, vec![chunk]);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(migration.progress.migrated, 1);
+        assert_eq!(migration.progress.pending(), 1);
+        assert!(!migration.progress.is_done());
+
+        // The holders which confirmed holding the data aren't asked about it again.
+        let pending = migration.pending_by_holder();
+        assert_eq!(pending[&first], std::iter::once(other_chunk).collect());
+        assert!(!pending.contains_key(&XorName::random()));
+    }
+}
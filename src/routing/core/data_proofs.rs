@@ -0,0 +1,124 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::waitlists::{Marks, Waitlists};
+use crate::messaging::{system::KeyedSig, EndUser, MessageId};
+use crate::types::{Cache, ChunkAddress};
+use std::{sync::Arc, time::Duration};
+
+// Number of chunk addresses signatures are kept around for, and for how long.
+const CACHE_CAPACITY: usize = 10_000;
+const SIGS_CACHE_DURATION: Duration = Duration::from_secs(60 * 60);
+// How long clients are kept waiting for a signature to be agreed on.
+const WAITING_DURATION: Duration = Duration::from_secs(120);
+
+/// Keeps the section signatures agreed on over chunk addresses, the clients waiting for those
+/// which haven't been agreed on yet, and the addresses other Elders proposed, for us to co-sign
+/// once we've checked the chunk is held.
+#[derive(Clone, Debug)]
+pub(crate) struct DataProofs {
+    sigs: Arc<Cache<ChunkAddress, KeyedSig>>,
+    waiting: Waitlists<ChunkAddress, (MessageId, EndUser)>,
+    to_co_sign: Marks<ChunkAddress>,
+    proposed: Marks<ChunkAddress>,
+}
+
+impl DataProofs {
+    pub(crate) fn new() -> Self {
+        Self {
+            sigs: Arc::new(Cache::with_expiry_duration_and_capacity(
+                SIGS_CACHE_DURATION,
+                CACHE_CAPACITY,
+            )),
+            waiting: Waitlists::new(WAITING_DURATION, CACHE_CAPACITY),
+            to_co_sign: Marks::new(WAITING_DURATION, CACHE_CAPACITY),
+            proposed: Marks::new(WAITING_DURATION, CACHE_CAPACITY),
+        }
+    }
+
+    /// Returns the signature over `address`, if it's been agreed on.
+    pub(crate) async fn sig(&self, address: &ChunkAddress) -> Option<KeyedSig> {
+        self.sigs.get(address).await
+    }
+
+    /// Records that `user` is waiting for the signature over `address`, to answer their query
+    /// `msg_id`.
+    pub(crate) async fn wait_for(&self, address: ChunkAddress, msg_id: MessageId, user: EndUser) {
+        self.waiting.push(address, (msg_id, user)).await
+    }
+
+    /// Records that another Elder proposed the signature over `address`, for us to co-sign it
+    /// once we've checked the chunk is held.
+    ///
+    /// Only the Elders a client queries propose a signature, so the rest
+    /// co-sign it upon receiving their proposals, for it to reach a supermajority.
+    pub(crate) async fn co_sign(&self, address: ChunkAddress) {
+        let _ = self.to_co_sign.mark(address).await;
+    }
+
+    /// Returns whether to propose the signature over `address` now that the chunk was checked
+    /// to be held: if clients are waiting for it, or other Elders proposed it, and we haven't
+    /// proposed it yet, nor has it been agreed on.
+    pub(crate) async fn checked(&self, address: ChunkAddress) -> bool {
+        let co_signing = self.to_co_sign.unmark(&address).await;
+        if !co_signing && self.waiting.get(&address).await.is_empty() {
+            return false;
+        }
+        self.sigs.get(&address).await.is_none() && self.proposed.mark(address).await
+    }
+
+    /// Drops what was waiting on the signature over `address` now that the chunk was checked
+    /// not to be held, returning the queries of the clients who were waiting.
+    pub(crate) async fn unheld(&self, address: ChunkAddress) -> Vec<(MessageId, EndUser)> {
+        let _ = self.to_co_sign.unmark(&address).await;
+        self.waiting.take(&address).await
+    }
+
+    /// Records the signature agreed on over `address`,
+    /// returning the queries of the clients who were waiting for it.
+    pub(crate) async fn agreed(
+        &self,
+        address: ChunkAddress,
+        sig: KeyedSig,
+    ) -> Vec<(MessageId, EndUser)> {
+        let _ = self.sigs.set(address, sig, None).await;
+        self.waiting.take(&address).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataProofs;
+    use crate::messaging::{EndUser, MessageId};
+    use crate::types::ChunkAddress;
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_chunks_waited_for_are_proposed_once_checked() {
+        let proofs = DataProofs::new();
+        let (waited, co_signed) = (
+            ChunkAddress(XorName::random()),
+            ChunkAddress(XorName::random()),
+        );
+
+        // Without clients waiting nor Elders proposing, there's nothing to sign.
+        assert!(!proofs.checked(waited).await);
+
+        let user = EndUser(XorName::random());
+        let msg_id = MessageId::new();
+        proofs.wait_for(waited, msg_id, user).await;
+        assert!(proofs.checked(waited).await);
+        // Nor is it proposed twice.
+        assert!(!proofs.checked(waited).await);
+
+        proofs.co_sign(co_signed).await;
+        assert_eq!(proofs.unheld(co_signed).await, vec![]);
+        assert!(!proofs.checked(co_signed).await);
+        assert_eq!(proofs.unheld(waited).await, vec![(msg_id, user)]);
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::waitlists::Waitlists;
+use crate::messaging::{EndUser, MessageId};
+use crate::types::{Cache, ChunkAddress};
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
+use xor_name::XorName;
+
+// Number of chunk addresses checks are kept around for, and for how long holders are waited
+// for. Clients whose checks expire get no answer, as when a chunk read times out.
+const CACHE_CAPACITY: usize = 10_000;
+const WAITING_DURATION: Duration = Duration::from_secs(60);
+
+// A client's query, with the holders which haven't answered yet.
+type Check = (MessageId, EndUser, BTreeSet<XorName>);
+
+// Our own check of which holders hold a chunk: those which haven't answered yet,
+// and those which answered they do.
+type HoldingCheck = (BTreeSet<XorName>, BTreeSet<XorName>);
+
+/// Keeps the clients waiting to know whether chunks are stored, until the Adults holding them
+/// answer, and our own checks of which Adults hold chunks, for our section to only sign for
+/// chunks, and holders, shown to be held.
+#[derive(Clone, Debug)]
+pub(crate) struct ExistenceChecks {
+    checks: Waitlists<ChunkAddress, Check>,
+    holdings: Arc<Cache<ChunkAddress, HoldingCheck>>,
+}
+
+impl ExistenceChecks {
+    pub(crate) fn new() -> Self {
+        Self {
+            checks: Waitlists::new(WAITING_DURATION, CACHE_CAPACITY),
+            holdings: Arc::new(Cache::with_expiry_duration_and_capacity(
+                WAITING_DURATION,
+                CACHE_CAPACITY,
+            )),
+        }
+    }
+
+    /// Records that `user` is waiting to know whether any of `holders` holds the chunk at
+    /// `address`, to answer their query `msg_id`.
+    pub(crate) async fn start(
+        &self,
+        address: ChunkAddress,
+        msg_id: MessageId,
+        user: EndUser,
+        holders: BTreeSet<XorName>,
+    ) {
+        self.checks.push(address, (msg_id, user, holders)).await
+    }
+
+    /// Records that we're checking which of `holders` hold the chunk at `address`, returning
+    /// false if we already are.
+    pub(crate) async fn start_holding(
+        &self,
+        address: ChunkAddress,
+        holders: BTreeSet<XorName>,
+    ) -> bool {
+        if self.holdings.get(&address).await.is_some() {
+            return false;
+        }
+        let _ = self
+            .holdings
+            .set(address, (holders, BTreeSet::new()), None)
+            .await;
+        true
+    }
+
+    /// Records whether `holder` holds the chunk at `address`, as part of our own check, returning
+    /// the holders which do once all of them answered.
+    pub(crate) async fn record_holding(
+        &self,
+        address: ChunkAddress,
+        holder: XorName,
+        exists: bool,
+    ) -> Option<BTreeSet<XorName>> {
+        let (mut pending, mut held_by) = self.holdings.get(&address).await?;
+        if !pending.remove(&holder) {
+            return None;
+        }
+        if exists {
+            let _ = held_by.insert(holder);
+        }
+        if pending.is_empty() {
+            let _ = self.holdings.remove(&address).await;
+            Some(held_by)
+        } else {
+            let _ = self.holdings.set(address, (pending, held_by), None).await;
+            None
+        }
+    }
+
+    /// Records whether `holder` holds the chunk at `address`, returning the queries which can
+    /// be answered, with whether the chunk is stored: as soon as any holder holds it, or once
+    /// none of them do.
+    pub(crate) async fn record(
+        &self,
+        address: ChunkAddress,
+        holder: XorName,
+        exists: bool,
+    ) -> Vec<(MessageId, EndUser, bool)> {
+        if exists {
+            return self
+                .checks
+                .take(&address)
+                .await
+                .into_iter()
+                .map(|(msg_id, user, _)| (msg_id, user, true))
+                .collect();
+        }
+
+        self.checks
+            .update(address, |checks| {
+                let mut answered = vec![];
+                for (msg_id, user, mut holders) in std::mem::take(checks) {
+                    let _ = holders.remove(&holder);
+                    if holders.is_empty() {
+                        answered.push((msg_id, user, false));
+                    } else {
+                        checks.push((msg_id, user, holders));
+                    }
+                }
+                answered
+            })
+            .await
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExistenceChecks;
+    use crate::messaging::{EndUser, MessageId};
+    use crate::types::ChunkAddress;
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn chunks_exist_once_any_holder_holds_them() {
+        let checks = ExistenceChecks::new();
+        let (lhs, rhs) = (XorName::random(), XorName::random());
+        let holders = vec![lhs, rhs].into_iter().collect();
+        let user = EndUser(XorName::random());
+        let msg_id = MessageId::new();
+
+        let missing = ChunkAddress(XorName::random());
+        checks.start(missing, msg_id, user, holders).await;
+        assert!(checks.record(missing, lhs, false).await.is_empty());
+        assert_eq!(
+            checks.record(missing, rhs, false).await,
+            vec![(msg_id, user, false)]
+        );
+
+        let stored = ChunkAddress(XorName::random());
+        let holders = vec![lhs, rhs].into_iter().collect();
+        checks.start(stored, msg_id, user, holders).await;
+        assert_eq!(
+            checks.record(stored, rhs, true).await,
+            vec![(msg_id, user, true)]
+        );
+        // Later answers are for queries already answered.
+        assert!(checks.record(stored, lhs, true).await.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn holdings_are_known_once_all_holders_answered() {
+        let checks = ExistenceChecks::new();
+        let (lhs, rhs) = (XorName::random(), XorName::random());
+        let address = ChunkAddress(XorName::random());
+
+        assert!(
+            checks
+                .start_holding(address, vec![lhs, rhs].into_iter().collect())
+                .await
+        );
+        assert!(
+            !checks
+                .start_holding(address, vec![lhs].into_iter().collect())
+                .await
+        );
+        assert_eq!(checks.record_holding(address, lhs, true).await, None);
+        // Holders not asked are ignored.
+        assert_eq!(
+            checks
+                .record_holding(address, XorName::random(), true)
+                .await,
+            None
+        );
+        assert_eq!(
+            checks.record_holding(address, rhs, false).await,
+            Some(vec![lhs].into_iter().collect())
+        );
+        assert_eq!(checks.record_holding(address, rhs, true).await, None);
+    }
+}
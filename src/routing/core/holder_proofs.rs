@@ -0,0 +1,164 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::waitlists::{Marks, Waitlists};
+use crate::messaging::{system::KeyedSig, EndUser, MessageId};
+use crate::types::{Cache, ChunkAddress};
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
+use xor_name::XorName;
+
+// Number of chunk addresses signatures are kept around for, and for how long.
+// Holders change as Adults come and go, so signatures aren't kept for long.
+const CACHE_CAPACITY: usize = 10_000;
+const SIGS_CACHE_DURATION: Duration = Duration::from_secs(60);
+// How long clients are kept waiting for a signature to be agreed on.
+const WAITING_DURATION: Duration = Duration::from_secs(120);
+
+/// Keeps the section signatures agreed on over the holders of chunks, the clients waiting for
+/// those which haven't been agreed on yet, and the holders other Elders proposed, for us to
+/// co-sign once we've checked they hold the chunk.
+#[derive(Clone, Debug)]
+pub(crate) struct HolderProofs {
+    sigs: Arc<Cache<ChunkAddress, (BTreeSet<XorName>, KeyedSig)>>,
+    waiting: Waitlists<ChunkAddress, (MessageId, EndUser)>,
+    to_co_sign: Waitlists<ChunkAddress, BTreeSet<XorName>>,
+    // Keyed by the digest of the holders proposed.
+    proposed: Marks<(ChunkAddress, XorName)>,
+}
+
+impl HolderProofs {
+    pub(crate) fn new() -> Self {
+        Self {
+            sigs: Arc::new(Cache::with_expiry_duration_and_capacity(
+                SIGS_CACHE_DURATION,
+                CACHE_CAPACITY,
+            )),
+            waiting: Waitlists::new(WAITING_DURATION, CACHE_CAPACITY),
+            to_co_sign: Waitlists::new(WAITING_DURATION, CACHE_CAPACITY),
+            proposed: Marks::new(WAITING_DURATION, CACHE_CAPACITY),
+        }
+    }
+
+    /// Returns the holders of `address` last agreed on, with the signature over them, if any.
+    pub(crate) async fn latest(
+        &self,
+        address: &ChunkAddress,
+    ) -> Option<(BTreeSet<XorName>, KeyedSig)> {
+        self.sigs.get(address).await
+    }
+
+    /// Records that `user` is waiting for the signature over the holders of `address`, to
+    /// answer their query `msg_id`.
+    pub(crate) async fn wait_for(&self, address: ChunkAddress, msg_id: MessageId, user: EndUser) {
+        self.waiting.push(address, (msg_id, user)).await
+    }
+
+    /// Records that another Elder proposed `holders` for `address`, for us to co-sign them once
+    /// we've checked they hold the chunk.
+    pub(crate) async fn co_sign(&self, address: ChunkAddress, holders: BTreeSet<XorName>) {
+        self.to_co_sign.push(address, holders).await
+    }
+
+    /// Returns the holders of `address` to propose now that `held_by`, which mustn't be empty,
+    /// were checked to hold it: those other Elders proposed which all did, and, for the clients
+    /// waiting, `held_by` themselves. Holders already proposed, or agreed on, aren't returned
+    /// again.
+    pub(crate) async fn checked(
+        &self,
+        address: ChunkAddress,
+        held_by: &BTreeSet<XorName>,
+    ) -> Vec<BTreeSet<XorName>> {
+        let mut candidates: Vec<_> = self
+            .to_co_sign
+            .take(&address)
+            .await
+            .into_iter()
+            .filter(|holders| !holders.is_empty() && holders.is_subset(held_by))
+            .collect();
+        if !self.waiting.get(&address).await.is_empty() {
+            candidates.push(held_by.clone());
+        }
+
+        let mut to_propose = vec![];
+        for holders in candidates {
+            if !to_propose.contains(&holders) && self.to_propose(address, &holders).await {
+                to_propose.push(holders);
+            }
+        }
+        to_propose
+    }
+
+    // Records that we're proposing `holders` for `address`, returning false
+    // if we already had, or they've already been agreed on.
+    async fn to_propose(&self, address: ChunkAddress, holders: &BTreeSet<XorName>) -> bool {
+        if matches!(self.sigs.get(&address).await, Some((agreed, _)) if agreed == *holders) {
+            return false;
+        }
+        match bincode::serialize(holders) {
+            Ok(bytes) => {
+                self.proposed
+                    .mark((address, XorName::from_content(&bytes)))
+                    .await
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Records the signature agreed on over `holders` of `address`,
+    /// returning the queries of the clients who were waiting for it.
+    pub(crate) async fn agreed(
+        &self,
+        address: ChunkAddress,
+        holders: BTreeSet<XorName>,
+        sig: KeyedSig,
+    ) -> Vec<(MessageId, EndUser)> {
+        let _ = self.sigs.set(address, (holders, sig), None).await;
+        self.waiting.take(&address).await
+    }
+
+    /// Drops what was waiting on the holders of `address` now that none were checked to hold
+    /// it, returning the queries of the clients who were waiting.
+    pub(crate) async fn unheld(&self, address: ChunkAddress) -> Vec<(MessageId, EndUser)> {
+        let _ = self.to_co_sign.take(&address).await;
+        self.waiting.take(&address).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HolderProofs;
+    use crate::messaging::{EndUser, MessageId};
+    use crate::types::ChunkAddress;
+    use std::collections::BTreeSet;
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_holders_checked_are_proposed() {
+        let proofs = HolderProofs::new();
+        let address = ChunkAddress(XorName::random());
+        let (lhs, rhs, other) = (XorName::random(), XorName::random(), XorName::random());
+        let held_by: BTreeSet<_> = vec![lhs, rhs].into_iter().collect();
+
+        // Without clients waiting nor Elders proposing, there's nothing to sign.
+        assert!(proofs.checked(address, &held_by).await.is_empty());
+
+        let proposed: BTreeSet<_> = vec![lhs].into_iter().collect();
+        let unchecked: BTreeSet<_> = vec![lhs, other].into_iter().collect();
+        proofs.co_sign(address, proposed.clone()).await;
+        proofs.co_sign(address, unchecked).await;
+        proofs
+            .wait_for(address, MessageId::new(), EndUser(XorName::random()))
+            .await;
+        assert_eq!(
+            proofs.checked(address, &held_by).await,
+            vec![proposed, held_by.clone()]
+        );
+        // Nor are holders proposed twice.
+        assert!(proofs.checked(address, &held_by).await.is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::dbs::{deserialise, serialise, Error, Result};
+use crate::routing::section::SectionKeyShare;
+use secured_linked_list::SecuredLinkedList;
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use std::path::{Path, PathBuf};
+
+const DATABASE_NAME: &str = "section_keys";
+const KEY_SHARES_TREE: &str = "key_shares";
+const CHAIN_KEY: &str = "chain";
+// Plain file copy of the chain, used if the db copy is missing or unreadable.
+const CHAIN_BACKUP_FILENAME: &str = "section_chain.backup";
+
+/// Everything about an elder's key share, except for the secret share itself.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct KeyShareMetadata {
+    /// Public key set the share belongs to.
+    pub(crate) public_key_set: bls::PublicKeySet,
+    /// Index of the share within the set of section elders.
+    pub(crate) index: usize,
+}
+
+impl From<&SectionKeyShare> for KeyShareMetadata {
+    fn from(share: &SectionKeyShare) -> Self {
+        Self {
+            public_key_set: share.public_key_set.clone(),
+            index: share.index,
+        }
+    }
+}
+
+/// Persists the section key chain, so it survives the (possibly simultaneous) restart of
+/// elders, and the metadata of our key shares. The secret shares aren't persisted, so the
+/// metadata only tells which section keys we lost our share of on restarting. A lost share
+/// of the current section key isn't recovered: the node can't sign as an elder until the next
+/// DKG round, which is only held once the section's elders change.
+#[derive(Clone, Debug)]
+pub(crate) struct KeyShareBackup {
+    db: Db,
+    key_shares: Tree,
+    chain_file: PathBuf,
+}
+
+impl KeyShareBackup {
+    pub(crate) fn new(path: &Path) -> Result<Self> {
+        let db_dir = path.join("db").join(DATABASE_NAME.to_string());
+
+        let db = sled::open(db_dir).map_err(|error| {
+            trace!("Sled Error: {:?}", error);
+            Error::Sled(error)
+        })?;
+        let key_shares = db.open_tree(KEY_SHARES_TREE)?;
+
+        Ok(Self {
+            db,
+            key_shares,
+            chain_file: path.join(CHAIN_BACKUP_FILENAME),
+        })
+    }
+
+    /// Stores the chain, unless we already hold a longer one.
+    pub(crate) fn store_chain(&self, chain: &SecuredLinkedList) -> Result<()> {
+        if let Some(stored) = self.chain()? {
+            if stored.len() > chain.len() {
+                return Ok(());
+            }
+        }
+
+        let bytes = serialise(chain)?;
+        let _ = self.db.insert(CHAIN_KEY, bytes.as_slice())?;
+        std::fs::write(&self.chain_file, &bytes)?;
+        let _ = self.db.flush()?;
+
+        Ok(())
+    }
+
+    /// Stores the metadata of a key share we obtained from a DKG session.
+    pub(crate) fn store_key_share(&self, share: &SectionKeyShare) -> Result<()> {
+        let metadata = KeyShareMetadata::from(share);
+        let key = metadata.public_key_set.public_key().to_bytes();
+        let _ = self.key_shares.insert(key, serialise(&metadata)?)?;
+        let _ = self.key_shares.flush()?;
+
+        Ok(())
+    }
+
+    /// Returns the metadata of the key share we held of `section_key`, if any.
+    pub(crate) fn key_share(
+        &self,
+        section_key: &bls::PublicKey,
+    ) -> Result<Option<KeyShareMetadata>> {
+        match self.key_shares.get(section_key.to_bytes())? {
+            Some(value) => Ok(Some(deserialise(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the chain persisted by a previous run, if any, from the db, falling back to the
+    /// file copy.
+    pub(crate) fn chain(&self) -> Result<Option<SecuredLinkedList>> {
+        let from_db = match self.db.get(CHAIN_KEY)? {
+            Some(bytes) => deserialise::<SecuredLinkedList>(&bytes).ok(),
+            None => None,
+        };
+
+        let chain = match from_db {
+            Some(chain) => Some(chain),
+            None if self.chain_file.is_file() => {
+                warn!("Section chain missing from db, reading it from backup file");
+                let bytes = std::fs::read(&self.chain_file)?;
+                Some(deserialise::<SecuredLinkedList>(&bytes)?)
+            }
+            None => None,
+        };
+
+        Ok(chain.filter(|chain| chain.self_verify()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyShareBackup;
+    use crate::routing::section::SectionKeyShare;
+    use eyre::{eyre, Result};
+    use secured_linked_list::SecuredLinkedList;
+    use tempfile::tempdir;
+
+    #[test]
+    fn chain_and_key_share_are_recovered() -> Result<()> {
+        let root = tempdir()?;
+        let secret_key_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let public_key_set = secret_key_set.public_keys();
+        let share = SectionKeyShare {
+            public_key_set: public_key_set.clone(),
+            index: 0,
+            secret_key_share: secret_key_set.secret_key_share(0),
+        };
+        let chain = SecuredLinkedList::new(public_key_set.public_key());
+
+        {
+            let backup = KeyShareBackup::new(root.path())?;
+            backup.store_chain(&chain)?;
+            backup.store_key_share(&share)?;
+        }
+
+        let backup = KeyShareBackup::new(root.path())?;
+        let recovered = backup
+            .chain()?
+            .ok_or_else(|| eyre!("no chain was recovered"))?;
+        assert_eq!(recovered, chain);
+
+        let metadata = backup
+            .key_share(&public_key_set.public_key())?
+            .ok_or_else(|| eyre!("no key share metadata was recovered"))?;
+        assert_eq!(metadata.index, 0);
+        assert_eq!(metadata.public_key_set, public_key_set);
+
+        Ok(())
+    }
+}
@@ -13,11 +13,15 @@ use dashmap::DashMap;
 use itertools::Itertools;
 use std::collections::BTreeSet;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 const NEIGHBOUR_COUNT: usize = 2;
 const MIN_PENDING_OPS: usize = 10;
 const PENDING_OP_TOLERANCE_RATIO: f64 = 0.1;
+// For how long a message coming in corrupted counts against the node. Corruptions are
+// occasional on any link, so only those piling up recently tell of a faulty node.
+const CORRUPTED_MSG_DECAY: Duration = Duration::from_secs(10 * 60);
 
 /// Some reproducible xorname derived from the operation. Which can be re-derived from the appropriate response when received (to remove from tracking)
 type NodeIdentifier = XorName;
@@ -26,6 +30,12 @@ type NodeIdentifier = XorName;
 pub(crate) struct Liveness {
     /// One of (potentially many) different ways of assessing unresponsiveness of nodes.
     unfulfilled_requests: Arc<DashMap<NodeIdentifier, Arc<RwLock<Vec<OperationId>>>>>,
+    /// When messages from the nodes came in corrupted, each counting as an unfulfilled request
+    /// until it decays.
+    corrupted_msgs: Arc<DashMap<NodeIdentifier, Vec<Instant>>>,
+    /// Spot-checks of their advertised storage capacity the nodes failed, each counting as an
+    /// unfulfilled request.
+    capacity_discrepancies: Arc<DashMap<NodeIdentifier, usize>>,
     closest_nodes_to: Arc<DashMap<XorName, Vec<XorName>>>,
 }
 
@@ -33,10 +43,29 @@ impl Liveness {
     pub(crate) fn new() -> Self {
         Self {
             unfulfilled_requests: Arc::new(DashMap::new()),
+            corrupted_msgs: Arc::new(DashMap::new()),
+            capacity_discrepancies: Arc::new(DashMap::new()),
             closest_nodes_to: Arc::new(DashMap::new()),
         }
     }
 
+    /// Records a message from the node coming in corrupted, returning how many did so
+    /// recently.
+    pub(crate) fn record_corrupted_msg(&self, node_id: NodeIdentifier) -> usize {
+        let mut times = self.corrupted_msgs.entry(node_id).or_default();
+        times.retain(|time| time.elapsed() < CORRUPTED_MSG_DECAY);
+        times.push(Instant::now());
+        times.len()
+    }
+
+    /// Records the node failing a spot-check of its advertised storage capacity, returning how
+    /// many it failed so far.
+    pub(crate) fn record_capacity_discrepancy(&self, node_id: NodeIdentifier) -> usize {
+        let mut count = self.capacity_discrepancies.entry(node_id).or_default();
+        *count += 1;
+        *count
+    }
+
     // Inserts a pending_operation, and is deemed as such until we get the appropriate response from the node
     // Returns false if the operation already existed.
     pub(crate) async fn add_a_pending_request_operation(
@@ -66,6 +95,8 @@ impl Liveness {
         for key in &all_keys {
             if !current_members.contains(key) {
                 let _ = self.unfulfilled_requests.remove(key);
+                let _ = self.corrupted_msgs.remove(key);
+                let _ = self.capacity_discrepancies.remove(key);
                 let _ = self.closest_nodes_to.remove(key);
             }
         }
@@ -143,23 +174,14 @@ impl Liveness {
             let mut max_pending_by_neighbours = 0;
             // if let Some(max_pending_by_neighbours) =
             for neighbour in neighbours.iter() {
-                if let Some(entry) = self.unfulfilled_requests.get(neighbour) {
-                    // let (k,v) = entry.pair();
-                    let val = entry.value().read().await.len();
+                let val = self.pending_operations_count(neighbour).await;
 
-                    if val > max_pending_by_neighbours {
-                        max_pending_by_neighbours = val
-                    }
+                if val > max_pending_by_neighbours {
+                    max_pending_by_neighbours = val
                 }
             }
 
-            let pending_operations_count = if let Some(entry) = self.unfulfilled_requests.get(&node)
-            {
-                // let (k,v) = entry.pair();
-                entry.value().read().await.len()
-            } else {
-                0
-            };
+            let pending_operations_count = self.pending_operations_count(&node).await;
 
             if pending_operations_count > MIN_PENDING_OPS
                 && max_pending_by_neighbours > MIN_PENDING_OPS
@@ -177,4 +199,25 @@ impl Liveness {
         }
         unresponsive_nodes
     }
+
+    // The requests the node left unfulfilled, those it recently responded to with corrupted
+    // messages and the capacity spot-checks it failed included.
+    async fn pending_operations_count(&self, node_id: &NodeIdentifier) -> usize {
+        let pending = if let Some(entry) = self.unfulfilled_requests.get(node_id) {
+            entry.value().read().await.len()
+        } else {
+            0
+        };
+        let corrupted = self.corrupted_msgs.get(node_id).map_or(0, |times| {
+            times
+                .iter()
+                .filter(|time| time.elapsed() < CORRUPTED_MSG_DECAY)
+                .count()
+        });
+        let discrepancies = self
+            .capacity_discrepancies
+            .get(node_id)
+            .map_or(0, |count| *count.value());
+        pending + corrupted + discrepancies
+    }
 }
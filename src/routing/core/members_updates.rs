@@ -0,0 +1,135 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::system::{SectionPeers, SectionPeersUpdate};
+use crate::routing::{error::Result, section::SectionPeersUtils};
+use dashmap::DashMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use xor_name::XorName;
+
+/// Keeps track of the section members each peer was last sent in AE-Updates,
+/// so further updates only need to carry the members which changed since then.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MembersUpdates {
+    /// Digest of the members last sent to each peer.
+    sent: Arc<DashMap<XorName, XorName>>,
+    /// Sets of members sent to any peer, by digest.
+    snapshots: Arc<DashMap<XorName, SectionPeers>>,
+}
+
+impl MembersUpdates {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the update bringing `peer` up to date with `members`,
+    /// and records they'll have been sent them.
+    pub(crate) fn update_for(
+        &self,
+        peer: &XorName,
+        members: &SectionPeers,
+    ) -> Result<SectionPeersUpdate> {
+        let digest = members.digest()?;
+
+        let base = self
+            .sent
+            .get(peer)
+            .map(|entry| *entry.value())
+            .and_then(|base| self.snapshots.get(&base).map(|entry| (base, entry.clone())));
+        let update = match base {
+            Some((base, known)) => SectionPeersUpdate::Delta {
+                base,
+                changes: members.changes_since(&known),
+                digest,
+            },
+            None => SectionPeersUpdate::Full(members.clone()),
+        };
+
+        let _ = self.snapshots.insert(digest, members.clone());
+        let _ = self.sent.insert(*peer, digest);
+        self.prune();
+
+        Ok(update)
+    }
+
+    /// Forgets what `peer` was sent, so they are sent all the members next time.
+    pub(crate) fn reset(&self, peer: &XorName) {
+        let _ = self.sent.remove(peer);
+        self.prune();
+    }
+
+    /// Stops tracking the peers which aren't in `current_members` anymore.
+    pub(crate) fn retain_members_only(&self, current_members: &BTreeSet<XorName>) {
+        self.sent.retain(|peer, _| current_members.contains(peer));
+        self.prune();
+    }
+
+    // Drops the snapshots no peer is known to be at.
+    fn prune(&self) {
+        let in_use: BTreeSet<_> = self.sent.iter().map(|entry| *entry.value()).collect();
+        self.snapshots.retain(|digest, _| in_use.contains(digest));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MembersUpdates;
+    use crate::messaging::system::{NodeState, Peer, SectionPeers, SectionPeersUpdate};
+    use crate::routing::{
+        dkg::test_utils::section_signed,
+        peer::PeerUtils,
+        section::{test_utils::gen_addr, NodeStateUtils, SectionPeersUtils},
+    };
+    use eyre::{bail, Result};
+    use xor_name::XorName;
+
+    fn add_member(members: &mut SectionPeers, sk: &bls::SecretKey) -> Result<()> {
+        let peer = Peer::new(XorName::random(), gen_addr());
+        let _ = members.update(section_signed(sk, NodeState::joined(peer, None))?);
+        Ok(())
+    }
+
+    #[test]
+    fn deltas_after_first_full_update() -> Result<()> {
+        let sk = bls::SecretKey::random();
+        let mut members = SectionPeers::default();
+        add_member(&mut members, &sk)?;
+        add_member(&mut members, &sk)?;
+
+        let updates = MembersUpdates::new();
+        let adult = XorName::random();
+        assert_eq!(
+            updates.update_for(&adult, &members)?,
+            SectionPeersUpdate::Full(members.clone())
+        );
+
+        let base = members.digest()?;
+        add_member(&mut members, &sk)?;
+        match updates.update_for(&adult, &members)? {
+            SectionPeersUpdate::Delta {
+                base: delta_base,
+                changes,
+                digest,
+            } => {
+                assert_eq!(delta_base, base);
+                assert_eq!(changes.members.len(), 1);
+                assert_eq!(digest, members.digest()?);
+            }
+            update => bail!("Unexpected update {:?}", update),
+        }
+
+        updates.reset(&adult);
+        assert_eq!(
+            updates.update_for(&adult, &members)?,
+            SectionPeersUpdate::Full(members)
+        );
+
+        Ok(())
+    }
+}
@@ -10,7 +10,7 @@ use super::Core;
 use crate::messaging::{
     system::{
         DkgKey, ElderCandidates, JoinResponse, NodeState, Peer, Proposal, RelocateDetails,
-        RelocatePromise, Section, SectionAuth, SystemMsg,
+        RelocatePromise, Section, SectionAuth, SectionPeersUpdate, SystemMsg,
     },
     DstLocation, WireMsg,
 };
@@ -100,7 +100,10 @@ impl Core {
             && public_key != *self.section.chain().last_key()
         {
             let dst_section_pk = sig_share.public_key_set.public_key();
-            let msg = self.generate_ae_update(dst_section_pk, true)?;
+            let members = self
+                .members_updates
+                .update_for(&peer.0, self.section.members())?;
+            let msg = self.generate_ae_update(dst_section_pk, Some(members))?;
 
             let cmd = self.send_direct_message(peer, msg, dst_section_pk)?;
             Ok(Some(cmd))
@@ -144,10 +147,7 @@ impl Core {
         let dst_section_pk = *self.section_chain().last_key();
         // the previous PK which is likely what adults know
         let previous_pk = *self.section_chain().prev_key();
-        let node_msg = self.generate_ae_update(previous_pk, true)?;
-        let cmd = self.send_direct_message_to_nodes(nodes, node_msg, dst_section_pk)?;
-
-        Ok(vec![cmd])
+        self.send_ae_update_to_nodes(nodes, previous_pk, dst_section_pk)
     }
 
     pub(crate) fn send_ae_update_to_adults(&mut self) -> Result<Vec<Command>> {
@@ -158,11 +158,36 @@ impl Core {
             .collect();
 
         let dst_section_pk = *self.section_chain().last_key();
-        let node_msg = self.generate_ae_update(dst_section_pk, true)?;
+        self.send_ae_update_to_nodes(adults, dst_section_pk, dst_section_pk)
+    }
 
-        let cmd = self.send_direct_message_to_nodes(adults, node_msg, dst_section_pk)?;
+    /// Send an AE-Update to each of the `recipients`, with our section chain from `proof_key`,
+    /// and only the section members which changed since the last update we sent them, if any.
+    pub(crate) fn send_ae_update_to_nodes(
+        &self,
+        recipients: Vec<(XorName, SocketAddr)>,
+        proof_key: BlsPublicKey,
+        dst_section_pk: BlsPublicKey,
+    ) -> Result<Vec<Command>> {
+        // Recipients getting the same update share the same message.
+        let mut groups: Vec<(SectionPeersUpdate, Vec<(XorName, SocketAddr)>)> = vec![];
+        for (name, addr) in recipients {
+            let update = self
+                .members_updates
+                .update_for(&name, self.section.members())?;
+            match groups.iter_mut().find(|(other, _)| *other == update) {
+                Some((_, group)) => group.push((name, addr)),
+                None => groups.push((update, vec![(name, addr)])),
+            }
+        }
 
-        Ok(vec![cmd])
+        groups
+            .into_iter()
+            .map(|(update, group)| {
+                let node_msg = self.generate_ae_update(proof_key, Some(update))?;
+                self.send_direct_message_to_nodes(group, node_msg, dst_section_pk)
+            })
+            .collect()
     }
 
     pub(crate) fn send_relocate(
@@ -212,9 +237,19 @@ impl Core {
         elder_candidates: ElderCandidates,
         recipients: &[Peer],
     ) -> Result<Vec<Command>> {
-        let src_prefix = elder_candidates.prefix;
         let generation = self.section.chain().main_branch_len() as u64;
         let dkg_key = DkgKey::new(&elder_candidates, generation);
+        self.send_dkg_start_for(dkg_key, elder_candidates, recipients)
+    }
+
+    // Starts the DKG session identified by `dkg_key`, e.g. restarting a failed one.
+    pub(crate) fn send_dkg_start_for(
+        &self,
+        dkg_key: DkgKey,
+        elder_candidates: ElderCandidates,
+        recipients: &[Peer],
+    ) -> Result<Vec<Command>> {
+        let src_prefix = elder_candidates.prefix;
 
         trace!(
             "Send DkgStart for {:?} with {:?} to {:?}",
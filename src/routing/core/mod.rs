@@ -9,27 +9,47 @@
 mod api;
 mod bootstrap;
 mod capacity;
+mod capacity_proofs;
+mod chunk_inventory;
 mod chunk_records;
 mod chunk_store;
 mod comm;
 mod connectivity;
+mod data_migration;
+mod data_proofs;
 mod delivery_group;
+mod existence_checks;
+mod holder_proofs;
+mod key_share_backup;
 mod liveness_tracking;
+mod members_updates;
 mod messaging;
 mod msg_count;
 mod msg_handling;
+mod network_times;
+mod payment_store;
 mod register_storage;
+mod replication_check;
+mod resends;
+mod resource_pressure;
 mod split_barrier;
+mod waitlists;
 
 pub(crate) use bootstrap::{join_network, JoiningAsRelocated};
 pub(crate) use capacity::{CHUNK_COPY_COUNT, MIN_LEVEL_WHEN_FULL};
+pub use capacity_proofs::CapacityRecord;
 pub(crate) use chunk_store::ChunkStore;
 pub(crate) use comm::{Comm, ConnectionEvent, SendStatus};
+pub use data_migration::MigrationProgress;
+pub(crate) use payment_store::PaymentStore;
 pub(crate) use register_storage::RegisterStorage;
+pub use replication_check::{ReplicationReport, ReplicationStatus};
+pub use resource_pressure::ResourcePressure;
 
 use self::split_barrier::SplitBarrier;
 use crate::dbs::UsedSpace;
 use crate::messaging::{
+    data::DataLimits,
     signature_aggregator::SignatureAggregator,
     system::{Proposal, Section},
     MessageId,
@@ -45,8 +65,20 @@ use crate::routing::{
     Elders, Event, NodeElderChange, SectionAuthorityProviderUtils,
 };
 use capacity::Capacity;
+use capacity_proofs::CapacityProofs;
+use chunk_inventory::ChunkInventoryRounds;
+use data_migration::DataMigration;
+use data_proofs::DataProofs;
+use existence_checks::ExistenceChecks;
+use holder_proofs::HolderProofs;
 use itertools::Itertools;
+use key_share_backup::KeyShareBackup;
 use liveness_tracking::Liveness;
+use members_updates::MembersUpdates;
+use network_times::NetworkTimes;
+use replication_check::ReplicationCheck;
+use resends::Resends;
+use resource_pressure::LoadShedding;
 use resource_proof::ResourceProof;
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -79,9 +111,24 @@ pub(crate) struct Core {
     used_space: UsedSpace,
     pub(super) register_storage: RegisterStorage,
     pub(super) chunk_storage: ChunkStore,
+    pub(super) payment_store: PaymentStore,
+    key_share_backup: KeyShareBackup,
     root_storage_dir: PathBuf,
     capacity: Capacity,
+    capacity_proofs: CapacityProofs,
     liveness: Liveness,
+    chunk_inventory: ChunkInventoryRounds,
+    data_migration: DataMigration,
+    replication_check: ReplicationCheck,
+    members_updates: MembersUpdates,
+    data_proofs: DataProofs,
+    holder_proofs: HolderProofs,
+    existence_checks: ExistenceChecks,
+    resends: Resends,
+    network_times: NetworkTimes,
+    load_shedding: LoadShedding,
+    replication_factor: usize,
+    data_limits: DataLimits,
 }
 
 impl Core {
@@ -89,7 +136,7 @@ impl Core {
     pub(crate) fn new(
         comm: Comm,
         mut node: Node,
-        section: Section,
+        mut section: Section,
         section_key_share: Option<SectionKeyShare>,
         event_tx: mpsc::Sender<Event>,
         used_space: UsedSpace,
@@ -102,6 +149,29 @@ impl Core {
 
         let register_storage = RegisterStorage::new(&root_storage_dir, used_space.clone())?;
         let chunk_storage = ChunkStore::new(&root_storage_dir, used_space.clone())?;
+        let payment_store = PaymentStore::new(&root_storage_dir, used_space.clone())?;
+
+        let key_share_backup = KeyShareBackup::new(&root_storage_dir)?;
+        Self::recover_section_chain(&key_share_backup, &mut section);
+        if let Some(share) = &section_key_share {
+            if let Err(error) = key_share_backup.store_key_share(share) {
+                error!(
+                    "Failed to back up our section key share metadata: {:?}",
+                    error
+                );
+            }
+        } else if let Ok(Some(lost)) = key_share_backup.key_share(section.chain().last_key()) {
+            // The secret share isn't backed up, so it's gone for good. Nothing triggers a new
+            // DKG round for the current elders on this: we can't start one ourselves, as the
+            // `DkgStart` is signed with the section key share we lost, and the other elders
+            // aren't told of it. So we can't take part in signing until the elders next change.
+            error!(
+                "We held share {} of the current section key {:?}, which was lost on restarting: \
+                we won't take part in signing until the section's elders change",
+                lost.index,
+                lost.public_key_set.public_key()
+            );
+        }
 
         let capacity = Capacity::new(BTreeMap::new());
         let adult_liveness = Liveness::new();
@@ -123,8 +193,23 @@ impl Core {
             resource_proof: ResourceProof::new(RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY),
             register_storage,
             chunk_storage,
+            payment_store,
+            key_share_backup,
             capacity,
+            capacity_proofs: CapacityProofs::new(),
             liveness: adult_liveness,
+            chunk_inventory: ChunkInventoryRounds::new(),
+            data_migration: DataMigration::new(),
+            replication_check: ReplicationCheck::new(),
+            members_updates: MembersUpdates::new(),
+            data_proofs: DataProofs::new(),
+            holder_proofs: HolderProofs::new(),
+            existence_checks: ExistenceChecks::new(),
+            resends: Resends::new(),
+            network_times: NetworkTimes::new(),
+            load_shedding: LoadShedding::new(),
+            replication_factor: CHUNK_COPY_COUNT,
+            data_limits: DataLimits::default(),
             root_storage_dir,
             used_space,
         })
@@ -159,6 +244,10 @@ impl Core {
         }
 
         if new.last_key != old.last_key {
+            if let Err(error) = self.key_share_backup.store_chain(self.section.chain()) {
+                error!("Failed to back up our section chain: {:?}", error);
+            }
+
             if new.is_elder {
                 info!(
                     "Section updated: prefix: ({:b}), key: {:?}, elders: {}",
@@ -209,6 +298,7 @@ impl Core {
             } else {
                 NodeElderChange::None
             };
+            commands.extend(self.start_data_migration(self_status_change).await?);
 
             let sibling_elders = if new.prefix != old.prefix {
                 self.network.get(&new.prefix.sibling()).map(|sec_auth| {
@@ -268,6 +358,44 @@ impl Core {
         }
     }
 
+    // Merges the history of a section chain persisted by a previous run into ours,
+    // as long as it doesn't move our current section key.
+    fn recover_section_chain(backup: &KeyShareBackup, section: &mut Section) {
+        let recovered = match backup.chain() {
+            Ok(Some(recovered)) => recovered,
+            Ok(None) => return,
+            Err(error) => {
+                error!("Failed to read section chain backup: {:?}", error);
+                return;
+            }
+        };
+
+        if recovered.root_key() != section.genesis_key() {
+            warn!("Discarding backed up section chain from a different network");
+            return;
+        }
+
+        let mut chain = section.chain().clone();
+        match chain.merge(recovered) {
+            Ok(()) if chain.last_key() == section.chain().last_key() => {
+                info!(
+                    "Recovered section chain of {} keys from backup",
+                    chain.len()
+                );
+                section.chain = chain;
+            }
+            Ok(()) => {
+                trace!("Backed up section chain is ahead of our section, not merging it");
+            }
+            Err(error) => {
+                warn!(
+                    "Backed up section chain is incompatible with ours: {:?}",
+                    error
+                );
+            }
+        }
+    }
+
     pub(crate) fn print_network_stats(&self) {
         self.network
             .network_stats(self.section.authority_provider())
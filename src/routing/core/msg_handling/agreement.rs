@@ -46,6 +46,44 @@ impl Core {
                 self.joins_allowed = joins_allowed.1;
                 Ok(vec![])
             }
+            Proposal::DataProof(address) => {
+                let mut commands = vec![];
+                for (msg_id, user) in self.data_proofs.agreed(address, sig.clone()).await {
+                    commands.extend(self.send_data_proof(msg_id, address, sig.clone(), user)?);
+                }
+                Ok(commands)
+            }
+            Proposal::NetworkTime(timestamp) => {
+                let mut commands = vec![];
+                for (msg_id, name, user) in self.network_times.agreed(timestamp, sig.clone()).await
+                {
+                    commands.extend(self.send_network_time(
+                        msg_id,
+                        name,
+                        timestamp,
+                        sig.clone(),
+                        user,
+                    )?);
+                }
+                Ok(commands)
+            }
+            Proposal::ChunkHolders { address, holders } => {
+                let mut commands = vec![];
+                for (msg_id, user) in self
+                    .holder_proofs
+                    .agreed(address, holders.clone(), sig.clone())
+                    .await
+                {
+                    commands.extend(self.send_chunk_holders(
+                        msg_id,
+                        address,
+                        holders.clone(),
+                        sig.clone(),
+                        user,
+                    )?);
+                }
+                Ok(commands)
+            }
         }
     }
 
@@ -184,14 +222,11 @@ impl Core {
 
             let mut commands = vec![];
             if !ae_update_recipients.is_empty() {
-                let node_msg = self.generate_ae_update(sig.public_key, true)?;
-                let cmd = self.send_direct_message_to_nodes(
+                commands.extend(self.send_ae_update_to_nodes(
                     ae_update_recipients,
-                    node_msg,
                     sig.public_key,
-                )?;
-
-                commands.push(cmd);
+                    sig.public_key,
+                )?);
             }
 
             // Send the `OurElder` proposal to all of the to-be-elders so it's aggregated by them.
@@ -8,7 +8,7 @@
 
 use super::Core;
 use crate::messaging::{
-    system::{KeyedSig, SectionAuth, SectionPeers, SystemMsg},
+    system::{KeyedSig, SectionAuth, SectionPeersUpdate, SystemMsg},
     MessageType, SectionAuthorityProvider, SrcLocation, WireMsg,
 };
 use crate::routing::{
@@ -16,6 +16,7 @@ use crate::routing::{
     error::{Error, Result},
     messages::WireMsgUtils,
     routing_api::command::Command,
+    section::SectionPeersUtils,
     SectionAuthorityProviderUtils,
 };
 use crate::types::PublicKey;
@@ -32,11 +33,27 @@ impl Core {
         section_auth: SectionAuthorityProvider,
         section_signed: KeyedSig,
         proof_chain: SecuredLinkedList,
-        members: Option<SectionPeers>,
+        members: Option<SectionPeersUpdate>,
         sender: SocketAddr,
+        src_name: XorName,
     ) -> Result<Vec<Command>> {
         let snapshot = self.state_snapshot();
 
+        // A delta of the members only brings us up to date with the sender if we knew the
+        // members it's based on, or at least all the others it has, so we ask for all of them
+        // if we still don't have the same members once we've applied it.
+        let mut expected_digest = None;
+        let members = match members {
+            Some(SectionPeersUpdate::Full(members)) => Some(members),
+            Some(SectionPeersUpdate::Delta {
+                changes, digest, ..
+            }) => {
+                expected_digest = Some(digest);
+                Some(changes)
+            }
+            None => None,
+        };
+
         let signed_section_auth = SectionAuth {
             value: section_auth.clone(),
             sig: section_signed,
@@ -68,15 +85,53 @@ impl Core {
                     "Anti-Entropy: Did not update remote section SAP provided by {:?}: {:?}",
                     sender, err
                 );
-                return Err(err);
+                return Err(err.into());
             }
         }
 
         self.fire_node_event_for_any_new_adults().await?;
 
         // always run this, only changes will trigger events
-        self.update_for_new_node_state_and_fire_events(snapshot)
-            .await
+        let mut commands = self
+            .update_for_new_node_state_and_fire_events(snapshot)
+            .await?;
+
+        if let Some(expected_digest) = expected_digest {
+            let known_members = self.section.members().digest()?;
+            if known_members != expected_digest {
+                debug!(
+                    "Anti-Entropy: members delta from {:?} left us with other members than theirs, requesting all of them",
+                    sender
+                );
+                commands.push(self.send_direct_message(
+                    (src_name, sender),
+                    SystemMsg::AntiEntropyMembersResync { known_members },
+                    *self.section.chain().last_key(),
+                )?);
+            }
+        }
+
+        Ok(commands)
+    }
+
+    pub(crate) fn handle_anti_entropy_members_resync_msg(
+        &self,
+        known_members: XorName,
+        sender: SocketAddr,
+        src_name: XorName,
+    ) -> Result<Vec<Command>> {
+        if self.is_not_elder() {
+            return Ok(vec![]);
+        }
+
+        debug!(
+            "Anti-Entropy: sending all our members to {:?}, who know members {:?}",
+            src_name, known_members
+        );
+        self.members_updates.reset(&src_name);
+
+        let section_key = *self.section.chain().last_key();
+        self.send_ae_update_to_nodes(vec![(src_name, sender)], section_key, section_key)
     }
     pub(crate) async fn handle_anti_entropy_retry_msg(
         &mut self,
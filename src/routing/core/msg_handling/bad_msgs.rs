@@ -8,7 +8,7 @@
 
 use super::Core;
 use crate::messaging::{
-    system::{Peer, SystemMsg},
+    system::{Peer, SectionPeersUpdate, SystemMsg},
     NodeMsgAuthority,
 };
 use crate::routing::{
@@ -40,11 +40,12 @@ impl Core {
         self.send_direct_message((src_name, sender), bounce_system_msg, bounce_dst_section_pk)
     }
 
-    /// Generate message to update a peer with our current section chain
+    /// Generate message to update a peer with our current section chain,
+    /// and optionally our section members.
     pub(crate) fn generate_ae_update(
         &self,
         dst_section_key: BlsPublicKey,
-        add_peer_info_to_update: bool,
+        members: Option<SectionPeersUpdate>,
     ) -> Result<SystemMsg> {
         let section_signed_auth = self.section.section_signed_authority_provider().clone();
         let section_auth = section_signed_auth.value;
@@ -62,12 +63,6 @@ impl Core {
             }
         };
 
-        let members = if add_peer_info_to_update {
-            Some(self.section.members().clone())
-        } else {
-            None
-        };
-
         Ok(SystemMsg::AntiEntropyUpdate {
             section_auth,
             section_signed,
@@ -88,7 +83,7 @@ impl Core {
 
         // first lets update the sender with our section info, which they currently do not trust
         // we do not send our adult info there
-        let ae_msg = self.generate_ae_update(dst_section_key, false)?;
+        let ae_msg = self.generate_ae_update(dst_section_key, None)?;
         let cmd =
             self.send_direct_message((*sender.name(), *sender.addr()), ae_msg, dst_section_key)?;
         commands.push(cmd);
@@ -12,7 +12,7 @@ use crate::messaging::{
     SectionAuthorityProvider,
 };
 use crate::routing::{
-    dkg::DkgFailureSigSetUtils,
+    dkg::{DkgFailureSigSetUtils, DkgKeyUtils},
     error::{Error, Result},
     routing_api::command::Command,
     section::{SectionKeyShare, SectionPeersUtils},
@@ -71,6 +71,7 @@ impl Core {
     pub(crate) fn handle_dkg_failure_agreement(
         &self,
         sender: &XorName,
+        dkg_key: &DkgKey,
         failure_set: &DkgFailureSigSet,
     ) -> Result<Vec<Command>> {
         let sender = &self
@@ -81,11 +82,19 @@ impl Core {
             .peer;
 
         let generation = self.section.chain().main_branch_len() as u64;
+        if dkg_key.generation != generation {
+            trace!(
+                "Ignore DKG failure agreement for outdated session {:?}",
+                dkg_key
+            );
+            return Ok(vec![]);
+        }
+
         let elder_candidates = self
             .section
             .promote_and_demote_elders(&self.node.name())
             .into_iter()
-            .find(|elder_candidates| failure_set.verify(elder_candidates, generation));
+            .find(|elder_candidates| failure_set.verify(elder_candidates, dkg_key));
         let elder_candidates = if let Some(elder_candidates) = elder_candidates {
             elder_candidates
         } else {
@@ -95,12 +104,16 @@ impl Core {
 
         if failure_set.failed_participants.is_empty() {
             // The DKG failure is a corrupted one due to lagging.
+            // Restarting with a new attempt, for participants to tell the messages of the
+            // restarted session apart from those of the corrupted one.
+            let dkg_key = dkg_key.restart();
             trace!(
-                "Received DKG failure agreement - restarting: {:?}",
+                "Received DKG failure agreement - restarting as {:?}: {:?}",
+                dkg_key,
                 elder_candidates
             );
 
-            self.send_dkg_start(elder_candidates, slice::from_ref(sender))
+            self.send_dkg_start_for(dkg_key, elder_candidates, slice::from_ref(sender))
         } else {
             // The DKG failure is regarding failed_participants, i.e. potential unresponsive node.
             trace!(
@@ -124,6 +137,13 @@ impl Core {
 
         let public_key = key_share.public_key_set.public_key();
 
+        if let Err(error) = self.key_share_backup.store_key_share(&key_share) {
+            error!(
+                "Failed to back up our section key share metadata: {:?}",
+                error
+            );
+        }
+
         self.section_keys_provider.insert_dkg_outcome(key_share);
 
         if self.section.chain().has_key(&public_key) {
@@ -133,8 +153,15 @@ impl Core {
         result
     }
 
-    pub(crate) fn handle_dkg_failure(&mut self, failure_set: DkgFailureSigSet) -> Result<Command> {
-        let node_msg = SystemMsg::DkgFailureAgreement(failure_set);
+    pub(crate) fn handle_dkg_failure(
+        &mut self,
+        dkg_key: DkgKey,
+        failure_set: DkgFailureSigSet,
+    ) -> Result<Command> {
+        let node_msg = SystemMsg::DkgFailureAgreement {
+            dkg_key,
+            failure_set,
+        };
         self.send_message_to_our_elders(node_msg)
     }
 }
@@ -17,9 +17,10 @@ use crate::messaging::{
 use crate::routing::{
     error::Result, peer::PeerUtils, relocation::RelocatePayloadUtils,
     routing_api::command::Command, section::SectionPeersUtils, FIRST_SECTION_MAX_AGE,
-    FIRST_SECTION_MIN_AGE, MIN_ADULT_AGE,
+    FIRST_SECTION_MIN_AGE, MAX_CLOCK_SKEW, MIN_ADULT_AGE,
 };
 use bls::PublicKey as BlsPublicKey;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 // Message handling
 impl Core {
@@ -63,11 +64,62 @@ impl Core {
         }
 
         if self.section.members().is_joined(peer.name()) {
+            let member_addr = self
+                .section
+                .members()
+                .get(peer.name())
+                .map(|member| *member.peer.addr());
+            match member_addr {
+                Some(addr) if addr != *peer.addr() => {
+                    // Another node runs with the same identity, e.g. off a copy of the same
+                    // root dir. Letting both take turns at being the member would make us flap.
+                    debug!(
+                        "Rejecting JoinRequest from {} - already member of our section at {}.",
+                        peer, addr
+                    );
+                    let node_msg = SystemMsg::JoinResponse(Box::new(JoinResponse::Rejected(
+                        JoinRejectionReason::DuplicateIdentity(addr),
+                    )));
+                    trace!("Sending {:?} to {}", node_msg, peer);
+                    return Ok(vec![self.send_direct_message(
+                        (*peer.name(), *peer.addr()),
+                        node_msg,
+                        *self.section.chain().last_key(),
+                    )?]);
+                }
+                _ => {
+                    debug!(
+                        "Ignoring JoinRequest from {} - already member of our section.",
+                        peer
+                    );
+                    return Ok(vec![]);
+                }
+            }
+        }
+
+        let elder_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or(0);
+        let skew = if join_request.timestamp > elder_timestamp {
+            join_request.timestamp - elder_timestamp
+        } else {
+            elder_timestamp - join_request.timestamp
+        };
+        if skew > MAX_CLOCK_SKEW.as_secs() {
             debug!(
-                "Ignoring JoinRequest from {} - already member of our section.",
-                peer
+                "Rejecting JoinRequest from {} - its clock is {} seconds off ours.",
+                peer, skew
             );
-            return Ok(vec![]);
+            let node_msg = SystemMsg::JoinResponse(Box::new(JoinResponse::Rejected(
+                JoinRejectionReason::ClockSkew { elder_timestamp },
+            )));
+            trace!("Sending {:?} to {}", node_msg, peer);
+            return Ok(vec![self.send_direct_message(
+                (*peer.name(), *peer.addr()),
+                node_msg,
+                *self.section.chain().last_key(),
+            )?]);
         }
 
         if !self.joins_allowed {
@@ -159,6 +211,11 @@ impl Core {
             return Ok(vec![cmd]);
         }
 
+        if let Some(capacity) = join_request.committed_capacity {
+            debug!("{} commits to {} bytes of storage", peer, capacity);
+            self.capacity_proofs.advertised(*peer.name(), capacity);
+        }
+
         Ok(vec![Command::ProposeOnline {
             peer,
             previous_name: None,
@@ -17,9 +17,9 @@ mod resource_proof;
 mod service_msgs;
 mod update_section;
 
-use super::Core;
+use super::{network_times::NetworkTimes, Core};
 use crate::messaging::{
-    data::{ServiceMsg, StorageLevel},
+    data::{Error as ErrorMessage, NetworkTime, ServiceMsg, StorageLevel},
     signature_aggregator::Error as AggregatorError,
     system::{NodeCmd, NodeQuery, Proposal, SystemMsg},
     DstLocation, EndUser, MessageId, MessageType, MsgKind, NodeMsgAuthority, SectionAuth,
@@ -35,7 +35,7 @@ use crate::types::{Chunk, Keypair, PublicKey};
 use bls::PublicKey as BlsPublicKey;
 use bytes::Bytes;
 use rand::rngs::OsRng;
-use std::{collections::BTreeSet, net::SocketAddr};
+use std::{collections::BTreeSet, net::SocketAddr, time::SystemTime};
 use xor_name::XorName;
 
 // Message handling
@@ -108,6 +108,7 @@ impl Core {
                         SystemMsg::AntiEntropyRetry { .. }
                         | SystemMsg::AntiEntropyUpdate { .. }
                         | SystemMsg::AntiEntropyRedirect { .. }
+                        | SystemMsg::AntiEntropyMembersResync { .. }
                         | SystemMsg::JoinRequest(_)
                         | SystemMsg::JoinAsRelocatedRequest(_) => {}
                         _ => match dst_location.section_pk() {
@@ -144,6 +145,7 @@ impl Core {
                     msg,
                     payload,
                     known_keys,
+                    trace: None,
                 }])
             }
             MessageType::Service {
@@ -239,6 +241,7 @@ impl Core {
                         msg,
                         msg_authority,
                         dst_location,
+                        trace: None,
                     };
 
                     Ok(vec![cmd])
@@ -324,9 +327,14 @@ impl Core {
                     proof_chain,
                     members,
                     sender,
+                    src_name,
                 )
                 .await
             }
+            SystemMsg::AntiEntropyMembersResync { known_members } => {
+                trace!("Handling msg: AE-MembersResync from {}", sender);
+                self.handle_anti_entropy_members_resync_msg(known_members, sender, src_name)
+            }
             SystemMsg::Relocate(ref details) => {
                 trace!("Handling msg: Relocate from {}", sender);
                 if let NodeMsgAuthority::Section(section_signed) = msg_authority {
@@ -411,9 +419,12 @@ impl Core {
                 trace!("Handling msg: Dkg-FailureObservation from {}", sender);
                 self.handle_dkg_failure_observation(dkg_key, &failed_participants, sig)
             }
-            SystemMsg::DkgFailureAgreement(sig_set) => {
+            SystemMsg::DkgFailureAgreement {
+                dkg_key,
+                failure_set,
+            } => {
                 trace!("Handling msg: Dkg-FailureAgreement from {}", sender);
-                self.handle_dkg_failure_agreement(&src_name, &sig_set)
+                self.handle_dkg_failure_agreement(&src_name, &dkg_key, &failure_set)
             }
             SystemMsg::Propose {
                 ref content,
@@ -452,6 +463,29 @@ impl Core {
 
                 commands.extend(self.check_lagging((src_name, sender), sig_share)?);
 
+                match content {
+                    // We only vouch for chunks our own check shows to be held.
+                    Proposal::DataProof(address) => {
+                        self.data_proofs.co_sign(*address).await;
+                        commands.extend(self.check_holders(*address).await?);
+                    }
+                    // We only vouch for timestamps our clock roughly agrees with.
+                    Proposal::NetworkTime(timestamp) => {
+                        let own_timestamp = NetworkTime::coarse_timestamp(SystemTime::now());
+                        if NetworkTimes::vouches_for(*timestamp, own_timestamp)
+                            && self.network_times.to_propose(*timestamp).await
+                        {
+                            commands.extend(self.propose(content.clone())?);
+                        }
+                    }
+                    // We only vouch for holders our own check shows to hold the chunk.
+                    Proposal::ChunkHolders { address, holders } => {
+                        self.holder_proofs.co_sign(*address, holders.clone()).await;
+                        commands.extend(self.check_holders(*address).await?);
+                    }
+                    _ => {}
+                }
+
                 let result = self.handle_proposal(content.clone(), sig_share.clone())?;
                 commands.extend(result);
 
@@ -476,6 +510,10 @@ impl Core {
 
                 Ok(vec![])
             }
+            SystemMsg::NodeMsgError {
+                error: ErrorMessage::CorruptedMessage,
+                correlation_id,
+            } => self.resend_corrupted_store(src_name, correlation_id).await,
             SystemMsg::NodeMsgError {
                 error,
                 correlation_id,
@@ -546,6 +584,169 @@ impl Core {
 
                         return self.republish_chunk(chunk).await;
                     }
+                    NodeCmd::ReportChunkInventory {
+                        partner,
+                        adults,
+                        cells,
+                    } => {
+                        let requesting_elder = msg_authority.get_auth_xorname();
+                        if !self.section.is_elder(&requesting_elder) {
+                            warn!(
+                                "Ignoring chunk inventory request from {:?}, not an Elder of our section",
+                                requesting_elder
+                            );
+                            return Ok(vec![]);
+                        }
+                        return self.handle_report_chunk_inventory(
+                            requesting_elder,
+                            partner,
+                            adults,
+                            cells,
+                        );
+                    }
+                    NodeCmd::ChunkInventoryReport {
+                        inventory,
+                        partner,
+                        excess,
+                    } => {
+                        if !self.is_elder() {
+                            error!("Received unexpected message while Adult");
+                            return Ok(vec![]);
+                        }
+                        let adult = msg_authority.get_auth_xorname();
+                        return self
+                            .handle_chunk_inventory_report(adult, partner, inventory, excess);
+                    }
+                    NodeCmd::ReplicateChunks { names, to } => {
+                        let elder = msg_authority.get_auth_xorname();
+                        if !self.section.is_elder(&elder) {
+                            warn!(
+                                "Ignoring chunks replication request from {:?}, not an Elder of our section",
+                                elder
+                            );
+                            return Ok(vec![]);
+                        }
+                        info!(
+                            "Replicating {} chunks to {} with MessageId {:?}",
+                            names.len(),
+                            to,
+                            msg_id
+                        );
+                        return self.replicate_chunks(names, to);
+                    }
+                    NodeCmd::HandOffRegisters(data) => {
+                        if !self.is_elder() {
+                            error!("Received unexpected message while Adult");
+                            return Ok(vec![]);
+                        }
+                        info!(
+                            "Merging {} registers handed off with MessageId {:?}",
+                            data.0.len(),
+                            msg_id
+                        );
+                        self.register_storage.merge(data)?;
+                        return Ok(vec![]);
+                    }
+                    NodeCmd::CheckHandoff { chunks, registers } => {
+                        let requester = msg_authority.get_auth_xorname();
+                        return self.handle_check_handoff(requester, chunks, registers);
+                    }
+                    NodeCmd::HandoffChecked { chunks, registers } => {
+                        let holder = msg_authority.get_auth_xorname();
+                        return self.handle_handoff_checked(holder, chunks, registers).await;
+                    }
+                    NodeCmd::CheckReplication(target) => {
+                        let requesting_elder = msg_authority.get_auth_xorname();
+                        if !self.section.is_elder(&requesting_elder) {
+                            warn!(
+                                "Ignoring replication check from {:?}, not an Elder of our section",
+                                requesting_elder
+                            );
+                            return Ok(vec![]);
+                        }
+                        return self.handle_check_replication(requesting_elder, target);
+                    }
+                    NodeCmd::ReplicationChecked { held } => {
+                        if !self.is_elder() {
+                            error!("Received unexpected message while Adult");
+                            return Ok(vec![]);
+                        }
+                        let adult = msg_authority.get_auth_xorname();
+                        return self.handle_replication_checked(adult, held).await;
+                    }
+                    NodeCmd::ReportCapacity => {
+                        let requesting_elder = msg_authority.get_auth_xorname();
+                        if !self.section.is_elder(&requesting_elder) {
+                            warn!(
+                                "Ignoring capacity request from {:?}, not an Elder of our section",
+                                requesting_elder
+                            );
+                            return Ok(vec![]);
+                        }
+                        return Ok(self.handle_report_capacity(requesting_elder));
+                    }
+                    NodeCmd::CapacityReported { capacity } => {
+                        if !self.is_elder() {
+                            error!("Received unexpected message while Adult");
+                            return Ok(vec![]);
+                        }
+                        let adult = msg_authority.get_auth_xorname();
+                        self.handle_capacity_reported(adult, capacity);
+                        return Ok(vec![]);
+                    }
+                    NodeCmd::StoreCapacityFiller { id, seed, size } => {
+                        let requesting_elder = msg_authority.get_auth_xorname();
+                        if !self.section.is_elder(&requesting_elder) {
+                            warn!(
+                                "Ignoring capacity filler from {:?}, not an Elder of our section",
+                                requesting_elder
+                            );
+                            return Ok(vec![]);
+                        }
+                        return self
+                            .handle_store_capacity_filler(requesting_elder, id, seed, size)
+                            .await;
+                    }
+                    NodeCmd::CapacityFillerStored { id, stored } => {
+                        if !self.is_elder() {
+                            error!("Received unexpected message while Adult");
+                            return Ok(vec![]);
+                        }
+                        let adult = msg_authority.get_auth_xorname();
+                        return self.handle_capacity_filler_stored(adult, id, stored).await;
+                    }
+                    NodeCmd::ReadCapacityFiller { id, salt, offsets } => {
+                        let requesting_elder = msg_authority.get_auth_xorname();
+                        if !self.section.is_elder(&requesting_elder) {
+                            warn!(
+                                "Ignoring capacity filler read from {:?}, not an Elder of our section",
+                                requesting_elder
+                            );
+                            return Ok(vec![]);
+                        }
+                        return self
+                            .handle_read_capacity_filler(requesting_elder, id, salt, offsets)
+                            .await;
+                    }
+                    NodeCmd::CapacityFillerRead { id, proof } => {
+                        if !self.is_elder() {
+                            error!("Received unexpected message while Adult");
+                            return Ok(vec![]);
+                        }
+                        let adult = msg_authority.get_auth_xorname();
+                        return self.handle_capacity_filler_read(adult, id, proof).await;
+                    }
+                    NodeCmd::ClockReading { time } => {
+                        let elder = msg_authority.get_auth_xorname();
+                        if !self.is_elder() || !self.section.is_elder(&elder) {
+                            warn!(
+                                "Ignoring clock reading from {:?}, not an Elder of our section",
+                                elder
+                            );
+                            return Ok(vec![]);
+                        }
+                        return self.handle_clock_reading(elder, time).await;
+                    }
                     _ => {
                         self.send_event(Event::MessageReceived {
                             msg_id,
@@ -570,6 +771,11 @@ impl Core {
                         self.handle_get_chunk_at_adult(msg_id, address, origin, sender_xorname)
                             .await
                     }
+                    // Whether chunks are held is answered the same way, without their content.
+                    NodeQuery::ChunkExists { origin, address } => {
+                        let sender_xorname = msg_authority.get_auth_xorname();
+                        self.handle_chunk_exists_at_adult(msg_id, address, origin, sender_xorname)
+                    }
                     _ => {
                         self.send_event(Event::MessageReceived {
                             msg_id,
@@ -645,7 +851,11 @@ impl Core {
                 section_pk: self.section.section_auth.value.section_key(),
             };
 
-            cmds.push(Command::PrepareNodeMsgToSend { msg, dst });
+            cmds.push(Command::PrepareNodeMsgToSend {
+                msg,
+                dst,
+                trace: None,
+            });
         }
         cmds
     }
@@ -677,8 +887,18 @@ impl Core {
         targets: BTreeSet<XorName>,
         aggregation: bool,
     ) -> Result<Vec<Command>> {
-        let msg_id = MessageId::new();
+        self.send_node_msg_to_targets_as(msg, MessageId::new(), targets, aggregation)
+    }
 
+    // Sends `msg` to `targets` in a message of the given id, e.g. for the recipients' responses
+    // to be correlated with it.
+    pub(super) fn send_node_msg_to_targets_as(
+        &self,
+        msg: SystemMsg,
+        msg_id: MessageId,
+        targets: BTreeSet<XorName>,
+        aggregation: bool,
+    ) -> Result<Vec<Command>> {
         let our_name = self.node().name();
 
         // we create a dummy/random dst location,
@@ -766,6 +986,7 @@ impl Core {
         let msg = MsgKind::ServiceMsg(ServiceAuth {
             public_key: keypair.public_key(),
             signature,
+            delegation: None,
         });
 
         Ok((msg, payload))
@@ -7,18 +7,23 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::Core;
-use crate::dbs::convert_to_error_message as convert_db_error_to_error_message;
+use crate::dbs::{convert_to_error_message as convert_db_error_to_error_message, Error as DbError};
 use crate::messaging::{
-    data::{CmdError, DataCmd, DataQuery, QueryResponse, RegisterRead, RegisterWrite, ServiceMsg},
-    system::{NodeQueryResponse, SystemMsg},
+    data::{
+        ChunkHolders, CmdError, DataCmd, DataProof, DataQuery, Error as ErrorMessage, NetworkTime,
+        QueryResponse, RegisterRead, RegisterWrite, ServiceMsg,
+    },
+    system::{KeyedSig, NodeCmd, NodeQueryResponse, Proposal, SystemMsg},
     AuthorityProof, DstLocation, EndUser, MessageId, MsgKind, NodeAuth, ServiceAuth, WireMsg,
 };
-use crate::routing::{
-    core::capacity::CHUNK_COPY_COUNT, error::Result, peer::PeerUtils, routing_api::command::Command,
-};
-use crate::types::{ChunkAddress, PublicKey};
+use crate::routing::{error::Result, peer::PeerUtils, routing_api::command::Command};
+use crate::types::{ChunkAddress, DataAddress, PaymentProof, PublicKey};
 use itertools::Itertools;
-use std::{cmp::Ordering, collections::BTreeSet};
+use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
+    time::{SystemTime, UNIX_EPOCH},
+};
 use xor_name::XorName;
 
 impl Core {
@@ -47,6 +52,30 @@ impl Core {
         Ok(vec![command])
     }
 
+    /// Forms a command to answer the provided query with an error
+    pub(crate) fn send_query_error_response(
+        &self,
+        query: &DataQuery,
+        error: ErrorMessage,
+        target: EndUser,
+        msg_id: MessageId,
+    ) -> Result<Vec<Command>> {
+        let msg = ServiceMsg::QueryResponse {
+            response: query.error(error)?,
+            correlation_id: msg_id,
+        };
+
+        let dst = DstLocation::EndUser(target);
+
+        // FIXME: define which signature/authority this message should really carry,
+        // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+        // Giving a random sig temporarily
+        let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+        let wire_msg = WireMsg::new_msg(MessageId::new(), payload, msg_kind, dst)?;
+
+        Ok(vec![Command::ParseAndSendWireMsg(wire_msg)])
+    }
+
     /// Handle register commands
     pub(crate) async fn handle_register_write(
         &self,
@@ -55,7 +84,11 @@ impl Core {
         user: EndUser,
         auth: AuthorityProof<ServiceAuth>,
     ) -> Result<Vec<Command>> {
-        match self.register_storage.write(register_write, auth).await {
+        match self
+            .register_storage
+            .write(register_write, auth, &self.data_limits)
+            .await
+        {
             Ok(_) => {
                 info!("Successfully wrote Register from Message: {:?}", msg_id);
                 Ok(vec![])
@@ -78,7 +111,7 @@ impl Core {
         user: EndUser,
         auth: AuthorityProof<ServiceAuth>,
     ) -> Result<Vec<Command>> {
-        match self.register_storage.read(&query, auth.public_key) {
+        match self.register_storage.read(&query, auth.requester()) {
             Ok(response) => {
                 if response.failed_with_data_not_found() {
                     // we don't return data not found errors.
@@ -112,6 +145,478 @@ impl Core {
         }
     }
 
+    /// Handle recording of a payment proof
+    pub(crate) fn handle_record_payment(
+        &self,
+        msg_id: MessageId,
+        proof: PaymentProof,
+        user: EndUser,
+        auth: AuthorityProof<ServiceAuth>,
+    ) -> Result<Vec<Command>> {
+        match self
+            .payment_store
+            .write(proof, auth.public_key, auth.requester())
+        {
+            Ok(()) => {
+                info!("Successfully recorded payment from Message: {:?}", msg_id);
+                Ok(vec![])
+            }
+            Err(error) => {
+                trace!("Problem on recording payment! {:?}", error);
+                let error = convert_db_error_to_error_message(error);
+
+                let error = CmdError::Data(error);
+                self.send_cmd_error_response(error, user, msg_id)
+            }
+        }
+    }
+
+    /// Handle cataloguing of the data a payment proof is for
+    pub(crate) async fn handle_catalog_payment(
+        &self,
+        msg_id: MessageId,
+        owner: PublicKey,
+        proof: PaymentProof,
+        user: EndUser,
+        auth: AuthorityProof<ServiceAuth>,
+    ) -> Result<Vec<Command>> {
+        match self
+            .payment_store
+            .catalog(owner, proof, auth.public_key, auth.requester())
+            .await
+        {
+            Ok(()) => {
+                info!("Successfully catalogued payment from Message: {:?}", msg_id);
+                Ok(vec![])
+            }
+            Err(error) => {
+                trace!("Problem on cataloguing payment! {:?}", error);
+                let error = convert_db_error_to_error_message(error);
+
+                let error = CmdError::Data(error);
+                self.send_cmd_error_response(error, user, msg_id)
+            }
+        }
+    }
+
+    /// Handle cataloguing of data stored by its owner
+    pub(crate) async fn handle_catalog_data(
+        &self,
+        msg_id: MessageId,
+        owner: PublicKey,
+        address: DataAddress,
+        user: EndUser,
+        auth: AuthorityProof<ServiceAuth>,
+    ) -> Result<Vec<Command>> {
+        match self
+            .payment_store
+            .catalog_data(owner, address, auth.requester())
+            .await
+        {
+            Ok(()) => {
+                info!("Successfully catalogued data from Message: {:?}", msg_id);
+                Ok(vec![])
+            }
+            Err(error) => {
+                trace!("Problem on cataloguing data! {:?}", error);
+                let error = convert_db_error_to_error_message(error);
+
+                let error = CmdError::Data(error);
+                self.send_cmd_error_response(error, user, msg_id)
+            }
+        }
+    }
+
+    /// Handle listings of the catalog of owned data
+    pub(crate) fn handle_list_owned_data(
+        &self,
+        msg_id: MessageId,
+        query: DataQuery,
+        user: EndUser,
+        auth: AuthorityProof<ServiceAuth>,
+    ) -> Result<Vec<Command>> {
+        let (owner, start_after, limit) = match &query {
+            DataQuery::ListOwnedData {
+                owner,
+                start_after,
+                limit,
+            } => (*owner, start_after.clone(), *limit),
+            _ => return Ok(vec![]),
+        };
+        let response = query
+            .operation_id()
+            .map_err(|_| DbError::NoOperationId)
+            .and_then(|operation_id| {
+                self.payment_store.list_owned(
+                    owner,
+                    auth.requester(),
+                    start_after,
+                    limit,
+                    operation_id,
+                )
+            });
+
+        match response {
+            Ok(response) => {
+                let msg = ServiceMsg::QueryResponse {
+                    response,
+                    correlation_id: msg_id,
+                };
+
+                // FIXME: define which signature/authority this message should really carry,
+                // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+                // Giving a random sig temporarily
+                let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+
+                let dst = DstLocation::EndUser(user);
+                let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst)?;
+
+                Ok(vec![Command::ParseAndSendWireMsg(wire_msg)])
+            }
+            Err(error) => {
+                trace!("Problem on listing owned data! {:?}", error);
+                let error = convert_db_error_to_error_message(error);
+                let error = CmdError::Data(error);
+
+                self.send_cmd_error_response(error, user, msg_id)
+            }
+        }
+    }
+
+    /// Handle payment proof reads
+    pub(crate) fn handle_get_payment_proof(
+        &self,
+        msg_id: MessageId,
+        address: DataAddress,
+        user: EndUser,
+        auth: AuthorityProof<ServiceAuth>,
+    ) -> Result<Vec<Command>> {
+        let response = DataQuery::GetPaymentProof(address.clone())
+            .operation_id()
+            .map_err(|_| DbError::NoOperationId)
+            .and_then(|operation_id| {
+                self.payment_store
+                    .read(&address, auth.requester(), operation_id)
+            });
+
+        match response {
+            Ok(response) => {
+                let msg = ServiceMsg::QueryResponse {
+                    response,
+                    correlation_id: msg_id,
+                };
+
+                // FIXME: define which signature/authority this message should really carry,
+                // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+                // Giving a random sig temporarily
+                let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+
+                let dst = DstLocation::EndUser(user);
+                let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst)?;
+
+                Ok(vec![Command::ParseAndSendWireMsg(wire_msg)])
+            }
+            Err(DbError::NoSuchData(_)) => {
+                // we don't return data not found errors.
+                Ok(vec![])
+            }
+            Err(error) => {
+                trace!("Problem on reading payment proof! {:?}", error);
+                let error = convert_db_error_to_error_message(error);
+                let error = CmdError::Data(error);
+
+                self.send_cmd_error_response(error, user, msg_id)
+            }
+        }
+    }
+
+    /// Handle queries for the number of copies of each chunk our section maintains
+    pub(crate) fn handle_get_replication_factor(
+        &self,
+        msg_id: MessageId,
+        name: XorName,
+        user: EndUser,
+    ) -> Result<Vec<Command>> {
+        let operation_id = DataQuery::GetReplicationFactor(name).operation_id()?;
+        let msg = ServiceMsg::QueryResponse {
+            response: QueryResponse::GetReplicationFactor((
+                Ok(self.get_copy_count()),
+                operation_id,
+            )),
+            correlation_id: msg_id,
+        };
+
+        // FIXME: define which signature/authority this message should really carry,
+        // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+        // Giving a random sig temporarily
+        let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+
+        let dst = DstLocation::EndUser(user);
+        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst)?;
+
+        Ok(vec![Command::ParseAndSendWireMsg(wire_msg)])
+    }
+
+    /// Handle queries for the limits on the size of the data our section accepts
+    pub(crate) fn handle_get_data_limits(
+        &self,
+        msg_id: MessageId,
+        name: XorName,
+        user: EndUser,
+    ) -> Result<Vec<Command>> {
+        let operation_id = DataQuery::GetDataLimits(name).operation_id()?;
+        let msg = ServiceMsg::QueryResponse {
+            response: QueryResponse::GetDataLimits((Ok(self.data_limits), operation_id)),
+            correlation_id: msg_id,
+        };
+
+        // FIXME: define which signature/authority this message should really carry,
+        // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+        // Giving a random sig temporarily
+        let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+
+        let dst = DstLocation::EndUser(user);
+        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst)?;
+
+        Ok(vec![Command::ParseAndSendWireMsg(wire_msg)])
+    }
+
+    /// Handle queries for our section's signature over a chunk address, checking the chunk is
+    /// held before proposing to sign it, and answering once the signature is agreed on.
+    pub(crate) async fn handle_get_data_proof(
+        &self,
+        msg_id: MessageId,
+        address: ChunkAddress,
+        user: EndUser,
+    ) -> Result<Vec<Command>> {
+        if let Some(sig) = self.data_proofs.sig(&address).await {
+            return self.send_data_proof(msg_id, address, sig, user);
+        }
+
+        self.data_proofs.wait_for(address, msg_id, user).await;
+        self.check_holders(address).await
+    }
+
+    /// Send our section's signature over `address` to `user`,
+    /// along with the proof chain of its key from the genesis key.
+    pub(crate) fn send_data_proof(
+        &self,
+        msg_id: MessageId,
+        address: ChunkAddress,
+        sig: KeyedSig,
+        user: EndUser,
+    ) -> Result<Vec<Command>> {
+        let operation_id = DataQuery::GetDataProof(address).operation_id()?;
+        let proof_chain = self
+            .section
+            .chain()
+            .get_proof_chain(self.section.genesis_key(), &sig.public_key)?;
+        let msg = ServiceMsg::QueryResponse {
+            response: QueryResponse::GetDataProof((
+                Ok(DataProof {
+                    address,
+                    sig,
+                    proof_chain,
+                }),
+                operation_id,
+            )),
+            correlation_id: msg_id,
+        };
+
+        // FIXME: define which signature/authority this message should really carry,
+        // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+        // Giving a random sig temporarily
+        let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+
+        let dst = DstLocation::EndUser(user);
+        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst)?;
+
+        Ok(vec![Command::ParseAndSendWireMsg(wire_msg)])
+    }
+
+    /// Handle queries for the Adults holding a chunk, checking which of those we'd pick do,
+    /// and answering once the signature over them is agreed on.
+    pub(crate) async fn handle_get_chunk_holders(
+        &self,
+        msg_id: MessageId,
+        address: ChunkAddress,
+        user: EndUser,
+    ) -> Result<Vec<Command>> {
+        if let Some((holders, sig)) = self.holder_proofs.latest(&address).await {
+            return self.send_chunk_holders(msg_id, address, holders, sig, user);
+        }
+
+        self.holder_proofs.wait_for(address, msg_id, user).await;
+        self.check_holders(address).await
+    }
+
+    /// Answer `user`'s `query` for a signature over a chunk, telling them none of the Adults
+    /// meant to hold the chunk do.
+    pub(crate) fn send_chunk_not_held(
+        &self,
+        msg_id: MessageId,
+        query: DataQuery,
+        user: EndUser,
+    ) -> Result<Vec<Command>> {
+        let address = match &query {
+            DataQuery::GetDataProof(address) | DataQuery::GetChunkHolders(address) => *address,
+            query => {
+                error!(
+                    "Not answering {:?}, as it's not for a chunk's signature",
+                    query
+                );
+                return Ok(vec![]);
+            }
+        };
+        let response = query.error(ErrorMessage::DataNotFound(DataAddress::Chunk(address)))?;
+        let msg = ServiceMsg::QueryResponse {
+            response,
+            correlation_id: msg_id,
+        };
+
+        // FIXME: define which signature/authority this message should really carry,
+        // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+        // Giving a random sig temporarily
+        let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+
+        let dst = DstLocation::EndUser(user);
+        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst)?;
+
+        Ok(vec![Command::ParseAndSendWireMsg(wire_msg)])
+    }
+
+    /// Send our section's signature over the `holders` of `address` to `user`,
+    /// along with the proof chain of its key from the genesis key.
+    pub(crate) fn send_chunk_holders(
+        &self,
+        msg_id: MessageId,
+        address: ChunkAddress,
+        holders: BTreeSet<XorName>,
+        sig: KeyedSig,
+        user: EndUser,
+    ) -> Result<Vec<Command>> {
+        let operation_id = DataQuery::GetChunkHolders(address).operation_id()?;
+        let proof_chain = self
+            .section
+            .chain()
+            .get_proof_chain(self.section.genesis_key(), &sig.public_key)?;
+        let msg = ServiceMsg::QueryResponse {
+            response: QueryResponse::GetChunkHolders((
+                Ok(ChunkHolders {
+                    address,
+                    holders,
+                    sig,
+                    proof_chain,
+                }),
+                operation_id,
+            )),
+            correlation_id: msg_id,
+        };
+
+        // FIXME: define which signature/authority this message should really carry,
+        // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+        // Giving a random sig temporarily
+        let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+
+        let dst = DstLocation::EndUser(user);
+        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst)?;
+
+        Ok(vec![Command::ParseAndSendWireMsg(wire_msg)])
+    }
+
+    /// Handle queries for the network time, sharing our clock reading with the other Elders
+    /// for the median of the readings to be proposed, and answering once a timestamp is
+    /// agreed on.
+    pub(crate) async fn handle_get_network_time(
+        &self,
+        msg_id: MessageId,
+        name: XorName,
+        user: EndUser,
+    ) -> Result<Vec<Command>> {
+        let timestamp = NetworkTime::coarse_timestamp(SystemTime::now());
+        if let Some(sig) = self.network_times.sig(timestamp).await {
+            return self.send_network_time(msg_id, name, timestamp, sig, user);
+        }
+
+        self.network_times.wait_for(msg_id, name, user).await;
+        self.share_clock_reading().await
+    }
+
+    /// Records the clock reading of another Elder, sharing ours in return if we haven't lately.
+    pub(crate) async fn handle_clock_reading(
+        &self,
+        elder: XorName,
+        time: u64,
+    ) -> Result<Vec<Command>> {
+        self.network_times.record_reading(elder, time).await;
+        self.share_clock_reading().await
+    }
+
+    // Shares our clock reading with the other Elders unless we just did, then proposes the
+    // median of the readings of the Elders, once we have enough of them.
+    async fn share_clock_reading(&self) -> Result<Vec<Command>> {
+        let mut commands = vec![];
+        let elders = self.section.authority_provider().names();
+        if self.network_times.to_share().await {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0);
+            let our_name = self.node.name();
+            self.network_times.record_reading(our_name, time).await;
+            let mut others = elders.clone();
+            let _ = others.remove(&our_name);
+            let msg = SystemMsg::NodeCmd(NodeCmd::ClockReading { time });
+            commands.extend(self.send_node_msg_to_targets(msg, others, false)?);
+        }
+
+        if let Some(timestamp) = self.network_times.median(&elders).await {
+            // Already proposed otherwise, we'll answer once it's agreed on.
+            if self.network_times.to_propose(timestamp).await {
+                commands.extend(self.propose(Proposal::NetworkTime(timestamp))?);
+            }
+        }
+        Ok(commands)
+    }
+
+    /// Send our section's signature over `timestamp` to `user`,
+    /// along with the proof chain of its key from the genesis key.
+    pub(crate) fn send_network_time(
+        &self,
+        msg_id: MessageId,
+        name: XorName,
+        timestamp: u64,
+        sig: KeyedSig,
+        user: EndUser,
+    ) -> Result<Vec<Command>> {
+        let operation_id = DataQuery::GetNetworkTime(name).operation_id()?;
+        let proof_chain = self
+            .section
+            .chain()
+            .get_proof_chain(self.section.genesis_key(), &sig.public_key)?;
+        let msg = ServiceMsg::QueryResponse {
+            response: QueryResponse::GetNetworkTime((
+                Ok(NetworkTime {
+                    timestamp,
+                    sig,
+                    proof_chain,
+                }),
+                operation_id,
+            )),
+            correlation_id: msg_id,
+        };
+
+        // FIXME: define which signature/authority this message should really carry,
+        // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+        // Giving a random sig temporarily
+        let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+
+        let dst = DstLocation::EndUser(user);
+        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst)?;
+
+        Ok(vec![Command::ParseAndSendWireMsg(wire_msg)])
+    }
+
     /// Sign and serialize node message to be sent
     pub(crate) fn prepare_node_msg(
         &self,
@@ -160,7 +665,11 @@ impl Core {
                     section_pk,
                 };
 
-                commands.push(Command::PrepareNodeMsgToSend { msg, dst });
+                commands.push(Command::PrepareNodeMsgToSend {
+                    msg,
+                    dst,
+                    trace: None,
+                });
 
                 Ok(commands)
             }
@@ -172,6 +681,90 @@ impl Core {
         }
     }
 
+    /// Handle chunk existence check
+    pub(crate) fn handle_chunk_exists_at_adult(
+        &self,
+        msg_id: MessageId,
+        address: ChunkAddress,
+        user: EndUser,
+        requesting_elder: XorName,
+    ) -> Result<Vec<Command>> {
+        trace!("Handling chunk existence check at adult");
+        let exists = match self.chunk_storage.has(&address) {
+            Ok(exists) => exists,
+            Err(error) => {
+                error!("Problem checking chunk in storage! {:?}", error);
+                return Ok(vec![]);
+            }
+        };
+
+        let msg = SystemMsg::NodeQueryResponse {
+            response: NodeQueryResponse::ChunkExists { address, exists },
+            correlation_id: msg_id,
+            user,
+        };
+        let section_pk = *self.section().chain().last_key();
+        let dst = DstLocation::Node {
+            name: requesting_elder,
+            section_pk,
+        };
+
+        Ok(vec![Command::PrepareNodeMsgToSend {
+            msg,
+            dst,
+            trace: None,
+        }])
+    }
+
+    /// Handle a chunk existence check response
+    /// Answers the users waiting to know, once the chunk was found or none of its holders
+    /// hold it
+    async fn handle_chunk_exists_response_at_elder(
+        &self,
+        address: ChunkAddress,
+        exists: bool,
+        sending_nodes_pk: PublicKey,
+    ) -> Result<Vec<Command>> {
+        let holder = XorName::from(sending_nodes_pk);
+        let operation_id = DataQuery::ChunkExists(address).operation_id()?;
+        if !self
+            .liveness
+            .request_operation_fulfilled(&holder, operation_id.clone())
+            .await
+        {
+            trace!("Ignoring un-expected response");
+            return Ok(vec![]);
+        }
+        self.resends.query_fulfilled(holder, &operation_id).await;
+
+        let mut commands = vec![];
+        if let Some(held_by) = self
+            .existence_checks
+            .record_holding(address, holder, exists)
+            .await
+        {
+            commands.extend(self.holders_checked(address, held_by).await?);
+        }
+        for (correlation_id, user, exists) in
+            self.existence_checks.record(address, holder, exists).await
+        {
+            let msg = ServiceMsg::QueryResponse {
+                response: QueryResponse::ChunkExists((Ok(exists), operation_id.clone())),
+                correlation_id,
+            };
+
+            // FIXME: define which signature/authority this message should really carry,
+            // perhaps it needs to carry Node signature on a NodeMsg::QueryResponse msg type.
+            // Giving a random sig temporarily
+            let (msg_kind, payload) = Self::random_client_signature(&msg)?;
+
+            let dst = DstLocation::EndUser(user);
+            let wire_msg = WireMsg::new_msg(MessageId::new(), payload, msg_kind, dst)?;
+            commands.push(Command::ParseAndSendWireMsg(wire_msg));
+        }
+        Ok(commands)
+    }
+
     /// Handle chunk read
     /// Records response in liveness tracking
     /// Forms a response to send to the requester
@@ -190,13 +783,21 @@ impl Core {
             sending_nodes_pk
         );
 
-        let NodeQueryResponse::GetChunk(response) = response;
+        let response = match response {
+            NodeQueryResponse::GetChunk(response) => response,
+            NodeQueryResponse::ChunkExists { address, exists } => {
+                return self
+                    .handle_chunk_exists_response_at_elder(address, exists, sending_nodes_pk)
+                    .await
+            }
+        };
 
         let query_response = QueryResponse::GetChunk(response);
 
         let pending_removed = match query_response.operation_id() {
             Ok(op_id) => {
                 let node_id = XorName::from(sending_nodes_pk);
+                self.resends.query_fulfilled(node_id, &op_id).await;
                 self.liveness
                     .request_operation_fulfilled(&node_id, op_id)
                     .await
@@ -261,6 +862,44 @@ impl Core {
         user: EndUser,
         auth: AuthorityProof<ServiceAuth>,
     ) -> Result<Vec<Command>> {
+        if !self
+            .load_shedding
+            .admit_client_msg(XorName::from(auth.public_key))
+        {
+            debug!(
+                "Rejecting {:?} from {:?}: throttled under resource pressure",
+                msg_id, auth.public_key
+            );
+            // Queries are answered with an error response, for clients not to wait on them.
+            return match &msg {
+                ServiceMsg::Query(query) => {
+                    self.send_query_error_response(query, ErrorMessage::Throttled, user, msg_id)
+                }
+                _ => {
+                    let error = CmdError::Data(ErrorMessage::Throttled);
+                    self.send_cmd_error_response(error, user, msg_id)
+                }
+            };
+        }
+
+        // Apps acting for users may only do so while their capability holds.
+        if let Some(delegation) = &auth.delegation {
+            let is_cmd = matches!(msg, ServiceMsg::Cmd(_));
+            if delegation.is_expired() || (is_cmd && !delegation.allows_writes) {
+                debug!(
+                    "Rejecting {:?} sent by {:?} on behalf of {:?}: delegation doesn't allow it",
+                    msg_id, auth.public_key, delegation.user
+                );
+                let error = ErrorMessage::AccessDenied(auth.public_key);
+                return match &msg {
+                    ServiceMsg::Query(query) => {
+                        self.send_query_error_response(query, error, user, msg_id)
+                    }
+                    _ => self.send_cmd_error_response(CmdError::Data(error), user, msg_id),
+                };
+            }
+        }
+
         match msg {
             // Register
             // Commands to be handled at elder.
@@ -271,6 +910,45 @@ impl Core {
             ServiceMsg::Query(DataQuery::Register(read)) => {
                 self.handle_register_read(msg_id, read, user, auth)
             }
+            // Payment proofs are kept at elders, next to the data ownership records.
+            ServiceMsg::Cmd(DataCmd::RecordPayment(proof)) => {
+                self.handle_record_payment(msg_id, proof, user, auth)
+            }
+            ServiceMsg::Query(DataQuery::GetPaymentProof(address)) => {
+                self.handle_get_payment_proof(msg_id, address, user, auth)
+            }
+            // As are the catalogs of the data owners stored or paid for, at the section of the owner.
+            ServiceMsg::Cmd(DataCmd::CatalogPayment { owner, proof }) => {
+                self.handle_catalog_payment(msg_id, owner, proof, user, auth)
+                    .await
+            }
+            ServiceMsg::Cmd(DataCmd::CatalogData { owner, address }) => {
+                self.handle_catalog_data(msg_id, owner, address, user, auth)
+                    .await
+            }
+            ServiceMsg::Query(query @ DataQuery::ListOwnedData { .. }) => {
+                self.handle_list_owned_data(msg_id, query, user, auth)
+            }
+            // The replication factor is a parameter of the section, advertised by its elders.
+            ServiceMsg::Query(DataQuery::GetReplicationFactor(name)) => {
+                self.handle_get_replication_factor(msg_id, name, user)
+            }
+            // As are the limits on the size of the data it accepts.
+            ServiceMsg::Query(DataQuery::GetDataLimits(name)) => {
+                self.handle_get_data_limits(msg_id, name, user)
+            }
+            // Data proofs are signed by the section, thus handed out by its elders.
+            ServiceMsg::Query(DataQuery::GetDataProof(address)) => {
+                self.handle_get_data_proof(msg_id, address, user).await
+            }
+            // Network time is vouched for by the section, thus handed out by its elders.
+            ServiceMsg::Query(DataQuery::GetNetworkTime(name)) => {
+                self.handle_get_network_time(msg_id, name, user).await
+            }
+            // Chunk holders are vouched for by the section, thus handed out by its elders.
+            ServiceMsg::Query(DataQuery::GetChunkHolders(address)) => {
+                self.handle_get_chunk_holders(msg_id, address, user).await
+            }
             // These will only be received at elders.
             // These reads/writes are for adult nodes...
             ServiceMsg::Cmd(DataCmd::StoreChunk(chunk)) => {
@@ -279,6 +957,9 @@ impl Core {
             ServiceMsg::Query(DataQuery::GetChunk(address)) => {
                 self.read_chunk_from_adults(address, msg_id, user).await
             }
+            ServiceMsg::Query(DataQuery::ChunkExists(address)) => {
+                self.check_chunk_at_adults(address, msg_id, user).await
+            }
             _ => {
                 warn!("!!!! Unexpected ServiceMsg received in routing. Was not sent to node layer: {:?}", msg);
                 Ok(vec![])
@@ -299,7 +980,7 @@ impl Core {
         let mut candidates = adults
             .sorted_by(|lhs, rhs| target.cmp_distance(lhs, rhs))
             .filter(|name| !full_adults.contains(name))
-            .take(CHUNK_COPY_COUNT)
+            .take(self.get_copy_count())
             .collect::<BTreeSet<_>>();
         trace!(
             "Chunk holders of {:?} are empty adults: {:?} and full adults: {:?}",
@@ -0,0 +1,191 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::waitlists::Marks;
+use crate::messaging::{
+    data::{NetworkTime, NETWORK_TIME_GRANULARITY},
+    system::KeyedSig,
+    EndUser, MessageId,
+};
+use crate::routing::supermajority;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+use tokio::sync::RwLock;
+use xor_name::XorName;
+
+// Number of timestamps we remember proposing, and for how long.
+const PROPOSED_CAPACITY: usize = 100;
+const PROPOSED_DURATION: Duration = Duration::from_secs(10 * 60);
+// How long the clock readings of the Elders are used for, and how often we share ours at most.
+const READING_DURATION: Duration = Duration::from_secs(30);
+
+/// Keeps the latest timestamp our section agreed on, the clients waiting for one, and the
+/// clock readings of the Elders the timestamps we propose are the median of.
+#[derive(Clone, Debug)]
+pub(crate) struct NetworkTimes {
+    latest: Arc<RwLock<Option<(u64, KeyedSig)>>>,
+    waiting: Arc<RwLock<Vec<(MessageId, XorName, EndUser)>>>,
+    proposed: Marks<u64>,
+    readings: Arc<RwLock<BTreeMap<XorName, (u64, Instant)>>>,
+    shared: Arc<RwLock<Option<Instant>>>,
+}
+
+impl NetworkTimes {
+    pub(crate) fn new() -> Self {
+        Self {
+            latest: Arc::new(RwLock::new(None)),
+            waiting: Arc::new(RwLock::new(Vec::new())),
+            proposed: Marks::new(PROPOSED_DURATION, PROPOSED_CAPACITY),
+            readings: Arc::new(RwLock::new(BTreeMap::new())),
+            shared: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Returns the signature over `timestamp`, if it's the latest one agreed on.
+    pub(crate) async fn sig(&self, timestamp: u64) -> Option<KeyedSig> {
+        match &*self.latest.read().await {
+            Some((latest, sig)) if *latest == timestamp => Some(sig.clone()),
+            _ => None,
+        }
+    }
+
+    /// Records that `user` is waiting for a timestamp to be agreed on,
+    /// to answer their query `msg_id` for the time at `name`.
+    pub(crate) async fn wait_for(&self, msg_id: MessageId, name: XorName, user: EndUser) {
+        self.waiting.write().await.push((msg_id, name, user));
+    }
+
+    /// Records that we're proposing `timestamp`, returning false if we already had.
+    pub(crate) async fn to_propose(&self, timestamp: u64) -> bool {
+        self.proposed.mark(timestamp).await
+    }
+
+    /// Returns whether it's time to share our clock reading with the other Elders, i.e. we
+    /// haven't lately, recording that we're about to if so.
+    pub(crate) async fn to_share(&self) -> bool {
+        let mut shared = self.shared.write().await;
+        match *shared {
+            Some(at) if at.elapsed() < READING_DURATION => false,
+            _ => {
+                *shared = Some(Instant::now());
+                true
+            }
+        }
+    }
+
+    /// Records `elder`'s clock reading, in seconds since the Unix epoch.
+    pub(crate) async fn record_reading(&self, elder: XorName, time: u64) {
+        let mut readings = self.readings.write().await;
+        readings.retain(|_, (_, received)| received.elapsed() < READING_DURATION);
+        let _ = readings.insert(elder, (time, Instant::now()));
+    }
+
+    /// The coarse median of the recent clock readings of `elders`, brought forward to now,
+    /// once we have those of a supermajority of them.
+    pub(crate) async fn median(&self, elders: &BTreeSet<XorName>) -> Option<u64> {
+        let readings: Vec<_> = self
+            .readings
+            .read()
+            .await
+            .iter()
+            .filter(|(name, (_, received))| {
+                elders.contains(name) && received.elapsed() < READING_DURATION
+            })
+            .map(|(_, (time, received))| time + received.elapsed().as_secs())
+            .collect();
+        if readings.len() < supermajority(elders.len()) {
+            return None;
+        }
+        let median = median(readings)?;
+        Some(NetworkTime::coarse_timestamp(
+            UNIX_EPOCH + Duration::from_secs(median),
+        ))
+    }
+
+    /// Whether we vouch for `timestamp`, i.e. it's at most one step of granularity
+    /// away from `own_timestamp`, as per our clock.
+    pub(crate) fn vouches_for(timestamp: u64, own_timestamp: u64) -> bool {
+        let diff = if timestamp > own_timestamp {
+            timestamp - own_timestamp
+        } else {
+            own_timestamp - timestamp
+        };
+        diff <= NETWORK_TIME_GRANULARITY.as_secs()
+    }
+
+    /// Records the signature agreed on over `timestamp`, returning the queries of
+    /// the clients who were waiting for a timestamp.
+    pub(crate) async fn agreed(
+        &self,
+        timestamp: u64,
+        sig: KeyedSig,
+    ) -> Vec<(MessageId, XorName, EndUser)> {
+        {
+            let mut latest = self.latest.write().await;
+            let newer = latest
+                .as_ref()
+                .map(|(latest, _)| timestamp > *latest)
+                .unwrap_or(true);
+            if newer {
+                *latest = Some((timestamp, sig));
+            }
+        }
+        self.waiting.write().await.drain(..).collect()
+    }
+}
+
+// The middle value of `values`, or the mean of the two middle ones if there's an even number
+// of them.
+fn median(mut values: Vec<u64>) -> Option<u64> {
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    match values.len() {
+        0 => None,
+        len if len % 2 == 1 => Some(values[mid]),
+        _ => Some(values[mid - 1] + (values[mid] - values[mid - 1]) / 2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{median, NetworkTimes};
+    use crate::messaging::data::NETWORK_TIME_GRANULARITY;
+    use std::collections::BTreeSet;
+    use xor_name::XorName;
+
+    #[test]
+    fn median_of_readings() {
+        assert_eq!(median(vec![]), None);
+        assert_eq!(median(vec![7]), Some(7));
+        assert_eq!(median(vec![900, 100, 130]), Some(130));
+        assert_eq!(median(vec![100, 1_000, 120, 130]), Some(125));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn median_needs_a_supermajority_of_elders() {
+        let times = NetworkTimes::new();
+        let elders: Vec<_> = (0..7).map(|_| XorName::random()).collect();
+        let elder_names: BTreeSet<_> = elders.iter().copied().collect();
+        let granularity = NETWORK_TIME_GRANULARITY.as_secs();
+        let base = 1_000_000 * granularity;
+
+        // A far off clock doesn't move the median, and only the readings of Elders count.
+        let readings = [base + 10, base + 20, base + 100 * granularity, base + 30];
+        for (elder, time) in elders.iter().zip(readings.iter()) {
+            times.record_reading(*elder, *time).await;
+        }
+        times.record_reading(XorName::random(), 0).await;
+        assert_eq!(times.median(&elder_names).await, None);
+
+        times.record_reading(elders[4], base + 40).await;
+        assert_eq!(times.median(&elder_names).await, Some(base));
+    }
+}
@@ -0,0 +1,475 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::dbs::{deserialise, serialise, Error, Result, UsedSpace};
+use crate::messaging::data::{
+    CatalogDataExchange, CatalogPage, Error as ErrorMessage, OperationId, QueryResponse,
+    MAX_CATALOG_PAGE_SIZE,
+};
+use crate::routing::Prefix;
+use crate::types::{DataAddress, PaymentProof, PublicKey};
+use sled::{Db, Tree};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound,
+    path::Path,
+};
+use xor_name::XorName;
+
+const DATABASE_NAME: &str = "payments";
+const CATALOG_TREE_NAME: &str = "catalog";
+
+/// Persists the proofs of payment for stored data, keyed by the data address, along with the
+/// owner each was sent for, and the catalogs of the data owners stored or paid for, keyed by
+/// owner then data address.
+///
+/// The owner is the payer, unless the payer is an app acting for a user, whose data it is.
+#[derive(Clone, Debug)]
+pub(crate) struct PaymentStore {
+    db: Db,
+    catalog: Tree,
+    used_space: UsedSpace,
+}
+
+impl PaymentStore {
+    pub(crate) fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
+        used_space.add_dir(path);
+        let db_dir = path.join("db").join(DATABASE_NAME.to_string());
+
+        let db = sled::open(db_dir).map_err(|error| {
+            trace!("Sled Error: {:?}", error);
+            Error::Sled(error)
+        })?;
+
+        let catalog = db.open_tree(CATALOG_TREE_NAME)?;
+
+        Ok(Self {
+            db,
+            catalog,
+            used_space,
+        })
+    }
+
+    /// Stores a proof of payment signed by `signer`, who must be the payer, for `owner`.
+    pub(crate) fn write(
+        &self,
+        proof: PaymentProof,
+        signer: PublicKey,
+        owner: PublicKey,
+    ) -> Result<()> {
+        if proof.payer != signer {
+            return Err(Error::InvalidOwner(signer));
+        }
+        proof
+            .verify()
+            .map_err(|_| Error::InvalidSignature(proof.payer))?;
+
+        let key = serialise(&proof.address)?;
+        let mut proofs = self.proofs(&key)?;
+        let record = (owner, proof);
+        if proofs.contains(&record) {
+            return Ok(());
+        }
+        proofs.push(record);
+
+        let _ = self.db.insert(key, serialise(&proofs)?)?;
+        let _ = self.db.flush()?;
+
+        Ok(())
+    }
+
+    /// Returns the proofs of payment for the data at `address` which were made for `requester`.
+    pub(crate) fn read(
+        &self,
+        address: &DataAddress,
+        requester: PublicKey,
+        operation_id: OperationId,
+    ) -> Result<QueryResponse> {
+        let proofs = self.proofs(&serialise(address)?)?;
+        if proofs.is_empty() {
+            return Err(Error::NoSuchData(address.clone()));
+        }
+
+        let own_proofs: Vec<_> = proofs
+            .into_iter()
+            .filter(|(owner, _)| *owner == requester)
+            .map(|(_, proof)| proof)
+            .collect();
+        let result = if own_proofs.is_empty() {
+            Err(ErrorMessage::AccessDenied(requester))
+        } else {
+            Ok(own_proofs)
+        };
+
+        Ok(QueryResponse::GetPaymentProof((result, operation_id)))
+    }
+
+    /// Adds the data a proof of payment signed by `signer`, who must be the payer, is for to
+    /// the catalog of `owner`, who must be the requester the signer acts for.
+    pub(crate) async fn catalog(
+        &self,
+        owner: PublicKey,
+        proof: PaymentProof,
+        signer: PublicKey,
+        requester: PublicKey,
+    ) -> Result<()> {
+        if owner != requester {
+            return Err(Error::InvalidOwner(requester));
+        }
+        if proof.payer != signer {
+            return Err(Error::InvalidOwner(signer));
+        }
+        proof
+            .verify()
+            .map_err(|_| Error::InvalidSignature(proof.payer))?;
+
+        self.add_to_catalog(&owner, &proof.address).await
+    }
+
+    /// Adds the data at `address`, stored by `requester`, to the catalog of `owner`, who must be
+    /// the requester.
+    pub(crate) async fn catalog_data(
+        &self,
+        owner: PublicKey,
+        address: DataAddress,
+        requester: PublicKey,
+    ) -> Result<()> {
+        if owner != requester {
+            return Err(Error::InvalidOwner(requester));
+        }
+        self.add_to_catalog(&owner, &address).await
+    }
+
+    // Adds `address` to the catalog of `owner`, provided there's space for it.
+    async fn add_to_catalog(&self, owner: &PublicKey, address: &DataAddress) -> Result<()> {
+        let key = catalog_key(owner, address)?;
+        if self.catalog.contains_key(&key)? {
+            return Ok(());
+        }
+        let value = serialise(address)?;
+        if !self
+            .used_space
+            .can_consume((key.len() + value.len()) as u64)
+            .await
+        {
+            return Err(Error::NotEnoughSpace);
+        }
+
+        let _ = self.catalog.insert(key, value)?;
+        let _ = self.catalog.flush()?;
+
+        Ok(())
+    }
+
+    /// Returns the catalogs of the owners whose key falls within `prefix`, whose section keeps
+    /// them, to hand them over on churn and splits.
+    pub(crate) fn get_catalog_of(&self, prefix: &Prefix) -> Result<CatalogDataExchange> {
+        let mut catalogs = BTreeMap::new();
+        for entry in self.catalog.iter() {
+            let (key, value) = entry?;
+            // The owner's key prefixes the address it's followed by, which is the value.
+            let owner: PublicKey = deserialise(&key[..key.len() - value.len()])?;
+            if prefix.matches(&XorName::from(owner)) {
+                let _ = catalogs
+                    .entry(owner)
+                    .or_insert_with(BTreeSet::new)
+                    .insert(deserialise(&value)?);
+            }
+        }
+        Ok(CatalogDataExchange(catalogs))
+    }
+
+    /// Adds the catalogs handed over by other Elders on churn and splits to ours.
+    pub(crate) async fn update_catalog(&self, catalog_data: CatalogDataExchange) -> Result<()> {
+        debug!("Updating the catalogs of owned data");
+        let CatalogDataExchange(catalogs) = catalog_data;
+        for (owner, addresses) in catalogs {
+            for address in addresses {
+                self.add_to_catalog(&owner, &address).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns up to `limit` addresses of the catalog of `owner`, following `start_after`,
+    /// provided `requester` is the owner.
+    pub(crate) fn list_owned(
+        &self,
+        owner: PublicKey,
+        requester: PublicKey,
+        start_after: Option<DataAddress>,
+        limit: u32,
+        operation_id: OperationId,
+    ) -> Result<QueryResponse> {
+        if owner != requester {
+            return Ok(QueryResponse::ListOwnedData((
+                Err(ErrorMessage::AccessDenied(requester)),
+                operation_id,
+            )));
+        }
+
+        let prefix = serialise(&owner)?;
+        let start = match start_after {
+            Some(address) => Bound::Excluded(catalog_key(&owner, &address)?),
+            None => Bound::Included(prefix.clone()),
+        };
+        let limit = limit.clamp(1, MAX_CATALOG_PAGE_SIZE) as usize;
+
+        let mut addresses = Vec::new();
+        let mut more = false;
+        for entry in self.catalog.range((start, Bound::Unbounded)) {
+            let (key, value) = entry?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            if addresses.len() == limit {
+                more = true;
+                break;
+            }
+            addresses.push(deserialise(&value)?);
+        }
+        let next = if more {
+            addresses.last().cloned()
+        } else {
+            None
+        };
+
+        Ok(QueryResponse::ListOwnedData((
+            Ok(CatalogPage { addresses, next }),
+            operation_id,
+        )))
+    }
+
+    fn proofs(&self, key: &[u8]) -> Result<Vec<(PublicKey, PaymentProof)>> {
+        match self.db.get(key)? {
+            Some(bytes) => deserialise(&bytes),
+            None => Ok(vec![]),
+        }
+    }
+}
+
+// Catalog entries of an owner share the owner's key as prefix, so they're listed together, in
+// the order of their addresses.
+fn catalog_key(owner: &PublicKey, address: &DataAddress) -> Result<Vec<u8>> {
+    let mut key = serialise(owner)?;
+    key.extend(serialise(address)?);
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PaymentStore;
+    use crate::dbs::{serialise, UsedSpace};
+    use crate::messaging::data::{CatalogPage, Error as ErrorMessage, QueryResponse};
+    use crate::routing::Prefix;
+    use crate::types::{ChunkAddress, DataAddress, Keypair, PaymentProof, PublicKey, Token};
+    use eyre::Result;
+    use std::collections::BTreeSet;
+    use tempfile::tempdir;
+    use xor_name::XorName;
+
+    #[test]
+    fn only_payer_can_retrieve_proof() -> Result<()> {
+        let root = tempdir()?;
+        let store = PaymentStore::new(root.path(), UsedSpace::new(u64::MAX))?;
+
+        let payer = Keypair::new_ed25519(&mut rand::thread_rng());
+        let other: PublicKey = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+        let address = DataAddress::Chunk(ChunkAddress(XorName::random()));
+        let proof = PaymentProof::new(address.clone(), Token::from_nano(10), &payer)?;
+
+        assert!(store.write(proof.clone(), other, other).is_err());
+        store.write(proof.clone(), payer.public_key(), payer.public_key())?;
+
+        let response = store.read(&address, payer.public_key(), "op".to_string())?;
+        assert_eq!(
+            response,
+            QueryResponse::GetPaymentProof((Ok(vec![proof]), "op".to_string()))
+        );
+
+        let response = store.read(&address, other, "op".to_string())?;
+        assert_eq!(
+            response,
+            QueryResponse::GetPaymentProof((
+                Err(ErrorMessage::AccessDenied(other)),
+                "op".to_string()
+            ))
+        );
+
+        // A payment made by an app for a user is the user's.
+        let app = Keypair::new_ed25519(&mut rand::thread_rng());
+        let address = DataAddress::Chunk(ChunkAddress(XorName::random()));
+        let proof = PaymentProof::new(address.clone(), Token::from_nano(10), &app)?;
+        store.write(proof.clone(), app.public_key(), other)?;
+        let response = store.read(&address, other, "op".to_string())?;
+        assert_eq!(
+            response,
+            QueryResponse::GetPaymentProof((Ok(vec![proof]), "op".to_string()))
+        );
+        let response = store.read(&address, app.public_key(), "op".to_string())?;
+        assert_eq!(
+            response,
+            QueryResponse::GetPaymentProof((
+                Err(ErrorMessage::AccessDenied(app.public_key())),
+                "op".to_string()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn catalog_is_listed_in_pages_by_owner_only() -> Result<()> {
+        let root = tempdir()?;
+        let store = PaymentStore::new(root.path(), UsedSpace::new(u64::MAX))?;
+
+        let owner = Keypair::new_ed25519(&mut rand::thread_rng());
+        let other = Keypair::new_ed25519(&mut rand::thread_rng());
+        let mut addresses = Vec::new();
+        for _ in 0..5 {
+            let address = DataAddress::Chunk(ChunkAddress(XorName::random()));
+            let proof = PaymentProof::new(address.clone(), Token::from_nano(10), &owner)?;
+            assert!(store
+                .catalog(
+                    owner.public_key(),
+                    proof.clone(),
+                    other.public_key(),
+                    owner.public_key()
+                )
+                .await
+                .is_err());
+            assert!(store
+                .catalog(
+                    owner.public_key(),
+                    proof.clone(),
+                    owner.public_key(),
+                    other.public_key()
+                )
+                .await
+                .is_err());
+            store
+                .catalog(
+                    owner.public_key(),
+                    proof.clone(),
+                    owner.public_key(),
+                    owner.public_key(),
+                )
+                .await?;
+            // cataloguing is idempotent
+            store
+                .catalog(
+                    owner.public_key(),
+                    proof,
+                    owner.public_key(),
+                    owner.public_key(),
+                )
+                .await?;
+            addresses.push((serialise(&address)?, address));
+        }
+        let address = DataAddress::Chunk(ChunkAddress(XorName::random()));
+        let proof = PaymentProof::new(address, Token::from_nano(10), &other)?;
+        store
+            .catalog(
+                other.public_key(),
+                proof,
+                other.public_key(),
+                other.public_key(),
+            )
+            .await?;
+
+        let list = |start_after, limit| -> Result<CatalogPage> {
+            match store.list_owned(
+                owner.public_key(),
+                owner.public_key(),
+                start_after,
+                limit,
+                "op".to_string(),
+            )? {
+                QueryResponse::ListOwnedData((Ok(page), _)) => Ok(page),
+                response => Err(eyre::eyre!("Unexpected response: {:?}", response)),
+            }
+        };
+
+        let mut listed = Vec::new();
+        let mut start_after = None;
+        loop {
+            let page = list(start_after, 2)?;
+            assert!(page.addresses.len() <= 2);
+            listed.extend(page.addresses);
+            match page.next {
+                Some(next) => start_after = Some(next),
+                None => break,
+            }
+        }
+        // listed in the order of the serialised addresses
+        addresses.sort();
+        let expected: Vec<_> = addresses.into_iter().map(|(_, address)| address).collect();
+        assert_eq!(listed, expected);
+        assert_eq!(list(None, 10)?.next, None);
+
+        let response = store.list_owned(
+            owner.public_key(),
+            other.public_key(),
+            None,
+            10,
+            "op".to_string(),
+        )?;
+        assert_eq!(
+            response,
+            QueryResponse::ListOwnedData((
+                Err(ErrorMessage::AccessDenied(other.public_key())),
+                "op".to_string()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn catalogs_are_handed_over_by_prefix() -> Result<()> {
+        let root = tempdir()?;
+        let store = PaymentStore::new(root.path(), UsedSpace::new(u64::MAX))?;
+
+        let owner = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+        let other = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+        let address = DataAddress::Chunk(ChunkAddress(XorName::random()));
+        assert!(store
+            .catalog_data(owner, address.clone(), other)
+            .await
+            .is_err());
+        store.catalog_data(owner, address.clone(), owner).await?;
+        store
+            .catalog_data(
+                other,
+                DataAddress::Chunk(ChunkAddress(XorName::random())),
+                other,
+            )
+            .await?;
+
+        let prefix = Prefix::default().pushed(XorName::from(owner).bit(0));
+        let handed_over = store.get_catalog_of(&prefix)?;
+        assert!(handed_over
+            .0
+            .keys()
+            .all(|key| prefix.matches(&XorName::from(*key))));
+        let addresses: BTreeSet<_> = vec![address.clone()].into_iter().collect();
+        assert_eq!(handed_over.0.get(&owner), Some(&addresses));
+
+        let root = tempdir()?;
+        let receiver = PaymentStore::new(root.path(), UsedSpace::new(u64::MAX))?;
+        receiver.update_catalog(handed_over.clone()).await?;
+        assert_eq!(receiver.get_catalog_of(&Prefix::default())?, handed_over);
+
+        // Nothing is catalogued once out of space.
+        let root = tempdir()?;
+        let full = PaymentStore::new(root.path(), UsedSpace::new(0))?;
+        assert!(full.catalog_data(owner, address, owner).await.is_err());
+
+        Ok(())
+    }
+}
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::dbs::{convert_to_error_message, Error, EventStore, Result, UsedSpace};
+use crate::dbs::{convert_to_error_message, Error, EventStore, Result, ToDbKey, UsedSpace};
 use crate::types::{
     register::{Action, Address, Register, User},
     PublicKey,
@@ -14,8 +14,8 @@ use crate::types::{
 use crate::{
     messaging::{
         data::{
-            DataCmd, OperationId, QueryResponse, RegisterCmd, RegisterDataExchange, RegisterRead,
-            RegisterWrite, ServiceMsg,
+            DataCmd, DataLimits, OperationId, QueryResponse, RegisterCmd, RegisterDataExchange,
+            RegisterRead, RegisterWrite, ServiceMsg,
         },
         AuthorityProof, ServiceAuth, WireMsg,
     },
@@ -115,17 +115,89 @@ impl RegisterStorage {
         Ok(())
     }
 
+    /// On receiving registers handed off by a demoted Elder: those we don't hold are
+    /// replayed in full, and the operations we miss applied to those we do.
+    pub(crate) fn merge(&self, reg_data: RegisterDataExchange) -> Result<()> {
+        let RegisterDataExchange(data) = reg_data;
+
+        for (key, history) in data {
+            let known = if self.holds(&key) {
+                self.load_store(key)?.get_all()?
+            } else {
+                vec![]
+            };
+            for op in history {
+                if known.contains(&op) {
+                    continue;
+                }
+                let auth = WireMsg::verify_sig(
+                    op.auth.clone(),
+                    ServiceMsg::Cmd(DataCmd::Register(op.write.clone())),
+                )
+                .map_err(|_| Error::InvalidSignature(op.auth.public_key))?;
+                let _ = self.apply(op, auth)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether we hold the register of the given key, be it cached or on disk only.
+    pub(crate) fn holds(&self, key: &XorName) -> bool {
+        if self.registers.contains_key(key) {
+            return true;
+        }
+        match key.to_db_key() {
+            Ok(tree_name) => self
+                .db
+                .tree_names()
+                .iter()
+                .any(|name| name.as_ref() == tree_name.as_bytes()),
+            Err(_) => false,
+        }
+    }
+
+    /// Drops our copy of the register of the given key, e.g. once it's been handed off.
+    pub(crate) fn remove(&self, key: &XorName) -> Result<()> {
+        let _ = self.registers.remove(key);
+        let _ = self.db.drop_tree(key.to_db_key()?)?;
+        Ok(())
+    }
+
     /// --- Writing ---
 
+    /// Applies `write`, refusing entries larger than the limits allow once serialised, and
+    /// batches of more operations than they allow.
     pub(crate) async fn write(
         &self,
         write: RegisterWrite,
         auth: AuthorityProof<ServiceAuth>,
+        limits: &DataLimits,
     ) -> Result<()> {
         let required_space = std::mem::size_of::<RegisterCmd>() as u64;
         if !self.used_space.can_consume(required_space).await {
             return Err(Error::NotEnoughSpace);
         }
+        let ops = match &write {
+            RegisterWrite::Edit(op) => std::slice::from_ref(op),
+            RegisterWrite::EditBatch { ops, .. } => ops.as_slice(),
+            _ => &[],
+        };
+        if ops.len() as u64 > limits.max_register_batch_ops {
+            return Err(Error::TooManyOps {
+                ops: ops.len() as u64,
+                max: limits.max_register_batch_ops,
+            });
+        }
+        for op in ops {
+            let size = bincode::serialized_size(&op.crdt_op.value)?;
+            if size > limits.max_register_entry_size {
+                return Err(Error::EntryTooLarge {
+                    size,
+                    max: limits.max_register_entry_size,
+                });
+            }
+        }
         let op = RegisterCmd {
             write,
             auth: auth.clone().into_inner(),
@@ -171,8 +243,8 @@ impl RegisterStorage {
                             }
                             // TODO - Register::check_permission() doesn't support Delete yet in safe-nd
                             // register.check_permission(action, Some(auth.public_key))?;
-                            if auth.public_key != entry.state.owner() {
-                                Err(Error::InvalidOwner(auth.public_key))
+                            if auth.requester() != entry.state.owner() {
+                                Err(Error::InvalidOwner(auth.requester()))
                             } else {
                                 info!("Deleting Register");
                                 let _ = self.db.drop_tree(key)?;
@@ -194,27 +266,11 @@ impl RegisterStorage {
 
                 result
             }
-            Edit(reg_op) => {
-                let mut cache = self
-                    .registers
-                    .get_mut(&key)
-                    .ok_or(Error::NoSuchData(DataAddress::Register(address)))?;
-                let entry = if let Some(cached_entry) = cache.as_mut() {
-                    cached_entry
-                } else {
-                    let fresh_entry = self.load_state(key)?;
-                    let _ = cache.replace(fresh_entry);
-                    if let Some(entry) = cache.as_mut() {
-                        entry
-                    } else {
-                        return Err(Error::NoSuchData(DataAddress::Register(address)));
-                    }
-                };
-
+            Edit(reg_op) => self.update_state(key, address, |entry| {
                 info!("Editing Register");
                 entry
                     .state
-                    .check_permissions(Action::Write, Some(auth.public_key))?;
+                    .check_permissions(Action::Write, Some(auth.requester()))?;
                 let result = entry.state.apply_op(reg_op).map_err(Error::NetworkData);
 
                 if result.is_ok() {
@@ -225,10 +281,64 @@ impl RegisterStorage {
                 }
 
                 result
-            }
+            }),
+            EditBatch { ops, .. } => self.update_state(key, address, |entry| {
+                info!("Editing Register with {} operations", ops.len());
+                entry
+                    .state
+                    .check_permissions(Action::Write, Some(auth.requester()))?;
+                // Applied to a copy first, so a failing operation leaves the state untouched.
+                let mut state = entry.state.clone();
+                for reg_op in ops {
+                    state.apply_op(reg_op).map_err(Error::NetworkData)?;
+                }
+                entry.store.append(op)?;
+                entry.state = state;
+                trace!("Editing Register with a batch success!");
+
+                Ok(())
+            }),
+            TransferOwnership(transfer) => self.update_state(key, address, |entry| {
+                info!("Transferring Register ownership");
+                if auth.requester() != entry.state.owner() {
+                    return Err(Error::InvalidOwner(auth.requester()));
+                }
+                entry
+                    .state
+                    .transfer_ownership(&transfer)
+                    .map_err(Error::NetworkData)?;
+                entry.store.append(op)?;
+                trace!("Transferring Register ownership success!");
+
+                Ok(())
+            }),
         }
     }
 
+    // Applies `update` to the state of the register, loading it from disk if not cached.
+    fn update_state<F>(&self, key: XorName, address: Address, update: F) -> Result<()>
+    where
+        F: FnOnce(&mut StateEntry) -> Result<()>,
+    {
+        let mut cache = self
+            .registers
+            .get_mut(&key)
+            .ok_or(Error::NoSuchData(DataAddress::Register(address)))?;
+        let entry = if let Some(cached_entry) = cache.as_mut() {
+            cached_entry
+        } else {
+            let fresh_entry = self.load_state(key)?;
+            let _ = cache.replace(fresh_entry);
+            if let Some(entry) = cache.as_mut() {
+                entry
+            } else {
+                return Err(Error::NoSuchData(DataAddress::Register(address)));
+            }
+        };
+
+        update(entry)
+    }
+
     /// --- Reading ---
 
     pub(crate) fn read(
@@ -385,8 +495,17 @@ impl RegisterStorage {
             if let New(register) = op.write {
                 reg = Some(register);
             } else if let Some(register) = &mut reg {
-                if let Edit(reg_op) = op.write {
-                    register.apply_op(reg_op).map_err(Error::NetworkData)?;
+                match op.write {
+                    Edit(reg_op) => register.apply_op(reg_op).map_err(Error::NetworkData)?,
+                    EditBatch { ops, .. } => {
+                        for reg_op in ops {
+                            register.apply_op(reg_op).map_err(Error::NetworkData)?;
+                        }
+                    }
+                    TransferOwnership(transfer) => register
+                        .transfer_ownership(&transfer)
+                        .map_err(Error::NetworkData)?,
+                    New(_) | Delete(_) => {}
                 }
             }
         }
@@ -465,6 +584,7 @@ mod test {
         let auth = ServiceAuth {
             public_key: pk,
             signature: authority_keypair1.sign(b""),
+            delegation: None,
         };
 
         let cmd = RegisterCmd { write, auth };
@@ -0,0 +1,397 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{chunk_inventory::holders_of, Command, Core, Result};
+use crate::messaging::{
+    system::{NodeCmd, ReplicationTarget, SystemMsg},
+    DstLocation,
+};
+use crate::routing::{peer::PeerUtils, routing_api::command::next_timer_token, Error};
+use crate::types::ChunkAddress;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+use xor_name::XorName;
+
+/// How long Adults are given to report which of the chunks checked they hold.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Replication status of a chunk, as found by a replication check.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReplicationStatus {
+    /// Held by all its holders.
+    Replicated,
+    /// Missing at some of its holders, to which it's being replicated.
+    Repairing {
+        /// The holders missing it.
+        missing: BTreeSet<XorName>,
+    },
+    /// Held by none of the Adults which reported, so it can't be repaired.
+    Lost,
+}
+
+/// Outcome of a replication check, started by an operator on an Elder to verify chunks are
+/// held by all their holders, e.g. after an Adult's disk was found faulty.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplicationReport {
+    /// The chunks checked.
+    pub target: ReplicationTarget,
+    /// Status of each chunk checked, by name, once the check is complete. The chunks of a
+    /// range are those held by the Adults which reported.
+    pub chunks: BTreeMap<XorName, ReplicationStatus>,
+    /// Adults which haven't reported yet, or hadn't by the time the check timed out. The chunks
+    /// they should hold are taken as missing there.
+    pub pending: BTreeSet<XorName>,
+    /// Whether all the Adults reported, or the check timed out.
+    pub complete: bool,
+}
+
+impl ReplicationReport {
+    /// The chunks which weren't held by all their holders, by name.
+    pub fn failures(&self) -> impl Iterator<Item = (&XorName, &ReplicationStatus)> {
+        self.chunks
+            .iter()
+            .filter(|(_, status)| **status != ReplicationStatus::Replicated)
+    }
+}
+
+/// The replication check last started at this Elder, if any. Only the last one is tracked:
+/// starting a check while one is under way drops it.
+#[derive(Clone, Debug)]
+pub(crate) struct ReplicationCheck {
+    timer_token: u64,
+    check: Arc<Mutex<Option<Check>>>,
+}
+
+#[derive(Debug)]
+struct Check {
+    report: ReplicationReport,
+    // The chunks checked each Adult which reported holds.
+    held: BTreeMap<XorName, BTreeSet<XorName>>,
+    started: Instant,
+}
+
+impl ReplicationCheck {
+    pub(crate) fn new() -> Self {
+        Self {
+            timer_token: next_timer_token(),
+            check: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Token of the timeout checks are completed on.
+    pub(crate) fn timer_token(&self) -> u64 {
+        self.timer_token
+    }
+
+    /// Outcome of the replication check last started, if any.
+    pub(crate) fn report(&self) -> Option<ReplicationReport> {
+        self.check
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map(|check| check.report.clone())
+    }
+}
+
+impl Check {
+    fn new(target: ReplicationTarget, adults: BTreeSet<XorName>) -> Self {
+        Self {
+            report: ReplicationReport {
+                target,
+                chunks: BTreeMap::new(),
+                pending: adults,
+                complete: false,
+            },
+            held: BTreeMap::new(),
+            started: Instant::now(),
+        }
+    }
+
+    // Records which of the chunks checked `adult` holds, returning whether all have reported.
+    fn record_held(&mut self, adult: XorName, held: BTreeSet<XorName>) -> bool {
+        if !self.report.pending.remove(&adult) {
+            return false;
+        }
+        let held = held
+            .into_iter()
+            .filter(|name| self.report.target.contains(name))
+            .collect();
+        let _ = self.held.insert(adult, held);
+        self.report.pending.is_empty()
+    }
+
+    // Works out the status of each chunk checked, holders being picked among `adults`, the
+    // Adults which aren't full. Returns the chunks to replicate, by the Adult holding them and
+    // the Adult to replicate them to.
+    fn complete(
+        &mut self,
+        adults: &BTreeSet<XorName>,
+        copy_count: usize,
+    ) -> BTreeMap<(XorName, XorName), BTreeSet<XorName>> {
+        let names: BTreeSet<XorName> = match &self.report.target {
+            ReplicationTarget::Chunks(names) => names.clone(),
+            ReplicationTarget::Range(_) => self.held.values().flatten().copied().collect(),
+        };
+
+        let mut repairs: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for name in names {
+            let held_by: BTreeSet<_> = self
+                .held
+                .iter()
+                .filter(|(_, held)| held.contains(&name))
+                .map(|(adult, _)| *adult)
+                .collect();
+            let missing: BTreeSet<_> = holders_of(&name, adults, copy_count)
+                .difference(&held_by)
+                .copied()
+                .collect();
+            // The closest Adult holding the chunk replicates it.
+            let source = held_by
+                .iter()
+                .min_by(|lhs, rhs| name.cmp_distance(lhs, rhs))
+                .copied();
+
+            let status = match source {
+                None => ReplicationStatus::Lost,
+                Some(_) if missing.is_empty() => ReplicationStatus::Replicated,
+                Some(source) => {
+                    for to in &missing {
+                        let _ = repairs.entry((source, *to)).or_default().insert(name);
+                    }
+                    ReplicationStatus::Repairing { missing }
+                }
+            };
+            let _ = self.report.chunks.insert(name, status);
+        }
+
+        self.report.complete = true;
+        repairs
+    }
+}
+
+impl Core {
+    /// Starts verifying the given chunks are held by all their holders, at an Elder, asking all
+    /// the Adults of our section which of them they hold. Chunks found missing at any of their
+    /// holders are replicated to them once all the Adults reported, or the check timed out.
+    pub(crate) fn start_replication_check(
+        &self,
+        target: ReplicationTarget,
+    ) -> Result<Vec<Command>> {
+        if !self.is_elder() {
+            return Err(Error::InvalidState);
+        }
+        let adults: BTreeSet<_> = self.section().adults().map(|peer| *peer.name()).collect();
+        if adults.is_empty() {
+            return Err(Error::NoAdults(*self.section().prefix()));
+        }
+
+        info!(
+            "Checking the replication of {:?} at {} Adults",
+            target,
+            adults.len()
+        );
+        *self
+            .replication_check
+            .check
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) =
+            Some(Check::new(target.clone(), adults.clone()));
+
+        let msg = SystemMsg::NodeCmd(NodeCmd::CheckReplication(target));
+        let mut commands = self.send_node_msg_to_targets(msg, adults, false)?;
+        commands.push(Command::ScheduleTimeout {
+            duration: CHECK_TIMEOUT,
+            token: self.replication_check.timer_token(),
+        });
+        Ok(commands)
+    }
+
+    /// Reports back which of the chunks checked we hold, at an Adult.
+    pub(crate) fn handle_check_replication(
+        &self,
+        requesting_elder: XorName,
+        target: ReplicationTarget,
+    ) -> Result<Vec<Command>> {
+        let held = match target {
+            ReplicationTarget::Range(prefix) => self
+                .chunk_storage
+                .keys()?
+                .into_iter()
+                .map(|address| *address.name())
+                .filter(|name| prefix.matches(name))
+                .collect(),
+            ReplicationTarget::Chunks(names) => names
+                .into_iter()
+                .filter(|name| {
+                    self.chunk_storage
+                        .has(&ChunkAddress(*name))
+                        .unwrap_or(false)
+                })
+                .collect(),
+        };
+
+        let msg = SystemMsg::NodeCmd(NodeCmd::ReplicationChecked { held });
+        let dst = DstLocation::Node {
+            name: requesting_elder,
+            section_pk: *self.section().chain().last_key(),
+        };
+
+        Ok(vec![Command::PrepareNodeMsgToSend {
+            msg,
+            dst,
+            trace: None,
+        }])
+    }
+
+    /// Records which of the chunks checked an Adult holds, completing the check once all the
+    /// Adults reported.
+    pub(crate) async fn handle_replication_checked(
+        &self,
+        adult: XorName,
+        held: BTreeSet<XorName>,
+    ) -> Result<Vec<Command>> {
+        let adults = self.replication_holders().await;
+        let repairs = {
+            let mut check = self
+                .replication_check
+                .check
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            match check.as_mut() {
+                Some(check) if !check.report.complete && check.record_held(adult, held) => {
+                    check.complete(&adults, self.get_copy_count())
+                }
+                _ => return Ok(vec![]),
+            }
+        };
+        self.repair_replication(repairs)
+    }
+
+    /// Completes the check under way, if it timed out, with the Adults which have reported.
+    pub(crate) async fn handle_replication_check_timeout(&self) -> Result<Vec<Command>> {
+        let adults = self.replication_holders().await;
+        let repairs = {
+            let mut check = self
+                .replication_check
+                .check
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            match check.as_mut() {
+                // A check started since the timeout was scheduled has its own.
+                Some(check)
+                    if !check.report.complete && check.started.elapsed() >= CHECK_TIMEOUT =>
+                {
+                    warn!(
+                        "Completing replication check without the reports of {} Adults",
+                        check.report.pending.len()
+                    );
+                    check.complete(&adults, self.get_copy_count())
+                }
+                _ => return Ok(vec![]),
+            }
+        };
+        self.repair_replication(repairs)
+    }
+
+    // The Adults holders are picked among, which are those new chunks go to.
+    async fn replication_holders(&self) -> BTreeSet<XorName> {
+        let full_adults = self.full_adults().await;
+        self.section()
+            .adults()
+            .map(|peer| *peer.name())
+            .filter(|name| !full_adults.contains(name))
+            .collect()
+    }
+
+    fn repair_replication(
+        &self,
+        repairs: BTreeMap<(XorName, XorName), BTreeSet<XorName>>,
+    ) -> Result<Vec<Command>> {
+        let mut commands = vec![];
+        for ((holder, to), names) in repairs {
+            info!(
+                "Adult {} misses {} of the chunks checked held by {}, replicating them",
+                to,
+                names.len(),
+                holder
+            );
+            let msg = SystemMsg::NodeCmd(NodeCmd::ReplicateChunks { names, to });
+            commands.extend(self.send_node_msg_to_targets(
+                msg,
+                iter::once(holder).collect(),
+                false,
+            )?);
+        }
+        Ok(commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, ReplicationStatus};
+    use crate::messaging::system::ReplicationTarget;
+    use std::{collections::BTreeSet, iter};
+    use xor_name::{Prefix, XorName};
+
+    #[test]
+    fn chunks_missing_at_their_holders_are_repaired() {
+        let adults: BTreeSet<_> = (0..3).map(|_| XorName::random()).collect();
+        let (chunk, lost) = (XorName::random(), XorName::random());
+        let mut check = Check::new(
+            ReplicationTarget::Chunks(vec![chunk, lost].into_iter().collect()),
+            adults.clone(),
+        );
+
+        let reporting: Vec<_> = adults.iter().copied().collect();
+        let (holder, other) = (reporting[0], reporting[1]);
+        assert!(!check.record_held(holder, iter::once(chunk).collect()));
+        assert!(!check.record_held(other, BTreeSet::new()));
+        // Reports from Adults which weren't asked are ignored.
+        assert!(!check.record_held(XorName::random(), iter::once(lost).collect()));
+
+        let repairs = check.complete(&adults, 3);
+        let report = &check.report;
+        assert!(report.complete);
+        assert_eq!(report.pending.len(), 1);
+        assert_eq!(report.chunks[&lost], ReplicationStatus::Lost);
+        let missing: BTreeSet<_> = adults.iter().copied().filter(|a| *a != holder).collect();
+        assert_eq!(
+            report.chunks[&chunk],
+            ReplicationStatus::Repairing {
+                missing: missing.clone()
+            }
+        );
+        assert_eq!(report.failures().count(), 2);
+        assert_eq!(repairs.len(), 2);
+        assert!(repairs.iter().all(|((from, to), names)| *from == holder
+            && missing.contains(to)
+            && names.contains(&chunk)));
+    }
+
+    #[test]
+    fn ranges_cover_the_chunks_reported() {
+        let adults: BTreeSet<_> = (0..2).map(|_| XorName::random()).collect();
+        let chunk = XorName::random();
+        let prefix = Prefix::default().pushed(chunk.bit(0));
+        let mut check = Check::new(ReplicationTarget::Range(prefix), adults.clone());
+
+        let outside = XorName::random().with_bit(0, !chunk.bit(0));
+        let held: BTreeSet<_> = vec![chunk, outside].into_iter().collect();
+        for adult in &adults {
+            let _ = check.record_held(*adult, held.clone());
+        }
+
+        assert!(check.complete(&adults, 2).is_empty());
+        assert_eq!(check.report.chunks.len(), 1);
+        assert_eq!(check.report.chunks[&chunk], ReplicationStatus::Replicated);
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::waitlists::Waitlists;
+use crate::messaging::{
+    data::OperationId,
+    system::{NodeQuery, SystemMsg},
+    MessageId,
+};
+use std::time::Duration;
+use xor_name::XorName;
+
+// Number of Adults queries are kept around for, and for how long. Queries whose response
+// doesn't come in by then aren't sent again, as when a chunk read times out.
+const QUERIES_CAPACITY: usize = 1_000;
+const QUERIES_DURATION: Duration = Duration::from_secs(60);
+// Number of chunks sent to Adults kept around, and for how long: long enough for an Adult to
+// tell us the message carrying one came in corrupted.
+const STORES_CAPACITY: usize = 1_000;
+const STORES_DURATION: Duration = Duration::from_secs(10);
+
+// A query awaiting a response, with whether it was sent again already.
+type PendingQuery = (OperationId, NodeQuery, bool);
+
+/// Keeps the messages sent to Adults which are sent again, once, if they come in corrupted:
+/// the queries until the Adult responds, resent when a message from it comes in corrupted as
+/// it may be the response, and the chunks to store for a little while, resent when the Adult
+/// tells us the message carrying one came in corrupted.
+#[derive(Clone, Debug)]
+pub(crate) struct Resends {
+    queries: Waitlists<XorName, PendingQuery>,
+    stores: Waitlists<(XorName, MessageId), SystemMsg>,
+}
+
+impl Resends {
+    pub(crate) fn new() -> Self {
+        Self {
+            queries: Waitlists::new(QUERIES_DURATION, QUERIES_CAPACITY),
+            stores: Waitlists::new(STORES_DURATION, STORES_CAPACITY),
+        }
+    }
+
+    /// Records that `query` was sent to `adult`.
+    pub(crate) async fn query_sent(
+        &self,
+        adult: XorName,
+        operation_id: OperationId,
+        query: NodeQuery,
+    ) {
+        self.queries.push(adult, (operation_id, query, false)).await
+    }
+
+    /// Records that `adult` responded to a query for `operation_id`.
+    pub(crate) async fn query_fulfilled(&self, adult: XorName, operation_id: &OperationId) {
+        let _ = self
+            .queries
+            .update(adult, |queries| {
+                // only remove the first instance, as the liveness records do
+                if let Some(index) = queries.iter().position(|(id, _, _)| id == operation_id) {
+                    let _ = queries.remove(index);
+                }
+            })
+            .await;
+    }
+
+    /// Returns the queries awaiting a response from `adult` to send again, those which were
+    /// sent again already excepted.
+    pub(crate) async fn queries_to_resend(&self, adult: XorName) -> Vec<NodeQuery> {
+        self.queries
+            .update(adult, |queries| {
+                queries
+                    .iter_mut()
+                    .filter(|(_, _, resent)| !*resent)
+                    .map(|(_, query, resent)| {
+                        *resent = true;
+                        query.clone()
+                    })
+                    .collect()
+            })
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Records that `msg`, storing a chunk, was sent to `adult` in the message of `msg_id`.
+    pub(crate) async fn store_sent(&self, adult: XorName, msg_id: MessageId, msg: SystemMsg) {
+        self.stores.push((adult, msg_id), msg).await
+    }
+
+    /// Takes the chunk store sent to `adult` in the message of `msg_id`, to send it again,
+    /// returning `None` if it was already, or was sent too long ago.
+    pub(crate) async fn store_to_resend(
+        &self,
+        adult: XorName,
+        msg_id: MessageId,
+    ) -> Option<SystemMsg> {
+        self.stores.take(&(adult, msg_id)).await.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Resends;
+    use crate::messaging::{
+        system::{NodeCmd, NodeQuery, SystemMsg},
+        EndUser, MessageId,
+    };
+    use crate::types::{Chunk, ChunkAddress};
+    use bytes::Bytes;
+    use xor_name::XorName;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn queries_are_resent_once_until_fulfilled() {
+        let resends = Resends::new();
+        let adult = XorName::random();
+        let origin = EndUser(XorName::random());
+        let query = |name| NodeQuery::GetChunk {
+            address: ChunkAddress(name),
+            origin,
+        };
+        let (fulfilled, pending) = (XorName::random(), XorName::random());
+
+        resends
+            .query_sent(adult, "fulfilled".to_string(), query(fulfilled))
+            .await;
+        resends
+            .query_sent(adult, "pending".to_string(), query(pending))
+            .await;
+        resends
+            .query_fulfilled(adult, &"fulfilled".to_string())
+            .await;
+
+        assert_eq!(resends.queries_to_resend(adult).await, vec![query(pending)]);
+        assert!(resends.queries_to_resend(adult).await.is_empty());
+        assert!(resends
+            .queries_to_resend(XorName::random())
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stores_are_resent_once() {
+        let resends = Resends::new();
+        let (adult, msg_id) = (XorName::random(), MessageId::new());
+        let msg = SystemMsg::NodeCmd(NodeCmd::ReplicateChunk(Chunk::new(Bytes::from_static(
+            b"chunk",
+        ))));
+
+        resends.store_sent(adult, msg_id, msg.clone()).await;
+
+        assert!(resends
+            .store_to_resend(XorName::random(), msg_id)
+            .await
+            .is_none());
+        assert_eq!(resends.store_to_resend(adult, msg_id).await, Some(msg));
+        assert!(resends.store_to_resend(adult, msg_id).await.is_none());
+    }
+}
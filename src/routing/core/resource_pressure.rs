@@ -0,0 +1,160 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Core;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+use xor_name::XorName;
+
+// Messages let through per second from each client while under pressure. Those beyond are
+// rejected, so clients sending the most are throttled first, rather than all of them alike.
+const CLIENT_MSGS_PER_SEC_UNDER_PRESSURE: u32 = 10;
+// Chunks replicated at once to an Adult missing them while under pressure. Those left out are
+// found missing again by later inventory rounds.
+const REPLICATION_BATCH_UNDER_PRESSURE: usize = 16;
+
+/// How pressed the node is for CPU or memory, as reported by its resource monitor.
+///
+/// Under pressure, the work which can wait is shed, so the node keeps up with its duties to its
+/// section, e.g. agreeing on proposals and taking part in DKG, rather than falling behind on
+/// them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ResourcePressure {
+    /// All work is carried out.
+    Normal,
+    /// CPU or memory use has been high for a while. Chunk inventory rounds are put off, chunks
+    /// are replicated in smaller batches, and the messages of each client beyond a rate are
+    /// rejected with a `Throttled` error.
+    High,
+}
+
+/// The work shed as per the pressure the node is under.
+#[derive(Clone, Debug)]
+pub(crate) struct LoadShedding {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    pressure: ResourcePressure,
+    // Start of the second client messages are being counted in, and how many were let through
+    // from each client.
+    window_start: Instant,
+    admitted: BTreeMap<XorName, u32>,
+}
+
+impl LoadShedding {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                pressure: ResourcePressure::Normal,
+                window_start: Instant::now(),
+                admitted: BTreeMap::new(),
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn pressure(&self) -> ResourcePressure {
+        self.state().pressure
+    }
+
+    /// Returns whether the pressure changed.
+    pub(crate) fn set_pressure(&self, pressure: ResourcePressure) -> bool {
+        let mut state = self.state();
+        if state.pressure == pressure {
+            return false;
+        }
+        state.pressure = pressure;
+        state.window_start = Instant::now();
+        state.admitted.clear();
+        true
+    }
+
+    /// Whether a message from `client` is let through, rather than rejected as throttled.
+    pub(crate) fn admit_client_msg(&self, client: XorName) -> bool {
+        let mut state = self.state();
+        if state.pressure == ResourcePressure::Normal {
+            return true;
+        }
+        let now = Instant::now();
+        if now.duration_since(state.window_start) >= Duration::from_secs(1) {
+            state.window_start = now;
+            state.admitted.clear();
+        }
+        let admitted = state.admitted.entry(client).or_insert(0);
+        if *admitted >= CLIENT_MSGS_PER_SEC_UNDER_PRESSURE {
+            return false;
+        }
+        *admitted += 1;
+        true
+    }
+
+    /// Max number of chunks replicated at once, if capped.
+    pub(crate) fn replication_batch(&self) -> Option<usize> {
+        match self.pressure() {
+            ResourcePressure::Normal => None,
+            ResourcePressure::High => Some(REPLICATION_BATCH_UNDER_PRESSURE),
+        }
+    }
+}
+
+impl Core {
+    /// Sets how pressed the node is for resources, shedding the work which can wait while it
+    /// is.
+    pub(crate) fn set_resource_pressure(&self, pressure: ResourcePressure) {
+        if self.load_shedding.set_pressure(pressure) {
+            match pressure {
+                ResourcePressure::Normal => info!("Resource pressure relieved, resuming all work"),
+                ResourcePressure::High => {
+                    warn!("Under resource pressure, shedding work which can wait")
+                }
+            }
+        }
+    }
+
+    /// Returns how pressed the node is for resources.
+    pub(crate) fn resource_pressure(&self) -> ResourcePressure {
+        self.load_shedding.pressure()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LoadShedding, ResourcePressure, CLIENT_MSGS_PER_SEC_UNDER_PRESSURE};
+    use xor_name::XorName;
+
+    #[test]
+    fn client_msgs_are_throttled_under_pressure_only() {
+        let shedding = LoadShedding::new();
+        let (busy, quiet) = (XorName::random(), XorName::random());
+        for _ in 0..2 * CLIENT_MSGS_PER_SEC_UNDER_PRESSURE {
+            assert!(shedding.admit_client_msg(busy));
+        }
+        assert_eq!(shedding.replication_batch(), None);
+
+        assert!(shedding.set_pressure(ResourcePressure::High));
+        assert!(!shedding.set_pressure(ResourcePressure::High));
+        for _ in 0..CLIENT_MSGS_PER_SEC_UNDER_PRESSURE {
+            assert!(shedding.admit_client_msg(busy));
+        }
+        assert!(!shedding.admit_client_msg(busy));
+        // Other clients aren't throttled for the busy one.
+        assert!(shedding.admit_client_msg(quiet));
+        assert!(shedding.replication_batch().is_some());
+
+        assert!(shedding.set_pressure(ResourcePressure::Normal));
+        assert!(shedding.admit_client_msg(busy));
+    }
+}
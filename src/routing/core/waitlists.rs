@@ -0,0 +1,134 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::types::Cache;
+use std::{hash::Hash, sync::Arc, time::Duration};
+
+/// Lists of items kept per key until taken, or for a limited time, e.g. the queries of the
+/// clients waiting for an answer about a chunk. Lists kept for too long are dropped, as are the
+/// oldest ones once there are too many.
+#[derive(Clone, Debug)]
+pub(crate) struct Waitlists<K, T>
+where
+    K: Hash + Eq + Copy,
+{
+    lists: Arc<Cache<K, Vec<T>>>,
+}
+
+impl<K, T> Waitlists<K, T>
+where
+    K: Ord + Hash + Copy,
+    T: Clone,
+{
+    pub(crate) fn new(duration: Duration, capacity: usize) -> Self {
+        Self {
+            lists: Arc::new(Cache::with_expiry_duration_and_capacity(duration, capacity)),
+        }
+    }
+
+    /// Adds `item` to the list of `key`.
+    pub(crate) async fn push(&self, key: K, item: T) {
+        let mut list = self.lists.get(&key).await.unwrap_or_default();
+        list.push(item);
+        let _ = self.lists.set(key, list, None).await;
+    }
+
+    /// Returns the list of `key`, empty if there's none.
+    pub(crate) async fn get(&self, key: &K) -> Vec<T> {
+        self.lists.get(key).await.unwrap_or_default()
+    }
+
+    /// Removes and returns the list of `key`, empty if there's none.
+    pub(crate) async fn take(&self, key: &K) -> Vec<T> {
+        self.lists.remove(key).await.unwrap_or_default()
+    }
+
+    /// Applies `update` to the list of `key`, if there's one, dropping it if left empty.
+    pub(crate) async fn update<R>(
+        &self,
+        key: K,
+        update: impl FnOnce(&mut Vec<T>) -> R,
+    ) -> Option<R> {
+        let mut list = self.lists.get(&key).await?;
+        let result = update(&mut list);
+        if list.is_empty() {
+            let _ = self.lists.remove(&key).await;
+        } else {
+            let _ = self.lists.set(key, list, None).await;
+        }
+        Some(result)
+    }
+}
+
+/// Keys marked for a limited time, e.g. those we proposed a section signature over, for it not
+/// to be proposed twice.
+#[derive(Clone, Debug)]
+pub(crate) struct Marks<K>
+where
+    K: Hash + Eq + Copy,
+{
+    marks: Arc<Cache<K, ()>>,
+}
+
+impl<K> Marks<K>
+where
+    K: Ord + Hash + Copy,
+{
+    pub(crate) fn new(duration: Duration, capacity: usize) -> Self {
+        Self {
+            marks: Arc::new(Cache::with_expiry_duration_and_capacity(duration, capacity)),
+        }
+    }
+
+    /// Marks `key`, returning false if it already was.
+    pub(crate) async fn mark(&self, key: K) -> bool {
+        if self.is_marked(&key).await {
+            return false;
+        }
+        let _ = self.marks.set(key, (), None).await;
+        true
+    }
+
+    /// Whether `key` is marked.
+    pub(crate) async fn is_marked(&self, key: &K) -> bool {
+        self.marks.get(key).await.is_some()
+    }
+
+    /// Unmarks `key`, returning whether it was marked.
+    pub(crate) async fn unmark(&self, key: &K) -> bool {
+        self.marks.remove(key).await.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Marks, Waitlists};
+    use std::time::Duration;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn lists_are_dropped_once_emptied() {
+        let lists = Waitlists::new(Duration::from_secs(60), 10);
+        lists.push(1, "a").await;
+        lists.push(1, "b").await;
+        assert_eq!(lists.get(&1).await, vec!["a", "b"]);
+
+        assert_eq!(lists.update(1, |list| list.pop()).await, Some(Some("b")));
+        assert_eq!(lists.update(1, |list| list.pop()).await, Some(Some("a")));
+        assert_eq!(lists.update(1, |list| list.pop()).await, None);
+        assert!(lists.take(&1).await.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn keys_are_marked_once() {
+        let marks = Marks::new(Duration::from_secs(60), 10);
+        assert!(marks.mark(1).await);
+        assert!(!marks.mark(1).await);
+        assert!(marks.unmark(&1).await);
+        assert!(!marks.is_marked(&1).await);
+    }
+}
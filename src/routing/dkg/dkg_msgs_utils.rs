@@ -19,6 +19,13 @@ use xor_name::XorName;
 
 pub(crate) trait DkgKeyUtils {
     fn new(elder_candidates: &ElderCandidates, generation: u64) -> Self;
+
+    // The key of the session restarting this one, with the same peers and generation.
+    fn restart(&self) -> Self;
+
+    // Whether the session of `other` is to be dropped in favour of this one's: either it's for
+    // an older generation, or it's an earlier attempt with the same peers and generation.
+    fn supersedes(&self, other: &Self) -> bool;
 }
 
 impl DkgKeyUtils for DkgKey {
@@ -35,7 +42,25 @@ impl DkgKeyUtils for DkgKey {
         hasher.update(&elder_candidates.prefix.bit_count().to_le_bytes());
         hasher.finalize(&mut hash);
 
-        Self { hash, generation }
+        Self {
+            hash,
+            generation,
+            attempt: 0,
+        }
+    }
+
+    fn restart(&self) -> Self {
+        Self {
+            attempt: self.attempt + 1,
+            ..*self
+        }
+    }
+
+    fn supersedes(&self, other: &Self) -> bool {
+        self.generation > other.generation
+            || (self.generation == other.generation
+                && self.hash == other.hash
+                && self.attempt > other.attempt)
     }
 }
 
@@ -64,7 +89,7 @@ pub(crate) trait DkgFailureSigSetUtils {
 
     fn has_agreement(&self, elder_candidates: &ElderCandidates) -> bool;
 
-    fn verify(&self, elder_candidates: &ElderCandidates, generation: u64) -> bool;
+    fn verify(&self, elder_candidates: &ElderCandidates, dkg_key: &DkgKey) -> bool;
 }
 
 impl DkgFailureSigSetUtils for DkgFailureSigSet {
@@ -92,11 +117,12 @@ impl DkgFailureSigSetUtils for DkgFailureSigSet {
         has_failure_agreement(elder_candidates.elders.len(), self.sigs.len())
     }
 
-    fn verify(&self, elder_candidates: &ElderCandidates, generation: u64) -> bool {
-        let hash = hashed_failure(
-            &DkgKey::new(elder_candidates, generation),
-            &self.failed_participants,
-        );
+    fn verify(&self, elder_candidates: &ElderCandidates, dkg_key: &DkgKey) -> bool {
+        if DkgKey::new(elder_candidates, dkg_key.generation).hash != dkg_key.hash {
+            return false;
+        }
+
+        let hash = hashed_failure(dkg_key, &self.failed_participants);
         let votes = self
             .sigs
             .iter()
@@ -126,6 +152,7 @@ fn hashed_failure(dkg_key: &DkgKey, failed_participants: &BTreeSet<XorName>) ->
     let mut hash = Digest256::default();
     hasher.update(&dkg_key.hash);
     hasher.update(&dkg_key.generation.to_le_bytes());
+    hasher.update(&dkg_key.attempt.to_le_bytes());
     for name in failed_participants.iter() {
         hasher.update(&name.0);
     }
@@ -22,6 +22,7 @@ pub(crate) use self::{
 pub(crate) use crate::messaging::system::{KeyedSig, SigShare};
 pub use section_signed::SectionAuthUtils;
 use serde::Serialize;
+pub use session::{DkgSessionInfo, DkgSessionStatus};
 
 // Verify the integrity of `message` against `sig`.
 pub(crate) fn verify_sig<T: Serialize>(sig: &KeyedSig, message: &T) -> bool {
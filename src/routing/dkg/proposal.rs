@@ -58,6 +58,9 @@ impl<'a> Serialize for SignableView<'a> {
             Proposal::SectionInfo(info) => info.serialize(serializer),
             Proposal::OurElders(info) => info.sig.public_key.serialize(serializer),
             Proposal::JoinsAllowed(joins_allowed) => joins_allowed.serialize(serializer),
+            Proposal::DataProof(address) => address.serialize(serializer),
+            Proposal::NetworkTime(timestamp) => timestamp.serialize(serializer),
+            Proposal::ChunkHolders { address, holders } => (address, holders).serialize(serializer),
         }
     }
 }
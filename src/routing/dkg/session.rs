@@ -11,7 +11,7 @@ use crate::messaging::{
     DstLocation, SectionAuthorityProvider, WireMsg,
 };
 use crate::routing::{
-    dkg::dkg_msgs_utils::{DkgFailureSigSetUtils, DkgFailureSigUtils},
+    dkg::dkg_msgs_utils::{DkgFailureSigSetUtils, DkgFailureSigUtils, DkgKeyUtils},
     ed25519,
     error::Result,
     messages::WireMsgUtils,
@@ -28,15 +28,43 @@ use std::{
     collections::{BTreeSet, VecDeque},
     iter, mem,
     net::SocketAddr,
-    time::Duration,
+    time::{Duration, Instant},
 };
-use xor_name::XorName;
+use xor_name::{Prefix, XorName};
 
 // Interval to progress DKG timed phase
 const DKG_PROGRESS_INTERVAL: Duration = Duration::from_secs(30);
 
 const BACKLOG_CAPACITY: usize = 100;
 
+/// Where a DKG session a node takes part in is at.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DkgSessionStatus {
+    /// The participants are still exchanging messages.
+    InProgress,
+    /// The section key was generated.
+    Complete,
+    /// The participants agreed the session failed.
+    Failed,
+}
+
+/// A DKG session a node takes part in, as listed by [`Routing::dkg_sessions`].
+///
+/// [`Routing::dkg_sessions`]: crate::routing::Routing::dkg_sessions
+#[derive(Clone, Debug)]
+pub struct DkgSessionInfo {
+    /// Identifier of the session, as carried by its messages.
+    pub dkg_key: DkgKey,
+    /// Prefix of the section the key is generated for.
+    pub prefix: Prefix,
+    /// Names of the participants, i.e. the Elder candidates.
+    pub participants: BTreeSet<XorName>,
+    /// Where the session is at.
+    pub status: DkgSessionStatus,
+    /// How long ago the node joined the session.
+    pub elapsed: Duration,
+}
+
 // Data for a DKG participant.
 pub(crate) struct Session {
     pub(crate) elder_candidates: ElderCandidates,
@@ -44,10 +72,11 @@ pub(crate) struct Session {
     pub(crate) key_gen: KeyGen,
     pub(crate) timer_token: u64,
     pub(crate) failures: DkgFailureSigSet,
-    // Flag to track whether this session has completed (either with success or failure). We don't
-    // remove complete sessions because the other participants might still need us to respond to
+    // Whether this session has completed, either with success or failure. We don't remove
+    // complete sessions because the other participants might still need us to respond to
     // their messages.
-    pub(crate) complete: bool,
+    pub(crate) status: DkgSessionStatus,
+    pub(crate) started: Instant,
 }
 
 impl Session {
@@ -55,6 +84,20 @@ impl Session {
         self.timer_token
     }
 
+    fn is_complete(&self) -> bool {
+        self.status != DkgSessionStatus::InProgress
+    }
+
+    pub(crate) fn info(&self, dkg_key: &DkgKey) -> DkgSessionInfo {
+        DkgSessionInfo {
+            dkg_key: *dkg_key,
+            prefix: self.elder_candidates.prefix,
+            participants: self.elder_candidates.elders.keys().copied().collect(),
+            status: self.status,
+            elapsed: self.started.elapsed(),
+        }
+    }
+
     pub(crate) fn process_message(
         &mut self,
         node: &Node,
@@ -139,7 +182,7 @@ impl Session {
         dkg_key: &DkgKey,
         section_pk: BlsPublicKey,
     ) -> Result<Vec<Command>> {
-        if self.complete {
+        if self.is_complete() {
             return Ok(vec![]);
         }
 
@@ -169,7 +212,7 @@ impl Session {
         dkg_key: &DkgKey,
         section_pk: BlsPublicKey,
     ) -> Result<Vec<Command>> {
-        if self.complete {
+        if self.is_complete() {
             return Ok(vec![]);
         }
 
@@ -229,7 +272,7 @@ impl Session {
             outcome.public_key_set.public_key()
         );
 
-        self.complete = true;
+        self.status = DkgSessionStatus::Complete;
         let section_auth = SectionAuthorityProvider::from_elder_candidates(
             self.elder_candidates.clone(),
             outcome.public_key_set.clone(),
@@ -261,7 +304,7 @@ impl Session {
         }
 
         let cmds = self
-            .check_failure_agreement()
+            .check_failure_agreement(dkg_key)
             .into_iter()
             .chain(iter::once({
                 let node_msg = SystemMsg::DkgFailureObservation {
@@ -311,14 +354,17 @@ impl Session {
             return None;
         }
 
-        self.check_failure_agreement()
+        self.check_failure_agreement(dkg_key)
     }
 
-    fn check_failure_agreement(&mut self) -> Option<Command> {
+    fn check_failure_agreement(&mut self, dkg_key: &DkgKey) -> Option<Command> {
         if self.failures.has_agreement(&self.elder_candidates) {
-            self.complete = true;
+            self.status = DkgSessionStatus::Failed;
 
-            Some(Command::HandleDkgFailure(mem::take(&mut self.failures)))
+            Some(Command::HandleDkgFailure {
+                dkg_key: *dkg_key,
+                failure_set: mem::take(&mut self.failures),
+            })
         } else {
             None
         }
@@ -367,7 +413,7 @@ impl Backlog {
 
     pub(crate) fn prune(&mut self, dkg_key: &DkgKey) {
         self.0
-            .retain(|(old_dkg_key, _)| old_dkg_key.generation >= dkg_key.generation)
+            .retain(|(old_dkg_key, _)| !dkg_key.supersedes(old_dkg_key))
     }
 }
 
@@ -407,6 +453,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn restarts_supersede_earlier_attempts() -> Result<()> {
+        let section_pk = bls::SecretKey::random().public_key();
+        let nodes: Vec<_> = (0..3)
+            .map(|_| {
+                Node::new(
+                    ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE),
+                    gen_addr(),
+                )
+            })
+            .collect();
+        let elder_candidates =
+            ElderCandidates::new(nodes.iter().map(Node::peer), Prefix::default());
+        let dkg_key = DkgKey::new(&elder_candidates, 0);
+        let restarted_dkg_key = dkg_key.restart();
+
+        let mut voter = DkgVoter::default();
+        let _ = voter.start(&nodes[0], dkg_key, elder_candidates.clone(), section_pk)?;
+        let _ = voter.start(
+            &nodes[0],
+            restarted_dkg_key,
+            elder_candidates.clone(),
+            section_pk,
+        )?;
+
+        let sessions = voter.sessions();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].dkg_key, restarted_dkg_key);
+        assert_eq!(sessions[0].status, DkgSessionStatus::InProgress);
+        assert_eq!(sessions[0].participants.len(), 3);
+
+        // The superseded session isn't started again, e.g. by a lagging `DkgStart`.
+        let commands = voter.start(&nodes[0], dkg_key, elder_candidates, section_pk)?;
+        assert!(commands.is_empty());
+        assert_eq!(voter.sessions().len(), 1);
+
+        Ok(())
+    }
+
     proptest! {
         // Run a DKG session where every participant handles every message sent to them.
         // Expect the session to successfully complete without timed transitions.
@@ -11,7 +11,10 @@ use crate::messaging::{
     SectionAuthorityProvider,
 };
 use crate::routing::{
-    dkg::session::{Backlog, Session},
+    dkg::{
+        session::{Backlog, DkgSessionInfo, DkgSessionStatus, Session},
+        DkgKeyUtils,
+    },
     ed25519,
     error::Result,
     node::Node,
@@ -21,7 +24,11 @@ use crate::routing::{
 };
 use bls::PublicKey as BlsPublicKey;
 use bls_dkg::key_gen::{message::Message as DkgMessage, KeyGen};
-use std::collections::{BTreeSet, HashMap};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+    time::Instant,
+};
 use xor_name::XorName;
 
 /// DKG voter carries out the work of participating and/or observing a DKG.
@@ -37,6 +44,10 @@ use xor_name::XorName;
 /// 5. On DKG completion, the participants send `DkgResult` vote to the current elders (observers)
 /// 6. When the observers accumulate the votes, they can proceed with voting for the section update.
 ///
+/// Sessions are identified by their `DkgKey`, which all their messages carry. Starting a session
+/// drops those it supersedes: the ones of older generations, and the earlier attempts of the
+/// same session when it's restarted. Messages for superseded sessions are dropped as well.
+///
 /// Note: in case of heavy churn, it can happen that more than one DKG session completes
 /// successfully. Some kind of disambiguation strategy needs to be employed in that case, but that
 /// is currently not a responsibility of this module.
@@ -72,6 +83,15 @@ impl DkgVoter {
             return Ok(vec![]);
         }
 
+        if self.is_superseded(&dkg_key) {
+            trace!(
+                "DKG not starting for {:?}: {:?} is superseded",
+                elder_candidates,
+                dkg_key
+            );
+            return Ok(vec![]);
+        }
+
         let name = ed25519::name(&node.keypair.public);
         let participant_index = if let Some(index) = elder_candidates.position(&name) {
             index
@@ -113,7 +133,8 @@ impl DkgVoter {
                     participant_index,
                     timer_token: 0,
                     failures: DkgFailureSigSet::default(),
-                    complete: false,
+                    status: DkgSessionStatus::InProgress,
+                    started: Instant::now(),
                 };
 
                 let mut commands = vec![];
@@ -125,9 +146,15 @@ impl DkgVoter {
 
                 let _ = self.sessions.insert(dkg_key, session);
 
-                // Remove uneeded old sessions.
+                // Remove sessions this one supersedes, i.e. of older generations, or
+                // earlier attempts of this one.
                 self.sessions.retain(|existing_dkg_key, _| {
-                    existing_dkg_key.generation >= dkg_key.generation
+                    if dkg_key.supersedes(existing_dkg_key) {
+                        trace!("DKG {:?} superseded by {:?}", existing_dkg_key, dkg_key);
+                        false
+                    } else {
+                        true
+                    }
                 });
                 self.backlog.prune(&dkg_key);
 
@@ -169,6 +196,9 @@ impl DkgVoter {
     ) -> Result<Vec<Command>> {
         if let Some(session) = self.sessions.get_mut(dkg_key) {
             session.process_message(node, dkg_key, message, section_pk)
+        } else if self.is_superseded(dkg_key) {
+            trace!("Dropping DKG message for superseded {:?}", dkg_key);
+            Ok(vec![])
         } else {
             self.backlog.push(*dkg_key, message);
             Ok(vec![])
@@ -185,4 +215,28 @@ impl DkgVoter {
             .get_mut(dkg_key)?
             .process_failure(dkg_key, failed_participants, signed)
     }
+
+    // Lists the sessions we take part in, most recent generations and attempts first.
+    pub(crate) fn sessions(&self) -> Vec<DkgSessionInfo> {
+        let mut sessions: Vec<_> = self
+            .sessions
+            .iter()
+            .map(|(dkg_key, session)| session.info(dkg_key))
+            .collect();
+        sessions.sort_by_key(|info| {
+            (
+                Reverse(info.dkg_key.generation),
+                Reverse(info.dkg_key.attempt),
+                info.elapsed,
+            )
+        });
+        sessions
+    }
+
+    // Whether one of our sessions supersedes that of `dkg_key`.
+    fn is_superseded(&self, dkg_key: &DkgKey) -> bool {
+        self.sessions
+            .keys()
+            .any(|existing_dkg_key| existing_dkg_key.supersedes(dkg_key))
+    }
 }
@@ -82,6 +82,12 @@ pub enum Error {
     NoMatchingElder,
     #[error("Node cannot join the network since it is not externally reachable: {0}")]
     NodeNotReachable(SocketAddr),
+    #[error("Node cannot join the network since its clock is too far off the Elders' ({elder_timestamp} seconds since the Unix epoch)")]
+    ClockSkew { elder_timestamp: u64 },
+    #[error(
+        "Node cannot join the network since a node with the same name is already a member, at {0}"
+    )]
+    DuplicateIdentity(SocketAddr),
     /// Database error.
     #[error("Database error:: {0}")]
     Database(#[from] crate::dbs::Error),
@@ -158,6 +164,19 @@ impl From<qp2p::ClientEndpointError> for Error {
     }
 }
 
+impl From<crate::prefix_map::Error> for Error {
+    fn from(error: crate::prefix_map::Error) -> Self {
+        use crate::prefix_map::Error as PrefixMapError;
+        match error {
+            PrefixMapError::NoMatchingSection => Self::NoMatchingSection,
+            PrefixMapError::UntrustedSectionAuthProvider(msg) => {
+                Self::UntrustedSectionAuthProvider(msg)
+            }
+            PrefixMapError::UntrustedProofChain(msg) => Self::UntrustedProofChain(msg),
+        }
+    }
+}
+
 impl From<qp2p::SendError> for Error {
     fn from(error: qp2p::SendError) -> Self {
         Self::AddressNotReachable {
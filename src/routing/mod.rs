@@ -26,12 +26,16 @@
 pub use self::error::ProposalError;
 pub(crate) use self::{
     core::ChunkStore,
+    core::PaymentStore,
     core::RegisterStorage,
     core::{CHUNK_COPY_COUNT, MIN_LEVEL_WHEN_FULL},
     section::section_keys::SectionKeyShare,
 };
 pub use self::{
-    dkg::SectionAuthUtils,
+    core::{
+        CapacityRecord, MigrationProgress, ReplicationReport, ReplicationStatus, ResourcePressure,
+    },
+    dkg::{DkgSessionInfo, DkgSessionStatus, SectionAuthUtils},
     error::{Error, Result},
     peer::PeerUtils,
     routing_api::{
@@ -48,6 +52,10 @@ pub use self::{
 pub use qp2p::{Config as NetworkConfig, SendStream};
 pub use xor_name::{Prefix, XorName, XOR_NAME_LEN}; // TODO remove pub on API update
 
+use std::time::Duration;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub use self::routing_api::snapshot::{NetworkSnapshot, NodeSnapshot};
 #[cfg(any(test, feature = "test-utils"))]
 pub use test_utils::*;
 
@@ -77,8 +85,10 @@ mod section;
 /// this number of nodes.
 pub const RECOMMENDED_SECTION_SIZE: usize = 2 * ELDER_SIZE;
 
-/// Number of elders per section.
-pub const ELDER_SIZE: usize = 7;
+pub use crate::messaging::ELDER_SIZE;
+
+/// How far off the Elders' clocks the clock of a node may be for it to join their section.
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(5 * 60);
 
 /// SuperMajority of a given group (i.e. > 2/3)
 #[inline]
@@ -7,8 +7,8 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::messaging::{
-    system::{DkgFailureSigSet, KeyedSig, Proposal, Section, SystemMsg},
-    DstLocation, MessageId, NodeMsgAuthority, SectionAuthorityProvider, WireMsg,
+    system::{DkgFailureSigSet, DkgKey, KeyedSig, Proposal, ReplicationTarget, Section, SystemMsg},
+    DstLocation, MessageId, MsgTrace, NodeMsgAuthority, SectionAuthorityProvider, WireMsg,
 };
 use crate::routing::{node::Node, routing_api::Peer, section::SectionKeyShare, XorName};
 use bls::PublicKey as BlsPublicKey;
@@ -45,6 +45,9 @@ pub(crate) enum Command {
         payload: Bytes,
         #[debug(skip)]
         known_keys: Vec<BlsPublicKey>,
+        /// Path of the message, if it's traced.
+        #[debug(skip)]
+        trace: Option<MsgTrace>,
     },
     /// Handle verified node message after aggregation either directly or notify via event listener
     HandleVerifiedNodeNonDataMessage {
@@ -61,6 +64,9 @@ pub(crate) enum Command {
         msg: SystemMsg,
         msg_authority: NodeMsgAuthority,
         dst_location: DstLocation,
+        /// Path of the message, if it's traced.
+        #[debug(skip)]
+        trace: Option<MsgTrace>,
     },
     /// Handle a timeout previously scheduled with `ScheduleTimeout`.
     HandleTimeout(u64),
@@ -68,6 +74,11 @@ pub(crate) enum Command {
     HandleConnectionLost(SocketAddr),
     /// Handle peer that's been detected as lost.
     HandlePeerLost(SocketAddr),
+    /// Handle a message from `sender` whose payload came in corrupted.
+    HandleCorruptedMessage {
+        sender: SocketAddr,
+        msg_id: MessageId,
+    },
     /// Handle agreement on a proposal.
     HandleAgreement { proposal: Proposal, sig: KeyedSig },
     /// Handle the outcome of a DKG session where we are one of the participants (that is, one of
@@ -77,7 +88,10 @@ pub(crate) enum Command {
         outcome: SectionKeyShare,
     },
     /// Handle a DKG failure that was observed by a majority of the DKG participants.
-    HandleDkgFailure(DkgFailureSigSet),
+    HandleDkgFailure {
+        dkg_key: DkgKey,
+        failure_set: DkgFailureSigSet,
+    },
     /// Send a message to the given `recipients`.
     SendMessage {
         recipients: Vec<(XorName, SocketAddr)>,
@@ -86,7 +100,13 @@ pub(crate) enum Command {
     /// Parses WireMsg to send to the correct location
     ParseAndSendWireMsg(WireMsg),
     /// Performs serialisation and signing for sending of NodeMst
-    PrepareNodeMsgToSend { msg: SystemMsg, dst: DstLocation },
+    PrepareNodeMsgToSend {
+        msg: SystemMsg,
+        dst: DstLocation,
+        /// Path of the traced message this one is sent in response to, if any.
+        #[debug(skip)]
+        trace: Option<MsgTrace>,
+    },
     /// Send a message to `delivery_group_size` peers out of the given `recipients`.
     SendMessageDeliveryGroup {
         recipients: Vec<(XorName, SocketAddr)>,
@@ -120,6 +140,43 @@ pub(crate) enum Command {
     StartConnectivityTest(XorName),
     /// Test Connectivity
     TestConnectivity(XorName),
+    /// Verify the given chunks are held by all their holders, replicating them where missing
+    StartReplicationCheck(ReplicationTarget),
+}
+
+impl Command {
+    /// The path of the traced message this command handles, or is caused by, if any.
+    pub(crate) fn trace(&self) -> Option<&MsgTrace> {
+        match self {
+            Self::HandleMessage { wire_msg, .. } => wire_msg.trace(),
+            Self::HandleSystemMessage { trace, .. }
+            | Self::HandleVerifiedNodeDataMessage { trace, .. }
+            | Self::PrepareNodeMsgToSend { trace, .. } => trace.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Passes the path of the traced message which caused this command on, to the message the
+    /// command sends, or to the commands handling the message further, unless they already
+    /// have a path of their own. The path is thus carried by every message the traced one
+    /// causes, from node to node, and back to the client with the response.
+    pub(crate) fn forward_trace(&mut self, path: &MsgTrace) {
+        match self {
+            Self::SendMessage { wire_msg, .. }
+            | Self::SendMessageDeliveryGroup { wire_msg, .. }
+            | Self::ParseAndSendWireMsg(wire_msg) => {
+                if wire_msg.trace().is_none() {
+                    wire_msg.set_trace(path.clone());
+                }
+            }
+            Self::HandleSystemMessage { trace, .. }
+            | Self::HandleVerifiedNodeDataMessage { trace, .. }
+            | Self::PrepareNodeMsgToSend { trace, .. } => {
+                let _ = trace.get_or_insert_with(|| path.clone());
+            }
+            _ => (),
+        }
+    }
 }
 
 /// Generate unique timer token.
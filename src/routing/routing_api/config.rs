@@ -6,7 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::routing::NetworkConfig;
+use crate::messaging::data::DataLimits;
+use crate::routing::{NetworkConfig, CHUNK_COPY_COUNT};
 use ed25519_dalek::Keypair;
 use std::{
     collections::BTreeSet,
@@ -29,6 +30,12 @@ pub struct Config {
     pub genesis_key: Option<String>,
     /// Configuration for the underlying network transport.
     pub network_config: NetworkConfig,
+    /// Number of copies of each chunk the section maintains among its Adults.
+    /// All the nodes of a network are expected to use the same value.
+    pub replication_factor: usize,
+    /// Limits on the size of the data the section accepts, advertised to clients.
+    /// All the nodes of a network are expected to use the same values.
+    pub data_limits: DataLimits,
 }
 
 impl Default for Config {
@@ -40,6 +47,8 @@ impl Default for Config {
             bootstrap_nodes: BTreeSet::new(),
             genesis_key: None,
             network_config: NetworkConfig::default(),
+            replication_factor: CHUNK_COPY_COUNT,
+            data_limits: DataLimits::default(),
         }
     }
 }
@@ -13,7 +13,7 @@ use crate::messaging::{
     DstLocation, EndUser, MsgKind, WireMsg,
 };
 use crate::routing::{
-    core::{ChunkStore, RegisterStorage},
+    core::{ChunkStore, PaymentStore, RegisterStorage},
     core::{Core, SendStatus},
     error::Result,
     messages::WireMsgUtils,
@@ -39,6 +39,12 @@ pub(super) struct Dispatcher {
 
     cancel_timer_tx: watch::Sender<bool>,
     cancel_timer_rx: watch::Receiver<bool>,
+
+    // Whether outgoing messages are being held back, e.g. to snapshot the network in tests.
+    #[cfg(any(test, feature = "test-utils"))]
+    delivery_frozen_tx: watch::Sender<bool>,
+    #[cfg(any(test, feature = "test-utils"))]
+    delivery_frozen_rx: watch::Receiver<bool>,
 }
 
 impl Drop for Dispatcher {
@@ -51,10 +57,32 @@ impl Drop for Dispatcher {
 impl Dispatcher {
     pub(super) fn new(core: Core) -> Self {
         let (cancel_timer_tx, cancel_timer_rx) = watch::channel(false);
+        #[cfg(any(test, feature = "test-utils"))]
+        let (delivery_frozen_tx, delivery_frozen_rx) = watch::channel(false);
         Self {
             core: RwLock::new(core),
             cancel_timer_tx,
             cancel_timer_rx,
+            #[cfg(any(test, feature = "test-utils"))]
+            delivery_frozen_tx,
+            #[cfg(any(test, feature = "test-utils"))]
+            delivery_frozen_rx,
+        }
+    }
+
+    #[cfg(any(test, feature = "test-utils"))]
+    pub(super) fn set_delivery_frozen(&self, frozen: bool) {
+        let _ = self.delivery_frozen_tx.send(frozen);
+    }
+
+    // Waits until outgoing messages are allowed to be sent.
+    #[cfg(any(test, feature = "test-utils"))]
+    async fn wait_for_delivery(&self) {
+        let mut delivery_frozen_rx = self.delivery_frozen_rx.clone();
+        while *delivery_frozen_rx.borrow() {
+            if delivery_frozen_rx.changed().await.is_err() {
+                break;
+            }
         }
     }
 
@@ -66,10 +94,18 @@ impl Dispatcher {
         self.core.read().await.chunk_storage.clone()
     }
 
+    pub(super) async fn get_payment_store(&self) -> PaymentStore {
+        self.core.read().await.payment_store.clone()
+    }
+
     pub(super) async fn get_chunk_data_of(&self, prefix: &Prefix) -> ChunkDataExchange {
         self.core.read().await.get_data_of(prefix).await
     }
 
+    pub(super) async fn replication_factor(&self) -> usize {
+        self.core.read().await.get_copy_count()
+    }
+
     /// Returns whether the level changed or not.
     pub(super) async fn set_storage_level(&self, node_id: &PublicKey, level: StorageLevel) -> bool {
         self.core
@@ -85,9 +121,18 @@ impl Dispatcher {
 
     /// Handles the given command and transitively any new commands that are produced during its
     /// handling.
-    pub(super) async fn handle_commands(self: Arc<Self>, command: Command) -> Result<()> {
+    pub(super) async fn handle_commands(self: Arc<Self>, mut command: Command) -> Result<()> {
+        // Record we handled the message, if its sender wants to know its path, before passing
+        // the path on to the messages it causes.
+        if let Command::HandleMessage { wire_msg, .. } = &mut command {
+            wire_msg.add_hop(&self.core.read().await.node().keypair)?;
+        }
+        let trace = command.trace().cloned();
         let commands = self.handle_command(command).await?;
-        for command in commands {
+        for mut command in commands {
+            if let Some(trace) = &trace {
+                command.forward_trace(trace);
+            }
             self.clone().spawn_handle_commands(command)
         }
 
@@ -129,6 +174,16 @@ impl Dispatcher {
     }
 
     async fn try_handle_command(&self, command: Command) -> Result<Vec<Command>> {
+        #[cfg(any(test, feature = "test-utils"))]
+        if matches!(
+            command,
+            Command::SendMessage { .. }
+                | Command::SendMessageDeliveryGroup { .. }
+                | Command::ParseAndSendWireMsg(_)
+        ) {
+            self.wait_for_delivery().await;
+        }
+
         match command {
             // Data node msg that requires no locking
             Command::HandleVerifiedNodeDataMessage {
@@ -136,6 +191,7 @@ impl Dispatcher {
                 msg_authority,
                 dst_location,
                 msg,
+                ..
             } => {
                 self.core
                     .read()
@@ -177,6 +233,7 @@ impl Dispatcher {
                 msg,
                 payload,
                 known_keys,
+                ..
             } => {
                 self.core
                     .read()
@@ -192,7 +249,7 @@ impl Dispatcher {
                     )
                     .await
             }
-            Command::PrepareNodeMsgToSend { msg, dst } => {
+            Command::PrepareNodeMsgToSend { msg, dst, .. } => {
                 self.core.read().await.prepare_node_msg(msg, dst)
             }
             Command::HandleMessage {
@@ -206,7 +263,7 @@ impl Dispatcher {
                     .handle_message(sender, wire_msg, original_bytes)
                     .await
             }
-            Command::HandleTimeout(token) => self.core.write().await.handle_timeout(token),
+            Command::HandleTimeout(token) => self.core.write().await.handle_timeout(token).await,
             Command::HandleAgreement { proposal, sig } => {
                 self.core
                     .write()
@@ -218,6 +275,13 @@ impl Dispatcher {
                 self.core.read().await.handle_connection_lost(addr)
             }
             Command::HandlePeerLost(addr) => self.core.read().await.handle_peer_lost(&addr),
+            Command::HandleCorruptedMessage { sender, msg_id } => {
+                self.core
+                    .read()
+                    .await
+                    .handle_corrupted_msg(sender, msg_id)
+                    .await
+            }
             Command::HandleDkgOutcome {
                 section_auth,
                 outcome,
@@ -226,11 +290,14 @@ impl Dispatcher {
                 .write()
                 .await
                 .handle_dkg_outcome(section_auth, outcome),
-            Command::HandleDkgFailure(signeds) => self
+            Command::HandleDkgFailure {
+                dkg_key,
+                failure_set,
+            } => self
                 .core
                 .write()
                 .await
-                .handle_dkg_failure(signeds)
+                .handle_dkg_failure(dkg_key, failure_set)
                 .map(|command| vec![command]),
             Command::SendMessage {
                 recipients,
@@ -326,6 +393,9 @@ impl Dispatcher {
                 }
                 Ok(commands)
             }
+            Command::StartReplicationCheck(target) => {
+                self.core.read().await.start_replication_check(target)
+            }
         }
     }
 
@@ -401,8 +471,8 @@ impl Dispatcher {
                 debug!("Sending client msg to {:?}: {:?}", socket_addr, wire_msg);
 
                 let recipients = vec![(*name, socket_addr)];
-                wire_msg
-                    .set_dst_section_pk(*self.core.read().await.section_chain().clone().last_key());
+                let core = self.core.read().await;
+                wire_msg.set_dst_section_pk(*core.section_chain().clone().last_key());
 
                 let command = Command::SendMessage {
                     recipients,
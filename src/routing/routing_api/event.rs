@@ -18,7 +18,7 @@ use xor_name::{Prefix, XorName};
 
 /// A flag in EldersChanged event, indicating
 /// whether the node got promoted, demoted or did not change.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum NodeElderChange {
     /// The node was promoted to Elder.
     Promoted,
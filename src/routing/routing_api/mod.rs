@@ -15,6 +15,8 @@ pub(super) mod config;
 mod dispatcher;
 pub(super) mod event;
 pub(super) mod event_stream;
+#[cfg(any(test, feature = "test-utils"))]
+pub(super) mod snapshot;
 
 use self::{
     command::Command,
@@ -25,11 +27,15 @@ use self::{
 };
 use crate::messaging::{
     data::StorageLevel,
-    system::{Peer, SystemMsg},
-    DstLocation, SectionAuthorityProvider, WireMsg,
+    system::{Peer, ReplicationTarget, SystemMsg},
+    DstLocation, Error as MessagingError, SectionAuthorityProvider, WireMsg,
 };
 use crate::routing::{
-    core::{join_network, ChunkStore, Comm, ConnectionEvent, Core, RegisterStorage},
+    core::{
+        join_network, CapacityRecord, ChunkStore, Comm, ConnectionEvent, Core, MigrationProgress,
+        PaymentStore, RegisterStorage, ReplicationReport, ResourcePressure,
+    },
+    dkg::DkgSessionInfo,
     ed25519,
     error::{Error, Result},
     messages::WireMsgUtils,
@@ -44,7 +50,11 @@ use crate::types::PublicKey as TypesPublicKey;
 use itertools::Itertools;
 use secured_linked_list::SecuredLinkedList;
 use std::path::PathBuf;
-use std::{collections::BTreeSet, net::SocketAddr, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    sync::Arc,
+};
 use tokio::{sync::mpsc, task};
 use xor_name::{Prefix, XorName};
 
@@ -77,10 +87,18 @@ impl Routing {
         used_space: UsedSpace,
         root_storage_dir: PathBuf,
     ) -> Result<(Self, EventStream)> {
+        if config.replication_factor == 0 {
+            return Err(Error::Configuration(
+                "Replication factor must be at least 1.".to_string(),
+            ));
+        }
+        let replication_factor = config.replication_factor;
+        let data_limits = config.data_limits;
+
         let (event_tx, event_rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
         let (connection_event_tx, mut connection_event_rx) = mpsc::channel(1);
 
-        let core = if config.first {
+        let mut core = if config.first {
             // Genesis node having a fix age of 255.
             let keypair = ed25519::gen_keypair(&Prefix::default().range_inclusive(), 255);
             let node_name = ed25519::name(&keypair.public);
@@ -167,6 +185,7 @@ impl Routing {
                 &mut connection_event_rx,
                 bootstrap_addr,
                 genesis_key,
+                used_space.max_capacity(),
             )
             .await?;
             let core = Core::new(
@@ -183,6 +202,11 @@ impl Routing {
             core
         };
 
+        core.set_replication_factor(replication_factor);
+        core.set_data_limits(data_limits);
+        let chunk_inventory_timer = core.schedule_chunk_inventory();
+        let capacity_check_timer = core.schedule_capacity_checks();
+
         let dispatcher = Arc::new(Dispatcher::new(core));
         let event_stream = EventStream::new(event_rx);
 
@@ -192,6 +216,12 @@ impl Routing {
             connection_event_rx,
         ));
 
+        // Start running inventory rounds, which are skipped until we're an Elder.
+        let _ = task::spawn(dispatcher.clone().handle_commands(chunk_inventory_timer));
+
+        // Likewise for capacity spot-checks.
+        let _ = task::spawn(dispatcher.clone().handle_commands(capacity_check_timer));
+
         let routing = Self { dispatcher };
 
         Ok((routing, event_stream))
@@ -204,10 +234,19 @@ impl Routing {
     pub(crate) async fn get_chunk_storage(&self) -> ChunkStore {
         self.dispatcher.get_chunk_storage().await
     }
+
+    pub(crate) async fn get_payment_store(&self) -> PaymentStore {
+        self.dispatcher.get_payment_store().await
+    }
     pub(crate) async fn get_chunk_data_of(&self, prefix: &Prefix) -> ChunkDataExchange {
         self.dispatcher.get_chunk_data_of(prefix).await
     }
 
+    /// Number of copies of each chunk maintained by our section.
+    pub(crate) async fn replication_factor(&self) -> usize {
+        self.dispatcher.replication_factor().await
+    }
+
     /// Returns whether the level changed or not.
     pub(crate) async fn set_storage_level(
         &self,
@@ -241,6 +280,27 @@ impl Routing {
         self.dispatcher.clone().handle_commands(command).await
     }
 
+    /// Starts verifying the given chunks are held by all their holders, asking the Adults of
+    /// our section which of them they hold, and replicating them to the holders missing them.
+    /// Only Elders can check replication. The outcome is then returned by
+    /// [`replication_check`](Self::replication_check).
+    pub async fn start_replication_check(&self, target: ReplicationTarget) -> Result<()> {
+        let command = Command::StartReplicationCheck(target);
+        self.dispatcher.clone().handle_commands(command).await
+    }
+
+    /// Returns the outcome of the replication check last started at this node, if any.
+    pub async fn replication_check(&self) -> Option<ReplicationReport> {
+        self.dispatcher.core.read().await.replication_check()
+    }
+
+    /// Returns the storage capacity the Adults of our section advertised on joining, and how
+    /// they fared at the spot-checks of it by this node. Only Elders spot-check capacity, each
+    /// the Adults it's the closest Elder to.
+    pub async fn capacity_records(&self) -> BTreeMap<XorName, CapacityRecord> {
+        self.dispatcher.core.read().await.capacity_records()
+    }
+
     /// Returns the current age of this node.
     pub async fn age(&self) -> u8 {
         self.dispatcher.core.read().await.node().age()
@@ -322,6 +382,48 @@ impl Routing {
         self.dispatcher.core.read().await.is_elder()
     }
 
+    /// Returns the progress of handing off the data this node held before its last change of
+    /// role, if it changed since it started: chunks on being promoted, registers on being
+    /// demoted.
+    pub async fn data_migration(&self) -> Option<MigrationProgress> {
+        self.dispatcher.core.read().await.data_migration()
+    }
+
+    /// Sets how pressed this node is for CPU or memory. Under pressure, the work which can wait
+    /// is shed, so the node keeps up with its duties to its section.
+    pub async fn set_resource_pressure(&self, pressure: ResourcePressure) {
+        self.dispatcher
+            .core
+            .read()
+            .await
+            .set_resource_pressure(pressure)
+    }
+
+    /// Returns how pressed this node is for CPU or memory.
+    pub async fn resource_pressure(&self) -> ResourcePressure {
+        self.dispatcher.core.read().await.resource_pressure()
+    }
+
+    /// Returns the DKG sessions this node takes part in, most recent first, including
+    /// the complete ones other participants may still need it to respond to.
+    pub async fn dkg_sessions(&self) -> Vec<DkgSessionInfo> {
+        self.dispatcher.core.read().await.dkg_sessions()
+    }
+
+    /// Returns the names of the other Elders of our section we have a connection open with.
+    pub async fn connected_elders(&self) -> BTreeSet<XorName> {
+        let core = self.dispatcher.core.read().await;
+        let our_name = core.node().name();
+        let mut connected = BTreeSet::new();
+        for peer in core.section().authority_provider().peers() {
+            if *peer.name() != our_name && core.comm.get_connection_id(peer.addr()).await.is_some()
+            {
+                let _ = connected.insert(*peer.name());
+            }
+        }
+        connected
+    }
+
     /// Returns the information of all the current section elders.
     pub async fn our_elders(&self) -> Vec<Peer> {
         self.dispatcher
@@ -465,6 +567,11 @@ async fn handle_connection_events(
                 // bytes.clone is cheap
                 let wire_msg = match WireMsg::from(bytes.clone()) {
                     Ok(wire_msg) => wire_msg,
+                    Err(MessagingError::CorruptedPayload(msg_id)) => {
+                        let command = Command::HandleCorruptedMessage { sender, msg_id };
+                        let _ = task::spawn(dispatcher.clone().handle_commands(command));
+                        continue;
+                    }
                     Err(error) => {
                         error!("Failed to deserialize message header: {}", error);
                         continue;
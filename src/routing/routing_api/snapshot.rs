@@ -0,0 +1,222 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Routing;
+use crate::dbs::serialise;
+use crate::messaging::data::RegisterDataExchange;
+use crate::routing::{
+    error::{Error, Result},
+    peer::PeerUtils,
+};
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+use xor_name::{Prefix, XorName};
+
+/// State of a single node at the time a snapshot was taken.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    /// Name of the node.
+    pub name: XorName,
+    /// Whether the node was an elder.
+    pub is_elder: bool,
+    /// Prefix of the node's section.
+    pub prefix: Prefix,
+    /// Last key of the node's section chain.
+    pub section_key: bls::PublicKey,
+    /// Elders of the node's section, as seen by the node.
+    pub elders: BTreeSet<XorName>,
+    /// Adults of the node's section, as seen by the node.
+    pub adults: BTreeSet<XorName>,
+    /// Names of the chunks stored by the node, which are digests of their content.
+    pub chunks: BTreeSet<XorName>,
+    /// Digest of the operations applied to each of the Registers held by the node.
+    pub registers: BTreeMap<XorName, XorName>,
+}
+
+/// Snapshot of the state of a set of nodes, taken while message delivery between them was frozen.
+///
+/// Snapshots can be serialised, so they can also be compared across different runs of a test.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSnapshot {
+    /// Snapshots of each node, by node name.
+    pub nodes: BTreeMap<XorName, NodeSnapshot>,
+}
+
+impl NetworkSnapshot {
+    /// Freezes message delivery on all the given nodes, snapshots their state and resumes delivery.
+    ///
+    /// Fails if the snapshot couldn't be taken within `time_box`, in which case delivery
+    /// is resumed anyway.
+    pub async fn take(nodes: &[&Routing], time_box: Duration) -> Result<Self> {
+        for node in nodes {
+            node.freeze_message_delivery().await;
+        }
+
+        let snapshots = tokio::time::timeout(
+            time_box,
+            try_join_all(nodes.iter().map(|node| node.snapshot())),
+        )
+        .await;
+
+        for node in nodes {
+            node.resume_message_delivery().await;
+        }
+
+        let snapshots = snapshots.map_err(|_| {
+            Error::Logic(format!("Network snapshot not taken within {:?}", time_box))
+        })??;
+
+        Ok(Self {
+            nodes: snapshots
+                .into_iter()
+                .map(|snapshot| (snapshot.name, snapshot))
+                .collect(),
+        })
+    }
+
+    /// Returns whether all the nodes of each section agree on their section's
+    /// key, elders and adults.
+    pub fn is_converged(&self) -> bool {
+        let mut views = BTreeMap::new();
+        for node in self.nodes.values() {
+            let view = (&node.section_key, &node.elders, &node.adults);
+            if *views.entry(node.prefix).or_insert(view) != view {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns the names of the chunks, and how many of the nodes hold each of them.
+    pub fn chunk_holders(&self) -> BTreeMap<XorName, usize> {
+        let mut holders = BTreeMap::new();
+        for chunk in self.nodes.values().flat_map(|node| node.chunks.iter()) {
+            *holders.entry(*chunk).or_insert(0) += 1;
+        }
+        holders
+    }
+
+    /// Returns the names of the nodes whose state differs from `other`,
+    /// including those only present in one of the two snapshots.
+    pub fn differences(&self, other: &Self) -> BTreeSet<XorName> {
+        self.nodes
+            .keys()
+            .chain(other.nodes.keys())
+            .filter(|name| self.nodes.get(name) != other.nodes.get(name))
+            .copied()
+            .collect()
+    }
+}
+
+impl Routing {
+    /// Takes a snapshot of this node's state.
+    pub async fn snapshot(&self) -> Result<NodeSnapshot> {
+        let chunks = self
+            .dispatcher
+            .get_chunk_storage()
+            .await
+            .keys()?
+            .into_iter()
+            .map(|address| *address.name())
+            .collect();
+
+        let RegisterDataExchange(registers) = self
+            .dispatcher
+            .get_register_storage()
+            .await
+            .get_data_of(Prefix::default())
+            .await?;
+        let mut register_digests = BTreeMap::new();
+        for (name, ops) in registers {
+            // Ops may have been applied in a different order by each replica.
+            let op_digests = ops
+                .iter()
+                .map(|op| Ok(XorName::from_content(&serialise(op)?)))
+                .collect::<Result<BTreeSet<_>>>()?;
+            let _ = register_digests.insert(name, XorName::from_content(&serialise(&op_digests)?));
+        }
+
+        Ok(NodeSnapshot {
+            name: self.name().await,
+            is_elder: self.is_elder().await,
+            prefix: self.our_prefix().await,
+            section_key: *self.section_chain().await.last_key(),
+            elders: self
+                .our_elders()
+                .await
+                .iter()
+                .map(|peer| *peer.name())
+                .collect(),
+            adults: self
+                .our_adults()
+                .await
+                .iter()
+                .map(|peer| *peer.name())
+                .collect(),
+            chunks,
+            registers: register_digests,
+        })
+    }
+
+    /// Holds back all the messages this node sends until delivery is resumed.
+    pub async fn freeze_message_delivery(&self) {
+        self.dispatcher.set_delivery_frozen(true)
+    }
+
+    /// Sends all the messages held back since delivery was frozen, and any further ones.
+    pub async fn resume_message_delivery(&self) {
+        self.dispatcher.set_delivery_frozen(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NetworkSnapshot, NodeSnapshot};
+    use std::collections::{BTreeMap, BTreeSet};
+    use xor_name::{Prefix, XorName};
+
+    fn node_snapshot(section_key: bls::PublicKey, elders: BTreeSet<XorName>) -> NodeSnapshot {
+        NodeSnapshot {
+            name: XorName::random(),
+            is_elder: true,
+            prefix: Prefix::default(),
+            section_key,
+            elders,
+            adults: BTreeSet::new(),
+            chunks: BTreeSet::new(),
+            registers: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn convergence_and_differences() {
+        let section_key = bls::SecretKey::random().public_key();
+        let elders: BTreeSet<_> = (0..3).map(|_| XorName::random()).collect();
+
+        let mut snapshot = NetworkSnapshot::default();
+        for _ in 0..3 {
+            let node = node_snapshot(section_key, elders.clone());
+            let _ = snapshot.nodes.insert(node.name, node);
+        }
+        assert!(snapshot.is_converged());
+        assert!(snapshot.differences(&snapshot.clone()).is_empty());
+
+        let mut lagging = snapshot.clone();
+        let lagging_node = node_snapshot(bls::SecretKey::random().public_key(), elders);
+        let _ = lagging
+            .nodes
+            .insert(lagging_node.name, lagging_node.clone());
+        assert!(!lagging.is_converged());
+        assert_eq!(
+            snapshot.differences(&lagging),
+            std::iter::once(lagging_node.name).collect()
+        );
+    }
+}
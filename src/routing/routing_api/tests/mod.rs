@@ -12,9 +12,9 @@ use super::{Comm, Command, Core, Dispatcher};
 use crate::dbs::UsedSpace;
 use crate::messaging::{
     system::{
-        JoinAsRelocatedRequest, JoinRequest, JoinResponse, KeyedSig, MembershipState, NodeState,
-        Peer, Proposal, RelocateDetails, RelocatePayload, ResourceProofResponse, Section,
-        SectionAuth, SystemMsg,
+        JoinAsRelocatedRequest, JoinRejectionReason, JoinRequest, JoinResponse, KeyedSig,
+        MembershipState, NodeState, Peer, Proposal, RelocateDetails, RelocatePayload,
+        ResourceProofResponse, Section, SectionAuth, SectionPeersUpdate, SystemMsg,
     },
     AuthorityProof, DstLocation, MessageId, MessageType, MsgKind, NodeAuth,
     SectionAuth as MsgKindSectionAuth, SectionAuthorityProvider, WireMsg,
@@ -35,7 +35,7 @@ use crate::routing::{
         test_utils::*, ElderCandidatesUtils, NodeStateUtils, SectionKeyShare, SectionPeersUtils,
     },
     supermajority, Error, Event, Result as RoutingResult, SectionAuthorityProviderUtils,
-    ELDER_SIZE, FIRST_SECTION_MIN_AGE, MIN_ADULT_AGE, MIN_AGE,
+    CHUNK_COPY_COUNT, ELDER_SIZE, FIRST_SECTION_MIN_AGE, MAX_CLOCK_SKEW, MIN_ADULT_AGE, MIN_AGE,
 };
 use crate::types::{Keypair, PublicKey};
 use assert_matches::assert_matches;
@@ -87,10 +87,7 @@ async fn receive_join_request_without_resource_proof_response() -> Result<()> {
             name: XorName::from(PublicKey::Bls(section_key)),
             section_pk: section_key,
         },
-        SystemMsg::JoinRequest(Box::new(JoinRequest {
-            section_key,
-            resource_proof_response: None,
-        })),
+        SystemMsg::JoinRequest(Box::new(JoinRequest::new(section_key, None))),
         section_key,
     )?;
 
@@ -124,6 +121,71 @@ async fn receive_join_request_without_resource_proof_response() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn receive_join_request_with_skewed_clock() -> Result<()> {
+    let node = create_node(FIRST_SECTION_MIN_AGE, None);
+    let (used_space, root_storage_dir) = create_test_used_space_and_root_storage()?;
+    let core = Core::first_node(
+        create_comm().await?,
+        node,
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        used_space,
+        root_storage_dir,
+    )?;
+    let dispatcher = Dispatcher::new(core);
+
+    let new_node = Node::new(
+        ed25519::gen_keypair(&Prefix::default().range_inclusive(), FIRST_SECTION_MIN_AGE),
+        gen_addr(),
+    );
+    let section_key = *dispatcher.core.read().await.section().chain().last_key();
+
+    let mut join_request = JoinRequest::new(section_key, None);
+    join_request.timestamp -= 2 * MAX_CLOCK_SKEW.as_secs();
+
+    let wire_msg = WireMsg::single_src(
+        &new_node,
+        DstLocation::Section {
+            name: XorName::from(PublicKey::Bls(section_key)),
+            section_pk: section_key,
+        },
+        SystemMsg::JoinRequest(Box::new(join_request)),
+        section_key,
+    )?;
+
+    let mut commands = get_internal_commands(
+        Command::HandleMessage {
+            sender: new_node.addr,
+            wire_msg,
+            original_bytes: None,
+        },
+        &dispatcher,
+    )
+    .await?
+    .into_iter();
+
+    let response_wire_msg = assert_matches!(
+        commands.next(),
+        Some(Command::SendMessage {
+            wire_msg,
+            ..
+        }) => wire_msg
+    );
+
+    assert_matches!(
+        response_wire_msg.into_message(),
+        Ok(MessageType::System {
+            msg: SystemMsg::JoinResponse(response),
+            ..
+        }) => assert_matches!(
+            *response,
+            JoinResponse::Rejected(JoinRejectionReason::ClockSkew { .. })
+        )
+    );
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn receive_join_request_with_resource_proof_response() -> Result<()> {
     let node = create_node(FIRST_SECTION_MIN_AGE, None);
@@ -158,15 +220,15 @@ async fn receive_join_request_with_resource_proof_response() -> Result<()> {
             name: XorName::from(PublicKey::Bls(section_key)),
             section_pk: section_key,
         },
-        SystemMsg::JoinRequest(Box::new(JoinRequest {
+        SystemMsg::JoinRequest(Box::new(JoinRequest::new(
             section_key,
-            resource_proof_response: Some(ResourceProofResponse {
+            Some(ResourceProofResponse {
                 solution,
                 data,
                 nonce,
                 nonce_signature,
             }),
-        })),
+        ))),
         section_key,
     )?;
 
@@ -1128,7 +1190,7 @@ async fn ae_msg_from_the_future_is_handled() -> Result<()> {
         },
         SystemMsg::AntiEntropyUpdate {
             section_auth: new_section_auth,
-            members: Some(new_section.members().clone()),
+            members: Some(SectionPeersUpdate::Full(new_section.members().clone())),
             section_signed: new_section.section_auth.sig,
             proof_chain,
         },
@@ -1672,6 +1734,39 @@ async fn handle_demote_during_split() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn chunk_holders_follow_replication_factor() -> Result<()> {
+    let (event_tx, _) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
+    let (section_auth, mut nodes, sk_set) = create_section_auth();
+    let (mut section, section_key_share) = create_section(&sk_set, &section_auth)?;
+    for _ in 0..6 {
+        let node_state = NodeState::joined(create_peer(MIN_ADULT_AGE), None);
+        let _ = section.update_member(section_signed(sk_set.secret_key(), node_state)?);
+    }
+
+    let (used_space, root_storage_dir) = create_test_used_space_and_root_storage()?;
+    let mut core = Core::new(
+        create_comm().await?,
+        nodes.remove(0),
+        section,
+        Some(section_key_share),
+        event_tx,
+        used_space,
+        root_storage_dir,
+    )?;
+
+    let chunk_name = XorName::random();
+    assert_eq!(
+        core.get_chunk_holder_adults(&chunk_name).await.len(),
+        CHUNK_COPY_COUNT
+    );
+
+    core.set_replication_factor(2);
+    assert_eq!(core.get_chunk_holder_adults(&chunk_name).await.len(), 2);
+
+    Ok(())
+}
+
 fn create_peer(age: u8) -> Peer {
     let name = ed25519::gen_name_with_age(age);
     let mut peer = Peer::new(name, gen_addr());
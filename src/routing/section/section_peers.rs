@@ -11,7 +11,7 @@ use crate::messaging::{
     system::{MembershipState, NodeState, Peer, SectionAuth, SectionPeers},
     SectionAuthorityProvider,
 };
-use crate::routing::{peer::PeerUtils, SectionAuthorityProviderUtils};
+use crate::routing::{error::Result, peer::PeerUtils, SectionAuthorityProviderUtils};
 use itertools::Itertools;
 use std::{cmp::Ordering, collections::btree_map::Entry, mem};
 use xor_name::{Prefix, XorName};
@@ -60,6 +60,12 @@ pub(crate) trait SectionPeersUtils {
 
     /// Remove all members whose name does not match `prefix`.
     fn prune_not_matching(&mut self, prefix: &Prefix);
+
+    /// Digest identifying this set of members, and the state of each of them.
+    fn digest(&self) -> Result<XorName>;
+
+    /// Returns the members whose state differs from (or is missing in) `base`.
+    fn changes_since(&self, base: &SectionPeers) -> SectionPeers;
 }
 
 impl SectionPeersUtils for SectionPeers {
@@ -182,6 +188,23 @@ impl SectionPeersUtils for SectionPeers {
             .filter(|(name, _)| prefix.matches(name))
             .collect();
     }
+
+    /// Digest identifying this set of members, and the state of each of them.
+    fn digest(&self) -> Result<XorName> {
+        Ok(XorName::from_content(&bincode::serialize(&self.members)?))
+    }
+
+    /// Returns the members whose state differs from (or is missing in) `base`.
+    fn changes_since(&self, base: &SectionPeers) -> SectionPeers {
+        SectionPeers {
+            members: self
+                .members
+                .iter()
+                .filter(|(name, info)| base.members.get(name) != Some(info))
+                .map(|(name, info)| (*name, info.clone()))
+                .collect(),
+        }
+    }
 }
 
 // Returns the nodes that should become the next elders out of the given members, sorted by names.
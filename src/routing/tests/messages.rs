@@ -39,6 +39,7 @@ async fn test_messages_client_node() -> Result<()> {
     let auth = ServiceAuth {
         public_key: pk,
         signature: keypair.sign(b"the msg"),
+        delegation: None,
     };
     let id = MessageId::new();
 
@@ -0,0 +1,294 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::MAX_CHUNK_SIZE_IN_BYTES;
+use bytes::{Bytes, BytesMut};
+use lazy_static::lazy_static;
+use std::{
+    env,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Size of the pooled buffers: enough for a chunk of the max size, along with the message
+/// carrying it.
+pub const POOLED_BUFFER_SIZE: usize = MAX_CHUNK_SIZE_IN_BYTES + 64 * 1024;
+
+/// Data smaller than this isn't worth a pooled buffer.
+pub const MIN_POOLED_SIZE: usize = 64 * 1024;
+
+/// Memory the buffers of [`chunk_buffers`] may take up, unless set otherwise, in bytes, with
+/// [`set_chunk_buffers_memory_cap`] or the `SAFE_BUFFER_MEMORY_CAP` env var.
+pub const DEFAULT_BUFFER_MEMORY_CAP: usize = 256 * 1024 * 1024;
+
+const BUFFER_MEMORY_CAP_ENV: &str = "SAFE_BUFFER_MEMORY_CAP";
+
+// Memory cap set with `set_chunk_buffers_memory_cap`, or 0 if none was.
+static MEMORY_CAP: AtomicUsize = AtomicUsize::new(0);
+// Whether the pool was created, its cap being set for good.
+static POOL_CREATED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref CHUNK_BUFFERS: BufferPool = {
+        POOL_CREATED.store(true, Ordering::SeqCst);
+        BufferPool::new(match MEMORY_CAP.load(Ordering::SeqCst) {
+            0 => env::var(BUFFER_MEMORY_CAP_ENV)
+                .ok()
+                .and_then(|cap| cap.parse().ok())
+                .unwrap_or(DEFAULT_BUFFER_MEMORY_CAP),
+            cap => cap,
+        })
+    };
+}
+
+/// The pool of buffers chunks are serialised into, by the client and the node alike, to send
+/// them in messages.
+pub fn chunk_buffers() -> &'static BufferPool {
+    &CHUNK_BUFFERS
+}
+
+/// Sets the memory the buffers of [`chunk_buffers`] may take up, in bytes, over the
+/// `SAFE_BUFFER_MEMORY_CAP` env var. The pool is shared by the whole process, so this has to be
+/// done before it's first used, e.g. by the client's or node's config when they're started.
+/// Returns false, the cap being left as it was, if it's too late.
+pub fn set_chunk_buffers_memory_cap(memory_cap: usize) -> bool {
+    MEMORY_CAP.store(memory_cap.max(1), Ordering::SeqCst);
+    !POOL_CREATED.load(Ordering::SeqCst)
+}
+
+/// Use of a [`BufferPool`], as returned by [`BufferPool::stats`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BufferPoolStats {
+    /// Max number of buffers in use at any one time.
+    pub capacity: usize,
+    /// Buffers in use.
+    pub in_use: usize,
+    /// Buffers kept around for reuse.
+    pub idle: usize,
+    /// Buffers allocated, rather than reused.
+    pub allocated: u64,
+    /// Buffers which had to be waited for, as all were in use.
+    pub waited: u64,
+}
+
+/// A pool of chunk sized buffers, reused rather than allocated anew for each chunk, and capped
+/// in number so parallel transfers can't take up more memory than allowed: once all the
+/// buffers are in use, those acquiring one wait for one to be released.
+#[derive(Clone, Debug)]
+pub struct BufferPool {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    capacity: usize,
+    permits: Arc<Semaphore>,
+    idle: Mutex<Vec<BytesMut>>,
+    allocated: AtomicU64,
+    waited: AtomicU64,
+}
+
+impl BufferPool {
+    /// Creates a pool whose buffers take up at most `memory_cap` bytes, but for one buffer
+    /// being always available.
+    pub fn new(memory_cap: usize) -> Self {
+        let capacity = (memory_cap / POOLED_BUFFER_SIZE).max(1);
+        Self {
+            inner: Arc::new(Inner {
+                capacity,
+                permits: Arc::new(Semaphore::new(capacity)),
+                idle: Mutex::new(Vec::new()),
+                allocated: AtomicU64::new(0),
+                waited: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Acquires a buffer, waiting for one to be released if all are in use.
+    pub async fn acquire(&self) -> PooledBuffer {
+        if let Some(buffer) = self.try_acquire() {
+            return buffer;
+        }
+        let _ = self.inner.waited.fetch_add(1, Ordering::Relaxed);
+        trace!("All pooled buffers are in use, waiting for one to be released");
+        // The permits are never closed, but if they were, the buffer would just go uncapped.
+        let permit = self.inner.permits.clone().acquire_owned().await.ok();
+        self.take(permit)
+    }
+
+    /// Acquires a buffer, unless all are in use.
+    pub fn try_acquire(&self) -> Option<PooledBuffer> {
+        let permit = self.inner.permits.clone().try_acquire_owned().ok()?;
+        Some(self.take(Some(permit)))
+    }
+
+    /// Returns the use of the pool so far.
+    pub fn stats(&self) -> BufferPoolStats {
+        BufferPoolStats {
+            capacity: self.inner.capacity,
+            in_use: self.inner.capacity - self.inner.permits.available_permits(),
+            idle: self
+                .inner
+                .idle
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .len(),
+            allocated: self.inner.allocated.load(Ordering::Relaxed),
+            waited: self.inner.waited.load(Ordering::Relaxed),
+        }
+    }
+
+    fn take(&self, permit: Option<OwnedSemaphorePermit>) -> PooledBuffer {
+        let idle = self
+            .inner
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop();
+        let mut buffer = idle.unwrap_or_else(|| {
+            let _ = self.inner.allocated.fetch_add(1, Ordering::Relaxed);
+            BytesMut::with_capacity(POOLED_BUFFER_SIZE)
+        });
+        // Reclaims the memory of the bytes frozen out of it, if they've all been dropped.
+        buffer.reserve(POOLED_BUFFER_SIZE);
+        PooledBuffer {
+            buffer,
+            pool: self.inner.clone(),
+            _permit: permit,
+        }
+    }
+}
+
+/// A buffer from a [`BufferPool`], returned to it once dropped.
+#[derive(Debug)]
+pub struct PooledBuffer {
+    buffer: BytesMut,
+    pool: Arc<Inner>,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl PooledBuffer {
+    /// Freezes the content of the buffer, which is kept out of the pool until the bytes
+    /// returned are dropped.
+    pub fn freeze(mut self) -> PooledBytes {
+        let bytes = self.buffer.split().freeze();
+        PooledBytes {
+            bytes,
+            _buffer: Some(self),
+        }
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = BytesMut;
+
+    fn deref(&self) -> &BytesMut {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut BytesMut {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        let mut idle = self
+            .pool
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if idle.len() < self.pool.capacity {
+            idle.push(buffer);
+        }
+    }
+}
+
+/// Bytes frozen out of a [`PooledBuffer`], or small enough not to need one, holding on to the
+/// buffer until dropped.
+#[derive(Debug)]
+pub struct PooledBytes {
+    // Dropped first, so the buffer can reclaim its memory.
+    bytes: Bytes,
+    _buffer: Option<PooledBuffer>,
+}
+
+impl Deref for PooledBytes {
+    type Target = Bytes;
+
+    fn deref(&self) -> &Bytes {
+        &self.bytes
+    }
+}
+
+impl From<Bytes> for PooledBytes {
+    fn from(bytes: Bytes) -> Self {
+        Self {
+            bytes,
+            _buffer: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chunk_buffers, set_chunk_buffers_memory_cap, BufferPool, POOLED_BUFFER_SIZE};
+    use eyre::Result;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn buffers_are_capped_and_reused() -> Result<()> {
+        let pool = BufferPool::new(2 * POOLED_BUFFER_SIZE);
+        let mut first = pool.acquire().await;
+        first.extend_from_slice(b"chunk");
+        let bytes = first.freeze();
+        let second = pool.acquire().await;
+        assert!(pool.try_acquire().is_none());
+        assert_eq!(pool.stats().in_use, 2);
+
+        // Acquiring waits for a buffer to be released.
+        assert!(timeout(Duration::from_millis(100), pool.acquire())
+            .await
+            .is_err());
+        assert_eq!(&bytes[..], b"chunk");
+        drop(bytes);
+        let third = timeout(Duration::from_millis(100), pool.acquire()).await?;
+        assert!(third.is_empty());
+
+        drop(third);
+        drop(second);
+        let stats = pool.stats();
+        assert_eq!(stats.in_use, 0);
+        assert_eq!(stats.idle, 2);
+        assert_eq!(stats.allocated, 2);
+        assert_eq!(stats.waited, 1);
+
+        let reused = pool.acquire().await;
+        assert!(reused.is_empty());
+        assert!(reused.capacity() >= POOLED_BUFFER_SIZE);
+        assert_eq!(pool.stats().allocated, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn memory_cap_is_only_set_before_use() {
+        let _ = chunk_buffers();
+        assert!(!set_chunk_buffers_memory_cap(POOLED_BUFFER_SIZE));
+    }
+}
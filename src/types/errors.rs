@@ -45,6 +45,22 @@ pub enum Error {
     /// Serialization error
     #[error("Serialisation error: {0}")]
     Serialisation(String),
+    /// The JSON parsed is that of another type.
+    #[error("Expected the JSON of a {expected}, found that of a {found}")]
+    JsonTypeMismatch {
+        /// Type the JSON was parsed as.
+        expected: String,
+        /// Type named in the JSON.
+        found: String,
+    },
+    /// The JSON parsed is of a later version of the type's schema than the one known.
+    #[error("Version {version} of the JSON schema of {kind} is not supported")]
+    UnsupportedJsonVersion {
+        /// Type named in the JSON.
+        kind: String,
+        /// Version of the schema named in the JSON.
+        version: u32,
+    },
     /// Entry already exists. Contains the current entry Key.
     #[error("Entry already exists {0}")]
     EntryExists(u8),
@@ -79,6 +95,9 @@ pub enum Error {
     /// Failed signature validation.
     #[error("Invalid signature")]
     InvalidSignature,
+    /// A signer failed to sign, e.g. its signing device being unreachable.
+    #[error("Failed to sign: {0}")]
+    SigningFailed(String),
     /// While parsing, precision would be lost.
     #[error("Lost precision on the number of coins during parsing")]
     LossOfPrecision,
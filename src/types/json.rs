@@ -0,0 +1,259 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{
+    register::{
+        Address as RegisterAddress, Policy, PrivatePermissions, PrivatePolicy, PublicPermissions,
+        PublicPolicy, User,
+    },
+    ChunkAddress, Error, PaymentProof, PublicKey, Result,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Types with a canonical JSON representation, for web gateways and services not written in
+/// Rust to consume and produce them.
+///
+/// Values are wrapped in an envelope naming their type and the version of its schema, e.g.
+/// `{"type":"BlobAddress","version":1,"value":{"Public":[...]}}`. Fields are in the order
+/// they're declared in, and maps sorted by key, so the same value always has the same JSON.
+/// Maps JSON can't key by their keys, e.g. addresses or public keys, are lists of key-value
+/// pairs instead. JSON of a later version of the schema than the one known is refused, rather
+/// than misread.
+pub trait JsonRepr: Serialize + DeserializeOwned {
+    /// Name of the type in the envelope.
+    const JSON_TYPE: &'static str;
+    /// Version of the type's schema, bumped whenever its JSON changes.
+    const JSON_VERSION: u32 = 1;
+
+    /// Returns the canonical JSON of the value.
+    fn to_json(&self) -> Result<String> {
+        encode::<Self, _>(self)
+    }
+
+    /// Parses a value from its canonical JSON.
+    fn from_json(json: &str) -> Result<Self> {
+        decode::<Self, _>(json)
+    }
+}
+
+// Returns the JSON of `value` as the representation of a `T`.
+fn encode<T: JsonRepr, V: Serialize>(value: &V) -> Result<String> {
+    serde_json::to_string(&Envelope {
+        kind: T::JSON_TYPE.to_string(),
+        version: T::JSON_VERSION,
+        value,
+    })
+    .map_err(|err| Error::Serialisation(err.to_string()))
+}
+
+// Parses the representation of a `T` from its JSON.
+fn decode<T: JsonRepr, V: DeserializeOwned>(json: &str) -> Result<V> {
+    let envelope: Envelope<V> =
+        serde_json::from_str(json).map_err(|err| Error::Serialisation(err.to_string()))?;
+    if envelope.kind != T::JSON_TYPE {
+        return Err(Error::JsonTypeMismatch {
+            expected: T::JSON_TYPE.to_string(),
+            found: envelope.kind,
+        });
+    }
+    if envelope.version > T::JSON_VERSION {
+        return Err(Error::UnsupportedJsonVersion {
+            kind: envelope.kind,
+            version: envelope.version,
+        });
+    }
+    Ok(envelope.value)
+}
+
+/// (De)serialises a map as a sequence of key-value pairs, for maps whose keys JSON can't key
+/// maps by. Bincode encodes both alike, so the field's bincode is left as it was.
+pub(crate) mod map_entries {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    pub(crate) fn serialize<K, V, S>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub(crate) fn deserialize<'de, K, V, D>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct Envelope<T> {
+    #[serde(rename = "type")]
+    kind: String,
+    version: u32,
+    value: T,
+}
+
+impl JsonRepr for ChunkAddress {
+    const JSON_TYPE: &'static str = "ChunkAddress";
+}
+
+impl JsonRepr for RegisterAddress {
+    const JSON_TYPE: &'static str = "RegisterAddress";
+}
+
+impl JsonRepr for Policy {
+    const JSON_TYPE: &'static str = "RegisterPolicy";
+    const JSON_VERSION: u32 = 2;
+
+    // Policies' permissions are keyed by users and public keys. Their serialisation is left as
+    // it is, being that of Registers on the wire, so they're represented as `PolicyJson`.
+    fn to_json(&self) -> Result<String> {
+        encode::<Self, _>(&PolicyJson::from(self.clone()))
+    }
+
+    fn from_json(json: &str) -> Result<Self> {
+        Ok(decode::<Self, PolicyJson>(json)?.into())
+    }
+}
+
+// The JSON representation of `Policy`, as of version 2.
+#[derive(Deserialize, Serialize)]
+enum PolicyJson {
+    Public {
+        owner: PublicKey,
+        #[serde(with = "map_entries")]
+        permissions: BTreeMap<User, PublicPermissions>,
+    },
+    Private {
+        owner: PublicKey,
+        #[serde(with = "map_entries")]
+        permissions: BTreeMap<PublicKey, PrivatePermissions>,
+    },
+}
+
+impl From<Policy> for PolicyJson {
+    fn from(policy: Policy) -> Self {
+        match policy {
+            Policy::Public(PublicPolicy { owner, permissions }) => {
+                Self::Public { owner, permissions }
+            }
+            Policy::Private(PrivatePolicy { owner, permissions }) => {
+                Self::Private { owner, permissions }
+            }
+        }
+    }
+}
+
+impl From<PolicyJson> for Policy {
+    fn from(policy: PolicyJson) -> Self {
+        match policy {
+            PolicyJson::Public { owner, permissions } => {
+                Self::Public(PublicPolicy { owner, permissions })
+            }
+            PolicyJson::Private { owner, permissions } => {
+                Self::Private(PrivatePolicy { owner, permissions })
+            }
+        }
+    }
+}
+
+impl JsonRepr for PaymentProof {
+    const JSON_TYPE: &'static str = "PaymentProof";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonRepr;
+    use crate::types::{
+        register::{
+            Address as RegisterAddress, Policy, PrivatePermissions, PrivatePolicy,
+            PublicPermissions, PublicPolicy, User,
+        },
+        ChunkAddress, DataAddress, Error, Keypair, PaymentProof, Token,
+    };
+    use eyre::Result;
+    use std::fmt::Debug;
+    use xor_name::XorName;
+
+    fn assert_round_trip<T: JsonRepr + Debug + PartialEq>(value: &T) -> Result<()> {
+        let json = value.to_json()?;
+        assert_eq!(&T::from_json(&json)?, value);
+        assert_eq!(value.to_json()?, json);
+        Ok(())
+    }
+
+    #[test]
+    fn every_type_round_trips() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut rand::thread_rng());
+        let other = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+
+        assert_round_trip(&ChunkAddress(XorName::random()))?;
+        assert_round_trip(&RegisterAddress::Private {
+            name: XorName::random(),
+            tag: 15000,
+        })?;
+        assert_round_trip(&Policy::Public(PublicPolicy {
+            owner: keypair.public_key(),
+            permissions: vec![
+                (User::Anyone, PublicPermissions::new(false)),
+                (User::Key(other), PublicPermissions::new(true)),
+            ]
+            .into_iter()
+            .collect(),
+        }))?;
+        assert_round_trip(&Policy::Private(PrivatePolicy {
+            owner: keypair.public_key(),
+            permissions: vec![(other, PrivatePermissions::new(true, false))]
+                .into_iter()
+                .collect(),
+        }))?;
+        assert_round_trip(&PaymentProof::new(
+            DataAddress::Chunk(ChunkAddress(XorName::random())),
+            Token::from_nano(10),
+            &keypair,
+        )?)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn json_is_versioned_and_typed() -> Result<()> {
+        let address = RegisterAddress::Public {
+            name: XorName::random(),
+            tag: 15000,
+        };
+        let json = address.to_json()?;
+        assert!(json.starts_with(r#"{"type":"RegisterAddress","version":1,"value":"#));
+        assert_eq!(RegisterAddress::from_json(&json)?, address);
+        // The same value always has the same JSON.
+        assert_eq!(address.to_json()?, json);
+
+        let newer = json.replace(r#""version":1"#, r#""version":2"#);
+        assert!(matches!(
+            RegisterAddress::from_json(&newer),
+            Err(Error::UnsupportedJsonVersion { version: 2, .. })
+        ));
+
+        let other = json.replace("RegisterAddress", "PaymentProof");
+        assert!(matches!(
+            RegisterAddress::from_json(&other),
+            Err(Error::JsonTypeMismatch { .. })
+        ));
+
+        Ok(())
+    }
+}
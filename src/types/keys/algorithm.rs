@@ -0,0 +1,77 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Signature scheme of a key, keypair or signature.
+///
+/// Keys and signatures are serialised tagged with their scheme. New schemes, e.g. post-quantum
+/// ones such as Dilithium, are to be added as new variants after the existing ones, here and in
+/// the key and signature types, so the encoding of existing keys and signatures, and of the
+/// addresses derived from them, remains unchanged.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum KeyAlgorithm {
+    /// Ed25519.
+    Ed25519,
+    /// BLS.
+    Bls,
+    /// Share of a BLS threshold key.
+    BlsShare,
+}
+
+impl Display for KeyAlgorithm {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Ed25519 => write!(f, "Ed25519"),
+            Self::Bls => write!(f, "BLS"),
+            Self::BlsShare => write!(f, "BLS share"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyAlgorithm;
+    use crate::types::{utils, Keypair, PublicKey};
+    use eyre::Result;
+    use rand::rngs::OsRng;
+
+    // The tag each scheme is serialised with must never change,
+    // or keys stored with it couldn't be read anymore.
+    #[test]
+    fn serialisation_tags_are_stable() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut OsRng);
+        let bls_key = PublicKey::from(bls::SecretKey::random().public_key());
+        let bls_share_key = PublicKey::BlsShare(
+            bls::SecretKeySet::random(1, &mut rand::thread_rng())
+                .secret_key_share(0)
+                .public_key_share(),
+        );
+
+        for (key, tag) in [
+            (keypair.public_key(), 0u32),
+            (bls_key, 1),
+            (bls_share_key, 2),
+        ]
+        .iter()
+        {
+            let bytes = utils::serialise(key)?;
+            assert_eq!(bytes[..4], tag.to_le_bytes());
+            assert_eq!(utils::serialise(&key.algorithm())?[..4], tag.to_le_bytes());
+        }
+
+        let signature = keypair.sign(b"data");
+        assert_eq!(signature.algorithm(), KeyAlgorithm::Ed25519);
+        assert_eq!(keypair.algorithm(), KeyAlgorithm::Ed25519);
+
+        Ok(())
+    }
+}
@@ -14,7 +14,7 @@
 //! secret key.
 
 use super::super::{Error, Result};
-use super::super::{PublicKey, SecretKey, Signature, SignatureShare};
+use super::super::{KeyAlgorithm, PublicKey, SecretKey, Signature, SignatureShare};
 
 use bls::{self, serde_impl::SerdeSecret, PublicKeySet};
 use bytes::Bytes;
@@ -115,6 +115,7 @@ impl Signing for Keypair {
 }
 
 /// Wrapper for different keypair types.
+#[non_exhaustive]
 #[derive(Serialize, Deserialize, Clone, custom_debug::Debug)]
 pub enum Keypair {
     /// Ed25519 keypair.
@@ -161,6 +162,14 @@ impl Keypair {
         }))
     }
 
+    /// Returns the signature scheme of this keypair.
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        match self {
+            Self::Ed25519(_) => KeyAlgorithm::Ed25519,
+            Self::BlsShare(_) => KeyAlgorithm::BlsShare,
+        }
+    }
+
     /// Returns the public key associated with this keypair.
     pub fn public_key(&self) -> PublicKey {
         match self {
@@ -13,8 +13,10 @@
 //! `new` functions. A `PublicKey` can't be generated by itself; it must always be derived from a
 //! secret key.
 
+pub(super) mod algorithm;
 pub(super) mod keypair;
 pub(super) mod node_keypairs;
 pub(super) mod public_key;
 pub(super) mod secret_key;
 pub(super) mod signature;
+pub(super) mod signer;
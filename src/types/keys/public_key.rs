@@ -14,7 +14,7 @@
 //! secret key.
 
 use super::super::{utils, Error, Result};
-use super::super::{Keypair, Signature};
+use super::super::{KeyAlgorithm, Keypair, Signature};
 
 use hex_fmt::HexFmt;
 use serde::{Deserialize, Serialize};
@@ -28,6 +28,7 @@ use std::{
 use xor_name::{XorName, XOR_NAME_LEN};
 
 /// Wrapper for different public key types.
+#[non_exhaustive]
 #[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize, custom_debug::Debug)]
 pub enum PublicKey {
     /// Ed25519 public key.
@@ -88,6 +89,15 @@ impl PublicKey {
         Ok(Self::from(pk))
     }
 
+    /// Returns the signature scheme of this key.
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        match self {
+            Self::Ed25519(_) => KeyAlgorithm::Ed25519,
+            Self::Bls(_) => KeyAlgorithm::Bls,
+            Self::BlsShare(_) => KeyAlgorithm::BlsShare,
+        }
+    }
+
     /// Returns the bytes of the underlying public key
     pub fn to_bytes(self) -> Vec<u8> {
         match self {
@@ -13,12 +13,13 @@
 //! `new` functions. A `PublicKey` can't be generated by itself; it must always be derived from a
 //! secret key.
 
-use super::super::{Error, Result};
+use super::super::{Error, KeyAlgorithm, Result};
 use bls::{self, serde_impl::SerdeSecret};
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Debug, Display, Formatter};
 // TODO: remove clones. We need to restructure to hold keypair ones and only require references for this.
 /// Wrapper for different secret key types.
+#[non_exhaustive]
 #[derive(Debug, Serialize, Deserialize)]
 pub enum SecretKey {
     /// Ed25519 secretkey.
@@ -28,6 +29,14 @@ pub enum SecretKey {
 }
 
 impl SecretKey {
+    /// Returns the signature scheme of this key.
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        match self {
+            Self::Ed25519(_) => KeyAlgorithm::Ed25519,
+            Self::BlsShare(_) => KeyAlgorithm::BlsShare,
+        }
+    }
+
     /// Construct a secret key from a hex string
     ///
     /// Similar to public key, it is often useful in user
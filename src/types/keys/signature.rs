@@ -13,6 +13,7 @@
 //! `new` functions. A `PublicKey` can't be generated by itself; it must always be derived from a
 //! secret key.
 
+use super::super::KeyAlgorithm;
 use hex_fmt::HexFmt;
 use serde::{Deserialize, Serialize};
 use std::{fmt, hash::Hash};
@@ -27,6 +28,7 @@ pub struct SignatureShare {
 }
 
 /// Wrapper for different signature types.
+#[non_exhaustive]
 #[derive(Clone, Eq, PartialEq, Serialize, Deserialize, custom_debug::Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum Signature {
@@ -43,6 +45,15 @@ pub enum Signature {
 }
 
 impl Signature {
+    /// Returns the signature scheme this signature was made with.
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        match self {
+            Self::Ed25519(_) => KeyAlgorithm::Ed25519,
+            Self::Bls(_) => KeyAlgorithm::Bls,
+            Self::BlsShare(_) => KeyAlgorithm::BlsShare,
+        }
+    }
+
     /// Returns bls::Signature if Self is a BLS variant.
     pub fn into_bls(self) -> Option<bls::Signature> {
         match self {
@@ -0,0 +1,76 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::super::{Keypair, PublicKey, Result, Signature};
+use std::fmt::Debug;
+
+/// Signs data with a key, whose secret may be held outside of the process, e.g. by a hardware
+/// security module or a smart card attached to the machine.
+///
+/// Signing is synchronous, as every command and query is signed on its way out, so it suits
+/// devices which sign within milliseconds. Remote signing services, which take a network round
+/// trip or wait for a user's approval, would hold up the async runtime, and are better used to
+/// grant the client a [`Delegation`] instead.
+///
+/// [`Keypair`] is the default implementation, signing with a secret key held in memory.
+///
+/// [`Delegation`]: crate::messaging::Delegation
+pub trait Signer: Debug + Send + Sync {
+    /// The public key the signatures are verified with.
+    fn public_key(&self) -> PublicKey;
+
+    /// Signs `data`, failing with [`Error::SigningFailed`] if the signature couldn't be made,
+    /// e.g. the signing device being unreachable.
+    ///
+    /// Signatures are made on the threads of the async runtime, so implementations shouldn't
+    /// block for long.
+    ///
+    /// [`Error::SigningFailed`]: super::super::Error::SigningFailed
+    fn sign(&self, data: &[u8]) -> Result<Signature>;
+
+    /// The keypair signatures are made with, if its secret key is held in memory by this
+    /// process, as it's the case of a [`Keypair`].
+    fn as_keypair(&self) -> Option<&Keypair> {
+        None
+    }
+}
+
+impl Signer for Keypair {
+    fn public_key(&self) -> PublicKey {
+        Keypair::public_key(self)
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Signature> {
+        Ok(Keypair::sign(self, data))
+    }
+
+    fn as_keypair(&self) -> Option<&Keypair> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Signer;
+    use crate::types::{Keypair, Result};
+
+    #[test]
+    fn keypairs_sign_as_signers() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut rand::thread_rng());
+        let signer: &dyn Signer = &keypair;
+
+        let signature = signer.sign(b"data")?;
+        assert_eq!(signer.public_key(), keypair.public_key());
+        signer.public_key().verify(&signature, b"data")?;
+        assert!(signer.public_key().verify(&signature, b"other").is_err());
+        assert_eq!(signer.as_keypair(), Some(&keypair));
+
+        Ok(())
+    }
+}
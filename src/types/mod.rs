@@ -14,22 +14,34 @@ pub mod register;
 /// Encoding utils
 pub mod utils;
 
+mod buffer_pool;
 mod cache;
 mod chunk;
 mod errors;
+mod json;
 mod keys;
+mod payment;
 mod token;
 
+pub use buffer_pool::{
+    chunk_buffers, set_chunk_buffers_memory_cap, BufferPool, BufferPoolStats, PooledBuffer,
+    PooledBytes, DEFAULT_BUFFER_MEMORY_CAP, MIN_POOLED_SIZE, POOLED_BUFFER_SIZE,
+};
 pub use cache::Cache;
 pub use chunk::{Address as ChunkAddress, Chunk, MAX_CHUNK_SIZE_IN_BYTES};
 pub use errors::{convert_dt_error_to_error_message, Error, Result};
+pub(crate) use json::map_entries;
+pub use json::JsonRepr;
 pub use keys::{
+    algorithm::KeyAlgorithm,
     keypair::{BlsKeypairShare, Encryption, Keypair, OwnerType, Signing},
     node_keypairs::NodeKeypairs,
     public_key::PublicKey,
     secret_key::SecretKey,
     signature::{Signature, SignatureShare},
+    signer::Signer,
 };
+pub use payment::PaymentProof;
 pub use register::Address as RegisterAddress;
 pub use token::Token;
 
@@ -44,3 +56,18 @@ pub enum DataAddress {
     /// Register Address
     Register(RegisterAddress),
 }
+
+impl DataAddress {
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        match self {
+            Self::Chunk(address) => address.name(),
+            Self::Register(address) => address.name(),
+        }
+    }
+
+    /// Returns the Address serialised and encoded in z-base-32.
+    pub fn encode_to_zbase32(&self) -> Result<String> {
+        utils::encode(&self)
+    }
+}
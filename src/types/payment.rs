@@ -0,0 +1,69 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{utils, DataAddress, PublicKey, Result, Signature, Signer, Token};
+use serde::{Deserialize, Serialize};
+
+/// Proof that storing data at a given address was paid for,
+/// signed by the payer, who is considered the owner of the data.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct PaymentProof {
+    /// Address of the data that was paid for.
+    pub address: DataAddress,
+    /// Amount paid to store the data.
+    pub amount: Token,
+    /// Key of the payer.
+    pub payer: PublicKey,
+    /// Payer's signature over the address and amount.
+    pub signature: Signature,
+}
+
+impl PaymentProof {
+    /// Creates a proof of payment for storing data at `address`, signed by `signer`.
+    pub fn new(address: DataAddress, amount: Token, signer: &dyn Signer) -> Result<Self> {
+        let bytes = Self::bytes_to_sign(&address, amount)?;
+        Ok(Self {
+            address,
+            amount,
+            payer: signer.public_key(),
+            signature: signer.sign(&bytes)?,
+        })
+    }
+
+    /// Verifies the payer's signature.
+    pub fn verify(&self) -> Result<()> {
+        let bytes = Self::bytes_to_sign(&self.address, self.amount)?;
+        self.payer.verify(&self.signature, bytes)
+    }
+
+    fn bytes_to_sign(address: &DataAddress, amount: Token) -> Result<Vec<u8>> {
+        utils::serialise(&(address, amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PaymentProof;
+    use crate::types::{ChunkAddress, DataAddress, Keypair, Result, Token};
+    use xor_name::XorName;
+
+    #[test]
+    fn proof_is_verified_against_its_content() -> Result<()> {
+        let keypair = Keypair::new_ed25519(&mut rand::thread_rng());
+        let address = DataAddress::Chunk(ChunkAddress(XorName::random()));
+
+        let mut proof = PaymentProof::new(address, Token::from_nano(10), &keypair)?;
+        assert!(proof.verify().is_ok());
+
+        proof.amount = Token::from_nano(1000);
+        assert!(proof.verify().is_err());
+
+        Ok(())
+    }
+}
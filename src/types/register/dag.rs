@@ -0,0 +1,141 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::{Entry, EntryHash};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An entry of a Register, as a node of the graph of its history.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DagEntry {
+    /// Hash of the entry.
+    pub hash: EntryHash,
+    /// The entry.
+    pub entry: Entry,
+    /// The entries it superseded when written, i.e. those it causally follows.
+    pub children: BTreeSet<EntryHash>,
+    /// Its version: the number of entries on its longest path back to a first one.
+    pub version: u64,
+}
+
+/// The history of a Register as a graph, each entry pointing to the entries it superseded, so
+/// applications can show users how concurrent writes diverged, rather than just the latest
+/// entries. Registers don't record who wrote each entry: applications needing it store it in
+/// their entries.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegisterDag {
+    entries: BTreeMap<EntryHash, DagEntry>,
+    heads: BTreeSet<EntryHash>,
+}
+
+impl RegisterDag {
+    pub(super) fn new(entries: BTreeMap<EntryHash, DagEntry>, heads: BTreeSet<EntryHash>) -> Self {
+        Self { entries, heads }
+    }
+
+    /// Returns the entry with the given hash, if any.
+    pub fn get(&self, hash: &EntryHash) -> Option<&DagEntry> {
+        self.entries.get(hash)
+    }
+
+    /// All the entries, oldest first, those of the same version in order of hash.
+    pub fn entries(&self) -> Vec<&DagEntry> {
+        let mut entries: Vec<_> = self.entries.values().collect();
+        entries.sort_by_key(|entry| (entry.version, entry.hash));
+        entries
+    }
+
+    /// The latest entries, several of them if the Register was written to concurrently.
+    pub fn heads(&self) -> &BTreeSet<EntryHash> {
+        &self.heads
+    }
+
+    /// Whether the Register was written to concurrently, with no entry superseding all the
+    /// concurrent ones since.
+    pub fn has_conflicts(&self) -> bool {
+        self.heads.len() > 1
+    }
+
+    /// The entries the entry with the given hash follows, directly or not.
+    pub fn ancestors(&self, hash: &EntryHash) -> BTreeSet<EntryHash> {
+        let mut ancestors = BTreeSet::new();
+        let mut to_visit: Vec<_> = self
+            .get(hash)
+            .map(|entry| entry.children.iter().copied().collect())
+            .unwrap_or_default();
+        while let Some(hash) = to_visit.pop() {
+            if ancestors.insert(hash) {
+                if let Some(entry) = self.get(&hash) {
+                    to_visit.extend(entry.children.iter().copied());
+                }
+            }
+        }
+        ancestors
+    }
+
+    /// Whether the entry `before` was known to the writer of the entry `after`.
+    pub fn happened_before(&self, before: &EntryHash, after: &EntryHash) -> bool {
+        self.ancestors(after).contains(before)
+    }
+
+    /// Whether the two entries were written without either writer knowing of the other entry.
+    pub fn are_concurrent(&self, lhs: &EntryHash, rhs: &EntryHash) -> bool {
+        lhs != rhs && !self.happened_before(lhs, rhs) && !self.happened_before(rhs, lhs)
+    }
+
+    /// The latest entries all the heads follow, where the branches forked, none if the heads
+    /// have no entry in common, or there's a single head.
+    pub fn fork_points(&self) -> BTreeSet<EntryHash> {
+        if !self.has_conflicts() {
+            return BTreeSet::new();
+        }
+        let common = self.common_history();
+        let superseded: BTreeSet<_> = common
+            .iter()
+            .flat_map(|hash| self.ancestors(hash))
+            .collect();
+        common.difference(&superseded).copied().collect()
+    }
+
+    /// The entries of each branch since the fork points, oldest first, by the head of the
+    /// branch. A single head has no branch of its own: its entries are empty.
+    pub fn branches(&self) -> BTreeMap<EntryHash, Vec<&DagEntry>> {
+        let common = self.common_history();
+        self.heads
+            .iter()
+            .map(|head| {
+                let mut branch: Vec<_> = self
+                    .history(head)
+                    .difference(&common)
+                    .filter_map(|hash| self.get(hash))
+                    .collect();
+                branch.sort_by_key(|entry| (entry.version, entry.hash));
+                (*head, branch)
+            })
+            .collect()
+    }
+
+    // The entries all the heads follow, the heads themselves included.
+    fn common_history(&self) -> BTreeSet<EntryHash> {
+        let mut heads = self.heads.iter();
+        let first = match heads.next() {
+            Some(head) => self.history(head),
+            None => return BTreeSet::new(),
+        };
+        heads.fold(first, |common, head| {
+            common.intersection(&self.history(head)).copied().collect()
+        })
+    }
+
+    // The entry with its ancestors.
+    fn history(&self, hash: &EntryHash) -> BTreeSet<EntryHash> {
+        let mut history = self.ancestors(hash);
+        let _ = history.insert(*hash);
+        history
+    }
+}
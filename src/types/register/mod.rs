@@ -7,11 +7,15 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+mod dag;
 mod metadata;
 mod policy;
 mod reg_crdt;
+mod template;
+mod transfer;
 
 use super::{Error, PublicKey, Result};
+pub use dag::{DagEntry, RegisterDag};
 pub use metadata::{Action, Address, Entry, Kind};
 pub use policy::{
     Permissions, Policy, PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, User,
@@ -23,6 +27,8 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     hash::Hash,
 };
+pub use template::{PolicyIssue, PolicyTemplate};
+pub use transfer::OwnershipTransfer;
 use xor_name::XorName;
 
 /// Register mutation operation to apply to Register.
@@ -34,6 +40,7 @@ pub struct Register {
     authority: PublicKey,
     crdt: RegisterCrdt,
     policy: Policy,
+    policy_history: Vec<Policy>,
 }
 
 impl Register {
@@ -57,6 +64,7 @@ impl Register {
             authority,
             crdt: RegisterCrdt::new(Address::Public { name, tag }),
             policy: policy.into(),
+            policy_history: Vec::new(),
         }
     }
 
@@ -80,6 +88,7 @@ impl Register {
             authority,
             crdt: RegisterCrdt::new(Address::Private { name, tag }),
             policy: policy.into(),
+            policy_history: Vec::new(),
         }
     }
 
@@ -133,6 +142,32 @@ impl Register {
         Ok(self.crdt.get(hash))
     }
 
+    /// Return the entry at the provided `version` of the history, if present.
+    /// The first entry written to the register is version 0.
+    pub fn get_by_version(
+        &self,
+        version: u64,
+        requester: Option<PublicKey>,
+    ) -> Result<Option<(EntryHash, &Entry)>> {
+        self.check_permissions(Action::Read, requester)?;
+
+        Ok(self.crdt.get_by_version(version))
+    }
+
+    /// Return the version of the history the entry with the provided 'hash' is at, if present.
+    pub fn version(&self, hash: EntryHash, requester: Option<PublicKey>) -> Result<Option<u64>> {
+        self.check_permissions(Action::Read, requester)?;
+
+        Ok(self.crdt.version(hash))
+    }
+
+    /// Return the graph of the history of the register, to show how its branches diverged.
+    pub fn dag(&self, requester: Option<PublicKey>) -> Result<RegisterDag> {
+        self.check_permissions(Action::Read, requester)?;
+
+        Ok(self.crdt.dag())
+    }
+
     /// Read the last entry, or entries when there are branches, if the register is not empty.
     pub fn read(&self, requester: Option<PublicKey>) -> Result<BTreeSet<(EntryHash, Entry)>> {
         self.check_permissions(Action::Read, requester)?;
@@ -174,6 +209,33 @@ impl Register {
         Ok(&self.policy)
     }
 
+    /// Return the policies the register had before the current one, oldest first.
+    pub fn policy_history(&self, requester: Option<PublicKey>) -> Result<&[Policy]> {
+        self.check_permissions(Action::Read, requester)?;
+
+        Ok(&self.policy_history)
+    }
+
+    /// Apply a transfer of the ownership of the register, signed by its current owner.
+    /// The policy being replaced is recorded in the policy history.
+    pub fn transfer_ownership(&mut self, transfer: &OwnershipTransfer) -> Result<()> {
+        if transfer.address != *self.address() {
+            return Err(Error::CrdtWrongAddress(transfer.address));
+        }
+        if transfer.previous_owner != self.owner() {
+            return Err(Error::AccessDenied(transfer.previous_owner));
+        }
+        if transfer.index != self.policy_history.len() as u64 {
+            return Err(Error::InvalidOperation);
+        }
+        transfer.verify()?;
+
+        self.policy_history.push(self.policy.clone());
+        self.policy.set_owner(transfer.new_owner);
+
+        Ok(())
+    }
+
     /// Helper to check permissions for given `action`
     /// for the given requester's public key.
     ///
@@ -200,8 +262,8 @@ impl Register {
 mod tests {
     use super::super::{
         register::{
-            Address, Entry, EntryHash, Kind, Permissions, PrivatePermissions, PrivatePolicy,
-            PublicPermissions, PublicPolicy, Register, RegisterOp, User,
+            Address, Entry, EntryHash, Kind, OwnershipTransfer, Permissions, PrivatePermissions,
+            PrivatePolicy, PublicPermissions, PublicPolicy, Register, RegisterOp, User,
         },
         utils, Error, Keypair, Result,
     };
@@ -357,6 +419,102 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn register_get_by_version() -> eyre::Result<()> {
+        let (_, register) = &mut create_public_reg_replicas(1)[0];
+
+        let entry1 = random_url()?;
+        let entry2 = random_url()?;
+        let entry3 = random_url()?;
+
+        let (entry1_hash, _) = register.write(entry1.clone(), BTreeSet::new())?;
+        let (entry2_hash, _) =
+            register.write(entry2.clone(), vec![entry1_hash].into_iter().collect())?;
+        let (entry3_hash, _) =
+            register.write(entry3.clone(), vec![entry2_hash].into_iter().collect())?;
+
+        assert_eq!(
+            register.get_by_version(0, None)?,
+            Some((entry1_hash, &entry1))
+        );
+        assert_eq!(
+            register.get_by_version(1, None)?,
+            Some((entry2_hash, &entry2))
+        );
+        assert_eq!(
+            register.get_by_version(2, None)?,
+            Some((entry3_hash, &entry3))
+        );
+        assert!(register.get_by_version(3, None)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_dag_shows_branches() -> eyre::Result<()> {
+        let (_, register) = &mut create_public_reg_replicas(1)[0];
+
+        let (root, _) = register.write(random_url()?, BTreeSet::new())?;
+        let (fork, _) = register.write(random_url()?, vec![root].into_iter().collect())?;
+        assert!(!register.dag(None)?.has_conflicts());
+
+        let (lhs, _) = register.write(random_url()?, vec![fork].into_iter().collect())?;
+        let (lhs_head, _) = register.write(random_url()?, vec![lhs].into_iter().collect())?;
+        let (rhs_head, _) = register.write(random_url()?, vec![fork].into_iter().collect())?;
+
+        let dag = register.dag(None)?;
+        assert!(dag.has_conflicts());
+        assert_eq!(dag.entries().len(), 5);
+        assert_eq!(dag.entries()[0].hash, root);
+        assert_eq!(dag.get(&lhs_head).map(|entry| entry.version), Some(3));
+        assert_eq!(dag.fork_points(), vec![fork].into_iter().collect());
+        assert!(dag.happened_before(&root, &lhs_head));
+        assert!(dag.are_concurrent(&lhs, &rhs_head));
+        assert!(!dag.are_concurrent(&fork, &rhs_head));
+
+        let branches = dag.branches();
+        let hashes =
+            |head| -> Vec<EntryHash> { branches[&head].iter().map(|entry| entry.hash).collect() };
+        assert_eq!(hashes(lhs_head), vec![lhs, lhs_head]);
+        assert_eq!(hashes(rhs_head), vec![rhs_head]);
+
+        // Superseding both heads merges the branches.
+        let heads = dag.heads().clone();
+        let _ = register.write(random_url()?, heads)?;
+        let dag = register.dag(None)?;
+        assert!(!dag.has_conflicts());
+        assert!(dag.fork_points().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_ownership_transfer() -> eyre::Result<()> {
+        let owner = Keypair::new_ed25519(&mut OsRng);
+        let new_owner = Keypair::new_ed25519(&mut OsRng);
+        let mut register =
+            create_public_reg_replica_with(XorName::random(), 43_000, Some(owner.clone()), None);
+        let address = *register.address();
+
+        // Only the current owner can hand the register over.
+        let forged = OwnershipTransfer::new(address, 0, new_owner.public_key(), &new_owner)?;
+        check_op_not_allowed_failure(register.transfer_ownership(&forged))?;
+
+        let transfer = OwnershipTransfer::new(address, 0, new_owner.public_key(), &owner)?;
+        register.transfer_ownership(&transfer)?;
+        assert_eq!(register.owner(), new_owner.public_key());
+        assert_eq!(register.policy_history(None)?.len(), 1);
+        assert_eq!(
+            register.policy_history(None)?[0].owner(),
+            &owner.public_key()
+        );
+
+        // The same transfer can't be replayed.
+        assert!(register.transfer_ownership(&transfer).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn register_query_public_policy() -> eyre::Result<()> {
         let register_name = XorName::random();
@@ -45,6 +45,14 @@ impl Policy {
             Policy::Private(policy) => policy.owner(),
         }
     }
+
+    /// Sets the owner.
+    pub(super) fn set_owner(&mut self, owner: PublicKey) {
+        match self {
+            Policy::Public(policy) => policy.owner = owner,
+            Policy::Private(policy) => policy.owner = owner,
+        }
+    }
 }
 
 impl From<PrivatePolicy> for Policy {
@@ -10,6 +10,7 @@
 use super::super::{
     Signature, {utils, Error, PublicKey, Result},
 };
+use super::dag::{DagEntry, RegisterDag};
 use super::metadata::{Address, Entry};
 pub use crdts::merkle_reg::Hash as EntryHash;
 use crdts::{
@@ -18,7 +19,7 @@ use crdts::{
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Display},
     hash::Hash,
 };
@@ -139,4 +140,82 @@ impl RegisterCrdt {
             .map(|(hash, node)| (hash, node.value.clone()))
             .collect()
     }
+
+    /// Get the graph of the history, from the current entries back to the first ones.
+    /// Entries received ahead of those they supersede aren't part of it until those are.
+    pub(super) fn dag(&self) -> RegisterDag {
+        let heads: BTreeSet<_> = self
+            .data
+            .read()
+            .hashes_and_nodes()
+            .map(|(hash, _)| hash)
+            .collect();
+
+        let mut versions = BTreeMap::new();
+        let mut entries = BTreeMap::new();
+        let mut to_visit: Vec<_> = heads.iter().copied().collect();
+        while let Some(hash) = to_visit.pop() {
+            if entries.contains_key(&hash) {
+                continue;
+            }
+            if let Some(node) = self.data.node(hash) {
+                to_visit.extend(node.children.iter().copied());
+                let entry = DagEntry {
+                    hash,
+                    entry: node.value.clone(),
+                    children: node.children.clone(),
+                    version: self.version_of(hash, &mut versions),
+                };
+                let _ = entries.insert(hash, entry);
+            }
+        }
+
+        RegisterDag::new(entries, heads)
+    }
+
+    /// Get the entry at the given version of the history, i.e. the entry preceded by
+    /// `version` entries on its longest path back to the first one (version 0).
+    /// If concurrent writes left several entries at that version, the one with the
+    /// lowest hash is returned, so that all replicas resolve it the same way.
+    pub(super) fn get_by_version(&self, version: u64) -> Option<(EntryHash, &Entry)> {
+        let mut versions = BTreeMap::new();
+        for hash in self.data.read().hashes() {
+            let _ = self.version_of(hash, &mut versions);
+        }
+
+        versions
+            .into_iter()
+            .find(|(_, entry_version)| *entry_version == version)
+            .and_then(|(hash, _)| self.get(hash).map(|entry| (hash, entry)))
+    }
+
+    /// Get the version of the entry with the given hash in the history, if present.
+    pub(super) fn version(&self, hash: EntryHash) -> Option<u64> {
+        if self.get(hash).is_none() {
+            return None;
+        }
+        Some(self.version_of(hash, &mut BTreeMap::new()))
+    }
+
+    // Computes the version of the entry with the given hash, memoising
+    // the versions of all its ancestors along the way.
+    fn version_of(&self, hash: EntryHash, versions: &mut BTreeMap<EntryHash, u64>) -> u64 {
+        if let Some(version) = versions.get(&hash) {
+            return *version;
+        }
+
+        let children = self
+            .data
+            .node(hash)
+            .map(|node| node.children.clone())
+            .unwrap_or_default();
+        let version = children
+            .into_iter()
+            .map(|child| self.version_of(child, versions) + 1)
+            .max()
+            .unwrap_or(0);
+
+        let _ = versions.insert(hash, version);
+        version
+    }
 }
@@ -0,0 +1,382 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::super::PublicKey;
+use super::{
+    Action, Policy, PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, User,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+};
+
+/// Commonly used Register policies, to build them without setting up permissions by hand.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum PolicyTemplate {
+    /// A private Register only its owner can read and write to.
+    OwnerOnly,
+    /// A public Register anyone can read, but only its owner can write to.
+    PublicReadOnly,
+    /// A public Register anyone can read and append entries to.
+    PublicAppend,
+    /// A public Register anyone can read, but only its owner and the given keys can write to.
+    AllowlistedWriters(BTreeSet<PublicKey>),
+}
+
+impl PolicyTemplate {
+    /// Builds the policy of a Register owned by `owner`, as per the template.
+    pub fn policy(&self, owner: PublicKey) -> Policy {
+        match self {
+            Self::OwnerOnly => PrivatePolicy {
+                owner,
+                permissions: BTreeMap::new(),
+            }
+            .into(),
+            Self::PublicReadOnly => PublicPolicy {
+                owner,
+                permissions: BTreeMap::new(),
+            }
+            .into(),
+            Self::PublicAppend => PublicPolicy {
+                owner,
+                permissions: vec![(User::Anyone, PublicPermissions::new(true))]
+                    .into_iter()
+                    .collect(),
+            }
+            .into(),
+            Self::AllowlistedWriters(writers) => PublicPolicy {
+                owner,
+                permissions: writers
+                    .iter()
+                    .filter(|writer| **writer != owner)
+                    .map(|writer| (User::Key(*writer), PublicPermissions::new(true)))
+                    .collect(),
+            }
+            .into(),
+        }
+    }
+
+    /// Returns the template `policy` is equivalent to, if any, regardless of permissions
+    /// which make no difference, e.g. those given to the owner.
+    pub fn of(policy: &Policy) -> Option<Self> {
+        match policy {
+            Policy::Private(policy) => {
+                let shared = policy
+                    .permissions
+                    .iter()
+                    .any(|(key, perms)| *key != policy.owner && *perms != no_private_access());
+                if shared {
+                    None
+                } else {
+                    Some(Self::OwnerOnly)
+                }
+            }
+            Policy::Public(policy) => {
+                let (granted, denied) = public_writers(policy);
+                if anyone_can_write(policy) {
+                    if denied.is_empty() {
+                        Some(Self::PublicAppend)
+                    } else {
+                        None
+                    }
+                } else if granted.is_empty() {
+                    Some(Self::PublicReadOnly)
+                } else {
+                    Some(Self::AllowlistedWriters(granted))
+                }
+            }
+        }
+    }
+}
+
+impl Display for PolicyTemplate {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::OwnerOnly => write!(formatter, "owner-only"),
+            Self::PublicReadOnly => write!(formatter, "public read-only"),
+            Self::PublicAppend => write!(formatter, "public append"),
+            Self::AllowlistedWriters(writers) => {
+                write!(formatter, "allowlisted writers ({})", writers.len())
+            }
+        }
+    }
+}
+
+/// Something about a Register policy likely not to be what was meant, as found by
+/// [`Policy::validate`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum PolicyIssue {
+    /// The owner is given permissions, which make no difference as it's allowed everything.
+    OwnerPermissions,
+    /// The user is given the same permissions as if they weren't listed at all.
+    Redundant(User),
+    /// The user is allowed to write to a private Register, but not to read it.
+    WriteOnly(PublicKey),
+}
+
+impl Display for PolicyIssue {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::OwnerPermissions => write!(
+                formatter,
+                "the owner is given permissions, but it's always allowed to read and write"
+            ),
+            Self::Redundant(User::Anyone) => write!(
+                formatter,
+                "permissions for anyone are listed, but make no difference"
+            ),
+            Self::Redundant(User::Key(key)) => write!(
+                formatter,
+                "permissions for {} are listed, but make no difference",
+                key
+            ),
+            Self::WriteOnly(key) => write!(
+                formatter,
+                "{} is allowed to write, but not to read what's written",
+                key
+            ),
+        }
+    }
+}
+
+impl Policy {
+    /// Checks the policy for permissions likely not to be what was meant, e.g. ones which
+    /// make no difference. None of them are errors: the policy is valid as far as the network
+    /// is concerned.
+    pub fn validate(&self) -> Vec<PolicyIssue> {
+        let mut issues = vec![];
+        match self {
+            Policy::Private(policy) => {
+                for (key, perms) in &policy.permissions {
+                    if *key == policy.owner {
+                        issues.push(PolicyIssue::OwnerPermissions);
+                    } else if *perms == no_private_access() {
+                        issues.push(PolicyIssue::Redundant(User::Key(*key)));
+                    } else if *perms == PrivatePermissions::new(false, true) {
+                        issues.push(PolicyIssue::WriteOnly(*key));
+                    }
+                }
+            }
+            Policy::Public(policy) => {
+                let anyone_can_write = anyone_can_write(policy);
+                for (user, perms) in &policy.permissions {
+                    match user {
+                        User::Key(key) if *key == policy.owner => {
+                            issues.push(PolicyIssue::OwnerPermissions)
+                        }
+                        User::Anyone if perms.is_allowed(Action::Write) != Some(true) => {
+                            issues.push(PolicyIssue::Redundant(*user))
+                        }
+                        User::Key(_) => {
+                            let write = perms.is_allowed(Action::Write);
+                            if write.is_none() || write == Some(anyone_can_write) {
+                                issues.push(PolicyIssue::Redundant(*user))
+                            }
+                        }
+                        User::Anyone => {}
+                    }
+                }
+            }
+        }
+        issues
+    }
+
+    /// Describes who can do what with the Register, for showing to users.
+    pub fn describe(&self) -> String {
+        match self {
+            Policy::Private(policy) => {
+                let describe_perms = |(key, perms): (&PublicKey, &PrivatePermissions)| {
+                    let access = match (
+                        perms.is_allowed(Action::Read),
+                        perms.is_allowed(Action::Write),
+                    ) {
+                        (true, true) => "read and write to it",
+                        (true, false) => "read it",
+                        (false, true) => "write to it",
+                        (false, false) => return None,
+                    };
+                    Some(format!("{} can {}", key, access))
+                };
+                let others = policy
+                    .permissions
+                    .iter()
+                    .filter(|(key, _)| **key != policy.owner)
+                    .filter_map(describe_perms)
+                    .collect_vec();
+
+                if others.is_empty() {
+                    format!(
+                        "Private Register owned by {}. Only its owner can read and write to it.",
+                        policy.owner
+                    )
+                } else {
+                    format!(
+                        "Private Register owned by {}. Besides its owner, {}.",
+                        policy.owner,
+                        others.join(", ")
+                    )
+                }
+            }
+            Policy::Public(policy) => {
+                let (granted, denied) = public_writers(policy);
+                let writers = if anyone_can_write(policy) {
+                    if denied.is_empty() {
+                        "anyone can write to it".to_string()
+                    } else {
+                        format!("anyone but {} can write to it", denied.iter().join(", "))
+                    }
+                } else if granted.is_empty() {
+                    "only its owner can write to it".to_string()
+                } else {
+                    format!(
+                        "only its owner and {} can write to it",
+                        granted.iter().join(", ")
+                    )
+                };
+                format!(
+                    "Public Register owned by {}. Anyone can read it, {}.",
+                    policy.owner, writers
+                )
+            }
+        }
+    }
+}
+
+fn no_private_access() -> PrivatePermissions {
+    PrivatePermissions::new(false, false)
+}
+
+fn anyone_can_write(policy: &PublicPolicy) -> bool {
+    policy.is_action_allowed_by_user(&User::Anyone, Action::Write) == Some(true)
+}
+
+// Keys other than the owner's explicitly allowed to write, and those explicitly denied it.
+fn public_writers(policy: &PublicPolicy) -> (BTreeSet<PublicKey>, BTreeSet<PublicKey>) {
+    let mut granted = BTreeSet::new();
+    let mut denied = BTreeSet::new();
+    for (user, perms) in &policy.permissions {
+        if let User::Key(key) = user {
+            if *key == policy.owner {
+                continue;
+            }
+            match perms.is_allowed(Action::Write) {
+                Some(true) => {
+                    let _ = granted.insert(*key);
+                }
+                Some(false) => {
+                    let _ = denied.insert(*key);
+                }
+                None => {}
+            }
+        }
+    }
+    (granted, denied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{Keypair, PublicKey};
+    use super::super::{
+        Action, Policy, PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, User,
+    };
+    use super::{PolicyIssue, PolicyTemplate};
+    use eyre::Result;
+    use rand::rngs::OsRng;
+
+    fn random_key() -> PublicKey {
+        Keypair::new_ed25519(&mut OsRng).public_key()
+    }
+
+    #[test]
+    fn templates_grant_what_they_describe() -> Result<()> {
+        let owner = random_key();
+        let writer = random_key();
+        let stranger = random_key();
+
+        let owner_only = PolicyTemplate::OwnerOnly.policy(owner);
+        owner_only.is_action_allowed(owner, Action::Write)?;
+        assert!(owner_only
+            .is_action_allowed(stranger, Action::Read)
+            .is_err());
+
+        let read_only = PolicyTemplate::PublicReadOnly.policy(owner);
+        read_only.is_action_allowed(stranger, Action::Read)?;
+        assert!(read_only
+            .is_action_allowed(stranger, Action::Write)
+            .is_err());
+
+        let append = PolicyTemplate::PublicAppend.policy(owner);
+        append.is_action_allowed(stranger, Action::Write)?;
+
+        let allowlist =
+            PolicyTemplate::AllowlistedWriters(vec![writer].into_iter().collect()).policy(owner);
+        allowlist.is_action_allowed(writer, Action::Write)?;
+        allowlist.is_action_allowed(owner, Action::Write)?;
+        assert!(allowlist
+            .is_action_allowed(stranger, Action::Write)
+            .is_err());
+
+        for template in vec![
+            PolicyTemplate::OwnerOnly,
+            PolicyTemplate::PublicReadOnly,
+            PolicyTemplate::PublicAppend,
+            PolicyTemplate::AllowlistedWriters(vec![writer].into_iter().collect()),
+        ] {
+            let policy = template.policy(owner);
+            assert_eq!(PolicyTemplate::of(&policy), Some(template));
+            assert!(policy.validate().is_empty());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn hand_built_policies_are_validated() {
+        let owner = random_key();
+        let reader = random_key();
+        let writer = random_key();
+
+        let policy: Policy = PrivatePolicy {
+            owner,
+            permissions: vec![
+                (owner, PrivatePermissions::new(true, true)),
+                (reader, PrivatePermissions::new(true, false)),
+                (writer, PrivatePermissions::new(false, true)),
+            ]
+            .into_iter()
+            .collect(),
+        }
+        .into();
+        let issues = policy.validate();
+        assert!(issues.contains(&PolicyIssue::OwnerPermissions));
+        assert!(issues.contains(&PolicyIssue::WriteOnly(writer)));
+        assert_eq!(issues.len(), 2);
+        assert_eq!(PolicyTemplate::of(&policy), None);
+
+        // Denying a key writes nobody else can do.
+        let policy: Policy = PublicPolicy {
+            owner,
+            permissions: vec![(User::Key(writer), PublicPermissions::new(false))]
+                .into_iter()
+                .collect(),
+        }
+        .into();
+        assert_eq!(
+            policy.validate(),
+            vec![PolicyIssue::Redundant(User::Key(writer))]
+        );
+        assert_eq!(
+            PolicyTemplate::of(&policy),
+            Some(PolicyTemplate::PublicReadOnly)
+        );
+        assert!(policy.describe().contains("only its owner can write"));
+    }
+}
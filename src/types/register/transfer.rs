@@ -0,0 +1,68 @@
+// Copyright 2021 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use super::super::{utils, PublicKey, Result, Signature, Signer};
+use super::Address;
+use serde::{Deserialize, Serialize};
+
+/// Transfer of the ownership of a Register to a new owner, signed by its current owner.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct OwnershipTransfer {
+    /// Address of the Register.
+    pub address: Address,
+    /// Position of this transfer in the Register's policy history, so it can't be replayed.
+    pub index: u64,
+    /// Owner handing over the Register.
+    pub previous_owner: PublicKey,
+    /// Owner taking over the Register.
+    pub new_owner: PublicKey,
+    /// Previous owner's signature over the fields above.
+    pub signature: Signature,
+}
+
+impl OwnershipTransfer {
+    /// Creates the transfer of the Register at `address` to `new_owner`, signed by its
+    /// current owner's `signer`, as the `index`th change of the Register's policy.
+    pub fn new(
+        address: Address,
+        index: u64,
+        new_owner: PublicKey,
+        signer: &dyn Signer,
+    ) -> Result<Self> {
+        let previous_owner = signer.public_key();
+        let bytes = Self::bytes_to_sign(&address, index, &previous_owner, &new_owner)?;
+        Ok(Self {
+            address,
+            index,
+            previous_owner,
+            new_owner,
+            signature: signer.sign(&bytes)?,
+        })
+    }
+
+    /// Verifies the previous owner's signature.
+    pub fn verify(&self) -> Result<()> {
+        let bytes = Self::bytes_to_sign(
+            &self.address,
+            self.index,
+            &self.previous_owner,
+            &self.new_owner,
+        )?;
+        self.previous_owner.verify(&self.signature, bytes)
+    }
+
+    fn bytes_to_sign(
+        address: &Address,
+        index: u64,
+        previous_owner: &PublicKey,
+        new_owner: &PublicKey,
+    ) -> Result<Vec<u8>> {
+        utils::serialise(&(address, index, previous_owner, new_owner))
+    }
+}
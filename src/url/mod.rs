@@ -245,6 +245,7 @@ pub struct Url {
     query_string: String,                 // query-string, no separator, url-encoded
     fragment: String,                     // fragment, no separator
     content_version: Option<VersionHash>, // convenience for ?v=<version
+    content_version_number: Option<u64>,  // convenience for ?v=<N>
     url_type: UrlType,                    // nrsurl or xorurl
 }
 
@@ -356,7 +357,8 @@ impl Url {
             path: String::default(),         // set below.
             query_string: String::default(), // set below.
             fragment: fragment.unwrap_or("").to_string(),
-            content_version: None, // set below.
+            content_version: None,        // set below.
+            content_version_number: None, // set below.
             url_type,
         };
 
@@ -713,6 +715,32 @@ impl Url {
             });
     }
 
+    /// gets content version number
+    ///
+    /// This is a shortcut method for getting the "?v=" query param
+    /// when it selects the Nth version of the content rather than a VersionHash.
+    pub fn content_version_number(&self) -> Option<u64> {
+        self.content_version_number
+    }
+
+    /// sets content version number
+    ///
+    /// This is a shortcut method for setting the "?v=" query param
+    /// to the Nth version of the content, the first one being version 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - u64 representing value of ?v=<val>
+    pub fn set_content_version_number(&mut self, version: Option<u64>) {
+        let version_string = version.map(|v| v.to_string());
+
+        // note: as with set_content_version(), this should never fail.
+        self.set_query_key(URL_VERSION_QUERY_NAME, version_string.as_deref())
+            .unwrap_or_else(|e| {
+                warn!("{}", e);
+            });
+    }
+
     /// sets or unsets a key/val pair in query string.
     ///
     /// if val is Some, then key=val will be set in query string.
@@ -1109,24 +1137,35 @@ impl Url {
         pairs
     }
 
-    // sets content_version property.
+    // sets content_version and content_version_number properties.
     //
     // This should never be called directly.
-    // Use ::set_content_version() or ::set_query_key() instead.
+    // Use ::set_content_version(), ::set_content_version_number()
+    // or ::set_query_key() instead.
     fn set_content_version_internal(&mut self, version_option: Option<&str>) -> Result<()> {
+        self.content_version = None;
+        self.content_version_number = None;
         if let Some(version_str) = version_option {
-            let version = version_str.parse::<VersionHash>().map_err(|_e| {
-                let msg = format!(
-                    "{} param could not be parsed as VersionHash. invalid: '{}'",
-                    URL_VERSION_QUERY_NAME, version_str
-                );
-                Error::InvalidInput(msg)
-            })?;
-            self.content_version = Some(version);
-        } else {
-            self.content_version = None;
+            // A plain number selects the Nth version, anything else must be a VersionHash.
+            // Base32z encoded VersionHash strings always start with 'h', so they can't clash.
+            if let Ok(number) = version_str.parse::<u64>() {
+                self.content_version_number = Some(number);
+            } else {
+                let version = version_str.parse::<VersionHash>().map_err(|_e| {
+                    let msg = format!(
+                        "{} param could not be parsed as VersionHash or version number. invalid: '{}'",
+                        URL_VERSION_QUERY_NAME, version_str
+                    );
+                    Error::InvalidInput(msg)
+                })?;
+                self.content_version = Some(version);
+            }
         }
-        trace!("Set version: {:#?}", self.content_version);
+        trace!(
+            "Set version: {:#?}, version number: {:?}",
+            self.content_version,
+            self.content_version_number
+        );
         Ok(())
     }
 
@@ -1683,6 +1722,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_url_content_version_number() -> Result<()> {
+        let mut x = Url::from_url("safe://myname?v=3")?;
+        assert_eq!(x.content_version_number(), Some(3));
+        assert_eq!(x.content_version(), None);
+
+        let version_hash = VersionHash::default();
+        x.set_content_version(Some(version_hash));
+        assert_eq!(x.content_version_number(), None);
+        assert_eq!(x.content_version(), Some(version_hash));
+
+        x.set_content_version_number(Some(0));
+        assert_eq!(x.content_version_number(), Some(0));
+        assert_eq!(x.content_version(), None);
+        assert_eq!(x.to_string(), "safe://myname?v=0");
+
+        x.set_content_version_number(None);
+        assert_eq!(x.content_version_number(), None);
+        assert_eq!(x.to_string(), "safe://myname");
+
+        Ok(())
+    }
+
     #[test]
     fn test_url_path() -> Result<()> {
         // Make sure we can read percent-encoded paths, and set them as well.